@@ -1,4 +1,4 @@
-use alloc::boxed::Box;
+use alloc::{boxed::Box, string::String};
 
 use wie_backend::{AudioSink, Platform};
 
@@ -10,16 +10,24 @@ impl Platform for TestPlatform {
     }
 
     fn now(&self) -> wie_backend::Instant {
-        todo!()
+        wie_backend::Instant::from_epoch_millis(0)
     }
 
     fn database_repository(&self) -> &dyn wie_backend::DatabaseRepository {
         todo!()
     }
 
+    fn file_storage(&self) -> &dyn wie_backend::FileStorage {
+        todo!()
+    }
+
     fn audio_sink(&self) -> Box<dyn AudioSink> {
         Box::new(TestAudioSink)
     }
+
+    fn system_property(&self, _name: &str) -> Option<String> {
+        None
+    }
 }
 
 struct TestAudioSink;
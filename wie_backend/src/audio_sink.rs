@@ -1,3 +1,11 @@
 pub trait AudioSink {
     fn play_wave(&self, channel: u8, sampling_rate: u32, wave_data: &[i16]);
+
+    /// Plays a clip that can be stopped by its id. A clip already playing with the same id is replaced.
+    fn play_clip(&self, _id: u32, channel: u8, sampling_rate: u32, wave_data: &[i16], _repeat: bool) {
+        // sinks which can't stop what they play still get it once
+        self.play_wave(channel, sampling_rate, wave_data);
+    }
+
+    fn stop_clip(&self, _id: u32) {}
 }
@@ -1,13 +1,16 @@
-use core::mem::size_of;
+mod font;
 
-use ab_glyph::{Font, FontRef, ScaleFont};
-use bytemuck::{cast_slice, pod_collect_to_vec, Pod};
+use core::{
+    marker::PhantomData,
+    mem::size_of,
+    ops::{Deref, DerefMut, Range},
+};
+
+use bytemuck::{bytes_of, cast_slice, pod_collect_to_vec, pod_read_unaligned, Pod};
 use image::io::Reader as ImageReader;
 use num_traits::{Num, Zero};
 
-lazy_static::lazy_static! {
-    static ref FONT: FontRef<'static> = FontRef::try_from_slice(include_bytes!("../../fonts/neodgm.ttf")).unwrap();
-}
+pub use self::font::{Font, FontFace, FontSize, FontStyle};
 
 pub enum TextAlignment {
     Left,
@@ -15,6 +18,156 @@ pub enum TextAlignment {
     Right,
 }
 
+/// Applied to the source when drawing, rotations are clockwise and come after mirroring
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Transform {
+    #[default]
+    None,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Flipped horizontally
+    Mirror,
+    MirrorRotate90,
+    /// Same as flipping vertically
+    MirrorRotate180,
+    MirrorRotate270,
+}
+
+impl Transform {
+    /// From the numbering of midp `Sprite.TRANS_*`, which the other apis follow too
+    pub fn from_sprite_transform(value: i32) -> Option<Self> {
+        Some(match value {
+            0 => Self::None,
+            1 => Self::MirrorRotate180,
+            2 => Self::Mirror,
+            3 => Self::Rotate180,
+            4 => Self::MirrorRotate270,
+            5 => Self::Rotate90,
+            6 => Self::Rotate270,
+            7 => Self::MirrorRotate90,
+            _ => return None,
+        })
+    }
+
+    /// Size of a `width` by `height` region once transformed
+    pub fn transformed_size(&self, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            Self::Rotate90 | Self::Rotate270 | Self::MirrorRotate90 | Self::MirrorRotate270 => (height, width),
+            _ => (width, height),
+        }
+    }
+
+    // position in the source region of what ends up at (x, y) of the transformed one
+    fn source_position(&self, x: u32, y: u32, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            Self::None => (x, y),
+            Self::Rotate90 => (y, height - 1 - x),
+            Self::Rotate180 => (width - 1 - x, height - 1 - y),
+            Self::Rotate270 => (width - 1 - y, x),
+            Self::Mirror => (width - 1 - x, y),
+            Self::MirrorRotate90 => (width - 1 - y, height - 1 - x),
+            Self::MirrorRotate180 => (x, height - 1 - y),
+            Self::MirrorRotate270 => (y, x),
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// Which point of the text or image the given position is, with the values of lcdui `Graphics` in both midp and wipi
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct Anchor: i32 {
+        const HCENTER = 1;
+        const VCENTER = 2;
+        const LEFT = 4;
+        const RIGHT = 8;
+        const TOP = 16;
+        const BOTTOM = 32;
+        const BASELINE = 64;
+    }
+}
+
+impl Anchor {
+    /// From the anchor point to the top left of something this big
+    pub fn offset(&self, width: u32, height: u32) -> (i32, i32) {
+        let x = if self.contains(Self::HCENTER) {
+            -((width / 2) as i32)
+        } else if self.contains(Self::RIGHT) {
+            -(width as i32)
+        } else {
+            0
+        };
+
+        let y = if self.contains(Self::VCENTER) {
+            -((height / 2) as i32)
+        } else if self.contains(Self::BOTTOM) {
+            -(height as i32)
+        } else {
+            0
+        };
+
+        (x, y)
+    }
+
+    /// Alignment and top of text drawn at `y` in `font`, as `Canvas::draw_text` takes it
+    pub fn text_position(&self, y: i32, font: &Font) -> (TextAlignment, i32) {
+        let alignment = if self.contains(Self::HCENTER) {
+            TextAlignment::Center
+        } else if self.contains(Self::RIGHT) {
+            TextAlignment::Right
+        } else {
+            TextAlignment::Left
+        };
+
+        let y = if self.contains(Self::BOTTOM) {
+            y - font.height() as i32
+        } else if self.contains(Self::BASELINE) {
+            y - font.ascent() as i32
+        } else if self.contains(Self::VCENTER) {
+            y - font.height() as i32 / 2
+        } else {
+            y
+        };
+
+        (alignment, y)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        let (dx, dy) = (x as i64 - self.x as i64, y as i64 - self.y as i64);
+
+        dx >= 0 && dy >= 0 && dx < self.width as i64 && dy < self.height as i64
+    }
+
+    // empty if they don't overlap
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x as i64 + self.width as i64).min(other.x as i64 + other.width as i64);
+        let bottom = (self.y as i64 + self.height as i64).min(other.y as i64 + other.height as i64);
+
+        Rect {
+            x: left,
+            y: top,
+            width: (right - left as i64).max(0) as _,
+            height: (bottom - top as i64).max(0) as _,
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct Color {
     pub a: u8,
@@ -41,11 +194,33 @@ pub trait Canvas {
     fn image(&self) -> &dyn Image;
     #[allow(clippy::too_many_arguments)]
     fn draw(&mut self, dx: u32, dy: u32, w: u32, h: u32, src: &dyn Image, sx: u32, sy: u32);
+    /// Draws the `w` by `h` region of `src` at (`sx`, `sy`) transformed, with its top left at (`dx`, `dy`)
+    #[allow(clippy::too_many_arguments)]
+    fn draw_region(&mut self, dx: i32, dy: i32, src: &dyn Image, sx: u32, sy: u32, w: u32, h: u32, transform: Transform);
     fn draw_line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32, color: Color);
-    fn draw_text(&mut self, string: &str, x: u32, y: u32, text_alignment: TextAlignment);
+    /// `y` is the top of the text
+    fn draw_text(&mut self, string: &str, x: u32, y: u32, font: &Font, color: Color, text_alignment: TextAlignment);
+    /// Width and height of the text as drawn
+    fn measure_text(&self, string: &str, font: &Font) -> (u32, u32);
     fn draw_rect(&mut self, x: u32, y: u32, w: u32, h: u32, color: Color);
     fn fill_rect(&mut self, x: u32, y: u32, w: u32, h: u32, color: Color);
+    #[allow(clippy::too_many_arguments)]
+    fn draw_round_rect(&mut self, x: i32, y: i32, w: u32, h: u32, arc_width: u32, arc_height: u32, color: Color);
+    #[allow(clippy::too_many_arguments)]
+    fn fill_round_rect(&mut self, x: i32, y: i32, w: u32, h: u32, arc_width: u32, arc_height: u32, color: Color);
+    /// Angles are in degrees, counterclockwise from 3 o'clock, and stretched along with the ellipse
+    #[allow(clippy::too_many_arguments)]
+    fn draw_arc(&mut self, x: i32, y: i32, w: u32, h: u32, start_angle: i32, arc_angle: i32, color: Color);
+    #[allow(clippy::too_many_arguments)]
+    fn fill_arc(&mut self, x: i32, y: i32, w: u32, h: u32, start_angle: i32, arc_angle: i32, color: Color);
+    /// The last point is connected back to the first
+    fn draw_polygon(&mut self, points: &[(i32, i32)], color: Color);
+    fn fill_polygon(&mut self, points: &[(i32, i32)], color: Color);
     fn put_pixel(&mut self, x: u32, y: u32, color: Color);
+    /// Narrows drawing to the intersection of `rect` and the current clip, until popped
+    fn push_clip(&mut self, rect: Rect);
+    fn pop_clip(&mut self);
+    fn clip(&self) -> Rect;
 }
 
 pub trait PixelType {
@@ -223,11 +398,110 @@ where
     }
 }
 
+/// Pixels kept in the byte layout of the java or guest side buffer they came from, so they can be drawn on
+/// and handed back without converting to a typed vec and back
+pub struct ByteImageBuffer<T>
+where
+    T: PixelType,
+{
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+    _pixel: PhantomData<T>,
+}
+
+impl<T> ByteImageBuffer<T>
+where
+    T: PixelType,
+{
+    pub fn from_raw(width: u32, height: u32, raw: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            data: raw,
+            _pixel: PhantomData,
+        }
+    }
+
+    pub fn into_raw(self) -> Vec<u8> {
+        self.data
+    }
+
+    fn pixel_range(&self, x: u32, y: u32) -> Range<usize> {
+        let start = (y * self.width + x) as usize * size_of::<T::DataType>();
+
+        start..start + size_of::<T::DataType>()
+    }
+}
+
+impl<T> Image for ByteImageBuffer<T>
+where
+    T: PixelType + 'static,
+{
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn bytes_per_pixel(&self) -> u32 {
+        size_of::<T::DataType>() as u32
+    }
+
+    fn get_pixel(&self, x: u32, y: u32) -> Color {
+        let raw = pod_read_unaligned(&self.data[self.pixel_range(x, y)]);
+
+        T::to_color(raw)
+    }
+
+    fn raw(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn colors(&self) -> Vec<Color> {
+        self.data
+            .chunks_exact(size_of::<T::DataType>())
+            .map(|x| T::to_color(pod_read_unaligned(x)))
+            .collect()
+    }
+}
+
+impl<T> ImageBuffer for ByteImageBuffer<T>
+where
+    T: PixelType + 'static,
+{
+    fn put_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let range = self.pixel_range(x, y);
+        self.data[range].copy_from_slice(bytes_of(&T::from_color(color)));
+    }
+
+    fn put_pixels(&mut self, x: u32, y: u32, width: u32, colors: &[Color]) {
+        for (i, color) in colors.iter().enumerate() {
+            let x = x + (i as u32 % width);
+            let y = y + (i as u32 / width);
+
+            if x >= self.width || y >= self.height {
+                continue;
+            }
+
+            let range = self.pixel_range(x, y);
+            self.data[range].copy_from_slice(bytes_of(&T::from_color(*color)));
+        }
+    }
+}
+
 pub struct ImageBufferCanvas<T>
 where
     T: ImageBuffer + Image,
 {
     image_buffer: T,
+    clips: Vec<Rect>,
 }
 
 impl<T> ImageBufferCanvas<T>
@@ -235,7 +509,10 @@ where
     T: ImageBuffer + Image,
 {
     pub fn new(image_buffer: T) -> Self {
-        Self { image_buffer }
+        Self {
+            image_buffer,
+            clips: Vec::new(),
+        }
     }
 
     pub fn into_inner(self) -> T {
@@ -243,6 +520,10 @@ where
     }
 
     fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if !self.clip().contains(x as _, y as _) {
+            return;
+        }
+
         let bg = self.image_buffer.get_pixel(x, y);
         let factor = color.a as f32 / 255.0;
 
@@ -255,6 +536,110 @@ where
 
         self.put_pixel(x, y, computed_color);
     }
+
+    // bresenham's line drawing, without the end point
+    fn line(&mut self, x1: i32, y1: i32, x2: i32, y2: i32, color: Color) {
+        let dx = (x2 - x1).abs();
+        let dy = (y2 - y1).abs();
+        let sx = if x1 < x2 { 1i32 } else { -1 };
+        let sy = if y1 < y2 { 1i32 } else { -1 };
+        let mut err = dx - dy;
+
+        let mut x = x1;
+        let mut y = y1;
+
+        while x != x2 || y != y2 {
+            if x >= 0 && y >= 0 {
+                self.blend_pixel(x as _, y as _, color);
+            }
+
+            let e2 = 2 * err;
+            if e2 > -dy {
+                err -= dy;
+                x += sx;
+            }
+            if e2 < dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    // fills pixels of `bounds` whose centers are inside the shape
+    fn fill_shape<F>(&mut self, bounds: Rect, color: Color, inside: F)
+    where
+        F: Fn(f32, f32) -> bool,
+    {
+        let area = bounds.intersection(&self.clip());
+
+        for y in area.y..area.y + area.height as i32 {
+            for x in area.x..area.x + area.width as i32 {
+                if inside(x as f32 + 0.5, y as f32 + 0.5) {
+                    self.blend_pixel(x as _, y as _, color);
+                }
+            }
+        }
+    }
+
+    fn is_in_round_rect(px: f32, py: f32, bounds: Rect, arc_width: u32, arc_height: u32) -> bool {
+        let (x, y, w, h) = (bounds.x as f32, bounds.y as f32, bounds.width as f32, bounds.height as f32);
+        if px < x || py < y || px >= x + w || py >= y + h {
+            return false;
+        }
+
+        // corners are quarters of an ellipse of the arc size
+        let rx = (arc_width as f32 / 2.0).min(w / 2.0);
+        let ry = (arc_height as f32 / 2.0).min(h / 2.0);
+        if rx <= 0.0 || ry <= 0.0 {
+            return true;
+        }
+
+        let cx = px.clamp(x + rx, x + w - rx);
+        let cy = py.clamp(y + ry, y + h - ry);
+        let (dx, dy) = ((px - cx) / rx, (py - cy) / ry);
+
+        dx * dx + dy * dy <= 1.0
+    }
+
+    fn is_in_ellipse(px: f32, py: f32, bounds: Rect) -> bool {
+        let (rx, ry) = (bounds.width as f32 / 2.0, bounds.height as f32 / 2.0);
+        if rx <= 0.0 || ry <= 0.0 {
+            return false;
+        }
+
+        let dx = (px - bounds.x as f32 - rx) / rx;
+        let dy = (py - bounds.y as f32 - ry) / ry;
+
+        dx * dx + dy * dy <= 1.0
+    }
+
+    fn is_in_angle(px: f32, py: f32, bounds: Rect, start_angle: i32, arc_angle: i32) -> bool {
+        if arc_angle.abs() >= 360 {
+            return true;
+        }
+
+        let (start, arc) = if arc_angle < 0 {
+            (start_angle + arc_angle, -arc_angle)
+        } else {
+            (start_angle, arc_angle)
+        };
+
+        // measured on the ellipse squashed into a circle, so 45 degrees points to the corner of the bounds
+        let dx = (px - bounds.x as f32 - bounds.width as f32 / 2.0) / bounds.width.max(1) as f32;
+        let dy = (bounds.y as f32 + bounds.height as f32 / 2.0 - py) / bounds.height.max(1) as f32;
+        let angle = dy.atan2(dx).to_degrees();
+
+        (angle - start as f32).rem_euclid(360.0) <= arc as f32
+    }
+
+    fn shrink(bounds: Rect) -> Rect {
+        Rect::new(
+            bounds.x + 1,
+            bounds.y + 1,
+            bounds.width.saturating_sub(2),
+            bounds.height.saturating_sub(2),
+        )
+    }
 }
 
 impl<T> Canvas for ImageBufferCanvas<T>
@@ -281,68 +666,55 @@ where
         }
     }
 
-    fn draw_line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32, color: Color) {
-        // bresenham's line drawing
-        let dx = (x2 as i32 - x1 as i32).abs();
-        let dy = (y2 as i32 - y1 as i32).abs();
-        let sx = if x1 < x2 { 1i32 } else { -1 };
-        let sy = if y1 < y2 { 1i32 } else { -1 };
-        let mut err = dx - dy;
+    #[allow(clippy::too_many_arguments)]
+    fn draw_region(&mut self, dx: i32, dy: i32, src: &dyn Image, sx: u32, sy: u32, w: u32, h: u32, transform: Transform) {
+        // parts outside of the source are left out
+        let w = w.min(src.width().saturating_sub(sx));
+        let h = h.min(src.height().saturating_sub(sy));
 
-        let mut x = x1 as i32;
-        let mut y = y1 as i32;
+        let (width, height) = transform.transformed_size(w, h);
+        let area = Rect::new(dx, dy, width, height).intersection(&self.clip());
 
-        while x != x2 as i32 || y != y2 as i32 {
-            self.blend_pixel(x as _, y as _, color);
+        for y in area.y..area.y + area.height as i32 {
+            for x in area.x..area.x + area.width as i32 {
+                let (u, v) = transform.source_position((x - dx) as _, (y - dy) as _, w, h);
 
-            let e2 = 2 * err;
-            if e2 > -dy {
-                err -= dy;
-                x += sx;
-            }
-            if e2 < dx {
-                err += dx;
-                y += sy;
+                self.blend_pixel(x as _, y as _, src.get_pixel(sx + u, sy + v));
             }
         }
     }
 
-    fn draw_text(&mut self, string: &str, x: u32, y: u32, text_alignment: TextAlignment) {
-        let font = FONT.as_scaled(FONT.pt_to_px_scale(10.0).unwrap());
+    fn draw_line(&mut self, x1: u32, y1: u32, x2: u32, y2: u32, color: Color) {
+        self.line(x1 as _, y1 as _, x2 as _, y2 as _, color);
+    }
 
-        let total_width = string.chars().map(|c| font.h_advance(font.scaled_glyph(c).id)).sum::<f32>();
+    fn draw_text(&mut self, string: &str, x: u32, y: u32, font: &Font, color: Color, text_alignment: TextAlignment) {
+        let width = font.string_width(string);
         let x = match text_alignment {
-            TextAlignment::Left => x,
-            TextAlignment::Center => x - (total_width / 2.0) as u32,
-            TextAlignment::Right => x - total_width as u32,
+            TextAlignment::Left => x as i32,
+            TextAlignment::Center => x as i32 - (width / 2) as i32,
+            TextAlignment::Right => x as i32 - width as i32,
         };
 
-        let mut position = 0.0;
-        for c in string.chars() {
-            if c.is_control() {
-                continue;
+        font.rasterize(string, |glyph_x, glyph_y, coverage| {
+            let (x, y) = (x + glyph_x, y as i32 + glyph_y);
+            if x < 0 || y < 0 || x as u32 >= self.image_buffer.width() || y as u32 >= self.image_buffer.height() {
+                return;
             }
 
-            let glyph = font.scaled_glyph(c);
-            let h_advance = font.h_advance(glyph.id);
-
-            if let Some(outlined_glyph) = font.outline_glyph(glyph) {
-                outlined_glyph.draw(|glyph_x: u32, glyph_y, c| {
-                    self.blend_pixel(
-                        x + (glyph_x as f32 + position) as u32,
-                        y + glyph_y,
-                        Color {
-                            a: (c * 255.0) as u8,
-                            r: 0,
-                            g: 0,
-                            b: 0,
-                        },
-                    )
-                });
-            }
+            self.blend_pixel(
+                x as _,
+                y as _,
+                Color {
+                    a: (coverage.min(1.0) * color.a as f32) as u8,
+                    ..color
+                },
+            );
+        });
+    }
 
-            position += h_advance;
-        }
+    fn measure_text(&self, string: &str, font: &Font) -> (u32, u32) {
+        (font.string_width(string), font.height())
     }
 
     fn draw_rect(&mut self, x: u32, y: u32, w: u32, h: u32, color: Color) {
@@ -373,31 +745,327 @@ where
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn draw_round_rect(&mut self, x: i32, y: i32, w: u32, h: u32, arc_width: u32, arc_height: u32, color: Color) {
+        let bounds = Rect::new(x, y, w, h);
+        let inner = Self::shrink(bounds);
+
+        self.fill_shape(bounds, color, |px, py| {
+            Self::is_in_round_rect(px, py, bounds, arc_width, arc_height)
+                && !Self::is_in_round_rect(px, py, inner, arc_width.saturating_sub(2), arc_height.saturating_sub(2))
+        });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn fill_round_rect(&mut self, x: i32, y: i32, w: u32, h: u32, arc_width: u32, arc_height: u32, color: Color) {
+        let bounds = Rect::new(x, y, w, h);
+
+        self.fill_shape(bounds, color, |px, py| Self::is_in_round_rect(px, py, bounds, arc_width, arc_height));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_arc(&mut self, x: i32, y: i32, w: u32, h: u32, start_angle: i32, arc_angle: i32, color: Color) {
+        let bounds = Rect::new(x, y, w, h);
+        let inner = Self::shrink(bounds);
+
+        self.fill_shape(bounds, color, |px, py| {
+            Self::is_in_ellipse(px, py, bounds) && !Self::is_in_ellipse(px, py, inner) && Self::is_in_angle(px, py, bounds, start_angle, arc_angle)
+        });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn fill_arc(&mut self, x: i32, y: i32, w: u32, h: u32, start_angle: i32, arc_angle: i32, color: Color) {
+        let bounds = Rect::new(x, y, w, h);
+
+        self.fill_shape(bounds, color, |px, py| {
+            Self::is_in_ellipse(px, py, bounds) && Self::is_in_angle(px, py, bounds, start_angle, arc_angle)
+        });
+    }
+
+    fn draw_polygon(&mut self, points: &[(i32, i32)], color: Color) {
+        for (i, &(x1, y1)) in points.iter().enumerate() {
+            let (x2, y2) = points[(i + 1) % points.len()];
+
+            self.line(x1, y1, x2, y2, color);
+        }
+    }
+
+    // even-odd rule, as scanlines through pixel centers
+    fn fill_polygon(&mut self, points: &[(i32, i32)], color: Color) {
+        if points.len() < 3 {
+            return;
+        }
+
+        let top = points.iter().map(|x| x.1).min().unwrap();
+        let bottom = points.iter().map(|x| x.1).max().unwrap();
+        let clip = self.clip();
+
+        let mut crossings = Vec::new();
+        for y in top.max(clip.y)..bottom.min(clip.y + clip.height as i32) {
+            let center = y as f32 + 0.5;
+
+            crossings.clear();
+            for (i, &(x1, y1)) in points.iter().enumerate() {
+                let (x2, y2) = points[(i + 1) % points.len()];
+                let (y1, y2) = (y1 as f32, y2 as f32);
+
+                if (y1 <= center) != (y2 <= center) {
+                    crossings.push(x1 as f32 + (center - y1) / (y2 - y1) * (x2 - x1) as f32);
+                }
+            }
+            crossings.sort_by(|a, b| a.total_cmp(b));
+
+            for span in crossings.chunks_exact(2) {
+                let start = (span[0] - 0.5).ceil() as i32;
+                let end = (span[1] - 0.5).ceil() as i32;
+
+                for x in start.max(clip.x)..end.min(clip.x + clip.width as i32) {
+                    self.blend_pixel(x as _, y as _, color);
+                }
+            }
+        }
+    }
+
     fn put_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if !self.clip().contains(x as _, y as _) {
+            return;
+        }
+
         self.image_buffer.put_pixel(x, y, color)
     }
+
+    fn push_clip(&mut self, rect: Rect) {
+        let clip = self.clip().intersection(&rect);
+
+        self.clips.push(clip);
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn clip(&self) -> Rect {
+        match self.clips.last() {
+            Some(x) => *x,
+            None => Rect::new(0, 0, self.image_buffer.width(), self.image_buffer.height()),
+        }
+    }
 }
 
+/// Canvas on pixels in the byte layout java images keep them in, rgb565 with 2 bytes per pixel or argb with 4.
+/// Drawing keeps the layout, so the data can be stored back as it is
+pub enum ByteImageCanvas {
+    Rgb565(ImageBufferCanvas<ByteImageBuffer<Rgb565Pixel>>),
+    Argb(ImageBufferCanvas<ByteImageBuffer<ArgbPixel>>),
+}
+
+impl ByteImageCanvas {
+    /// None if the layout is not one of them or `raw` is too short for the size
+    pub fn new(width: u32, height: u32, bytes_per_pixel: u32, raw: Vec<u8>) -> Option<Self> {
+        if !byte_image_fits(width, height, bytes_per_pixel, &raw) {
+            return None;
+        }
+
+        Some(match bytes_per_pixel {
+            2 => Self::Rgb565(ImageBufferCanvas::new(ByteImageBuffer::from_raw(width, height, raw))),
+            _ => Self::Argb(ImageBufferCanvas::new(ByteImageBuffer::from_raw(width, height, raw))),
+        })
+    }
+
+    pub fn into_raw(self) -> Vec<u8> {
+        match self {
+            Self::Rgb565(x) => x.into_inner().into_raw(),
+            Self::Argb(x) => x.into_inner().into_raw(),
+        }
+    }
+}
+
+impl Deref for ByteImageCanvas {
+    type Target = dyn Canvas;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Rgb565(x) => x,
+            Self::Argb(x) => x,
+        }
+    }
+}
+
+impl DerefMut for ByteImageCanvas {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Rgb565(x) => x,
+            Self::Argb(x) => x,
+        }
+    }
+}
+
+/// Image on pixels in the layouts `ByteImageCanvas` takes, None on the same conditions
+pub fn byte_image(width: u32, height: u32, bytes_per_pixel: u32, raw: Vec<u8>) -> Option<Box<dyn Image>> {
+    if !byte_image_fits(width, height, bytes_per_pixel, &raw) {
+        return None;
+    }
+
+    Some(match bytes_per_pixel {
+        2 => Box::new(ByteImageBuffer::<Rgb565Pixel>::from_raw(width, height, raw)),
+        _ => Box::new(ByteImageBuffer::<ArgbPixel>::from_raw(width, height, raw)),
+    })
+}
+
+// size and layout come from fields the app can change, so they are checked before any pixel is indexed
+fn byte_image_fits(width: u32, height: u32, bytes_per_pixel: u32, raw: &[u8]) -> bool {
+    matches!(bytes_per_pixel, 2 | 4) && raw.len() as u64 >= width as u64 * height as u64 * bytes_per_pixel as u64
+}
+
+/// Decodes png, gif (first frame only), bmp or wbmp into argb
 pub fn decode_image(data: &[u8]) -> anyhow::Result<Box<dyn Image>> {
     use std::io::Cursor;
 
-    let image = ImageReader::new(Cursor::new(&data)).with_guessed_format()?.decode()?;
-    let rgba = image.into_rgba8();
+    let reader = ImageReader::new(Cursor::new(&data)).with_guessed_format()?;
+    if reader.format().is_none() {
+        return decode_wbmp(data);
+    }
+
+    // palette and transparency chunks of png are expanded here as well
+    let image = reader.decode()?;
 
+    Ok(from_rgba(image.into_rgba8()))
+}
+
+fn from_rgba(rgba: image::RgbaImage) -> Box<dyn Image> {
     let data = rgba.pixels().flat_map(|x| [x.0[2], x.0[1], x.0[0], x.0[3]]).collect::<Vec<_>>();
 
-    Ok(Box::new(VecImageBuffer::<ArgbPixel>::from_raw(
+    Box::new(VecImageBuffer::<ArgbPixel>::from_raw(
         rgba.width(),
         rgba.height(),
         pod_collect_to_vec(&data),
-    )) as Box<_>)
+    )) as Box<_>
+}
+
+/// Frames of an animation along with how long each is shown in milliseconds. It loops forever, like gifs do on handsets
+pub struct AnimatedImage {
+    frames: Vec<(Box<dyn Image>, u32)>,
+}
+
+impl AnimatedImage {
+    pub fn new(frames: Vec<(Box<dyn Image>, u32)>) -> Self {
+        Self { frames }
+    }
+
+    /// Frames of a gif, already composed to the full size. Other formats come as a single frame
+    pub fn decode(data: &[u8]) -> anyhow::Result<Self> {
+        use image::{codecs::gif::GifDecoder, AnimationDecoder, ImageFormat};
+        use std::io::Cursor;
+
+        // delays shorter than this are shown as 100ms by most viewers, as encoders wrote 0 for "as fast as possible"
+        const MIN_DELAY: u32 = 20;
+
+        let reader = ImageReader::new(Cursor::new(data)).with_guessed_format()?;
+        if reader.format() != Some(ImageFormat::Gif) {
+            return Ok(Self::new(vec![(decode_image(data)?, 0)]));
+        }
+
+        let frames = GifDecoder::new(Cursor::new(data))?.into_frames().collect_frames()?;
+        anyhow::ensure!(!frames.is_empty(), "Gif has no frames");
+
+        let frames = frames
+            .into_iter()
+            .map(|frame| {
+                let (numerator, denominator) = frame.delay().numer_denom_ms();
+                let delay = numerator / denominator.max(1);
+
+                (from_rgba(frame.into_buffer()), if delay < MIN_DELAY { 100 } else { delay })
+            })
+            .collect();
+
+        Ok(Self::new(frames))
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn frame(&self, index: usize) -> &dyn Image {
+        &*self.frames[index].0
+    }
+
+    pub fn delays(&self) -> Vec<u32> {
+        self.frames.iter().map(|(_, x)| *x).collect()
+    }
+
+    /// Index of the frame shown `elapsed` milliseconds after the first one, with each frame shown for its delay
+    pub fn frame_at(delays: &[u32], elapsed: u64) -> usize {
+        let total = delays.iter().map(|&x| x as u64).sum::<u64>();
+        if total == 0 {
+            return 0;
+        }
+
+        let mut remaining = elapsed % total;
+        for (index, &delay) in delays.iter().enumerate() {
+            if remaining < delay as u64 {
+                return index;
+            }
+            remaining -= delay as u64;
+        }
+
+        delays.len() - 1
+    }
+}
+
+// wbmp has no signature, so it's tried only on data no other format claims. type 0 is the only one in use
+fn decode_wbmp(data: &[u8]) -> anyhow::Result<Box<dyn Image>> {
+    fn read_multibyte(data: &[u8], cursor: &mut usize) -> anyhow::Result<u32> {
+        let mut value = 0u32;
+        loop {
+            let byte = *data.get(*cursor).ok_or_else(|| anyhow::anyhow!("Truncated wbmp header"))?;
+            *cursor += 1;
+
+            anyhow::ensure!(value >> 25 == 0, "Invalid wbmp header");
+            value = (value << 7) | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    let mut cursor = 0;
+    let r#type = read_multibyte(data, &mut cursor)?;
+    let fix_header = *data.get(cursor).ok_or_else(|| anyhow::anyhow!("Truncated wbmp header"))?;
+    cursor += 1;
+    anyhow::ensure!(r#type == 0 && fix_header == 0, "Unsupported image format");
+
+    let width = read_multibyte(data, &mut cursor)?;
+    let height = read_multibyte(data, &mut cursor)?;
+    let stride = (width as usize).div_ceil(8);
+    let size = stride.checked_mul(height as usize).ok_or_else(|| anyhow::anyhow!("Invalid wbmp size"))?;
+    anyhow::ensure!(width > 0 && height > 0 && data.len() - cursor >= size, "Invalid wbmp size");
+
+    // rows are packed from the most significant bit, set bits are white
+    let rows = &data[cursor..cursor + size];
+    let pixels = (0..height as usize)
+        .flat_map(|y| (0..width as usize).map(move |x| (y, x)))
+        .map(|(y, x)| {
+            let white = rows[y * stride + x / 8] & (0x80 >> (x % 8)) != 0;
+
+            if white {
+                0xffffffff
+            } else {
+                0xff000000
+            }
+        })
+        .collect::<Vec<u32>>();
+
+    Ok(Box::new(VecImageBuffer::<ArgbPixel>::from_raw(width, height, pixels)) as Box<_>)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::canvas::{Image, ImageBufferCanvas};
 
-    use super::{ArgbPixel, Canvas, Color, VecImageBuffer};
+    use super::{
+        byte_image, Anchor, ArgbPixel, ByteImageBuffer, ByteImageCanvas, Canvas, Color, Font, FontSize, FontStyle, ImageBuffer, Rect, Rgb565Pixel,
+        TextAlignment, Transform, VecImageBuffer,
+    };
 
     #[test]
     fn test_canvas() -> anyhow::Result<()> {
@@ -419,4 +1087,209 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_byte_image_buffer() {
+        // odd width, so rows of 16 bit pixels don't stay aligned
+        let mut canvas = ImageBufferCanvas::new(ByteImageBuffer::<Rgb565Pixel>::from_raw(3, 2, vec![0; 3 * 2 * 2]));
+        let white = Color {
+            r: 255,
+            g: 255,
+            b: 255,
+            a: 255,
+        };
+
+        canvas.fill_rect(1, 1, 2, 1, white);
+        assert_eq!(canvas.image().get_pixel(2, 1).g, 255);
+        assert_eq!(canvas.image().get_pixel(0, 1).g, 0);
+
+        let raw = canvas.into_inner().into_raw();
+        assert_eq!(raw, [0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn test_byte_image_canvas() {
+        let mut canvas = ByteImageCanvas::new(2, 2, 4, vec![0; 2 * 2 * 4]).unwrap();
+        canvas.fill_rect(1, 0, 1, 1, Color { r: 0, g: 0, b: 0, a: 255 });
+        assert_eq!(canvas.into_raw()[4..8], [0, 0, 0, 255]);
+
+        // layouts and sizes which don't match the data
+        assert!(ByteImageCanvas::new(2, 2, 3, vec![0; 2 * 2 * 3]).is_none());
+        assert!(ByteImageCanvas::new(2, 2, 2, vec![0; 7]).is_none());
+        assert!(byte_image(u32::MAX, u32::MAX, 4, vec![0; 16]).is_none());
+        assert_eq!(byte_image(2, 2, 2, vec![0; 8]).unwrap().width(), 2);
+    }
+
+    #[test]
+    fn test_anchor() {
+        assert_eq!(Anchor::empty().offset(10, 6), (0, 0));
+        assert_eq!((Anchor::HCENTER | Anchor::VCENTER).offset(10, 6), (-5, -3));
+        assert_eq!((Anchor::RIGHT | Anchor::BOTTOM).offset(10, 6), (-10, -6));
+
+        let font = Font::default();
+        let (alignment, y) = (Anchor::HCENTER | Anchor::BOTTOM).text_position(20, &font);
+        assert!(matches!(alignment, TextAlignment::Center));
+        assert_eq!(y, 20 - font.height() as i32);
+    }
+
+    #[test]
+    fn test_clip_and_shapes() -> anyhow::Result<()> {
+        let image_buffer = VecImageBuffer::<ArgbPixel>::new(20, 20);
+        let mut canvas = ImageBufferCanvas::new(image_buffer);
+        let red = Color { r: 255, g: 0, b: 0, a: 255 };
+        let black = Color { r: 0, g: 0, b: 0, a: 255 };
+        let is_red = |canvas: &ImageBufferCanvas<VecImageBuffer<ArgbPixel>>, x, y| canvas.image().get_pixel(x, y).r == 255;
+
+        canvas.push_clip(Rect::new(5, 5, 10, 10));
+        canvas.push_clip(Rect::new(0, 0, 12, 12));
+        assert_eq!(canvas.clip(), Rect::new(5, 5, 7, 7));
+        canvas.pop_clip();
+
+        canvas.fill_rect(0, 0, 20, 20, red);
+        assert!(!is_red(&canvas, 4, 4));
+        assert!(is_red(&canvas, 5, 5));
+        assert!(is_red(&canvas, 14, 14));
+        assert!(!is_red(&canvas, 15, 15));
+
+        canvas.pop_clip();
+        assert_eq!(canvas.clip(), Rect::new(0, 0, 20, 20));
+        canvas.fill_rect(0, 0, 20, 20, black);
+
+        // upper right quarter only
+        canvas.fill_arc(0, 0, 10, 10, 0, 90, red);
+        assert!(is_red(&canvas, 7, 2));
+        assert!(!is_red(&canvas, 2, 7));
+        assert!(!is_red(&canvas, 2, 2));
+
+        canvas.fill_rect(0, 0, 20, 20, black);
+        canvas.fill_polygon(&[(0, 0), (10, 0), (0, 10)], red);
+        assert!(is_red(&canvas, 1, 1));
+        assert!(!is_red(&canvas, 9, 9));
+
+        canvas.fill_rect(0, 0, 20, 20, black);
+        canvas.draw_round_rect(0, 0, 10, 10, 4, 4, red);
+        assert!(!is_red(&canvas, 0, 0));
+        assert!(is_red(&canvas, 5, 0));
+        assert!(is_red(&canvas, 0, 5));
+        assert!(!is_red(&canvas, 5, 5));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_draw_region() -> anyhow::Result<()> {
+        // 3x2 source with distinct red values, 0 1 2 on the first row and 3 4 5 on the second
+        let mut src = VecImageBuffer::<ArgbPixel>::new(3, 2);
+        for i in 0..6 {
+            src.put_pixel(
+                i % 3,
+                i / 3,
+                Color {
+                    r: i as u8,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                },
+            );
+        }
+
+        let cases = [
+            (Transform::None, [[0, 1, 2], [3, 4, 5]].concat()),
+            (Transform::Rotate90, [[3, 0], [4, 1], [5, 2]].concat()),
+            (Transform::Rotate180, [[5, 4, 3], [2, 1, 0]].concat()),
+            (Transform::Rotate270, [[2, 5], [1, 4], [0, 3]].concat()),
+            (Transform::Mirror, [[2, 1, 0], [5, 4, 3]].concat()),
+            (Transform::MirrorRotate90, [[5, 2], [4, 1], [3, 0]].concat()),
+            (Transform::MirrorRotate180, [[3, 4, 5], [0, 1, 2]].concat()),
+            (Transform::MirrorRotate270, [[0, 3], [1, 4], [2, 5]].concat()),
+        ];
+
+        for (transform, expected) in cases {
+            let mut canvas = ImageBufferCanvas::new(VecImageBuffer::<ArgbPixel>::new(4, 4));
+            canvas.draw_region(1, 1, &src, 0, 0, 3, 2, transform);
+
+            let (width, _) = transform.transformed_size(3, 2);
+            let drawn = (0..expected.len() as u32)
+                .map(|i| canvas.image().get_pixel(1 + i % width, 1 + i / width).r)
+                .collect::<Vec<_>>();
+
+            assert_eq!(drawn, expected, "{:?}", transform);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_draw_text() -> anyhow::Result<()> {
+        let image_buffer = VecImageBuffer::<ArgbPixel>::new(64, 32);
+        let mut canvas = ImageBufferCanvas::new(image_buffer);
+
+        let font = Font::default();
+        let (width, height) = canvas.measure_text("A한", &font);
+        assert!(width > 0 && width < 64);
+        assert_eq!(height, font.height());
+
+        canvas.draw_text("A한", 0, 0, &font, Color { r: 255, g: 0, b: 0, a: 255 }, TextAlignment::Left);
+
+        // glyphs are drawn in the given color, within the measured box
+        let image = canvas.image();
+        let drawn = (0..image.height())
+            .flat_map(|y| (0..image.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| image.get_pixel(x, y).r > 0)
+            .collect::<Vec<_>>();
+        assert!(!drawn.is_empty());
+        assert!(drawn.iter().all(|&(x, y)| x < width && y < height));
+        assert!(drawn.iter().all(|&(x, y)| image.get_pixel(x, y).g == 0));
+
+        let bold = Font {
+            style: FontStyle {
+                bold: true,
+                ..Default::default()
+            },
+            ..font
+        };
+        assert_eq!(canvas.measure_text("A한", &bold).0, width + 1);
+
+        let large = Font {
+            size: FontSize::Large,
+            ..font
+        };
+        assert!(large.height() > font.height());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_wbmp() -> anyhow::Result<()> {
+        // 10x2, the second row all white
+        let image = super::decode_image(&[0, 0, 10, 2, 0b1010_0000, 0b0100_0000, 0xff, 0xc0])?;
+        let is_white = |x, y| image.get_pixel(x, y).r == 255;
+
+        assert_eq!((image.width(), image.height()), (10, 2));
+        assert!(is_white(0, 0) && !is_white(1, 0) && is_white(2, 0));
+        assert!(!is_white(8, 0) && is_white(9, 0));
+        assert!((0..10).all(|x| is_white(x, 1)));
+
+        assert!(super::decode_image(&[0, 0, 10, 2, 0xff]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_animation_frame() {
+        use super::AnimatedImage;
+
+        let delays = [100, 50, 200];
+
+        assert_eq!(AnimatedImage::frame_at(&delays, 0), 0);
+        assert_eq!(AnimatedImage::frame_at(&delays, 99), 0);
+        assert_eq!(AnimatedImage::frame_at(&delays, 100), 1);
+        assert_eq!(AnimatedImage::frame_at(&delays, 150), 2);
+        assert_eq!(AnimatedImage::frame_at(&delays, 349), 2);
+        // loops after the last frame
+        assert_eq!(AnimatedImage::frame_at(&delays, 350), 0);
+        assert_eq!(AnimatedImage::frame_at(&delays, 3500 + 120), 1);
+
+        assert_eq!(AnimatedImage::frame_at(&[0], 1000), 0);
+    }
 }
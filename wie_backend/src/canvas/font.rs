@@ -0,0 +1,116 @@
+use ab_glyph::{point, Font as _, FontRef, PxScale, ScaleFont};
+
+lazy_static::lazy_static! {
+    // NeoDunggeunmo, a bitmap font traced to outlines, covering ascii and every hangul syllable of KS X 1001
+    static ref FONT: FontRef<'static> = FontRef::try_from_slice(include_bytes!("../../../fonts/neodgm.ttf")).unwrap();
+}
+
+// the embedded font has fixed half width ascii and full width hangul already, so every face is drawn the same
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FontFace {
+    #[default]
+    System,
+    Monospace,
+    Proportional,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FontSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FontStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub underlined: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Font {
+    pub face: FontFace,
+    pub style: FontStyle,
+    pub size: FontSize,
+}
+
+impl Font {
+    pub fn new(face: FontFace, style: FontStyle, size: FontSize) -> Self {
+        Self { face, style, size }
+    }
+
+    pub fn height(&self) -> u32 {
+        self.ascent() + self.descent()
+    }
+
+    pub fn ascent(&self) -> u32 {
+        FONT.as_scaled(self.scale()).ascent().ceil() as _
+    }
+
+    pub fn descent(&self) -> u32 {
+        (-FONT.as_scaled(self.scale()).descent()).ceil() as _
+    }
+
+    pub fn string_width(&self, string: &str) -> u32 {
+        let font = FONT.as_scaled(self.scale());
+
+        let width = string
+            .chars()
+            .filter(|c| !c.is_control())
+            .map(|c| font.h_advance(font.glyph_id(c)))
+            .sum::<f32>();
+
+        // bold glyphs are drawn twice, one pixel apart
+        width.ceil() as u32 + if self.style.bold && !string.is_empty() { 1 } else { 0 }
+    }
+
+    // calls back with coverage of each pixel, relative to the top left of the text
+    pub(super) fn rasterize<F>(&self, string: &str, mut put: F)
+    where
+        F: FnMut(i32, i32, f32),
+    {
+        let font = FONT.as_scaled(self.scale());
+        let ascent = font.ascent();
+
+        let mut position = 0.0;
+        for c in string.chars().filter(|c| !c.is_control()) {
+            let mut glyph = font.scaled_glyph(c);
+            glyph.position = point(position, ascent);
+            position += font.h_advance(glyph.id);
+
+            let outlined_glyph = match font.outline_glyph(glyph) {
+                Some(x) => x,
+                None => continue,
+            };
+            let bounds = outlined_glyph.px_bounds();
+
+            outlined_glyph.draw(|glyph_x, glyph_y, coverage| {
+                let x = bounds.min.x as i32 + glyph_x as i32;
+                let y = bounds.min.y as i32 + glyph_y as i32;
+                // slanted by a fifth of the height above the baseline
+                let x = if self.style.italic { x + ((ascent - y as f32) / 5.0) as i32 } else { x };
+
+                put(x, y, coverage);
+                if self.style.bold {
+                    put(x + 1, y, coverage);
+                }
+            });
+        }
+
+        if self.style.underlined {
+            for x in 0..self.string_width(string) {
+                put(x as _, ascent as i32 + 1, 1.0);
+            }
+        }
+    }
+
+    fn scale(&self) -> PxScale {
+        PxScale::from(match self.size {
+            FontSize::Small => 12.0,
+            FontSize::Medium => 16.0,
+            FontSize::Large => 20.0,
+        })
+    }
+}
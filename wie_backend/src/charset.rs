@@ -0,0 +1,38 @@
+use alloc::{string::String, vec::Vec};
+
+use encoding_rs::{EUC_KR, UTF_8};
+
+/// Encoding of the byte strings an app exchanges with the platform.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Charset {
+    /// KS X 1001 hangul, used by almost every korean title
+    #[default]
+    EucKr,
+    Utf8,
+}
+
+impl Charset {
+    /// Accepts the names apps pass to `getBytes`, like `KSC5601` or `UTF-8`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().replace('_', "-").as_str() {
+            "euc-kr" | "ksc5601" | "ks-c-5601-1987" | "cp949" | "ms949" | "x-windows-949" => Some(Self::EucKr),
+            "utf-8" | "utf8" => Some(Self::Utf8),
+            _ => None,
+        }
+    }
+
+    pub fn encode(&self, string: &str) -> Vec<u8> {
+        match self {
+            Self::EucKr => EUC_KR.encode(string).0.to_vec(),
+            Self::Utf8 => string.as_bytes().to_vec(),
+        }
+    }
+
+    // malformed sequences are replaced rather than failing, apps often cut strings in the middle of a character
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::EucKr => EUC_KR.decode(bytes).0.into_owned(),
+            Self::Utf8 => UTF_8.decode(bytes).0.into_owned(),
+        }
+    }
+}
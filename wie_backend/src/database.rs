@@ -7,8 +7,15 @@ pub trait Database {
     fn delete(&mut self, id: RecordId) -> bool;
 
     fn get_record_ids(&self) -> Vec<RecordId>;
+
+    /// Id the next added record gets. Ids start at 1 and are not reused while a larger one exists
+    fn next_record_id(&self) -> RecordId {
+        self.get_record_ids().into_iter().max().map(|x| x + 1).unwrap_or(1)
+    }
 }
 
 pub trait DatabaseRepository {
     fn open(&self, name: &str) -> Box<dyn Database>;
+    /// Names of every database stored for the app, not only the ones opened in this run
+    fn list(&self) -> Vec<String>;
 }
@@ -1,4 +1,4 @@
-use alloc::rc::Rc;
+use alloc::{rc::Rc, vec::Vec};
 use core::{
     cell::RefCell,
     fmt::Debug,
@@ -14,6 +14,8 @@ type Task = Pin<Box<dyn Future<Output = anyhow::Result<()>>>>;
 
 pub struct ExecutorInner {
     current_task_id: Option<usize>,
+    // set when the running task cancels itself, to drop it after the poll
+    current_task_cancelled: bool,
     tasks: HashMap<usize, Task>,
     sleeping_tasks: HashMap<usize, Instant>,
     last_task_id: usize,
@@ -47,6 +49,7 @@ impl Executor {
     pub fn new() -> Self {
         let inner = Rc::new(RefCell::new(ExecutorInner {
             current_task_id: None,
+            current_task_cancelled: false,
             tasks: HashMap::new(),
             sleeping_tasks: HashMap::new(),
             last_task_id: 0,
@@ -55,7 +58,11 @@ impl Executor {
         Self { inner }
     }
 
-    pub fn spawn<C, R, E>(&mut self, callable: C) -> usize
+    pub fn task_count(&self) -> usize {
+        self.inner.borrow().tasks.len()
+    }
+
+    pub fn spawn<C, R, E>(&mut self, callable: C) -> TaskHandle
     where
         C: AsyncCallable<R, E> + 'static,
         E: Debug,
@@ -74,7 +81,17 @@ impl Executor {
 
         self.inner.borrow_mut().tasks.insert(task_id, Box::pin(fut));
 
-        task_id
+        TaskHandle {
+            id: task_id,
+            executor: self.clone(),
+        }
+    }
+
+    pub fn current_task(&self) -> Option<TaskHandle> {
+        Some(TaskHandle {
+            id: self.inner.borrow().current_task_id?,
+            executor: self.clone(),
+        })
     }
 
     // TODO we need to remove error handling from here. we need to JoinHandle like on spawn..
@@ -104,50 +121,90 @@ impl Executor {
         Ok(())
     }
 
+    // each task is polled once, in the order they were spawned. tasks spawned meanwhile are polled on the next step
     fn step(&mut self, now: Instant) -> anyhow::Result<()> {
-        let mut next_tasks = HashMap::new();
-        let tasks = self.inner.borrow_mut().tasks.drain().collect::<HashMap<_, _>>();
-        let mut sleeping_tasks = self.inner.borrow_mut().sleeping_tasks.drain().collect::<HashMap<_, _>>();
-
-        for (task_id, mut task) in tasks.into_iter() {
-            let item = sleeping_tasks.get(&task_id);
-            if let Some(item) = item {
-                if *item <= now {
-                    sleeping_tasks.remove(&task_id);
-                } else {
-                    next_tasks.insert(task_id, task);
-                    continue;
+        let mut task_ids = self.inner.borrow().tasks.keys().copied().collect::<Vec<_>>();
+        task_ids.sort();
+
+        for task_id in task_ids {
+            let mut task = {
+                let mut inner = self.inner.borrow_mut();
+
+                if let Some(until) = inner.sleeping_tasks.get(&task_id) {
+                    if *until > now {
+                        continue;
+                    }
+                    inner.sleeping_tasks.remove(&task_id);
                 }
-            }
+
+                // cancelled by a task polled earlier
+                match inner.tasks.remove(&task_id) {
+                    Some(x) => x,
+                    None => continue,
+                }
+            };
 
             let waker = self.create_waker();
             let mut context = Context::from_waker(&waker);
             self.inner.borrow_mut().current_task_id = Some(task_id);
 
-            match task.as_mut().poll(&mut context) {
+            let result = task.as_mut().poll(&mut context);
+
+            let mut inner = self.inner.borrow_mut();
+            inner.current_task_id = None;
+            let cancelled = core::mem::take(&mut inner.current_task_cancelled);
+
+            match result {
+                Poll::Pending if !cancelled => {
+                    inner.tasks.insert(task_id, task);
+                }
                 Poll::Ready(x) => {
+                    Self::remove_task(&mut inner, task_id);
                     x?;
                 }
-                Poll::Pending => {
-                    next_tasks.insert(task_id, task);
-                }
+                Poll::Pending => Self::remove_task(&mut inner, task_id),
             }
-
-            self.inner.borrow_mut().current_task_id = None;
         }
 
-        self.inner.borrow_mut().sleeping_tasks.extend(sleeping_tasks);
-        self.inner.borrow_mut().tasks.extend(next_tasks);
-
         Ok(())
     }
 
+    fn remove_task(inner: &mut ExecutorInner, task_id: usize) {
+        inner.tasks.remove(&task_id);
+        inner.sleeping_tasks.remove(&task_id);
+    }
+
     pub(crate) fn sleep(&mut self, until: Instant) {
         let task_id = self.inner.borrow().current_task_id.unwrap();
 
         self.inner.borrow_mut().sleeping_tasks.insert(task_id, until);
     }
 
+    // keeps how long each task has left to sleep when the clock jumps by `millis`
+    pub(crate) fn shift_sleeping(&mut self, millis: i64) {
+        for until in self.inner.borrow_mut().sleeping_tasks.values_mut() {
+            *until = until.offset(millis);
+        }
+    }
+
+    fn cancel(&self, task_id: usize) {
+        // dropped once the borrow ends, as dropping a task runs its cleanup
+        let _task = {
+            let mut inner = self.inner.borrow_mut();
+
+            if inner.current_task_id == Some(task_id) {
+                inner.current_task_cancelled = true;
+
+                None
+            } else {
+                let task = inner.tasks.remove(&task_id);
+                Self::remove_task(&mut inner, task_id);
+
+                task
+            }
+        };
+    }
+
     fn create_waker(&self) -> Waker {
         unsafe fn noop_clone(_data: *const ()) -> RawWaker {
             noop_raw_waker()
@@ -164,3 +221,60 @@ impl Executor {
         unsafe { Waker::from_raw(noop_raw_waker()) }
     }
 }
+
+// handle to a spawned task, which doesn't keep the task alive
+#[derive(Clone)]
+pub struct TaskHandle {
+    id: usize,
+    executor: Executor,
+}
+
+impl TaskHandle {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    // the task is dropped without running further. if it cancels itself, it stops at its next await point
+    pub fn cancel(&self) {
+        self.executor.cancel(self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{rc::Rc, vec::Vec};
+    use core::cell::RefCell;
+
+    use crate::{task::YieldFuture, time::Instant};
+
+    use super::Executor;
+
+    fn push_after_yield(executor: &mut Executor, log: &Rc<RefCell<Vec<u32>>>, value: u32) -> super::TaskHandle {
+        let log = log.clone();
+
+        executor.spawn(move || async move {
+            YieldFuture::new().await;
+            log.borrow_mut().push(value);
+
+            anyhow::Ok(())
+        })
+    }
+
+    #[test]
+    fn test_cancel() -> anyhow::Result<()> {
+        let mut executor = Executor::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        let cancelled = push_after_yield(&mut executor, &log, 1);
+        push_after_yield(&mut executor, &log, 2);
+
+        executor.step(Instant::from_epoch_millis(0))?;
+        cancelled.cancel();
+        executor.step(Instant::from_epoch_millis(0))?;
+
+        assert_eq!(*log.borrow(), [2]);
+        assert_eq!(executor.task_count(), 0);
+
+        Ok(())
+    }
+}
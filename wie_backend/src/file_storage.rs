@@ -0,0 +1,9 @@
+/// Writable files of an app kept on the host. Paths are relative to the app's own area and separated by `/`.
+pub trait FileStorage {
+    fn read(&self, path: &str) -> Option<Vec<u8>>;
+    fn write(&self, path: &str, data: &[u8]) -> bool;
+    fn remove(&self, path: &str) -> bool;
+    fn create_dir(&self, path: &str) -> bool;
+    /// Names of the entries directly under the directory, directories have a trailing `/`
+    fn list(&self, path: &str) -> Option<Vec<String>>;
+}
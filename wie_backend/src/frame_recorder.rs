@@ -0,0 +1,160 @@
+use alloc::vec::Vec;
+
+use crate::{canvas::Image, time::Instant};
+
+// nothing comes after the last frame to tell how long it stayed, so it's shown for this long
+const LAST_FRAME_DELAY: u64 = 1000;
+
+// most viewers show shorter gif delays as 100ms, as old encoders wrote 0 for "as fast as possible"
+const MIN_GIF_DELAY: u64 = 20;
+
+#[derive(Clone, Copy)]
+pub enum AnimationFormat {
+    Gif,
+    Apng,
+}
+
+/// Frames along with the virtual time they were shown at, encoded as an animation when done.
+/// Each frame lasts until the next one, so only frames that changed need to be pushed
+#[derive(Default)]
+pub struct FrameRecorder {
+    width: u32,
+    height: u32,
+    // rgb
+    frames: Vec<(Instant, Vec<u8>)>,
+}
+
+impl FrameRecorder {
+    pub fn push(&mut self, time: Instant, image: &dyn Image) {
+        if self.frames.is_empty() {
+            self.width = image.width();
+            self.height = image.height();
+        } else if (image.width(), image.height()) != (self.width, self.height) {
+            tracing::warn!(
+                "Skipping {}x{} frame in {}x{} recording",
+                image.width(),
+                image.height(),
+                self.width,
+                self.height
+            );
+
+            return;
+        }
+
+        let rgb = image.colors().iter().flat_map(|x| [x.r, x.g, x.b]).collect::<Vec<_>>();
+
+        // only the last one painted at the same time is seen
+        match self.frames.last_mut() {
+            Some((last_time, last)) if *last_time == time => *last = rgb,
+            _ => self.frames.push((time, rgb)),
+        }
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn encode(&self, format: AnimationFormat) -> anyhow::Result<Vec<u8>> {
+        anyhow::ensure!(!self.frames.is_empty(), "No frames recorded");
+
+        // time may go back on loading a state, which shows the frame before it for no time
+        let delays = self
+            .frames
+            .windows(2)
+            .map(|x| x[1].0.raw().saturating_sub(x[0].0.raw()))
+            .chain([LAST_FRAME_DELAY])
+            .collect::<Vec<_>>();
+
+        match format {
+            AnimationFormat::Gif => self.encode_gif(&delays),
+            AnimationFormat::Apng => self.encode_apng(&delays),
+        }
+    }
+
+    fn encode_gif(&self, delays: &[u64]) -> anyhow::Result<Vec<u8>> {
+        use image::{
+            codecs::gif::{GifEncoder, Repeat},
+            Delay, Frame, RgbaImage,
+        };
+
+        let mut data = Vec::new();
+        let mut encoder = GifEncoder::new_with_speed(&mut data, 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+
+        // frames too short to be shown as they are are dropped, and the next one comes earlier in their place
+        let mut carried = 0;
+        for ((_, frame), &delay) in self.frames.iter().zip(delays) {
+            let delay = delay + carried;
+            if delay < MIN_GIF_DELAY {
+                carried = delay;
+                continue;
+            }
+            carried = 0;
+
+            let rgba = frame.chunks_exact(3).flat_map(|x| [x[0], x[1], x[2], 0xff]).collect::<Vec<_>>();
+            let image = RgbaImage::from_raw(self.width, self.height, rgba).unwrap();
+
+            encoder.encode_frame(Frame::from_parts(image, 0, 0, Delay::from_numer_denom_ms(delay as _, 1)))?;
+        }
+        drop(encoder);
+
+        Ok(data)
+    }
+
+    fn encode_apng(&self, delays: &[u64]) -> anyhow::Result<Vec<u8>> {
+        let mut data = Vec::new();
+
+        let mut encoder = png::Encoder::new(&mut data, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(self.frames.len() as _, 0)?;
+
+        let mut writer = encoder.write_header()?;
+        for ((_, frame), &delay) in self.frames.iter().zip(delays) {
+            writer.set_frame_delay(delay.min(u16::MAX as _) as _, 1000)?;
+            writer.write_image_data(frame)?;
+        }
+        writer.finish()?;
+
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        canvas::{AnimatedImage, ArgbPixel, VecImageBuffer},
+        time::Instant,
+    };
+
+    use super::{AnimationFormat, FrameRecorder};
+
+    #[test]
+    fn test_frame_recorder() -> anyhow::Result<()> {
+        let mut recorder = FrameRecorder::default();
+
+        let frame = |color: u32| VecImageBuffer::<ArgbPixel>::from_raw(2, 2, [color; 4].to_vec());
+        recorder.push(Instant::from_epoch_millis(1000), &frame(0xffff0000));
+        // replaces the one before, as it's painted at the same time
+        recorder.push(Instant::from_epoch_millis(1000), &frame(0xff00ff00));
+        recorder.push(Instant::from_epoch_millis(1100), &frame(0xff0000ff));
+        // too short for a gif, so the last one takes its place
+        recorder.push(Instant::from_epoch_millis(1350), &frame(0xffffffff));
+        recorder.push(Instant::from_epoch_millis(1360), &frame(0xff000000));
+        // different size
+        recorder.push(Instant::from_epoch_millis(1400), &VecImageBuffer::<ArgbPixel>::new(4, 4));
+        assert_eq!(recorder.frame_count(), 4);
+
+        let gif = AnimatedImage::decode(&recorder.encode(AnimationFormat::Gif)?)?;
+        assert_eq!(gif.delays(), [100, 250, 1010]);
+        // colors go through the palette of gif
+        assert!(gif.frame(0).get_pixel(1, 1).g > 0xf0);
+        assert!(gif.frame(2).get_pixel(0, 0).r < 0x10);
+
+        let apng = recorder.encode(AnimationFormat::Apng)?;
+        assert!(apng.starts_with(b"\x89PNG"));
+        assert!(apng.windows(4).any(|x| x == b"acTL"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use alloc::{string::String, vec::Vec};
+
+/// Direct access to the emulated guest, for tools like scripts or debuggers.
+pub trait GuestAccess {
+    fn read_memory(&self, address: u32, size: u32) -> anyhow::Result<Vec<u8>>;
+    fn write_memory(&mut self, address: u32, data: &[u8]) -> anyhow::Result<()>;
+    /// Schedules a call to the guest function at `address`, which runs on the next tick.
+    fn call_function(&mut self, address: u32, params: &[u32]) -> anyhow::Result<()>;
+
+    /// Register names and values, as of where the guest stopped if paused.
+    fn registers(&self) -> Vec<(&'static str, u32)>;
+    fn add_breakpoint(&mut self, address: u32);
+    fn remove_breakpoint(&mut self, address: u32) -> bool;
+    fn breakpoints(&self) -> Vec<u32>;
+    /// Stops guest code before its next instruction. Hitting a breakpoint pauses as well.
+    fn pause(&mut self);
+    fn resume(&mut self);
+    /// Resumes for a single instruction, then pauses again.
+    fn step(&mut self);
+    fn is_paused(&self) -> bool;
+
+    /// Mapped memory as (address, size).
+    fn memory_regions(&self) -> Vec<(u32, u32)>;
+    /// What the mapped memory is for, as (name, address, size, permission like `r-x`, owner), sorted by address.
+    fn memory_map(&self) -> Vec<(String, u32, u32, &'static str, String)>;
+    /// Recently taken branches as (from, to), oldest first.
+    fn branch_history(&self) -> Vec<(u32, u32)>;
+    /// Records every instruction run in `start..end` to the trace, or stops recording them.
+    fn set_trace_region(&mut self, start: u32, end: u32, enabled: bool);
+    /// Records calls to native functions to the trace.
+    fn set_trace_functions(&mut self, enabled: bool);
+    /// Most recent trace entries, oldest first. Older ones are dropped as new ones come in.
+    fn trace(&self) -> Vec<String>;
+    /// Unmapped address the guest accessed, which stops the app.
+    fn fault_address(&self) -> Option<u32>;
+
+    /// Instructions run before yielding to other tasks, lower values keep the ui responsive on busy loops at some speed cost.
+    fn set_instruction_budget(&mut self, count: u32);
+}
@@ -0,0 +1,365 @@
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    format,
+    rc::Rc,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::cell::{Cell, RefCell};
+
+use crate::{
+    audio_sink::AudioSink,
+    canvas::{Image, Rect},
+    database::{Database, DatabaseRepository, RecordId},
+    file_storage::FileStorage,
+    platform::Platform,
+    screen::Screen,
+    time::Instant,
+};
+
+#[derive(Clone)]
+struct HeadlessScreen {
+    width: u32,
+    height: u32,
+    // rgba
+    frame: Rc<RefCell<Vec<u8>>>,
+    redraw_requested: Rc<Cell<bool>>,
+}
+
+impl Screen for HeadlessScreen {
+    fn request_redraw(&self) -> anyhow::Result<()> {
+        self.redraw_requested.set(true);
+
+        Ok(())
+    }
+
+    fn paint(&mut self, image: &dyn Image) {
+        self.paint_rect(image, Rect::new(0, 0, image.width(), image.height()))
+    }
+
+    fn paint_rect(&mut self, image: &dyn Image, rect: Rect) {
+        let rect = rect.intersection(&Rect::new(0, 0, self.width.min(image.width()), self.height.min(image.height())));
+        let mut frame = self.frame.borrow_mut();
+
+        for y in rect.y as u32..rect.y as u32 + rect.height {
+            for x in rect.x as u32..rect.x as u32 + rect.width {
+                let color = image.get_pixel(x, y);
+                let offset = ((y * self.width + x) * 4) as usize;
+
+                frame[offset..offset + 4].copy_from_slice(&[color.r, color.g, color.b, 0xff]);
+            }
+        }
+    }
+
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+struct NullAudioSink;
+
+impl AudioSink for NullAudioSink {
+    fn play_wave(&self, _channel: u8, _sampling_rate: u32, _wave_data: &[i16]) {}
+}
+
+type Records = Rc<RefCell<BTreeMap<RecordId, Vec<u8>>>>;
+
+struct MemoryDatabase {
+    records: Records,
+}
+
+impl Database for MemoryDatabase {
+    fn add(&mut self, data: &[u8]) -> RecordId {
+        let id = self.next_record_id();
+        self.records.borrow_mut().insert(id, data.to_vec());
+
+        id
+    }
+
+    fn get(&self, id: RecordId) -> Option<Vec<u8>> {
+        self.records.borrow().get(&id).cloned()
+    }
+
+    fn set(&mut self, id: RecordId, data: &[u8]) -> bool {
+        self.records.borrow_mut().insert(id, data.to_vec());
+
+        true
+    }
+
+    fn delete(&mut self, id: RecordId) -> bool {
+        self.records.borrow_mut().remove(&id).is_some()
+    }
+
+    fn get_record_ids(&self) -> Vec<RecordId> {
+        self.records.borrow().keys().copied().collect()
+    }
+}
+
+// databases opened by the same name share their records, as they would on disk
+#[derive(Default)]
+struct MemoryDatabaseRepository {
+    databases: RefCell<BTreeMap<String, Records>>,
+}
+
+impl DatabaseRepository for MemoryDatabaseRepository {
+    fn open(&self, name: &str) -> Box<dyn Database> {
+        let records = self.databases.borrow_mut().entry(name.to_string()).or_default().clone();
+
+        Box::new(MemoryDatabase { records })
+    }
+
+    fn list(&self) -> Vec<String> {
+        self.databases.borrow().keys().cloned().collect()
+    }
+}
+
+// directories are kept with `None`, parents of a written file are made along with it
+#[derive(Default)]
+struct MemoryFileStorage {
+    entries: RefCell<BTreeMap<String, Option<Vec<u8>>>>,
+}
+
+impl MemoryFileStorage {
+    fn normalize(path: &str) -> String {
+        path.trim_matches('/').to_string()
+    }
+
+    fn children<'a>(entries: &'a BTreeMap<String, Option<Vec<u8>>>, path: &'a str) -> impl Iterator<Item = (&'a String, &'a Option<Vec<u8>>)> {
+        entries
+            .iter()
+            .filter(move |(key, _)| path.is_empty() || key.strip_prefix(path).is_some_and(|x| x.starts_with('/')))
+    }
+}
+
+impl FileStorage for MemoryFileStorage {
+    fn read(&self, path: &str) -> Option<Vec<u8>> {
+        self.entries.borrow().get(&Self::normalize(path)).cloned().flatten()
+    }
+
+    fn write(&self, path: &str, data: &[u8]) -> bool {
+        let path = Self::normalize(path);
+        let mut entries = self.entries.borrow_mut();
+
+        if path.is_empty() || matches!(entries.get(&path), Some(None)) {
+            return false;
+        }
+
+        let mut parent = path.as_str();
+        while let Some((x, _)) = parent.rsplit_once('/') {
+            entries.entry(x.to_string()).or_insert(None);
+            parent = x;
+        }
+        entries.insert(path, Some(data.to_vec()));
+
+        true
+    }
+
+    fn remove(&self, path: &str) -> bool {
+        let path = Self::normalize(path);
+        let mut entries = self.entries.borrow_mut();
+
+        // directories have to be empty, as with the host filesystem
+        if Self::children(&entries, &path).next().is_some() {
+            return false;
+        }
+
+        entries.remove(&path).is_some()
+    }
+
+    fn create_dir(&self, path: &str) -> bool {
+        let path = Self::normalize(path);
+        let mut entries = self.entries.borrow_mut();
+
+        if matches!(entries.get(&path), Some(Some(_))) {
+            return false;
+        }
+
+        let mut current = String::new();
+        for component in path.split('/').filter(|x| !x.is_empty()) {
+            current = if current.is_empty() {
+                component.to_string()
+            } else {
+                format!("{}/{}", current, component)
+            };
+            entries.entry(current.clone()).or_insert(None);
+        }
+
+        true
+    }
+
+    fn list(&self, path: &str) -> Option<Vec<String>> {
+        let path = Self::normalize(path);
+        let entries = self.entries.borrow();
+
+        if !path.is_empty() && !matches!(entries.get(&path), Some(None)) {
+            return None;
+        }
+
+        let names = Self::children(&entries, &path)
+            .filter_map(|(key, value)| {
+                let rest = if path.is_empty() { key.as_str() } else { &key[path.len() + 1..] };
+
+                // only the direct children, deeper ones are found through their own directory
+                (!rest.contains('/')).then(|| if value.is_none() { format!("{}/", rest) } else { rest.to_string() })
+            })
+            .collect::<BTreeSet<_>>();
+
+        Some(names.into_iter().collect())
+    }
+}
+
+/// Platform without any host window or sound device, keeping everything in memory.
+///
+/// Time only moves by [`HeadlessHandle::advance`], so runs are the same on every host.
+pub struct HeadlessPlatform {
+    screen: HeadlessScreen,
+    now: Rc<Cell<u64>>,
+    database_repository: MemoryDatabaseRepository,
+    file_storage: MemoryFileStorage,
+    system_properties: BTreeMap<String, String>,
+}
+
+impl HeadlessPlatform {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            screen: HeadlessScreen {
+                width,
+                height,
+                frame: Rc::new(RefCell::new([0, 0, 0, 0xff].repeat((width * height) as usize))),
+                redraw_requested: Rc::new(Cell::new(false)),
+            },
+            now: Rc::new(Cell::new(0)),
+            database_repository: MemoryDatabaseRepository::default(),
+            file_storage: MemoryFileStorage::default(),
+            system_properties: BTreeMap::new(),
+        }
+    }
+
+    pub fn set_system_property(&mut self, name: &str, value: &str) {
+        self.system_properties.insert(name.to_string(), value.to_string());
+    }
+
+    /// To keep after the platform is handed over to the app
+    pub fn handle(&self) -> HeadlessHandle {
+        HeadlessHandle {
+            screen: self.screen.clone(),
+            now: self.now.clone(),
+        }
+    }
+}
+
+impl Platform for HeadlessPlatform {
+    fn screen(&mut self) -> &mut dyn Screen {
+        &mut self.screen
+    }
+
+    fn now(&self) -> Instant {
+        Instant::from_epoch_millis(self.now.get())
+    }
+
+    fn database_repository(&self) -> &dyn DatabaseRepository {
+        &self.database_repository
+    }
+
+    fn file_storage(&self) -> &dyn FileStorage {
+        &self.file_storage
+    }
+
+    fn audio_sink(&self) -> Box<dyn AudioSink> {
+        Box::new(NullAudioSink)
+    }
+
+    fn system_property(&self, name: &str) -> Option<String> {
+        self.system_properties.get(name).cloned()
+    }
+}
+
+#[derive(Clone)]
+pub struct HeadlessHandle {
+    screen: HeadlessScreen,
+    now: Rc<Cell<u64>>,
+}
+
+impl HeadlessHandle {
+    pub fn width(&self) -> u32 {
+        self.screen.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.screen.height
+    }
+
+    /// Last painted frame as rgba rows, black where nothing was painted yet
+    pub fn frame_rgba(&self) -> Vec<u8> {
+        self.screen.frame.borrow().clone()
+    }
+
+    /// Whether the app asked for a redraw since the last call. It's up to the caller to send [`crate::Event::Redraw`]
+    pub fn take_redraw_request(&self) -> bool {
+        self.screen.redraw_requested.replace(false)
+    }
+
+    pub fn now(&self) -> Instant {
+        Instant::from_epoch_millis(self.now.get())
+    }
+
+    pub fn advance(&self, millis: u64) {
+        self.now.set(self.now.get() + millis);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, string::ToString, vec};
+
+    use crate::{
+        canvas::{ArgbPixel, Color, ImageBuffer, VecImageBuffer},
+        Platform, System,
+    };
+
+    use super::HeadlessPlatform;
+
+    #[test]
+    fn test_headless_frame() {
+        let platform = HeadlessPlatform::new(4, 2);
+        let handle = platform.handle();
+        let system = System::new(Box::new(platform), Box::new(()));
+
+        let mut image = VecImageBuffer::<ArgbPixel>::new(4, 2);
+        image.put_pixel(1, 1, Color { a: 255, r: 10, g: 20, b: 30 });
+        system.repaint(&image);
+
+        let frame = handle.frame_rgba();
+        assert_eq!(frame.len(), 4 * 2 * 4);
+        assert_eq!(&frame[(4 + 1) * 4..(4 + 2) * 4], &[10, 20, 30, 255]);
+        assert_eq!(&frame[0..4], &[0, 0, 0, 255]);
+
+        handle.advance(100);
+        assert_eq!(system.platform().now().raw(), 100);
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        let platform = HeadlessPlatform::new(1, 1);
+
+        let storage = platform.file_storage();
+        assert!(storage.write("/save/slot1.dat", b"data"));
+        assert!(storage.create_dir("empty"));
+        assert_eq!(storage.read("save/slot1.dat"), Some(b"data".to_vec()));
+        assert_eq!(storage.list("/"), Some(vec!["empty/".to_string(), "save/".to_string()]));
+        assert_eq!(storage.list("save"), Some(vec!["slot1.dat".to_string()]));
+        assert!(!storage.remove("save"));
+        assert!(storage.remove("save/slot1.dat"));
+        assert!(storage.remove("save"));
+        assert_eq!(storage.list("save"), None);
+
+        let mut database = platform.database_repository().open("scores");
+        let id = database.add(b"100");
+        assert_eq!(platform.database_repository().open("scores").get(id), Some(b"100".to_vec()));
+    }
+}
@@ -2,8 +2,13 @@ extern crate alloc;
 
 mod audio_sink;
 pub mod canvas;
+mod charset;
 mod database;
 mod executor;
+mod file_storage;
+mod frame_recorder;
+mod guest;
+mod headless;
 mod platform;
 mod screen;
 mod system;
@@ -12,11 +17,19 @@ mod time;
 
 pub use self::{
     audio_sink::AudioSink,
+    charset::Charset,
     database::{Database, DatabaseRepository, RecordId},
-    executor::AsyncCallable,
+    executor::{AsyncCallable, TaskHandle},
+    file_storage::FileStorage,
+    frame_recorder::{AnimationFormat, FrameRecorder},
+    guest::GuestAccess,
+    headless::{HeadlessHandle, HeadlessPlatform},
     platform::Platform,
     screen::Screen,
-    system::{Event, KeyCode, System},
+    system::{
+        AudioError, AudioHandle, CompatibilityReport, Event, FileError, FileHandle, HeapUsage, ImportKind, ImportStatus, KeyCode, OpenOptions,
+        SeekOrigin, System, SystemSnapshot, Telemetry, TimerToken, Tone,
+    },
     time::Instant,
 };
 
@@ -26,13 +39,32 @@ pub trait App {
     fn start(&mut self) -> anyhow::Result<()>;
     fn on_event(&mut self, event: Event);
     fn tick(&mut self) -> anyhow::Result<()>;
+    fn report(&self) -> CompatibilityReport;
+    fn save_state(&self) -> anyhow::Result<Vec<u8>>;
+    fn load_state(&mut self, state: &[u8]) -> anyhow::Result<()>;
+    fn guest(&self) -> Option<Box<dyn GuestAccess>>;
+    fn telemetry(&self) -> Telemetry;
 }
 
 pub trait Archive {
     fn id(&self) -> String;
+    /// Name of the platform the archive targets, like `KTF`
+    fn platform(&self) -> &'static str;
+    fn main_class_name(&self) -> Option<String>;
+    /// Human readable name of the app from its descriptor
+    fn name(&self) -> Option<String>;
+    /// Contents of the icon image in the archive, if the descriptor has one
+    fn icon(&self) -> Option<Vec<u8>>;
+    /// Every file the app would see, including the binary
+    fn files(&self) -> anyhow::Result<BTreeMap<String, Vec<u8>>>;
     fn load_app(self: Box<Self>, platform: Box<dyn Platform>) -> anyhow::Result<Box<dyn App>>;
 }
 
+/// Decodes text of descriptors, which is usually in EUC-KR.
+pub fn decode_euc_kr(bytes: &[u8]) -> String {
+    Charset::EucKr.decode(bytes)
+}
+
 pub fn extract_zip(zip: &[u8]) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
     use std::io::{Cursor, Read};
     use zip::ZipArchive;
@@ -50,3 +82,142 @@ pub fn extract_zip(zip: &[u8]) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
         })
         .collect::<anyhow::Result<_>>()
 }
+
+/// Locates zip archives stored contiguously inside a raw blob, such as a flash dump taken from a handset.
+///
+/// Archives are found by their end of central directory record, so this works even if the
+/// surrounding filesystem structure is unknown.
+pub fn carve_zip_archives(data: &[u8]) -> Vec<&[u8]> {
+    const LOCAL_FILE_HEADER_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x03, 0x04];
+    const END_OF_CENTRAL_DIRECTORY_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
+    const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+
+    let read_u16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]) as usize;
+    let read_u32 = |offset: usize| u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]) as usize;
+
+    let mut result = Vec::new();
+    if data.len() < END_OF_CENTRAL_DIRECTORY_SIZE {
+        return result;
+    }
+
+    for eocd in 0..=data.len() - END_OF_CENTRAL_DIRECTORY_SIZE {
+        if data[eocd..eocd + 4] != END_OF_CENTRAL_DIRECTORY_SIGNATURE {
+            continue;
+        }
+
+        let central_directory_size = read_u32(eocd + 12);
+        let central_directory_offset = read_u32(eocd + 16);
+        let comment_length = read_u16(eocd + 20);
+
+        // offsets come from the blob, so candidates with out of range values are skipped instead of trusted
+        let start = match central_directory_size
+            .checked_add(central_directory_offset)
+            .and_then(|x| eocd.checked_sub(x))
+        {
+            Some(x) => x,
+            None => continue,
+        };
+        let end = match (eocd + END_OF_CENTRAL_DIRECTORY_SIZE).checked_add(comment_length) {
+            Some(x) if x <= data.len() => x,
+            _ => continue,
+        };
+
+        if data[start..].get(..4) != Some(&LOCAL_FILE_HEADER_SIGNATURE[..]) {
+            continue;
+        }
+
+        tracing::debug!("Found zip archive at {:#x}..{:#x}", start, end);
+
+        result.push(&data[start..end]);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::carve_zip_archives;
+
+    // smallest blob carving accepts: a local file header, a central directory and the end record
+    fn archive(comment_length: u16) -> Vec<u8> {
+        let mut result = Vec::new();
+        result.extend_from_slice(&[0x50, 0x4b, 0x03, 0x04]);
+        result.extend_from_slice(&[0; 26]);
+
+        let central_directory_offset = result.len() as u32;
+        result.extend_from_slice(&[0x50, 0x4b, 0x01, 0x02]);
+        result.extend_from_slice(&[0; 42]);
+
+        result.extend_from_slice(&[0x50, 0x4b, 0x05, 0x06]);
+        result.extend_from_slice(&[0; 8]);
+        result.extend_from_slice(&46u32.to_le_bytes());
+        result.extend_from_slice(&central_directory_offset.to_le_bytes());
+        result.extend_from_slice(&comment_length.to_le_bytes());
+        result.extend(core::iter::repeat(b'c').take(comment_length as usize));
+
+        result
+    }
+
+    #[test]
+    fn test_carve_embedded() {
+        let zip = archive(3);
+
+        let mut dump = Vec::new();
+        dump.extend_from_slice(&[0xff; 100]);
+        dump.extend_from_slice(&zip);
+        dump.extend_from_slice(&[0xff; 50]);
+
+        assert_eq!(carve_zip_archives(&dump), [&zip[..]]);
+    }
+
+    #[test]
+    fn test_carve_truncated() {
+        let mut dump = [0xffu8; 10].to_vec();
+        dump.extend_from_slice(&archive(10));
+        // comment runs past the end
+        dump.truncate(dump.len() - 5);
+
+        assert!(carve_zip_archives(&dump).is_empty());
+
+        // end record cut in the middle
+        let mut dump = archive(0);
+        dump.truncate(dump.len() - 4);
+
+        assert!(carve_zip_archives(&dump).is_empty());
+    }
+
+    #[test]
+    fn test_carve_bogus_offsets() {
+        let mut dump = archive(0);
+        let eocd = dump.len() - 22;
+        dump[eocd + 12..eocd + 16].copy_from_slice(&u32::MAX.to_le_bytes());
+        dump[eocd + 16..eocd + 20].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        assert!(carve_zip_archives(&dump).is_empty());
+    }
+
+    #[test]
+    fn test_carve_overlapping() {
+        // archive stored uncompressed inside another one, both of which are valid
+        let inner = archive(0);
+
+        let mut outer = Vec::new();
+        outer.extend_from_slice(&[0x50, 0x4b, 0x03, 0x04]);
+        outer.extend_from_slice(&[0; 26]);
+        outer.extend_from_slice(&inner);
+
+        let central_directory_offset = outer.len() as u32;
+        outer.extend_from_slice(&[0x50, 0x4b, 0x01, 0x02]);
+        outer.extend_from_slice(&[0; 42]);
+        outer.extend_from_slice(&[0x50, 0x4b, 0x05, 0x06]);
+        outer.extend_from_slice(&[0; 8]);
+        outer.extend_from_slice(&46u32.to_le_bytes());
+        outer.extend_from_slice(&central_directory_offset.to_le_bytes());
+        outer.extend_from_slice(&[0; 2]);
+
+        let mut dump = [0xffu8; 7].to_vec();
+        dump.extend_from_slice(&outer);
+
+        assert_eq!(carve_zip_archives(&dump), [&inner[..], &outer[..]]);
+    }
+}
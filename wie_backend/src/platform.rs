@@ -1,8 +1,11 @@
-use crate::{audio_sink::AudioSink, database::DatabaseRepository, screen::Screen, time::Instant};
+use crate::{audio_sink::AudioSink, database::DatabaseRepository, file_storage::FileStorage, screen::Screen, time::Instant};
 
 pub trait Platform {
     fn screen(&mut self) -> &mut dyn Screen;
     fn now(&self) -> Instant;
     fn database_repository(&self) -> &dyn DatabaseRepository;
+    fn file_storage(&self) -> &dyn FileStorage;
     fn audio_sink(&self) -> Box<dyn AudioSink>;
+    /// Value of a handset property queried by the app, such as the platform version.
+    fn system_property(&self, name: &str) -> Option<String>;
 }
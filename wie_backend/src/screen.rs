@@ -1,8 +1,96 @@
-use crate::canvas::Image;
+use alloc::vec::Vec;
+
+use crate::canvas::{Image, Rect};
 
 pub trait Screen {
     fn request_redraw(&self) -> anyhow::Result<()>;
     fn paint(&mut self, image: &dyn Image);
+    /// Updates only `rect` from `image`, which is the whole screen. It may be shown on the next vsync instead of right away
+    fn paint_rect(&mut self, image: &dyn Image, rect: Rect) {
+        let _ = rect;
+
+        self.paint(image)
+    }
     fn width(&self) -> u32;
     fn height(&self) -> u32;
 }
+
+// keeps the last painted frame as is, so finding changes costs a comparison instead of a pixel conversion
+#[derive(Default)]
+pub struct DirtyTracker {
+    width: u32,
+    height: u32,
+    last_frame: Vec<u8>,
+}
+
+impl DirtyTracker {
+    /// Bounds of what changed since the previous frame, `None` if nothing did
+    pub fn update(&mut self, image: &dyn Image) -> Option<Rect> {
+        let (width, height) = (image.width(), image.height());
+        let raw = image.raw();
+
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        if width != self.width || height != self.height || raw.len() != self.last_frame.len() {
+            self.width = width;
+            self.height = height;
+            self.last_frame = raw.to_vec();
+
+            return Some(Rect::new(0, 0, width, height));
+        }
+
+        let stride = raw.len() / height as usize;
+        let bytes_per_pixel = image.bytes_per_pixel() as usize;
+
+        // left, top, right and bottom, right and bottom exclusive
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+        for (y, (row, last_row)) in raw.chunks_exact(stride).zip(self.last_frame.chunks_exact_mut(stride)).enumerate() {
+            let first = match row.iter().zip(last_row.iter()).position(|(a, b)| a != b) {
+                Some(x) => x,
+                None => continue,
+            };
+            let last = stride - 1 - row.iter().rev().zip(last_row.iter().rev()).position(|(a, b)| a != b).unwrap();
+
+            let (left, right) = (first / bytes_per_pixel, last / bytes_per_pixel + 1);
+            bounds = Some(match bounds {
+                Some((l, t, r, _)) => (l.min(left), t, r.max(right), y + 1),
+                None => (left, y, right, y + 1),
+            });
+
+            last_row.copy_from_slice(row);
+        }
+
+        bounds.map(|(left, top, right, bottom)| Rect::new(left as _, top as _, (right - left) as _, (bottom - top) as _))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::canvas::{ArgbPixel, Color, ImageBuffer, Rect, VecImageBuffer};
+
+    use super::DirtyTracker;
+
+    #[test]
+    fn test_dirty_tracker() {
+        let mut tracker = DirtyTracker::default();
+        let mut image = VecImageBuffer::<ArgbPixel>::new(8, 8);
+
+        // everything is new on the first frame
+        assert_eq!(tracker.update(&image), Some(Rect::new(0, 0, 8, 8)));
+        assert_eq!(tracker.update(&image), None);
+
+        let color = Color { a: 255, r: 255, g: 0, b: 0 };
+        image.put_pixel(2, 3, color);
+        image.put_pixel(5, 1, color);
+        assert_eq!(tracker.update(&image), Some(Rect::new(2, 1, 4, 3)));
+        assert_eq!(tracker.update(&image), None);
+
+        image.put_pixel(7, 7, Color { a: 255, r: 0, g: 255, b: 0 });
+        assert_eq!(tracker.update(&image), Some(Rect::new(7, 7, 1, 1)));
+
+        // size changes repaint everything
+        assert_eq!(tracker.update(&VecImageBuffer::<ArgbPixel>::new(4, 4)), Some(Rect::new(0, 0, 4, 4)));
+    }
+}
@@ -1,48 +1,95 @@
 mod audio;
 mod event_queue;
+mod filesystem;
+mod report;
 mod resource;
+mod snapshot;
+mod telemetry;
+mod timers;
 
-use alloc::rc::Rc;
+use alloc::{collections::BTreeMap, rc::Rc};
 use core::{
     any::Any,
-    cell::{Ref, RefCell, RefMut},
+    cell::{Cell, Ref, RefCell, RefMut},
     fmt::Debug,
 };
 
 use crate::{
-    executor::Executor,
+    canvas::{ArgbPixel, Image, PixelType, VecImageBuffer},
+    charset::Charset,
+    executor::{Executor, TaskHandle},
+    frame_recorder::{AnimationFormat, FrameRecorder},
     platform::Platform,
+    screen::DirtyTracker,
     task::{SleepFuture, YieldFuture},
     AsyncCallable, Instant,
 };
 
-use self::{audio::Audio, event_queue::EventQueue, resource::Resource};
+use self::{audio::Audio, event_queue::EventQueue, filesystem::VirtualFilesystem, resource::Resource, timers::Timers};
 
-pub use self::event_queue::{Event, KeyCode};
+pub use self::{
+    audio::{AudioError, AudioHandle, Tone},
+    event_queue::{Event, KeyCode},
+    filesystem::{FileError, FileHandle, OpenOptions, SeekOrigin},
+    report::{CompatibilityReport, ImportKind, ImportStatus},
+    snapshot::SystemSnapshot,
+    telemetry::{HeapUsage, Telemetry},
+    timers::TimerToken,
+};
+
+// repaints happen every frame, so a frame is copied only when asked for
+enum FrameCapture {
+    None,
+    Requested,
+    Captured(VecImageBuffer<ArgbPixel>),
+}
 
 #[derive(Clone)]
 pub struct System {
     executor: Executor,
     platform: Rc<RefCell<Box<dyn Platform>>>,
     resource: Rc<RefCell<Resource>>,
+    filesystem: Rc<RefCell<VirtualFilesystem>>,
     event_queue: Rc<RefCell<EventQueue>>,
+    timers: Rc<RefCell<Timers>>,
     audio: Option<Rc<RefCell<Audio>>>,
     context: Rc<RefCell<Box<dyn Any>>>,
+    report: CompatibilityReport,
+    charset: Rc<Cell<Charset>>,
+    dirty_tracker: Rc<RefCell<DirtyTracker>>,
+    frame_capture: Rc<RefCell<FrameCapture>>,
+    frame_recorder: Rc<RefCell<Option<FrameRecorder>>>,
+    // from the platform clock to the time the app sees, which continues from a restored snapshot
+    time_offset: Rc<Cell<i64>>,
 }
 
 impl System {
     pub fn new(platform: Box<dyn Platform>, context: Box<dyn Any>) -> Self {
         let audio_sink = platform.audio_sink();
+        // same property midlets read the default encoding from
+        let charset = platform
+            .system_property("microedition.encoding")
+            .and_then(|x| Charset::from_name(&x))
+            .unwrap_or_default();
 
         let platform = Rc::new(RefCell::new(platform));
+        let resource = Rc::new(RefCell::new(Resource::new()));
 
         let mut result = Self {
             executor: Executor::new(),
             platform: platform.clone(),
-            resource: Rc::new(RefCell::new(Resource::new())),
+            resource: resource.clone(),
+            filesystem: Rc::new(RefCell::new(VirtualFilesystem::new(platform, resource))),
             event_queue: Rc::new(RefCell::new(EventQueue::new())),
+            timers: Rc::new(RefCell::new(Timers::default())),
             audio: None,
             context: Rc::new(RefCell::new(context)),
+            report: CompatibilityReport::new(),
+            charset: Rc::new(Cell::new(charset)),
+            dirty_tracker: Rc::new(RefCell::new(DirtyTracker::default())),
+            frame_capture: Rc::new(RefCell::new(FrameCapture::None)),
+            frame_recorder: Rc::new(RefCell::new(None)),
+            time_offset: Rc::new(Cell::new(0)),
         };
 
         // late initialization
@@ -52,20 +99,31 @@ impl System {
     }
 
     pub fn tick(&mut self) -> anyhow::Result<()> {
-        let platform = self.platform.clone();
-        self.executor.tick(move || {
-            let platform = platform.borrow();
+        let system = self.clone();
+        let result = self.executor.tick(move || system.now());
+
+        // once a tick instead of on every write, as files are stored whole
+        self.filesystem.borrow_mut().flush();
 
-            platform.now()
-        })
+        result
     }
 
-    pub fn spawn<C, R, E>(&mut self, callable: C)
+    pub fn spawn<C, R, E>(&mut self, callable: C) -> TaskHandle
     where
         C: AsyncCallable<R, E> + 'static,
         E: Debug,
     {
-        self.executor.spawn(callable);
+        self.executor.spawn(callable)
+    }
+
+    // handle of the task calling this
+    pub fn current_task(&self) -> Option<TaskHandle> {
+        self.executor.current_task()
+    }
+
+    /// Time as the app sees it, which is the platform time unless a snapshot was restored
+    pub fn now(&self) -> Instant {
+        self.platform.borrow().now().offset(self.time_offset.get())
     }
 
     pub fn sleep(&mut self, until: Instant) -> SleepFuture {
@@ -73,20 +131,24 @@ impl System {
     }
 
     pub fn yield_now(&self) -> YieldFuture {
-        YieldFuture {}
+        YieldFuture::new()
     }
 
-    // TODO add encoding configuration..
-    pub fn encode_str(&self, string: &str) -> Vec<u8> {
-        use encoding_rs::EUC_KR;
+    pub fn charset(&self) -> Charset {
+        self.charset.get()
+    }
 
-        EUC_KR.encode(string).0.to_vec()
+    pub fn set_charset(&self, charset: Charset) {
+        self.charset.set(charset)
     }
 
-    pub fn decode_str(&self, bytes: &[u8]) -> String {
-        use encoding_rs::EUC_KR;
+    // in the app default charset
+    pub fn encode_str(&self, string: &str) -> Vec<u8> {
+        self.charset.get().encode(string)
+    }
 
-        EUC_KR.decode(bytes).0.to_string()
+    pub fn decode_str(&self, bytes: &[u8]) -> String {
+        self.charset.get().decode(bytes)
     }
 
     pub fn resource(&self) -> Ref<'_, Resource> {
@@ -97,10 +159,69 @@ impl System {
         self.resource.borrow_mut()
     }
 
+    pub fn filesystem(&self) -> RefMut<'_, VirtualFilesystem> {
+        self.filesystem.borrow_mut()
+    }
+
     pub fn platform(&self) -> RefMut<'_, Box<dyn Platform>> {
         self.platform.borrow_mut()
     }
 
+    /// Shows `image` as the whole screen, passing on only the part that changed since the last repaint
+    pub fn repaint(&self, image: &dyn Image) {
+        let mut frame_capture = self.frame_capture.borrow_mut();
+        if matches!(*frame_capture, FrameCapture::Requested) {
+            let colors = image.colors().into_iter().map(ArgbPixel::from_color).collect();
+
+            *frame_capture = FrameCapture::Captured(VecImageBuffer::from_raw(image.width(), image.height(), colors));
+        }
+        drop(frame_capture);
+
+        let dirty = self.dirty_tracker.borrow_mut().update(image);
+
+        if let Some(rect) = dirty {
+            if let Some(x) = self.frame_recorder.borrow_mut().as_mut() {
+                let now = self.now();
+                x.push(now, image);
+            }
+
+            self.platform().screen().paint_rect(image, rect);
+        }
+    }
+
+    /// Copies the next repainted frame, to be taken with `take_frame`
+    pub fn request_frame(&self) {
+        *self.frame_capture.borrow_mut() = FrameCapture::Requested;
+    }
+
+    /// Frame copied after `request_frame`, `None` if nothing was repainted since
+    pub fn take_frame(&self) -> Option<VecImageBuffer<ArgbPixel>> {
+        let mut frame_capture = self.frame_capture.borrow_mut();
+
+        match core::mem::replace(&mut *frame_capture, FrameCapture::None) {
+            FrameCapture::Captured(x) => Some(x),
+            FrameCapture::Requested => {
+                *frame_capture = FrameCapture::Requested;
+
+                None
+            }
+            FrameCapture::None => None,
+        }
+    }
+
+    /// Starts keeping repainted frames on virtual time, to be encoded by `stop_recording`
+    pub fn start_recording(&self) {
+        *self.frame_recorder.borrow_mut() = Some(FrameRecorder::default());
+        // so the screen as it is now becomes the first frame, without waiting for it to change
+        *self.dirty_tracker.borrow_mut() = DirtyTracker::default();
+    }
+
+    pub fn stop_recording(&self, format: AnimationFormat) -> anyhow::Result<Vec<u8>> {
+        let recorder = self.frame_recorder.borrow_mut().take().ok_or_else(|| anyhow::anyhow!("Not recording"))?;
+
+        recorder.encode(format)
+    }
+
     pub fn audio(&self) -> RefMut<'_, Audio> {
         self.audio.as_ref().unwrap().borrow_mut()
     }
@@ -108,7 +229,94 @@ impl System {
     pub fn event_queue(&self) -> RefMut<'_, EventQueue> {
         self.event_queue.borrow_mut()
     }
+
+    pub fn timers(&self) -> RefMut<'_, Timers> {
+        self.timers.borrow_mut()
+    }
+
     pub fn context(&self) -> RefMut<'_, Box<dyn Any>> {
         self.context.borrow_mut()
     }
+
+    /// State kept outside of the app's memory, to be saved along with it
+    pub fn snapshot(&self) -> SystemSnapshot {
+        let platform = self.platform.borrow();
+        let repository = platform.database_repository();
+
+        let databases = repository
+            .list()
+            .into_iter()
+            .map(|name| {
+                let database = repository.open(&name);
+                let records = database
+                    .get_record_ids()
+                    .into_iter()
+                    .filter_map(|id| Some((id, database.get(id)?)))
+                    .collect::<BTreeMap<_, _>>();
+
+                (name, records)
+            })
+            .collect();
+
+        SystemSnapshot {
+            now: self.now(),
+            events: self.event_queue.borrow().events().cloned().collect(),
+            databases,
+        }
+    }
+
+    /// Time continues from the snapshot, with sleeping tasks and timers keeping what they had left.
+    /// Databases are put back as they were, including removing ones made since
+    pub fn restore_snapshot(&mut self, snapshot: &SystemSnapshot) -> anyhow::Result<()> {
+        let delta = snapshot.now.raw() as i64 - self.now().raw() as i64;
+        self.time_offset.set(self.time_offset.get() + delta);
+        self.executor.shift_sleeping(delta);
+        self.timers.borrow_mut().shift(delta);
+
+        self.event_queue.borrow_mut().replace(snapshot.events.iter().cloned());
+
+        let platform = self.platform.borrow();
+        let repository = platform.database_repository();
+        for name in repository.list() {
+            if !snapshot.databases.contains_key(&name) {
+                let mut database = repository.open(&name);
+                for id in database.get_record_ids() {
+                    database.delete(id);
+                }
+            }
+        }
+        for (name, records) in &snapshot.databases {
+            let mut database = repository.open(name);
+            for id in database.get_record_ids() {
+                if !records.contains_key(&id) {
+                    database.delete(id);
+                }
+            }
+            for (&id, data) in records {
+                if !database.set(id, data) {
+                    anyhow::bail!("Failed to restore record {} of database {}", id, name);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn report(&self) -> &CompatibilityReport {
+        &self.report
+    }
+
+    pub fn telemetry(&self) -> Telemetry {
+        Telemetry {
+            task_count: self.executor.task_count(),
+            // audio is borrowed while a clip is being played from a task
+            audio_voice_count: self
+                .audio
+                .as_ref()
+                .and_then(|x| x.try_borrow().ok())
+                .map(|x| x.playing_count())
+                .unwrap_or(0),
+            heap_usage: None,
+        }
+    }
 }
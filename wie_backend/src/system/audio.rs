@@ -1,93 +1,317 @@
-use alloc::collections::BTreeMap;
-use core::{result::Result, time::Duration};
+mod midi;
+mod synth;
+mod tone;
+mod wave;
+
+use alloc::{collections::BTreeMap, rc::Rc, vec, vec::Vec};
+use core::{
+    cell::{Cell, RefCell},
+    result::Result,
+    time::Duration,
+};
 
 use smaf::Smaf;
 use smaf_player::{play_smaf, AudioBackend};
 
-use crate::{audio_sink::AudioSink, System};
+use crate::{audio_sink::AudioSink, Instant, System};
 
-struct AudioBackendImpl {
-    system: System,
-    sink: Box<dyn AudioSink>,
+use self::{
+    midi::parse_midi,
+    synth::{render, SynthEvent, TimedEvent, SAMPLE_RATE},
+    tone::tone_events,
+    wave::{parse_pcm, parse_wave},
+};
+
+pub use self::tone::Tone;
+
+// collects what the player does instead of playing it, sleeping only advances the clock
+#[derive(Default)]
+struct EventCollector {
+    time: Cell<f64>,
+    events: RefCell<Vec<TimedEvent>>,
+}
+
+impl EventCollector {
+    fn push(&self, event: SynthEvent) {
+        self.events.borrow_mut().push(TimedEvent {
+            time: self.time.get(),
+            event,
+        });
+    }
 }
 
 #[async_trait::async_trait(?Send)]
-impl AudioBackend for AudioBackendImpl {
+impl AudioBackend for EventCollector {
     fn play_wave(&self, channel: u8, sampling_rate: u32, wave_data: &[i16]) {
-        self.sink.play_wave(channel, sampling_rate, wave_data);
+        self.push(SynthEvent::Wave {
+            channels: channel,
+            sampling_rate,
+            data: wave_data.to_vec(),
+        });
     }
 
-    fn midi_note_on(&self, _channel_id: u8, _note: u8, _velocity: u8) {
-        // TODO
+    fn midi_note_on(&self, channel_id: u8, note: u8, velocity: u8) {
+        self.push(SynthEvent::NoteOn {
+            channel: channel_id,
+            note,
+            velocity,
+        });
     }
 
-    fn midi_note_off(&self, _channel_id: u8, _note: u8, _velocity: u8) {
-        // TODO
+    fn midi_note_off(&self, channel_id: u8, note: u8, _velocity: u8) {
+        self.push(SynthEvent::NoteOff { channel: channel_id, note });
     }
 
-    fn midi_program_change(&self, _channel_id: u8, _program: u8) {
-        // TODO
+    fn midi_program_change(&self, channel_id: u8, program: u8) {
+        self.push(SynthEvent::ProgramChange {
+            channel: channel_id,
+            program,
+        });
     }
 
-    fn midi_control_change(&self, _channel_id: u8, _control: u8, _value: u8) {
-        // TODO
+    fn midi_control_change(&self, channel_id: u8, control: u8, value: u8) {
+        self.push(SynthEvent::ControlChange {
+            channel: channel_id,
+            control,
+            value,
+        });
     }
 
     async fn sleep(&self, duration: Duration) {
-        let now = self.system.platform().now();
-        let end = now + duration.as_millis() as _;
-
-        self.system.clone().sleep(end).await
+        self.time.set(self.time.get() + duration.as_secs_f64());
     }
 
     fn now_millis(&self) -> u64 {
-        self.system.platform().now().raw()
+        (self.time.get() * 1000.0) as _
     }
 }
 
 pub type AudioHandle = u32;
+
+#[derive(Debug)]
 pub enum AudioError {
     InvalidHandle,
     InvalidAudio,
 }
 
-enum AudioFile {
+enum AudioData {
     Smaf(Vec<u8>),
+    Events(Vec<TimedEvent>),
 }
 
+#[derive(Clone, Copy)]
+enum ClipState {
+    Stopped,
+    Playing { started: Instant, repeat: bool },
+    // position in samples
+    Paused { position: usize, repeat: bool },
+}
+
+struct Clip {
+    data: AudioData,
+    volume: u8,
+    state: ClipState,
+    // rendered on first play, and again when the volume changes. the volume it was rendered with comes along
+    pcm: Option<(u8, Rc<Vec<i16>>)>,
+}
+
+// clips are rendered whole and handed to the sink, which plays them on its own
 pub struct Audio {
-    backend: AudioBackendImpl,
-    files: BTreeMap<AudioHandle, AudioFile>,
+    sink: Box<dyn AudioSink>,
+    system: System,
+    clips: BTreeMap<AudioHandle, Clip>,
     last_audio_handle: AudioHandle,
 }
 
 impl Audio {
+    pub const MAX_VOLUME: u8 = 100;
+
     pub fn new(sink: Box<dyn AudioSink>, system: System) -> Self {
         Self {
-            backend: AudioBackendImpl { sink, system },
-            files: BTreeMap::new(),
+            sink,
+            system,
+            clips: BTreeMap::new(),
             last_audio_handle: 0,
         }
     }
 
-    pub fn load_smaf(&mut self, data: &[u8]) -> Result<AudioHandle, AudioError> {
-        let audio_handle = self.last_audio_handle;
+    /// Loads a midi, smaf or wave clip, detected by its header.
+    pub fn load(&mut self, data: &[u8]) -> Result<AudioHandle, AudioError> {
+        let data = if data.starts_with(b"MMMD") {
+            Smaf::parse(data).map_err(|_| AudioError::InvalidAudio)?;
+
+            AudioData::Smaf(data.to_vec())
+        } else if data.starts_with(b"MThd") {
+            AudioData::Events(parse_midi(data).ok_or(AudioError::InvalidAudio)?)
+        } else if data.starts_with(b"RIFF") {
+            let wave = parse_wave(data).ok_or(AudioError::InvalidAudio)?;
 
+            AudioData::Events(vec![TimedEvent { time: 0.0, event: wave }])
+        } else {
+            return Err(AudioError::InvalidAudio);
+        };
+
+        Ok(self.insert(data))
+    }
+
+    /// Loads headerless 16 bit little endian pcm.
+    pub fn load_pcm(&mut self, data: &[u8], channels: u8, sampling_rate: u32) -> Result<AudioHandle, AudioError> {
+        if channels == 0 || sampling_rate == 0 {
+            return Err(AudioError::InvalidAudio);
+        }
+
+        let wave = parse_pcm(data, channels, sampling_rate);
+
+        Ok(self.insert(AudioData::Events(vec![TimedEvent { time: 0.0, event: wave }])))
+    }
+
+    pub fn load_tones(&mut self, tones: &[Tone]) -> AudioHandle {
+        self.insert(AudioData::Events(tone_events(tones)))
+    }
+
+    fn insert(&mut self, data: AudioData) -> AudioHandle {
+        let audio_handle = self.last_audio_handle;
         self.last_audio_handle += 1;
-        self.files.insert(audio_handle, AudioFile::Smaf(data.to_vec()));
 
-        Ok(audio_handle)
+        self.clips.insert(
+            audio_handle,
+            Clip {
+                data,
+                volume: Self::MAX_VOLUME,
+                state: ClipState::Stopped,
+                pcm: None,
+            },
+        );
+
+        audio_handle
     }
 
-    pub async fn play(&self, audio_handle: AudioHandle) -> Result<(), AudioError> {
-        match self.files.get(&audio_handle) {
-            Some(AudioFile::Smaf(data)) => {
-                let smaf = Smaf::parse(data).map_err(|_| AudioError::InvalidAudio)?;
-                play_smaf(&smaf, &self.backend).await;
+    pub fn unload(&mut self, audio_handle: AudioHandle) -> Result<(), AudioError> {
+        self.stop(audio_handle)?;
+        self.clips.remove(&audio_handle);
+
+        Ok(())
+    }
+
+    /// Starts the clip from the beginning, restarting it if it's playing already.
+    pub async fn play(&mut self, audio_handle: AudioHandle, repeat: bool) -> Result<(), AudioError> {
+        let pcm = self.rendered(audio_handle).await?;
+        let now = self.system.now();
+
+        self.sink.play_clip(audio_handle, 1, SAMPLE_RATE, &pcm, repeat);
+        self.clip_mut(audio_handle)?.state = ClipState::Playing { started: now, repeat };
+
+        Ok(())
+    }
+
+    pub fn stop(&mut self, audio_handle: AudioHandle) -> Result<(), AudioError> {
+        self.clip_mut(audio_handle)?.state = ClipState::Stopped;
+        self.sink.stop_clip(audio_handle);
+
+        Ok(())
+    }
+
+    pub fn pause(&mut self, audio_handle: AudioHandle) -> Result<(), AudioError> {
+        let now = self.system.now();
+        let clip = self.clip_mut(audio_handle)?;
+
+        if let ClipState::Playing { started, repeat } = clip.state {
+            let length = clip.pcm.as_ref().map(|x| x.1.len()).unwrap_or(0).max(1);
+            let position = ((now - started) * SAMPLE_RATE as u64 / 1000) as usize;
+
+            clip.state = ClipState::Paused {
+                position: if repeat { position % length } else { position.min(length) },
+                repeat,
+            };
+            self.sink.stop_clip(audio_handle);
+        }
+
+        Ok(())
+    }
+
+    pub fn resume(&mut self, audio_handle: AudioHandle) -> Result<(), AudioError> {
+        let now = self.system.now();
+        let clip = self.clip_mut(audio_handle)?;
+
+        if let ClipState::Paused { position, repeat } = clip.state {
+            let pcm = clip.pcm.as_ref().map(|x| x.1.clone()).unwrap_or_default();
+            let position = position.min(pcm.len());
+
+            // started is moved back by the part already played, so pausing again finds the right position
+            let elapsed = position as u64 * 1000 / SAMPLE_RATE as u64;
+            clip.state = ClipState::Playing {
+                started: Instant::from_epoch_millis(now.raw().saturating_sub(elapsed)),
+                repeat,
+            };
+            if repeat {
+                // rotated, so the loop continues from where it was paused
+                let rotated = [&pcm[position..], &pcm[..position]].concat();
+                self.sink.play_clip(audio_handle, 1, SAMPLE_RATE, &rotated, true);
+            } else {
+                self.sink.play_clip(audio_handle, 1, SAMPLE_RATE, &pcm[position..], false);
             }
-            None => return Err(AudioError::InvalidHandle),
         }
 
         Ok(())
     }
+
+    /// Sets the volume from 0 to [`Self::MAX_VOLUME`], which applies from the next play.
+    pub fn set_volume(&mut self, audio_handle: AudioHandle, volume: u8) -> Result<(), AudioError> {
+        self.clip_mut(audio_handle)?.volume = volume.min(Self::MAX_VOLUME);
+
+        Ok(())
+    }
+
+    pub fn volume(&self, audio_handle: AudioHandle) -> Result<u8, AudioError> {
+        Ok(self.clips.get(&audio_handle).ok_or(AudioError::InvalidHandle)?.volume)
+    }
+
+    pub fn is_playing(&self, audio_handle: AudioHandle) -> bool {
+        self.clips.get(&audio_handle).map(|x| self.clip_is_playing(x)).unwrap_or(false)
+    }
+
+    pub fn playing_count(&self) -> usize {
+        self.clips.values().filter(|x| self.clip_is_playing(x)).count()
+    }
+
+    fn clip_is_playing(&self, clip: &Clip) -> bool {
+        match clip.state {
+            ClipState::Playing { repeat: true, .. } => true,
+            ClipState::Playing { started, repeat: false } => {
+                let length = clip.pcm.as_ref().map(|x| x.1.len()).unwrap_or(0) as u64 * 1000 / SAMPLE_RATE as u64;
+
+                self.system.now() < started + length
+            }
+            _ => false,
+        }
+    }
+
+    async fn rendered(&mut self, audio_handle: AudioHandle) -> Result<Rc<Vec<i16>>, AudioError> {
+        let clip = self.clip_mut(audio_handle)?;
+        if let Some((_, pcm)) = clip.pcm.as_ref().filter(|x| x.0 == clip.volume) {
+            return Ok(pcm.clone());
+        }
+
+        let volume = clip.volume as f64 / Self::MAX_VOLUME as f64;
+        let pcm = match &clip.data {
+            AudioData::Smaf(data) => {
+                let smaf = Smaf::parse(data).map_err(|_| AudioError::InvalidAudio)?;
+
+                let collector = EventCollector::default();
+                play_smaf(&smaf, &collector).await;
+
+                render(&collector.events.into_inner(), volume)
+            }
+            AudioData::Events(events) => render(events, volume),
+        };
+
+        let pcm = Rc::new(pcm);
+        clip.pcm = Some((clip.volume, pcm.clone()));
+
+        Ok(pcm)
+    }
+
+    fn clip_mut(&mut self, audio_handle: AudioHandle) -> Result<&mut Clip, AudioError> {
+        self.clips.get_mut(&audio_handle).ok_or(AudioError::InvalidHandle)
+    }
 }
@@ -0,0 +1,254 @@
+use alloc::vec::Vec;
+
+use super::synth::{SynthEvent, TimedEvent};
+
+// microseconds per quarter note until the file sets one, which is 120 bpm
+const DEFAULT_TEMPO: u32 = 500_000;
+
+struct Reader<'a> {
+    data: &'a [u8],
+    cursor: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, cursor: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.cursor >= self.data.len()
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        let result = *self.data.get(self.cursor)?;
+        self.cursor += 1;
+
+        Some(result)
+    }
+
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        let result = self.data.get(self.cursor..self.cursor.checked_add(length)?)?;
+        self.cursor += length;
+
+        Some(result)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn variable_length(&mut self) -> Option<u32> {
+        let mut result = 0u32;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            result = (result << 7) | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                return Some(result);
+            }
+        }
+
+        None
+    }
+}
+
+enum TrackEvent {
+    Synth(SynthEvent),
+    Tempo(u32),
+}
+
+/// Parses a standard midi file into events timed in seconds.
+pub fn parse_midi(data: &[u8]) -> Option<Vec<TimedEvent>> {
+    let mut reader = Reader::new(data);
+
+    if reader.bytes(4)? != b"MThd" {
+        return None;
+    }
+    let header_length = reader.u32()? as usize;
+    let mut header = Reader::new(reader.bytes(header_length)?);
+    let _format = header.u16()?;
+    let track_count = header.u16()?;
+    let division = header.u16()?;
+
+    // (tick, order in file, event), order keeps events on the same tick as written
+    let mut events = Vec::new();
+    for _ in 0..track_count {
+        // chunks other than tracks are skipped, as the spec asks
+        let (kind, length) = (reader.bytes(4)?, reader.u32()? as usize);
+        let chunk = reader.bytes(length)?;
+        if kind != b"MTrk" {
+            continue;
+        }
+
+        parse_track(chunk, &mut events);
+    }
+    events.sort_by_key(|&(tick, order, _)| (tick, order));
+
+    let seconds_per_tick = |tempo: u32| {
+        if division & 0x8000 != 0 {
+            // smpte, frames per second in the upper byte as a negative number and ticks per frame in the lower
+            let fps = -((division >> 8) as i8 as i32) as f64;
+            1.0 / (fps * (division & 0xff) as f64)
+        } else {
+            tempo as f64 / 1_000_000.0 / division.max(1) as f64
+        }
+    };
+
+    let mut result = Vec::with_capacity(events.len());
+    let (mut last_tick, mut time, mut tempo) = (0, 0.0, DEFAULT_TEMPO);
+    for (tick, _, event) in events {
+        time += (tick - last_tick) as f64 * seconds_per_tick(tempo);
+        last_tick = tick;
+
+        match event {
+            TrackEvent::Tempo(x) => tempo = x,
+            TrackEvent::Synth(x) => result.push(TimedEvent { time, event: x }),
+        }
+    }
+
+    Some(result)
+}
+
+// a truncated track ends where the data does
+fn parse_track(data: &[u8], events: &mut Vec<(u64, usize, TrackEvent)>) {
+    let mut reader = Reader::new(data);
+    let mut tick = 0u64;
+    let mut running_status = 0;
+
+    while !reader.is_empty() {
+        let delta = match reader.variable_length() {
+            Some(x) => x,
+            None => return,
+        };
+        tick += delta as u64;
+
+        let mut status = match reader.u8() {
+            Some(x) => x,
+            None => return,
+        };
+        // running status, the byte we read is the first data byte
+        let mut pending = if status & 0x80 == 0 {
+            let data = status;
+            status = running_status;
+            Some(data)
+        } else {
+            None
+        };
+
+        let event = match status {
+            0xff => {
+                let (kind, length) = match (reader.u8(), reader.variable_length()) {
+                    (Some(kind), Some(length)) => (kind, length),
+                    _ => return,
+                };
+                let data = match reader.bytes(length as _) {
+                    Some(x) => x,
+                    None => return,
+                };
+
+                match (kind, data) {
+                    (0x2f, _) => return,
+                    (0x51, &[a, b, c]) => Some(TrackEvent::Tempo(u32::from_be_bytes([0, a, b, c]))),
+                    _ => None,
+                }
+            }
+            0xf0 | 0xf7 => {
+                let length = match reader.variable_length() {
+                    Some(x) => x,
+                    None => return,
+                };
+                if reader.bytes(length as _).is_none() {
+                    return;
+                }
+
+                None
+            }
+            0x80..=0xef => {
+                running_status = status;
+
+                let mut next = || pending.take().or_else(|| reader.u8());
+                let channel = status & 0x0f;
+                let first = match next() {
+                    Some(x) => x,
+                    None => return,
+                };
+
+                match status & 0xf0 {
+                    0xc0 => Some(TrackEvent::Synth(SynthEvent::ProgramChange { channel, program: first })),
+                    0xd0 => None,
+                    kind => {
+                        let second = match next() {
+                            Some(x) => x,
+                            None => return,
+                        };
+
+                        match kind {
+                            0x80 => Some(SynthEvent::NoteOff { channel, note: first }),
+                            0x90 if second == 0 => Some(SynthEvent::NoteOff { channel, note: first }),
+                            0x90 => Some(SynthEvent::NoteOn {
+                                channel,
+                                note: first,
+                                velocity: second,
+                            }),
+                            0xb0 => Some(SynthEvent::ControlChange {
+                                channel,
+                                control: first,
+                                value: second,
+                            }),
+                            _ => None,
+                        }
+                        .map(TrackEvent::Synth)
+                    }
+                }
+            }
+            _ => return,
+        };
+
+        if let Some(event) = event {
+            events.push((tick, events.len(), event));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::synth::SynthEvent, parse_midi};
+
+    #[test]
+    fn test_parse_midi() {
+        #[rustfmt::skip]
+        let data = [
+            b"MThd".as_slice(), &[0, 0, 0, 6], &[0, 0], &[0, 1], &[0, 96],
+            b"MTrk", &[0, 0, 0, 21],
+            // tempo of 1 second per quarter note
+            &[0x00, 0xff, 0x51, 0x03, 0x0f, 0x42, 0x40],
+            &[0x00, 0x90, 60, 100],
+            // running status, note on with zero velocity is note off
+            &[0x60, 60, 0],
+            &[0x30, 0xc0, 5],
+            &[0x00, 0xff, 0x2f, 0x00],
+        ]
+        .concat();
+
+        let events = parse_midi(&data).unwrap();
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0].event,
+            SynthEvent::NoteOn {
+                channel: 0,
+                note: 60,
+                velocity: 100
+            }
+        );
+        assert_eq!(events[1].event, SynthEvent::NoteOff { channel: 0, note: 60 });
+        assert!((events[1].time - 1.0).abs() < 1e-9);
+        assert_eq!(events[2].event, SynthEvent::ProgramChange { channel: 0, program: 5 });
+        assert!((events[2].time - 1.5).abs() < 1e-9);
+
+        assert!(parse_midi(&[0; 16]).is_none());
+    }
+}
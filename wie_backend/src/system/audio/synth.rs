@@ -0,0 +1,319 @@
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::f64::consts::PI;
+
+pub const SAMPLE_RATE: u32 = 22050;
+
+// longer clips are cut, a broken file shouldn't take all the memory
+const MAX_LENGTH: f64 = 600.0;
+
+const PERCUSSION_CHANNEL: u8 = 9;
+const ATTACK: f64 = 0.005;
+const RELEASE: f64 = 0.05;
+// leaves headroom for a handful of voices before clipping
+const VOICE_GAIN: f64 = 0.2;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SynthEvent {
+    NoteOn {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+    },
+    NoteOff {
+        channel: u8,
+        note: u8,
+    },
+    ProgramChange {
+        channel: u8,
+        program: u8,
+    },
+    ControlChange {
+        channel: u8,
+        control: u8,
+        value: u8,
+    },
+    /// Interleaved pcm mixed in as is
+    Wave {
+        channels: u8,
+        sampling_rate: u32,
+        data: Vec<i16>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct TimedEvent {
+    /// Seconds from the start
+    pub time: f64,
+    pub event: SynthEvent,
+}
+
+#[derive(Clone, Copy)]
+enum Waveform {
+    Sine,
+    Triangle,
+    Square,
+    Saw,
+}
+
+// a rough stand-in for general midi instruments, by the family of the program
+fn instrument(program: u8) -> (Waveform, bool) {
+    match program / 8 {
+        0 => (Waveform::Triangle, true),    // piano
+        1 => (Waveform::Sine, true),        // chromatic percussion
+        2 => (Waveform::Square, false),     // organ
+        3 => (Waveform::Saw, true),         // guitar
+        4 => (Waveform::Triangle, false),   // bass
+        5 | 6 => (Waveform::Saw, false),    // strings and ensemble
+        7..=9 => (Waveform::Square, false), // brass, reed and pipe
+        10 => (Waveform::Square, false),    // synth lead
+        11 => (Waveform::Sine, false),      // synth pad
+        _ => (Waveform::Triangle, true),
+    }
+}
+
+struct Note {
+    start: f64,
+    end: f64,
+    channel: u8,
+    note: u8,
+    gain: f64,
+    program: u8,
+}
+
+#[derive(Clone, Copy)]
+struct ChannelState {
+    program: u8,
+    volume: u8,
+    expression: u8,
+}
+
+impl Default for ChannelState {
+    fn default() -> Self {
+        Self {
+            program: 0,
+            volume: 100,
+            expression: 127,
+        }
+    }
+}
+
+/// Renders events to mono pcm at [`SAMPLE_RATE`], scaled by `volume` from 0.0 to 1.0.
+pub fn render(events: &[TimedEvent], volume: f64) -> Vec<i16> {
+    let mut channels = [ChannelState::default(); 16];
+    let mut sounding = BTreeMap::new();
+    let mut notes = Vec::<Note>::new();
+    let mut waves = Vec::new();
+    let mut end = 0.0f64;
+
+    for TimedEvent { time, event } in events {
+        let time = time.min(MAX_LENGTH);
+        end = end.max(time);
+
+        match event {
+            &SynthEvent::NoteOn { channel, note, velocity } => {
+                let state = channels[channel as usize & 0xf];
+                let gain = VOICE_GAIN * (velocity as f64 / 127.0) * (state.volume as f64 / 127.0) * (state.expression as f64 / 127.0);
+
+                // retriggering a sounding note ends the previous one
+                if let Some(index) = sounding.insert((channel, note), notes.len()) {
+                    notes[index].end = time;
+                }
+                notes.push(Note {
+                    start: time,
+                    end: f64::INFINITY,
+                    channel,
+                    note,
+                    gain,
+                    program: state.program,
+                });
+            }
+            &SynthEvent::NoteOff { channel, note } => {
+                if let Some(index) = sounding.remove(&(channel, note)) {
+                    notes[index].end = time;
+                }
+            }
+            &SynthEvent::ProgramChange { channel, program } => channels[channel as usize & 0xf].program = program,
+            &SynthEvent::ControlChange { channel, control, value } => {
+                let state = &mut channels[channel as usize & 0xf];
+                match control {
+                    7 => state.volume = value,
+                    11 => state.expression = value,
+                    // all sound off and all notes off
+                    120 | 123 => {
+                        sounding.retain(|&(x, _), &mut index| {
+                            if x == channel {
+                                notes[index].end = time;
+                            }
+                            x != channel
+                        });
+                    }
+                    _ => {}
+                }
+            }
+            SynthEvent::Wave {
+                channels,
+                sampling_rate,
+                data,
+            } => {
+                let frames = data.len() / (*channels).max(1) as usize;
+                end = end.max(time + frames as f64 / *sampling_rate as f64);
+                waves.push((time, *channels, *sampling_rate, data));
+            }
+        }
+    }
+
+    // notes still held at the end are let go there
+    for note in &mut notes {
+        if note.end.is_infinite() {
+            note.end = end;
+        }
+    }
+    let end = notes.iter().map(|x| x.end + RELEASE).fold(end, f64::max).min(MAX_LENGTH);
+
+    let mut mix = vec![0.0f64; (end * SAMPLE_RATE as f64).ceil() as usize];
+    for note in &notes {
+        render_note(note, &mut mix);
+    }
+    for (time, channels, sampling_rate, data) in waves {
+        mix_wave(time, channels, sampling_rate, data, &mut mix);
+    }
+
+    mix.into_iter()
+        .map(|x| (x * volume * i16::MAX as f64).clamp(i16::MIN as f64, i16::MAX as f64) as i16)
+        .collect()
+}
+
+fn render_note(note: &Note, mix: &mut [f64]) {
+    let first = (note.start * SAMPLE_RATE as f64) as usize;
+
+    if note.channel == PERCUSSION_CHANNEL {
+        // noise burst, lower keys ring longer
+        let decay = 0.05 + (80u8.saturating_sub(note.note)) as f64 / 200.0;
+        let mut seed = 0x1234_5678u32 ^ note.note as u32;
+
+        for (i, sample) in mix.iter_mut().skip(first).enumerate().take((decay * 5.0 * SAMPLE_RATE as f64) as usize) {
+            let t = i as f64 / SAMPLE_RATE as f64;
+
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            let noise = seed as f64 / u32::MAX as f64 * 2.0 - 1.0;
+
+            *sample += noise * note.gain * (-t / decay).exp();
+        }
+
+        return;
+    }
+
+    let (waveform, decays) = instrument(note.program);
+    let frequency = 440.0 * 2f64.powf((note.note as f64 - 69.0) / 12.0);
+    let length = note.end - note.start;
+
+    for (i, sample) in mix.iter_mut().skip(first).enumerate() {
+        let t = i as f64 / SAMPLE_RATE as f64;
+        if t >= length + RELEASE {
+            break;
+        }
+
+        let mut envelope = (t / ATTACK).min(1.0);
+        if decays {
+            envelope *= (-t / 0.6).exp();
+        }
+        if t > length {
+            envelope *= 1.0 - (t - length) / RELEASE;
+        }
+
+        let phase = (frequency * t).fract();
+        let value = match waveform {
+            Waveform::Sine => (phase * 2.0 * PI).sin(),
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Waveform::Square => {
+                if phase < 0.5 {
+                    0.5
+                } else {
+                    -0.5
+                }
+            }
+            Waveform::Saw => phase - 0.5,
+        };
+
+        *sample += value * envelope * note.gain;
+    }
+}
+
+// resampled with linear interpolation, channels are mixed down
+fn mix_wave(time: f64, channels: u8, sampling_rate: u32, data: &[i16], mix: &mut [f64]) {
+    let channels = channels.max(1) as usize;
+    let frames = data.len() / channels;
+    if frames == 0 || sampling_rate == 0 {
+        return;
+    }
+
+    let frame =
+        |index: usize| data[index * channels..(index + 1) * channels].iter().map(|&x| x as f64).sum::<f64>() / channels as f64 / i16::MAX as f64;
+
+    let first = (time * SAMPLE_RATE as f64) as usize;
+    let step = sampling_rate as f64 / SAMPLE_RATE as f64;
+    for (i, sample) in mix.iter_mut().skip(first).enumerate() {
+        let position = i as f64 * step;
+        let index = position as usize;
+        if index + 1 >= frames {
+            break;
+        }
+
+        let fraction = position.fract();
+        *sample += frame(index) * (1.0 - fraction) + frame(index + 1) * fraction;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::{render, SynthEvent, TimedEvent, SAMPLE_RATE};
+
+    #[test]
+    fn test_render() {
+        let events = vec![
+            TimedEvent {
+                time: 0.0,
+                event: SynthEvent::NoteOn {
+                    channel: 0,
+                    note: 69,
+                    velocity: 127,
+                },
+            },
+            TimedEvent {
+                time: 0.5,
+                event: SynthEvent::NoteOff { channel: 0, note: 69 },
+            },
+        ];
+
+        let pcm = render(&events, 1.0);
+
+        // the note, and its release after
+        assert_eq!(pcm.len(), (0.55 * SAMPLE_RATE as f64).ceil() as usize);
+        assert!(pcm[..SAMPLE_RATE as usize / 2].iter().any(|&x| x.abs() > 1000));
+        assert_eq!(*pcm.last().unwrap(), 0);
+
+        let silent = render(&events, 0.0);
+        assert!(silent.iter().all(|&x| x == 0));
+    }
+
+    #[test]
+    fn test_render_wave() {
+        let events = vec![TimedEvent {
+            time: 0.0,
+            event: SynthEvent::Wave {
+                channels: 1,
+                sampling_rate: SAMPLE_RATE / 2,
+                data: vec![i16::MAX / 2; 100],
+            },
+        }];
+
+        let pcm = render(&events, 1.0);
+
+        assert_eq!(pcm.len(), 200);
+        assert!((pcm[10] - i16::MAX / 2).abs() <= 1);
+    }
+}
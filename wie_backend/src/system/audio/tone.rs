@@ -0,0 +1,109 @@
+use alloc::{vec, vec::Vec};
+use core::f64::consts::PI;
+
+use super::synth::{SynthEvent, TimedEvent, SAMPLE_RATE};
+
+// square lead, closest to the beeper tones are played on
+const TONE_PROGRAM: u8 = 80;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tone {
+    /// Note number as in midi, out of range ones are rests
+    Note { note: i32, duration: u32 },
+    /// Two frequencies in hz sounding together like dial tones, zero is silent
+    Frequency { high: u32, low: u32, duration: u32 },
+}
+
+/// Lays out tones one after another, durations are in milliseconds.
+pub fn tone_events(tones: &[Tone]) -> Vec<TimedEvent> {
+    let mut result = vec![TimedEvent {
+        time: 0.0,
+        event: SynthEvent::ProgramChange {
+            channel: 0,
+            program: TONE_PROGRAM,
+        },
+    }];
+
+    let mut time = 0.0;
+    for tone in tones {
+        match *tone {
+            Tone::Note { note, duration } => {
+                let end = time + duration as f64 / 1000.0;
+
+                if let Ok(note @ 0..=127) = u8::try_from(note) {
+                    result.push(TimedEvent {
+                        time,
+                        event: SynthEvent::NoteOn {
+                            channel: 0,
+                            note,
+                            velocity: 127,
+                        },
+                    });
+                    result.push(TimedEvent {
+                        time: end,
+                        event: SynthEvent::NoteOff { channel: 0, note },
+                    });
+                }
+
+                time = end;
+            }
+            Tone::Frequency { high, low, duration } => {
+                let length = (duration as u64 * SAMPLE_RATE as u64 / 1000) as usize;
+                let data = (0..length)
+                    .map(|i| {
+                        let t = i as f64 / SAMPLE_RATE as f64;
+                        let value = [high, low]
+                            .iter()
+                            .filter(|&&x| x != 0)
+                            .map(|&x| (2.0 * PI * x as f64 * t).sin())
+                            .sum::<f64>();
+
+                        (value * 0.25 * i16::MAX as f64) as i16
+                    })
+                    .collect();
+
+                result.push(TimedEvent {
+                    time,
+                    event: SynthEvent::Wave {
+                        channels: 1,
+                        sampling_rate: SAMPLE_RATE,
+                        data,
+                    },
+                });
+
+                time += duration as f64 / 1000.0;
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::synth::SynthEvent, tone_events, Tone};
+
+    #[test]
+    fn test_tone_events() {
+        let events = tone_events(&[
+            Tone::Note { note: 60, duration: 500 },
+            Tone::Note { note: -1, duration: 250 },
+            Tone::Frequency {
+                high: 1209,
+                low: 697,
+                duration: 100,
+            },
+        ]);
+
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[2].event, SynthEvent::NoteOff { channel: 0, note: 60 });
+        assert!((events[2].time - 0.5).abs() < 1e-9);
+
+        // after the rest
+        assert!((events[3].time - 0.75).abs() < 1e-9);
+        match &events[3].event {
+            SynthEvent::Wave { data, .. } => assert_eq!(data.len(), 2205),
+            _ => panic!(),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use alloc::vec::Vec;
+
+use super::synth::SynthEvent;
+
+const FORMAT_PCM: u16 = 1;
+
+/// Parses a riff wave file of 8 or 16 bit pcm.
+pub fn parse_wave(data: &[u8]) -> Option<SynthEvent> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return None;
+    }
+
+    let mut format = None;
+    let mut cursor = 12;
+    while cursor + 8 <= data.len() {
+        let kind = &data[cursor..cursor + 4];
+        let length = u32::from_le_bytes(data[cursor + 4..cursor + 8].try_into().unwrap()) as usize;
+        // a truncated last chunk is taken as far as it goes
+        let chunk = &data[cursor + 8..(cursor + 8).saturating_add(length).min(data.len())];
+
+        match kind {
+            b"fmt " if chunk.len() >= 16 => {
+                let tag = u16::from_le_bytes([chunk[0], chunk[1]]);
+                let channels = u16::from_le_bytes([chunk[2], chunk[3]]);
+                let sampling_rate = u32::from_le_bytes(chunk[4..8].try_into().unwrap());
+                let bits_per_sample = u16::from_le_bytes([chunk[14], chunk[15]]);
+
+                if tag != FORMAT_PCM || channels == 0 || channels > u8::MAX as u16 {
+                    return None;
+                }
+                format = Some((channels as u8, sampling_rate, bits_per_sample));
+            }
+            b"data" => {
+                let (channels, sampling_rate, bits_per_sample) = format?;
+                let data = match bits_per_sample {
+                    8 => chunk.iter().map(|&x| ((x as i16) - 128) << 8).collect::<Vec<_>>(),
+                    16 => chunk.chunks_exact(2).map(|x| i16::from_le_bytes([x[0], x[1]])).collect(),
+                    _ => return None,
+                };
+
+                return Some(SynthEvent::Wave {
+                    channels,
+                    sampling_rate,
+                    data,
+                });
+            }
+            _ => {}
+        }
+
+        // chunks are padded to even length
+        cursor += 8 + length + (length & 1);
+    }
+
+    None
+}
+
+/// Decodes headerless 16 bit little endian pcm.
+pub fn parse_pcm(data: &[u8], channels: u8, sampling_rate: u32) -> SynthEvent {
+    SynthEvent::Wave {
+        channels,
+        sampling_rate,
+        data: data.chunks_exact(2).map(|x| i16::from_le_bytes([x[0], x[1]])).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::{super::synth::SynthEvent, parse_wave};
+
+    #[test]
+    fn test_parse_wave() {
+        #[rustfmt::skip]
+        let data = [
+            b"RIFF".as_slice(), &[36 + 4, 0, 0, 0], b"WAVE",
+            b"fmt ", &[16, 0, 0, 0], &[1, 0], &[1, 0], &[0x40, 0x1f, 0, 0], &[0x40, 0x1f, 0, 0], &[1, 0], &[8, 0],
+            b"data", &[4, 0, 0, 0], &[0x80, 0xff, 0x00, 0x80],
+        ]
+        .concat();
+
+        assert_eq!(
+            parse_wave(&data),
+            Some(SynthEvent::Wave {
+                channels: 1,
+                sampling_rate: 8000,
+                data: vec![0, 127 << 8, -128 << 8, 0],
+            })
+        );
+
+        assert_eq!(parse_wave(b"RIFF\0\0\0\0AVI "), None);
+    }
+}
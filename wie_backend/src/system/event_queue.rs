@@ -21,12 +21,16 @@ pub enum KeyCode {
     NUM9,
     HASH,
     STAR,
+
+    LEFT_SOFT_KEY,
+    RIGHT_SOFT_KEY,
+    CLEAR,
 }
 
 impl KeyCode {
     // TODO we can use libraries like strum
-    pub fn parse(string: &str) -> KeyCode {
-        match string {
+    pub fn parse(string: &str) -> Option<KeyCode> {
+        Some(match string {
             "UP" => KeyCode::UP,
             "DOWN" => KeyCode::DOWN,
             "LEFT" => KeyCode::LEFT,
@@ -44,16 +48,57 @@ impl KeyCode {
             "9" => KeyCode::NUM9,
             "#" => KeyCode::HASH,
             "*" => KeyCode::STAR,
-            _ => unimplemented!("Unknown key: {}", string),
+            "SOFT1" => KeyCode::LEFT_SOFT_KEY,
+            "SOFT2" => KeyCode::RIGHT_SOFT_KEY,
+            "CLEAR" => KeyCode::CLEAR,
+            _ => return None,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyCode::UP => "UP",
+            KeyCode::DOWN => "DOWN",
+            KeyCode::LEFT => "LEFT",
+            KeyCode::RIGHT => "RIGHT",
+            KeyCode::OK => "OK",
+            KeyCode::NUM0 => "0",
+            KeyCode::NUM1 => "1",
+            KeyCode::NUM2 => "2",
+            KeyCode::NUM3 => "3",
+            KeyCode::NUM4 => "4",
+            KeyCode::NUM5 => "5",
+            KeyCode::NUM6 => "6",
+            KeyCode::NUM7 => "7",
+            KeyCode::NUM8 => "8",
+            KeyCode::NUM9 => "9",
+            KeyCode::HASH => "#",
+            KeyCode::STAR => "*",
+            KeyCode::LEFT_SOFT_KEY => "SOFT1",
+            KeyCode::RIGHT_SOFT_KEY => "SOFT2",
+            KeyCode::CLEAR => "CLEAR",
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Event {
     Redraw,
     Keydown(KeyCode),
     Keyup(KeyCode),
+    // coordinates are in screen pixels
+    PointerDown { x: u32, y: u32 },
+    PointerMove { x: u32, y: u32 },
+    PointerUp { x: u32, y: u32 },
+    // composed character from host text input, like hangul from IME
+    Char(char),
+    // ids and handles are the ones the subsystem gave to the app when it was started
+    TimerExpired { id: u32 },
+    AudioComplete { handle: u32 },
+    NetworkReady { handle: u32 },
+    // app is sent to background, like on incoming call, and back
+    Suspend,
+    Resume,
 }
 
 #[derive(Default)]
@@ -73,4 +118,16 @@ impl EventQueue {
     pub fn pop(&mut self) -> Option<Event> {
         self.events.pop_front()
     }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    pub(crate) fn events(&self) -> impl Iterator<Item = &Event> {
+        self.events.iter()
+    }
+
+    pub(crate) fn replace(&mut self, events: impl IntoIterator<Item = Event>) {
+        self.events = events.into_iter().collect();
+    }
 }
@@ -0,0 +1,390 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+    string::String,
+    vec::Vec,
+};
+use core::cell::RefCell;
+
+use crate::platform::Platform;
+
+use super::resource::Resource;
+
+pub type FileHandle = u32;
+
+// largest a file can grow, so a guest can't make us allocate whatever offset it seeks to
+const MAX_FILE_SIZE: usize = 0x400000;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum FileError {
+    NotFound,
+    ReadOnly,
+    InvalidHandle,
+    InvalidOffset,
+    Io,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct OpenOptions {
+    pub write: bool,
+    /// Creates the file if it doesn't exist, requires `write`
+    pub create: bool,
+    pub truncate: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum SeekOrigin {
+    Start,
+    Current,
+    End,
+}
+
+struct OpenFile {
+    path: String,
+    data: Vec<u8>,
+    position: usize,
+    writable: bool,
+    // written since it was last stored on the host
+    dirty: bool,
+}
+
+// files the app sees: the writable area on the host, falling back to the read-only resources of the archive.
+// files are read whole on open, and stored back on close and by `flush`, which runs every tick so nothing is lost if the app never closes them
+pub struct VirtualFilesystem {
+    platform: Rc<RefCell<Box<dyn Platform>>>,
+    resource: Rc<RefCell<Resource>>,
+    files: BTreeMap<FileHandle, OpenFile>,
+    next_handle: FileHandle,
+}
+
+impl VirtualFilesystem {
+    pub fn new(platform: Rc<RefCell<Box<dyn Platform>>>, resource: Rc<RefCell<Resource>>) -> Self {
+        Self {
+            platform,
+            resource,
+            files: BTreeMap::new(),
+            next_handle: 1,
+        }
+    }
+
+    pub fn open(&mut self, path: &str, options: OpenOptions) -> Result<FileHandle, FileError> {
+        let path = Self::normalize(path);
+        tracing::trace!("Opening file {}", path);
+
+        let (data, changed) = match self.read_whole(&path) {
+            Some(_) if options.truncate && options.write => (Vec::new(), true),
+            Some(x) => (x, false),
+            None if options.create && options.write => (Vec::new(), true),
+            None => return Err(FileError::NotFound),
+        };
+
+        // created or truncated files are there right away, like on the handset
+        if changed && !self.platform.borrow().file_storage().write(&path, &data) {
+            return Err(FileError::Io);
+        }
+
+        let handle = self.next_handle;
+        self.next_handle += 1;
+
+        self.files.insert(
+            handle,
+            OpenFile {
+                path,
+                data,
+                position: 0,
+                writable: options.write,
+                dirty: false,
+            },
+        );
+
+        Ok(handle)
+    }
+
+    pub fn close(&mut self, handle: FileHandle) -> Result<(), FileError> {
+        let mut file = self.files.remove(&handle).ok_or(FileError::InvalidHandle)?;
+
+        Self::store(&self.platform, &mut file)
+    }
+
+    // stores every file written since, failures are only logged as there's no call of the app to report them to
+    pub fn flush(&mut self) {
+        for file in self.files.values_mut() {
+            if Self::store(&self.platform, file).is_err() {
+                tracing::error!("Failed to store file {}", file.path);
+            }
+        }
+    }
+
+    pub fn read(&mut self, handle: FileHandle, length: usize) -> Result<Vec<u8>, FileError> {
+        let file = self.files.get_mut(&handle).ok_or(FileError::InvalidHandle)?;
+
+        let start = file.position.min(file.data.len());
+        let end = (start + length).min(file.data.len());
+        file.position = end;
+
+        Ok(file.data[start..end].to_vec())
+    }
+
+    // writing past the end fills the gap with zeros
+    pub fn write(&mut self, handle: FileHandle, data: &[u8]) -> Result<usize, FileError> {
+        let file = self.files.get_mut(&handle).ok_or(FileError::InvalidHandle)?;
+        if !file.writable {
+            return Err(FileError::ReadOnly);
+        }
+
+        let end = file.position + data.len();
+        if end > MAX_FILE_SIZE {
+            return Err(FileError::InvalidOffset);
+        }
+        if file.data.len() < end {
+            file.data.resize(end, 0);
+        }
+        file.data[file.position..end].copy_from_slice(data);
+        file.position = end;
+        file.dirty = true;
+
+        Ok(data.len())
+    }
+
+    pub fn seek(&mut self, handle: FileHandle, offset: i64, origin: SeekOrigin) -> Result<usize, FileError> {
+        let file = self.files.get_mut(&handle).ok_or(FileError::InvalidHandle)?;
+
+        let base = match origin {
+            SeekOrigin::Start => 0,
+            SeekOrigin::Current => file.position,
+            SeekOrigin::End => file.data.len(),
+        };
+        let position = match (base as i64).checked_add(offset) {
+            Some(x) if (0..=MAX_FILE_SIZE as i64).contains(&x) => x,
+            _ => return Err(FileError::InvalidOffset),
+        };
+        file.position = position as _;
+
+        Ok(file.position)
+    }
+
+    pub fn tell(&self, handle: FileHandle) -> Result<usize, FileError> {
+        Ok(self.files.get(&handle).ok_or(FileError::InvalidHandle)?.position)
+    }
+
+    pub fn size(&self, handle: FileHandle) -> Result<usize, FileError> {
+        Ok(self.files.get(&handle).ok_or(FileError::InvalidHandle)?.data.len())
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        let path = Self::normalize(path);
+
+        self.read_whole(&path).is_some() || self.list(&path).is_ok()
+    }
+
+    pub fn remove(&mut self, path: &str) -> Result<(), FileError> {
+        let path = Self::normalize(path);
+
+        if self.platform.borrow().file_storage().remove(&path) {
+            Ok(())
+        } else if self.resource_data(&path).is_some() {
+            Err(FileError::ReadOnly)
+        } else {
+            Err(FileError::NotFound)
+        }
+    }
+
+    pub fn create_dir(&mut self, path: &str) -> Result<(), FileError> {
+        let path = Self::normalize(path);
+
+        if self.platform.borrow().file_storage().create_dir(&path) {
+            Ok(())
+        } else {
+            Err(FileError::Io)
+        }
+    }
+
+    // entries of both areas merged, directories have a trailing `/`
+    pub fn list(&self, path: &str) -> Result<Vec<String>, FileError> {
+        let path = Self::normalize(path);
+        let prefix = if path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", path.trim_end_matches('/'))
+        };
+
+        let stored = self.platform.borrow().file_storage().list(&path);
+        let found = stored.is_some();
+        let mut entries = stored.unwrap_or_default().into_iter().collect::<BTreeSet<_>>();
+
+        let resource = self.resource.borrow();
+        let mut found_resource = false;
+        for file in resource.files() {
+            let rest = match file.strip_prefix(&prefix) {
+                Some(x) if !x.is_empty() => x,
+                _ => continue,
+            };
+            found_resource = true;
+
+            match rest.find('/') {
+                Some(x) => entries.insert(rest[..=x].into()),
+                None => entries.insert(rest.into()),
+            };
+        }
+
+        if !found && !found_resource {
+            return Err(FileError::NotFound);
+        }
+
+        Ok(entries.into_iter().collect())
+    }
+
+    fn store(platform: &RefCell<Box<dyn Platform>>, file: &mut OpenFile) -> Result<(), FileError> {
+        if !file.dirty {
+            return Ok(());
+        }
+        file.dirty = false;
+
+        if platform.borrow().file_storage().write(&file.path, &file.data) {
+            Ok(())
+        } else {
+            Err(FileError::Io)
+        }
+    }
+
+    fn read_whole(&self, path: &str) -> Option<Vec<u8>> {
+        self.platform.borrow().file_storage().read(path).or_else(|| self.resource_data(path))
+    }
+
+    fn resource_data(&self, path: &str) -> Option<Vec<u8>> {
+        let resource = self.resource.borrow();
+        let id = resource.files().position(|x| x == path)?;
+
+        Some(resource.data(id as _).to_vec())
+    }
+
+    fn normalize(path: &str) -> String {
+        path.trim_start_matches('/').into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, rc::Rc, string::ToString, vec};
+    use core::cell::RefCell;
+
+    use crate::{HeadlessPlatform, Platform};
+
+    use super::{FileError, OpenOptions, Resource, SeekOrigin, VirtualFilesystem, MAX_FILE_SIZE};
+
+    fn filesystem() -> (VirtualFilesystem, Rc<RefCell<Box<dyn Platform>>>) {
+        let platform: Rc<RefCell<Box<dyn Platform>>> = Rc::new(RefCell::new(Box::new(HeadlessPlatform::new(1, 1))));
+
+        let mut resource = Resource::new();
+        resource.add("data/level1.bin", vec![1, 2, 3]);
+        resource.add("title.png", vec![4]);
+
+        (VirtualFilesystem::new(platform.clone(), Rc::new(RefCell::new(resource))), platform)
+    }
+
+    fn write_options() -> OpenOptions {
+        OpenOptions {
+            write: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_overlay_fallback() -> Result<(), FileError> {
+        let (mut filesystem, platform) = filesystem();
+
+        let handle = filesystem.open("/data/level1.bin", OpenOptions::default())?;
+        assert_eq!(filesystem.read(handle, 10)?, [1, 2, 3]);
+        filesystem.close(handle)?;
+
+        // files on the host take precedence over the ones in the archive
+        platform.borrow().file_storage().write("data/level1.bin", &[9]);
+        let handle = filesystem.open("data/level1.bin", OpenOptions::default())?;
+        assert_eq!(filesystem.read(handle, 10)?, [9]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_only_resource() -> Result<(), FileError> {
+        let (mut filesystem, platform) = filesystem();
+
+        let handle = filesystem.open("title.png", OpenOptions::default())?;
+        assert_eq!(filesystem.write(handle, &[1]), Err(FileError::ReadOnly));
+        assert_eq!(filesystem.remove("title.png"), Err(FileError::ReadOnly));
+        assert_eq!(filesystem.remove("missing.png"), Err(FileError::NotFound));
+
+        // writing to it keeps a copy on the host
+        let handle = filesystem.open("title.png", write_options())?;
+        filesystem.seek(handle, 0, SeekOrigin::End)?;
+        filesystem.write(handle, &[5])?;
+        filesystem.close(handle)?;
+        assert_eq!(platform.borrow().file_storage().read("title.png"), Some(vec![4, 5]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncate_create() -> Result<(), FileError> {
+        let (mut filesystem, platform) = filesystem();
+
+        assert_eq!(filesystem.open("save.dat", write_options()).err(), Some(FileError::NotFound));
+
+        let create = OpenOptions {
+            create: true,
+            ..write_options()
+        };
+        let handle = filesystem.open("save.dat", create)?;
+        assert_eq!(platform.borrow().file_storage().read("save.dat"), Some(vec![]));
+
+        // stored on close or flush, not on every write
+        filesystem.write(handle, b"abc")?;
+        assert_eq!(platform.borrow().file_storage().read("save.dat"), Some(vec![]));
+        filesystem.flush();
+        assert_eq!(platform.borrow().file_storage().read("save.dat"), Some(b"abc".to_vec()));
+        filesystem.close(handle)?;
+
+        let truncate = OpenOptions {
+            truncate: true,
+            ..write_options()
+        };
+        let handle = filesystem.open("save.dat", truncate)?;
+        assert_eq!(filesystem.size(handle)?, 0);
+        assert_eq!(platform.borrow().file_storage().read("save.dat"), Some(vec![]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_merge() -> Result<(), FileError> {
+        let (filesystem, platform) = filesystem();
+
+        platform.borrow().file_storage().write("data/save.bin", &[1]);
+
+        assert_eq!(filesystem.list("/data")?, ["level1.bin".to_string(), "save.bin".to_string()]);
+        assert_eq!(filesystem.list("/")?, ["data/".to_string(), "title.png".to_string()]);
+        assert_eq!(filesystem.list("missing"), Err(FileError::NotFound));
+        assert!(filesystem.exists("data"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_seek_limit() -> Result<(), FileError> {
+        let (mut filesystem, _) = filesystem();
+
+        let handle = filesystem.open("title.png", write_options())?;
+        assert_eq!(filesystem.seek(handle, -1, SeekOrigin::Start), Err(FileError::InvalidOffset));
+        assert_eq!(filesystem.seek(handle, i64::MAX, SeekOrigin::End), Err(FileError::InvalidOffset));
+        assert_eq!(
+            filesystem.seek(handle, MAX_FILE_SIZE as i64 + 1, SeekOrigin::Start),
+            Err(FileError::InvalidOffset)
+        );
+
+        filesystem.seek(handle, MAX_FILE_SIZE as i64 - 1, SeekOrigin::Start)?;
+        assert_eq!(filesystem.write(handle, &[1, 2]), Err(FileError::InvalidOffset));
+        assert_eq!(filesystem.size(handle)?, 1);
+
+        Ok(())
+    }
+}
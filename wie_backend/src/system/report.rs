@@ -0,0 +1,74 @@
+use alloc::{collections::BTreeMap, rc::Rc, string::String, vec::Vec};
+use core::{
+    cell::RefCell,
+    fmt::{self, Display},
+};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum ImportKind {
+    Interface,
+    Class,
+    Function,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum ImportStatus {
+    Implemented,
+    Stub,
+    Missing,
+}
+
+// Collects what a title asked from the platform while running, to tell which parts of it we can't handle yet
+#[derive(Clone, Default)]
+pub struct CompatibilityReport {
+    imports: Rc<RefCell<BTreeMap<(ImportKind, String), ImportStatus>>>,
+}
+
+impl CompatibilityReport {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn record(&self, kind: ImportKind, name: &str, status: ImportStatus) {
+        let mut imports = self.imports.borrow_mut();
+
+        // worse status wins, a function once seen as stub should stay so even if other calls succeeded
+        let entry = imports.entry((kind, name.into())).or_insert(status);
+        if status > *entry {
+            *entry = status;
+        }
+    }
+
+    pub fn count(&self, status: ImportStatus) -> usize {
+        self.imports.borrow().values().filter(|&&x| x == status).count()
+    }
+
+    pub fn names(&self, kind: ImportKind) -> Vec<(String, ImportStatus)> {
+        self.imports
+            .borrow()
+            .iter()
+            .filter(|((x, _), _)| *x == kind)
+            .map(|((_, name), status)| (name.clone(), *status))
+            .collect()
+    }
+}
+
+impl Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let imports = self.imports.borrow();
+
+        writeln!(
+            f,
+            "Compatibility report: {} implemented, {} stub, {} missing",
+            self.count(ImportStatus::Implemented),
+            self.count(ImportStatus::Stub),
+            self.count(ImportStatus::Missing)
+        )?;
+
+        for ((kind, name), status) in imports.iter() {
+            writeln!(f, "{:?}\t{:?}\t{}", status, kind, name)?;
+        }
+
+        Ok(())
+    }
+}
@@ -26,12 +26,9 @@ impl Resource {
     pub fn id(&self, path: &str) -> Option<u32> {
         tracing::trace!("Looking for resource {}", path);
 
-        if let Some(x) = path.strip_prefix('/') {
-            return self.id(x);
-        }
-
+        let normalized = Self::normalize(path);
         for (id, file) in self.files.iter().enumerate() {
-            if file.0 == path {
+            if file.0 == normalized {
                 return Some(id as _);
             }
         }
@@ -53,6 +50,22 @@ impl Resource {
         self.files.iter().map(|file| file.0.as_ref())
     }
 
+    // paths in the archive are relative without `.` or `..`, while apps pass them absolute or built by concatenation
+    fn normalize(path: &str) -> String {
+        let mut components = Vec::new();
+        for component in path.split('/') {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    components.pop();
+                }
+                x => components.push(x),
+            }
+        }
+
+        components.join("/")
+    }
+
     pub fn mount_zip(&mut self, zip: &[u8]) -> anyhow::Result<()> {
         let files = extract_zip(zip)?;
 
@@ -63,3 +76,23 @@ impl Resource {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::Resource;
+
+    #[test]
+    fn test_resource_path() {
+        let mut resource = Resource::new();
+        resource.add("img/title.png", vec![1]);
+        resource.add("data.bin", vec![2]);
+
+        assert_eq!(resource.id("img/title.png"), Some(0));
+        assert_eq!(resource.id("/img/title.png"), Some(0));
+        assert_eq!(resource.id("//img/./title.png"), Some(0));
+        assert_eq!(resource.id("/img/../data.bin"), Some(1));
+        assert_eq!(resource.id("title.png"), None);
+    }
+}
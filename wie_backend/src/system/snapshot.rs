@@ -0,0 +1,216 @@
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+use crate::{database::RecordId, time::Instant};
+
+use super::event_queue::{Event, KeyCode};
+
+// changed whenever the layout changes
+const SNAPSHOT_MAGIC: &[u8; 4] = b"WSS1";
+
+/// What the app sees of the system besides its own memory: the time, events not handled yet and the databases
+pub struct SystemSnapshot {
+    pub(super) now: Instant,
+    pub(super) events: Vec<Event>,
+    pub(super) databases: BTreeMap<String, BTreeMap<RecordId, Vec<u8>>>,
+}
+
+impl SystemSnapshot {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = SNAPSHOT_MAGIC.to_vec();
+        result.extend_from_slice(&self.now.raw().to_le_bytes());
+
+        result.extend_from_slice(&(self.events.len() as u32).to_le_bytes());
+        for event in &self.events {
+            Self::write_event(&mut result, event);
+        }
+
+        result.extend_from_slice(&(self.databases.len() as u32).to_le_bytes());
+        for (name, records) in &self.databases {
+            Self::write_bytes(&mut result, name.as_bytes());
+            result.extend_from_slice(&(records.len() as u32).to_le_bytes());
+            for (id, data) in records {
+                result.extend_from_slice(&id.to_le_bytes());
+                Self::write_bytes(&mut result, data);
+            }
+        }
+
+        result
+    }
+
+    pub fn from_bytes(data: &[u8]) -> anyhow::Result<Self> {
+        let mut reader = Reader { rest: data };
+
+        if reader.take(4)? != SNAPSHOT_MAGIC {
+            anyhow::bail!("Invalid system snapshot");
+        }
+        let now = Instant::from_epoch_millis(reader.u64()?);
+
+        let events = (0..reader.u32()?)
+            .map(|_| Self::read_event(&mut reader))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut databases = BTreeMap::new();
+        for _ in 0..reader.u32()? {
+            let name = String::from_utf8(reader.bytes()?.to_vec())?;
+            let records = (0..reader.u32()?)
+                .map(|_| Ok((reader.u32()?, reader.bytes()?.to_vec())))
+                .collect::<anyhow::Result<BTreeMap<_, _>>>()?;
+
+            databases.insert(name, records);
+        }
+
+        Ok(Self { now, events, databases })
+    }
+
+    fn write_bytes(result: &mut Vec<u8>, data: &[u8]) {
+        result.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        result.extend_from_slice(data);
+    }
+
+    // a tag followed by its fields, keys by their names so the layout doesn't follow the order of the enum
+    fn write_event(result: &mut Vec<u8>, event: &Event) {
+        let tag = match event {
+            Event::Redraw => 0,
+            Event::Keydown(_) => 1,
+            Event::Keyup(_) => 2,
+            Event::PointerDown { .. } => 3,
+            Event::PointerMove { .. } => 4,
+            Event::PointerUp { .. } => 5,
+            Event::Char(_) => 6,
+            Event::TimerExpired { .. } => 7,
+            Event::AudioComplete { .. } => 8,
+            Event::NetworkReady { .. } => 9,
+            Event::Suspend => 10,
+            Event::Resume => 11,
+        };
+        result.push(tag);
+
+        match event {
+            Event::Keydown(x) | Event::Keyup(x) => Self::write_bytes(result, x.name().as_bytes()),
+            Event::PointerDown { x, y } | Event::PointerMove { x, y } | Event::PointerUp { x, y } => {
+                result.extend_from_slice(&x.to_le_bytes());
+                result.extend_from_slice(&y.to_le_bytes());
+            }
+            Event::Char(x) => result.extend_from_slice(&(*x as u32).to_le_bytes()),
+            Event::TimerExpired { id: x } | Event::AudioComplete { handle: x } | Event::NetworkReady { handle: x } => {
+                result.extend_from_slice(&x.to_le_bytes())
+            }
+            Event::Redraw | Event::Suspend | Event::Resume => {}
+        }
+    }
+
+    fn read_event(reader: &mut Reader) -> anyhow::Result<Event> {
+        let tag = reader.take(1)?[0];
+
+        Ok(match tag {
+            0 => Event::Redraw,
+            1 => Event::Keydown(Self::read_key(reader)?),
+            2 => Event::Keyup(Self::read_key(reader)?),
+            3 => Event::PointerDown {
+                x: reader.u32()?,
+                y: reader.u32()?,
+            },
+            4 => Event::PointerMove {
+                x: reader.u32()?,
+                y: reader.u32()?,
+            },
+            5 => Event::PointerUp {
+                x: reader.u32()?,
+                y: reader.u32()?,
+            },
+            6 => Event::Char(char::from_u32(reader.u32()?).ok_or_else(|| anyhow::anyhow!("Invalid character"))?),
+            7 => Event::TimerExpired { id: reader.u32()? },
+            8 => Event::AudioComplete { handle: reader.u32()? },
+            9 => Event::NetworkReady { handle: reader.u32()? },
+            10 => Event::Suspend,
+            11 => Event::Resume,
+            x => anyhow::bail!("Unknown event {}", x),
+        })
+    }
+
+    fn read_key(reader: &mut Reader) -> anyhow::Result<KeyCode> {
+        let name = core::str::from_utf8(reader.bytes()?)?;
+
+        KeyCode::parse(name).ok_or_else(|| anyhow::anyhow!("Unknown key {}", name))
+    }
+}
+
+struct Reader<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> anyhow::Result<&'a [u8]> {
+        let (value, rest) = self
+            .rest
+            .split_at_checked(length)
+            .ok_or_else(|| anyhow::anyhow!("System snapshot is truncated"))?;
+        self.rest = rest;
+
+        Ok(value)
+    }
+
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> anyhow::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self) -> anyhow::Result<&'a [u8]> {
+        let length = self.u32()?;
+
+        self.take(length as _)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+
+    use crate::{Event, HeadlessPlatform, Instant, KeyCode, System};
+
+    use super::SystemSnapshot;
+
+    #[test]
+    fn test_system_snapshot() -> anyhow::Result<()> {
+        let platform = HeadlessPlatform::new(1, 1);
+        let handle = platform.handle();
+        let mut system = System::new(Box::new(platform), Box::new(()));
+
+        let first = system.platform().database_repository().open("scores").add(b"100");
+        system.event_queue().push(Event::Keydown(KeyCode::OK));
+        system.event_queue().push(Event::PointerDown { x: 1, y: 2 });
+        handle.advance(100);
+
+        let snapshot = system.snapshot().to_bytes();
+
+        system.platform().database_repository().open("scores").add(b"200");
+        system.platform().database_repository().open("other").add(b"300");
+        system.event_queue().push(Event::Suspend);
+        handle.advance(50);
+        system.timers().set(0x100, Instant::from_epoch_millis(200));
+
+        system.restore_snapshot(&SystemSnapshot::from_bytes(&snapshot)?)?;
+
+        // time goes on from the snapshot, and the timer still has 50ms left
+        assert_eq!(system.now(), Instant::from_epoch_millis(100));
+        assert_eq!(system.timers().expiry(0x100), Some(Instant::from_epoch_millis(150)));
+        handle.advance(10);
+        assert_eq!(system.now(), Instant::from_epoch_millis(110));
+
+        assert!(matches!(system.event_queue().pop(), Some(Event::Keydown(KeyCode::OK))));
+        assert!(matches!(system.event_queue().pop(), Some(Event::PointerDown { x: 1, y: 2 })));
+        assert!(system.event_queue().pop().is_none());
+
+        let scores = system.platform().database_repository().open("scores");
+        assert_eq!(scores.get_record_ids(), [first]);
+        assert_eq!(scores.get(first), Some(b"100".to_vec()));
+        assert!(system.platform().database_repository().open("other").get_record_ids().is_empty());
+
+        assert!(SystemSnapshot::from_bytes(&snapshot[..snapshot.len() - 1]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+// Snapshot of runtime state, for frontends to display while debugging
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Telemetry {
+    pub task_count: usize,
+    pub audio_voice_count: usize,
+    /// Guest heap, if the runtime manages one
+    pub heap_usage: Option<HeapUsage>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HeapUsage {
+    /// Bytes in use, including allocation headers
+    pub used: u32,
+    pub free: u32,
+    /// Largest allocation that would succeed now, lower than `free` when the heap is fragmented
+    pub largest_free: u32,
+}
@@ -0,0 +1,136 @@
+use alloc::collections::BTreeMap;
+
+use crate::{Instant, TaskHandle};
+
+/// One arming of a timer, which goes stale once the timer is set again or unset
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TimerToken(u64);
+
+struct Arming {
+    token: TimerToken,
+    expiry: Instant,
+    // task waiting to run the callback, cancelled when the arming goes stale
+    task: Option<TaskHandle>,
+}
+
+impl Arming {
+    fn cancel(self) {
+        if let Some(x) = self.task {
+            x.cancel();
+        }
+    }
+}
+
+// one-shot timers keyed by whatever the guest identifies them with, usually the address of its timer structure.
+// setting a timer again replaces its pending expiry instead of adding another one
+#[derive(Default)]
+pub struct Timers {
+    armed: BTreeMap<u32, Arming>,
+    next_token: u64,
+}
+
+impl Timers {
+    pub fn set(&mut self, key: u32, expiry: Instant) -> TimerToken {
+        let token = TimerToken(self.next_token);
+        self.next_token += 1;
+
+        let previous = self.armed.insert(key, Arming { token, expiry, task: None });
+        if let Some(x) = previous {
+            x.cancel();
+        }
+
+        token
+    }
+
+    /// Returns whether the timer was pending
+    pub fn unset(&mut self, key: u32) -> bool {
+        match self.armed.remove(&key) {
+            Some(x) => {
+                x.cancel();
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Records the task waiting for `token` to expire, so it is cancelled if the arming goes stale. Returns whether `token` is still current
+    pub fn attach(&mut self, key: u32, token: TimerToken, task: TaskHandle) -> bool {
+        match self.armed.get_mut(&key) {
+            Some(x) if x.token == token => {
+                x.task = Some(task);
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Disarms the timer if `token` is still its current arming, returning whether its callback should run
+    pub fn take(&mut self, key: u32, token: TimerToken) -> bool {
+        match self.armed.get(&key) {
+            Some(x) if x.token == token => {
+                self.armed.remove(&key);
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn expiry(&self, key: u32) -> Option<Instant> {
+        self.armed.get(&key).map(|x| x.expiry)
+    }
+
+    pub(crate) fn shift(&mut self, millis: i64) {
+        for arming in self.armed.values_mut() {
+            arming.expiry = arming.expiry.offset(millis);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{executor::Executor, task::YieldFuture, Instant};
+
+    use super::Timers;
+
+    #[test]
+    fn test_timers() {
+        let mut timers = Timers::default();
+
+        let first = timers.set(0x100, Instant::from_epoch_millis(50));
+        timers.set(0x200, Instant::from_epoch_millis(30));
+        assert_eq!(timers.expiry(0x100), Some(Instant::from_epoch_millis(50)));
+
+        // setting again makes the earlier arming stale
+        let second = timers.set(0x100, Instant::from_epoch_millis(80));
+        assert!(!timers.take(0x100, first));
+        assert_eq!(timers.expiry(0x100), Some(Instant::from_epoch_millis(80)));
+        assert!(timers.take(0x100, second));
+        assert!(!timers.take(0x100, second));
+
+        assert!(timers.unset(0x200));
+        assert!(!timers.unset(0x200));
+        assert_eq!(timers.expiry(0x200), None);
+    }
+
+    #[test]
+    fn test_timers_cancel() {
+        let mut executor = Executor::new();
+        let mut timers = Timers::default();
+
+        let first = timers.set(0x100, Instant::from_epoch_millis(50));
+        let task = executor.spawn(move || async move {
+            YieldFuture::new().await;
+
+            anyhow::Ok(())
+        });
+        assert!(timers.attach(0x100, first, task.clone()));
+
+        // the waiting task goes away with its arming
+        timers.set(0x100, Instant::from_epoch_millis(80));
+        assert_eq!(executor.task_count(), 0);
+        assert!(!timers.attach(0x100, first, task));
+    }
+}
@@ -6,16 +6,39 @@ use core::{
 
 use crate::{executor::Executor, time::Instant};
 
-pub struct YieldFuture {}
+// lets other tasks run before continuing
+pub struct YieldFuture {
+    yielded: bool,
+}
+
+impl YieldFuture {
+    pub fn new() -> Self {
+        Self { yielded: false }
+    }
+}
+
+impl Default for YieldFuture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Future for YieldFuture {
     type Output = ();
 
-    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
-        Poll::Ready(())
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
+        if !self.yielded {
+            self.yielded = true;
+
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
     }
 }
 
+impl Unpin for YieldFuture {}
+
 pub struct SleepFuture {
     polled: bool,
 }
@@ -1,6 +1,6 @@
 use core::ops::{Add, Sub};
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Instant {
     value: u64,
 }
@@ -13,6 +13,13 @@ impl Instant {
     pub fn raw(&self) -> u64 {
         self.value
     }
+
+    /// Moved by `millis` in either direction, stopping at the epoch
+    pub fn offset(&self, millis: i64) -> Self {
+        Self {
+            value: self.value.saturating_add_signed(millis),
+        }
+    }
 }
 
 impl Add<u64> for Instant {
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use wie_backend::{extract_zip, Archive};
+use wie_j2me::J2MEArchive;
+use wie_ktf::KtfArchive;
+use wie_lgt::LgtArchive;
+use wie_skt::SktArchive;
+
+use crate::descriptor::AppDescriptor;
+
+enum AppletData {
+    Archive(BTreeMap<String, Vec<u8>>),
+    Jar(Vec<u8>),
+}
+
+pub struct Applet {
+    pub name: String,
+    data: AppletData,
+}
+
+impl Applet {
+    pub fn from_data(name: String, data: Vec<u8>) -> anyhow::Result<Self> {
+        let files = extract_zip(&data)?;
+
+        let data = if is_archive(&files) {
+            AppletData::Archive(files)
+        } else {
+            AppletData::Jar(data)
+        };
+
+        Ok(Self { name, data })
+    }
+
+    pub fn into_archive(self) -> anyhow::Result<Box<dyn Archive>> {
+        Ok(match self.data {
+            AppletData::Archive(files) => {
+                if KtfArchive::is_ktf_archive(&files) {
+                    Box::new(KtfArchive::from_zip(files)?)
+                } else if LgtArchive::is_lgt_archive(&files) {
+                    Box::new(LgtArchive::from_zip(files)?)
+                } else {
+                    Box::new(SktArchive::from_zip(files)?)
+                }
+            }
+            AppletData::Jar(jar) => {
+                let main_class_name = AppDescriptor::from_jar(&jar).main_class_name;
+
+                if KtfArchive::is_ktf_jar(&jar) {
+                    Box::new(KtfArchive::from_jar(jar, self.name, main_class_name, Default::default()))
+                } else if LgtArchive::is_lgt_jar(&jar) {
+                    Box::new(LgtArchive::from_jar(jar, &self.name, main_class_name))
+                } else if SktArchive::is_skt_jar(&jar) {
+                    Box::new(SktArchive::from_jar(jar, &self.name, main_class_name, Default::default()))
+                } else {
+                    Box::new(J2MEArchive::from_jar(self.name, jar))
+                }
+            }
+        })
+    }
+}
+
+// an archive may bundle several applets (e.g. a game and its settings tool), each in its own directory
+pub fn find_applets(files: BTreeMap<String, Vec<u8>>) -> Vec<Applet> {
+    if is_archive(&files) {
+        return vec![Applet {
+            name: String::new(),
+            data: AppletData::Archive(files),
+        }];
+    }
+
+    let mut directories = BTreeMap::<String, BTreeMap<String, Vec<u8>>>::new();
+    let mut jars = Vec::new();
+
+    for (path, data) in files {
+        if let Some((directory, name)) = path.split_once('/') {
+            directories.entry(directory.into()).or_default().insert(name.into(), data);
+        } else if let Some(name) = path.strip_suffix(".jar") {
+            jars.push(Applet {
+                name: name.into(),
+                data: AppletData::Jar(data),
+            });
+        }
+    }
+
+    directories
+        .into_iter()
+        .filter(|(_, files)| is_archive(files))
+        .map(|(name, files)| Applet {
+            name,
+            data: AppletData::Archive(files),
+        })
+        .chain(jars)
+        .collect()
+}
+
+pub fn is_archive(files: &BTreeMap<String, Vec<u8>>) -> bool {
+    KtfArchive::is_ktf_archive(files) || LgtArchive::is_lgt_archive(files) || SktArchive::is_skt_archive(files)
+}
@@ -1,13 +1,142 @@
-use rodio::{buffer::SamplesBuffer, OutputStream, Sink};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-pub struct AudioSink;
+use rodio::{
+    buffer::SamplesBuffer,
+    cpal::{
+        self,
+        traits::{DeviceTrait, HostTrait},
+    },
+    OutputStream, OutputStreamHandle, Sink, Source,
+};
+
+use crate::{clock::Clock, config::AudioConfig, recorder::Recorder};
+
+// opened once and shared by every sink. audio is disabled if the device can't be opened
+#[derive(Clone)]
+pub struct AudioOutput {
+    stream: Option<Rc<(OutputStream, OutputStreamHandle)>>,
+}
+
+impl AudioOutput {
+    pub fn open(config: &AudioConfig) -> Self {
+        let stream = Self::open_stream(config)
+            .map_err(|x| tracing::error!("Failed to open audio output, audio is disabled: {}", x))
+            .ok();
+
+        Self { stream: stream.map(Rc::new) }
+    }
+
+    pub fn device_names() -> anyhow::Result<Vec<String>> {
+        Ok(cpal::default_host().output_devices()?.filter_map(|x| x.name().ok()).collect())
+    }
+
+    fn open_stream(config: &AudioConfig) -> anyhow::Result<(OutputStream, OutputStreamHandle)> {
+        let host = cpal::default_host();
+        let device = match &config.device {
+            Some(name) => host
+                .output_devices()?
+                .find(|x| x.name().ok().as_ref() == Some(name))
+                .ok_or_else(|| anyhow::anyhow!("No audio device named {}", name))?,
+            None => host.default_output_device().ok_or_else(|| anyhow::anyhow!("No default audio device"))?,
+        };
+
+        let stream = match config.sample_rate {
+            Some(rate) => {
+                let supported = device
+                    .supported_output_configs()?
+                    .find(|x| (x.min_sample_rate().0..=x.max_sample_rate().0).contains(&rate))
+                    .ok_or_else(|| anyhow::anyhow!("Sample rate {} is not supported by the device", rate))?
+                    .with_sample_rate(cpal::SampleRate(rate));
+
+                OutputStream::try_from_device_config(&device, supported)?
+            }
+            None => OutputStream::try_from_device(&device)?,
+        };
+
+        Ok(stream)
+    }
+}
+
+pub struct AudioSink {
+    output: AudioOutput,
+    recorder: Recorder,
+    clock: Clock,
+    volume: f32,
+    clips: RefCell<HashMap<u32, Sink>>,
+}
+
+impl AudioSink {
+    pub fn new(output: AudioOutput, recorder: Recorder, clock: Clock, volume: f32) -> Self {
+        Self {
+            output,
+            recorder,
+            clock,
+            volume,
+            clips: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn new_sink(&self) -> Option<Sink> {
+        // muted while fast forwarding, sped up audio isn't worth listening to
+        if self.clock.speed() > 1 {
+            return None;
+        }
+
+        let stream_handle = &self.output.stream.as_ref()?.1;
+
+        match Sink::try_new(stream_handle) {
+            Ok(x) => {
+                x.set_volume(self.volume);
+
+                Some(x)
+            }
+            Err(x) => {
+                tracing::error!("Failed to create audio sink: {}", x);
+
+                None
+            }
+        }
+    }
+}
 
 impl wie_backend::AudioSink for AudioSink {
     fn play_wave(&self, channel: u8, sampling_rate: u32, wave_data: &[i16]) {
+        self.recorder.push_audio(channel, sampling_rate, wave_data);
+
+        let sink = match self.new_sink() {
+            Some(x) => x,
+            None => return,
+        };
+
+        sink.append(SamplesBuffer::new(channel as _, sampling_rate as _, wave_data));
+        // keeps playing after the sink is dropped
+        sink.detach();
+    }
+
+    // repeats aren't recorded, the recording gets the clip once
+    fn play_clip(&self, id: u32, channel: u8, sampling_rate: u32, wave_data: &[i16], repeat: bool) {
+        self.stop_clip(id);
+        self.recorder.push_audio(channel, sampling_rate, wave_data);
+
+        let sink = match self.new_sink() {
+            Some(x) => x,
+            None => return,
+        };
+
         let buffer = SamplesBuffer::new(channel as _, sampling_rate as _, wave_data);
+        if repeat {
+            sink.append(buffer.repeat_infinite());
+        } else {
+            sink.append(buffer);
+        }
+
+        // dropping the sink stops it, so it's kept until the clip is stopped or replaced
+        self.clips.borrow_mut().insert(id, sink);
+    }
 
-        let (_output_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let sink = Sink::try_new(&stream_handle).unwrap();
-        sink.append(buffer);
+    fn stop_clip(&self, id: u32) {
+        if let Some(sink) = self.clips.borrow_mut().remove(&id) {
+            sink.stop();
+        }
     }
 }
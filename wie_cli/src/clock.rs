@@ -0,0 +1,94 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const TICK_MILLIS: u64 = 16;
+
+#[derive(Clone, Copy)]
+enum ClockKind {
+    Real,
+    // only advances when the frontend ticks, so runs driven by it are reproducible regardless of host speed
+    Virtual,
+}
+
+#[derive(Clone)]
+pub struct Clock {
+    kind: ClockKind,
+    // current time on virtual clock, or offset from system time on real clock, which is time skipped by fast forward minus time spent paused
+    elapsed: Rc<Cell<i64>>,
+    speed: Rc<Cell<u64>>,
+    // frozen time while paused
+    paused_at: Rc<Cell<Option<u64>>>,
+}
+
+impl Clock {
+    pub fn new_real() -> Self {
+        Self::new(ClockKind::Real)
+    }
+
+    pub fn new_virtual() -> Self {
+        Self::new(ClockKind::Virtual)
+    }
+
+    fn new(kind: ClockKind) -> Self {
+        Self {
+            kind,
+            elapsed: Rc::new(Cell::new(0)),
+            speed: Rc::new(Cell::new(1)),
+            paused_at: Rc::new(Cell::new(None)),
+        }
+    }
+
+    pub fn now(&self) -> u64 {
+        self.paused_at.get().unwrap_or_else(|| self.running_now())
+    }
+
+    fn running_now(&self) -> u64 {
+        match self.kind {
+            ClockKind::Real => (SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64 + self.elapsed.get()) as u64,
+            ClockKind::Virtual => self.elapsed.get() as u64,
+        }
+    }
+
+    // ticking while paused advances the frozen time by a tick, for frame advance
+    pub fn tick(&self) {
+        if let Some(x) = self.paused_at.get() {
+            self.paused_at.set(Some(x + TICK_MILLIS * self.speed.get()));
+
+            return;
+        }
+
+        let advance = match self.kind {
+            ClockKind::Real => TICK_MILLIS * (self.speed.get() - 1),
+            ClockKind::Virtual => TICK_MILLIS * self.speed.get(),
+        };
+
+        self.elapsed.set(self.elapsed.get() + advance as i64);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.get().is_some()
+    }
+
+    // resumes from the frozen time, so the app doesn't see the time spent paused
+    pub fn set_paused(&self, paused: bool) {
+        match (paused, self.paused_at.get()) {
+            (true, None) => self.paused_at.set(Some(self.running_now())),
+            (false, Some(x)) => {
+                self.elapsed.set(self.elapsed.get() + x as i64 - self.running_now() as i64);
+                self.paused_at.set(None);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn speed(&self) -> u64 {
+        self.speed.get()
+    }
+
+    pub fn set_speed(&self, speed: u64) {
+        self.speed.set(speed.max(1));
+    }
+}
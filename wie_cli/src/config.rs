@@ -0,0 +1,287 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use directories::ProjectDirs;
+use serde::Deserialize;
+
+use wie_backend::App;
+
+use crate::{descriptor::AppDescriptor, filter::Filter};
+
+// settings from config.toml in the config directory, overridden per game by games/<file stem>.toml.
+// compatibility profile matching the archive, if any, is applied beneath them
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Key mapping file, see keymap.rs for the format
+    pub keymap: Option<PathBuf>,
+    /// Applied on top of the key mapping, in the same format as its `[keys]`
+    pub keys: HashMap<String, String>,
+    pub screen: ScreenConfig,
+    pub window: WindowConfig,
+    pub audio: AudioConfig,
+    pub runtime: RuntimeConfig,
+    pub paths: PathsConfig,
+    pub crash: CrashConfig,
+    // whether [screen] was given by a config or a profile, over the size the app asks for
+    #[serde(skip)]
+    screen_configured: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScreenConfig {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    /// Initial integer scale of the window
+    pub scale: u32,
+    /// Clockwise rotation of the screen in degrees, one of 0, 90, 180 and 270
+    pub rotation: u32,
+    /// One of nearest, sharp-bilinear, lcd-grid and scanlines
+    pub filter: Filter,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AudioConfig {
+    /// 0.0 to 1.0
+    pub volume: f32,
+    /// Name of the output device as listed by `audio-devices` command, instead of the default one
+    pub device: Option<String>,
+    /// Output sample rate in Hz, the device default is used if not set
+    pub sample_rate: Option<u32>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuntimeConfig {
+    /// Forces the platform of a jar, one of KTF, LGT, SKT and J2ME, instead of detecting it
+    pub platform: Option<String>,
+    /// Handset properties reported to the app, like the platform version it checks on start
+    pub system_properties: BTreeMap<String, String>,
+    /// Guest memory writes applied after the binary is loaded, to work around what we can't emulate yet
+    pub patches: Vec<PatchConfig>,
+    /// Guest instructions run before other tasks get a turn, for titles spinning in a loop without returning
+    pub instruction_budget: Option<u32>,
+    /// Recent execution to keep for the crash report and the `trace` debugger command
+    pub trace: TraceConfig,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct TraceConfig {
+    /// Code ranges to record every instruction of, like the loaded binary
+    pub regions: Vec<TraceRegionConfig>,
+    /// Record calls to native functions as well
+    pub functions: bool,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TraceRegionConfig {
+    pub start: u32,
+    /// Exclusive
+    pub end: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatchConfig {
+    pub address: u32,
+    /// Hex string, like `"00bf00bf"`
+    pub bytes: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathsConfig {
+    /// Where databases and savestates are stored
+    pub data_dir: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrashConfig {
+    /// Include all guest memory in crash reports, which makes them several megabytes
+    pub full_memory: bool,
+}
+
+impl Default for CrashConfig {
+    fn default() -> Self {
+        Self { full_memory: true }
+    }
+}
+
+impl Default for ScreenConfig {
+    fn default() -> Self {
+        Self { width: 240, height: 320 }
+    }
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            scale: 1,
+            rotation: 0,
+            filter: Filter::default(),
+        }
+    }
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            device: None,
+            sample_rate: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn load(filename: &str) -> anyhow::Result<Self> {
+        let mut value = Self::find_profile(filename)?.unwrap_or_default();
+
+        if let Some(base_dir) = Self::project_dirs() {
+            let config_dir = base_dir.config_dir();
+            let stem = Path::new(filename).file_stem().and_then(|x| x.to_str()).unwrap_or(filename);
+
+            for path in [config_dir.join("config.toml"), config_dir.join("games").join(format!("{}.toml", stem))] {
+                if !path.exists() {
+                    continue;
+                }
+                tracing::debug!("Loading config from {}", path.display());
+
+                let table = fs::read_to_string(&path)?
+                    .parse::<toml::Table>()
+                    .map_err(|x| anyhow::anyhow!("{}: {}", path.display(), x))?;
+                Self::merge(&mut value, table);
+            }
+        }
+
+        let screen_configured = value.contains_key("screen");
+        let mut config: Self = toml::Value::Table(value).try_into()?;
+        config.screen_configured = screen_configured;
+        anyhow::ensure!(config.window.scale > 0, "Window scale must be positive");
+        anyhow::ensure!(
+            [0, 90, 180, 270].contains(&config.window.rotation),
+            "Window rotation must be one of 0, 90, 180 and 270"
+        );
+
+        Ok(config)
+    }
+
+    // profiles are looked up in profiles directory next to the executable, and in the config directory.
+    // a profile is a config file with `crc32` of the archive file, and optionally `name` of the title
+    fn find_profile(filename: &str) -> anyhow::Result<Option<toml::Table>> {
+        let crc32 = format!("{:08x}", crc32fast::hash(&fs::read(filename)?));
+
+        let bundled_dir = env::current_exe().ok().and_then(|x| Some(x.parent()?.join("profiles")));
+        let user_dir = Self::project_dirs().map(|x| x.config_dir().join("profiles"));
+
+        for dir in bundled_dir.into_iter().chain(user_dir) {
+            let entries = match fs::read_dir(&dir) {
+                Ok(x) => x,
+                Err(_) => continue,
+            };
+
+            for path in entries.filter_map(|x| Some(x.ok()?.path())) {
+                if path.extension().and_then(|x| x.to_str()) != Some("toml") {
+                    continue;
+                }
+
+                let mut table = fs::read_to_string(&path)?
+                    .parse::<toml::Table>()
+                    .map_err(|x| anyhow::anyhow!("{}: {}", path.display(), x))?;
+
+                let matches = table.get("crc32").and_then(|x| x.as_str()).map(|x| x.eq_ignore_ascii_case(&crc32));
+                if matches == Some(true) {
+                    let name = table.remove("name");
+                    table.remove("crc32");
+
+                    tracing::info!("Using compatibility profile {}", name.as_ref().and_then(|x| x.as_str()).unwrap_or(&crc32));
+
+                    return Ok(Some(table));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn apply_descriptor(&mut self, descriptor: &AppDescriptor) {
+        if self.screen_configured {
+            return;
+        }
+
+        if let Some((width, height)) = descriptor.screen_size {
+            tracing::info!("Using screen size {}x{} of the descriptor", width, height);
+
+            self.screen = ScreenConfig { width, height };
+        }
+    }
+
+    // needs to be called before the app starts
+    pub fn apply_runtime(&self, app: &dyn App) -> anyhow::Result<()> {
+        let trace = &self.runtime.trace;
+        if self.runtime.patches.is_empty() && self.runtime.instruction_budget.is_none() && trace.regions.is_empty() && !trace.functions {
+            return Ok(());
+        }
+
+        let mut guest = app
+            .guest()
+            .ok_or_else(|| anyhow::anyhow!("Patches, instruction budget and tracing are not supported on this platform"))?;
+        if let Some(x) = self.runtime.instruction_budget {
+            guest.set_instruction_budget(x);
+        }
+
+        for region in &trace.regions {
+            guest.set_trace_region(region.start, region.end, true);
+        }
+        guest.set_trace_functions(trace.functions);
+
+        for patch in &self.runtime.patches {
+            let bytes = (0..patch.bytes.len())
+                .step_by(2)
+                .map(|x| u8::from_str_radix(patch.bytes.get(x..x + 2).unwrap_or("?"), 16))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| anyhow::anyhow!("Invalid patch bytes {}", patch.bytes))?;
+
+            guest.write_memory(patch.address, &bytes)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn data_dir(&self) -> PathBuf {
+        self.paths
+            .data_dir
+            .clone()
+            .or_else(|| Some(Self::project_dirs()?.data_dir().to_path_buf()))
+            .unwrap_or_else(|| PathBuf::from("wie_data"))
+    }
+
+    fn project_dirs() -> Option<ProjectDirs> {
+        ProjectDirs::from("net", "dlunch", "wie")
+    }
+
+    // tables are merged key by key, so a game config only needs the values it changes
+    fn merge(base: &mut toml::Table, overrides: toml::Table) {
+        for (key, value) in overrides {
+            match (base.get_mut(&key), value) {
+                (Some(toml::Value::Table(base)), toml::Value::Table(value)) => Self::merge(base, value),
+                (_, value) => {
+                    base.insert(key, value);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use wie_backend::{App, GuestAccess};
+
+use crate::{config::Config, screenshot};
+
+// instructions shown before and after pc
+const CODE_WINDOW: u32 = 16;
+const STACK_WORDS: u32 = 64;
+const CPSR_THUMB: u32 = 1 << 5;
+
+// panics in the emulator, like guest access to unmapped memory, are turned into errors to get a crash report with the trace as well
+pub fn tick(app: &mut dyn App) -> anyhow::Result<()> {
+    panic::catch_unwind(AssertUnwindSafe(|| app.tick())).unwrap_or_else(|x| {
+        let message = x
+            .downcast_ref::<&str>()
+            .map(|x| x.to_string())
+            .or_else(|| x.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "<Unknown>".into());
+
+        Err(anyhow::anyhow!("Panicked: {}", message))
+    })
+}
+
+// writes a single zip with everything we need for a bug report, when the app fails
+pub struct CrashReporter {
+    base_path: PathBuf,
+    full_memory: bool,
+}
+
+impl CrashReporter {
+    pub fn new(config: &Config, app_id: &str) -> Self {
+        let base_path = config.data_dir().join(app_id).join("crashes");
+
+        Self {
+            base_path,
+            full_memory: config.crash.full_memory,
+        }
+    }
+
+    // failing to write the bundle is only logged, as we're already handling an error
+    pub fn report(&self, app: &dyn App, error: &anyhow::Error, frame: &[u32], width: u32, height: u32) {
+        match self.write_bundle(app, error, frame, width, height) {
+            Ok(path) => eprintln!("Crash report is written to {}, please attach it to the bug report", path.display()),
+            Err(x) => tracing::error!("Failed to write crash report: {}", x),
+        }
+    }
+
+    fn write_bundle(&self, app: &dyn App, error: &anyhow::Error, frame: &[u32], width: u32, height: u32) -> anyhow::Result<PathBuf> {
+        fs::create_dir_all(&self.base_path)?;
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let path = self.base_path.join(format!("crash-{}.zip", timestamp));
+
+        let mut zip = ZipWriter::new(File::create(&path)?);
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        zip.start_file("error.txt", options)?;
+        write!(zip, "{:?}", error)?;
+
+        zip.start_file("report.txt", options)?;
+        write!(zip, "{}", app.report())?;
+
+        if let Some(guest) = app.guest() {
+            zip.start_file("registers.txt", options)?;
+            Self::write_registers(&mut zip, guest.as_ref())?;
+
+            zip.start_file("code.txt", options)?;
+            Self::write_code(&mut zip, guest.as_ref())?;
+
+            zip.start_file("stack.txt", options)?;
+            Self::write_stack(&mut zip, guest.as_ref())?;
+
+            zip.start_file("branches.txt", options)?;
+            for (from, to) in guest.branch_history() {
+                writeln!(zip, "{:#010x} -> {:#010x}", from, to)?;
+            }
+
+            let trace = guest.trace();
+            if !trace.is_empty() {
+                zip.start_file("trace.txt", options)?;
+                for entry in trace {
+                    writeln!(zip, "{}", entry)?;
+                }
+            }
+
+            if self.full_memory {
+                for (address, size) in guest.memory_regions() {
+                    zip.start_file(format!("memory/{:08x}.bin", address), options)?;
+                    zip.write_all(&guest.read_memory(address, size)?)?;
+                }
+            }
+        }
+
+        if frame.len() == (width * height) as usize {
+            let mut data = Vec::new();
+            screenshot::encode_screenshot(&mut data, frame, width, height)?;
+
+            zip.start_file("screenshot.gif", options)?;
+            zip.write_all(&data)?;
+        }
+
+        zip.finish()?;
+
+        Ok(path)
+    }
+
+    // reading unmapped memory panics, as it does for guest code
+    fn read(guest: &dyn GuestAccess, address: u32, size: u32) -> Option<Vec<u8>> {
+        let mapped = guest
+            .memory_regions()
+            .iter()
+            .any(|&(start, length)| address >= start && address as u64 + size as u64 <= start as u64 + length as u64);
+
+        mapped.then(|| guest.read_memory(address, size).ok()).flatten()
+    }
+
+    fn register(guest: &dyn GuestAccess, name: &str) -> u32 {
+        guest.registers().into_iter().find(|(x, _)| *x == name).map(|(_, x)| x).unwrap_or(0)
+    }
+
+    fn write_registers(writer: &mut impl Write, guest: &dyn GuestAccess) -> anyhow::Result<()> {
+        for (name, value) in guest.registers() {
+            writeln!(writer, "{}: {:#010x}", name, value)?;
+        }
+
+        if let Some(x) = guest.fault_address() {
+            writeln!(writer, "Invalid access to {:#010x}", x)?;
+        }
+
+        Ok(())
+    }
+
+    // raw encodings around pc, to be fed to a disassembler in the mode given at the top
+    fn write_code(writer: &mut impl Write, guest: &dyn GuestAccess) -> anyhow::Result<()> {
+        let pc = Self::register(guest, "PC");
+        let thumb = Self::register(guest, "CPSR") & CPSR_THUMB != 0;
+        let size = if thumb { 2 } else { 4 };
+
+        writeln!(writer, "{}", if thumb { "Thumb" } else { "ARM" })?;
+
+        for address in (0..CODE_WINDOW * 2 + 1).map(|x| pc.wrapping_sub(CODE_WINDOW * size).wrapping_add(x * size)) {
+            let marker = if address == pc { "=>" } else { "  " };
+
+            match Self::read(guest, address, size) {
+                Some(data) if thumb => writeln!(writer, "{} {:#010x}: {:04x}", marker, address, u16::from_le_bytes([data[0], data[1]]))?,
+                Some(data) => writeln!(
+                    writer,
+                    "{} {:#010x}: {:08x}",
+                    marker,
+                    address,
+                    u32::from_le_bytes(data.try_into().unwrap())
+                )?,
+                None => writeln!(writer, "{} {:#010x}: <Unmapped>", marker, address)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_stack(writer: &mut impl Write, guest: &dyn GuestAccess) -> anyhow::Result<()> {
+        let sp = Self::register(guest, "SP");
+
+        for i in 0..STACK_WORDS {
+            match Self::read(guest, sp.wrapping_add(i * 4), 4) {
+                Some(data) => writeln!(writer, "SP+{:#x}: {:#010x}", i * 4, u32::from_le_bytes(data.try_into().unwrap()))?,
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+}
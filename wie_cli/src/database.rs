@@ -1,18 +1,36 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
 
-use directories::ProjectDirs;
+use wie_backend::{Database as _, RecordId};
 
-use wie_backend::RecordId;
+// written to a temporary file next to the target first, so exiting in the middle of a write doesn't leave a truncated save behind
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let temp_path = path.with_file_name(format!(".{}.tmp", path.file_name().and_then(|x| x.to_str()).unwrap_or_default()));
+
+    let mut file = File::create(&temp_path)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+
+    fs::rename(temp_path, path)
+}
+
+pub fn is_temporary(name: &str) -> bool {
+    name.starts_with('.') && name.ends_with(".tmp")
+}
+
+// other data of the app is kept in these directories next to its databases
+const RESERVED_NAMES: [&str; 3] = ["files", "savestates", "crashes"];
 
 pub struct DatabaseRepository {
     base_path: PathBuf,
 }
 
 impl DatabaseRepository {
-    pub fn new(app_id: &str) -> Self {
-        let base_dir = ProjectDirs::from("net", "dlunch", "wie").unwrap();
-
-        let base_path = base_dir.data_dir().join(app_id);
+    pub fn new(data_dir: &Path, app_id: &str) -> Self {
+        let base_path = data_dir.join(app_id);
 
         Self { base_path }
     }
@@ -28,6 +46,20 @@ impl wie_backend::DatabaseRepository for DatabaseRepository {
 
         Box::new(Database::new(path).unwrap())
     }
+
+    fn list(&self) -> Vec<String> {
+        let entries = match fs::read_dir(&self.base_path) {
+            Ok(x) => x,
+            Err(_) => return Vec::new(),
+        };
+
+        entries
+            .filter_map(|x| x.ok())
+            .filter(|x| x.path().is_dir())
+            .filter_map(|x| x.file_name().into_string().ok())
+            .filter(|x| !RESERVED_NAMES.contains(&x.as_str()))
+            .collect()
+    }
 }
 
 pub struct Database {
@@ -43,19 +75,6 @@ impl Database {
         Ok(Self { base_path })
     }
 
-    fn find_empty_record_id(&mut self) -> RecordId {
-        let mut record_id = 0;
-
-        loop {
-            let path = self.base_path.join(record_id.to_string());
-
-            if !path.exists() {
-                return record_id;
-            }
-
-            record_id += 1;
-        }
-    }
     fn get_path_for_record(&self, id: RecordId) -> PathBuf {
         self.base_path.join(id.to_string())
     }
@@ -63,12 +82,12 @@ impl Database {
 
 impl wie_backend::Database for Database {
     fn add(&mut self, data: &[u8]) -> RecordId {
-        let id = self.find_empty_record_id();
+        let id = self.next_record_id();
 
         tracing::trace!("Adding record {} to database {:?}", id, &self.base_path);
 
         let path = self.get_path_for_record(id);
-        fs::write(path, data).unwrap();
+        write_atomic(&path, data).unwrap();
 
         id
     }
@@ -86,7 +105,7 @@ impl wie_backend::Database for Database {
 
         tracing::trace!("Set record {} to database {:?}", id, &self.base_path);
 
-        fs::write(path, data).is_ok()
+        write_atomic(&path, data).is_ok()
     }
 
     fn delete(&mut self, id: RecordId) -> bool {
@@ -98,10 +117,11 @@ impl wie_backend::Database for Database {
     }
 
     fn get_record_ids(&self) -> Vec<RecordId> {
+        // leftovers of an interrupted write are skipped along with anything else that isn't a record
         fs::read_dir(&self.base_path)
             .unwrap()
             .filter(|x| x.as_ref().unwrap().path().is_file())
-            .map(|x| x.unwrap().file_name().to_str().unwrap().parse().unwrap())
+            .filter_map(|x| x.unwrap().file_name().to_str()?.parse().ok())
             .collect()
     }
 }
@@ -0,0 +1,206 @@
+use std::{
+    io::{stdin, BufRead},
+    sync::mpsc::{channel, Receiver},
+    thread,
+};
+
+use wie_backend::{App, GuestAccess, ImportKind};
+
+const HELP: &str = "\
+Commands:
+  regs                      show registers
+  mem <address> [size]      hex dump of memory
+  write <address> <byte>..  write bytes to memory
+  maps                      list mapped memory regions
+  break <address>           add a breakpoint
+  delete <address>          remove a breakpoint
+  breakpoints               list breakpoints
+  pause | continue | step   control execution
+  trace <start> <end>       record instructions in the range
+  untrace <start> <end>     stop recording instructions in the range
+  trace functions [off]     record native function calls
+  tracelog [count]          show recently recorded entries
+  classes [filter]          list loaded classes
+  help                      show this message";
+
+// line based debugger reading commands from stdin while the app runs
+pub struct Debugger {
+    commands: Receiver<String>,
+    guest: Option<Box<dyn GuestAccess>>,
+    was_paused: bool,
+}
+
+impl Debugger {
+    pub fn new(app: &dyn App) -> Self {
+        let (sender, commands) = channel();
+
+        // stdin blocks, so it's read on a separate thread
+        thread::spawn(move || {
+            for line in stdin().lock().lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let guest = app.guest();
+        if guest.is_none() {
+            eprintln!("Guest memory is not available on this platform, only classes command is available");
+        }
+        eprintln!("{}", HELP);
+
+        Self {
+            commands,
+            guest,
+            was_paused: false,
+        }
+    }
+
+    pub fn update(&mut self, app: &dyn App) {
+        while let Ok(line) = self.commands.try_recv() {
+            if let Err(x) = self.execute(app, &line) {
+                eprintln!("{}", x);
+            }
+        }
+
+        // breakpoint hits happen inside the app, so we notice them by polling
+        let paused = self.guest.as_ref().map(|x| x.is_paused()).unwrap_or(false);
+        if paused && !self.was_paused {
+            if let Some(guest) = &self.guest {
+                eprintln!("Paused");
+                eprintln!("{}", Self::format_registers(guest.as_ref()));
+            }
+        }
+        self.was_paused = paused;
+    }
+
+    fn execute(&mut self, app: &dyn App, line: &str) -> anyhow::Result<()> {
+        let mut args = line.split_whitespace();
+        let command = match args.next() {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        let args = args.collect::<Vec<_>>();
+
+        if command == "help" {
+            eprintln!("{}", HELP);
+
+            return Ok(());
+        }
+        if command == "classes" {
+            let filter = args.first().copied().unwrap_or("");
+            for (name, status) in app.report().names(ImportKind::Class) {
+                if name.contains(filter) {
+                    eprintln!("{:?}\t{}", status, name);
+                }
+            }
+
+            return Ok(());
+        }
+
+        let guest = self
+            .guest
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Guest memory is not available on this platform"))?;
+
+        match command {
+            "regs" => eprintln!("{}", Self::format_registers(guest.as_ref())),
+            "mem" => {
+                let address = parse_number(args.first())?;
+                let size = args.get(1).map(|x| parse_number(Some(x))).transpose()?.unwrap_or(0x40);
+
+                let data = guest.read_memory(address, size)?;
+                for (i, row) in data.chunks(16).enumerate() {
+                    let hex = row.iter().map(|x| format!("{:02x}", x)).collect::<Vec<_>>().join(" ");
+                    let ascii = row
+                        .iter()
+                        .map(|&x| if x.is_ascii_graphic() { x as char } else { '.' })
+                        .collect::<String>();
+
+                    eprintln!("{:08x}: {:<47} {}", address + i as u32 * 16, hex, ascii);
+                }
+            }
+            "write" => {
+                let address = parse_number(args.first())?;
+                let data = args[1..]
+                    .iter()
+                    .map(|x| u8::from_str_radix(x.trim_start_matches("0x"), 16))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                guest.write_memory(address, &data)?;
+            }
+            "maps" => {
+                for (name, address, size, permission, owner) in guest.memory_map() {
+                    eprintln!(
+                        "{:08x}-{:08x} {} {:<16} {}",
+                        address,
+                        address as u64 + size as u64,
+                        permission,
+                        name,
+                        owner
+                    );
+                }
+            }
+            "break" => guest.add_breakpoint(parse_number(args.first())?),
+            "delete" => {
+                if !guest.remove_breakpoint(parse_number(args.first())?) {
+                    anyhow::bail!("No such breakpoint");
+                }
+            }
+            "breakpoints" => {
+                for address in guest.breakpoints() {
+                    eprintln!("{:#x}", address);
+                }
+            }
+            "pause" => guest.pause(),
+            "continue" => guest.resume(),
+            "step" => {
+                if !guest.is_paused() {
+                    anyhow::bail!("Not paused");
+                }
+                guest.step();
+                // to print registers again when it pauses after the step
+                self.was_paused = false;
+            }
+            "trace" if args.first() == Some(&"functions") => guest.set_trace_functions(args.get(1) != Some(&"off")),
+            "trace" | "untrace" => {
+                let start = parse_number(args.first())?;
+                let end = parse_number(args.get(1))?;
+
+                guest.set_trace_region(start, end, command == "trace");
+            }
+            "tracelog" => {
+                let count = args.first().map(|x| x.parse::<usize>()).transpose()?.unwrap_or(32);
+
+                let trace = guest.trace();
+                for entry in &trace[trace.len().saturating_sub(count)..] {
+                    eprintln!("{}", entry);
+                }
+            }
+            _ => anyhow::bail!("Unknown command {}, type help for the list", command),
+        }
+
+        Ok(())
+    }
+
+    fn format_registers(guest: &dyn GuestAccess) -> String {
+        guest
+            .registers()
+            .chunks(6)
+            .map(|row| {
+                row.iter()
+                    .map(|(name, value)| format!("{}: {:#010x}", name, value))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// addresses are written in hex, with or without 0x prefix
+fn parse_number(value: Option<&&str>) -> anyhow::Result<u32> {
+    let value = value.ok_or_else(|| anyhow::anyhow!("Missing argument"))?;
+
+    Ok(u32::from_str_radix(value.trim_start_matches("0x"), 16)?)
+}
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+use wie_backend::{decode_euc_kr, extract_zip, Archive};
+
+const MANIFEST_PATH: &str = "META-INF/MANIFEST.MF";
+
+// what the app tells about itself in the manifest of its jar and in the jad next to it,
+// so the main class, window title and screen size don't have to be given by hand
+#[derive(Default)]
+pub struct AppDescriptor {
+    pub main_class_name: Option<String>,
+    pub name: Option<String>,
+    pub vendor: Option<String>,
+    /// Width and height the app was made for
+    pub screen_size: Option<(u32, u32)>,
+}
+
+impl AppDescriptor {
+    // attributes of the jad take precedence over the manifest, as on handsets
+    pub fn read(archive: &dyn Archive, jad: Option<&[u8]>) -> Self {
+        let mut attributes = archive
+            .files()
+            .ok()
+            .and_then(|mut x| x.remove(MANIFEST_PATH))
+            .map(|x| parse_attributes(&x))
+            .unwrap_or_default();
+        if let Some(jad) = jad {
+            attributes.extend(parse_attributes(jad));
+        }
+
+        let descriptor = Self::from_attributes(&attributes);
+
+        // descriptors of the platform, like __adf__ of KTF, know the main class better than the manifest.
+        // archives without one are named after the file though, so the name in the manifest goes first
+        Self {
+            main_class_name: archive.main_class_name().or(descriptor.main_class_name),
+            name: descriptor.name.or_else(|| archive.name()),
+            ..descriptor
+        }
+    }
+
+    // for jars loaded without a descriptor of their platform
+    pub fn from_jar(jar: &[u8]) -> Self {
+        let manifest = extract_zip(jar).ok().and_then(|mut x| x.remove(MANIFEST_PATH));
+
+        Self::from_attributes(&manifest.map(|x| parse_attributes(&x)).unwrap_or_default())
+    }
+
+    fn from_attributes(attributes: &BTreeMap<String, String>) -> Self {
+        // MIDlet-1 is `name, icon, class`
+        let main_class_name = attributes
+            .get("MIDlet-1")
+            .and_then(|x| x.split(',').nth(2))
+            .or(attributes.get("Main-Class").map(|x| x.as_str()))
+            .map(|x| x.trim().to_owned())
+            .filter(|x| !x.is_empty());
+
+        let screen_size = attributes.get("Nokia-MIDlet-Original-Display-Size").and_then(|x| {
+            let (width, height) = x.split_once([',', 'x'])?;
+
+            Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+        });
+
+        Self {
+            main_class_name,
+            name: attributes.get("MIDlet-Name").cloned(),
+            vendor: attributes.get("MIDlet-Vendor").cloned(),
+            screen_size,
+        }
+    }
+}
+
+// `Key: Value` lines, where a line starting with a space continues the previous one as in manifests.
+// values are in UTF-8 or, on most korean titles, in EUC-KR
+fn parse_attributes(data: &[u8]) -> BTreeMap<String, String> {
+    let mut lines = Vec::<Vec<u8>>::new();
+    for line in data.split(|x| *x == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        match (line.strip_prefix(b" "), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.extend_from_slice(continuation),
+            _ => lines.push(line.to_vec()),
+        }
+    }
+
+    lines
+        .into_iter()
+        .filter_map(|line| {
+            let line = String::from_utf8(line).unwrap_or_else(|x| decode_euc_kr(x.as_bytes()));
+            let (key, value) = line.split_once(':')?;
+
+            Some((key.trim().to_owned(), value.trim().to_owned()))
+        })
+        .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+        .collect()
+}
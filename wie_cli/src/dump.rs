@@ -0,0 +1,48 @@
+use std::{fs, path::Path};
+
+use wie_backend::{carve_zip_archives, extract_zip};
+
+use crate::applet::is_archive;
+
+// raw flash dumps don't carry file names, so carved archives are named after their offset in the dump
+pub struct DumpedArchive {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+pub fn scan_dump(dump: &[u8]) -> Vec<DumpedArchive> {
+    carve_zip_archives(dump)
+        .into_iter()
+        .filter(|x| is_runnable(x))
+        .map(|x| DumpedArchive {
+            name: format!("dump_{:08x}", x.as_ptr() as usize - dump.as_ptr() as usize),
+            data: x.to_vec(),
+        })
+        .collect()
+}
+
+pub fn extract_dump(archives: &[DumpedArchive], path: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(path)?;
+
+    for archive in archives {
+        let filename = path.join(format!("{}.zip", archive.name));
+        tracing::info!("Extracting {} to {}", archive.name, filename.display());
+
+        fs::write(filename, &archive.data)?;
+    }
+
+    Ok(())
+}
+
+// unrelated zip files (resources, settings, ...) are common in dumps, skip anything we can't launch
+fn is_runnable(data: &[u8]) -> bool {
+    let files = match extract_zip(data) {
+        Ok(x) => x,
+        Err(_) => return false,
+    };
+
+    is_archive(&files)
+        || files
+            .keys()
+            .any(|x| x == "META-INF/MANIFEST.MF" || x.ends_with(".class") || x.starts_with("client.bin") || x == "binary.mod")
+}
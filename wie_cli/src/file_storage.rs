@@ -0,0 +1,120 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::database::{is_temporary, write_atomic};
+
+pub struct FileStorage {
+    base_path: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(data_dir: &Path, app_id: &str) -> Self {
+        let base_path = data_dir.join(app_id).join("files");
+
+        Self { base_path }
+    }
+
+    // paths climbing out of the app area are refused, as are ones the host would take as absolute or with a drive, like `C:` or `\\server`
+    fn get_path(&self, path: &str) -> Option<PathBuf> {
+        let relative = path.trim_start_matches('/');
+
+        let valid = relative.split('/').all(|x| x != ".." && !x.contains(['\\', ':']))
+            && Path::new(relative)
+                .components()
+                .all(|x| matches!(x, Component::Normal(_) | Component::CurDir));
+        if !valid {
+            tracing::warn!("Refusing file path {}", path);
+
+            return None;
+        }
+
+        Some(self.base_path.join(relative))
+    }
+}
+
+impl wie_backend::FileStorage for FileStorage {
+    fn read(&self, path: &str) -> Option<Vec<u8>> {
+        let path = self.get_path(path)?;
+
+        tracing::trace!("Read file {:?}", path);
+
+        fs::read(path).ok()
+    }
+
+    fn write(&self, path: &str, data: &[u8]) -> bool {
+        let path = match self.get_path(path) {
+            Some(x) => x,
+            None => return false,
+        };
+
+        tracing::trace!("Write file {:?}, {}b", path, data.len());
+
+        if let Some(parent) = path.parent() {
+            if fs::create_dir_all(parent).is_err() {
+                return false;
+            }
+        }
+
+        write_atomic(&path, data).is_ok()
+    }
+
+    fn remove(&self, path: &str) -> bool {
+        let path = match self.get_path(path) {
+            Some(x) => x,
+            None => return false,
+        };
+
+        tracing::trace!("Remove file {:?}", path);
+
+        if path.is_dir() {
+            fs::remove_dir(path).is_ok()
+        } else {
+            fs::remove_file(path).is_ok()
+        }
+    }
+
+    fn create_dir(&self, path: &str) -> bool {
+        match self.get_path(path) {
+            Some(x) => fs::create_dir_all(x).is_ok(),
+            None => false,
+        }
+    }
+
+    fn list(&self, path: &str) -> Option<Vec<String>> {
+        let entries = fs::read_dir(self.get_path(path)?).ok()?;
+
+        Some(
+            entries
+                .filter_map(|x| {
+                    let entry = x.ok()?;
+                    let name = entry.file_name().into_string().ok().filter(|x| !is_temporary(x))?;
+
+                    Some(if entry.path().is_dir() { format!("{}/", name) } else { name })
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::FileStorage;
+
+    #[test]
+    fn test_get_path() {
+        let storage = FileStorage::new(Path::new("data"), "app");
+
+        assert_eq!(
+            storage.get_path("/save/slot1.dat"),
+            Some(Path::new("data/app/files/save/slot1.dat").to_path_buf())
+        );
+        assert_eq!(storage.get_path("save/../../other"), None);
+        assert_eq!(storage.get_path("..\\other"), None);
+        assert_eq!(storage.get_path("C:/Windows"), None);
+        assert_eq!(storage.get_path("save/c:file"), None);
+    }
+}
@@ -0,0 +1,111 @@
+use serde::Deserialize;
+
+// applied when scaling the screen up to the window
+#[derive(Deserialize, Clone, Copy, Default, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Filter {
+    #[default]
+    Nearest,
+    // nearest neighbor up to the integer scale, then bilinear to fill the window. only this one scales by a fraction
+    SharpBilinear,
+    // gaps between pixels, with each pixel split into red, green and blue columns
+    LcdGrid,
+    Scanlines,
+}
+
+impl Filter {
+    pub fn integer_scale(self) -> bool {
+        self != Filter::SharpBilinear
+    }
+
+    // color of the window pixel at (x, y), relative to the top left of the scaled content. `data` is in 0RGB
+    pub fn sample(self, data: &[u32], width: u32, height: u32, scale: f64, x: u32, y: u32) -> u32 {
+        match self {
+            Filter::Nearest => Self::nearest(data, width, height, scale, x, y),
+            Filter::SharpBilinear => Self::sharp_bilinear(data, width, height, scale, x, y),
+            Filter::LcdGrid => {
+                let color = Self::nearest(data, width, height, scale, x, y);
+                let scale = scale as u32;
+                // too small to show a grid
+                if scale < 3 {
+                    return color;
+                }
+
+                let (cell_x, cell_y) = (x % scale, y % scale);
+                if cell_x == scale - 1 || cell_y == scale - 1 {
+                    return scale_color(color, [0.5; 3]);
+                }
+
+                let mut weights = [0.75; 3];
+                weights[(cell_x * 3 / (scale - 1)).min(2) as usize] = 1.0;
+
+                scale_color(color, weights)
+            }
+            Filter::Scanlines => {
+                let color = Self::nearest(data, width, height, scale, x, y);
+                let scale = scale as u32;
+
+                if scale >= 2 && y % scale == scale - 1 {
+                    scale_color(color, [0.5; 3])
+                } else {
+                    color
+                }
+            }
+        }
+    }
+
+    fn nearest(data: &[u32], width: u32, height: u32, scale: f64, x: u32, y: u32) -> u32 {
+        let src_x = ((x as f64 / scale) as u32).min(width - 1);
+        let src_y = ((y as f64 / scale) as u32).min(height - 1);
+
+        data[(src_y * width + src_x) as usize]
+    }
+
+    // pixels stay sharp inside, only the edges between them are interpolated
+    fn sharp_bilinear(data: &[u32], width: u32, height: u32, scale: f64, x: u32, y: u32) -> u32 {
+        let prescale = scale.floor().max(1.0);
+        let region = 0.5 - 0.5 / prescale;
+
+        let coordinate = |position: u32| {
+            let texel = (position as f64 + 0.5) / scale;
+            let center_distance = texel.fract() - 0.5;
+
+            // back to pixel centers for bilinear sampling
+            texel.floor() + (center_distance - center_distance.clamp(-region, region)) * prescale
+        };
+        let u = coordinate(x).clamp(0.0, (width - 1) as f64);
+        let v = coordinate(y).clamp(0.0, (height - 1) as f64);
+
+        let (x0, y0) = (u as u32, v as u32);
+        let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+        let (tx, ty) = (u.fract(), v.fract());
+
+        let pixel = |x: u32, y: u32| data[(y * width + x) as usize];
+        let top = lerp_color(pixel(x0, y0), pixel(x1, y0), tx);
+        let bottom = lerp_color(pixel(x0, y1), pixel(x1, y1), tx);
+
+        lerp_color(top, bottom, ty)
+    }
+}
+
+fn channels(color: u32) -> [f64; 3] {
+    [(color >> 16) & 0xff, (color >> 8) & 0xff, color & 0xff].map(|x| x as f64)
+}
+
+fn from_channels(channels: [f64; 3]) -> u32 {
+    let [r, g, b] = channels.map(|x| x.round().clamp(0.0, 255.0) as u32);
+
+    (r << 16) | (g << 8) | b
+}
+
+fn scale_color(color: u32, weights: [f64; 3]) -> u32 {
+    let channels = channels(color);
+
+    from_channels([channels[0] * weights[0], channels[1] * weights[1], channels[2] * weights[2]])
+}
+
+fn lerp_color(a: u32, b: u32, t: f64) -> u32 {
+    let (a, b) = (channels(a), channels(b));
+
+    from_channels([0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t))
+}
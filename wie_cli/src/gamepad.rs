@@ -0,0 +1,34 @@
+use gilrs::{Button, EventType, Gilrs};
+
+// all connected gamepads act as one
+pub struct Gamepads {
+    gilrs: Option<Gilrs>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        // no gamepad backend on the host, e.g. no access to udev, just leaves gamepads out
+        let gilrs = Gilrs::new().map_err(|x| tracing::warn!("Gamepads are not available: {}", x)).ok();
+
+        Self { gilrs }
+    }
+
+    // buttons pressed or released since the last poll, with true for pressed
+    pub fn poll(&mut self) -> Vec<(Button, bool)> {
+        let gilrs = match &mut self.gilrs {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+
+        let mut result = Vec::new();
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) => result.push((button, true)),
+                EventType::ButtonReleased(button, _) => result.push((button, false)),
+                _ => {}
+            }
+        }
+
+        result
+    }
+}
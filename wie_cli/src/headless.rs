@@ -0,0 +1,202 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    path::Path,
+    rc::Rc,
+};
+
+use wie_backend::{
+    canvas::{Image, Rect},
+    App, Event, Instant, Platform, Screen,
+};
+
+use crate::{
+    clock::Clock,
+    config::Config,
+    crash::{self, CrashReporter},
+    database::DatabaseRepository,
+    file_storage::FileStorage,
+    movie::Movie,
+    screenshot,
+    scripting::Script,
+};
+
+#[derive(Clone)]
+struct HeadlessScreen {
+    width: u32,
+    height: u32,
+    frame: Rc<RefCell<Vec<u32>>>,
+    redraw_requested: Rc<Cell<bool>>,
+}
+
+impl Screen for HeadlessScreen {
+    fn request_redraw(&self) -> anyhow::Result<()> {
+        self.redraw_requested.set(true);
+
+        Ok(())
+    }
+
+    fn paint(&mut self, image: &dyn Image) {
+        *self.frame.borrow_mut() = image
+            .colors()
+            .iter()
+            .map(|x| ((x.a as u32) << 24) | ((x.r as u32) << 16) | ((x.g as u32) << 8) | (x.b as u32))
+            .collect::<Vec<_>>();
+    }
+
+    fn paint_rect(&mut self, image: &dyn Image, rect: Rect) {
+        let mut frame = self.frame.borrow_mut();
+        if frame.len() != (self.width * self.height) as usize {
+            frame.resize((self.width * self.height) as usize, 0);
+        }
+
+        for y in rect.y as u32..rect.y as u32 + rect.height {
+            for x in rect.x as u32..rect.x as u32 + rect.width {
+                let color = image.get_pixel(x, y);
+
+                frame[(y * self.width + x) as usize] =
+                    ((color.a as u32) << 24) | ((color.r as u32) << 16) | ((color.g as u32) << 8) | (color.b as u32);
+            }
+        }
+    }
+
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+struct NullAudioSink;
+
+impl wie_backend::AudioSink for NullAudioSink {
+    fn play_wave(&self, _channel: u8, _sampling_rate: u32, _wave_data: &[i16]) {}
+}
+
+pub struct HeadlessPlatform {
+    database_repository: DatabaseRepository,
+    file_storage: FileStorage,
+    screen: HeadlessScreen,
+    clock: Clock,
+    system_properties: BTreeMap<String, String>,
+}
+
+impl HeadlessPlatform {
+    pub fn new(config: &Config, app_id: &str) -> Self {
+        Self {
+            database_repository: DatabaseRepository::new(&config.data_dir(), app_id),
+            file_storage: FileStorage::new(&config.data_dir(), app_id),
+            screen: HeadlessScreen {
+                width: config.screen.width,
+                height: config.screen.height,
+                frame: Rc::new(RefCell::new(Vec::new())),
+                redraw_requested: Rc::new(Cell::new(false)),
+            },
+            clock: Clock::new_virtual(),
+            system_properties: config.runtime.system_properties.clone(),
+        }
+    }
+}
+
+impl Platform for HeadlessPlatform {
+    fn screen(&mut self) -> &mut dyn Screen {
+        &mut self.screen
+    }
+
+    fn now(&self) -> Instant {
+        Instant::from_epoch_millis(self.clock.now())
+    }
+
+    fn database_repository(&self) -> &dyn wie_backend::DatabaseRepository {
+        &self.database_repository
+    }
+
+    fn file_storage(&self) -> &dyn wie_backend::FileStorage {
+        &self.file_storage
+    }
+
+    fn audio_sink(&self) -> Box<dyn wie_backend::AudioSink> {
+        Box::new(NullAudioSink)
+    }
+
+    fn system_property(&self, name: &str) -> Option<String> {
+        self.system_properties.get(name).cloned()
+    }
+}
+
+pub struct HeadlessRunner {
+    screen: HeadlessScreen,
+    clock: Clock,
+    movie: Movie,
+    crash_reporter: CrashReporter,
+}
+
+impl HeadlessRunner {
+    pub fn new(platform: &HeadlessPlatform, config: &Config, app_id: &str, script_path: Option<&Path>) -> anyhow::Result<Self> {
+        let movie = script_path.map(Movie::load).transpose()?.unwrap_or_default();
+
+        Ok(Self {
+            screen: platform.screen.clone(),
+            clock: platform.clock.clone(),
+            movie,
+            crash_reporter: CrashReporter::new(config, app_id),
+        })
+    }
+
+    // runs the app for given virtual duration, and writes the last painted frame to `screenshot_path`
+    pub fn run(
+        mut self,
+        app: &mut dyn App,
+        mut user_script: Option<Script>,
+        duration_millis: u64,
+        screenshot_path: Option<&Path>,
+    ) -> anyhow::Result<()> {
+        let result = (|| {
+            app.start()?;
+            if let Some(x) = &mut user_script {
+                x.attach(app)?;
+            }
+
+            while self.clock.now() < duration_millis {
+                self.movie.play(app, self.clock.now());
+
+                if let Some(x) = &mut user_script {
+                    x.update(app)?;
+                }
+
+                if self.screen.redraw_requested.replace(false) {
+                    app.on_event(Event::Redraw);
+                }
+
+                crash::tick(app)?;
+
+                self.clock.tick();
+            }
+
+            anyhow::Ok(())
+        })();
+
+        if let Some(path) = screenshot_path {
+            let frame = self.screen.frame.borrow();
+            if frame.is_empty() {
+                tracing::warn!("Nothing was painted, skipping screenshot");
+            } else {
+                screenshot::write_screenshot(path, &frame, self.screen.width, self.screen.height)?;
+            }
+        }
+
+        match &result {
+            Ok(_) => eprintln!("Finished after {}ms", self.clock.now()),
+            Err(x) => {
+                eprintln!("Failed after {}ms: {:?}", self.clock.now(), x);
+
+                let frame = self.screen.frame.borrow();
+                self.crash_reporter.report(app, x, &frame, self.screen.width, self.screen.height);
+            }
+        }
+
+        result
+    }
+}
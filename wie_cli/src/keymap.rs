@@ -0,0 +1,180 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use directories::ProjectDirs;
+use gilrs::Button;
+use serde::Deserialize;
+use winit::keyboard::{KeyCode as WinitKeyCode, PhysicalKey};
+
+use wie_backend::KeyCode;
+
+#[derive(Deserialize)]
+struct KeyMapConfig {
+    #[serde(default)]
+    keys: HashMap<String, String>,
+    #[serde(default)]
+    buttons: HashMap<String, String>,
+}
+
+// maps host keys to keypad keys, e.g. `KeyQ = "4"` or `Escape = "CLEAR"` under `[keys]`,
+// and gamepad buttons the same way under `[buttons]`, e.g. `South = "OK"` or `DPadUp = "UP"`
+pub struct KeyMap {
+    keys: HashMap<WinitKeyCode, KeyCode>,
+    buttons: HashMap<Button, KeyCode>,
+    // from game config or compatibility profile, applied over the file
+    overrides: HashMap<WinitKeyCode, KeyCode>,
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+}
+
+impl KeyMap {
+    pub fn new(path: Option<PathBuf>, overrides: &HashMap<String, String>) -> anyhow::Result<Self> {
+        let path = path.or_else(|| {
+            let base_dir = ProjectDirs::from("net", "dlunch", "wie")?;
+            let path = base_dir.config_dir().join("keymap.toml");
+
+            path.exists().then_some(path)
+        });
+
+        let overrides = Self::parse_keys(overrides.clone())?;
+        let mut result = Self {
+            keys: Self::default_keys(),
+            buttons: Self::default_buttons(),
+            overrides,
+            path,
+            modified: None,
+        };
+
+        if let Some(path) = result.path.clone() {
+            (result.keys, result.buttons) = Self::load(&path)?;
+            result.modified = Self::modified(&path);
+        }
+        result.keys.extend(result.overrides.iter().map(|(k, v)| (*k, *v)));
+
+        Ok(result)
+    }
+
+    pub fn map(&self, key: PhysicalKey) -> Option<KeyCode> {
+        match key {
+            PhysicalKey::Code(x) => self.keys.get(&x).copied(),
+            _ => None,
+        }
+    }
+
+    pub fn map_button(&self, button: Button) -> Option<KeyCode> {
+        self.buttons.get(&button).copied()
+    }
+
+    pub fn reload_if_changed(&mut self) {
+        let path = match &self.path {
+            Some(x) => x.clone(),
+            None => return,
+        };
+
+        let modified = Self::modified(&path);
+        if modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+
+        match Self::load(&path) {
+            Ok((keys, buttons)) => {
+                tracing::info!("Reloaded keymap from {}", path.display());
+
+                self.keys = keys;
+                self.buttons = buttons;
+                self.keys.extend(self.overrides.iter().map(|(k, v)| (*k, *v)));
+            }
+            Err(x) => tracing::error!("Failed to reload keymap: {}", x),
+        }
+    }
+
+    // buttons not in the file keep the defaults, as the file is usually written for the keyboard
+    fn load(path: &Path) -> anyhow::Result<(HashMap<WinitKeyCode, KeyCode>, HashMap<Button, KeyCode>)> {
+        let config: KeyMapConfig = toml::from_str(&fs::read_to_string(path)?)?;
+
+        let mut buttons = Self::default_buttons();
+        buttons.extend(Self::parse_buttons(config.buttons)?);
+
+        Ok((Self::parse_keys(config.keys)?, buttons))
+    }
+
+    fn parse_keys(keys: HashMap<String, String>) -> anyhow::Result<HashMap<WinitKeyCode, KeyCode>> {
+        keys.into_iter()
+            .map(|(host, key)| {
+                let host_key =
+                    WinitKeyCode::deserialize(toml::Value::String(host.clone())).map_err(|_| anyhow::anyhow!("Unknown host key {}", host))?;
+                let key = KeyCode::parse(&key).ok_or_else(|| anyhow::anyhow!("Unknown keypad key {}", key))?;
+
+                Ok((host_key, key))
+            })
+            .collect()
+    }
+
+    fn parse_buttons(buttons: HashMap<String, String>) -> anyhow::Result<HashMap<Button, KeyCode>> {
+        buttons
+            .into_iter()
+            .map(|(host, key)| {
+                let button =
+                    Button::deserialize(toml::Value::String(host.clone())).map_err(|_| anyhow::anyhow!("Unknown gamepad button {}", host))?;
+                let key = KeyCode::parse(&key).ok_or_else(|| anyhow::anyhow!("Unknown keypad key {}", key))?;
+
+                Ok((button, key))
+            })
+            .collect()
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|x| x.modified()).ok()
+    }
+
+    fn default_keys() -> HashMap<WinitKeyCode, KeyCode> {
+        [
+            (WinitKeyCode::Digit1, KeyCode::NUM1),
+            (WinitKeyCode::Digit2, KeyCode::NUM2),
+            (WinitKeyCode::Digit3, KeyCode::NUM3),
+            (WinitKeyCode::KeyQ, KeyCode::NUM4),
+            (WinitKeyCode::KeyW, KeyCode::NUM5),
+            (WinitKeyCode::KeyE, KeyCode::NUM6),
+            (WinitKeyCode::KeyA, KeyCode::NUM7),
+            (WinitKeyCode::KeyS, KeyCode::NUM8),
+            (WinitKeyCode::KeyD, KeyCode::NUM9),
+            (WinitKeyCode::KeyZ, KeyCode::STAR),
+            (WinitKeyCode::KeyX, KeyCode::NUM0),
+            (WinitKeyCode::KeyC, KeyCode::HASH),
+            (WinitKeyCode::Space, KeyCode::OK),
+            (WinitKeyCode::ArrowUp, KeyCode::UP),
+            (WinitKeyCode::ArrowDown, KeyCode::DOWN),
+            (WinitKeyCode::ArrowLeft, KeyCode::LEFT),
+            (WinitKeyCode::ArrowRight, KeyCode::RIGHT),
+            (WinitKeyCode::F1, KeyCode::LEFT_SOFT_KEY),
+            (WinitKeyCode::F2, KeyCode::RIGHT_SOFT_KEY),
+            (WinitKeyCode::Backspace, KeyCode::CLEAR),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn default_buttons() -> HashMap<Button, KeyCode> {
+        [
+            (Button::DPadUp, KeyCode::UP),
+            (Button::DPadDown, KeyCode::DOWN),
+            (Button::DPadLeft, KeyCode::LEFT),
+            (Button::DPadRight, KeyCode::RIGHT),
+            (Button::South, KeyCode::OK),
+            (Button::East, KeyCode::CLEAR),
+            (Button::West, KeyCode::NUM5),
+            (Button::North, KeyCode::NUM0),
+            (Button::LeftTrigger, KeyCode::LEFT_SOFT_KEY),
+            (Button::RightTrigger, KeyCode::RIGHT_SOFT_KEY),
+            (Button::Select, KeyCode::STAR),
+            (Button::Start, KeyCode::HASH),
+        ]
+        .into_iter()
+        .collect()
+    }
+}
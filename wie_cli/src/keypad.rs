@@ -0,0 +1,88 @@
+use wie_backend::{
+    canvas::{ArgbPixel, Canvas, Color, Image, ImageBufferCanvas, TextAlignment, VecImageBuffer},
+    KeyCode,
+};
+
+const ROW_HEIGHT: u32 = 24;
+
+#[rustfmt::skip]
+const LAYOUT: [[Option<(KeyCode, &str)>; 3]; 7] = [
+    [Some((KeyCode::LEFT_SOFT_KEY, "SOFT1")), Some((KeyCode::UP, "UP")), Some((KeyCode::RIGHT_SOFT_KEY, "SOFT2"))],
+    [Some((KeyCode::LEFT, "LEFT")), Some((KeyCode::OK, "OK")), Some((KeyCode::RIGHT, "RIGHT"))],
+    [Some((KeyCode::CLEAR, "CLR")), Some((KeyCode::DOWN, "DOWN")), None],
+    [Some((KeyCode::NUM1, "1")), Some((KeyCode::NUM2, "2")), Some((KeyCode::NUM3, "3"))],
+    [Some((KeyCode::NUM4, "4")), Some((KeyCode::NUM5, "5")), Some((KeyCode::NUM6, "6"))],
+    [Some((KeyCode::NUM7, "7")), Some((KeyCode::NUM8, "8")), Some((KeyCode::NUM9, "9"))],
+    [Some((KeyCode::STAR, "*")), Some((KeyCode::NUM0, "0")), Some((KeyCode::HASH, "#"))],
+];
+
+// on-screen phone keypad drawn under the screen, for touch devices without a keyboard
+pub struct Keypad {
+    width: u32,
+    pixels: Vec<u32>,
+}
+
+impl Keypad {
+    pub fn new(width: u32) -> Self {
+        let height = LAYOUT.len() as u32 * ROW_HEIGHT;
+        let key_width = width / 3;
+
+        let mut canvas = ImageBufferCanvas::new(VecImageBuffer::<ArgbPixel>::new(width, height));
+        canvas.fill_rect(
+            0,
+            0,
+            width,
+            height,
+            Color {
+                a: 0xff,
+                r: 0xc0,
+                g: 0xc0,
+                b: 0xc0,
+            },
+        );
+
+        for (row, keys) in LAYOUT.iter().enumerate() {
+            for (column, (_, label)) in keys.iter().enumerate().filter_map(|(i, x)| Some((i, (*x)?))) {
+                let (x, y) = (column as u32 * key_width, row as u32 * ROW_HEIGHT);
+
+                canvas.draw_rect(
+                    x,
+                    y,
+                    key_width,
+                    ROW_HEIGHT,
+                    Color {
+                        a: 0xff,
+                        r: 0x60,
+                        g: 0x60,
+                        b: 0x60,
+                    },
+                );
+                canvas.draw_text(label, x + key_width / 2, y + 6, TextAlignment::Center);
+            }
+        }
+
+        let pixels = canvas
+            .into_inner()
+            .colors()
+            .iter()
+            .map(|x| ((x.r as u32) << 16) | ((x.g as u32) << 8) | (x.b as u32))
+            .collect();
+
+        Self { width, pixels }
+    }
+
+    pub fn height(&self) -> u32 {
+        self.pixels.len() as u32 / self.width
+    }
+
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    // position is relative to the top left of the keypad
+    pub fn key_at(&self, x: u32, y: u32) -> Option<KeyCode> {
+        let row = LAYOUT.get((y / ROW_HEIGHT) as usize)?;
+
+        row.get((x / (self.width / 3)) as usize).copied().flatten().map(|x| x.0)
+    }
+}
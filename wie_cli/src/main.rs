@@ -1,41 +1,89 @@
 extern crate alloc;
 
+mod applet;
 mod audio_sink;
+mod clock;
+mod config;
+mod crash;
 mod database;
+mod debugger;
+mod descriptor;
+mod dump;
+mod file_storage;
+mod filter;
+mod gamepad;
+mod headless;
+mod keymap;
+mod keypad;
+mod movie;
+mod recorder;
+mod savestate;
+mod screenshot;
+mod scripting;
 mod window;
 
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     fs,
     io::stderr,
-    time::{SystemTime, UNIX_EPOCH},
+    path::{Component, Path, PathBuf},
+    time::Duration,
 };
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use winit::keyboard::{KeyCode as WinitKeyCode, PhysicalKey};
 
-use wie_backend::{extract_zip, Archive, Event, Instant, KeyCode, Platform, Screen};
+use wie_backend::{
+    canvas::{decode_image, Image},
+    extract_zip, App, Archive, Event, Instant, KeyCode, Platform, Screen, Telemetry,
+};
 use wie_j2me::J2MEArchive;
 use wie_ktf::KtfArchive;
 use wie_lgt::LgtArchive;
 use wie_skt::SktArchive;
 
 use self::{
-    audio_sink::AudioSink,
+    applet::Applet,
+    audio_sink::{AudioOutput, AudioSink},
+    clock::Clock,
+    config::{Config, TraceRegionConfig},
+    crash::CrashReporter,
     database::DatabaseRepository,
+    debugger::Debugger,
+    descriptor::AppDescriptor,
+    file_storage::FileStorage,
+    gamepad::Gamepads,
+    headless::{HeadlessPlatform, HeadlessRunner},
+    keymap::KeyMap,
+    movie::{Movie, MovieAction, MovieSession, MovieWriter},
+    recorder::{Recorder, RecordingFormat, RecordingScreen},
+    savestate::SaveStates,
+    scripting::Script,
     window::{WindowCallbackEvent, WindowImpl},
 };
 
 struct WieCliPlatform {
     database_repository: DatabaseRepository,
+    file_storage: FileStorage,
     window: Box<dyn Screen>,
+    recorder: Recorder,
+    clock: Clock,
+    audio_output: AudioOutput,
+    volume: f32,
+    system_properties: BTreeMap<String, String>,
 }
 
 impl WieCliPlatform {
-    fn new(app_id: &str, window: Box<dyn Screen>) -> Self {
+    fn new(config: &Config, app_id: &str, window: Box<dyn Screen>, recorder: Recorder, clock: Clock) -> Self {
         Self {
-            database_repository: DatabaseRepository::new(app_id),
+            database_repository: DatabaseRepository::new(&config.data_dir(), app_id),
+            file_storage: FileStorage::new(&config.data_dir(), app_id),
             window,
+            recorder,
+            clock,
+            audio_output: AudioOutput::open(&config.audio),
+            volume: config.audio.volume,
+            system_properties: config.runtime.system_properties.clone(),
         }
     }
 }
@@ -46,123 +94,874 @@ impl Platform for WieCliPlatform {
     }
 
     fn now(&self) -> Instant {
-        let now = SystemTime::now();
-        let since_the_epoch = now.duration_since(UNIX_EPOCH).unwrap();
-
-        Instant::from_epoch_millis(since_the_epoch.as_millis() as _)
+        Instant::from_epoch_millis(self.clock.now())
     }
 
     fn database_repository(&self) -> &dyn wie_backend::DatabaseRepository {
         &self.database_repository
     }
 
+    fn file_storage(&self) -> &dyn wie_backend::FileStorage {
+        &self.file_storage
+    }
+
     fn audio_sink(&self) -> Box<dyn wie_backend::AudioSink> {
-        Box::new(AudioSink)
+        Box::new(AudioSink::new(
+            self.audio_output.clone(),
+            self.recorder.clone(),
+            self.clock.clone(),
+            self.volume,
+        ))
+    }
+
+    fn system_property(&self, name: &str) -> Option<String> {
+        self.system_properties.get(name).cloned()
     }
 }
 
 #[derive(Parser)]
 struct Args {
+    #[command(subcommand)]
+    command: Command,
+    /// Log filter like `warn,wie_ktf=debug`, instead of RUST_LOG
+    #[arg(long, global = true)]
+    log: Option<String>,
+    #[command(flatten)]
+    config: ConfigArgs,
+}
+
+// on top of config files and profiles
+#[derive(clap::Args)]
+struct ConfigArgs {
+    /// Initial integer scale of the window, instead of `scale` of `[window]` in config
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(1..))]
+    scale: Option<u32>,
+    /// Where databases and savestates are stored, instead of `data_dir` of `[paths]` in config
+    #[arg(long, global = true)]
+    data_dir: Option<PathBuf>,
+}
+
+impl ConfigArgs {
+    fn load(&self, filename: &str) -> anyhow::Result<Config> {
+        let mut config = Config::load(filename)?;
+
+        if let Some(x) = self.scale {
+            config.window.scale = x;
+        }
+        if let Some(x) = &self.data_dir {
+            config.paths.data_dir = Some(x.clone());
+        }
+
+        Ok(config)
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run an applet
+    Run(RunArgs),
+    /// Show metadata of an applet
+    Info {
+        #[command(flatten)]
+        source: SourceArgs,
+        /// Run headless for given seconds of virtual time and print the compatibility report
+        #[arg(long)]
+        probe: Option<u64>,
+    },
+    /// Run headless with execution tracing, and print what was recorded
+    Trace {
+        #[command(flatten)]
+        source: SourceArgs,
+        /// Code range to record every instruction of, like `0x100000:0x180000`, can be repeated.
+        /// Native function calls are recorded if neither this nor --functions is given
+        #[arg(long, value_parser = parse_trace_region)]
+        region: Vec<TraceRegionConfig>,
+        /// Record calls to native functions
+        #[arg(long)]
+        functions: bool,
+        /// Seconds of virtual time to run
+        #[arg(long, default_value = "10")]
+        duration: u64,
+    },
+    /// List files in an applet
+    List {
+        #[command(flatten)]
+        source: SourceArgs,
+    },
+    /// Extract files in an applet, including its binary
+    Extract {
+        #[command(flatten)]
+        source: SourceArgs,
+        /// Directory to extract files into
+        #[arg(long)]
+        to: PathBuf,
+    },
+    /// List audio output devices, for `device` of `[audio]` in config
+    AudioDevices,
+}
+
+#[derive(clap::Args)]
+struct SourceArgs {
     filename: String,
+    /// Treat the file as a raw flash dump and search it for installed applets
+    #[arg(long)]
+    dump: bool,
+    /// Write applets found in the dump to this directory
+    #[arg(long, requires = "dump")]
+    extract_to: Option<PathBuf>,
+    /// Name of the applet to use, if the file contains more than one
+    #[arg(long)]
+    applet: Option<String>,
+}
+
+#[derive(clap::Args)]
+struct RunArgs {
+    #[command(flatten)]
+    source: SourceArgs,
+    /// Save the compatibility report to this file on exit
+    #[arg(long)]
+    report: Option<PathBuf>,
+    /// Key mapping configuration, defaults to `keymap` of config.toml, or keymap.toml in the config directory
+    #[arg(long)]
+    keymap: Option<PathBuf>,
+    /// Output format of gameplay recording, toggled with F9
+    #[arg(long, value_enum, default_value = "gif")]
+    record_format: RecordingFormat,
+    /// Path of gameplay recording, extension is decided by the format
+    #[arg(long, default_value = "wie_recording")]
+    record_to: PathBuf,
+    /// Directory of png screenshots taken with PrintScreen, and of frame bursts started with Pause
+    #[arg(long, default_value = "wie_screenshots")]
+    screenshot_to: PathBuf,
+    /// Seconds of frames a burst saves
+    #[arg(long, default_value = "5")]
+    burst_duration: u64,
+    /// Record key, pointer and text inputs on virtual time to this movie file, replayable with --movie-play or --script
+    #[arg(long, conflicts_with = "movie_play")]
+    movie_record: Option<PathBuf>,
+    /// Play back a movie recorded with --movie-record
+    #[arg(long)]
+    movie_play: Option<PathBuf>,
+    /// Rhai script for automation, which can access guest memory and inject key events
+    #[arg(long)]
+    user_script: Option<PathBuf>,
+    /// Run without a window on virtual time, exiting with an error if the app fails
+    #[arg(long)]
+    headless: bool,
+    /// Timed key inputs for headless run, in the movie format written by --movie-record
+    #[arg(long, requires = "headless")]
+    script: Option<PathBuf>,
+    /// Seconds of virtual time to run in headless mode
+    #[arg(long, requires = "headless", default_value = "10")]
+    duration: u64,
+    /// Save the last frame to this file when headless run ends
+    #[arg(long, requires = "headless")]
+    screenshot: Option<PathBuf>,
+    /// How many times faster time runs while Tab is held
+    #[arg(long, default_value = "4", value_parser = clap::value_parser!(u64).range(2..=64))]
+    fast_forward_speed: u64,
+    /// Show an on-screen keypad below the screen, usable with mouse or touch
+    #[arg(long)]
+    keypad: bool,
+    /// Read debugger commands from stdin while running, type help for the list
+    #[arg(long, conflicts_with = "headless")]
+    debugger: bool,
+    /// Additional archive to run side by side in its own window, can be repeated.
+    /// Hotkeys, movies, recording and the debugger only apply to the first one
+    #[arg(long, conflicts_with = "headless")]
+    open: Vec<String>,
+}
+
+pub struct StartOptions {
+    user_script_path: Option<PathBuf>,
+    report_path: Option<PathBuf>,
+    keypad: bool,
+    debugger: bool,
+    fast_forward_speed: u64,
+    screenshot_dir: PathBuf,
+    burst_duration: u64,
+    config: Config,
+    descriptor: AppDescriptor,
 }
 
 fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt()
-        .with_writer(stderr)
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
+    let args = Args::parse();
+
+    let filter = match &args.log {
+        Some(x) => tracing_subscriber::EnvFilter::try_new(x)?,
+        None => tracing_subscriber::EnvFilter::from_default_env(),
+    };
+    tracing_subscriber::fmt().with_writer(stderr).with_env_filter(filter).init();
+
+    let config = args.config;
+    match args.command {
+        Command::Run(x) => run(x, &config),
+        Command::Info { source, probe } => info(source, probe, &config),
+        Command::Trace {
+            source,
+            region,
+            functions,
+            duration,
+        } => trace(source, region, functions, duration, &config),
+        Command::List { source } => list(source),
+        Command::Extract { source, to } => extract(source, &to),
+        Command::AudioDevices => audio_devices(),
+    }
+}
+
+fn load(source: SourceArgs, config: &Config) -> anyhow::Result<Box<dyn Archive>> {
+    if source.dump {
+        let applets = load_dump(&source.filename, source.extract_to)?;
+
+        select_applet(applets, source.applet.as_deref())
+    } else if source.filename.ends_with("zip") {
+        let files = extract_zip(&fs::read(&source.filename)?)?;
+        let applets = applet::find_applets(files);
+
+        select_applet(applets, source.applet.as_deref())
+    } else {
+        load_archive(&source.filename, config.runtime.platform.as_deref())
+    }
+}
+
+fn run(args: RunArgs, config_args: &ConfigArgs) -> anyhow::Result<()> {
+    let filename = args.source.filename.clone();
+    let mut config = config_args.load(&filename)?;
+    let archive = load(args.source, &config)?;
+    let descriptor = describe(archive.as_ref(), &filename, &mut config)?;
+
+    if args.headless {
+        return start_headless(
+            archive,
+            &config,
+            args.script,
+            args.user_script,
+            args.duration,
+            args.screenshot,
+            args.report,
+        );
+    }
+
+    let others = args
+        .open
+        .into_iter()
+        .map(|filename| {
+            let mut config = config_args.load(&filename)?;
+            let source = SourceArgs {
+                filename: filename.clone(),
+                dump: false,
+                extract_to: None,
+                applet: None,
+            };
+            let archive = load(source, &config)?;
+            let descriptor = describe(archive.as_ref(), &filename, &mut config)?;
+
+            Ok((archive, config, descriptor))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let keymap = KeyMap::new(args.keymap.or_else(|| config.keymap.clone()), &config.keys)?;
+    let recorder = Recorder::new(args.record_format, args.record_to);
+
+    let movie = if let Some(path) = args.movie_record {
+        Some(MovieSession::Recording(MovieWriter::new(&path)?))
+    } else {
+        args.movie_play.map(|x| Movie::load(&x)).transpose()?.map(MovieSession::Playing)
+    };
+
+    // phones usually have no keyboard attached
+    let keypad = args.keypad || cfg!(target_os = "android");
+
+    let options = StartOptions {
+        user_script_path: args.user_script,
+        report_path: args.report,
+        keypad,
+        debugger: args.debugger,
+        fast_forward_speed: args.fast_forward_speed,
+        screenshot_dir: args.screenshot_to,
+        burst_duration: args.burst_duration,
+        config,
+        descriptor,
+    };
+
+    start(archive, others, keymap, recorder, movie, options)
+}
+
+fn info(source: SourceArgs, probe: Option<u64>, config_args: &ConfigArgs) -> anyhow::Result<()> {
+    let filename = source.filename.clone();
+    let mut config = config_args.load(&filename)?;
+    let archive = load(source, &config)?;
+    let descriptor = describe(archive.as_ref(), &filename, &mut config)?;
+    let files = archive.files()?;
+
+    println!("Platform: {}", archive.platform());
+    println!("ID: {}", archive.id());
+    println!("Name: {}", descriptor.name.as_deref().unwrap_or("-"));
+    println!("Vendor: {}", descriptor.vendor.as_deref().unwrap_or("-"));
+    println!("Main class: {}", descriptor.main_class_name.as_deref().unwrap_or("-"));
+    match descriptor.screen_size {
+        Some((width, height)) => println!("Screen size: {}x{}", width, height),
+        None => println!("Screen size: -"),
+    }
+    println!("Files: {}, {} bytes", files.len(), files.values().map(|x| x.len()).sum::<usize>());
+
+    if let Some(duration) = probe {
+        let platform = HeadlessPlatform::new(&config, &archive.id());
+        let runner = HeadlessRunner::new(&platform, &config, &archive.id(), None)?;
+
+        let mut app = archive.load_app(Box::new(platform))?;
+        config.apply_runtime(app.as_ref())?;
+        let report = app.report();
+
+        // failure is a part of the report here
+        let _ = runner.run(app.as_mut(), None, duration * 1000, None);
 
-    start(&Args::parse().filename)
+        print!("{}", report);
+    }
+
+    Ok(())
+}
+
+fn trace(source: SourceArgs, regions: Vec<TraceRegionConfig>, functions: bool, duration: u64, config_args: &ConfigArgs) -> anyhow::Result<()> {
+    let filename = source.filename.clone();
+    let mut config = config_args.load(&filename)?;
+    let archive = load(source, &config)?;
+    describe(archive.as_ref(), &filename, &mut config)?;
+
+    let trace = &mut config.runtime.trace;
+    trace.functions |= functions || (regions.is_empty() && trace.regions.is_empty());
+    trace.regions.extend(regions);
+
+    let platform = HeadlessPlatform::new(&config, &archive.id());
+    let runner = HeadlessRunner::new(&platform, &config, &archive.id(), None)?;
+
+    let mut app = archive.load_app(Box::new(platform))?;
+    config.apply_runtime(app.as_ref())?;
+
+    // printed on failure as well, which is when it's needed the most
+    let result = runner.run(app.as_mut(), None, duration * 1000, None);
+
+    if let Some(guest) = app.guest() {
+        for entry in guest.trace() {
+            println!("{}", entry);
+        }
+    }
+
+    result
+}
+
+// `start:end`, in hex with or without 0x
+fn parse_trace_region(value: &str) -> Result<TraceRegionConfig, String> {
+    let parse = |x: &str| u32::from_str_radix(x.trim().trim_start_matches("0x"), 16).map_err(|_| format!("Invalid address {}", x));
+
+    let (start, end) = value.split_once(':').ok_or("Expected start:end")?;
+    let (start, end) = (parse(start)?, parse(end)?);
+    if start >= end {
+        return Err("End should be after start".into());
+    }
+
+    Ok(TraceRegionConfig { start, end })
+}
+
+fn list(source: SourceArgs) -> anyhow::Result<()> {
+    let config = Config::load(&source.filename)?;
+    let archive = load(source, &config)?;
+
+    for (name, data) in archive.files()? {
+        println!("{:>10} {}", data.len(), name);
+    }
+
+    Ok(())
+}
+
+fn extract(source: SourceArgs, path: &Path) -> anyhow::Result<()> {
+    let config = Config::load(&source.filename)?;
+    let archive = load(source, &config)?;
+
+    for (name, data) in archive.files()? {
+        // names come from the archive, don't let them escape the target directory
+        let relative = Path::new(&name)
+            .components()
+            .filter(|x| matches!(x, Component::Normal(_)))
+            .collect::<PathBuf>();
+        let target = path.join(relative);
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if !name.ends_with('/') {
+            fs::write(&target, data)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn audio_devices() -> anyhow::Result<()> {
+    for name in AudioOutput::device_names()? {
+        println!("{}", name);
+    }
+
+    Ok(())
 }
 
-pub fn start(filename: &str) -> anyhow::Result<()> {
+// the jad is read again here, as archives keep only what their platform needs of it
+fn describe(archive: &dyn Archive, filename: &str, config: &mut Config) -> anyhow::Result<AppDescriptor> {
+    let jad = if filename.ends_with("jad") { Some(fs::read(filename)?) } else { None };
+    let descriptor = AppDescriptor::read(archive, jad.as_deref());
+
+    config.apply_descriptor(&descriptor);
+
+    Ok(descriptor)
+}
+
+fn load_dump(filename: &str, extract_to: Option<PathBuf>) -> anyhow::Result<Vec<Applet>> {
     let buf = fs::read(filename)?;
-    let archive: Box<dyn Archive> = if filename.ends_with("zip") {
-        let files = extract_zip(&buf).unwrap();
-
-        if KtfArchive::is_ktf_archive(&files) {
-            Box::new(KtfArchive::from_zip(files)?)
-        } else if LgtArchive::is_lgt_archive(&files) {
-            Box::new(LgtArchive::from_zip(files)?)
-        } else if SktArchive::is_skt_archive(&files) {
-            Box::new(SktArchive::from_zip(files)?)
-        } else {
-            anyhow::bail!("Unknown archive format");
+    let archives = dump::scan_dump(&buf);
+
+    if let Some(path) = extract_to {
+        dump::extract_dump(&archives, &path)?;
+    }
+
+    archives.into_iter().map(|x| Applet::from_data(x.name, x.data)).collect()
+}
+
+fn select_applet(applets: Vec<Applet>, name: Option<&str>) -> anyhow::Result<Box<dyn Archive>> {
+    if applets.len() > 1 && name.is_none() {
+        for applet in &applets {
+            eprintln!("{}", applet.name);
         }
-    } else if filename.ends_with("jad") {
+
+        anyhow::bail!("Found {} applets, select one with --applet", applets.len());
+    }
+
+    let applet = applets
+        .into_iter()
+        .find(|x| name.is_none() || Some(x.name.as_str()) == name)
+        .ok_or_else(|| anyhow::anyhow!("No applet found"))?;
+
+    applet.into_archive()
+}
+
+// `platform` overrides detection of jar files
+fn load_archive(filename: &str, platform: Option<&str>) -> anyhow::Result<Box<dyn Archive>> {
+    let buf = fs::read(filename)?;
+    let archive: Box<dyn Archive> = if filename.ends_with("jad") {
         let jar_filename = filename.replace(".jad", ".jar");
         let jar = fs::read(jar_filename)?;
 
         Box::new(J2MEArchive::from_jad_jar(buf, jar))
     } else if filename.ends_with("jar") {
         let filename_without_ext = filename.trim_end_matches(".jar");
+        let main_class_name = AppDescriptor::from_jar(&buf).main_class_name;
 
-        if KtfArchive::is_ktf_jar(&buf) {
-            Box::new(KtfArchive::from_jar(buf, filename_without_ext.into(), None, Default::default()))
-        } else if LgtArchive::is_lgt_jar(&buf) {
-            Box::new(LgtArchive::from_jar(buf, filename_without_ext, None))
-        } else if SktArchive::is_skt_jar(&buf) {
-            Box::new(SktArchive::from_jar(buf, filename_without_ext, None, Default::default()))
-        } else {
-            Box::new(J2MEArchive::from_jar(filename_without_ext.into(), buf))
+        let platform = match platform {
+            Some(x) => x,
+            None if KtfArchive::is_ktf_jar(&buf) => "KTF",
+            None if LgtArchive::is_lgt_jar(&buf) => "LGT",
+            None if SktArchive::is_skt_jar(&buf) => "SKT",
+            None => "J2ME",
+        };
+
+        match platform {
+            "KTF" => Box::new(KtfArchive::from_jar(
+                buf,
+                filename_without_ext.into(),
+                main_class_name,
+                Default::default(),
+            )),
+            "LGT" => Box::new(LgtArchive::from_jar(buf, filename_without_ext, main_class_name)),
+            "SKT" => Box::new(SktArchive::from_jar(buf, filename_without_ext, main_class_name, Default::default())),
+            "J2ME" => Box::new(J2MEArchive::from_jar(filename_without_ext.into(), buf)),
+            x => anyhow::bail!("Unknown platform {}", x),
         }
     } else {
         anyhow::bail!("Unknown file format");
     };
 
-    let window = WindowImpl::new(240, 320).unwrap(); // TODO hardcoded size
-    let platform = WieCliPlatform::new(&archive.id(), Box::new(window.handle()));
+    Ok(archive)
+}
+
+pub fn start_headless(
+    archive: Box<dyn Archive>,
+    config: &Config,
+    script_path: Option<PathBuf>,
+    user_script_path: Option<PathBuf>,
+    duration: u64,
+    screenshot_path: Option<PathBuf>,
+    report_path: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let platform = HeadlessPlatform::new(config, &archive.id());
+    let runner = HeadlessRunner::new(&platform, config, &archive.id(), script_path.as_deref())?;
 
     let mut app = archive.load_app(Box::new(platform))?;
+    config.apply_runtime(app.as_ref())?;
+    let report = app.report();
+
+    let user_script = user_script_path.map(|x| Script::new(&x)).transpose()?;
+
+    let result = runner.run(app.as_mut(), user_script, duration * 1000, screenshot_path.as_deref());
+
+    if let Some(path) = report_path {
+        fs::write(path, report.to_string())?;
+    }
+
+    result
+}
+
+pub fn start(
+    archive: Box<dyn Archive>,
+    others: Vec<(Box<dyn Archive>, Config, AppDescriptor)>,
+    mut keymap: KeyMap,
+    recorder: Recorder,
+    mut movie: Option<MovieSession>,
+    options: StartOptions,
+) -> anyhow::Result<()> {
+    let mut window = WindowImpl::new()?;
+    let screen_config = &options.config.screen;
+    let window_handle = window.create_window(
+        &window_title(archive.as_ref(), &options.descriptor),
+        window_icon(archive.as_ref()).as_deref(),
+        screen_config.width,
+        screen_config.height,
+        options.keypad,
+        &options.config.window,
+    )?;
+    let (width, height) = (window_handle.width(), window_handle.height());
+    let screen = RecordingScreen::new(window_handle.clone(), recorder.clone());
+    // inputs in the movie are timed on virtual clock, so it replays the same regardless of host speed
+    let clock = if movie.is_some() { Clock::new_virtual() } else { Clock::new_real() };
+    let platform = WieCliPlatform::new(&options.config, &archive.id(), Box::new(screen), recorder.clone(), clock.clone());
+    let mut savestates = SaveStates::new(&options.config.data_dir(), &archive.id(), SaveStates::rom_hash(archive.as_ref())?);
+    let crash_reporter = CrashReporter::new(&options.config, &archive.id());
+
+    let mut app = archive.load_app(Box::new(platform))?;
+    options.config.apply_runtime(app.as_ref())?;
+
+    let mut others = others
+        .into_iter()
+        .map(|(archive, config, descriptor)| Instance::new(&mut window, archive, &config, &descriptor, options.keypad))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut user_script = options.user_script_path.map(|x| Script::new(&x)).transpose()?;
 
     app.start()?;
+    if let Some(x) = &mut user_script {
+        x.attach(app.as_ref())?;
+    }
+
+    let report = app.report();
+    let mut debugger = options.debugger.then(|| Debugger::new(app.as_ref()));
+
+    let start_time = clock.now();
+    let mut show_overlay = false;
+    let mut frame_advance = false;
+    let mut text_input = false;
 
     let mut key_events = HashSet::new();
-    window.run(move |event| {
+    let mut gamepads = Gamepads::new();
+    window.run(move |index, event| {
+        if index > 0 {
+            return others[index - 1].handle_event(event, &keymap);
+        }
+
         match event {
-            WindowCallbackEvent::Update => app.tick()?,
+            WindowCallbackEvent::Update if clock.is_paused() && !frame_advance => {}
+            WindowCallbackEvent::Update => {
+                frame_advance = false;
+                keymap.reload_if_changed();
+
+                // polled even during movie playback, so presses made then don't come out afterwards
+                for (button, pressed) in gamepads.poll() {
+                    let keycode = match keymap.map_button(button) {
+                        Some(x) if !matches!(movie, Some(MovieSession::Playing(_))) => x,
+                        _ => continue,
+                    };
+
+                    if pressed {
+                        key_down(app.as_mut(), &mut movie, &mut key_events, &clock, keycode)?;
+                    } else {
+                        key_up(app.as_mut(), &mut movie, &mut key_events, &clock, keycode)?;
+                    }
+                }
+
+                if let Some(MovieSession::Playing(x)) = &mut movie {
+                    x.play(app.as_mut(), clock.now());
+
+                    if x.is_finished() {
+                        tracing::info!("Movie playback finished");
+                        movie = None;
+                    }
+                }
+
+                if let Some(x) = &mut user_script {
+                    x.update(app.as_mut())?;
+                }
+                if let Some(x) = &mut debugger {
+                    x.update(app.as_ref());
+                }
+
+                if let Err(x) = crash::tick(app.as_mut()) {
+                    crash_reporter.report(app.as_ref(), &x, &recorder.last_frame(), width, height);
+
+                    return Err(x);
+                }
+                clock.tick();
+
+                if show_overlay {
+                    window_handle.set_overlay(Some(overlay_lines(&app.telemetry(), clock.now() - start_time)))?;
+                }
+            }
             WindowCallbackEvent::Redraw => app.on_event(Event::Redraw),
-            WindowCallbackEvent::Keydown(x) => {
-                if let Some(keycode) = convert_key(x) {
-                    if !key_events.contains(&keycode) {
-                        app.on_event(Event::Keydown(keycode));
-                        key_events.insert(keycode);
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::F3)) => {
+                show_overlay = !show_overlay;
+                if !show_overlay {
+                    window_handle.set_overlay(None)?;
+                }
+            }
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::Tab)) => {
+                // movies are timed on ticks of virtual clock, changing the speed would break them
+                if movie.is_some() {
+                    tracing::warn!("Fast forward is not available with movie");
+                } else {
+                    clock.set_speed(options.fast_forward_speed);
+                    window_handle.set_frame_limit(false)?;
+                }
+            }
+            WindowCallbackEvent::Keyup(PhysicalKey::Code(WinitKeyCode::Tab)) => {
+                clock.set_speed(1);
+                window_handle.set_frame_limit(true)?;
+            }
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::F5)) => savestates.save(app.as_ref(), &recorder.last_frame(), width, height),
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::F6)) => savestates.previous_slot(),
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::F7)) => savestates.next_slot(),
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::F8)) => {
+                if let Some(state) = savestates.load(app.as_mut()) {
+                    if let Some(MovieSession::Recording(x)) = &mut movie {
+                        x.write(clock.now(), &MovieAction::LoadState(state))?;
                     }
                 }
             }
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::F9)) => recorder.toggle(width, height)?,
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::F10)) => {
+                clock.set_paused(!clock.is_paused());
+                tracing::info!("{}", if clock.is_paused() { "Paused" } else { "Resumed" });
+            }
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::F11)) => window_handle.toggle_fullscreen()?,
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::PrintScreen)) => {
+                if let Err(x) = save_screenshot(&options.screenshot_dir, &recorder.last_frame(), width, height) {
+                    tracing::error!("Failed to save screenshot: {}", x);
+                }
+            }
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::Pause)) => {
+                let directory = options.screenshot_dir.join(format!("burst_{}", screenshot::timestamp()));
+                let duration = Duration::from_secs(options.burst_duration);
+
+                if let Err(x) = recorder.start_burst(&directory, duration, width, height) {
+                    tracing::error!("Failed to start burst: {}", x);
+                }
+            }
+            // runs a single update while paused, pausing first if running
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::F12)) => {
+                clock.set_paused(true);
+                frame_advance = true;
+            }
+            // for entering names, typing with host IME instead of multi-tap
+            WindowCallbackEvent::Keydown(PhysicalKey::Code(WinitKeyCode::F4)) => {
+                text_input = !text_input;
+                window_handle.set_text_input(text_input)?;
+                tracing::info!("Text input {}", if text_input { "enabled" } else { "disabled" });
+            }
+            // inputs are ignored while a movie is playing
+            WindowCallbackEvent::Keydown(_)
+            | WindowCallbackEvent::Keyup(_)
+            | WindowCallbackEvent::VirtualKeydown(_)
+            | WindowCallbackEvent::VirtualKeyup(_)
+            | WindowCallbackEvent::PointerDown(_, _)
+            | WindowCallbackEvent::PointerMove(_, _)
+            | WindowCallbackEvent::PointerUp(_, _)
+            | WindowCallbackEvent::Text(_)
+                if matches!(movie, Some(MovieSession::Playing(_))) => {}
+            WindowCallbackEvent::Keydown(x) => {
+                if let Some(keycode) = keymap.map(x) {
+                    key_down(app.as_mut(), &mut movie, &mut key_events, &clock, keycode)?;
+                }
+            }
             WindowCallbackEvent::Keyup(x) => {
-                if let Some(keycode) = convert_key(x) {
-                    if key_events.contains(&keycode) {
-                        key_events.remove(&keycode);
-                    }
-                    app.on_event(Event::Keyup(keycode));
+                if let Some(keycode) = keymap.map(x) {
+                    key_up(app.as_mut(), &mut movie, &mut key_events, &clock, keycode)?;
+                }
+            }
+            // on-screen keypad gives key codes directly, bypassing the keymap
+            WindowCallbackEvent::VirtualKeydown(x) => key_down(app.as_mut(), &mut movie, &mut key_events, &clock, x)?,
+            WindowCallbackEvent::VirtualKeyup(x) => key_up(app.as_mut(), &mut movie, &mut key_events, &clock, x)?,
+            WindowCallbackEvent::PointerDown(x, y) => input(app.as_mut(), &mut movie, &clock, MovieAction::PointerDown(x, y))?,
+            WindowCallbackEvent::PointerMove(x, y) => input(app.as_mut(), &mut movie, &clock, MovieAction::PointerMove(x, y))?,
+            WindowCallbackEvent::PointerUp(x, y) => input(app.as_mut(), &mut movie, &clock, MovieAction::PointerUp(x, y))?,
+            WindowCallbackEvent::Text(x) => {
+                for x in x.chars() {
+                    input(app.as_mut(), &mut movie, &clock, MovieAction::Char(x))?;
                 }
             }
         }
 
         anyhow::Ok(())
-    })
-}
-
-fn convert_key(key: PhysicalKey) -> Option<KeyCode> {
-    match key {
-        PhysicalKey::Code(WinitKeyCode::Digit1) => Some(KeyCode::NUM1),
-        PhysicalKey::Code(WinitKeyCode::Digit2) => Some(KeyCode::NUM2),
-        PhysicalKey::Code(WinitKeyCode::Digit3) => Some(KeyCode::NUM3),
-        PhysicalKey::Code(WinitKeyCode::KeyQ) => Some(KeyCode::NUM4),
-        PhysicalKey::Code(WinitKeyCode::KeyW) => Some(KeyCode::NUM5),
-        PhysicalKey::Code(WinitKeyCode::KeyE) => Some(KeyCode::NUM6),
-        PhysicalKey::Code(WinitKeyCode::KeyA) => Some(KeyCode::NUM7),
-        PhysicalKey::Code(WinitKeyCode::KeyS) => Some(KeyCode::NUM8),
-        PhysicalKey::Code(WinitKeyCode::KeyD) => Some(KeyCode::NUM9),
-        PhysicalKey::Code(WinitKeyCode::KeyZ) => Some(KeyCode::STAR),
-        PhysicalKey::Code(WinitKeyCode::KeyX) => Some(KeyCode::NUM0),
-        PhysicalKey::Code(WinitKeyCode::KeyC) => Some(KeyCode::HASH),
-        PhysicalKey::Code(WinitKeyCode::Space) => Some(KeyCode::OK),
-        PhysicalKey::Code(WinitKeyCode::ArrowUp) => Some(KeyCode::UP),
-        PhysicalKey::Code(WinitKeyCode::ArrowDown) => Some(KeyCode::DOWN),
-        PhysicalKey::Code(WinitKeyCode::ArrowLeft) => Some(KeyCode::LEFT),
-        PhysicalKey::Code(WinitKeyCode::ArrowRight) => Some(KeyCode::RIGHT),
-        _ => None,
+    })?;
+
+    eprint!("{}", report);
+    if let Some(path) = options.report_path {
+        fs::write(path, report.to_string())?;
     }
+
+    Ok(())
+}
+
+fn save_screenshot(directory: &Path, frame: &[u32], width: u32, height: u32) -> anyhow::Result<()> {
+    anyhow::ensure!(!frame.is_empty(), "Nothing was painted yet");
+
+    fs::create_dir_all(directory)?;
+    let path = directory.join(format!("{}.png", screenshot::timestamp()));
+    screenshot::write_png(&path, frame, width, height)?;
+
+    tracing::info!("Saved screenshot to {}", path.display());
+
+    Ok(())
+}
+
+fn window_title(archive: &dyn Archive, descriptor: &AppDescriptor) -> String {
+    format!("WIE - {}", descriptor.name.clone().unwrap_or_else(|| archive.id()))
+}
+
+// broken icon shouldn't prevent the app from running
+fn window_icon(archive: &dyn Archive) -> Option<Box<dyn Image>> {
+    let data = archive.icon()?;
+
+    decode_image(&data).map_err(|x| tracing::warn!("Failed to load icon: {}", x)).ok()
+}
+
+// an app running alongside the main one, with its own window and system. only plays, without any of hotkeys
+struct Instance {
+    app: Box<dyn App>,
+    clock: Clock,
+    key_events: HashSet<KeyCode>,
+}
+
+impl Instance {
+    fn new(window: &mut WindowImpl, archive: Box<dyn Archive>, config: &Config, descriptor: &AppDescriptor, keypad: bool) -> anyhow::Result<Self> {
+        let handle = window.create_window(
+            &window_title(archive.as_ref(), descriptor),
+            window_icon(archive.as_ref()).as_deref(),
+            config.screen.width,
+            config.screen.height,
+            keypad,
+            &config.window,
+        )?;
+        let clock = Clock::new_real();
+        // never toggled, only to satisfy the platform
+        let recorder = Recorder::new(RecordingFormat::Gif, PathBuf::new());
+        let platform = WieCliPlatform::new(config, &archive.id(), Box::new(handle), recorder, clock.clone());
+
+        let mut app = archive.load_app(Box::new(platform))?;
+        config.apply_runtime(app.as_ref())?;
+        app.start()?;
+
+        Ok(Self {
+            app,
+            clock,
+            key_events: HashSet::new(),
+        })
+    }
+
+    fn handle_event(&mut self, event: WindowCallbackEvent, keymap: &KeyMap) -> anyhow::Result<()> {
+        let app = self.app.as_mut();
+
+        match event {
+            WindowCallbackEvent::Update => {
+                app.tick()?;
+                self.clock.tick();
+            }
+            WindowCallbackEvent::Redraw => app.on_event(Event::Redraw),
+            WindowCallbackEvent::Keydown(x) => {
+                if let Some(keycode) = keymap.map(x) {
+                    key_down(app, &mut None, &mut self.key_events, &self.clock, keycode)?;
+                }
+            }
+            WindowCallbackEvent::Keyup(x) => {
+                if let Some(keycode) = keymap.map(x) {
+                    key_up(app, &mut None, &mut self.key_events, &self.clock, keycode)?;
+                }
+            }
+            WindowCallbackEvent::VirtualKeydown(x) => key_down(app, &mut None, &mut self.key_events, &self.clock, x)?,
+            WindowCallbackEvent::VirtualKeyup(x) => key_up(app, &mut None, &mut self.key_events, &self.clock, x)?,
+            WindowCallbackEvent::PointerDown(x, y) => app.on_event(Event::PointerDown { x, y }),
+            WindowCallbackEvent::PointerMove(x, y) => app.on_event(Event::PointerMove { x, y }),
+            WindowCallbackEvent::PointerUp(x, y) => app.on_event(Event::PointerUp { x, y }),
+            WindowCallbackEvent::Text(x) => x.chars().for_each(|x| app.on_event(Event::Char(x))),
+        }
+
+        Ok(())
+    }
+}
+
+fn key_down(
+    app: &mut dyn App,
+    movie: &mut Option<MovieSession>,
+    key_events: &mut HashSet<KeyCode>,
+    clock: &Clock,
+    keycode: KeyCode,
+) -> anyhow::Result<()> {
+    if !key_events.contains(&keycode) {
+        if let Some(MovieSession::Recording(x)) = movie {
+            x.write(clock.now(), &MovieAction::Keydown(keycode))?;
+        }
+
+        app.on_event(Event::Keydown(keycode));
+        key_events.insert(keycode);
+    }
+
+    Ok(())
+}
+
+fn key_up(
+    app: &mut dyn App,
+    movie: &mut Option<MovieSession>,
+    key_events: &mut HashSet<KeyCode>,
+    clock: &Clock,
+    keycode: KeyCode,
+) -> anyhow::Result<()> {
+    key_events.remove(&keycode);
+    if let Some(MovieSession::Recording(x)) = movie {
+        x.write(clock.now(), &MovieAction::Keyup(keycode))?;
+    }
+
+    app.on_event(Event::Keyup(keycode));
+
+    Ok(())
+}
+
+// pointer and text input, which isn't tracked for repeats as keys are
+fn input(app: &mut dyn App, movie: &mut Option<MovieSession>, clock: &Clock, action: MovieAction) -> anyhow::Result<()> {
+    if let Some(MovieSession::Recording(x)) = movie {
+        x.write(clock.now(), &action)?;
+    }
+
+    if let Some(event) = action.into_event() {
+        app.on_event(event);
+    }
+
+    Ok(())
+}
+
+fn overlay_lines(telemetry: &Telemetry, elapsed: u64) -> Vec<String> {
+    let heap = match telemetry.heap_usage {
+        Some(x) => format!("Heap: {}K used, {}K free ({}K max)", x.used / 1024, x.free / 1024, x.largest_free / 1024),
+        None => "Heap: -".into(),
+    };
+
+    vec![
+        format!("Time: {}.{:03}s", elapsed / 1000, elapsed % 1000),
+        format!("Tasks: {}", telemetry.task_count),
+        heap,
+        format!("Voices: {}", telemetry.audio_voice_count),
+    ]
 }
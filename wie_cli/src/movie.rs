@@ -0,0 +1,227 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+use wie_backend::{App, Event, KeyCode};
+
+use crate::clock::TICK_MILLIS;
+
+pub enum MovieAction {
+    Keydown(KeyCode),
+    Keyup(KeyCode),
+    PointerDown(u32, u32),
+    PointerMove(u32, u32),
+    PointerUp(u32, u32),
+    Char(char),
+    // anchors the movie to a savestate, kept in the movie so it plays back in any run of the same app
+    LoadState(Vec<u8>),
+}
+
+impl MovieAction {
+    /// `None` on actions for the emulator rather than the app
+    pub fn into_event(self) -> Option<Event> {
+        Some(match self {
+            Self::Keydown(x) => Event::Keydown(x),
+            Self::Keyup(x) => Event::Keyup(x),
+            Self::PointerDown(x, y) => Event::PointerDown { x, y },
+            Self::PointerMove(x, y) => Event::PointerMove { x, y },
+            Self::PointerUp(x, y) => Event::PointerUp { x, y },
+            Self::Char(x) => Event::Char(x),
+            Self::LoadState(_) => return None,
+        })
+    }
+}
+
+struct MovieEntry {
+    time: u64,
+    action: MovieAction,
+}
+
+// timed input sequence on virtual time. each line is `<milliseconds> <down|up|press> <KEY>`, `<milliseconds> state <HEX STATE>`,
+// `<milliseconds> <pointerdown|pointermove|pointerup> <X>,<Y>` or `<milliseconds> char <HEX CODE POINT>`.
+// lines starting with `#` are comments.
+#[derive(Default)]
+pub struct Movie {
+    entries: VecDeque<MovieEntry>,
+}
+
+impl Movie {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?).map_err(|x| anyhow::anyhow!("{}: {}", path.display(), x))
+    }
+
+    pub fn parse(movie: &str) -> anyhow::Result<Self> {
+        let mut entries = Vec::new();
+
+        for (i, line) in movie.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parts = line.split_whitespace().collect::<Vec<_>>();
+            if parts.len() != 3 {
+                anyhow::bail!("Invalid line {}: {}", i + 1, line);
+            }
+
+            let time = parts[0].parse::<u64>()?;
+            let parse_key = || KeyCode::parse(parts[2]).ok_or_else(|| anyhow::anyhow!("Unknown key {} at line {}", parts[2], i + 1));
+            let parse_position = || -> anyhow::Result<(u32, u32)> {
+                let (x, y) = parts[2]
+                    .split_once(',')
+                    .ok_or_else(|| anyhow::anyhow!("Invalid position {} at line {}", parts[2], i + 1))?;
+
+                Ok((x.parse()?, y.parse()?))
+            };
+
+            match parts[1] {
+                "down" => entries.push(MovieEntry {
+                    time,
+                    action: MovieAction::Keydown(parse_key()?),
+                }),
+                "up" => entries.push(MovieEntry {
+                    time,
+                    action: MovieAction::Keyup(parse_key()?),
+                }),
+                "press" => {
+                    let key = parse_key()?;
+
+                    entries.push(MovieEntry {
+                        time,
+                        action: MovieAction::Keydown(key),
+                    });
+                    entries.push(MovieEntry {
+                        time: time + TICK_MILLIS * 2,
+                        action: MovieAction::Keyup(key),
+                    });
+                }
+                "state" => {
+                    let state = (0..parts[2].len())
+                        .step_by(2)
+                        .map(|x| u8::from_str_radix(parts[2].get(x..x + 2)?, 16).ok())
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(|| anyhow::anyhow!("Invalid state at line {}", i + 1))?;
+
+                    entries.push(MovieEntry {
+                        time,
+                        action: MovieAction::LoadState(state),
+                    });
+                }
+                "pointerdown" | "pointermove" | "pointerup" => {
+                    let (x, y) = parse_position()?;
+                    let action = match parts[1] {
+                        "pointerdown" => MovieAction::PointerDown(x, y),
+                        "pointermove" => MovieAction::PointerMove(x, y),
+                        _ => MovieAction::PointerUp(x, y),
+                    };
+
+                    entries.push(MovieEntry { time, action });
+                }
+                "char" => {
+                    let character = u32::from_str_radix(parts[2], 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| anyhow::anyhow!("Invalid character {} at line {}", parts[2], i + 1))?;
+
+                    entries.push(MovieEntry {
+                        time,
+                        action: MovieAction::Char(character),
+                    });
+                }
+                x => anyhow::bail!("Unknown action {} at line {}", x, i + 1),
+            }
+        }
+
+        // stable sort keeps the order of entries on the same time
+        entries.sort_by_key(|x| x.time);
+
+        Ok(Self { entries: entries.into() })
+    }
+
+    // a state which can't be loaded ends playback rather than the app, as the rest of the movie was recorded on it
+    pub fn play(&mut self, app: &mut dyn App, now: u64) {
+        while let Some(action) = self.pop_due(now) {
+            if let MovieAction::LoadState(x) = action {
+                if let Err(x) = app.load_state(&x) {
+                    tracing::error!("Failed to load the state the movie was recorded on, stopping playback: {}", x);
+
+                    self.entries.clear();
+                }
+            } else if let Some(event) = action.into_event() {
+                app.on_event(event);
+            }
+        }
+    }
+
+    fn pop_due(&mut self, now: u64) -> Option<MovieAction> {
+        if self.entries.front()?.time <= now {
+            self.entries.pop_front().map(|x| x.action)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// written line by line, so the movie survives a crash of the emulator
+pub struct MovieWriter {
+    file: File,
+}
+
+impl MovieWriter {
+    pub fn new(path: &Path) -> anyhow::Result<Self> {
+        Ok(Self { file: File::create(path)? })
+    }
+
+    pub fn write(&mut self, time: u64, action: &MovieAction) -> anyhow::Result<()> {
+        match action {
+            MovieAction::Keydown(x) => writeln!(self.file, "{} down {}", time, x.name())?,
+            MovieAction::Keyup(x) => writeln!(self.file, "{} up {}", time, x.name())?,
+            MovieAction::LoadState(x) => writeln!(self.file, "{} state {}", time, x.iter().map(|x| format!("{:02x}", x)).collect::<String>())?,
+            MovieAction::PointerDown(x, y) => writeln!(self.file, "{} pointerdown {},{}", time, x, y)?,
+            MovieAction::PointerMove(x, y) => writeln!(self.file, "{} pointermove {},{}", time, x, y)?,
+            MovieAction::PointerUp(x, y) => writeln!(self.file, "{} pointerup {},{}", time, x, y)?,
+            MovieAction::Char(x) => writeln!(self.file, "{} char {:x}", time, *x as u32)?,
+        }
+
+        Ok(())
+    }
+}
+
+pub enum MovieSession {
+    Recording(MovieWriter),
+    Playing(Movie),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::{Movie, MovieAction, MovieWriter};
+
+    #[test]
+    fn test_state_round_trip() -> anyhow::Result<()> {
+        let path = env::temp_dir().join(format!("wie_movie_test_{}.txt", std::process::id()));
+
+        let mut writer = MovieWriter::new(&path)?;
+        writer.write(100, &MovieAction::LoadState(vec![0x00, 0x7f, 0xff]))?;
+        drop(writer);
+
+        // the state is in the movie itself, not in a slot of this run
+        let mut movie = Movie::load(&path)?;
+        assert!(matches!(movie.pop_due(100), Some(MovieAction::LoadState(x)) if x == [0x00, 0x7f, 0xff]));
+        assert!(movie.is_finished());
+
+        assert!(Movie::parse("100 state 0g").is_err());
+
+        fs::remove_file(&path)?;
+
+        Ok(())
+    }
+}
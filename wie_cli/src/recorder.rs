@@ -0,0 +1,292 @@
+use std::{
+    cell::RefCell,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+
+use wie_backend::{
+    canvas::{ArgbPixel, Image, Rect, VecImageBuffer},
+    AnimationFormat, FrameRecorder, Screen,
+};
+
+use crate::screenshot;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum RecordingFormat {
+    Gif,
+    Ffmpeg,
+    /// Lossless, kept in memory until the recording stops
+    Apng,
+}
+
+enum VideoOutput {
+    Gif(gif::Encoder<BufWriter<File>>),
+    Ffmpeg(Child),
+    Apng(FrameRecorder, PathBuf),
+}
+
+struct Recording {
+    video: VideoOutput,
+    audio: Option<hound::WavWriter<BufWriter<File>>>,
+    audio_path: PathBuf,
+    width: u32,
+    height: u32,
+    started: Instant,
+    last_frame: Instant,
+}
+
+impl Recording {
+    fn new(format: RecordingFormat, path: &Path, width: u32, height: u32) -> anyhow::Result<Self> {
+        let video = match format {
+            RecordingFormat::Gif => {
+                let mut encoder = gif::Encoder::new(BufWriter::new(File::create(path.with_extension("gif"))?), width as _, height as _, &[])?;
+                encoder.set_repeat(gif::Repeat::Infinite)?;
+
+                VideoOutput::Gif(encoder)
+            }
+            RecordingFormat::Ffmpeg => {
+                let child = Command::new("ffmpeg")
+                    .args(["-y", "-use_wallclock_as_timestamps", "1", "-f", "rawvideo", "-pix_fmt", "bgra", "-s"])
+                    .arg(format!("{}x{}", width, height))
+                    .args(["-i", "-", "-pix_fmt", "yuv420p"])
+                    .arg(path.with_extension("mp4"))
+                    .stdin(Stdio::piped())
+                    .spawn()?;
+
+                VideoOutput::Ffmpeg(child)
+            }
+            RecordingFormat::Apng => VideoOutput::Apng(FrameRecorder::default(), path.with_extension("png")),
+        };
+
+        Ok(Self {
+            video,
+            audio: None,
+            audio_path: path.with_extension("wav"),
+            width,
+            height,
+            started: Instant::now(),
+            last_frame: Instant::now(),
+        })
+    }
+
+    fn push_frame(&mut self, frame: &[u32]) -> anyhow::Result<()> {
+        match &mut self.video {
+            VideoOutput::Gif(encoder) => {
+                let mut rgba = frame
+                    .iter()
+                    .flat_map(|x| [(x >> 16) as u8, (x >> 8) as u8, *x as u8, 0xff])
+                    .collect::<Vec<_>>();
+
+                let mut gif_frame = gif::Frame::from_rgba_speed(self.width as _, self.height as _, &mut rgba, 10);
+                gif_frame.delay = (self.last_frame.elapsed().as_millis() / 10) as _;
+
+                encoder.write_frame(&gif_frame)?;
+            }
+            VideoOutput::Ffmpeg(child) => {
+                let bytes = frame.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<_>>();
+
+                child.stdin.as_mut().unwrap().write_all(&bytes)?;
+            }
+            VideoOutput::Apng(recorder, _) => {
+                let image = VecImageBuffer::<ArgbPixel>::from_raw(self.width, self.height, frame.to_vec());
+                let time = wie_backend::Instant::from_epoch_millis(self.started.elapsed().as_millis() as _);
+
+                recorder.push(time, &image);
+            }
+        }
+        self.last_frame = Instant::now();
+
+        Ok(())
+    }
+
+    fn push_audio(&mut self, channel: u8, sampling_rate: u32, samples: &[i16]) -> anyhow::Result<()> {
+        if self.audio.is_none() {
+            let spec = hound::WavSpec {
+                channels: channel as _,
+                sample_rate: sampling_rate,
+                bits_per_sample: 16,
+                sample_format: hound::SampleFormat::Int,
+            };
+
+            self.audio = Some(hound::WavWriter::new(BufWriter::new(File::create(&self.audio_path)?), spec)?);
+        }
+
+        let audio = self.audio.as_mut().unwrap();
+        for sample in samples {
+            audio.write_sample(*sample)?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> anyhow::Result<()> {
+        match self.video {
+            VideoOutput::Ffmpeg(mut child) => {
+                drop(child.stdin.take());
+                child.wait()?;
+            }
+            VideoOutput::Apng(recorder, path) => fs::write(path, recorder.encode(AnimationFormat::Apng)?)?,
+            VideoOutput::Gif(_) => {}
+        }
+
+        if let Some(audio) = self.audio {
+            audio.finalize()?;
+        }
+
+        Ok(())
+    }
+}
+
+// every painted frame saved as png for a while, independent of the recording
+struct Burst {
+    directory: PathBuf,
+    width: u32,
+    height: u32,
+    until: Instant,
+    count: u32,
+}
+
+// shared between screen and audio sink, so both can feed the recording in progress
+#[derive(Clone)]
+pub struct Recorder {
+    format: RecordingFormat,
+    path: PathBuf,
+    recording: Rc<RefCell<Option<Recording>>>,
+    burst: Rc<RefCell<Option<Burst>>>,
+    last_frame: Rc<RefCell<Vec<u32>>>,
+}
+
+impl Recorder {
+    pub fn new(format: RecordingFormat, path: PathBuf) -> Self {
+        Self {
+            format,
+            path,
+            recording: Rc::new(RefCell::new(None)),
+            burst: Rc::new(RefCell::new(None)),
+            last_frame: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    pub fn toggle(&self, width: u32, height: u32) -> anyhow::Result<()> {
+        let mut recording = self.recording.borrow_mut();
+
+        if let Some(x) = recording.take() {
+            tracing::info!("Recording stopped");
+
+            x.finish()
+        } else {
+            tracing::info!("Recording to {}", self.path.display());
+
+            *recording = Some(Recording::new(self.format, &self.path, width, height)?);
+
+            Ok(())
+        }
+    }
+
+    pub fn start_burst(&self, directory: &Path, duration: Duration, width: u32, height: u32) -> anyhow::Result<()> {
+        fs::create_dir_all(directory)?;
+        tracing::info!("Saving every frame to {} for {} seconds", directory.display(), duration.as_secs());
+
+        *self.burst.borrow_mut() = Some(Burst {
+            directory: directory.into(),
+            width,
+            height,
+            until: Instant::now() + duration,
+            count: 0,
+        });
+
+        Ok(())
+    }
+
+    pub fn push_frame(&self, frame: &[u32]) {
+        *self.last_frame.borrow_mut() = frame.to_vec();
+
+        if let Some(x) = self.recording.borrow_mut().as_mut() {
+            if let Err(x) = x.push_frame(frame) {
+                tracing::error!("Failed to record frame: {}", x);
+            }
+        }
+
+        let mut burst = self.burst.borrow_mut();
+        if let Some(x) = burst.as_mut() {
+            if Instant::now() >= x.until {
+                tracing::info!("Saved {} frames to {}", x.count, x.directory.display());
+                *burst = None;
+
+                return;
+            }
+
+            let path = x.directory.join(format!("{:05}.png", x.count));
+            if let Err(e) = screenshot::write_png(&path, frame, x.width, x.height) {
+                tracing::error!("Failed to save frame: {}", e);
+            }
+            x.count += 1;
+        }
+    }
+
+    pub fn last_frame(&self) -> Vec<u32> {
+        self.last_frame.borrow().clone()
+    }
+
+    pub fn push_audio(&self, channel: u8, sampling_rate: u32, samples: &[i16]) {
+        if let Some(x) = self.recording.borrow_mut().as_mut() {
+            if let Err(x) = x.push_audio(channel, sampling_rate, samples) {
+                tracing::error!("Failed to record audio: {}", x);
+            }
+        }
+    }
+}
+
+pub struct RecordingScreen<T: Screen> {
+    screen: T,
+    recorder: Recorder,
+}
+
+impl<T: Screen> RecordingScreen<T> {
+    pub fn new(screen: T, recorder: Recorder) -> Self {
+        Self { screen, recorder }
+    }
+
+    fn push_frame(&self, image: &dyn Image) {
+        let frame = image
+            .colors()
+            .iter()
+            .map(|x| ((x.a as u32) << 24) | ((x.r as u32) << 16) | ((x.g as u32) << 8) | (x.b as u32))
+            .collect::<Vec<_>>();
+
+        self.recorder.push_frame(&frame);
+    }
+}
+
+impl<T: Screen> Screen for RecordingScreen<T> {
+    fn request_redraw(&self) -> anyhow::Result<()> {
+        self.screen.request_redraw()
+    }
+
+    fn paint(&mut self, image: &dyn Image) {
+        self.push_frame(image);
+
+        self.screen.paint(image)
+    }
+
+    // recorded frames are always whole
+    fn paint_rect(&mut self, image: &dyn Image, rect: Rect) {
+        self.push_frame(image);
+
+        self.screen.paint_rect(image, rect)
+    }
+
+    fn width(&self) -> u32 {
+        self.screen.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.screen.height()
+    }
+}
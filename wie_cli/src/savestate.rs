@@ -0,0 +1,241 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use wie_backend::{App, Archive};
+
+use crate::screenshot;
+
+const SLOT_COUNT: u32 = 10;
+// bumped when what the app writes into a state changes
+const FORMAT_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize)]
+pub struct SlotMetadata {
+    pub timestamp: u64,
+    pub width: u32,
+    pub height: u32,
+    // both missing in slots saved before they were recorded, which are refused
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub rom_hash: u32,
+}
+
+// a state is only loaded into the same build of the app it was saved from, as it refers to code and data in the binary
+pub struct SaveStates {
+    base_path: PathBuf,
+    slot: u32,
+    rom_hash: u32,
+}
+
+impl SaveStates {
+    pub fn new(data_dir: &Path, app_id: &str, rom_hash: u32) -> Self {
+        let base_path = data_dir.join(app_id).join("savestates");
+
+        Self {
+            base_path,
+            slot: 0,
+            rom_hash,
+        }
+    }
+
+    pub fn next_slot(&mut self) {
+        self.slot = (self.slot + 1) % SLOT_COUNT;
+
+        self.log_slot();
+    }
+
+    pub fn previous_slot(&mut self) {
+        self.slot = (self.slot + SLOT_COUNT - 1) % SLOT_COUNT;
+
+        self.log_slot();
+    }
+
+    // a failed save or load is reported but doesn't stop the app
+    pub fn save(&self, app: &dyn App, frame: &[u32], width: u32, height: u32) {
+        if let Err(x) = self.try_save(app, frame, width, height) {
+            tracing::error!("Failed to save state to slot {}: {}", self.slot, x);
+        }
+    }
+
+    // returns the loaded state, for a movie being recorded to keep
+    pub fn load(&self, app: &mut dyn App) -> Option<Vec<u8>> {
+        match self.load_slot(app, self.slot) {
+            Ok(x) => Some(x),
+            Err(x) => {
+                tracing::error!("Failed to load state from slot {}: {}", self.slot, x);
+
+                None
+            }
+        }
+    }
+
+    fn try_save(&self, app: &dyn App, frame: &[u32], width: u32, height: u32) -> anyhow::Result<()> {
+        // serialize first, so a state which can't be taken doesn't clobber the slot.
+        // the app refuses while native code waits on guest code it called
+        let state = app.save_state()?;
+
+        fs::create_dir_all(&self.base_path)?;
+        fs::write(self.path(self.slot, "state"), state)?;
+
+        let metadata = SlotMetadata {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            width,
+            height,
+            version: FORMAT_VERSION,
+            rom_hash: self.rom_hash,
+        };
+        fs::write(self.path(self.slot, "toml"), toml::to_string(&metadata)?)?;
+
+        if frame.len() == (width * height) as usize {
+            Self::write_thumbnail(self.path(self.slot, "gif"), frame, width, height)?;
+        }
+
+        tracing::info!("Saved state to slot {}", self.slot);
+
+        Ok(())
+    }
+
+    fn load_slot(&self, app: &mut dyn App, slot: u32) -> anyhow::Result<Vec<u8>> {
+        let path = self.path(slot, "state");
+        if !path.exists() {
+            anyhow::bail!("Slot {} is empty", slot);
+        }
+
+        let metadata: SlotMetadata = toml::from_str(&fs::read_to_string(self.path(slot, "toml"))?)?;
+        if metadata.version != FORMAT_VERSION {
+            anyhow::bail!("Slot {} has state format {}, expected {}", slot, metadata.version, FORMAT_VERSION);
+        }
+        if metadata.rom_hash != self.rom_hash {
+            anyhow::bail!("Slot {} was saved from another build of the app", slot);
+        }
+        let state = fs::read(path)?;
+        app.load_state(&state)?;
+
+        tracing::info!("Loaded state from slot {} saved at {}", slot, metadata.timestamp);
+
+        Ok(state)
+    }
+
+    // every file of the archive, so a state doesn't go into another version of the app with the same id
+    pub fn rom_hash(archive: &dyn Archive) -> anyhow::Result<u32> {
+        let mut hasher = crc32fast::Hasher::new();
+        for (name, data) in archive.files()? {
+            hasher.update(name.as_bytes());
+            hasher.update(&data);
+        }
+
+        Ok(hasher.finalize())
+    }
+
+    fn path(&self, slot: u32, extension: &str) -> PathBuf {
+        self.base_path.join(format!("slot{}.{}", slot, extension))
+    }
+
+    fn log_slot(&self) {
+        match fs::read_to_string(self.path(self.slot, "toml"))
+            .ok()
+            .and_then(|x| toml::from_str::<SlotMetadata>(&x).ok())
+        {
+            Some(x) if x.version != FORMAT_VERSION || x.rom_hash != self.rom_hash => {
+                tracing::info!("Selected slot {}, saved from another build or version", self.slot)
+            }
+            Some(x) => tracing::info!("Selected slot {}, saved at {}", self.slot, x.timestamp),
+            None => tracing::info!("Selected slot {}, empty", self.slot),
+        }
+    }
+
+    // half-sized preview of the screen at the time of saving
+    fn write_thumbnail(path: PathBuf, frame: &[u32], width: u32, height: u32) -> anyhow::Result<()> {
+        let (thumbnail_width, thumbnail_height) = (width / 2, height / 2);
+
+        let thumbnail = (0..thumbnail_height)
+            .flat_map(|y| (0..thumbnail_width).map(move |x| frame[(y * 2 * width + x * 2) as usize]))
+            .collect::<Vec<_>>();
+
+        screenshot::write_screenshot(&path, &thumbnail, thumbnail_width, thumbnail_height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use wie_backend::{App, CompatibilityReport, Event, GuestAccess, Telemetry};
+
+    use super::SaveStates;
+
+    #[derive(Default)]
+    struct TestApp {
+        state: Vec<u8>,
+        loaded: Option<Vec<u8>>,
+    }
+
+    impl App for TestApp {
+        fn start(&mut self) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn on_event(&mut self, _event: Event) {}
+
+        fn tick(&mut self) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn report(&self) -> CompatibilityReport {
+            CompatibilityReport::new()
+        }
+
+        fn save_state(&self) -> anyhow::Result<Vec<u8>> {
+            Ok(self.state.clone())
+        }
+
+        fn load_state(&mut self, state: &[u8]) -> anyhow::Result<()> {
+            self.loaded = Some(state.to_vec());
+
+            Ok(())
+        }
+
+        fn guest(&self) -> Option<Box<dyn GuestAccess>> {
+            None
+        }
+
+        fn telemetry(&self) -> Telemetry {
+            Telemetry::default()
+        }
+    }
+
+    #[test]
+    fn test_round_trip() -> anyhow::Result<()> {
+        let data_dir = env::temp_dir().join(format!("wie_savestate_test_{}", std::process::id()));
+
+        let mut app = TestApp {
+            state: vec![1, 2, 3, 4],
+            ..Default::default()
+        };
+        let savestates = SaveStates::new(&data_dir, "test", 0x1234);
+
+        savestates.save(&app, &[], 2, 2);
+        savestates.load_slot(&mut app, 0)?;
+        assert_eq!(app.loaded, Some(vec![1, 2, 3, 4]));
+
+        // a later run of the same build can load it
+        let mut other = TestApp::default();
+        SaveStates::new(&data_dir, "test", 0x1234).load_slot(&mut other, 0)?;
+        assert_eq!(other.loaded, Some(vec![1, 2, 3, 4]));
+
+        // but another build of the app can't
+        let mut other = TestApp::default();
+        assert!(SaveStates::new(&data_dir, "test", 0x5678).load_slot(&mut other, 0).is_err());
+        assert!(other.loaded.is_none());
+
+        fs::remove_dir_all(&data_dir)?;
+
+        Ok(())
+    }
+}
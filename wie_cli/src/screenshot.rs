@@ -0,0 +1,39 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub fn write_screenshot(path: &Path, frame: &[u32], width: u32, height: u32) -> anyhow::Result<()> {
+    encode_screenshot(BufWriter::new(File::create(path)?), frame, width, height)
+}
+
+// lossless, for bug reports where exact colors matter
+pub fn write_png(path: &Path, frame: &[u32], width: u32, height: u32) -> anyhow::Result<()> {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let rgb = frame.iter().flat_map(|x| [(x >> 16) as u8, (x >> 8) as u8, *x as u8]).collect::<Vec<_>>();
+    encoder.write_header()?.write_image_data(&rgb)?;
+
+    Ok(())
+}
+
+// milliseconds since epoch, to name screenshots taken by hand apart
+pub fn timestamp() -> u128 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_millis()).unwrap_or(0)
+}
+
+pub fn encode_screenshot<W: Write>(writer: W, frame: &[u32], width: u32, height: u32) -> anyhow::Result<()> {
+    let mut rgba = frame
+        .iter()
+        .flat_map(|x| [(x >> 16) as u8, (x >> 8) as u8, *x as u8, 0xff])
+        .collect::<Vec<_>>();
+
+    let mut encoder = gif::Encoder::new(writer, width as _, height as _, &[])?;
+    encoder.write_frame(&gif::Frame::from_rgba(width as _, height as _, &mut rgba))?;
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use std::{cell::RefCell, fs, path::Path, rc::Rc};
+
+use rhai::{Array, CallFnOptions, Engine, EvalAltResult, Scope, AST, INT};
+
+use wie_backend::{App, Event, GuestAccess, KeyCode};
+
+type GuestHandle = Rc<RefCell<Option<Box<dyn GuestAccess>>>>;
+
+// user supplied rhai script which can poke guest memory and inject key events.
+// if the script defines `fn on_frame(frame)`, it's called before every tick.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    events: Rc<RefCell<Vec<Event>>>,
+    guest: GuestHandle,
+    frame: INT,
+    has_on_frame: bool,
+}
+
+impl Script {
+    pub fn new(path: &Path) -> anyhow::Result<Self> {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let guest: GuestHandle = Rc::new(RefCell::new(None));
+
+        let mut engine = Engine::new();
+        Self::register_memory_api(&mut engine, &guest);
+        Self::register_event_api(&mut engine, &events);
+
+        let ast = engine
+            .compile(fs::read_to_string(path)?)
+            .map_err(|x| anyhow::anyhow!("{}: {}", path.display(), x))?;
+        let has_on_frame = ast.iter_functions().any(|x| x.name == "on_frame");
+
+        Ok(Self {
+            engine,
+            ast,
+            scope: Scope::new(),
+            events,
+            guest,
+            frame: 0,
+            has_on_frame,
+        })
+    }
+
+    // runs top level statements of the script, after the app is loaded so that guest memory is available
+    pub fn attach(&mut self, app: &dyn App) -> anyhow::Result<()> {
+        *self.guest.borrow_mut() = app.guest();
+
+        self.engine
+            .run_ast_with_scope(&mut self.scope, &self.ast)
+            .map_err(|x| anyhow::anyhow!("Script failed: {}", x))
+    }
+
+    pub fn update(&mut self, app: &mut dyn App) -> anyhow::Result<()> {
+        if self.has_on_frame {
+            self.engine
+                .call_fn_with_options::<()>(
+                    CallFnOptions::new().eval_ast(false),
+                    &mut self.scope,
+                    &self.ast,
+                    "on_frame",
+                    (self.frame,),
+                )
+                .map_err(|x| anyhow::anyhow!("Script failed on frame {}: {}", self.frame, x))?;
+        }
+        self.frame += 1;
+
+        for event in self.events.borrow_mut().drain(..) {
+            app.on_event(event);
+        }
+
+        Ok(())
+    }
+
+    fn register_memory_api(engine: &mut Engine, guest: &GuestHandle) {
+        fn with_guest<T>(guest: &GuestHandle, f: impl FnOnce(&mut dyn GuestAccess) -> anyhow::Result<T>) -> Result<T, Box<EvalAltResult>> {
+            let mut guest = guest.borrow_mut();
+            let guest = guest.as_mut().ok_or("Guest memory is not available on this platform")?;
+
+            f(guest.as_mut()).map_err(|x| x.to_string().into())
+        }
+
+        fn read(guest: &GuestHandle, address: INT, size: u32) -> Result<INT, Box<EvalAltResult>> {
+            let data = with_guest(guest, |x| x.read_memory(address as _, size))?;
+
+            Ok(data.iter().rev().fold(0, |acc, &x| (acc << 8) | x as INT))
+        }
+
+        fn write(guest: &GuestHandle, address: INT, size: u32, value: INT) -> Result<(), Box<EvalAltResult>> {
+            let data = value.to_le_bytes();
+
+            with_guest(guest, |x| x.write_memory(address as _, &data[..size as usize]))
+        }
+
+        for (name, size) in [("8", 1), ("16", 2), ("32", 4)] {
+            let cloned = guest.clone();
+            engine.register_fn(format!("read_u{}", name), move |address: INT| read(&cloned, address, size));
+            let cloned = guest.clone();
+            engine.register_fn(format!("write_u{}", name), move |address: INT, value: INT| {
+                write(&cloned, address, size, value)
+            });
+        }
+
+        let cloned = guest.clone();
+        engine.register_fn("call", move |address: INT, params: Array| {
+            let params = params.into_iter().map(|x| x.as_int().map(|x| x as u32)).collect::<Result<Vec<_>, _>>()?;
+
+            with_guest(&cloned, |x| x.call_function(address as _, &params))
+        });
+    }
+
+    fn register_event_api(engine: &mut Engine, events: &Rc<RefCell<Vec<Event>>>) {
+        fn parse_key(key: &str) -> Result<KeyCode, Box<EvalAltResult>> {
+            KeyCode::parse(key).ok_or_else(|| format!("Unknown key {}", key).into())
+        }
+
+        let cloned = events.clone();
+        engine.register_fn("key_down", move |key: &str| {
+            cloned.borrow_mut().push(Event::Keydown(parse_key(key)?));
+
+            Ok::<_, Box<EvalAltResult>>(())
+        });
+        let cloned = events.clone();
+        engine.register_fn("key_up", move |key: &str| {
+            cloned.borrow_mut().push(Event::Keyup(parse_key(key)?));
+
+            Ok::<_, Box<EvalAltResult>>(())
+        });
+    }
+}
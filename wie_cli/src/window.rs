@@ -1,21 +1,36 @@
 use alloc::rc::Rc;
 use core::{fmt::Debug, num::NonZeroU32};
+use std::collections::{HashMap, HashSet};
 
 use softbuffer::{Context, Surface};
 use winit::{
-    dpi::PhysicalSize,
-    event::{ElementState, Event, KeyEvent, WindowEvent},
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, Event, Ime, KeyEvent, MouseButton, Touch, TouchPhase, WindowEvent},
     event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopProxy, EventLoopWindowTarget},
     keyboard::PhysicalKey,
-    window::{Window as WinitWindow, WindowBuilder},
+    window::{Fullscreen, Icon, Window as WinitWindow, WindowBuilder},
 };
 
-use wie_backend::{canvas::Image, Screen};
+use wie_backend::{
+    canvas::{ArgbPixel, Canvas, Color, Image, ImageBufferCanvas, Rect, TextAlignment, VecImageBuffer},
+    KeyCode, Screen,
+};
+
+use crate::{config::WindowConfig, filter::Filter, keypad::Keypad};
+
+const OVERLAY_WIDTH: u32 = 120;
+const OVERLAY_LINE_HEIGHT: u32 = 14;
+const MOUSE_POINTER_ID: u64 = u64::MAX;
 
 #[derive(Debug)]
 pub enum WindowInternalEvent {
     RequestRedraw,
-    Paint(Vec<u32>),
+    // pixels of the rect only, row by row
+    Paint(Rect, Vec<u32>),
+    Overlay(Option<Vec<String>>),
+    FrameLimit(bool),
+    TextInput(bool),
+    ToggleFullscreen,
 }
 
 pub enum WindowCallbackEvent {
@@ -23,20 +38,51 @@ pub enum WindowCallbackEvent {
     Redraw,
     Keydown(PhysicalKey),
     Keyup(PhysicalKey),
+    // in screen coordinates, only reported inside the screen area
+    PointerDown(u32, u32),
+    PointerMove(u32, u32),
+    PointerUp(u32, u32),
+    // from the on-screen keypad
+    VirtualKeydown(KeyCode),
+    VirtualKeyup(KeyCode),
+    // committed text while text input is enabled
+    Text(String),
 }
 
+#[derive(Clone)]
 pub struct WindowHandle {
+    index: usize,
     width: u32,
     height: u32,
-    event_loop_proxy: EventLoopProxy<WindowInternalEvent>,
+    event_loop_proxy: EventLoopProxy<(usize, WindowInternalEvent)>,
 }
 
 impl WindowHandle {
     fn send_event(&self, event: WindowInternalEvent) -> anyhow::Result<()> {
-        self.event_loop_proxy.send_event(event)?;
+        self.event_loop_proxy.send_event((self.index, event))?;
 
         Ok(())
     }
+
+    // when disabled, updates run as fast as the host allows
+    pub fn set_frame_limit(&self, enabled: bool) -> anyhow::Result<()> {
+        self.send_event(WindowInternalEvent::FrameLimit(enabled))
+    }
+
+    // lines drawn on top left of the window after scaling, or `None` to hide
+    pub fn set_overlay(&self, lines: Option<Vec<String>>) -> anyhow::Result<()> {
+        self.send_event(WindowInternalEvent::Overlay(lines))
+    }
+
+    // enables host IME, and reports typed characters as text instead of key events
+    pub fn set_text_input(&self, enabled: bool) -> anyhow::Result<()> {
+        self.send_event(WindowInternalEvent::TextInput(enabled))
+    }
+
+    // borderless on the current monitor, the content is scaled and letterboxed as on any resize
+    pub fn toggle_fullscreen(&self) -> anyhow::Result<()> {
+        self.send_event(WindowInternalEvent::ToggleFullscreen)
+    }
 }
 
 impl Screen for WindowHandle {
@@ -53,51 +99,421 @@ impl Screen for WindowHandle {
     }
 
     fn paint(&mut self, image: &dyn Image) {
-        let data = image
-            .colors()
-            .iter()
-            .map(|x| ((x.a as u32) << 24) | ((x.r as u32) << 16) | ((x.g as u32) << 8) | (x.b as u32))
+        self.paint_rect(image, Rect::new(0, 0, image.width(), image.height()))
+    }
+
+    fn paint_rect(&mut self, image: &dyn Image, rect: Rect) {
+        let data = (rect.y as u32..rect.y as u32 + rect.height)
+            .flat_map(|y| (rect.x as u32..rect.x as u32 + rect.width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let color = image.get_pixel(x, y);
+
+                ((color.a as u32) << 24) | ((color.r as u32) << 16) | ((color.g as u32) << 8) | (color.b as u32)
+            })
             .collect::<Vec<_>>();
 
-        self.send_event(WindowInternalEvent::Paint(data)).unwrap()
+        self.send_event(WindowInternalEvent::Paint(rect, data)).unwrap()
     }
 }
 
-pub struct WindowImpl {
+// counted on presented frames, not on app ticks. there's no monotonic clock in std on wasm, so it's not counted there
+#[derive(Default)]
+struct FpsCounter {
+    fps: Option<u32>,
+    #[cfg(not(target_arch = "wasm32"))]
+    frames: u32,
+    #[cfg(not(target_arch = "wasm32"))]
+    since: Option<std::time::Instant>,
+}
+
+impl FpsCounter {
+    fn frame(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let now = std::time::Instant::now();
+            let since = *self.since.get_or_insert(now);
+
+            self.frames += 1;
+            if now - since >= std::time::Duration::from_secs(1) {
+                self.fps = Some(self.frames);
+                self.frames = 0;
+                self.since = Some(now);
+            }
+        }
+    }
+}
+
+enum PointerTarget {
+    Screen,
+    Key(KeyCode),
+}
+
+// tracks each mouse or touch pointer from press to release, so that a drag started on the screen stays on the screen,
+// and a key pressed on the keypad is released even if the finger slides off
+#[derive(Default)]
+struct PointerTracker {
+    pointers: HashMap<u64, PointerTarget>,
+    last_screen_position: (u32, u32),
+}
+
+impl PointerTracker {
+    // position is in content coordinates
+    fn press(&mut self, id: u64, position: (u32, u32), height: u32, keypad: Option<&Keypad>) -> Option<WindowCallbackEvent> {
+        let (x, y) = position;
+
+        if y < height {
+            // the app sees a single pointer, so additional touches on the screen are ignored
+            if self.pointers.values().any(|x| matches!(x, PointerTarget::Screen)) {
+                return None;
+            }
+
+            self.pointers.insert(id, PointerTarget::Screen);
+            self.last_screen_position = (x, y);
+
+            Some(WindowCallbackEvent::PointerDown(x, y))
+        } else {
+            let key = keypad?.key_at(x, y - height)?;
+            self.pointers.insert(id, PointerTarget::Key(key));
+
+            Some(WindowCallbackEvent::VirtualKeydown(key))
+        }
+    }
+
+    fn moved(&mut self, id: u64, position: (u32, u32), height: u32) -> Option<WindowCallbackEvent> {
+        let (x, y) = position;
+
+        match self.pointers.get(&id)? {
+            PointerTarget::Screen if y < height => {
+                self.last_screen_position = (x, y);
+
+                Some(WindowCallbackEvent::PointerMove(x, y))
+            }
+            _ => None,
+        }
+    }
+
+    // release is always delivered to end the drag, at the last position inside the screen
+    fn release(&mut self, id: u64) -> Option<WindowCallbackEvent> {
+        Some(match self.pointers.remove(&id)? {
+            PointerTarget::Screen => WindowCallbackEvent::PointerUp(self.last_screen_position.0, self.last_screen_position.1),
+            PointerTarget::Key(key) => WindowCallbackEvent::VirtualKeyup(key),
+        })
+    }
+
+    fn is_pressed(&self, id: u64) -> bool {
+        self.pointers.contains_key(&id)
+    }
+}
+
+struct WindowState {
     window: Rc<WinitWindow>,
-    event_loop: EventLoop<WindowInternalEvent>,
+    surface: Surface<Rc<WinitWindow>, Rc<WinitWindow>>,
+    // of the app's screen, before rotation
+    width: u32,
+    height: u32,
+    rotation: u32,
+    filter: Filter,
+    keypad: Option<Keypad>,
+    window_size: PhysicalSize<u32>,
+    last_frame: Vec<u32>,
+    // painted but not presented yet, waiting for the next redraw
+    frame_pending: bool,
+    // app asked to be redrawn
+    redraw_requested: bool,
+    overlay: Option<Vec<String>>,
+    cursor_position: Option<(u32, u32)>,
+    pointers: PointerTracker,
+    fps_counter: FpsCounter,
+    text_input: bool,
+    // keys whose press was taken as text, to drop their release too
+    text_keys: HashSet<PhysicalKey>,
 }
 
-impl WindowImpl {
-    pub fn new(width: u32, height: u32) -> anyhow::Result<Self> {
-        let event_loop = EventLoopBuilder::<WindowInternalEvent>::with_user_event().build()?;
+impl WindowState {
+    // size of the screen as shown in the window
+    fn display_size(&self) -> (u32, u32) {
+        WindowImpl::rotated_size(self.width, self.height, self.rotation)
+    }
+
+    fn content_height(&self) -> u32 {
+        self.display_size().1 + self.keypad.as_ref().map(|x| x.height()).unwrap_or(0)
+    }
+
+    fn present(&mut self) {
+        let overlay = WindowImpl::overlay_lines(self.fps_counter.fps, self.overlay.as_deref());
+        let frame = self.rotate_frame();
+
+        WindowImpl::present(
+            &mut self.surface,
+            self.window_size,
+            self.display_size().0,
+            &frame,
+            self.keypad.as_ref(),
+            overlay.as_deref(),
+            self.filter,
+        );
+    }
+
+    fn update_frame(&mut self, rect: Rect, data: &[u32]) {
+        let (x, y, width) = (rect.x as u32, rect.y as u32, rect.width as usize);
+
+        for (row, src) in data.chunks_exact(width.max(1)).enumerate() {
+            let start = ((y + row as u32) * self.width + x) as usize;
+
+            self.last_frame[start..start + width].copy_from_slice(src);
+        }
+    }
+
+    fn rotate_frame(&self) -> Vec<u32> {
+        if self.rotation == 0 {
+            return self.last_frame.clone();
+        }
+
+        let (display_width, display_height) = self.display_size();
+
+        (0..display_height)
+            .flat_map(|y| (0..display_width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let (x, y) = self.to_screen(x, y);
 
-        let size = PhysicalSize::new(width, height);
+                self.last_frame[(y * self.width + x) as usize]
+            })
+            .collect()
+    }
+
+    // maps display coordinates back to the app's screen
+    fn to_screen(&self, x: u32, y: u32) -> (u32, u32) {
+        match self.rotation {
+            90 => (y, self.height - 1 - x),
+            180 => (self.width - 1 - x, self.height - 1 - y),
+            270 => (self.width - 1 - y, x),
+            _ => (x, y),
+        }
+    }
+
+    fn event_to_screen(&self, event: WindowCallbackEvent) -> WindowCallbackEvent {
+        match event {
+            WindowCallbackEvent::PointerDown(x, y) => {
+                let (x, y) = self.to_screen(x, y);
+
+                WindowCallbackEvent::PointerDown(x, y)
+            }
+            WindowCallbackEvent::PointerMove(x, y) => {
+                let (x, y) = self.to_screen(x, y);
+
+                WindowCallbackEvent::PointerMove(x, y)
+            }
+            WindowCallbackEvent::PointerUp(x, y) => {
+                let (x, y) = self.to_screen(x, y);
+
+                WindowCallbackEvent::PointerUp(x, y)
+            }
+            x => x,
+        }
+    }
+}
 
-        let builder = WindowBuilder::new().with_inner_size(size).with_title("WIE");
+// every window shares one event loop, as winit allows only one per process
+pub struct WindowImpl {
+    event_loop: EventLoop<(usize, WindowInternalEvent)>,
+    windows: Vec<WindowState>,
+}
 
-        let window = builder.build(&event_loop)?;
+impl WindowImpl {
+    pub fn new() -> anyhow::Result<Self> {
+        let event_loop = EventLoopBuilder::<(usize, WindowInternalEvent)>::with_user_event().build()?;
 
         Ok(Self {
-            window: Rc::new(window),
             event_loop,
+            windows: Vec::new(),
         })
     }
 
-    pub fn handle(&self) -> WindowHandle {
-        WindowHandle {
-            width: self.window.inner_size().width,
-            height: self.window.inner_size().height,
+    // window starts at `scale` times of the content size, and can be resized down to the content size
+    pub fn create_window(
+        &mut self,
+        title: &str,
+        icon: Option<&dyn Image>,
+        width: u32,
+        height: u32,
+        keypad: bool,
+        config: &WindowConfig,
+    ) -> anyhow::Result<WindowHandle> {
+        let (display_width, display_height) = Self::rotated_size(width, height, config.rotation);
+
+        let keypad = keypad.then(|| Keypad::new(display_width));
+        let content_height = display_height + keypad.as_ref().map(|x| x.height()).unwrap_or(0);
+
+        let size = PhysicalSize::new(display_width, content_height);
+        let scale = config.scale;
+
+        let builder = WindowBuilder::new()
+            .with_inner_size(PhysicalSize::new(display_width * scale, content_height * scale))
+            .with_min_inner_size(size)
+            .with_resizable(true)
+            .with_title(title)
+            .with_window_icon(icon.map(Self::window_icon).transpose()?);
+
+        let window = Rc::new(builder.build(&self.event_loop)?);
+
+        let context = Context::new(window.clone()).unwrap();
+        let mut surface = Surface::new(&context, window.clone()).unwrap();
+
+        let window_size = window.inner_size();
+        surface
+            .resize(NonZeroU32::new(window_size.width).unwrap(), NonZeroU32::new(window_size.height).unwrap())
+            .unwrap();
+
+        self.windows.push(WindowState {
+            window,
+            surface,
+            width,
+            height,
+            rotation: config.rotation,
+            filter: config.filter,
+            keypad,
+            window_size,
+            last_frame: vec![0; (width * height) as usize],
+            frame_pending: false,
+            redraw_requested: false,
+            overlay: None,
+            cursor_position: None,
+            pointers: PointerTracker::default(),
+            fps_counter: FpsCounter::default(),
+            text_input: false,
+            text_keys: HashSet::new(),
+        });
+
+        Ok(WindowHandle {
+            index: self.windows.len() - 1,
+            width,
+            height,
             event_loop_proxy: self.event_loop.create_proxy(),
+        })
+    }
+
+    fn window_icon(image: &dyn Image) -> anyhow::Result<Icon> {
+        let rgba = image.colors().iter().flat_map(|x| [x.r, x.g, x.b, x.a]).collect::<Vec<_>>();
+
+        Ok(Icon::from_rgba(rgba, image.width(), image.height())?)
+    }
+
+    fn rotated_size(width: u32, height: u32, rotation: u32) -> (u32, u32) {
+        if rotation % 180 == 90 {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    fn screen_transform(window_size: PhysicalSize<u32>, width: u32, height: u32, filter: Filter) -> (f64, u32, u32) {
+        let scale = (window_size.width as f64 / width as f64).min(window_size.height as f64 / height as f64);
+        let scale = if filter.integer_scale() { scale.floor() } else { scale }.max(1.0);
+
+        let offset_x = window_size.width.saturating_sub((width as f64 * scale) as u32) / 2;
+        let offset_y = window_size.height.saturating_sub((height as f64 * scale) as u32) / 2;
+
+        (scale, offset_x, offset_y)
+    }
+
+    // content is the screen plus the keypad below it, if any
+    fn window_to_content(
+        position: PhysicalPosition<f64>,
+        window_size: PhysicalSize<u32>,
+        width: u32,
+        height: u32,
+        filter: Filter,
+    ) -> Option<(u32, u32)> {
+        let (scale, offset_x, offset_y) = Self::screen_transform(window_size, width, height, filter);
+
+        let x = ((position.x - offset_x as f64) / scale).floor() as i64;
+        let y = ((position.y - offset_y as f64) / scale).floor() as i64;
+
+        if (0..width as i64).contains(&x) && (0..height as i64).contains(&y) {
+            Some((x as u32, y as u32))
+        } else {
+            None
+        }
+    }
+
+    // scales the content by largest factor fitting in the window, integer unless the filter allows otherwise, and letterboxes the rest
+    fn present(
+        surface: &mut Surface<Rc<WinitWindow>, Rc<WinitWindow>>,
+        window_size: PhysicalSize<u32>,
+        width: u32,
+        screen_data: &[u32],
+        keypad: Option<&Keypad>,
+        overlay: Option<&[String]>,
+        filter: Filter,
+    ) {
+        let mut buffer = surface.buffer_mut().unwrap();
+        buffer.fill(0);
+
+        let data = match keypad {
+            Some(x) => [screen_data, x.pixels()].concat(),
+            None => screen_data.to_vec(),
+        };
+        let height = data.len() as u32 / width;
+
+        let (scale, offset_x, offset_y) = Self::screen_transform(window_size, width, height, filter);
+        let scaled_width = ((width as f64 * scale) as u32).min(window_size.width);
+        let scaled_height = ((height as f64 * scale) as u32).min(window_size.height);
+
+        for y in 0..scaled_height {
+            let dst_row_start = ((y + offset_y) * window_size.width + offset_x) as usize;
+
+            for x in 0..scaled_width {
+                buffer[dst_row_start + x as usize] = filter.sample(&data, width, height, scale, x, y);
+            }
+        }
+
+        if let Some(lines) = overlay {
+            let overlay = Self::render_overlay(lines);
+
+            for y in 0..overlay.height().min(window_size.height) {
+                for x in 0..overlay.width().min(window_size.width) {
+                    let color = overlay.get_pixel(x, y);
+                    buffer[(y * window_size.width + x) as usize] = ((color.r as u32) << 16) | ((color.g as u32) << 8) | (color.b as u32);
+                }
+            }
         }
+
+        buffer.present().unwrap();
+    }
+
+    fn overlay_lines(fps: Option<u32>, lines: Option<&[String]>) -> Option<Vec<String>> {
+        lines.map(|x| fps.map(|fps| format!("FPS: {}", fps)).into_iter().chain(x.iter().cloned()).collect())
     }
 
-    fn callback<C, E>(event: WindowCallbackEvent, elwt: &EventLoopWindowTarget<WindowInternalEvent>, callback: &mut C)
+    fn render_overlay(lines: &[String]) -> impl Image {
+        let height = lines.len() as u32 * OVERLAY_LINE_HEIGHT + 4;
+        let mut canvas = ImageBufferCanvas::new(VecImageBuffer::<ArgbPixel>::new(OVERLAY_WIDTH, height));
+
+        canvas.fill_rect(
+            0,
+            0,
+            OVERLAY_WIDTH,
+            height,
+            Color {
+                a: 0xff,
+                r: 0xe0,
+                g: 0xe0,
+                b: 0xe0,
+            },
+        );
+        for (i, line) in lines.iter().enumerate() {
+            canvas.draw_text(line, 2, 2 + i as u32 * OVERLAY_LINE_HEIGHT, TextAlignment::Left);
+        }
+
+        canvas.into_inner()
+    }
+
+    fn callback<C, E>(index: usize, event: WindowCallbackEvent, elwt: &EventLoopWindowTarget<(usize, WindowInternalEvent)>, callback: &mut C)
     where
-        C: FnMut(WindowCallbackEvent) -> Result<(), E> + 'static,
+        C: FnMut(usize, WindowCallbackEvent) -> Result<(), E> + 'static,
         E: Debug,
     {
-        let result = callback(event);
+        let result = callback(index, event);
         if let Err(x) = result {
             tracing::error!(target: "wie", "{:?}", x);
 
@@ -105,79 +521,203 @@ impl WindowImpl {
         }
     }
 
-    pub fn run<C, E>(self, mut callback: C) -> anyhow::Result<()>
+    fn update_all<C, E>(windows: &[WindowState], elwt: &EventLoopWindowTarget<(usize, WindowInternalEvent)>, callback: &mut C)
     where
-        C: FnMut(WindowCallbackEvent) -> Result<(), E> + 'static,
+        C: FnMut(usize, WindowCallbackEvent) -> Result<(), E> + 'static,
         E: Debug,
     {
-        let context = Context::new(self.window.clone()).unwrap();
-        let mut surface = Surface::new(&context, self.window.clone()).unwrap();
-
-        let size = self.window.inner_size();
+        for index in 0..windows.len() {
+            Self::callback(index, WindowCallbackEvent::Update, elwt, callback);
+        }
+    }
 
-        surface
-            .resize(NonZeroU32::new(size.width).unwrap(), NonZeroU32::new(size.height).unwrap())
-            .unwrap();
+    // callback receives index of the window in creation order. closing any window ends the loop
+    pub fn run<C, E>(self, mut callback: C) -> anyhow::Result<()>
+    where
+        C: FnMut(usize, WindowCallbackEvent) -> Result<(), E> + 'static,
+        E: Debug,
+    {
+        let mut windows = self.windows;
+        let mut frame_limit = true;
 
         #[cfg(not(target_arch = "wasm32"))]
         let mut last_update = std::time::Instant::now();
 
         self.event_loop.run(move |event, elwt| match event {
-            Event::UserEvent(x) => match x {
-                WindowInternalEvent::RequestRedraw => {
-                    self.window.request_redraw();
-                }
-                WindowInternalEvent::Paint(data) => {
-                    let mut buffer = surface.buffer_mut().unwrap();
-                    buffer.copy_from_slice(&data);
+            Event::UserEvent((index, x)) => {
+                let state = &mut windows[index];
 
-                    buffer.present().unwrap();
-                }
-            },
+                match x {
+                    WindowInternalEvent::RequestRedraw => {
+                        state.redraw_requested = true;
+                        state.window.request_redraw();
+                    }
+                    // paints in between are merged, and presented together on the redraw winit paces to the display
+                    WindowInternalEvent::Paint(rect, data) => {
+                        state.update_frame(rect, &data);
 
-            Event::WindowEvent { event, .. } => match event {
-                WindowEvent::CloseRequested => elwt.exit(),
-                WindowEvent::KeyboardInput {
-                    event:
-                        KeyEvent {
-                            physical_key,
-                            state: ElementState::Pressed,
-                            ..
-                        },
-                    ..
-                } => {
-                    Self::callback(WindowCallbackEvent::Keydown(physical_key), elwt, &mut callback);
-                }
-                WindowEvent::KeyboardInput {
-                    event:
-                        KeyEvent {
-                            physical_key,
-                            state: ElementState::Released,
-                            ..
-                        },
-                    ..
-                } => {
-                    Self::callback(WindowCallbackEvent::Keyup(physical_key), elwt, &mut callback);
+                        if !state.frame_pending {
+                            state.frame_pending = true;
+                            state.window.request_redraw();
+                        }
+                    }
+                    WindowInternalEvent::Overlay(lines) => {
+                        state.overlay = lines;
+                    }
+                    WindowInternalEvent::FrameLimit(enabled) => {
+                        frame_limit = enabled;
+                    }
+                    WindowInternalEvent::TextInput(enabled) => {
+                        state.text_input = enabled;
+                        state.text_keys.clear();
+                        state.window.set_ime_allowed(enabled);
+                    }
+                    WindowInternalEvent::ToggleFullscreen => {
+                        let fullscreen = state.window.fullscreen().is_none().then_some(Fullscreen::Borderless(None));
+
+                        state.window.set_fullscreen(fullscreen);
+                    }
                 }
-                WindowEvent::RedrawRequested => {
-                    Self::callback(WindowCallbackEvent::Redraw, elwt, &mut callback);
+            }
+
+            Event::WindowEvent { event, window_id } => {
+                let index = match windows.iter().position(|x| x.window.id() == window_id) {
+                    Some(x) => x,
+                    None => return,
+                };
+                let state = &mut windows[index];
+                // pointers are tracked in display coordinates, and mapped to the screen when reported
+                let ((width, height), content_height) = (state.display_size(), state.content_height());
+
+                match event {
+                    WindowEvent::CloseRequested => elwt.exit(),
+                    WindowEvent::Resized(size) => {
+                        if let (Some(new_width), Some(new_height)) = (NonZeroU32::new(size.width), NonZeroU32::new(size.height)) {
+                            state.surface.resize(new_width, new_height).unwrap();
+                            state.window_size = size;
+
+                            state.present();
+                        }
+                    }
+                    // characters not going through IME composition, like latin letters, come as key events
+                    WindowEvent::KeyboardInput {
+                        event:
+                            KeyEvent {
+                                physical_key,
+                                state: ElementState::Pressed,
+                                text: Some(text),
+                                ..
+                            },
+                        ..
+                    } if state.text_input && !text.chars().any(char::is_control) => {
+                        state.text_keys.insert(physical_key);
+
+                        Self::callback(index, WindowCallbackEvent::Text(text.to_string()), elwt, &mut callback);
+                    }
+                    WindowEvent::KeyboardInput {
+                        event:
+                            KeyEvent {
+                                physical_key,
+                                state: ElementState::Pressed,
+                                repeat: false,
+                                ..
+                            },
+                        ..
+                    } => {
+                        Self::callback(index, WindowCallbackEvent::Keydown(physical_key), elwt, &mut callback);
+                    }
+                    WindowEvent::KeyboardInput {
+                        event:
+                            KeyEvent {
+                                physical_key,
+                                state: ElementState::Released,
+                                ..
+                            },
+                        ..
+                    } => {
+                        if !state.text_keys.remove(&physical_key) {
+                            Self::callback(index, WindowCallbackEvent::Keyup(physical_key), elwt, &mut callback);
+                        }
+                    }
+                    WindowEvent::Ime(Ime::Commit(text)) if state.text_input => {
+                        Self::callback(index, WindowCallbackEvent::Text(text), elwt, &mut callback);
+                    }
+                    WindowEvent::CursorMoved { position, .. } => {
+                        state.cursor_position = Self::window_to_content(position, state.window_size, width, content_height, state.filter);
+
+                        if let Some(event) = state.cursor_position.and_then(|x| state.pointers.moved(MOUSE_POINTER_ID, x, height)) {
+                            Self::callback(index, state.event_to_screen(event), elwt, &mut callback);
+                        }
+                    }
+                    WindowEvent::MouseInput {
+                        state: button_state,
+                        button: MouseButton::Left,
+                        ..
+                    } => {
+                        // press outside of the content is ignored
+                        let event = match (button_state, state.cursor_position) {
+                            (ElementState::Pressed, Some(x)) if !state.pointers.is_pressed(MOUSE_POINTER_ID) => {
+                                state.pointers.press(MOUSE_POINTER_ID, x, height, state.keypad.as_ref())
+                            }
+                            (ElementState::Released, _) => state.pointers.release(MOUSE_POINTER_ID),
+                            _ => None,
+                        };
+
+                        if let Some(event) = event {
+                            Self::callback(index, state.event_to_screen(event), elwt, &mut callback);
+                        }
+                    }
+                    WindowEvent::Touch(Touch { phase, location, id, .. }) => {
+                        let position = Self::window_to_content(location, state.window_size, width, content_height, state.filter);
+
+                        let event = match (phase, position) {
+                            (TouchPhase::Started, Some(x)) => state.pointers.press(id, x, height, state.keypad.as_ref()),
+                            (TouchPhase::Moved, Some(x)) => state.pointers.moved(id, x, height),
+                            (TouchPhase::Ended | TouchPhase::Cancelled, _) => state.pointers.release(id),
+                            _ => None,
+                        };
+
+                        if let Some(event) = event {
+                            Self::callback(index, state.event_to_screen(event), elwt, &mut callback);
+                        }
+                    }
+                    WindowEvent::RedrawRequested => {
+                        let frame_pending = state.frame_pending;
+                        if frame_pending {
+                            state.frame_pending = false;
+                            state.fps_counter.frame();
+                            state.present();
+                        }
+
+                        // redraws not coming from a paint, like when the window is exposed, are passed to the app
+                        if state.redraw_requested || !frame_pending {
+                            state.redraw_requested = false;
+
+                            Self::callback(index, WindowCallbackEvent::Redraw, elwt, &mut callback);
+                        }
+                    }
+                    _ => {}
                 }
-                _ => {}
-            },
+            }
             Event::AboutToWait => {
                 #[cfg(target_arch = "wasm32")]
                 {
-                    Self::callback(WindowCallbackEvent::Update, elwt, &mut callback);
-                    elwt.set_control_flow(ControlFlow::Wait);
+                    Self::update_all(&windows, elwt, &mut callback);
+                    elwt.set_control_flow(if frame_limit { ControlFlow::Wait } else { ControlFlow::Poll });
                 }
                 #[cfg(not(target_arch = "wasm32"))]
                 {
                     let now = std::time::Instant::now();
                     let next_update = last_update + std::time::Duration::from_millis(16);
-                    if now < next_update {
+                    if !frame_limit {
+                        Self::update_all(&windows, elwt, &mut callback);
+
+                        last_update = now;
+                        elwt.set_control_flow(ControlFlow::Poll);
+                    } else if now < next_update {
                         elwt.set_control_flow(ControlFlow::WaitUntil(next_update));
                     } else {
-                        Self::callback(WindowCallbackEvent::Update, elwt, &mut callback);
+                        Self::update_all(&windows, elwt, &mut callback);
 
                         last_update = now;
                         let next_update = last_update + std::time::Duration::from_millis(16);
@@ -1,12 +1,14 @@
+use alloc::vec::Vec;
 use core::mem::size_of;
 
 use bytemuck::{Pod, Zeroable};
 
-use wie_util::{read_generic, round_up, write_generic};
+use wie_backend::HeapUsage;
+use wie_util::{read_generic, round_up, write_generic, ByteRead, ByteWrite};
 
 use crate::{
     core::{ArmCore, HEAP_BASE},
-    ArmCoreResult,
+    ArmCoreError, ArmCoreResult,
 };
 
 const HEAP_SIZE: u32 = 0x1000000;
@@ -33,12 +35,15 @@ impl AllocationHeader {
     }
 }
 
-// crude, slow allocator.. we need to refactor it to faster one
+const HEADER_SIZE: u32 = size_of::<AllocationHeader>() as u32;
+
+// first fit over blocks laid out back to back from HEAP_BASE, each starting with its header.
+// adjacent free blocks are merged when freeing and while searching
 pub struct Allocator {}
 
 impl Allocator {
     pub fn init(core: &mut ArmCore) -> ArmCoreResult<(u32, u32)> {
-        core.map(HEAP_BASE, HEAP_SIZE)?;
+        core.map(HEAP_BASE, HEAP_SIZE, "heap", "allocator")?;
 
         let header = AllocationHeader::new(HEAP_SIZE, false);
 
@@ -48,56 +53,198 @@ impl Allocator {
     }
 
     pub fn alloc(core: &mut ArmCore, size: u32) -> ArmCoreResult<u32> {
-        let alloc_size = round_up(size as usize + size_of::<AllocationHeader>(), 4) as u32;
-
-        let address = Self::find_address(core, alloc_size).unwrap();
+        let alloc_size = Self::block_size(size)?;
 
-        let previous_header: AllocationHeader = read_generic(core, address)?;
+        let address = Self::find_address(core, alloc_size)?.ok_or(ArmCoreError::OutOfMemory)?;
 
-        let header = AllocationHeader::new(alloc_size, true);
-        write_generic(core, address, header)?;
+        let header: AllocationHeader = read_generic(core, address)?;
+        Self::split(core, address, header.size(), alloc_size)?;
 
-        // write next
-        if previous_header.size() > alloc_size {
-            let next_header = AllocationHeader::new(previous_header.size() - alloc_size, false);
-            write_generic(core, address + alloc_size, next_header)?;
-        }
+        tracing::trace!("Allocated {:#x} bytes at {:#x}", size, address + HEADER_SIZE);
 
-        tracing::trace!("Allocated {:#x} bytes at {:#x}", size, address + size_of::<AllocationHeader>() as u32);
-
-        Ok(address + size_of::<AllocationHeader>() as u32)
+        Ok(address + HEADER_SIZE)
     }
 
     pub fn free(core: &mut ArmCore, address: u32) -> ArmCoreResult<()> {
-        let base_address = address - size_of::<AllocationHeader>() as u32;
-
         tracing::trace!("Freeing {:#x}", address);
 
-        let header: AllocationHeader = read_generic(core, base_address)?;
-        assert!(header.in_use());
+        let (base_address, header) = Self::allocated_header(core, address)?;
 
         let header = AllocationHeader::new(header.size(), false);
         write_generic(core, base_address, header)?;
 
+        Self::merge_following(core, base_address)?;
+
         Ok(())
     }
 
-    fn find_address(core: &ArmCore, request_size: u32) -> Option<u32> {
+    // grows in place if the following blocks are free, otherwise moves the data to a new allocation
+    pub fn realloc(core: &mut ArmCore, address: u32, size: u32) -> ArmCoreResult<u32> {
+        if address == 0 {
+            return Self::alloc(core, size);
+        }
+
+        let alloc_size = Self::block_size(size)?;
+
+        let (base_address, header) = Self::allocated_header(core, address)?;
+        let old_data_size = header.size() - HEADER_SIZE;
+
+        let available = Self::merge_following(core, base_address)?;
+        if available >= alloc_size {
+            Self::split(core, base_address, available, alloc_size)?;
+
+            tracing::trace!("Reallocated {:#x} to {:#x} bytes in place", address, size);
+
+            return Ok(address);
+        }
+
+        let new_address = match Self::alloc(core, size) {
+            Ok(x) => x,
+            Err(e) => {
+                // give back the free blocks merged above, the original allocation stays as it was
+                Self::split(core, base_address, available, header.size())?;
+
+                return Err(e);
+            }
+        };
+        let data = core.read_bytes(address, old_data_size.min(size))?;
+        core.write_bytes(new_address, &data)?;
+        Self::free(core, address)?;
+
+        tracing::trace!("Reallocated {:#x} to {:#x} bytes at {:#x}", address, size, new_address);
+
+        Ok(new_address)
+    }
+
+    // walks the whole heap
+    pub fn stats(core: &ArmCore) -> ArmCoreResult<HeapUsage> {
+        let mut usage = HeapUsage::default();
+        let mut free_run = 0;
+
         let mut cursor = HEAP_BASE;
-        loop {
-            let header: AllocationHeader = read_generic(core, cursor).ok()?;
-            if !header.in_use() && header.size() >= request_size {
-                return Some(cursor);
+        while cursor < HEAP_BASE + HEAP_SIZE {
+            let header: AllocationHeader = read_generic(core, cursor)?;
+            if header.size() == 0 {
+                break;
+            }
+
+            if header.in_use() {
+                usage.used += header.size();
+                free_run = 0;
             } else {
+                usage.free += header.size();
+                // free blocks next to each other are merged before being handed out
+                free_run += header.size();
+                usage.largest_free = usage.largest_free.max(free_run.saturating_sub(HEADER_SIZE));
+            }
+
+            cursor += header.size();
+        }
+
+        Ok(usage)
+    }
+
+    // blocks in use as (address, size), excluding headers
+    pub fn allocations(core: &ArmCore) -> ArmCoreResult<Vec<(u32, u32)>> {
+        let mut result = Vec::new();
+
+        let mut cursor = HEAP_BASE;
+        while cursor < HEAP_BASE + HEAP_SIZE {
+            let header: AllocationHeader = read_generic(core, cursor)?;
+            if header.size() == 0 {
+                break;
+            }
+
+            if header.in_use() {
+                result.push((cursor + HEADER_SIZE, header.size() - HEADER_SIZE));
+            }
+
+            cursor += header.size();
+        }
+
+        Ok(result)
+    }
+
+    // header of the block `address` was allocated at, failing if it's not a block in use
+    fn allocated_header(core: &ArmCore, address: u32) -> ArmCoreResult<(u32, AllocationHeader)> {
+        if address < HEAP_BASE + HEADER_SIZE || address >= HEAP_BASE + HEAP_SIZE {
+            return Err(ArmCoreError::InvalidFree(address));
+        }
+
+        let base_address = address - HEADER_SIZE;
+        let header: AllocationHeader = read_generic(core, base_address)?;
+        if !header.in_use() {
+            return Err(ArmCoreError::InvalidFree(address));
+        }
+
+        Ok((base_address, header))
+    }
+
+    // sizes are guest controlled, anything not fitting the heap would wrap around or collide with the in use flag
+    fn block_size(size: u32) -> ArmCoreResult<u32> {
+        match size.checked_add(HEADER_SIZE) {
+            Some(x) if x <= HEAP_SIZE => Ok(round_up(x as usize, 4) as u32),
+            _ => Err(ArmCoreError::OutOfMemory),
+        }
+    }
+
+    fn find_address(core: &mut ArmCore, request_size: u32) -> ArmCoreResult<Option<u32>> {
+        let mut cursor = HEAP_BASE;
+        while cursor < HEAP_BASE + HEAP_SIZE {
+            let header: AllocationHeader = read_generic(core, cursor)?;
+            if header.size() == 0 {
+                break;
+            }
+
+            if header.in_use() {
                 cursor += header.size();
+                continue;
             }
 
-            if cursor >= HEAP_BASE + HEAP_SIZE {
+            let size = Self::merge_following(core, cursor)?;
+            if size >= request_size {
+                return Ok(Some(cursor));
+            }
+
+            cursor += size;
+        }
+
+        Ok(None)
+    }
+
+    // marks the first `alloc_size` bytes of the block in use, and the rest of it a free block
+    fn split(core: &mut ArmCore, address: u32, block_size: u32, alloc_size: u32) -> ArmCoreResult<()> {
+        write_generic(core, address, AllocationHeader::new(alloc_size, true))?;
+
+        if block_size > alloc_size {
+            let rest = address + alloc_size;
+            write_generic(core, rest, AllocationHeader::new(block_size - alloc_size, false))?;
+
+            Self::merge_following(core, rest)?;
+        }
+
+        Ok(())
+    }
+
+    // absorbs free blocks right after the block at `address`, keeping its in use flag. returns the new size
+    fn merge_following(core: &mut ArmCore, address: u32) -> ArmCoreResult<u32> {
+        let header: AllocationHeader = read_generic(core, address)?;
+        let mut size = header.size();
+
+        while address + size < HEAP_BASE + HEAP_SIZE {
+            let next: AllocationHeader = read_generic(core, address + size)?;
+            if next.in_use() || next.size() == 0 {
                 break;
             }
+
+            size += next.size();
+        }
+
+        if size != header.size() {
+            write_generic(core, address, AllocationHeader::new(size, header.in_use()))?;
         }
 
-        None
+        Ok(size)
     }
 }
 
@@ -105,12 +252,14 @@ impl Allocator {
 mod tests {
     use alloc::boxed::Box;
 
-    use crate::{Allocator, ArmCore, ArmCoreResult};
+    use wie_util::{ByteRead, ByteWrite};
+
+    use crate::{Allocator, ArmCore, ArmCoreConfig, ArmCoreError, ArmCoreResult};
 
     use test_utils::TestPlatform;
 
     pub fn test_arm_core() -> ArmCore {
-        ArmCore::new(wie_backend::System::new(Box::new(TestPlatform), Box::new(()))).unwrap()
+        ArmCore::new(wie_backend::System::new(Box::new(TestPlatform), Box::new(())), ArmCoreConfig::default()).unwrap()
     }
 
     #[test]
@@ -124,4 +273,120 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_free_coalesce() -> ArmCoreResult<()> {
+        let mut core = test_arm_core();
+
+        Allocator::init(&mut core)?;
+        let first = Allocator::alloc(&mut core, 0x10)?;
+        let second = Allocator::alloc(&mut core, 0x10)?;
+        Allocator::alloc(&mut core, 0x10)?;
+
+        Allocator::free(&mut core, second)?;
+        Allocator::free(&mut core, first)?;
+
+        // both freed blocks are merged to fit a larger one
+        assert_eq!(Allocator::alloc(&mut core, 0x20)?, first);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_realloc() -> ArmCoreResult<()> {
+        let mut core = test_arm_core();
+
+        Allocator::init(&mut core)?;
+        let address = Allocator::alloc(&mut core, 4)?;
+        core.write_bytes(address, &[1, 2, 3, 4])?;
+
+        // nothing after it yet, grows in place
+        assert_eq!(Allocator::realloc(&mut core, address, 0x100)?, address);
+
+        Allocator::alloc(&mut core, 4)?;
+        let moved = Allocator::realloc(&mut core, address, 0x200)?;
+        assert_ne!(moved, address);
+        assert_eq!(core.read_bytes(moved, 4)?, [1, 2, 3, 4]);
+
+        let stats = Allocator::stats(&core)?;
+        assert_eq!(stats.used + stats.free, 0x1000000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_double_free() -> ArmCoreResult<()> {
+        let mut core = test_arm_core();
+
+        Allocator::init(&mut core)?;
+        let address = Allocator::alloc(&mut core, 0x10)?;
+
+        Allocator::free(&mut core, address)?;
+        assert!(matches!(Allocator::free(&mut core, address), Err(ArmCoreError::InvalidFree(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_realloc_failure_keeps_block() -> ArmCoreResult<()> {
+        let mut core = test_arm_core();
+
+        Allocator::init(&mut core)?;
+        let address = Allocator::alloc(&mut core, 4)?;
+        let next = Allocator::alloc(&mut core, 0x100)?;
+        Allocator::alloc(&mut core, 4)?;
+        Allocator::free(&mut core, next)?;
+
+        assert!(matches!(
+            Allocator::realloc(&mut core, address, 0x2000000),
+            Err(ArmCoreError::OutOfMemory)
+        ));
+
+        // the free block after it wasn't swallowed by the failed realloc
+        assert_eq!(Allocator::allocations(&core)?[0], (address, 4));
+        assert_eq!(Allocator::alloc(&mut core, 0x100)?, next);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_alloc_huge() -> ArmCoreResult<()> {
+        let mut core = test_arm_core();
+
+        Allocator::init(&mut core)?;
+
+        // would wrap to an empty block
+        assert!(matches!(Allocator::alloc(&mut core, 0xfffffffe), Err(ArmCoreError::OutOfMemory)));
+        // would set the in use flag of the size
+        assert!(matches!(Allocator::alloc(&mut core, 0x80000000), Err(ArmCoreError::OutOfMemory)));
+        assert!(matches!(Allocator::alloc(&mut core, 0x1000000), Err(ArmCoreError::OutOfMemory)));
+
+        let address = Allocator::alloc(&mut core, 4)?;
+        assert!(matches!(
+            Allocator::realloc(&mut core, address, 0xfffffffe),
+            Err(ArmCoreError::OutOfMemory)
+        ));
+        assert_eq!(Allocator::allocations(&core)?, [(address, 4)]);
+
+        let stats = Allocator::stats(&core)?;
+        assert_eq!(stats.used + stats.free, 0x1000000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot() -> ArmCoreResult<()> {
+        let mut core = test_arm_core();
+
+        Allocator::init(&mut core)?;
+        Allocator::alloc(&mut core, 10)?;
+
+        let snapshot = core.snapshot()?;
+        let address = Allocator::alloc(&mut core, 10)?;
+
+        core.restore_snapshot(&snapshot)?;
+        assert_eq!(Allocator::alloc(&mut core, 10)?, address);
+
+        Ok(())
+    }
 }
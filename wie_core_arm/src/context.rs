@@ -1,3 +1,4 @@
+use alloc::{vec, vec::Vec};
 use core::clone::Clone;
 
 #[derive(Clone)]
@@ -19,10 +20,13 @@ pub struct ArmCoreContext {
     pub lr: u32,
     pub pc: u32,
     pub cpsr: u32,
+    /// VFP single precision registers, two for each double
+    pub vfp: [u32; 32],
+    pub fpscr: u32,
 }
 
 impl ArmCoreContext {
-    pub fn new(stack_base: u32) -> Self {
+    pub fn new(stack_base: u32, stack_size: u32) -> Self {
         Self {
             r0: 0,
             r1: 0,
@@ -37,10 +41,63 @@ impl ArmCoreContext {
             sl: 0,
             fp: 0,
             ip: 0,
-            sp: stack_base + 0x1000,
+            sp: stack_base + stack_size,
             lr: 0,
             pc: 0,
             cpsr: 0x10, // USR32
+            vfp: [0; 32],
+            fpscr: 0,
         }
     }
+
+    pub(crate) const WORD_COUNT: usize = 50;
+
+    // every register in the order of the fields, for snapshots
+    pub(crate) fn to_words(&self) -> Vec<u32> {
+        let mut result = vec![
+            self.r0, self.r1, self.r2, self.r3, self.r4, self.r5, self.r6, self.r7, self.r8, self.sb, self.sl, self.fp, self.ip, self.sp, self.lr,
+            self.pc, self.cpsr,
+        ];
+        result.extend_from_slice(&self.vfp);
+        result.push(self.fpscr);
+
+        result
+    }
+
+    // `words` is WORD_COUNT long, as returned by to_words
+    pub(crate) fn from_words(words: &[u32]) -> Self {
+        Self {
+            r0: words[0],
+            r1: words[1],
+            r2: words[2],
+            r3: words[3],
+            r4: words[4],
+            r5: words[5],
+            r6: words[6],
+            r7: words[7],
+            r8: words[8],
+            sb: words[9],
+            sl: words[10],
+            fp: words[11],
+            ip: words[12],
+            sp: words[13],
+            lr: words[14],
+            pc: words[15],
+            cpsr: words[16],
+            vfp: words[17..49].try_into().unwrap(),
+            fpscr: words[49],
+        }
+    }
+
+    // continues at `address` with bit 0 selecting thumb state, like BX
+    pub fn branch(&mut self, address: u32) {
+        const CPSR_THUMB: u32 = 1 << 5;
+
+        self.pc = address & !1;
+        self.cpsr = if address & 1 == 1 {
+            self.cpsr | CPSR_THUMB
+        } else {
+            self.cpsr & !CPSR_THUMB
+        };
+    }
 }
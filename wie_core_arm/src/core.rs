@@ -1,27 +1,136 @@
-use alloc::{borrow::ToOwned, boxed::Box, collections::BTreeMap, format, rc::Rc, string::String, vec::Vec};
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    format,
+    rc::Rc,
+    string::String,
+    vec,
+    vec::Vec,
+};
 use core::{cell::RefCell, fmt::Debug, mem::size_of};
 
-use wie_backend::{AsyncCallable, System};
-use wie_util::{read_generic, round_up, ByteRead, ByteWrite};
+use bytemuck::Pod;
+
+use wie_backend::{AsyncCallable, GuestAccess, ImportKind, ImportStatus, System, TaskHandle};
+use wie_util::{read_generic, read_null_terminated_string, round_up, write_generic, ByteRead, ByteWrite};
 
 use crate::{
     context::ArmCoreContext,
-    engine::{ArmEngine, ArmRegister, MemoryPermission},
+    engine::{ArmEngine, ArmRegister, Endianness, MemoryPermission},
     function::{EmulatedFunction, RegisteredFunction, RegisteredFunctionHolder, ResultWriter},
     future::SpawnFuture,
-    ArmCoreResult,
+    memory_map::MemoryMap,
+    symbols::SymbolTable,
+    tracer::Tracer,
+    ArmCoreError, ArmCoreResult,
 };
 
 const FUNCTIONS_BASE: u32 = 0x71000000;
+const PAUSE_POLL_MILLIS: u64 = 16;
+const DEFAULT_INSTRUCTION_BUDGET: u32 = 100000;
 pub const RUN_FUNCTION_LR: u32 = 0x7f000000;
 pub const HEAP_BASE: u32 = 0x40000000;
 pub const PEB_BASE: u32 = 0x7ff00000;
+// task stacks, each one above an unmapped guard page so running past the end stops the guest instead of corrupting what is below.
+// both are a whole page of the engine
+const STACKS_BASE: u32 = 0x50000000;
+const STACKS_END: u32 = 0x70000000;
+const STACK_GUARD_SIZE: u32 = 0x10000;
+// java threads run compiled code on it, which recurses deeper than native callbacks do
+pub(crate) const TASK_STACK_SIZE: u32 = 0x10000;
+// changed whenever the layout changes
+const SNAPSHOT_MAGIC: &[u8; 4] = b"WRM3";
 
 struct ArmCoreInner {
     engine: Box<dyn ArmEngine>,
+    config: ArmCoreConfig,
     system: System,
     functions: BTreeMap<u32, Rc<Box<dyn RegisteredFunction>>>,
     functions_count: usize,
+    instruction_budget: u32,
+    // shared with RunStop so calls don't copy the name
+    function_names: BTreeMap<u32, Rc<str>>,
+    // whether each native function was recorded to the report, by registration order
+    functions_reported: Vec<bool>,
+    symbols: SymbolTable,
+    memory_map: MemoryMap,
+    debug: DebugState,
+    // registers of suspended tasks by their stack base
+    task_contexts: BTreeMap<u32, ArmCoreContext>,
+    // run_function calls in progress by task id, more than one means native code is waiting on guest code it called
+    guest_calls: BTreeMap<usize, u32>,
+    stack_count: u32,
+    free_stacks: Vec<u32>,
+    // of every segment loaded, so a snapshot isn't restored over another binary
+    image_hasher: crc32fast::Hasher,
+}
+
+#[derive(Default)]
+struct DebugState {
+    breakpoints: BTreeSet<u32>,
+    paused: bool,
+    stepping: bool,
+    // registers where the guest stopped, as other tasks may switch the context while paused
+    paused_context: Option<ArmCoreContext>,
+    tracer: Tracer,
+}
+
+// why the guest stopped running, with what run_some has to await before continuing
+enum RunStop {
+    Native(u32, Rc<Box<dyn RegisteredFunction>>, Option<Rc<str>>, System),
+    Budget(System),
+    Other,
+}
+
+// counted while alive, so it is counted off even if the task is dropped in the middle of the call
+struct GuestCall {
+    core: ArmCore,
+    task_id: usize,
+}
+
+impl GuestCall {
+    fn new(core: ArmCore) -> Self {
+        let task_id = {
+            let mut inner = core.inner.borrow_mut();
+
+            let task_id = inner.system.current_task().map(|x| x.id()).unwrap_or(0);
+            *inner.guest_calls.entry(task_id).or_insert(0) += 1;
+
+            task_id
+        };
+
+        Self { core, task_id }
+    }
+}
+
+impl Drop for GuestCall {
+    fn drop(&mut self) {
+        let mut inner = self.core.inner.borrow_mut();
+
+        if let Some(x) = inner.guest_calls.get_mut(&self.task_id) {
+            *x -= 1;
+            if *x == 0 {
+                inner.guest_calls.remove(&self.task_id);
+            }
+        }
+    }
+}
+
+/// How the guest binary was built. The engine only implements ARMv4T, and whether code is arm or thumb follows bit 0 of addresses like BX
+#[derive(Clone, Copy, Default)]
+pub struct ArmCoreConfig {
+    /// Only little endian is accepted for now
+    pub endianness: Endianness,
+}
+
+pub struct Segment<'a> {
+    pub name: &'a str,
+    pub address: u32,
+    pub data: &'a [u8],
+    /// Mapped size, the rest after `data` is zero filled like bss
+    pub size: usize,
+    pub permission: MemoryPermission,
 }
 
 #[derive(Clone)]
@@ -30,17 +139,37 @@ pub struct ArmCore {
 }
 
 impl ArmCore {
-    pub fn new(system: System) -> ArmCoreResult<Self> {
-        let mut engine = Box::new(crate::engine::Armv4tEmuEngine::new());
+    pub fn new(system: System, config: ArmCoreConfig) -> ArmCoreResult<Self> {
+        // the engine runs big endian code, but structures we read and write with read_generic and write_generic are still little endian
+        if config.endianness == Endianness::Big {
+            return Err(ArmCoreError::UnsupportedConfig);
+        }
+
+        let mut engine = Box::new(crate::engine::Armv4tEmuEngine::new(config.endianness));
 
         engine.mem_map(FUNCTIONS_BASE, 0x1000, MemoryPermission::ReadExecute);
         engine.reg_write(ArmRegister::Cpsr, 0x10); // USR32
 
+        let mut memory_map = MemoryMap::default();
+        memory_map.add("native functions", FUNCTIONS_BASE, 0x1000, MemoryPermission::ReadExecute, "core");
+
         let inner = ArmCoreInner {
             engine,
+            config,
             system,
             functions: BTreeMap::new(),
             functions_count: 0,
+            instruction_budget: DEFAULT_INSTRUCTION_BUDGET,
+            function_names: BTreeMap::new(),
+            functions_reported: Vec::new(),
+            symbols: SymbolTable::default(),
+            memory_map,
+            debug: DebugState::default(),
+            task_contexts: BTreeMap::new(),
+            guest_calls: BTreeMap::new(),
+            stack_count: 0,
+            free_stacks: Vec::new(),
+            image_hasher: crc32fast::Hasher::new(),
         };
 
         Ok(Self {
@@ -48,43 +177,152 @@ impl ArmCore {
         })
     }
 
-    pub fn load(&mut self, data: &[u8], address: u32, map_size: usize) -> ArmCoreResult<()> {
+    pub fn load(&mut self, owner: &str, segments: &[Segment]) -> ArmCoreResult<()> {
         let mut inner = self.inner.borrow_mut();
 
-        inner
-            .engine
-            .mem_map(address, round_up(map_size, 0x1000), MemoryPermission::ReadWriteExecute);
-        inner.engine.mem_write(address, data)?;
+        for segment in segments {
+            if segment.data.len() > segment.size {
+                return Err(ArmCoreError::InvalidSegment(segment.address));
+            }
+
+            inner.engine.mem_map(segment.address, round_up(segment.size, 0x1000), segment.permission);
+            inner.engine.mem_write(segment.address, segment.data)?;
+            inner.image_hasher.update(&segment.address.to_le_bytes());
+            inner.image_hasher.update(segment.data);
+            inner
+                .memory_map
+                .add(segment.name, segment.address, segment.size as u32, segment.permission, owner);
+        }
 
         Ok(())
     }
 
-    #[allow(clippy::await_holding_refcell_ref)] // We manually drop RefMut https://github.com/rust-lang/rust-clippy/issues/6353
+    async fn wait_while_paused(&mut self) {
+        loop {
+            let mut system = {
+                let mut inner = self.inner.borrow_mut();
+                if !inner.debug.paused {
+                    break;
+                }
+                if inner.debug.paused_context.is_none() {
+                    inner.debug.paused_context = Some(Self::save_context_inner(&*inner.engine));
+                }
+
+                inner.system.clone()
+            };
+
+            let until = system.now() + PAUSE_POLL_MILLIS;
+            system.sleep(until).await;
+        }
+    }
+
+    // native functions may run guest code again through run_function, and other tasks may use the core while we are suspended,
+    // so the inner state is only borrowed in synchronous steps between await points and never held across one
     async fn run_some(&mut self) -> ArmCoreResult<()> {
+        self.wait_while_paused().await;
+
+        match self.run_guest()? {
+            RunStop::Native(address, function, name, mut system) => {
+                if let Some(x) = &name {
+                    tracing::trace!("Calling {}", x);
+                }
+
+                let result = function.call(self, &mut system).await;
+                if result.is_err() {
+                    tracing::error!("Native function {} failed", name.as_deref().unwrap_or("<Unknown>"));
+                } else if let Some(x) = &name {
+                    if self.mark_reported(address) {
+                        system.report().record(ImportKind::Function, x, ImportStatus::Implemented);
+                    }
+                }
+
+                result?;
+            }
+            // budget ran out in guest code, let other tasks, timers and rendering run before continuing
+            RunStop::Budget(system) => system.yield_now().await,
+            RunStop::Other => {}
+        }
+
+        Ok(())
+    }
+
+    // true on the first call for the function at `address`
+    fn mark_reported(&mut self, address: u32) -> bool {
+        let mut inner = self.inner.borrow_mut();
+
+        let index = ((address - FUNCTIONS_BASE) / 2) as usize;
+        match inner.functions_reported.get_mut(index) {
+            Some(x) if !*x => {
+                *x = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn run_guest(&mut self) -> ArmCoreResult<RunStop> {
         let mut inner = self.inner.borrow_mut();
 
-        inner.engine.run(RUN_FUNCTION_LR, FUNCTIONS_BASE..FUNCTIONS_BASE + 0x1000, 1000)?;
+        let count = if inner.debug.stepping { 1 } else { inner.instruction_budget };
+        let result = {
+            let ArmCoreInner { engine, debug, .. } = &mut *inner;
+
+            engine.run(
+                RUN_FUNCTION_LR,
+                FUNCTIONS_BASE..FUNCTIONS_BASE + 0x1000,
+                count,
+                &debug.breakpoints,
+                &mut debug.tracer,
+            )
+        };
+        let hit = match result {
+            Ok(x) => x,
+            Err(ArmCoreError::GuardPageAccess(address)) if (STACKS_BASE..STACKS_END).contains(&address) => {
+                let task = inner.system.current_task().map(|x| x.id()).unwrap_or(0);
+                tracing::error!("Stack overflow in task {}, accessing {:#x}", task, address);
+
+                return Err(ArmCoreError::StackOverflow(task));
+            }
+            Err(x) => return Err(x),
+        };
+
+        if hit || inner.debug.stepping {
+            if hit {
+                tracing::info!("Breakpoint hit at {:#x}", inner.engine.reg_read(ArmRegister::PC));
+            }
+
+            inner.debug.stepping = false;
+            inner.debug.paused = true;
+            inner.debug.paused_context = Some(Self::save_context_inner(&*inner.engine));
+
+            return Ok(RunStop::Other);
+        }
 
         let cur_pc = inner.engine.reg_read(ArmRegister::PC);
 
         if (FUNCTIONS_BASE..FUNCTIONS_BASE + 0x1000).contains(&cur_pc) {
-            let mut self1 = self.clone();
-            let mut system_clone = inner.system.clone();
-
             let function = inner.functions.get(&cur_pc).unwrap().clone();
+            let name = inner.function_names.get(&cur_pc).cloned();
+            inner.debug.tracer.function(cur_pc, name.as_deref());
 
-            drop(inner);
-
-            function.call(&mut self1, &mut system_clone).await?;
+            Ok(RunStop::Native(cur_pc, function, name, inner.system.clone()))
+        } else if cur_pc != RUN_FUNCTION_LR {
+            Ok(RunStop::Budget(inner.system.clone()))
+        } else {
+            Ok(RunStop::Other)
         }
+    }
 
-        Ok(())
+    /// Instructions run in a slice before yielding to other tasks.
+    pub fn set_instruction_budget(&mut self, count: u32) {
+        self.inner.borrow_mut().instruction_budget = count.max(1);
     }
 
     pub async fn run_function<R>(&mut self, address: u32, params: &[u32]) -> ArmCoreResult<R>
     where
         R: RunFunctionResult<R>,
     {
+        let _guest_call = GuestCall::new(self.clone());
         let previous_context = self.save_context(); // do we have to save context?
         {
             let mut inner = self.inner.borrow_mut();
@@ -101,13 +339,15 @@ impl ArmCore {
             if params.len() > 3 {
                 inner.engine.reg_write(ArmRegister::R3, params[3]);
             }
+            // the rest goes on the stack with the first one lowest, and sp is kept 8 byte aligned at the call as AAPCS requires
             if params.len() > 4 {
-                for param in params[4..].iter().rev() {
-                    let sp = inner.engine.reg_read(ArmRegister::SP) - 4;
+                let stack_params = &params[4..];
+                let sp = (inner.engine.reg_read(ArmRegister::SP) - stack_params.len() as u32 * 4) & !7;
 
-                    inner.engine.mem_write(sp, &param.to_le_bytes())?;
-                    inner.engine.reg_write(ArmRegister::SP, sp);
-                }
+                let endianness = inner.config.endianness;
+                let data = stack_params.iter().flat_map(|x| endianness.write_u32(*x)).collect::<Vec<_>>();
+                inner.engine.mem_write(sp, &data)?;
+                inner.engine.reg_write(ArmRegister::SP, sp);
             }
 
             inner.engine.reg_write(ArmRegister::PC, address);
@@ -130,14 +370,17 @@ impl ArmCore {
         Ok(result)
     }
 
-    pub fn spawn<C, R, E>(&mut self, callable: C)
+    pub fn spawn<C, R, E>(&mut self, callable: C) -> ArmCoreResult<TaskHandle>
     where
         C: AsyncCallable<R, E> + 'static,
         R: 'static,
         E: Debug + 'static,
     {
-        let self_cloned = self.clone();
-        self.inner.borrow_mut().system.spawn(move || SpawnFuture::new(self_cloned, callable));
+        let stack_base = self.alloc_stack()?;
+        // created here rather than on the first poll, so a task cancelled before it runs still gives its stack back
+        let future = SpawnFuture::new(self.clone(), stack_base, callable);
+
+        Ok(self.inner.borrow_mut().system.spawn(move || future))
     }
 
     pub fn register_function<F, P, E, R>(&mut self, function: F) -> ArmCoreResult<u32>
@@ -158,75 +401,317 @@ impl ArmCore {
 
         inner.functions.insert(address as u32, Rc::new(Box::new(callback)));
         inner.functions_count += 1;
+        inner.functions_reported.push(false);
 
         tracing::trace!("Register function at {:#x}", address);
 
         Ok(address as u32 + 1)
     }
 
-    pub fn map(&mut self, address: u32, size: u32) -> ArmCoreResult<()> {
-        tracing::trace!("Map address: {:#x}, size: {:#x}", address, size);
+    pub fn set_function_name(&mut self, address: u32, name: &str) {
+        let mut inner = self.inner.borrow_mut();
+
+        inner.function_names.insert(address & !1, Rc::from(name));
+    }
+
+    /// Names the image loaded at `base`, for call stacks
+    pub fn register_module(&mut self, name: &str, base: u32, size: u32) {
+        self.inner.borrow_mut().symbols.add_module(name, base, size);
+    }
+
+    /// Names a guest function, like the ones the image exports
+    pub fn add_symbol(&mut self, address: u32, name: &str) {
+        self.inner.borrow_mut().symbols.add_symbol(address, name);
+    }
+
+    /// Maps data memory, which guest code can't run from
+    pub fn map(&mut self, address: u32, size: u32, name: &str, owner: &str) -> ArmCoreResult<()> {
+        tracing::trace!("Map {} of {}, address: {:#x}, size: {:#x}", name, owner, address, size);
 
         let mut inner = self.inner.borrow_mut();
 
         inner.engine.mem_map(address, size as usize, MemoryPermission::ReadWrite);
+        inner.memory_map.add(name, address, size, MemoryPermission::ReadWrite, owner);
 
         Ok(())
     }
 
-    pub fn dump_reg_stack(&self, image_base: u32) -> String {
+    /// Lowest address of a `TASK_STACK_SIZE` bytes stack for a new task
+    pub(crate) fn alloc_stack(&mut self) -> ArmCoreResult<u32> {
+        let mut inner = self.inner.borrow_mut();
+
+        if let Some(x) = inner.free_stacks.pop() {
+            return Ok(x);
+        }
+
+        let index = inner.stack_count;
+        let guard = STACKS_BASE + index * (STACK_GUARD_SIZE + TASK_STACK_SIZE);
+        if guard >= STACKS_END {
+            return Err(ArmCoreError::OutOfMemory);
+        }
+        let stack_base = guard + STACK_GUARD_SIZE;
+
+        inner.engine.mem_guard(guard, STACK_GUARD_SIZE as _);
+        inner.engine.mem_map(stack_base, TASK_STACK_SIZE as _, MemoryPermission::ReadWrite);
+        inner.memory_map.add(
+            &format!("stack {}", index),
+            stack_base,
+            TASK_STACK_SIZE,
+            MemoryPermission::ReadWrite,
+            "core",
+        );
+        inner.stack_count += 1;
+
+        Ok(stack_base)
+    }
+
+    pub(crate) fn free_stack(&mut self, stack_base: u32) {
+        self.inner.borrow_mut().free_stacks.push(stack_base);
+    }
+
+    /// Named regions as (name, address, size, permission, owner), sorted by address
+    pub fn memory_map(&self) -> Vec<(String, u32, u32, &'static str, String)> {
+        self.inner
+            .borrow()
+            .memory_map
+            .regions()
+            .into_iter()
+            .map(|x| (x.name.clone(), x.address, x.size, x.permission.as_str(), x.owner.clone()))
+            .collect()
+    }
+
+    // thumb bit is ignored, as the engine reports pc without it
+    pub fn add_breakpoint(&mut self, address: u32) {
+        self.inner.borrow_mut().debug.breakpoints.insert(address & !1);
+    }
+
+    pub fn remove_breakpoint(&mut self, address: u32) -> bool {
+        self.inner.borrow_mut().debug.breakpoints.remove(&(address & !1))
+    }
+
+    pub fn breakpoints(&self) -> Vec<u32> {
+        self.inner.borrow().debug.breakpoints.iter().cloned().collect()
+    }
+
+    // guest code stops before the next instruction. native functions and tasks not running guest code are not affected
+    pub fn pause(&mut self) {
+        self.inner.borrow_mut().debug.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        let mut inner = self.inner.borrow_mut();
+
+        inner.debug.paused = false;
+        inner.debug.paused_context = None;
+    }
+
+    pub fn step(&mut self) {
+        self.resume();
+        self.inner.borrow_mut().debug.stepping = true;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.borrow().debug.paused
+    }
+
+    // registers of the guest where it was paused, or current ones if running
+    pub fn debug_context(&self) -> ArmCoreContext {
+        let inner = self.inner.borrow();
+
+        inner
+            .debug
+            .paused_context
+            .clone()
+            .unwrap_or_else(|| Self::save_context_inner(&*inner.engine))
+    }
+
+    pub fn branch_history(&self) -> Vec<(u32, u32)> {
+        self.inner.borrow().engine.branch_history()
+    }
+
+    // instructions in [start, end) are recorded to the trace. native functions are never stepped, so only calls to them show up
+    pub fn set_trace_region(&mut self, start: u32, end: u32, enabled: bool) {
+        self.inner.borrow_mut().debug.tracer.set_region(start..end, enabled);
+    }
+
+    pub fn set_trace_functions(&mut self, enabled: bool) {
+        self.inner.borrow_mut().debug.tracer.set_functions(enabled);
+    }
+
+    pub fn trace(&self) -> Vec<String> {
+        self.inner.borrow().debug.tracer.entries()
+    }
+
+    pub fn fault_address(&self) -> Option<u32> {
+        self.inner.borrow().engine.fault_address()
+    }
+
+    pub fn mapped_regions(&self) -> Vec<(u32, u32)> {
+        self.inner
+            .borrow()
+            .engine
+            .mapped_regions()
+            .into_iter()
+            .map(|(address, size, _)| (address, size))
+            .collect()
+    }
+
+    // contents of every mapped region, which covers the heap and the stacks of tasks, and registers of suspended tasks.
+    // our native tasks can't be saved, so it is only valid for the same binary, with tasks waiting where they are now,
+    // and while native code isn't waiting on guest code it called
+    pub fn snapshot(&self) -> ArmCoreResult<Vec<u8>> {
+        let mut inner = self.inner.borrow_mut();
+
+        if inner.guest_calls.values().any(|&x| x > 1) {
+            return Err(ArmCoreError::GuestCallPending);
+        }
+
+        let mut result = SNAPSHOT_MAGIC.to_vec();
+        result.extend_from_slice(&inner.image_hasher.clone().finalize().to_le_bytes());
+
+        result.extend_from_slice(&(inner.task_contexts.len() as u32).to_le_bytes());
+        for (stack_base, context) in &inner.task_contexts {
+            result.extend_from_slice(&stack_base.to_le_bytes());
+            result.extend(context.to_words().into_iter().flat_map(|x| x.to_le_bytes()));
+        }
+
+        let regions = inner.engine.mapped_regions();
+        result.extend_from_slice(&(regions.len() as u32).to_le_bytes());
+        for (address, size, permission) in regions {
+            result.extend_from_slice(&address.to_le_bytes());
+            result.extend_from_slice(&size.to_le_bytes());
+            result.extend_from_slice(&(permission as u32).to_le_bytes());
+            result.extend(inner.engine.mem_read(address, size as _)?);
+        }
+
+        Ok(result)
+    }
+
+    // tasks continue from their native code as it is now, so each task waiting in a native function must be waiting in the same call.
+    // regions mapped now must have the permission they had, ones mapped since are left as they are.
+    // nothing is changed if the snapshot can't be restored
+    pub fn restore_snapshot(&mut self, snapshot: &[u8]) -> ArmCoreResult<()> {
+        let mut inner = self.inner.borrow_mut();
+
+        let (magic, mut rest) = snapshot.split_at_checked(4).ok_or(ArmCoreError::InvalidSnapshot)?;
+        if magic != SNAPSHOT_MAGIC {
+            return Err(ArmCoreError::InvalidSnapshot);
+        }
+
+        let read_u32 = |rest: &mut &[u8]| {
+            let (value, remaining) = rest.split_at_checked(4).ok_or(ArmCoreError::InvalidSnapshot)?;
+            *rest = remaining;
+
+            Ok::<_, ArmCoreError>(u32::from_le_bytes(value.try_into().unwrap()))
+        };
+
+        if read_u32(&mut rest)? != inner.image_hasher.clone().finalize() {
+            tracing::error!("Snapshot was taken with another binary loaded");
+
+            return Err(ArmCoreError::SnapshotMismatch);
+        }
+        if inner.guest_calls.values().any(|&x| x > 1) {
+            return Err(ArmCoreError::GuestCallPending);
+        }
+
+        let mut task_contexts = BTreeMap::new();
+        for _ in 0..read_u32(&mut rest)? {
+            let stack_base = read_u32(&mut rest)?;
+            let words = (0..ArmCoreContext::WORD_COUNT)
+                .map(|_| read_u32(&mut rest))
+                .collect::<ArmCoreResult<Vec<_>>>()?;
+
+            task_contexts.insert(stack_base, ArmCoreContext::from_words(&words));
+        }
+        if !task_contexts.keys().eq(inner.task_contexts.keys()) {
+            tracing::error!("Snapshot was taken with other tasks running");
+
+            return Err(ArmCoreError::SnapshotMismatch);
+        }
+        for (stack_base, context) in &task_contexts {
+            let current = &inner.task_contexts[stack_base];
+            let is_native = |x: &ArmCoreContext| (FUNCTIONS_BASE..FUNCTIONS_BASE + 0x1000).contains(&x.pc);
+
+            if (is_native(context) || is_native(current)) && (context.pc, context.lr, context.sp) != (current.pc, current.lr, current.sp) {
+                tracing::error!("Task on stack {:#x} is waiting in another native call than in the snapshot", stack_base);
+
+                return Err(ArmCoreError::SnapshotMismatch);
+            }
+        }
+
+        let mapped = inner.engine.mapped_regions();
+
+        let mut regions = Vec::new();
+        for _ in 0..read_u32(&mut rest)? {
+            let address = read_u32(&mut rest)?;
+            let size = read_u32(&mut rest)?;
+            let permission = MemoryPermission::from_bits(read_u32(&mut rest)? as u8).ok_or(ArmCoreError::InvalidSnapshot)?;
+            let (data, remaining) = rest.split_at_checked(size as _).ok_or(ArmCoreError::InvalidSnapshot)?;
+            rest = remaining;
+
+            let end = address as u64 + size as u64;
+            let overlapping = mapped
+                .iter()
+                .find(|(x, x_size, _)| (*x as u64) < end && (address as u64) < *x as u64 + *x_size as u64);
+            let needs_map = match overlapping {
+                None => true,
+                Some(&(x, x_size, x_permission)) if x <= address && end <= x as u64 + x_size as u64 && x_permission == permission => false,
+                Some(_) => return Err(ArmCoreError::InvalidSnapshot),
+            };
+
+            regions.push((address, permission, data, needs_map));
+        }
+
+        for (address, permission, data, needs_map) in regions {
+            if needs_map {
+                inner.engine.mem_map(address, data.len(), permission);
+            }
+            inner.engine.mem_write(address, data)?;
+        }
+        inner.task_contexts = task_contexts;
+
+        Ok(())
+    }
+
+    pub fn dump_reg_stack(&self) -> String {
         format!(
             "\n{}\nPossible call stack:\n{}\nStack:\n{}",
             self.dump_regs(),
-            self.dump_call_stack(image_base).unwrap(),
+            self.dump_call_stack().unwrap(),
             self.dump_stack().unwrap()
         )
     }
 
     pub fn restore_context(&mut self, context: &ArmCoreContext) {
+        self.inner.borrow_mut().engine.context_write(context);
+    }
+
+    /// Registers of tasks waiting to be resumed, not including the running one.
+    pub fn task_contexts(&self) -> Vec<ArmCoreContext> {
+        self.inner.borrow().task_contexts.values().cloned().collect()
+    }
+
+    pub(crate) fn task_context(&self, stack_base: u32) -> Option<ArmCoreContext> {
+        self.inner.borrow().task_contexts.get(&stack_base).cloned()
+    }
+
+    pub(crate) fn set_task_context(&mut self, stack_base: u32, context: Option<ArmCoreContext>) {
         let mut inner = self.inner.borrow_mut();
 
-        inner.engine.reg_write(ArmRegister::R0, context.r0);
-        inner.engine.reg_write(ArmRegister::R1, context.r1);
-        inner.engine.reg_write(ArmRegister::R2, context.r2);
-        inner.engine.reg_write(ArmRegister::R3, context.r3);
-        inner.engine.reg_write(ArmRegister::R4, context.r4);
-        inner.engine.reg_write(ArmRegister::R5, context.r5);
-        inner.engine.reg_write(ArmRegister::R6, context.r6);
-        inner.engine.reg_write(ArmRegister::R7, context.r7);
-        inner.engine.reg_write(ArmRegister::R8, context.r8);
-        inner.engine.reg_write(ArmRegister::SB, context.sb);
-        inner.engine.reg_write(ArmRegister::SL, context.sl);
-        inner.engine.reg_write(ArmRegister::FP, context.fp);
-        inner.engine.reg_write(ArmRegister::IP, context.ip);
-        inner.engine.reg_write(ArmRegister::SP, context.sp);
-        inner.engine.reg_write(ArmRegister::LR, context.lr);
-        inner.engine.reg_write(ArmRegister::PC, context.pc);
-        inner.engine.reg_write(ArmRegister::Cpsr, context.cpsr);
+        match context {
+            Some(x) => inner.task_contexts.insert(stack_base, x),
+            None => inner.task_contexts.remove(&stack_base),
+        };
     }
 
     pub fn save_context(&self) -> ArmCoreContext {
         let inner = self.inner.borrow();
 
-        ArmCoreContext {
-            r0: inner.engine.reg_read(ArmRegister::R0),
-            r1: inner.engine.reg_read(ArmRegister::R1),
-            r2: inner.engine.reg_read(ArmRegister::R2),
-            r3: inner.engine.reg_read(ArmRegister::R3),
-            r4: inner.engine.reg_read(ArmRegister::R4),
-            r5: inner.engine.reg_read(ArmRegister::R5),
-            r6: inner.engine.reg_read(ArmRegister::R6),
-            r7: inner.engine.reg_read(ArmRegister::R7),
-            r8: inner.engine.reg_read(ArmRegister::R8),
-            sb: inner.engine.reg_read(ArmRegister::SB),
-            sl: inner.engine.reg_read(ArmRegister::SL),
-            fp: inner.engine.reg_read(ArmRegister::FP),
-            ip: inner.engine.reg_read(ArmRegister::IP),
-            sp: inner.engine.reg_read(ArmRegister::SP),
-            lr: inner.engine.reg_read(ArmRegister::LR),
-            pc: inner.engine.reg_read(ArmRegister::PC),
-            cpsr: inner.engine.reg_read(ArmRegister::Cpsr),
-        }
+        Self::save_context_inner(&*inner.engine)
+    }
+
+    fn save_context_inner(engine: &dyn ArmEngine) -> ArmCoreContext {
+        engine.context_read()
     }
 
     pub(crate) fn read_pc_lr(&self) -> ArmCoreResult<(u32, u32)> {
@@ -247,7 +732,15 @@ impl ArmCore {
         Ok(())
     }
 
-    pub(crate) fn read_param(&self, pos: usize) -> ArmCoreResult<u32> {
+    // in r0:r1
+    pub(crate) fn write_result_u64(&mut self, result: u64, lr: u32) -> ArmCoreResult<()> {
+        self.inner.borrow_mut().engine.reg_write(ArmRegister::R1, (result >> 32) as u32);
+
+        self.write_result(result as u32, lr)
+    }
+
+    /// Parameter `pos` of the running native function, from r0-r3 and then the stack like AAPCS passes them
+    pub fn read_param(&self, pos: usize) -> ArmCoreResult<u32> {
         let inner = self.inner.borrow();
 
         let result = if pos == 0 {
@@ -260,15 +753,41 @@ impl ArmCore {
             inner.engine.reg_read(ArmRegister::R3)
         } else {
             let sp = inner.engine.reg_read(ArmRegister::SP);
+            let endianness = inner.config.endianness;
 
             drop(inner);
 
-            read_generic(self, sp + 4 * (pos as u32 - 4))?
+            endianness.read_u32(read_generic(self, sp + 4 * (pos as u32 - 4))?)
         };
 
         Ok(result)
     }
 
+    pub fn read_params(&self, count: usize) -> ArmCoreResult<Vec<u32>> {
+        (0..count).map(|x| self.read_param(x)).collect()
+    }
+
+    /// Null terminated string the parameter points to
+    pub fn read_c_string(&self, pos: usize) -> ArmCoreResult<String> {
+        let address = self.read_param(pos)?;
+
+        Ok(read_null_terminated_string(self, address)?)
+    }
+
+    /// Struct the parameter points to
+    pub fn read_struct<T: Pod>(&self, pos: usize) -> ArmCoreResult<T> {
+        let address = self.read_param(pos)?;
+
+        Ok(read_generic(self, address)?)
+    }
+
+    /// Writes an out parameter back to where the parameter points
+    pub fn write_back_struct<T: Pod>(&mut self, pos: usize, value: T) -> ArmCoreResult<()> {
+        let address = self.read_param(pos)?;
+
+        Ok(write_generic(self, address, value)?)
+    }
+
     pub(crate) fn dump_regs_inner(engine: &dyn ArmEngine) -> String {
         [
             format!(
@@ -293,15 +812,27 @@ impl ArmCore {
                 engine.reg_read(ArmRegister::LR),
                 engine.reg_read(ArmRegister::PC),
             ),
-            format!("CPSR: {:032b}\n", engine.reg_read(ArmRegister::Cpsr)),
+            format!("CPSR: {:032b}", engine.reg_read(ArmRegister::Cpsr)),
+            Self::dump_vfp_regs(engine),
         ]
         .join("\n")
     }
 
-    fn is_code_address(address: u32, image_base: u32) -> bool {
-        // TODO image size temp
+    // as doubles, which is how compiled code mostly uses them
+    fn dump_vfp_regs(engine: &dyn ArmEngine) -> String {
+        let (registers, fpscr) = engine.vfp_read();
+
+        let doubles = registers
+            .chunks(2)
+            .enumerate()
+            .map(|(i, x)| format!("D{}: {}", i, f64::from_bits(x[0] as u64 | (x[1] as u64) << 32)))
+            .collect::<Vec<_>>();
+
+        format!("{}\n{}\nFPSCR: {:#x}\n", doubles[..8].join(" "), doubles[8..].join(" "), fpscr)
+    }
 
-        address % 2 == 1 && ((image_base..image_base + 0x100000).contains(&address) || (FUNCTIONS_BASE..FUNCTIONS_BASE + 0x10000).contains(&address))
+    fn is_code_address(address: u32, symbols: &SymbolTable, memory_map: &MemoryMap) -> bool {
+        address % 2 == 1 && (symbols.contains(address) || memory_map.find(address).is_some_and(|x| x.permission.is_executable()))
     }
 
     fn dump_regs(&self) -> String {
@@ -310,11 +841,23 @@ impl ArmCore {
         Self::dump_regs_inner(&*inner.engine)
     }
 
-    fn format_callstack_address(address: u32, image_base: u32) -> String {
-        let description = if (image_base..image_base + 0x100000).contains(&address) {
-            format!("<Base>+{:#x}", address - image_base)
+    fn format_callstack_address(address: u32, inner: &ArmCoreInner) -> String {
+        let ArmCoreInner {
+            symbols,
+            function_names,
+            memory_map,
+            ..
+        } = inner;
+
+        let description = if let Some(x) = symbols.describe(address) {
+            x
         } else if (FUNCTIONS_BASE..FUNCTIONS_BASE + 0x10000).contains(&address) {
-            "<Native function>".to_owned()
+            match function_names.get(&(address & !1)) {
+                Some(x) => format!("<Native function {}>", x),
+                None => "<Native function>".to_owned(),
+            }
+        } else if let Some(x) = memory_map.find(address) {
+            format!("<{}>+{:#x}", x.name, address - x.address)
         } else {
             "<Unknown>".to_owned()
         };
@@ -322,25 +865,26 @@ impl ArmCore {
         format!("{:#x}: {}\n", address, description)
     }
 
-    fn dump_call_stack(&self, image_base: u32) -> ArmCoreResult<String> {
+    fn dump_call_stack(&self) -> ArmCoreResult<String> {
         let mut inner = self.inner.borrow_mut();
 
         let sp = inner.engine.reg_read(ArmRegister::SP);
         let pc = inner.engine.reg_read(ArmRegister::PC);
         let lr = inner.engine.reg_read(ArmRegister::LR);
 
-        let mut call_stack = Self::format_callstack_address(pc, image_base);
+        let mut call_stack = Self::format_callstack_address(pc, &inner);
+        // bl is 4 bytes in both arm and thumb, thumb return addresses have bit 0 set
         if lr != RUN_FUNCTION_LR && lr != 0 {
-            call_stack += &Self::format_callstack_address(lr - 5, image_base);
+            call_stack += &Self::format_callstack_address((lr & !1) - 4, &inner);
         }
 
         for i in 0..128 {
             let address = sp + (i * 4);
             let value = inner.engine.mem_read(address, size_of::<u32>())?;
-            let value_u32 = u32::from_le_bytes(value.try_into().unwrap());
+            let value_u32 = inner.config.endianness.read_u32(value.try_into().unwrap());
 
-            if value_u32 > 5 && Self::is_code_address(value_u32 - 4, image_base) {
-                call_stack += &Self::format_callstack_address(value_u32 - 5, image_base);
+            if value_u32 > 5 && Self::is_code_address(value_u32 - 4, &inner.symbols, &inner.memory_map) {
+                call_stack += &Self::format_callstack_address(value_u32 - 5, &inner);
             }
         }
 
@@ -356,7 +900,7 @@ impl ArmCore {
         for i in 0..16 {
             let address = sp + (i * 4);
             let value = inner.engine.mem_read(address, size_of::<u32>())?;
-            let value_u32 = u32::from_le_bytes(value.try_into().unwrap());
+            let value_u32 = inner.config.endianness.read_u32(value.try_into().unwrap());
 
             result += &format!("SP+{:#x}: {:#x}\n", i * 4, value_u32);
         }
@@ -375,6 +919,12 @@ impl ByteRead for ArmCore {
 
         Ok(data)
     }
+
+    fn read_into(&self, address: u32, buffer: &mut [u8]) -> wie_util::Result<()> {
+        self.inner.borrow_mut().engine.mem_read_into(address, buffer)?;
+
+        Ok(())
+    }
 }
 
 impl ByteWrite for ArmCore {
@@ -388,6 +938,110 @@ impl ByteWrite for ArmCore {
     }
 }
 
+impl GuestAccess for ArmCore {
+    fn read_memory(&self, address: u32, size: u32) -> anyhow::Result<Vec<u8>> {
+        Ok(self.read_bytes(address, size).map_err(ArmCoreError::from)?)
+    }
+
+    fn write_memory(&mut self, address: u32, data: &[u8]) -> anyhow::Result<()> {
+        Ok(self.write_bytes(address, data).map_err(ArmCoreError::from)?)
+    }
+
+    fn call_function(&mut self, address: u32, params: &[u32]) -> anyhow::Result<()> {
+        let mut core = self.clone();
+        let params = params.to_vec();
+
+        self.spawn(move || async move { core.run_function::<()>(address, &params).await })?;
+
+        Ok(())
+    }
+
+    fn registers(&self) -> Vec<(&'static str, u32)> {
+        let context = self.debug_context();
+
+        vec![
+            ("R0", context.r0),
+            ("R1", context.r1),
+            ("R2", context.r2),
+            ("R3", context.r3),
+            ("R4", context.r4),
+            ("R5", context.r5),
+            ("R6", context.r6),
+            ("R7", context.r7),
+            ("R8", context.r8),
+            ("SB", context.sb),
+            ("SL", context.sl),
+            ("FP", context.fp),
+            ("IP", context.ip),
+            ("SP", context.sp),
+            ("LR", context.lr),
+            ("PC", context.pc),
+            ("CPSR", context.cpsr),
+            ("FPSCR", context.fpscr),
+        ]
+    }
+
+    fn add_breakpoint(&mut self, address: u32) {
+        ArmCore::add_breakpoint(self, address)
+    }
+
+    fn remove_breakpoint(&mut self, address: u32) -> bool {
+        ArmCore::remove_breakpoint(self, address)
+    }
+
+    fn breakpoints(&self) -> Vec<u32> {
+        ArmCore::breakpoints(self)
+    }
+
+    fn pause(&mut self) {
+        ArmCore::pause(self)
+    }
+
+    fn resume(&mut self) {
+        ArmCore::resume(self)
+    }
+
+    fn step(&mut self) {
+        ArmCore::step(self)
+    }
+
+    fn is_paused(&self) -> bool {
+        ArmCore::is_paused(self)
+    }
+
+    fn memory_regions(&self) -> Vec<(u32, u32)> {
+        ArmCore::mapped_regions(self)
+    }
+
+    fn memory_map(&self) -> Vec<(String, u32, u32, &'static str, String)> {
+        ArmCore::memory_map(self)
+    }
+
+    fn set_instruction_budget(&mut self, count: u32) {
+        ArmCore::set_instruction_budget(self, count)
+    }
+
+    fn branch_history(&self) -> Vec<(u32, u32)> {
+        ArmCore::branch_history(self)
+    }
+
+    fn set_trace_region(&mut self, start: u32, end: u32, enabled: bool) {
+        ArmCore::set_trace_region(self, start, end, enabled)
+    }
+
+    fn set_trace_functions(&mut self, enabled: bool) {
+        ArmCore::set_trace_functions(self, enabled)
+    }
+
+    fn trace(&self) -> Vec<String> {
+        ArmCore::trace(self)
+    }
+
+    fn fault_address(&self) -> Option<u32> {
+        ArmCore::fault_address(self)
+    }
+}
+
 pub trait RunFunctionResult<R> {
     fn get(core: &ArmCore) -> R;
 }
@@ -401,3 +1055,213 @@ impl RunFunctionResult<u32> for u32 {
 impl RunFunctionResult<()> for () {
     fn get(_: &ArmCore) {}
 }
+
+// 64-bit results come in r0:r1, and floats as their bits like soft-float code returns them
+impl RunFunctionResult<u64> for u64 {
+    fn get(core: &ArmCore) -> u64 {
+        core.read_param(0).unwrap() as u64 | (core.read_param(1).unwrap() as u64) << 32
+    }
+}
+
+impl RunFunctionResult<i64> for i64 {
+    fn get(core: &ArmCore) -> i64 {
+        u64::get(core) as i64
+    }
+}
+
+impl RunFunctionResult<f32> for f32 {
+    fn get(core: &ArmCore) -> f32 {
+        f32::from_bits(u32::get(core))
+    }
+}
+
+impl RunFunctionResult<f64> for f64 {
+    fn get(core: &ArmCore) -> f64 {
+        f64::from_bits(u64::get(core))
+    }
+}
+
+/// Parameters of `run_function` laid out like the soft-float AAPCS, where 64-bit values take an even register pair or an 8 byte aligned stack slot.
+#[derive(Default)]
+pub struct FunctionArgs {
+    words: Vec<u32>,
+}
+
+impl FunctionArgs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn u32(mut self, value: u32) -> Self {
+        self.words.push(value);
+
+        self
+    }
+
+    // run_function puts words from the fifth on an 8 byte aligned stack, so an even index is aligned both in registers and on the stack
+    pub fn u64(mut self, value: u64) -> Self {
+        if self.words.len() % 2 == 1 {
+            self.words.push(0);
+        }
+        self.words.push(value as u32);
+        self.words.push((value >> 32) as u32);
+
+        self
+    }
+
+    pub fn i64(self, value: i64) -> Self {
+        self.u64(value as u64)
+    }
+
+    pub fn f32(self, value: f32) -> Self {
+        self.u32(value.to_bits())
+    }
+
+    pub fn f64(self, value: f64) -> Self {
+        self.u64(value.to_bits())
+    }
+
+    pub fn as_slice(&self) -> &[u32] {
+        &self.words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+
+    use wie_backend::{Instant, System};
+
+    use test_utils::TestPlatform;
+
+    use crate::{
+        engine::{Endianness, MemoryPermission},
+        ArmCoreError, ArmCoreResult,
+    };
+
+    use super::{ArmCore, ArmCoreConfig, FunctionArgs, Segment, HEAP_BASE};
+
+    #[test]
+    fn test_function_args() {
+        let args = FunctionArgs::new().u32(1).u64(0x1122334455667788).f32(1.0).f64(2.0);
+
+        assert_eq!(args.as_slice(), [1, 0, 0x55667788, 0x11223344, 0x3f800000, 0, 0, 0x40000000]);
+    }
+
+    #[test]
+    fn test_big_endian_unsupported() {
+        let system = wie_backend::System::new(Box::new(TestPlatform), Box::new(()));
+        let config = ArmCoreConfig { endianness: Endianness::Big };
+
+        assert!(matches!(ArmCore::new(system, config), Err(ArmCoreError::UnsupportedConfig)));
+    }
+
+    #[test]
+    fn test_spawn_cancelled() -> ArmCoreResult<()> {
+        let system = wie_backend::System::new(Box::new(TestPlatform), Box::new(()));
+        let mut core = ArmCore::new(system, ArmCoreConfig::default())?;
+
+        let handle = core.spawn(move || async move { ArmCoreResult::Ok(()) })?;
+        assert_eq!(core.task_contexts().len(), 1);
+
+        // cancelled before it is polled, the task still gives its stack back
+        handle.cancel();
+        assert!(core.task_contexts().is_empty());
+        assert_eq!(core.inner.borrow().free_stacks.len(), 1);
+
+        Ok(())
+    }
+
+    async fn wait(_: &mut ArmCore, system: &mut System) -> ArmCoreResult<()> {
+        system.sleep(Instant::from_epoch_millis(1000)).await;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel_running_task() -> ArmCoreResult<()> {
+        let mut system = System::new(Box::new(TestPlatform), Box::new(()));
+        let mut core = ArmCore::new(system.clone(), ArmCoreConfig::default())?;
+        let function = core.register_function(wait)?;
+
+        let mut core1 = core.clone();
+        let handle = core.spawn(move || async move { core1.run_function::<()>(function, &[]).await })?;
+
+        // runs until the task sleeps in the native function
+        assert!(system.tick().is_ok());
+        let running = core.snapshot()?;
+        assert_eq!(core.task_contexts().len(), 1);
+
+        handle.cancel();
+        assert!(core.task_contexts().is_empty());
+
+        let snapshot = core.snapshot()?;
+        core.restore_snapshot(&snapshot)?;
+        assert!(core.restore_snapshot(&running).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_permission() -> ArmCoreResult<()> {
+        let system = wie_backend::System::new(Box::new(TestPlatform), Box::new(()));
+
+        let mut core = ArmCore::new(system.clone(), ArmCoreConfig::default())?;
+        core.map(HEAP_BASE, 0x10000, "heap", "test")?;
+        let snapshot = core.snapshot()?;
+
+        // unmapped regions are mapped again with the permission they had, not as executable
+        let mut restored = ArmCore::new(system.clone(), ArmCoreConfig::default())?;
+        restored.restore_snapshot(&snapshot)?;
+        assert_eq!(
+            restored.inner.borrow().engine.mapped_regions(),
+            core.inner.borrow().engine.mapped_regions()
+        );
+
+        // and a region mapped now with another permission is not changed
+        let mut conflicting = ArmCore::new(system, ArmCoreConfig::default())?;
+        conflicting
+            .inner
+            .borrow_mut()
+            .engine
+            .mem_map(HEAP_BASE, 0x10000, MemoryPermission::ReadExecute);
+        assert!(conflicting.restore_snapshot(&snapshot).is_err());
+        assert_eq!(
+            conflicting.inner.borrow().engine.mapped_regions()[0],
+            (HEAP_BASE, 0x10000, MemoryPermission::ReadExecute)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_image() -> ArmCoreResult<()> {
+        let system = || wie_backend::System::new(Box::new(TestPlatform), Box::new(()));
+        let loaded = |data: &'static [u8]| {
+            let mut core = ArmCore::new(system(), ArmCoreConfig::default())?;
+            core.load(
+                "test",
+                &[Segment {
+                    name: "text",
+                    address: 0x10000,
+                    size: 0x1000,
+                    data,
+                    permission: MemoryPermission::ReadExecute,
+                }],
+            )?;
+
+            ArmCoreResult::Ok(core)
+        };
+
+        // a fresh core with the same binary takes it, as nothing else differs
+        let snapshot = loaded(&[1, 2, 3, 4])?.snapshot()?;
+        loaded(&[1, 2, 3, 4])?.restore_snapshot(&snapshot)?;
+
+        assert!(matches!(
+            loaded(&[5, 6, 7, 8])?.restore_snapshot(&snapshot),
+            Err(ArmCoreError::SnapshotMismatch)
+        ));
+
+        Ok(())
+    }
+}
@@ -1,28 +1,96 @@
 mod armv4t_emu;
+mod vfp;
 
-use alloc::vec::Vec;
+use alloc::{collections::BTreeSet, vec::Vec};
 use core::ops::Range;
 
 pub use armv4t_emu::Armv4tEmuEngine;
 
-use crate::ArmCoreResult;
+use crate::{context::ArmCoreContext, tracer::Tracer, ArmCoreResult};
 
 pub trait ArmEngine {
-    fn run(&mut self, end: u32, hook: Range<u32>, count: u32) -> ArmCoreResult<()>;
+    /// Returns true if stopped on one of `breakpoints`. Breakpoint on the starting instruction is ignored, to be able to resume from it.
+    /// Each instruction is passed to `tracer` before it runs.
+    fn run(&mut self, end: u32, hook: Range<u32>, count: u32, breakpoints: &BTreeSet<u32>, tracer: &mut Tracer) -> ArmCoreResult<bool>;
     fn reg_write(&mut self, reg: ArmRegister, value: u32);
     fn reg_read(&self, reg: ArmRegister) -> u32;
+    /// Every register in one call, as tasks switch them around each native call
+    fn context_read(&self) -> ArmCoreContext;
+    fn context_write(&mut self, context: &ArmCoreContext);
+    /// VFP single precision registers and FPSCR
+    fn vfp_read(&self) -> ([u32; 32], u32);
+    fn vfp_write(&mut self, registers: &[u32; 32], fpscr: u32);
     fn mem_map(&mut self, address: u32, size: usize, permission: MemoryPermission);
+    /// Reserves unmapped memory guest code must not touch, like below a stack. Accessing it stops `run` with `GuardPageAccess`.
+    fn mem_guard(&mut self, address: u32, size: usize);
     fn mem_write(&mut self, address: u32, data: &[u8]) -> ArmCoreResult<()>;
     fn mem_read(&mut self, address: u32, size: usize) -> ArmCoreResult<Vec<u8>>;
+    fn mem_read_into(&mut self, address: u32, buffer: &mut [u8]) -> ArmCoreResult<()>;
+    /// Contiguous mapped ranges of the same permission as (address, size, permission).
+    fn mapped_regions(&self) -> Vec<(u32, u32, MemoryPermission)>;
+    /// Most recent taken branches as (from, to), oldest first.
+    fn branch_history(&self) -> Vec<(u32, u32)>;
+    /// Unmapped address guest code last tried to access.
+    fn fault_address(&self) -> Option<u32>;
 }
 
 #[allow(clippy::enum_variant_names)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MemoryPermission {
     ReadExecute = 5,
     ReadWrite = 6,
     ReadWriteExecute = 7,
 }
 
+impl MemoryPermission {
+    pub fn is_executable(self) -> bool {
+        self as u8 & 1 != 0
+    }
+
+    // from the bits `as u8` gives, or a union of them
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            5 => Some(MemoryPermission::ReadExecute),
+            6 => Some(MemoryPermission::ReadWrite),
+            7 => Some(MemoryPermission::ReadWriteExecute),
+            _ => None,
+        }
+    }
+
+    /// Like `r-x`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MemoryPermission::ReadExecute => "r-x",
+            MemoryPermission::ReadWrite => "rw-",
+            MemoryPermission::ReadWriteExecute => "rwx",
+        }
+    }
+}
+
+/// Byte order of guest memory
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub fn read_u32(self, bytes: [u8; 4]) -> u32 {
+        match self {
+            Endianness::Little => u32::from_le_bytes(bytes),
+            Endianness::Big => u32::from_be_bytes(bytes),
+        }
+    }
+
+    pub fn write_u32(self, value: u32) -> [u8; 4] {
+        match self {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        }
+    }
+}
+
 #[derive(Eq, PartialEq)]
 pub enum ArmRegister {
     R0,
@@ -1,45 +1,151 @@
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeSet, VecDeque},
+    vec,
+    vec::Vec,
+};
 use core::{array, cell::RefCell, ops::Range};
 
 use armv4t_emu::{reg, Cpu, Memory, Mode};
 
-use crate::engine::{ArmCoreResult, ArmEngine, ArmRegister, MemoryPermission};
+use crate::{
+    context::ArmCoreContext,
+    engine::{vfp::Vfp, ArmCoreResult, ArmEngine, ArmRegister, Endianness, MemoryPermission},
+    tracer::Tracer,
+    ArmCoreError,
+};
+
+const BRANCH_HISTORY_SIZE: usize = 64;
+const CPSR_THUMB: u32 = 1 << 5;
 
 pub struct Armv4tEmuEngine {
     cpu: Cpu,
     mem: Armv4tEmuMemory,
+    vfp: Vfp,
+    branch_history: VecDeque<(u32, u32)>,
 }
 
 impl Armv4tEmuEngine {
-    pub fn new() -> Self {
+    // ARMv4T only. big endian is BE-32, where instructions are fetched with the same byte order as data
+    pub fn new(endianness: Endianness) -> Self {
         Self {
             cpu: Cpu::new(),
-            mem: Armv4tEmuMemory::new(),
+            mem: Armv4tEmuMemory::new(endianness),
+            vfp: Vfp::new(),
+            branch_history: VecDeque::with_capacity(BRANCH_HISTORY_SIZE),
         }
     }
 }
 
 impl ArmEngine for Armv4tEmuEngine {
-    fn run(&mut self, end: u32, hook: Range<u32>, mut count: u32) -> ArmCoreResult<()> {
+    fn run(&mut self, end: u32, hook: Range<u32>, mut count: u32, breakpoints: &BTreeSet<u32>, tracer: &mut Tracer) -> ArmCoreResult<bool> {
+        let mut first = true;
+
         loop {
             let pc = self.cpu.reg_get(Mode::User, reg::PC);
             if pc == end || hook.contains(&pc) || count == 0 {
                 break;
             }
+            if !first && breakpoints.contains(&pc) {
+                return Ok(true);
+            }
+            // wild jumps into data stop here, instead of running whatever is there
+            if !self.mem.is_executable(pc) {
+                return Err(ArmCoreError::ExecuteViolation(pc));
+            }
+
+            tracer.instruction(pc);
 
-            self.cpu.step(&mut self.mem);
+            let thumb = self.cpu.reg_get(Mode::User, reg::CPSR) & CPSR_THUMB != 0;
+            if thumb || !self.step_vfp(pc)? {
+                self.cpu.step(&mut self.mem);
+            }
             count -= 1;
+            first = false;
+
+            // the access went to the scratch page. stop on the faulting instruction so the crash report points to it
+            if let Some(address) = self.mem.guard_fault.take() {
+                self.cpu.reg_set(Mode::User, reg::PC, pc);
+
+                return Err(ArmCoreError::GuardPageAccess(address));
+            }
+            if let Some(address) = self.mem.unmapped_fault.take() {
+                self.cpu.reg_set(Mode::User, reg::PC, pc);
+
+                return Err(ArmCoreError::UnmappedAccess(address));
+            }
+
+            let next_pc = self.cpu.reg_get(Mode::User, reg::PC);
+            if next_pc != pc + if thumb { 2 } else { 4 } {
+                if self.branch_history.len() == BRANCH_HISTORY_SIZE {
+                    self.branch_history.pop_front();
+                }
+                self.branch_history.push_back((pc, next_pc));
+            }
         }
 
-        Ok(())
+        Ok(false)
     }
 
-    fn reg_write(&mut self, reg: ArmRegister, value: u32) {
-        if reg == ArmRegister::PC && value % 2 == 1 {
-            self.cpu.reg_set(Mode::User, reg.into_armv4t(), value - 1);
+    fn context_read(&self) -> ArmCoreContext {
+        let r = |x| self.cpu.reg_get(Mode::User, x);
+
+        ArmCoreContext {
+            r0: r(0),
+            r1: r(1),
+            r2: r(2),
+            r3: r(3),
+            r4: r(4),
+            r5: r(5),
+            r6: r(6),
+            r7: r(7),
+            r8: r(8),
+            sb: r(9),
+            sl: r(10),
+            fp: r(11),
+            ip: r(12),
+            sp: r(reg::SP),
+            lr: r(reg::LR),
+            pc: r(reg::PC),
+            cpsr: r(reg::CPSR),
+            vfp: self.vfp.registers,
+            fpscr: self.vfp.fpscr,
+        }
+    }
 
+    // thumb state comes from cpsr, so bit 0 of pc is dropped
+    fn context_write(&mut self, context: &ArmCoreContext) {
+        let registers = [
+            context.r0, context.r1, context.r2, context.r3, context.r4, context.r5, context.r6, context.r7, context.r8, context.sb, context.sl,
+            context.fp, context.ip, context.sp, context.lr,
+        ];
+        for (i, value) in registers.into_iter().enumerate() {
+            self.cpu.reg_set(Mode::User, i as u8, value);
+        }
+        self.cpu.reg_set(Mode::User, reg::PC, context.pc & !1);
+        self.cpu.reg_set(Mode::User, reg::CPSR, context.cpsr);
+
+        self.vfp.registers = context.vfp;
+        self.vfp.fpscr = context.fpscr;
+    }
+
+    fn vfp_read(&self) -> ([u32; 32], u32) {
+        (self.vfp.registers, self.vfp.fpscr)
+    }
+
+    fn vfp_write(&mut self, registers: &[u32; 32], fpscr: u32) {
+        self.vfp.registers = *registers;
+        self.vfp.fpscr = fpscr;
+    }
+
+    // like BX, bit 0 of a new pc selects thumb or arm state
+    fn reg_write(&mut self, reg: ArmRegister, value: u32) {
+        if reg == ArmRegister::PC {
             let cpsr = self.cpu.reg_get(Mode::User, reg::CPSR);
-            self.cpu.reg_set(Mode::User, reg::CPSR, cpsr | (1 << 5)); // T bit
+            let cpsr = if value & 1 == 1 { cpsr | CPSR_THUMB } else { cpsr & !CPSR_THUMB };
+
+            self.cpu.reg_set(Mode::User, reg::CPSR, cpsr);
+            self.cpu.reg_set(Mode::User, reg::PC, value & !1);
 
             return;
         }
@@ -50,20 +156,69 @@ impl ArmEngine for Armv4tEmuEngine {
         self.cpu.reg_get(Mode::User, reg.into_armv4t())
     }
 
-    fn mem_map(&mut self, address: u32, size: usize, _permission: MemoryPermission) {
+    fn mem_map(&mut self, address: u32, size: usize, permission: MemoryPermission) {
         self.mem.map(address, size);
+        self.mem.protect(address, size, permission);
     }
 
-    fn mem_write(&mut self, address: u32, data: &[u8]) -> ArmCoreResult<()> {
-        self.mem.write_range(address, data);
+    fn mem_guard(&mut self, address: u32, size: usize) {
+        self.mem.guard(address, size);
+    }
 
-        Ok(())
+    fn mem_write(&mut self, address: u32, data: &[u8]) -> ArmCoreResult<()> {
+        self.mem.write_range(address, data)
     }
 
     fn mem_read(&mut self, address: u32, size: usize) -> ArmCoreResult<Vec<u8>> {
-        let result = self.mem.read_range(address, size);
+        self.mem.read_range(address, size)
+    }
 
-        Ok(result)
+    fn mem_read_into(&mut self, address: u32, buffer: &mut [u8]) -> ArmCoreResult<()> {
+        self.mem.read_range_into(address, buffer)
+    }
+
+    fn mapped_regions(&self) -> Vec<(u32, u32, MemoryPermission)> {
+        let mut result: Vec<(u32, u32, MemoryPermission)> = Vec::new();
+
+        for (index, _) in self.mem.pages.iter().enumerate().filter(|x| x.1.is_some()) {
+            let address = (index * PAGE_SIZE) as u32;
+            // every page is mapped with a permission, so this only falls back for a page with none
+            let permission = MemoryPermission::from_bits(self.mem.permissions[index]).unwrap_or(MemoryPermission::ReadWrite);
+
+            match result.last_mut() {
+                Some((start, size, last)) if *start + *size == address && *last == permission => *size += PAGE_SIZE as u32,
+                _ => result.push((address, PAGE_SIZE as u32, permission)),
+            }
+        }
+
+        result
+    }
+
+    fn branch_history(&self) -> Vec<(u32, u32)> {
+        self.branch_history.iter().cloned().collect()
+    }
+
+    fn fault_address(&self) -> Option<u32> {
+        self.mem.fault_address
+    }
+}
+
+impl Armv4tEmuEngine {
+    // returns false if the instruction at pc is not for the VFP, to be run by the cpu
+    fn step_vfp(&mut self, pc: u32) -> ArmCoreResult<bool> {
+        let instruction = self.mem.r32(pc);
+        if !Vfp::is_vfp_instruction(instruction) {
+            return Ok(false);
+        }
+
+        if !self.vfp.execute(instruction, &mut self.cpu, &mut self.mem) {
+            tracing::error!("Unknown VFP instruction {:#010x} at {:#x}", instruction, pc);
+
+            return Err(ArmCoreError::UndefinedInstruction(pc));
+        }
+        self.cpu.reg_set(Mode::User, reg::PC, pc + 4);
+
+        Ok(true)
     }
 }
 
@@ -97,15 +252,53 @@ const PAGE_MASK: u32 = (PAGE_SIZE - 1) as _;
 
 struct Armv4tEmuMemory {
     pages: [Option<Box<RefCell<[u8; PAGE_SIZE]>>>; TOTAL_MEMORY / PAGE_SIZE],
+    // bits of MemoryPermission for each page. regions sharing a page get the union of theirs
+    permissions: Vec<u8>,
+    // indexes of unmapped pages reserved as guards, reported apart from other unmapped pages
+    guards: BTreeSet<usize>,
+    // guest accesses to unmapped pages go here instead, and `run` stops after the instruction
+    scratch: Box<RefCell<[u8; PAGE_SIZE]>>,
+    guard_fault: Option<u32>,
+    unmapped_fault: Option<u32>,
+    endianness: Endianness,
+    // kept for the crash report
+    fault_address: Option<u32>,
 }
 
 impl Armv4tEmuMemory {
-    fn new() -> Self {
+    fn new(endianness: Endianness) -> Self {
         Self {
             pages: array::from_fn(|_| None),
+            permissions: vec![0; TOTAL_MEMORY / PAGE_SIZE],
+            guards: BTreeSet::new(),
+            scratch: Box::new(RefCell::new([0; PAGE_SIZE])),
+            guard_fault: None,
+            unmapped_fault: None,
+            endianness,
+            fault_address: None,
+        }
+    }
+
+    fn protect(&mut self, address: u32, size: usize, permission: MemoryPermission) {
+        let page_start = address as usize / PAGE_SIZE;
+        let page_end = (address as usize + size).div_ceil(PAGE_SIZE);
+
+        for page in page_start..page_end {
+            self.permissions[page] |= permission as u8;
         }
     }
 
+    fn guard(&mut self, address: u32, size: usize) {
+        let page_start = address as usize / PAGE_SIZE;
+        let page_end = (address as usize + size).div_ceil(PAGE_SIZE);
+
+        self.guards.extend(page_start..page_end);
+    }
+
+    fn is_executable(&self, address: u32) -> bool {
+        self.permissions.get(address as usize / PAGE_SIZE).is_some_and(|x| x & 1 != 0)
+    }
+
     fn map(&mut self, address: u32, size: usize) {
         let page_start = address & !PAGE_MASK;
         let page_end = (address + size as u32 + PAGE_MASK) & !PAGE_MASK;
@@ -118,49 +311,94 @@ impl Armv4tEmuMemory {
         }
     }
 
-    fn read_range(&self, address: u32, size: usize) -> Vec<u8> {
-        let mut result = Vec::with_capacity(size);
-        let mut remaining_size = size;
+    // ranges come from guest controlled values, so running into unmapped memory is an error
+    fn page(&self, index: usize) -> ArmCoreResult<&RefCell<[u8; PAGE_SIZE]>> {
+        match self.pages.get(index) {
+            Some(Some(x)) => Ok(x),
+            _ => Err(ArmCoreError::InvalidMemoryAccess),
+        }
+    }
+
+    fn check_range(&self, address: u32, size: usize) -> ArmCoreResult<()> {
+        if size == 0 {
+            return Ok(());
+        }
+
+        let last = (address as usize).checked_add(size - 1).ok_or(ArmCoreError::InvalidMemoryAccess)?;
+        for index in address as usize / PAGE_SIZE..=last / PAGE_SIZE {
+            self.page(index)?;
+        }
+
+        Ok(())
+    }
+
+    // checked first, so a bogus size fails before the buffer is allocated
+    fn read_range(&self, address: u32, size: usize) -> ArmCoreResult<Vec<u8>> {
+        self.check_range(address, size)?;
+
+        let mut result = vec![0; size];
+        self.read_range_into(address, &mut result)?;
+
+        Ok(result)
+    }
+
+    fn read_range_into(&self, address: u32, buffer: &mut [u8]) -> ArmCoreResult<()> {
         let mut current_address = address;
+        let mut buffer_index = 0;
 
-        while remaining_size > 0 {
+        while buffer_index < buffer.len() {
             let page_address = current_address & !PAGE_MASK;
-            let page_data = self.pages[page_address as usize / PAGE_SIZE].as_ref().unwrap();
+            let page_data = self.page(page_address as usize / PAGE_SIZE)?;
             let offset = (current_address - page_address) as usize;
-            let available_bytes = (PAGE_SIZE - offset).min(remaining_size);
+            let available_bytes = (PAGE_SIZE - offset).min(buffer.len() - buffer_index);
 
-            result.extend_from_slice(&page_data.borrow()[offset..offset + available_bytes]);
-            remaining_size -= available_bytes;
-            current_address += available_bytes as u32;
+            buffer[buffer_index..buffer_index + available_bytes].copy_from_slice(&page_data.borrow()[offset..offset + available_bytes]);
+            buffer_index += available_bytes;
+            current_address = current_address.wrapping_add(available_bytes as u32);
         }
 
-        result
+        Ok(())
     }
 
-    fn write_range(&mut self, address: u32, data: &[u8]) {
+    // checked first, so a failed write doesn't leave part of the data written
+    fn write_range(&mut self, address: u32, data: &[u8]) -> ArmCoreResult<()> {
+        self.check_range(address, data.len())?;
+
         let mut current_address = address;
         let mut data_index = 0;
 
         while data_index < data.len() {
             let page_address = current_address & !PAGE_MASK;
-            let page_data = self.pages[page_address as usize / PAGE_SIZE].as_mut().unwrap();
+            let page_data = self.page(page_address as usize / PAGE_SIZE)?;
             let offset = (current_address - page_address) as usize;
             let available_bytes = (PAGE_SIZE - offset).min(data.len() - data_index);
 
             page_data.borrow_mut()[offset..offset + available_bytes].copy_from_slice(&data[data_index..data_index + available_bytes]);
             data_index += available_bytes;
-            current_address += available_bytes as u32;
+            current_address = current_address.wrapping_add(available_bytes as u32);
         }
+
+        Ok(())
     }
 
     fn get_page(&mut self, addr: u32) -> &RefCell<[u8; PAGE_SIZE]> {
         let page_address = addr & !PAGE_MASK;
-        let page_data = self.pages[page_address as usize / PAGE_SIZE].as_mut();
+        let index = page_address as usize / PAGE_SIZE;
+
+        if !matches!(self.pages.get(index), Some(Some(_))) {
+            if self.guards.contains(&index) {
+                self.guard_fault.get_or_insert(addr);
+            } else {
+                self.fault_address = Some(addr);
+                self.unmapped_fault.get_or_insert(addr);
+            }
 
-        if let Some(x) = page_data {
-            x
-        } else {
-            panic!("Access to unmapped address {:#x}", addr); // TODO can we propagate error?
+            return &self.scratch;
+        }
+
+        match &self.pages[index] {
+            Some(x) => x,
+            None => &self.scratch,
         }
     }
 }
@@ -175,21 +413,24 @@ impl Memory for Armv4tEmuMemory {
     }
 
     fn r16(&mut self, addr: u32) -> u16 {
-        let offset = addr & PAGE_MASK;
+        let offset = (addr & PAGE_MASK) as usize;
+        let endianness = self.endianness;
 
         let data = self.get_page(addr).borrow();
+        let bytes = [data[offset], data[offset + 1]];
 
-        (data[offset as usize] as u16) | ((data[offset as usize + 1] as u16) << 8)
+        match endianness {
+            Endianness::Little => u16::from_le_bytes(bytes),
+            Endianness::Big => u16::from_be_bytes(bytes),
+        }
     }
 
     fn r32(&mut self, addr: u32) -> u32 {
-        let offset = addr & PAGE_MASK;
+        let offset = (addr & PAGE_MASK) as usize;
+        let endianness = self.endianness;
 
         let data = self.get_page(addr).borrow();
-        (data[offset as usize] as u32)
-            | ((data[offset as usize + 1] as u32) << 8)
-            | ((data[offset as usize + 2] as u32) << 16)
-            | ((data[offset as usize + 3] as u32) << 24)
+        endianness.read_u32(data[offset..offset + 4].try_into().unwrap())
     }
 
     fn w8(&mut self, addr: u32, val: u8) {
@@ -201,51 +442,64 @@ impl Memory for Armv4tEmuMemory {
     }
 
     fn w16(&mut self, addr: u32, val: u16) {
-        let offset = addr & PAGE_MASK;
+        let offset = (addr & PAGE_MASK) as usize;
+        let bytes = match self.endianness {
+            Endianness::Little => val.to_le_bytes(),
+            Endianness::Big => val.to_be_bytes(),
+        };
 
         let mut data = self.get_page(addr).borrow_mut();
 
-        data[offset as usize] = val as u8;
-        data[offset as usize + 1] = (val >> 8) as u8;
+        data[offset..offset + 2].copy_from_slice(&bytes);
     }
 
     fn w32(&mut self, addr: u32, val: u32) {
-        let offset = addr & PAGE_MASK;
+        let offset = (addr & PAGE_MASK) as usize;
+        let bytes = self.endianness.write_u32(val);
 
         let mut data = self.get_page(addr).borrow_mut();
 
-        data[offset as usize] = val as u8;
-        data[offset as usize + 1] = (val >> 8) as u8;
-        data[offset as usize + 2] = (val >> 16) as u8;
-        data[offset as usize + 3] = (val >> 24) as u8;
+        data[offset..offset + 4].copy_from_slice(&bytes);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use alloc::vec;
+    use alloc::{collections::BTreeSet, vec, vec::Vec};
     use armv4t_emu::Memory;
 
-    use super::Armv4tEmuMemory;
+    use crate::{
+        engine::{ArmEngine, ArmRegister, Endianness, MemoryPermission},
+        tracer::Tracer,
+        ArmCoreError, ArmCoreResult,
+    };
+
+    use super::{Armv4tEmuEngine, Armv4tEmuMemory, CPSR_THUMB};
 
     #[test]
-    fn test_memory_basic() {
-        let mut memory = Armv4tEmuMemory::new();
+    fn test_memory_basic() -> ArmCoreResult<()> {
+        let mut memory = Armv4tEmuMemory::new(Endianness::Little);
 
         memory.map(0x10000, 0x1000);
         memory.map(0x11000, 0x1000);
         memory.map(0x20000, 0x10000);
 
-        memory.write_range(0x10000, &[123; 0x1000]);
+        memory.write_range(0x10000, &[123; 0x1000])?;
 
-        let data = memory.read_range(0x10000, 0x1000);
+        let data = memory.read_range(0x10000, 0x1000)?;
         assert_eq!(data, vec![123; 0x1000]);
 
-        memory.write_range(0x10900, &[100; 0x1000]);
+        memory.write_range(0x10900, &[100; 0x1000])?;
 
-        let data = memory.read_range(0x10900, 0x1000);
+        let data = memory.read_range(0x10900, 0x1000)?;
         assert_eq!(data, vec![100; 0x1000]);
 
+        // crosses into the next page
+        memory.write_range(0x1fffe, &[1, 2, 3, 4])?;
+        let mut buffer = [0; 4];
+        memory.read_range_into(0x1fffe, &mut buffer)?;
+        assert_eq!(buffer, [1, 2, 3, 4]);
+
         let r8 = memory.r8(0x10000);
         assert_eq!(r8, 123);
 
@@ -266,25 +520,237 @@ mod tests {
         memory.w32(0x10000, 0x12345678);
         let r32 = memory.r32(0x10000);
         assert_eq!(r32, 0x12345678);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_breakpoint() -> ArmCoreResult<()> {
+        let mut engine = Armv4tEmuEngine::new(Endianness::Little);
+
+        engine.mem_map(0x10000, 0x1000, MemoryPermission::ReadWriteExecute);
+        // mov r0, #1; mov r0, #2; mov r0, #3
+        engine.mem_write(0x10000, &[0x01, 0x00, 0xa0, 0xe3, 0x02, 0x00, 0xa0, 0xe3, 0x03, 0x00, 0xa0, 0xe3])?;
+        engine.reg_write(ArmRegister::Cpsr, 0x10);
+        engine.reg_write(ArmRegister::PC, 0x10000);
+
+        let breakpoints = BTreeSet::from([0x10008]);
+
+        assert!(engine.run(0, 0..0, 10, &breakpoints, &mut Tracer::default())?);
+        assert_eq!(engine.reg_read(ArmRegister::PC), 0x10008);
+        assert_eq!(engine.reg_read(ArmRegister::R0), 2);
+
+        // resuming from the breakpoint shouldn't stop on it again
+        assert!(!engine.run(0, 0..0, 1, &breakpoints, &mut Tracer::default())?);
+        assert_eq!(engine.reg_read(ArmRegister::R0), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_branch_history() -> ArmCoreResult<()> {
+        let mut engine = Armv4tEmuEngine::new(Endianness::Little);
+
+        engine.mem_map(0x10000, 0x1000, MemoryPermission::ReadWriteExecute);
+        engine.mem_map(0x30000, 0x1000, MemoryPermission::ReadWrite);
+        // mov r0, #1; b 0x1000c; mov r0, #2; mov r0, #3
+        engine.mem_write(
+            0x10000,
+            &[
+                0x01, 0x00, 0xa0, 0xe3, 0x00, 0x00, 0x00, 0xea, 0x02, 0x00, 0xa0, 0xe3, 0x03, 0x00, 0xa0, 0xe3,
+            ],
+        )?;
+        engine.reg_write(ArmRegister::Cpsr, 0x10);
+        engine.reg_write(ArmRegister::PC, 0x10000);
+
+        engine.run(0, 0..0, 3, &BTreeSet::new(), &mut Tracer::default())?;
+
+        assert_eq!(engine.reg_read(ArmRegister::R0), 3);
+        assert_eq!(engine.branch_history(), vec![(0x10004, 0x1000c)]);
+        assert_eq!(
+            engine.mapped_regions(),
+            vec![
+                (0x10000, 0x10000, MemoryPermission::ReadWriteExecute),
+                (0x30000, 0x10000, MemoryPermission::ReadWrite)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_interworking() -> ArmCoreResult<()> {
+        let mut engine = Armv4tEmuEngine::new(Endianness::Little);
+
+        engine.mem_map(0x10000, 0x1000, MemoryPermission::ReadWriteExecute);
+        // arm: mov r0, #1; bx lr
+        engine.mem_write(0x10000, &[0x01, 0x00, 0xa0, 0xe3, 0x1e, 0xff, 0x2f, 0xe1])?;
+        // thumb: bx r1; movs r0, #2
+        engine.mem_write(0x10100, &[0x08, 0x47, 0x02, 0x20])?;
+        engine.reg_write(ArmRegister::Cpsr, 0x10);
+        engine.reg_write(ArmRegister::R1, 0x10000);
+        engine.reg_write(ArmRegister::LR, 0x10103);
+        engine.reg_write(ArmRegister::PC, 0x10101);
+
+        engine.run(0, 0..0, 2, &BTreeSet::new(), &mut Tracer::default())?;
+        assert_eq!(engine.reg_read(ArmRegister::R0), 1);
+        assert_eq!(engine.reg_read(ArmRegister::Cpsr) & CPSR_THUMB, 0);
+
+        engine.run(0, 0..0, 2, &BTreeSet::new(), &mut Tracer::default())?;
+        assert_eq!(engine.reg_read(ArmRegister::R0), 2);
+        assert_eq!(engine.reg_read(ArmRegister::Cpsr) & CPSR_THUMB, CPSR_THUMB);
+        assert_eq!(engine.reg_read(ArmRegister::PC), 0x10104);
+
+        // returning to arm code by setting pc directly
+        engine.reg_write(ArmRegister::PC, 0x10000);
+        assert_eq!(engine.reg_read(ArmRegister::Cpsr) & CPSR_THUMB, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_permission() -> ArmCoreResult<()> {
+        let mut engine = Armv4tEmuEngine::new(Endianness::Little);
+
+        engine.mem_map(0x10000, 0x1000, MemoryPermission::ReadWriteExecute);
+        engine.mem_map(0x30000, 0x1000, MemoryPermission::ReadWrite);
+        // bx r1
+        engine.mem_write(0x10000, &[0x11, 0xff, 0x2f, 0xe1])?;
+        engine.reg_write(ArmRegister::Cpsr, 0x10);
+        engine.reg_write(ArmRegister::R1, 0x30000);
+        engine.reg_write(ArmRegister::PC, 0x10000);
+
+        let result = engine.run(0, 0..0, 2, &BTreeSet::new(), &mut Tracer::default());
+        assert!(matches!(result, Err(ArmCoreError::ExecuteViolation(0x30000))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_endian() -> ArmCoreResult<()> {
+        let mut engine = Armv4tEmuEngine::new(Endianness::Big);
+
+        engine.mem_map(0x10000, 0x1000, MemoryPermission::ReadExecute);
+        engine.mem_map(0x20000, 0x1000, MemoryPermission::ReadWrite);
+        // ldr r1, [r0]; ldrh r2, [r0]; str r1, [r0, #4]
+        engine.mem_write(0x10000, &[0xe5, 0x90, 0x10, 0x00, 0xe1, 0xd0, 0x20, 0xb0, 0xe5, 0x80, 0x10, 0x04])?;
+        engine.mem_write(0x20000, &[0x12, 0x34, 0x56, 0x78])?;
+        engine.reg_write(ArmRegister::Cpsr, 0x10);
+        engine.reg_write(ArmRegister::R0, 0x20000);
+        engine.reg_write(ArmRegister::PC, 0x10000);
+
+        engine.run(0x1000c, 0..0, 10, &BTreeSet::new(), &mut Tracer::default())?;
+
+        assert_eq!(engine.reg_read(ArmRegister::R1), 0x12345678);
+        assert_eq!(engine.reg_read(ArmRegister::R2), 0x1234);
+        assert_eq!(engine.mem_read(0x20004, 4)?, [0x12, 0x34, 0x56, 0x78]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vfp() -> ArmCoreResult<()> {
+        let mut engine = Armv4tEmuEngine::new(Endianness::Little);
+
+        engine.mem_map(0x10000, 0x1000, MemoryPermission::ReadExecute);
+        // fmsr s0, r0; fsitod d1, s0; faddd d2, d1, d1; ftosizd s4, d2; fmrs r1, s4
+        let code = [0xee000a10u32, 0xeeb81bc0, 0xee312b01, 0xeebd2bc2, 0xee121a10];
+        engine.mem_write(0x10000, &code.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<_>>())?;
+        engine.reg_write(ArmRegister::Cpsr, 0x10);
+        engine.reg_write(ArmRegister::R0, 21);
+        engine.reg_write(ArmRegister::PC, 0x10000);
+
+        engine.run(0x10014, 0..0, 10, &BTreeSet::new(), &mut Tracer::default())?;
+
+        assert_eq!(engine.reg_read(ArmRegister::R1), 42);
+        let (registers, _) = engine.vfp_read();
+        assert_eq!(f64::from_bits(registers[2] as u64 | (registers[3] as u64) << 32), 21.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_context() {
+        let mut engine = Armv4tEmuEngine::new(Endianness::Little);
+        engine.reg_write(ArmRegister::Cpsr, 0x10);
+
+        let mut context = engine.context_read();
+        context.r4 = 4;
+        context.lr = 0x1234;
+        context.fpscr = 1 << 30;
+        context.branch(0x10001);
+        engine.context_write(&context);
+
+        assert_eq!(engine.reg_read(ArmRegister::R4), 4);
+        assert_eq!(engine.reg_read(ArmRegister::LR), 0x1234);
+        assert_eq!(engine.reg_read(ArmRegister::PC), 0x10000);
+        assert_eq!(engine.reg_read(ArmRegister::Cpsr), 0x10 | CPSR_THUMB);
+        assert_eq!(engine.vfp_read().1, 1 << 30);
+    }
+
+    #[test]
+    fn test_guard_page() -> ArmCoreResult<()> {
+        let mut engine = Armv4tEmuEngine::new(Endianness::Little);
+
+        engine.mem_map(0x10000, 0x1000, MemoryPermission::ReadExecute);
+        engine.mem_guard(0x20000, 0x10000);
+        engine.mem_map(0x30000, 0x10000, MemoryPermission::ReadWrite);
+        // push {r0}, twice
+        engine.mem_write(0x10000, &[0x04, 0x00, 0x2d, 0xe5, 0x04, 0x00, 0x2d, 0xe5])?;
+        engine.reg_write(ArmRegister::Cpsr, 0x10);
+        engine.reg_write(ArmRegister::SP, 0x30004);
+        engine.reg_write(ArmRegister::PC, 0x10000);
+
+        let result = engine.run(0, 0..0, 2, &BTreeSet::new(), &mut Tracer::default());
+        assert!(matches!(result, Err(ArmCoreError::GuardPageAccess(0x2fffc))));
+        assert_eq!(engine.reg_read(ArmRegister::PC), 0x10004);
+
+        Ok(())
     }
 
     #[test]
-    #[should_panic]
     fn test_memory_unmapped_read() {
-        let mut memory = Armv4tEmuMemory::new();
+        let mut memory = Armv4tEmuMemory::new(Endianness::Little);
 
         memory.map(0x10000, 0x10000);
 
-        memory.read_range(0x1f500, 0x1000);
+        assert!(matches!(memory.read_range(0x1f500, 0x1000), Err(ArmCoreError::InvalidMemoryAccess)));
+        assert!(matches!(memory.read_range(0x10000, 0xffffffff), Err(ArmCoreError::InvalidMemoryAccess)));
+        assert!(matches!(memory.read_range(0xffff0000, 0x10), Err(ArmCoreError::InvalidMemoryAccess)));
     }
 
     #[test]
-    #[should_panic]
-    fn test_memory_unmapped_write() {
-        let mut memory = Armv4tEmuMemory::new();
+    fn test_memory_unmapped_write() -> ArmCoreResult<()> {
+        let mut memory = Armv4tEmuMemory::new(Endianness::Little);
 
         memory.map(0x10000, 0x10000);
 
-        memory.write_range(0x1f500, &[12; 0x1000]);
+        assert!(matches!(
+            memory.write_range(0x1f500, &[12; 0x1000]),
+            Err(ArmCoreError::InvalidMemoryAccess)
+        ));
+        // nothing is written when the end is unmapped
+        assert_eq!(memory.read_range(0x1f500, 0x10)?, [0; 0x10]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unmapped_access() -> ArmCoreResult<()> {
+        let mut engine = Armv4tEmuEngine::new(Endianness::Little);
+
+        engine.mem_map(0x10000, 0x1000, MemoryPermission::ReadExecute);
+        // ldr r0, [r1]
+        engine.mem_write(0x10000, &[0x00, 0x00, 0x91, 0xe5])?;
+        engine.reg_write(ArmRegister::Cpsr, 0x10);
+        engine.reg_write(ArmRegister::R1, 0x50000);
+        engine.reg_write(ArmRegister::PC, 0x10000);
+
+        let result = engine.run(0, 0..0, 1, &BTreeSet::new(), &mut Tracer::default());
+        assert!(matches!(result, Err(ArmCoreError::UnmappedAccess(0x50000))));
+        assert_eq!(engine.reg_read(ArmRegister::PC), 0x10000);
+        assert_eq!(engine.fault_address(), Some(0x50000));
+
+        Ok(())
     }
 }
@@ -0,0 +1,435 @@
+use armv4t_emu::{reg, Cpu, Memory, Mode};
+
+const FPSID: u32 = 0x41011090; // VFP11 of ARM1136, which is VFPv2
+const FPEXC_EN: u32 = 1 << 30;
+const FPSCR_FLAGS: u32 = 0xf0000000;
+
+// VFPv2 done in software, as armv4t_emu stops at coprocessor instructions.
+// covers the scalar instructions compilers emit for float and double. vector lengths in FPSCR are ignored,
+// and arithmetic is rounded like Rust rounds f32 and f64, which is round to nearest
+#[derive(Clone)]
+pub struct Vfp {
+    // single precision registers. double register n is s[2n] as the low word and s[2n + 1] as the high word
+    pub registers: [u32; 32],
+    pub fpscr: u32,
+    fpexc: u32,
+}
+
+impl Vfp {
+    pub fn new() -> Self {
+        Self {
+            registers: [0; 32],
+            fpscr: 0,
+            fpexc: FPEXC_EN,
+        }
+    }
+
+    // arm state coprocessor 10 and 11 instructions, thumb has none on ARMv4T
+    pub fn is_vfp_instruction(instruction: u32) -> bool {
+        let class = (instruction >> 24) & 0xf;
+        let coprocessor = (instruction >> 8) & 0xf;
+
+        instruction >> 28 != 0xf && (0xc..=0xe).contains(&class) && (coprocessor == 10 || coprocessor == 11)
+    }
+
+    /// Returns false on an instruction we don't know
+    pub fn execute<M: Memory>(&mut self, instruction: u32, cpu: &mut Cpu, mem: &mut M) -> bool {
+        if !condition_passed(instruction >> 28, cpu.reg_get(Mode::User, reg::CPSR)) {
+            return true;
+        }
+
+        let double = (instruction >> 8) & 0xf == 11;
+        match (instruction >> 24) & 0xf {
+            0xc if instruction & 0x0fe00000 == 0x0c400000 => self.transfer_two_registers(instruction, double, cpu),
+            0xc | 0xd => self.transfer_memory(instruction, double, cpu, mem),
+            _ if instruction & 0x10 != 0 => self.transfer_register(instruction, double, cpu),
+            _ => self.data_processing(instruction, double),
+        }
+    }
+
+    // FLDS, FSTS, FLDD, FSTD and the multiple register forms
+    fn transfer_memory<M: Memory>(&mut self, instruction: u32, double: bool, cpu: &mut Cpu, mem: &mut M) -> bool {
+        let pre = instruction & (1 << 24) != 0;
+        let up = instruction & (1 << 23) != 0;
+        let writeback = instruction & (1 << 21) != 0;
+        let load = instruction & (1 << 20) != 0;
+        let rn = ((instruction >> 16) & 0xf) as u8;
+        let offset = (instruction & 0xff) * 4;
+
+        let first = (if double {
+            ((instruction >> 12) & 0xf) * 2
+        } else {
+            ((instruction >> 12) & 0xf) << 1 | (instruction >> 22) & 1
+        }) as usize;
+        let base = read_register(cpu, rn);
+        // FLDMX and FSTMX have an odd word count, with one word more than the registers
+        let multiple_count = (if double { instruction & 0xfe } else { instruction & 0xff }) as usize;
+
+        let (address, count, new_base) = match (pre, up, writeback) {
+            (true, _, false) => {
+                let address = if up { base.wrapping_add(offset) } else { base.wrapping_sub(offset) };
+
+                (address, if double { 2 } else { 1 }, None)
+            }
+            (false, true, _) => (base, multiple_count, writeback.then_some(base.wrapping_add(offset))),
+            (true, false, true) => (base.wrapping_sub(offset), multiple_count, Some(base.wrapping_sub(offset))),
+            _ => return false,
+        };
+        if first + count > self.registers.len() {
+            return false;
+        }
+
+        for i in 0..count {
+            let word_address = address.wrapping_add(i as u32 * 4);
+            if load {
+                self.registers[first + i] = mem.r32(word_address);
+            } else {
+                mem.w32(word_address, self.registers[first + i]);
+            }
+        }
+
+        if let Some(x) = new_base {
+            cpu.reg_set(Mode::User, rn, x);
+        }
+
+        true
+    }
+
+    // FMSR, FMRS, FMDLR, FMRDL, FMDHR, FMRDH, FMXR, FMRX and FMSTAT
+    fn transfer_register(&mut self, instruction: u32, double: bool, cpu: &mut Cpu) -> bool {
+        let to_arm = instruction & (1 << 20) != 0;
+        let rd = ((instruction >> 12) & 0xf) as u8;
+        let crn = (instruction >> 16) & 0xf;
+        let opcode = (instruction >> 21) & 7;
+
+        let index = match (double, opcode) {
+            (false, 0) => (crn << 1 | (instruction >> 7) & 1) as usize,
+            (true, 0) | (true, 1) => (crn * 2 + opcode) as usize,
+            (false, 7) => return self.transfer_system_register(crn, rd, to_arm, cpu),
+            _ => return false,
+        };
+
+        if to_arm {
+            cpu.reg_set(Mode::User, rd, self.registers[index]);
+        } else {
+            self.registers[index] = read_register(cpu, rd);
+        }
+
+        true
+    }
+
+    fn transfer_system_register(&mut self, register: u32, rd: u8, to_arm: bool, cpu: &mut Cpu) -> bool {
+        if to_arm {
+            let value = match register {
+                0 => FPSID,
+                1 => self.fpscr,
+                8 => self.fpexc,
+                _ => return false,
+            };
+
+            // FMSTAT, which copies the comparison flags to CPSR
+            if rd == reg::PC {
+                let cpsr = cpu.reg_get(Mode::User, reg::CPSR);
+                cpu.reg_set(Mode::User, reg::CPSR, (cpsr & !FPSCR_FLAGS) | (value & FPSCR_FLAGS));
+            } else {
+                cpu.reg_set(Mode::User, rd, value);
+            }
+        } else {
+            let value = read_register(cpu, rd);
+            match register {
+                0 => {}
+                1 => self.fpscr = value,
+                8 => self.fpexc = value,
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
+    // FMDRR, FMRRD, FMSRR and FMRRS
+    fn transfer_two_registers(&mut self, instruction: u32, double: bool, cpu: &mut Cpu) -> bool {
+        let to_arm = instruction & (1 << 20) != 0;
+        let rd = ((instruction >> 12) & 0xf) as u8;
+        let rn = ((instruction >> 16) & 0xf) as u8;
+        let index = (if double {
+            (instruction & 0xf) * 2
+        } else {
+            (instruction & 0xf) << 1 | (instruction >> 5) & 1
+        }) as usize;
+        if index + 1 >= self.registers.len() {
+            return false;
+        }
+
+        if to_arm {
+            cpu.reg_set(Mode::User, rd, self.registers[index]);
+            cpu.reg_set(Mode::User, rn, self.registers[index + 1]);
+        } else {
+            self.registers[index] = read_register(cpu, rd);
+            self.registers[index + 1] = read_register(cpu, rn);
+        }
+
+        true
+    }
+
+    fn data_processing(&mut self, instruction: u32, double: bool) -> bool {
+        let opcode = (instruction >> 20) & 0b1000 | (instruction >> 19) & 0b0100 | (instruction >> 19) & 0b0010 | (instruction >> 6) & 1;
+        let vd = (instruction >> 12) & 0xf;
+        let vn = (instruction >> 16) & 0xf;
+        let vm = instruction & 0xf;
+        let sd = (vd << 1 | (instruction >> 22) & 1) as usize;
+        let sn = (vn << 1 | (instruction >> 7) & 1) as usize;
+        let sm = (vm << 1 | (instruction >> 5) & 1) as usize;
+
+        if opcode == 0b1111 {
+            return self.extension(sn as u32, double, vd as usize, sd, vm as usize, sm);
+        }
+
+        if double {
+            let (d, n, m) = (self.d(vd as usize), self.d(vn as usize), self.d(vm as usize));
+            let result = match arithmetic(opcode, d, n, m) {
+                Some(x) => x,
+                None => return false,
+            };
+            self.set_d(vd as usize, result);
+        } else {
+            let (d, n, m) = (self.s(sd), self.s(sn), self.s(sm));
+            let result = match arithmetic(opcode, d, n, m) {
+                Some(x) => x,
+                None => return false,
+            };
+            self.set_s(sd, result);
+        }
+
+        true
+    }
+
+    // the instructions with one source, selected by Fn and N
+    fn extension(&mut self, opcode: u32, double: bool, dd: usize, sd: usize, dm: usize, sm: usize) -> bool {
+        let rounding = (self.fpscr >> 22) & 3;
+
+        match (opcode, double) {
+            (0b00000, false) => self.set_s(sd, self.s(sm)),
+            (0b00000, true) => self.set_d(dd, self.d(dm)),
+            (0b00001, false) => self.registers[sd] = self.registers[sm] & !(1 << 31),
+            (0b00001, true) => self.set_d(dd, f64::from_bits(self.d(dm).to_bits() & !(1 << 63))),
+            (0b00010, false) => self.set_s(sd, -self.s(sm)),
+            (0b00010, true) => self.set_d(dd, -self.d(dm)),
+            (0b00011, false) => self.set_s(sd, sqrt(self.s(sm) as f64) as f32),
+            (0b00011, true) => self.set_d(dd, sqrt(self.d(dm))),
+            (0b01000..=0b01011, false) => {
+                let m = if opcode & 0b10 != 0 { 0.0 } else { self.s(sm) };
+                self.compare(self.s(sd) as f64, m as f64);
+            }
+            (0b01000..=0b01011, true) => {
+                let m = if opcode & 0b10 != 0 { 0.0 } else { self.d(dm) };
+                self.compare(self.d(dd), m);
+            }
+            (0b01111, false) => self.set_d(dd, self.s(sm) as f64),
+            (0b01111, true) => self.set_s(sd, self.d(dm) as f32),
+            (0b10000, false) => self.set_s(sd, self.registers[sm] as f32),
+            (0b10000, true) => self.set_d(dd, self.registers[sm] as f64),
+            (0b10001, false) => self.set_s(sd, self.registers[sm] as i32 as f32),
+            (0b10001, true) => self.set_d(dd, self.registers[sm] as i32 as f64),
+            (0b11000..=0b11011, _) => {
+                let value = if double { self.d(dm) } else { self.s(sm) as f64 };
+                let rounding = if opcode & 1 != 0 { 3 } else { rounding };
+
+                self.registers[sd] = to_integer(value, opcode & 0b10 != 0, rounding);
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn compare(&mut self, a: f64, b: f64) {
+        let flags = if a.is_nan() || b.is_nan() {
+            0b0011
+        } else if a == b {
+            0b0110
+        } else if a < b {
+            0b1000
+        } else {
+            0b0010
+        };
+
+        self.fpscr = (self.fpscr & !FPSCR_FLAGS) | flags << 28;
+    }
+
+    fn s(&self, index: usize) -> f32 {
+        f32::from_bits(self.registers[index])
+    }
+
+    fn set_s(&mut self, index: usize, value: f32) {
+        self.registers[index] = value.to_bits();
+    }
+
+    fn d(&self, index: usize) -> f64 {
+        f64::from_bits(self.registers[index * 2] as u64 | (self.registers[index * 2 + 1] as u64) << 32)
+    }
+
+    fn set_d(&mut self, index: usize, value: f64) {
+        let bits = value.to_bits();
+
+        self.registers[index * 2] = bits as u32;
+        self.registers[index * 2 + 1] = (bits >> 32) as u32;
+    }
+}
+
+trait Float:
+    Copy
+    + core::ops::Add<Output = Self>
+    + core::ops::Sub<Output = Self>
+    + core::ops::Mul<Output = Self>
+    + core::ops::Div<Output = Self>
+    + core::ops::Neg<Output = Self>
+{
+}
+
+impl Float for f32 {}
+impl Float for f64 {}
+
+// FMAC, FNMAC, FMSC, FNMSC, FMUL, FNMUL, FADD, FSUB and FDIV by the p, q, r and s bits
+fn arithmetic<T: Float>(opcode: u32, d: T, n: T, m: T) -> Option<T> {
+    Some(match opcode {
+        0b0000 => d + n * m,
+        0b0001 => d - n * m,
+        0b0010 => -d + n * m,
+        0b0011 => -d - n * m,
+        0b0100 => n * m,
+        0b0101 => -(n * m),
+        0b0110 => n + m,
+        0b0111 => n - m,
+        0b1000 => n / m,
+        _ => return None,
+    })
+}
+
+fn condition_passed(condition: u32, cpsr: u32) -> bool {
+    let n = cpsr & (1 << 31) != 0;
+    let z = cpsr & (1 << 30) != 0;
+    let c = cpsr & (1 << 29) != 0;
+    let v = cpsr & (1 << 28) != 0;
+
+    match condition {
+        0x0 => z,
+        0x1 => !z,
+        0x2 => c,
+        0x3 => !c,
+        0x4 => n,
+        0x5 => !n,
+        0x6 => v,
+        0x7 => !v,
+        0x8 => c && !z,
+        0x9 => !c || z,
+        0xa => n == v,
+        0xb => n != v,
+        0xc => !z && n == v,
+        0xd => z || n != v,
+        _ => true,
+    }
+}
+
+// pc reads as the instruction address plus 8 in arm state
+fn read_register(cpu: &Cpu, register: u8) -> u32 {
+    let value = cpu.reg_get(Mode::User, register);
+
+    if register == reg::PC {
+        value + 8
+    } else {
+        value
+    }
+}
+
+// rounding is the RMode field of FPSCR: to nearest, towards plus infinity, towards minus infinity and towards zero.
+// out of range values saturate and nan becomes 0
+fn to_integer(value: f64, signed: bool, rounding: u32) -> u32 {
+    let truncated = value as i64 as f64;
+    let fraction = value - truncated;
+
+    let rounded = match rounding {
+        0 => {
+            let half_even = fraction == 0.5 || fraction == -0.5;
+            if fraction > 0.5 || (half_even && fraction > 0.0 && truncated as i64 % 2 != 0) {
+                truncated + 1.0
+            } else if fraction < -0.5 || (half_even && fraction < 0.0 && truncated as i64 % 2 != 0) {
+                truncated - 1.0
+            } else {
+                truncated
+            }
+        }
+        1 if fraction > 0.0 => truncated + 1.0,
+        2 if fraction < 0.0 => truncated - 1.0,
+        _ => truncated,
+    };
+
+    if signed {
+        rounded as i32 as u32
+    } else {
+        rounded as u32
+    }
+}
+
+// core has no sqrt. correctly rounded from the integer square root of the mantissa scaled to 106 bits
+fn sqrt(value: f64) -> f64 {
+    if value == 0.0 || value == f64::INFINITY || value.is_nan() {
+        return value;
+    }
+    if value < 0.0 {
+        return f64::NAN;
+    }
+
+    let bits = value.to_bits();
+    let mut exponent = ((bits >> 52) & 0x7ff) as i32 - 1075;
+    let mut mantissa = bits & ((1 << 52) - 1);
+    if bits >> 52 == 0 {
+        // subnormal
+        exponent += 1;
+        while mantissa & (1 << 52) == 0 {
+            mantissa <<= 1;
+            exponent -= 1;
+        }
+    } else {
+        mantissa |= 1 << 52;
+    }
+    if exponent % 2 != 0 {
+        mantissa <<= 1;
+        exponent -= 1;
+    }
+
+    let scaled = (mantissa as u128) << 52;
+    let mut root = scaled.isqrt();
+    // (root + 0.5)^2 is root^2 + root + 0.25, so a remainder above root is closer to root + 1
+    if scaled - root * root > root {
+        root += 1;
+    }
+
+    (root as f64) * f64::from_bits((((exponent - 52) / 2 + 1023) as u64) << 52)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sqrt, to_integer};
+
+    #[test]
+    fn test_to_integer() {
+        assert_eq!(to_integer(2.5, true, 0), 2);
+        assert_eq!(to_integer(3.5, true, 0), 4);
+        assert_eq!(to_integer(-2.7, true, 0), -3i32 as u32);
+        assert_eq!(to_integer(-2.7, true, 3), -2i32 as u32);
+        assert_eq!(to_integer(2.1, true, 1), 3);
+        assert_eq!(to_integer(-2.1, true, 2), -3i32 as u32);
+        assert_eq!(to_integer(-1.0, false, 0), 0);
+        assert_eq!(to_integer(1e20, true, 0), i32::MAX as u32);
+        assert_eq!(to_integer(f64::NAN, true, 0), 0);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        assert_eq!(sqrt(4.0), 2.0);
+        assert_eq!(sqrt(2.0), core::f64::consts::SQRT_2);
+        assert_eq!(sqrt(5e-324), 2.2227587494850775e-162);
+        assert!(sqrt(-1.0).is_nan());
+    }
+}
@@ -5,6 +5,27 @@ use wie_util::ByteReadWriteError;
 pub enum ArmCoreError {
     InvalidMemoryAccess,
     FunctionCallError(String),
+    InvalidSnapshot,
+    /// Snapshot taken with another binary loaded, or while tasks were somewhere else than now
+    SnapshotMismatch,
+    /// Native code is waiting on guest code it called, which snapshots can't capture
+    GuestCallPending,
+    InvalidSegment(u32),
+    /// Jump to memory not mapped as code, with the address jumped to
+    ExecuteViolation(u32),
+    /// Instruction we can't run, with its address
+    UndefinedInstruction(u32),
+    /// Access to a guard page, with the address accessed
+    GuardPageAccess(u32),
+    /// Guest access to memory that is not mapped, with the address accessed
+    UnmappedAccess(u32),
+    /// Guest code ran past the end of the stack of the task with the id
+    StackOverflow(usize),
+    OutOfMemory,
+    /// Config the core can't run with yet
+    UnsupportedConfig,
+    /// Free or realloc of an address that is not an allocation in use, with the address
+    InvalidFree(u32),
     Other,
 }
 
@@ -1,10 +1,12 @@
-use alloc::{boxed::Box, format, string::String};
+use alloc::{boxed::Box, format, string::String, vec::Vec};
 use core::{fmt::Debug, future::Future, marker::PhantomData};
 
+use bytemuck::Pod;
+
 use wie_backend::System;
-use wie_util::read_null_terminated_string;
+use wie_util::ByteRead;
 
-use crate::{ArmCore, ArmCoreError, ArmCoreResult};
+use crate::{context::ArmCoreContext, ArmCore, ArmCoreError, ArmCoreResult};
 
 #[async_trait::async_trait(?Send)]
 pub trait RegisteredFunction {
@@ -60,7 +62,8 @@ where
 
 trait FnHelper<'a, E, R, P> {
     type Output: Future<Output = Result<R, E>> + 'a;
-    fn do_call(&self, core: &'a mut ArmCore, system: &'a mut System) -> Self::Output;
+    // parameters are read before the call, so failing to read them fails the call instead of the function
+    fn do_call(&self, core: &'a mut ArmCore, system: &'a mut System) -> ArmCoreResult<Self::Output>;
 }
 
 macro_rules! generate_fn_helper {
@@ -74,13 +77,14 @@ macro_rules! generate_fn_helper {
         {
             type Output = Fut;
             #[allow(unused_variables, unused_assignments, non_snake_case, unused_mut)]
-            fn do_call(&self, core: &'a mut ArmCore, system: &'a mut System) -> Fut {
+            fn do_call(&self, core: &'a mut ArmCore, system: &'a mut System) -> ArmCoreResult<Fut> {
                 let mut index = 0;
                 $(
-                    let $arg = $arg::get(core, index);
-                    index += 1;
+                    index = index.next_multiple_of($arg::ALIGN);
+                    let $arg = $arg::get(core, index)?;
+                    index += $arg::WORDS;
                 )*
-                self(core, system, $($arg),*)
+                Ok(self(core, system, $($arg),*))
             }
         }
     };
@@ -91,6 +95,10 @@ generate_fn_helper!(P0);
 generate_fn_helper!(P0, P1);
 generate_fn_helper!(P0, P1, P2);
 generate_fn_helper!(P0, P1, P2, P3);
+generate_fn_helper!(P0, P1, P2, P3, P4);
+generate_fn_helper!(P0, P1, P2, P3, P4, P5);
+generate_fn_helper!(P0, P1, P2, P3, P4, P5, P6);
+generate_fn_helper!(P0, P1, P2, P3, P4, P5, P6, P7);
 
 #[async_trait::async_trait(?Send)]
 pub trait EmulatedFunction<P, E, R> {
@@ -103,10 +111,11 @@ macro_rules! generate_emulated_function {
         impl<Func, E, R, $($arg),*> EmulatedFunction<($($arg,)*), E, R> for Func
         where
             Func: for<'a> FnHelper<'a, E, R, ($($arg,)*)>,
+            E: From<ArmCoreError>,
             $($arg: EmulatedFunctionParam<$arg>),*
         {
             async fn call(&self, core: &mut ArmCore, system: &mut System) -> Result<R, E> {
-                self.do_call(core, system).await
+                self.do_call(core, system)?.await
             }
         }
     };
@@ -117,29 +126,115 @@ generate_emulated_function!(P0);
 generate_emulated_function!(P0, P1);
 generate_emulated_function!(P0, P1, P2);
 generate_emulated_function!(P0, P1, P2, P3);
+generate_emulated_function!(P0, P1, P2, P3, P4);
+generate_emulated_function!(P0, P1, P2, P3, P4, P5);
+generate_emulated_function!(P0, P1, P2, P3, P4, P5, P6);
+generate_emulated_function!(P0, P1, P2, P3, P4, P5, P6, P7);
 
+// parameters are laid out like AAPCS soft-float, where 64-bit values start at an even word in registers and on the stack
 pub trait EmulatedFunctionParam<T> {
-    fn get(core: &mut ArmCore, pos: usize) -> T;
+    /// Words the parameter takes
+    const WORDS: usize = 1;
+    /// Word alignment of the first of them
+    const ALIGN: usize = 1;
+
+    fn get(core: &mut ArmCore, pos: usize) -> ArmCoreResult<T>;
 
-    fn read(core: &mut ArmCore, pos: usize) -> u32 {
-        core.read_param(pos).unwrap()
+    fn read(core: &mut ArmCore, pos: usize) -> ArmCoreResult<u32> {
+        core.read_param(pos)
     }
 }
 
 impl EmulatedFunctionParam<String> for String {
-    fn get(core: &mut ArmCore, pos: usize) -> String {
-        let ptr = Self::read(core, pos);
-
-        read_null_terminated_string(core, ptr).unwrap()
+    fn get(core: &mut ArmCore, pos: usize) -> ArmCoreResult<String> {
+        core.read_c_string(pos)
     }
 }
 
 impl EmulatedFunctionParam<u32> for u32 {
-    fn get(core: &mut ArmCore, pos: usize) -> u32 {
+    fn get(core: &mut ArmCore, pos: usize) -> ArmCoreResult<u32> {
         Self::read(core, pos)
     }
 }
 
+impl EmulatedFunctionParam<i32> for i32 {
+    fn get(core: &mut ArmCore, pos: usize) -> ArmCoreResult<i32> {
+        Ok(Self::read(core, pos)? as i32)
+    }
+}
+
+impl EmulatedFunctionParam<bool> for bool {
+    fn get(core: &mut ArmCore, pos: usize) -> ArmCoreResult<bool> {
+        Ok(Self::read(core, pos)? != 0)
+    }
+}
+
+impl EmulatedFunctionParam<f32> for f32 {
+    fn get(core: &mut ArmCore, pos: usize) -> ArmCoreResult<f32> {
+        Ok(f32::from_bits(Self::read(core, pos)?))
+    }
+}
+
+impl EmulatedFunctionParam<u64> for u64 {
+    const WORDS: usize = 2;
+    const ALIGN: usize = 2;
+
+    fn get(core: &mut ArmCore, pos: usize) -> ArmCoreResult<u64> {
+        Ok(Self::read(core, pos)? as u64 | (Self::read(core, pos + 1)? as u64) << 32)
+    }
+}
+
+impl EmulatedFunctionParam<i64> for i64 {
+    const WORDS: usize = 2;
+    const ALIGN: usize = 2;
+
+    fn get(core: &mut ArmCore, pos: usize) -> ArmCoreResult<i64> {
+        Ok(u64::get(core, pos)? as i64)
+    }
+}
+
+impl EmulatedFunctionParam<f64> for f64 {
+    const WORDS: usize = 2;
+    const ALIGN: usize = 2;
+
+    fn get(core: &mut ArmCore, pos: usize) -> ArmCoreResult<f64> {
+        Ok(f64::from_bits(u64::get(core, pos)?))
+    }
+}
+
+/// Pointer parameter read as the struct it points to, zeroed if null. `address` is kept to write changes back.
+pub struct StructPtr<T> {
+    pub address: u32,
+    pub value: T,
+}
+
+impl<T: Pod> EmulatedFunctionParam<StructPtr<T>> for StructPtr<T> {
+    fn get(core: &mut ArmCore, pos: usize) -> ArmCoreResult<StructPtr<T>> {
+        let address = Self::read(core, pos)?;
+        let value = if address == 0 { T::zeroed() } else { core.read_struct(pos)? };
+
+        Ok(StructPtr { address, value })
+    }
+}
+
+/// Buffer passed as a pointer followed by its length in bytes.
+pub struct ByteSlice {
+    pub address: u32,
+    pub data: Vec<u8>,
+}
+
+impl EmulatedFunctionParam<ByteSlice> for ByteSlice {
+    const WORDS: usize = 2;
+
+    fn get(core: &mut ArmCore, pos: usize) -> ArmCoreResult<ByteSlice> {
+        let address = Self::read(core, pos)?;
+        let length = Self::read(core, pos + 1)?;
+        let data = if address == 0 { Vec::new() } else { core.read_bytes(address, length)? };
+
+        Ok(ByteSlice { address, data })
+    }
+}
+
 pub trait ResultWriter<R> {
     fn write(core: &mut ArmCore, value: R, lr: u32) -> ArmCoreResult<()>;
 }
@@ -150,8 +245,108 @@ impl ResultWriter<u32> for u32 {
     }
 }
 
+impl ResultWriter<i32> for i32 {
+    fn write(core: &mut ArmCore, value: i32, lr: u32) -> ArmCoreResult<()> {
+        core.write_result(value as u32, lr)
+    }
+}
+
+impl ResultWriter<bool> for bool {
+    fn write(core: &mut ArmCore, value: bool, lr: u32) -> ArmCoreResult<()> {
+        core.write_result(value as u32, lr)
+    }
+}
+
+impl ResultWriter<f32> for f32 {
+    fn write(core: &mut ArmCore, value: f32, lr: u32) -> ArmCoreResult<()> {
+        core.write_result(value.to_bits(), lr)
+    }
+}
+
+impl ResultWriter<u64> for u64 {
+    fn write(core: &mut ArmCore, value: u64, lr: u32) -> ArmCoreResult<()> {
+        core.write_result_u64(value, lr)
+    }
+}
+
+impl ResultWriter<i64> for i64 {
+    fn write(core: &mut ArmCore, value: i64, lr: u32) -> ArmCoreResult<()> {
+        core.write_result_u64(value as u64, lr)
+    }
+}
+
+impl ResultWriter<f64> for f64 {
+    fn write(core: &mut ArmCore, value: f64, lr: u32) -> ArmCoreResult<()> {
+        core.write_result_u64(value.to_bits(), lr)
+    }
+}
+
 impl ResultWriter<()> for () {
     fn write(core: &mut ArmCore, _: (), lr: u32) -> ArmCoreResult<()> {
         core.write_result(0, lr)
     }
 }
+
+/// Result of a function which may continue somewhere other than its caller, like longjmp.
+pub enum Resume<R> {
+    Return(R),
+    Jump(ArmCoreContext),
+}
+
+impl<R> ResultWriter<Resume<R>> for Resume<R>
+where
+    R: ResultWriter<R>,
+{
+    fn write(core: &mut ArmCore, value: Resume<R>, lr: u32) -> ArmCoreResult<()> {
+        match value {
+            Resume::Return(x) => R::write(core, x, lr),
+            Resume::Jump(context) => {
+                core.restore_context(&context);
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+
+    use wie_backend::System;
+
+    use test_utils::TestPlatform;
+
+    use crate::{core::HEAP_BASE, ArmCore, ArmCoreConfig, ArmCoreError, ArmCoreResult};
+
+    use super::{ByteSlice, FnHelper};
+
+    async fn length(_: &mut ArmCore, _: &mut System, data: ByteSlice) -> ArmCoreResult<u32> {
+        Ok(data.data.len() as u32)
+    }
+
+    #[test]
+    fn test_byte_slice_unmapped() -> ArmCoreResult<()> {
+        let mut system = System::new(Box::new(TestPlatform), Box::new(()));
+        let mut core = ArmCore::new(system.clone(), ArmCoreConfig::default())?;
+        core.map(HEAP_BASE, 0x10000, "heap", "test")?;
+
+        let mut context = core.save_context();
+        context.r0 = HEAP_BASE + 0xff00;
+        context.r1 = 0x100;
+        core.restore_context(&context);
+
+        assert!(<_ as FnHelper<'_, ArmCoreError, u32, (ByteSlice,)>>::do_call(&length, &mut core, &mut system).is_ok());
+
+        // runs into the unmapped page after the heap
+        context.r1 = 0x200;
+        core.restore_context(&context);
+
+        assert!(matches!(
+            <_ as FnHelper<'_, ArmCoreError, u32, (ByteSlice,)>>::do_call(&length, &mut core, &mut system),
+            Err(ArmCoreError::InvalidMemoryAccess)
+        ));
+
+        Ok(())
+    }
+}
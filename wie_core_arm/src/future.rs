@@ -8,12 +8,15 @@ use core::{
 
 use wie_backend::AsyncCallable;
 
-use crate::{context::ArmCoreContext, Allocator, ArmCore};
+use crate::{
+    context::ArmCoreContext,
+    core::{ArmCore, TASK_STACK_SIZE},
+};
 
 pub struct SpawnFuture<C, R, E> {
     core: ArmCore,
-    context: ArmCoreContext,
     stack_base: u32,
+    finished: bool,
     callable_fut: Pin<Box<dyn Future<Output = Result<R, E>>>>,
     _phantom: PhantomData<C>,
 }
@@ -24,15 +27,15 @@ where
     R: 'static,
     E: core::fmt::Debug + 'static,
 {
-    pub fn new(mut core: ArmCore, callable: C) -> Self {
-        let stack_base = Allocator::alloc(&mut core, 0x1000).unwrap();
-        let context = ArmCoreContext::new(stack_base);
+    pub fn new(mut core: ArmCore, stack_base: u32, callable: C) -> Self {
+        // registers are kept in the core while the task is suspended, so restoring a snapshot restores them as well
+        core.set_task_context(stack_base, Some(ArmCoreContext::new(stack_base, TASK_STACK_SIZE)));
         let callable_fut = Box::pin(callable.call());
 
         Self {
             core,
-            context,
             stack_base,
+            finished: false,
             callable_fut,
             _phantom: PhantomData,
         }
@@ -43,19 +46,40 @@ impl<C, R, E> Future for SpawnFuture<C, R, E> {
     type Output = Result<R, E>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.core.clone().restore_context(&self.context); // XXX clone is added to satisfy borrow checker
+        let stack_base = self.stack_base;
+
+        let context = self.core.task_context(stack_base).unwrap();
+        self.core.restore_context(&context);
         let result = self.callable_fut.as_mut().poll(cx);
-        self.context = self.core.save_context();
+        let context = self.core.save_context();
 
         if let Poll::Ready(x) = result {
-            let stack_base = self.stack_base;
-            Allocator::free(&mut self.core, stack_base).unwrap();
+            self.release();
 
             Poll::Ready(x)
         } else {
+            self.core.set_task_context(stack_base, Some(context));
+
             Poll::Pending
         }
     }
 }
 
+impl<C, R, E> SpawnFuture<C, R, E> {
+    fn release(&mut self) {
+        if !self.finished {
+            self.finished = true;
+            self.core.set_task_context(self.stack_base, None);
+            self.core.free_stack(self.stack_base);
+        }
+    }
+}
+
+// a cancelled task is dropped without finishing, and gives its stack back here
+impl<C, R, E> Drop for SpawnFuture<C, R, E> {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
 impl<C, R, E> Unpin for SpawnFuture<C, R, E> {}
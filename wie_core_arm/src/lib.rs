@@ -8,12 +8,17 @@ mod engine;
 mod error;
 mod function;
 mod future;
+mod memory_map;
+mod symbols;
+mod tracer;
 
 pub type ArmCoreResult<T> = Result<T, error::ArmCoreError>;
 
 pub use self::{
     allocator::Allocator,
-    core::{ArmCore, PEB_BASE},
+    context::ArmCoreContext,
+    core::{ArmCore, ArmCoreConfig, FunctionArgs, Segment, HEAP_BASE, PEB_BASE},
+    engine::{Endianness, MemoryPermission},
     error::ArmCoreError,
-    function::{EmulatedFunction, EmulatedFunctionParam},
+    function::{ByteSlice, EmulatedFunction, EmulatedFunctionParam, Resume, StructPtr},
 };
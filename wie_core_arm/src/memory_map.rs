@@ -0,0 +1,70 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::engine::MemoryPermission;
+
+pub struct MemoryRegion {
+    pub name: String,
+    pub address: u32,
+    pub size: u32,
+    pub permission: MemoryPermission,
+    /// What mapped it, like the loader of the platform or the allocator
+    pub owner: String,
+}
+
+// what lives where in the guest address space, as told by whoever mapped it
+#[derive(Default)]
+pub struct MemoryMap {
+    regions: Vec<MemoryRegion>,
+}
+
+impl MemoryMap {
+    pub fn add(&mut self, name: &str, address: u32, size: u32, permission: MemoryPermission, owner: &str) {
+        self.regions.push(MemoryRegion {
+            name: name.to_string(),
+            address,
+            size,
+            permission,
+            owner: owner.to_string(),
+        });
+    }
+
+    pub fn find(&self, address: u32) -> Option<&MemoryRegion> {
+        self.regions
+            .iter()
+            .find(|x| address >= x.address && (address as u64) < x.address as u64 + x.size as u64)
+    }
+
+    /// Sorted by address
+    pub fn regions(&self) -> Vec<&MemoryRegion> {
+        let mut result = self.regions.iter().collect::<Vec<_>>();
+        result.sort_by_key(|x| x.address);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::MemoryPermission;
+
+    use super::MemoryMap;
+
+    #[test]
+    fn test_memory_map() {
+        let mut map = MemoryMap::default();
+
+        map.add("heap", 0x40000000, 0x1000000, MemoryPermission::ReadWrite, "allocator");
+        map.add("client.bin", 0x100000, 0x2000, MemoryPermission::ReadWriteExecute, "ktf");
+
+        assert_eq!(map.find(0x101fff).map(|x| x.name.as_str()), Some("client.bin"));
+        assert_eq!(map.find(0x40ffffff).map(|x| x.owner.as_str()), Some("allocator"));
+        assert!(map.find(0x102000).is_none());
+        assert_eq!(
+            map.regions().iter().map(|x| x.address).collect::<alloc::vec::Vec<_>>(),
+            [0x100000, 0x40000000]
+        );
+    }
+}
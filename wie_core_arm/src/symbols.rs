@@ -0,0 +1,79 @@
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+struct Module {
+    name: String,
+    base: u32,
+    size: u32,
+}
+
+// loaded images and the functions we know the names of in them, to describe guest addresses in call stacks
+#[derive(Default)]
+pub struct SymbolTable {
+    modules: Vec<Module>,
+    // keyed without the thumb bit
+    symbols: BTreeMap<u32, String>,
+}
+
+impl SymbolTable {
+    pub fn add_module(&mut self, name: &str, base: u32, size: u32) {
+        self.modules.push(Module {
+            name: name.to_string(),
+            base,
+            size,
+        });
+    }
+
+    pub fn add_symbol(&mut self, address: u32, name: &str) {
+        self.symbols.insert(address & !1, name.to_string());
+    }
+
+    pub fn contains(&self, address: u32) -> bool {
+        self.module(address).is_some()
+    }
+
+    /// Like `client.bin!init+0x10`, or `client.bin+0x1234` if no symbol precedes it in the module
+    pub fn describe(&self, address: u32) -> Option<String> {
+        let module = self.module(address)?;
+
+        let symbol = self
+            .symbols
+            .range(module.base..=address)
+            .next_back()
+            .map(|(symbol_address, name)| (name, address - symbol_address));
+
+        Some(match symbol {
+            Some((name, 0)) => format!("{}!{}", module.name, name),
+            Some((name, offset)) => format!("{}!{}+{:#x}", module.name, name, offset),
+            None => format!("{}+{:#x}", module.name, address - module.base),
+        })
+    }
+
+    fn module(&self, address: u32) -> Option<&Module> {
+        self.modules.iter().find(|x| (x.base..x.base + x.size).contains(&address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SymbolTable;
+
+    #[test]
+    fn test_describe() {
+        let mut symbols = SymbolTable::default();
+
+        symbols.add_module("client.bin", 0x100000, 0x2000);
+        symbols.add_symbol(0x100101, "init");
+        symbols.add_symbol(0x300000, "elsewhere");
+
+        assert_eq!(symbols.describe(0x100010).as_deref(), Some("client.bin+0x10"));
+        assert_eq!(symbols.describe(0x100100).as_deref(), Some("client.bin!init"));
+        assert_eq!(symbols.describe(0x100124).as_deref(), Some("client.bin!init+0x24"));
+        assert_eq!(symbols.describe(0x102000), None);
+        assert!(!symbols.contains(0x300000));
+    }
+}
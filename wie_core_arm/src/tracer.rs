@@ -0,0 +1,118 @@
+use alloc::{collections::VecDeque, format, string::String, vec::Vec};
+use core::ops::Range;
+
+const DEFAULT_CAPACITY: usize = 4096;
+
+pub enum TraceEntry {
+    Instruction(u32),
+    /// Call into a registered native function, with its name if it has one
+    Function(u32, Option<String>),
+}
+
+// recent execution kept in memory to be looked at when something goes wrong, as logging every instruction is too slow to play with.
+// nothing is recorded until a region or function tracing is enabled
+pub struct Tracer {
+    regions: Vec<Range<u32>>,
+    functions: bool,
+    entries: VecDeque<TraceEntry>,
+    capacity: usize,
+}
+
+impl Tracer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            regions: Vec::new(),
+            functions: false,
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn set_region(&mut self, region: Range<u32>, enabled: bool) {
+        if enabled {
+            if !self.regions.contains(&region) {
+                self.regions.push(region);
+            }
+        } else {
+            self.regions.retain(|x| *x != region);
+        }
+    }
+
+    pub fn set_functions(&mut self, enabled: bool) {
+        self.functions = enabled;
+    }
+
+    // called on every instruction, so it should be cheap when tracing is off
+    #[inline]
+    pub fn instruction(&mut self, pc: u32) {
+        if !self.regions.is_empty() && self.regions.iter().any(|x| x.contains(&pc)) {
+            self.push(TraceEntry::Instruction(pc));
+        }
+    }
+
+    pub fn function(&mut self, address: u32, name: Option<&str>) {
+        if self.functions {
+            self.push(TraceEntry::Function(address, name.map(|x| x.into())));
+        }
+    }
+
+    /// Oldest first
+    pub fn entries(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|x| match x {
+                TraceEntry::Instruction(pc) => format!("{:#010x}", pc),
+                TraceEntry::Function(address, name) => format!("{:#010x} <Native function {}>", address, name.as_deref().unwrap_or("<Unknown>")),
+            })
+            .collect()
+    }
+
+    fn push(&mut self, entry: TraceEntry) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::Tracer;
+
+    #[test]
+    fn test_tracer() {
+        let mut tracer = Tracer::new(3);
+
+        // off by default
+        tracer.instruction(0x1000);
+        tracer.function(0x71000000, Some("MC_knlPrintk"));
+        assert!(tracer.entries().is_empty());
+
+        tracer.set_region(0x1000..0x2000, true);
+        tracer.set_functions(true);
+        tracer.instruction(0x1000);
+        tracer.instruction(0x3000);
+        tracer.function(0x71000000, Some("MC_knlPrintk"));
+        tracer.instruction(0x1004);
+        tracer.instruction(0x1008);
+
+        // oldest one is dropped when full
+        assert_eq!(
+            tracer.entries(),
+            vec!["0x71000000 <Native function MC_knlPrintk>", "0x00001004", "0x00001008"]
+        );
+
+        tracer.set_region(0x1000..0x2000, false);
+        tracer.instruction(0x100c);
+        assert_eq!(tracer.entries().len(), 3);
+        assert_eq!(tracer.entries()[2], "0x00001008");
+    }
+}
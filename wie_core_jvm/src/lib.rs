@@ -26,7 +26,7 @@ struct JvmCoreRuntime {
 #[async_trait::async_trait(?Send)]
 impl Runtime for JvmCoreRuntime {
     async fn sleep(&self, duration: Duration) {
-        let now = self.system.platform().now();
+        let now = self.system.now();
         let until = now + duration.as_millis() as u64;
 
         self.system.clone().sleep(until).await; // TODO remove clone
@@ -58,7 +58,7 @@ impl Runtime for JvmCoreRuntime {
     }
 
     fn now(&self) -> u64 {
-        self.system.platform().now().raw()
+        self.system.now().raw()
     }
 
     fn encode_str(&self, s: &str) -> Vec<u8> {
@@ -1,6 +1,6 @@
-use alloc::{string::String, vec::Vec};
+use alloc::{boxed::Box, string::String, vec::Vec};
 
-use wie_backend::{App, Event, System};
+use wie_backend::{App, CompatibilityReport, Event, GuestAccess, System, Telemetry};
 use wie_core_jvm::JvmCore;
 
 pub struct J2MEApp {
@@ -35,7 +35,7 @@ impl J2MEApp {
         let normalized_class_name = main_class_name.replace('.', "/");
         let main_class = core.jvm().new_class(&normalized_class_name, "()V", []).await?;
 
-        let result: Result<(), _> = core.jvm().invoke_virtual(&main_class, "startApp", "()V", [None.into()]).await;
+        let result: Result<(), _> = core.jvm().invoke_virtual(&main_class, "startApp", "()V", ()).await;
         if let Err(x) = result {
             anyhow::bail!(JvmCore::format_err(core.jvm(), x).await)
         }
@@ -64,4 +64,24 @@ impl App for J2MEApp {
     fn tick(&mut self) -> anyhow::Result<()> {
         self.system.tick()
     }
+
+    fn report(&self) -> CompatibilityReport {
+        self.system.report().clone()
+    }
+
+    fn save_state(&self) -> anyhow::Result<Vec<u8>> {
+        anyhow::bail!("Savestate is not supported on J2ME yet")
+    }
+
+    fn load_state(&mut self, _state: &[u8]) -> anyhow::Result<()> {
+        anyhow::bail!("Savestate is not supported on J2ME yet")
+    }
+
+    fn guest(&self) -> Option<Box<dyn GuestAccess>> {
+        None
+    }
+
+    fn telemetry(&self) -> Telemetry {
+        self.system.telemetry()
+    }
 }
@@ -1,11 +1,12 @@
 use alloc::{
     boxed::Box,
+    collections::BTreeMap,
     str,
     string::{String, ToString},
     vec::Vec,
 };
 
-use wie_backend::{App, Archive, Platform, System};
+use wie_backend::{extract_zip, App, Archive, Platform, System};
 
 use crate::app::J2MEApp;
 
@@ -13,6 +14,7 @@ pub struct J2MEArchive {
     jar: Vec<u8>,
     name: String,
     main_class_name: Option<String>,
+    icon_path: Option<String>,
 }
 
 impl J2MEArchive {
@@ -23,6 +25,7 @@ impl J2MEArchive {
             jar,
             name: descriptor.name,
             main_class_name: Some(descriptor.main_class_name),
+            icon_path: descriptor.icon_path,
         }
     }
 
@@ -31,6 +34,7 @@ impl J2MEArchive {
             jar,
             name: filename,
             main_class_name: None,
+            icon_path: None,
         }
     }
 }
@@ -40,6 +44,28 @@ impl Archive for J2MEArchive {
         self.name.clone()
     }
 
+    fn platform(&self) -> &'static str {
+        "J2ME"
+    }
+
+    fn main_class_name(&self) -> Option<String> {
+        self.main_class_name.clone()
+    }
+
+    fn name(&self) -> Option<String> {
+        Some(self.name.clone())
+    }
+
+    fn icon(&self) -> Option<Vec<u8>> {
+        let path = self.icon_path.as_ref()?;
+
+        self.files().ok()?.remove(path.trim_start_matches('/'))
+    }
+
+    fn files(&self) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
+        extract_zip(&self.jar)
+    }
+
     fn load_app(self: Box<Self>, platform: Box<dyn Platform>) -> anyhow::Result<Box<dyn App>> {
         let system = System::new(platform, Box::new(()));
 
@@ -50,6 +76,7 @@ impl Archive for J2MEArchive {
 struct J2MEDescriptor {
     name: String,
     main_class_name: String,
+    icon_path: Option<String>,
 }
 
 impl J2MEDescriptor {
@@ -58,6 +85,7 @@ impl J2MEDescriptor {
 
         let mut name = String::new();
         let mut main_class_name = String::new();
+        let mut icon_path = None;
 
         for line in lines {
             let line = str::from_utf8(line).unwrap().trim();
@@ -73,11 +101,24 @@ impl J2MEDescriptor {
 
             match key {
                 "MIDlet-Name" => name = value.to_string(),
-                "MIDlet-1" => main_class_name = value.split(',').nth(2).unwrap().trim().to_string(),
+                "MIDlet-1" => {
+                    main_class_name = value.split(',').nth(2).unwrap().trim().to_string();
+
+                    // MIDlet-Icon takes precedence
+                    let icon = value.split(',').nth(1).unwrap().trim();
+                    if icon_path.is_none() && !icon.is_empty() {
+                        icon_path = Some(icon.to_string());
+                    }
+                }
+                "MIDlet-Icon" => icon_path = Some(value.to_string()),
                 _ => {}
             }
         }
 
-        Self { name, main_class_name }
+        Self {
+            name,
+            main_class_name,
+            icon_path,
+        }
     }
 }
@@ -1,9 +1,9 @@
-use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
 
 use anyhow::Context;
 
-use wie_backend::{App, Event, System};
-use wie_core_arm::{Allocator, ArmCore};
+use wie_backend::{App, CompatibilityReport, Event, GuestAccess, System, SystemSnapshot, Telemetry};
+use wie_core_arm::{Allocator, ArmCore, ArmCoreConfig, MemoryPermission, Segment};
 
 use crate::context::KtfContextExt;
 
@@ -18,7 +18,7 @@ pub struct KtfApp {
 
 impl KtfApp {
     pub fn new(jar: Vec<u8>, additional_files: BTreeMap<String, Vec<u8>>, main_class_name: Option<String>, system: System) -> anyhow::Result<Self> {
-        let mut core = ArmCore::new(system.clone())?;
+        let mut core = ArmCore::new(system.clone(), ArmCoreConfig::default())?;
 
         system.resource_mut().mount_zip(&jar)?;
 
@@ -32,6 +32,10 @@ impl KtfApp {
         let bss_size = {
             let resource = system.resource();
             let filename = resource.files().find(|x| x.starts_with("client.bin")).context("Invalid archive")?;
+            if Self::is_precompiled(resource.files()) {
+                tracing::debug!("No class files found, assuming java code is precompiled into {}", filename);
+            }
+
             let data = resource.data(resource.id(filename).context("Resource not found")?);
 
             Self::load(&mut core, data, filename)?
@@ -45,6 +49,11 @@ impl KtfApp {
         })
     }
 
+    // some titles ship java logic compiled ahead of time into client.bin, without any class file
+    fn is_precompiled<'a>(mut files: impl Iterator<Item = &'a str>) -> bool {
+        !files.any(|x| x.ends_with(".class"))
+    }
+
     #[tracing::instrument(name = "start", skip_all)]
     async fn do_start(core: &mut ArmCore, system: &mut System, bss_size: u32, main_class_name: Option<String>) -> anyhow::Result<()> {
         // we should reverse the order of initialization
@@ -83,7 +92,20 @@ impl KtfApp {
         let bss_start = filename.find("client.bin").context("Incorrect filename")? + 10;
         let bss_size = filename[bss_start..].parse::<u32>()?;
 
-        core.load(data, IMAGE_BASE, data.len() + bss_size as usize)?;
+        // relocations and the data section are written in place, so the whole image stays writable
+        core.load(
+            "ktf",
+            &[Segment {
+                name: "client.bin",
+                address: IMAGE_BASE,
+                data,
+                size: data.len() + bss_size as usize,
+                permission: MemoryPermission::ReadWriteExecute,
+            }],
+        )?;
+
+        core.register_module("client.bin", IMAGE_BASE, data.len() as u32 + bss_size);
+        core.add_symbol(IMAGE_BASE, "start");
 
         tracing::debug!("Loaded at {:#x}, size {:#x}, bss {:#x}", IMAGE_BASE, data.len(), bss_size);
 
@@ -110,8 +132,47 @@ impl App for KtfApp {
     }
 
     fn tick(&mut self) -> anyhow::Result<()> {
-        self.system
-            .tick()
-            .map_err(|x| anyhow::anyhow!("{}\n{}", x, self.core.dump_reg_stack(IMAGE_BASE)))
+        self.system.tick().map_err(|x| anyhow::anyhow!("{}\n{}", x, self.core.dump_reg_stack()))
+    }
+
+    fn report(&self) -> CompatibilityReport {
+        self.system.report().clone()
+    }
+
+    // memory of the core, followed by what the system keeps outside of it
+    fn save_state(&self) -> anyhow::Result<Vec<u8>> {
+        let core = self.core.snapshot()?;
+
+        let mut result = (core.len() as u32).to_le_bytes().to_vec();
+        result.extend(core);
+        result.extend(self.system.snapshot().to_bytes());
+
+        Ok(result)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> anyhow::Result<()> {
+        let (length, rest) = state.split_at_checked(4).context("Invalid state")?;
+        let (core, system) = rest
+            .split_at_checked(u32::from_le_bytes(length.try_into().unwrap()) as _)
+            .context("Invalid state")?;
+
+        // parsed before anything is restored, so a broken state leaves the app as it was
+        let system = SystemSnapshot::from_bytes(system)?;
+        self.core.restore_snapshot(core)?;
+        // inputs queued before loading are replaced by the ones pending when it was saved
+        self.system.restore_snapshot(&system)?;
+
+        Ok(())
+    }
+
+    fn guest(&self) -> Option<Box<dyn GuestAccess>> {
+        Some(Box::new(self.core.clone()))
+    }
+
+    fn telemetry(&self) -> Telemetry {
+        let mut telemetry = self.system.telemetry();
+        telemetry.heap_usage = Allocator::stats(&self.core).ok();
+
+        telemetry
     }
 }
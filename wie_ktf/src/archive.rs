@@ -2,7 +2,7 @@ use alloc::{borrow::ToOwned, boxed::Box, collections::BTreeMap, format, string::
 
 use anyhow::Context;
 
-use wie_backend::{extract_zip, App, Archive, Platform, System};
+use wie_backend::{decode_euc_kr, extract_zip, App, Archive, Platform, System};
 
 use crate::{app::KtfApp, context::KtfContext};
 
@@ -10,6 +10,7 @@ pub struct KtfArchive {
     jar: Vec<u8>,
     id: String,
     main_class_name: Option<String>,
+    name: Option<String>,
     additional_files: BTreeMap<String, Vec<u8>>,
 }
 
@@ -40,7 +41,10 @@ impl KtfArchive {
 
         let additional_files = files.into_iter().filter(|x| x.0.starts_with("P/")).collect();
 
-        Ok(Self::from_jar(jar, adf.aid, Some(adf.mclass), additional_files))
+        Ok(Self {
+            name: adf.name,
+            ..Self::from_jar(jar, adf.aid, Some(adf.mclass), additional_files)
+        })
     }
 
     pub fn from_jar(data: Vec<u8>, id: String, main_class_name: Option<String>, additional_files: BTreeMap<String, Vec<u8>>) -> Self {
@@ -48,6 +52,7 @@ impl KtfArchive {
             jar: data,
             id,
             main_class_name,
+            name: None,
             additional_files,
         }
     }
@@ -58,6 +63,30 @@ impl Archive for KtfArchive {
         self.id.to_owned()
     }
 
+    fn platform(&self) -> &'static str {
+        "KTF"
+    }
+
+    fn main_class_name(&self) -> Option<String> {
+        self.main_class_name.clone()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    // TODO we don't know where the icon is
+    fn icon(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn files(&self) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
+        let mut files = extract_zip(&self.jar)?;
+        files.extend(self.additional_files.clone());
+
+        Ok(files)
+    }
+
     fn load_app(self: Box<Self>, platform: Box<dyn Platform>) -> anyhow::Result<Box<dyn App>> {
         let system = System::new(platform, Box::new(KtfContext::new()));
 
@@ -68,12 +97,14 @@ impl Archive for KtfArchive {
 struct KtfAdf {
     aid: String,
     mclass: String,
+    name: Option<String>,
 }
 
 impl KtfAdf {
     pub fn parse(data: &[u8]) -> Self {
         let mut aid = String::new();
         let mut mclass = String::new();
+        let mut name = None;
 
         let mut lines = data.split(|x| *x == b'\n');
 
@@ -82,10 +113,11 @@ impl KtfAdf {
                 aid = String::from_utf8_lossy(&line[4..]).into();
             } else if line.starts_with(b"MClass:") {
                 mclass = String::from_utf8_lossy(&line[7..]).into();
+            } else if line.starts_with(b"Name:") {
+                name = Some(decode_euc_kr(&line[5..]).trim().into());
             }
-            // TODO load name, it's in euc-kr..
         }
 
-        Self { aid, mclass }
+        Self { aid, mclass, name }
     }
 }
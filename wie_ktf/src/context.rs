@@ -6,17 +6,24 @@ use jvm::Jvm;
 
 pub struct KtfContext {
     jvm: Option<Rc<Jvm>>,
+    // handle of the wipi c screen framebuffer, which is shared by every caller of MC_grpGetScreenFrameBuffer
+    screen_framebuffer: Option<u32>,
 }
 
 impl KtfContext {
     pub fn new() -> Self {
-        Self { jvm: None }
+        Self {
+            jvm: None,
+            screen_framebuffer: None,
+        }
     }
 }
 
 pub trait KtfContextExt {
     fn jvm(&mut self) -> Rc<Jvm>;
     fn set_jvm(&mut self, jvm: Jvm);
+    fn screen_framebuffer(&mut self) -> Option<u32>;
+    fn set_screen_framebuffer(&mut self, framebuffer: u32);
 }
 
 impl KtfContextExt for System {
@@ -33,4 +40,18 @@ impl KtfContextExt for System {
 
         context.jvm = Some(Rc::new(jvm))
     }
+
+    fn screen_framebuffer(&mut self) -> Option<u32> {
+        let context = self.context();
+        let context = (*context).downcast_ref::<KtfContext>().unwrap();
+
+        context.screen_framebuffer
+    }
+
+    fn set_screen_framebuffer(&mut self, framebuffer: u32) {
+        let mut context = self.context();
+        let context = (*context).downcast_mut::<KtfContext>().unwrap();
+
+        context.screen_framebuffer = Some(framebuffer)
+    }
 }
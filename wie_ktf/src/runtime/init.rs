@@ -3,7 +3,7 @@ use core::mem::size_of;
 
 use bytemuck::{Pod, Zeroable};
 
-use wie_backend::System;
+use wie_backend::{ImportKind, ImportStatus, System};
 use wie_core_arm::{Allocator, ArmCore, ArmCoreResult};
 use wie_util::{read_generic, write_generic};
 
@@ -125,6 +125,8 @@ struct ExeInterfaceFunctions {
 pub struct KtfPeb {
     pub ptr_java_context_data: u32,
     pub ptr_current_java_exception_handler: u32,
+    pub ptr_instance_table: u32,
+    pub ptr_string_table: u32,
 }
 
 pub async fn start(core: &mut ArmCore, image_base: u32, bss_size: u32) -> RuntimeResult<u32> {
@@ -200,6 +202,12 @@ pub async fn init(core: &mut ArmCore, system: &mut System, wipi_exe: u32) -> Run
     let exe_interface: ExeInterface = read_generic(core, wipi_exe.ptr_exe_interface)?;
     let exe_interface_functions: ExeInterfaceFunctions = read_generic(core, exe_interface.ptr_functions)?;
 
+    // the only functions client.bin tells us about, the rest are found through these
+    core.add_symbol(wipi_exe.fn_init, "WipiExe::init");
+    core.add_symbol(exe_interface_functions.fn_init, "ExeInterface::init");
+    core.add_symbol(exe_interface_functions.fn_get_default_dll, "ExeInterface::get_default_dll");
+    core.add_symbol(exe_interface_functions.fn_get_class, "ExeInterface::get_class");
+
     let ptr_vtables_base = ptr_param_2 + 12;
     KtfJvmSupport::init(core, system, ptr_vtables_base, exe_interface_functions.fn_get_class, ptr_unk_struct + 32).await?;
 
@@ -218,6 +226,11 @@ pub async fn init(core: &mut ArmCore, system: &mut System, wipi_exe: u32) -> Run
 async fn get_interface(core: &mut ArmCore, system: &mut System, r#struct: String) -> ArmCoreResult<u32> {
     tracing::trace!("get_interface({})", r#struct);
 
+    match r#struct.as_str() {
+        "WIPIC_knlInterface" | "WIPI_JBInterface" => system.report().record(ImportKind::Interface, &r#struct, ImportStatus::Implemented),
+        _ => system.report().record(ImportKind::Interface, &r#struct, ImportStatus::Missing),
+    }
+
     match r#struct.as_str() {
         "WIPIC_knlInterface" => get_wipic_knl_interface(core, system),
         "WIPI_JBInterface" => get_wipi_jb_interface(core),
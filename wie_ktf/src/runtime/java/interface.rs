@@ -8,8 +8,8 @@ use jvm::runtime::JavaLangString;
 
 use bytemuck::{Pod, Zeroable};
 
-use wie_backend::System;
-use wie_core_arm::{Allocator, ArmCore, ArmCoreResult};
+use wie_backend::{ImportKind, ImportStatus, System};
+use wie_core_arm::{Allocator, ArmCore, ArmCoreResult, Resume};
 use wie_util::{read_generic, write_generic, ByteRead};
 
 use crate::{
@@ -33,6 +33,9 @@ struct WIPIJBInterface {
     fn_register_class: u32,
     fn_register_java_string: u32,
     fn_call_native: u32,
+    // entries below are only used by titles having java code precompiled into client.bin
+    fn_get_java_virtual_method: u32,
+    fn_get_java_method_by_name: u32,
 }
 
 pub fn get_wipi_jb_interface(core: &mut ArmCore) -> ArmCoreResult<u32> {
@@ -50,8 +53,30 @@ pub fn get_wipi_jb_interface(core: &mut ArmCore) -> ArmCoreResult<u32> {
         fn_register_class: core.register_function(register_class)?,
         fn_register_java_string: core.register_function(register_java_string)?,
         fn_call_native: core.register_function(call_native)?,
+        fn_get_java_virtual_method: core.register_function(get_java_virtual_method)?,
+        fn_get_java_method_by_name: core.register_function(get_java_method_by_name)?,
     };
 
+    let names = [
+        (interface.fn_java_jump_1, "java_jump_1"),
+        (interface.fn_java_jump_2, "java_jump_2"),
+        (interface.fn_java_jump_3, "java_jump_3"),
+        (interface.fn_get_java_method, "get_java_method"),
+        (interface.fn_get_static_field, "get_static_field"),
+        (interface.fn_unk4, "jb_unk4"),
+        (interface.fn_unk5, "jb_unk5"),
+        (interface.fn_unk7, "jb_unk7"),
+        (interface.fn_unk8, "jb_unk8"),
+        (interface.fn_register_class, "register_class"),
+        (interface.fn_register_java_string, "register_java_string"),
+        (interface.fn_call_native, "call_native"),
+        (interface.fn_get_java_virtual_method, "get_java_virtual_method"),
+        (interface.fn_get_java_method_by_name, "get_java_method_by_name"),
+    ];
+    for (address, name) in names {
+        core.set_function_name(address, name);
+    }
+
     let address = Allocator::alloc(core, size_of::<WIPIJBInterface>() as u32)?;
     write_generic(core, address, interface)?;
 
@@ -64,21 +89,28 @@ pub async fn java_class_load(core: &mut ArmCore, system: &mut System, ptr_target
     let class = system.jvm().resolve_class(&name).await;
 
     if let Ok(x) = class {
+        system.report().record(ImportKind::Class, &name, ImportStatus::Implemented);
+
         let raw = KtfJvmSupport::class_definition_raw(&*x.definition)?;
         write_generic(core, ptr_target, raw)?;
 
         Ok(0)
     } else {
         tracing::error!("load_java_class({}) failed", name);
+        system.report().record(ImportKind::Class, &name, ImportStatus::Missing);
 
         Ok(1)
     }
 }
 
-pub async fn java_throw(_: &mut ArmCore, _: &mut System, error: String, a1: u32) -> RuntimeResult<u32> {
-    tracing::error!("java_throw({}, {})", error, a1);
+// compiled code throws runtime exceptions like NullPointerException by class name
+pub async fn java_throw(core: &mut ArmCore, system: &mut System, error: String, a1: u32) -> RuntimeResult<Resume<u32>> {
+    tracing::debug!("java_throw({}, {:#x})", error, a1);
+
+    let jvm = system.jvm();
+    let exception = jvm.new_class(&error.replace('.', "/"), "()V", ()).await?;
 
-    anyhow::bail!("Java Exception thrown {}, {:#x}", error, a1)
+    Ok(Resume::Jump(KtfJvmSupport::throw(core, &jvm, &exception).await?))
 }
 
 async fn get_java_method(core: &mut ArmCore, _system: &mut System, ptr_class: u32, ptr_fullname: u32) -> RuntimeResult<u32> {
@@ -99,6 +131,35 @@ async fn get_java_method(core: &mut ArmCore, _system: &mut System, ptr_class: u3
     Ok(method.ptr_raw)
 }
 
+// precompiled builds resolve virtual calls from the receiver instead of the declaring class
+async fn get_java_virtual_method(core: &mut ArmCore, _system: &mut System, ptr_instance: u32, ptr_fullname: u32) -> RuntimeResult<u32> {
+    let fullname = KtfJvmSupport::read_name(core, ptr_fullname)?;
+
+    tracing::trace!("get_java_virtual_method({:#x}, {})", ptr_instance, fullname);
+
+    let class = KtfJvmSupport::instance_class_from_raw(core, ptr_instance)?;
+    let method = class.virtual_method(&fullname.name, &fullname.descriptor)?;
+
+    if method.is_none() {
+        anyhow::bail!("Method {} not found from {}", fullname, class.name()?);
+    }
+
+    Ok(method.unwrap().ptr_raw)
+}
+
+async fn get_java_method_by_name(core: &mut ArmCore, _system: &mut System, ptr_class: u32, name: String, descriptor: String) -> RuntimeResult<u32> {
+    tracing::trace!("get_java_method_by_name({:#x}, {}, {})", ptr_class, name, descriptor);
+
+    let class = KtfJvmSupport::class_from_raw(core, ptr_class);
+    let method = class.method(&name, &descriptor)?;
+
+    if method.is_none() {
+        anyhow::bail!("Method {}{} not found from {}", name, descriptor, class.name()?);
+    }
+
+    Ok(method.unwrap().ptr_raw)
+}
+
 async fn java_jump_1(core: &mut ArmCore, _: &mut System, arg1: u32, address: u32) -> RuntimeResult<u32> {
     tracing::trace!("java_jump_1({:#x}, {:#x})", arg1, address);
 
@@ -123,6 +184,11 @@ async fn register_class(core: &mut ArmCore, system: &mut System, ptr_class: u32)
 async fn register_java_string(core: &mut ArmCore, system: &mut System, offset: u32, length: u32) -> RuntimeResult<u32> {
     tracing::trace!("register_java_string({:#x}, {:#x})", offset, length);
 
+    // the same literal gives the same instance, as constants do on the jvm
+    if let Some(x) = KtfJvmSupport::interned_string(core, offset)? {
+        return Ok(x);
+    }
+
     let mut cursor = offset;
     let length = if length == 0xffff_ffff {
         let length: u16 = read_generic(core, offset)?;
@@ -137,8 +203,10 @@ async fn register_java_string(core: &mut ArmCore, system: &mut System, offset: u
     let rust_string = String::from_utf16(&bytes_u16)?;
 
     let instance = JavaLangString::from_rust_string(&system.jvm(), &rust_string).await?;
+    let ptr_instance = KtfJvmSupport::class_instance_raw(&instance);
+    KtfJvmSupport::intern_string(core, offset, ptr_instance)?;
 
-    Ok(KtfJvmSupport::class_instance_raw(&instance) as _)
+    Ok(ptr_instance as _)
 }
 
 async fn get_static_field(core: &mut ArmCore, _system: &mut System, ptr_class: u32, field_name: u32) -> RuntimeResult<u32> {
@@ -234,8 +302,20 @@ pub async fn java_array_new(core: &mut ArmCore, system: &mut System, element_typ
     Ok(raw)
 }
 
-pub async fn java_check_cast(_: &mut ArmCore, _: &mut System, ptr_class: u32, ptr_instance: u32) -> RuntimeResult<u32> {
-    tracing::warn!("stub java_check_cast({:#x}, {:#x})", ptr_class, ptr_instance);
+pub async fn java_check_cast(core: &mut ArmCore, _: &mut System, ptr_class: u32, ptr_instance: u32) -> RuntimeResult<u32> {
+    tracing::trace!("java_check_cast({:#x}, {:#x})", ptr_class, ptr_instance);
+
+    if ptr_instance == 0 {
+        return Ok(1);
+    }
+
+    let class = KtfJvmSupport::class_from_raw(core, ptr_class);
+    let instance_class = KtfJvmSupport::instance_class_from_raw(core, ptr_instance)?;
+
+    // what compiled code does on a failed cast is not known yet, so it's reported and let through
+    if !instance_class.is_assignable_to(&class)? {
+        tracing::warn!("java_check_cast: {} is not assignable to {}", instance_class.name()?, class.name()?);
+    }
 
     Ok(1)
 }
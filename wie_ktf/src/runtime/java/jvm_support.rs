@@ -6,19 +6,21 @@ mod classes;
 mod context_data;
 mod detail;
 mod field;
+mod gc;
 mod method;
 mod name;
+mod string_table;
 mod value;
 mod vtable_builder;
 
-use alloc::{boxed::Box, rc::Rc, string::ToString};
+use alloc::{boxed::Box, format, rc::Rc, string::ToString, vec, vec::Vec};
 use bytemuck::{Pod, Zeroable};
 
 use wie_backend::System;
-use wie_core_arm::{ArmCore, PEB_BASE};
-use wie_util::write_generic;
+use wie_core_arm::{ArmCore, ArmCoreContext, PEB_BASE};
+use wie_util::{read_generic, write_generic};
 
-use jvm::{ClassDefinition, ClassInstance, Jvm};
+use jvm::{ClassDefinition, ClassInstance, JavaValue, Jvm};
 
 use crate::{
     context::KtfContextExt,
@@ -31,13 +33,17 @@ use self::{
     class_definition::JavaClassDefinition,
     class_instance::JavaClassInstance,
     classes::wie::{ClassLoaderContextBase, KtfClassLoader},
+    gc::JavaGarbageCollector,
+    method::JavaMethod,
     name::JavaFullName,
+    string_table::JavaStringTable,
 };
 
 pub type KtfJvmWord = u32;
 
 type JvmSupportResult<T> = anyhow::Result<T>;
 
+// installed by compiled methods with try blocks, like setjmp
 #[allow(dead_code)]
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
@@ -63,13 +69,18 @@ impl KtfJvmSupport {
     ) -> JvmSupportResult<Rc<Jvm>> {
         let ptr_java_context_data = context_data::JavaContextData::init(core, ptr_vtables_base, fn_get_class)?;
 
-        core.map(PEB_BASE, 0x1000)?;
+        let ptr_instance_table = JavaGarbageCollector::init(core)?;
+        let ptr_string_table = JavaStringTable::init(core)?;
+
+        core.map(PEB_BASE, 0x1000, "peb", "ktf")?;
         write_generic(
             core,
             PEB_BASE,
             KtfPeb {
                 ptr_java_context_data,
                 ptr_current_java_exception_handler,
+                ptr_instance_table,
+                ptr_string_table,
             },
         )?;
         system.set_jvm(Jvm::new(detail::KtfJvmDetail::new(core)).await?);
@@ -168,10 +179,132 @@ impl KtfJvmSupport {
         JavaClassDefinition::from_raw(ptr_class, core)
     }
 
+    pub fn instance_class_from_raw(core: &ArmCore, ptr_instance: u32) -> JvmSupportResult<JavaClassDefinition> {
+        JavaClassInstance::from_raw(ptr_instance, core).class()
+    }
+
     pub fn read_name(core: &ArmCore, ptr_name: u32) -> JvmSupportResult<JavaFullName> {
         JavaFullName::from_ptr(core, ptr_name)
     }
 
+    // unwinds guest handlers until one catches `exception`, and returns the context to continue in its catch block.
+    // the handler state is returned in r0 like setjmp, and the exception is passed in r1
+    #[allow(clippy::borrowed_box)]
+    pub async fn throw(core: &mut ArmCore, jvm: &Jvm, exception: &Box<dyn ClassInstance>) -> JvmSupportResult<ArmCoreContext> {
+        let peb: KtfPeb = read_generic(core, PEB_BASE)?;
+        let class_name = exception.class_definition().name();
+
+        tracing::debug!("Throwing {}", class_name);
+
+        loop {
+            let ptr_handler: u32 = read_generic(core, peb.ptr_current_java_exception_handler)?;
+            if ptr_handler == 0 {
+                anyhow::bail!("Uncaught java exception {}", class_name);
+            }
+
+            let handler: JavaExceptionHandler = read_generic(core, ptr_handler)?;
+            let method = JavaMethod::from_raw(handler.ptr_method, core);
+
+            for entry in method.exception_table()? {
+                if !(entry.start_state..entry.end_state).contains(&handler.current_state) {
+                    continue;
+                }
+                if entry.ptr_catch_class != 0 {
+                    let catch_class = Self::class_from_raw(core, entry.ptr_catch_class).name()?;
+                    if !jvm.is_instance(&**exception, &catch_class).await? {
+                        continue;
+                    }
+                }
+
+                tracing::debug!("Caught {} in {} at state {}", class_name, method.name()?, entry.handler_state);
+
+                let mut context = core.save_context();
+                [
+                    context.r4, context.r5, context.r6, context.r7, context.r8, context.sb, context.sl, context.fp, context.ip, context.sp,
+                    context.lr,
+                ] = handler.context;
+                context.r0 = entry.handler_state;
+                context.r1 = Self::class_instance_raw(exception);
+                context.branch(context.lr);
+
+                return Ok(context);
+            }
+
+            // not caught in this method, continue with the handler of its caller
+            write_generic(core, peb.ptr_current_java_exception_handler, handler.ptr_old_handler)?;
+        }
+    }
+
+    // frees instances unreachable from guest memory and registers, returns how many were freed.
+    // instances held only by rust code are freed as well, so this must not be called while any native method runs
+    pub fn collect_garbage(core: &mut ArmCore) -> JvmSupportResult<usize> {
+        JavaGarbageCollector::collect(core)
+    }
+
+    // string constant of compiled code loaded from `address` before, if any
+    pub fn interned_string(core: &ArmCore, address: u32) -> JvmSupportResult<Option<u32>> {
+        JavaStringTable::get(core, address)
+    }
+
+    pub fn intern_string(core: &mut ArmCore, address: u32, ptr_instance: u32) -> JvmSupportResult<()> {
+        JavaStringTable::insert(core, address, ptr_instance)
+    }
+
+    // bulk copy for arraycopy style natives, instead of loading and storing every element as JavaValue
+    #[allow(clippy::borrowed_box)]
+    pub fn copy_array(
+        source: &Box<dyn ClassInstance>,
+        offset: usize,
+        dest: &Box<dyn ClassInstance>,
+        dest_offset: usize,
+        count: usize,
+    ) -> JvmSupportResult<()> {
+        let source = Self::array_instance(source)?;
+        let mut dest = Self::array_instance(dest)?;
+
+        source.copy_array(offset, &mut dest, dest_offset, count)
+    }
+
+    #[allow(clippy::borrowed_box)]
+    pub fn fill_array(array: &Box<dyn ClassInstance>, offset: usize, count: usize, value: &JavaValue) -> JvmSupportResult<()> {
+        Self::array_instance(array)?.fill_array(offset, count, value)
+    }
+
+    // like multianewarray, `new int[2][3]` is ("I", [2, 3]) and `new int[2][]` is ("[I", [2]), leaving the inner arrays null
+    pub async fn instantiate_multi_array(jvm: &Jvm, element_type_name: &str, dimensions: &[usize]) -> JvmSupportResult<Box<dyn ClassInstance>> {
+        if dimensions.is_empty() {
+            anyhow::bail!("No dimensions given for {}", element_type_name);
+        }
+
+        let array_type_name = |depth: usize| format!("{}{}", "[".repeat(dimensions.len() - depth - 1), element_type_name);
+
+        let root = jvm.instantiate_array(&array_type_name(0), dimensions[0]).await?;
+
+        let mut arrays = vec![Self::array_instance(&root)?];
+        for (depth, &dimension) in dimensions.iter().enumerate().skip(1) {
+            let mut next = Vec::new();
+            for mut array in arrays {
+                for index in 0..array.array_length()? {
+                    let sub_array = jvm.instantiate_array(&array_type_name(depth), dimension).await?;
+                    next.push(Self::array_instance(&sub_array)?);
+
+                    array.store_element(index, &JavaValue::Object(Some(sub_array)))?;
+                }
+            }
+            arrays = next;
+        }
+
+        Ok(root)
+    }
+
+    #[allow(clippy::borrowed_box)]
+    fn array_instance(instance: &Box<dyn ClassInstance>) -> JvmSupportResult<JavaArrayClassInstance> {
+        match instance.as_any().downcast_ref::<JavaArrayClassInstance>() {
+            Some(x) => Ok(x.clone()),
+            None => anyhow::bail!("Not an array"),
+        }
+    }
+
     #[allow(clippy::borrowed_box)]
     pub fn class_instance_raw(instance: &Box<dyn ClassInstance>) -> u32 {
         if let Some(x) = instance.as_any().downcast_ref::<JavaClassInstance>() {
@@ -186,19 +319,27 @@ impl KtfJvmSupport {
 
 #[cfg(test)]
 mod test {
-    use alloc::{boxed::Box, rc::Rc};
+    use alloc::{boxed::Box, rc::Rc, vec, vec::Vec};
 
-    use jvm::{runtime::JavaLangString, Jvm};
+    use java_class_proto::{JavaClassProto, JavaMethodProto};
+    use jvm::{runtime::JavaLangString, Array, ClassDefinition, ClassInstanceRef, JavaValue, Jvm, Result as JvmResult};
 
     use wie_backend::System;
-    use wie_core_arm::{Allocator, ArmCore};
+    use wie_core_arm::{Allocator, ArmCore, ArmCoreConfig};
+    use wie_wipi_java::WIPIJavaContextBase;
 
-    use crate::{context::KtfContext, runtime::java::jvm_support::KtfJvmSupport};
+    use crate::{
+        context::KtfContext,
+        runtime::{
+            java::jvm_support::{JavaClassDefinition, KtfJvmSupport},
+            KtfWIPIJavaContext,
+        },
+    };
 
     use test_utils::TestPlatform;
 
-    async fn init_jvm(system: &mut System) -> anyhow::Result<Rc<Jvm>> {
-        let mut core = ArmCore::new(system.clone())?;
+    async fn init_jvm(system: &mut System) -> anyhow::Result<(ArmCore, Rc<Jvm>)> {
+        let mut core = ArmCore::new(system.clone(), ArmCoreConfig::default())?;
         Allocator::init(&mut core)?;
 
         let mut context = core.save_context();
@@ -209,13 +350,13 @@ mod test {
         let ptr_vtables_base = Allocator::alloc(&mut core, 0x100)?;
         let jvm = KtfJvmSupport::init(&mut core, system, ptr_vtables_base, 0, 0).await?;
 
-        Ok(jvm)
+        Ok((core, jvm))
     }
 
     #[futures_test::test]
     async fn test_jvm_support() -> anyhow::Result<()> {
         let mut system = System::new(Box::new(TestPlatform), Box::new(KtfContext::new()));
-        let jvm = init_jvm(&mut system).await?;
+        let (_, jvm) = init_jvm(&mut system).await?;
 
         let string1 = JavaLangString::from_rust_string(&jvm, "test1").await?;
         let string2 = JavaLangString::from_rust_string(&jvm, "test2").await?;
@@ -228,4 +369,173 @@ mod test {
 
         Ok(())
     }
+
+    #[futures_test::test]
+    async fn test_string_table() -> anyhow::Result<()> {
+        let mut system = System::new(Box::new(TestPlatform), Box::new(KtfContext::new()));
+        let (mut core, jvm) = init_jvm(&mut system).await?;
+
+        assert_eq!(KtfJvmSupport::interned_string(&core, 0x1000)?, None);
+
+        // more than the initial capacity, to grow the table
+        for i in 0..100 {
+            let string = JavaLangString::from_rust_string(&jvm, "test").await?;
+            KtfJvmSupport::intern_string(&mut core, 0x1000 + i * 4, KtfJvmSupport::class_instance_raw(&string))?;
+        }
+
+        let string = JavaLangString::from_rust_string(&jvm, "test").await?;
+        KtfJvmSupport::intern_string(&mut core, 0x2000, KtfJvmSupport::class_instance_raw(&string))?;
+
+        assert_eq!(
+            KtfJvmSupport::interned_string(&core, 0x2000)?,
+            Some(KtfJvmSupport::class_instance_raw(&string))
+        );
+        assert!(KtfJvmSupport::interned_string(&core, 0x1000 + 99 * 4)?.is_some());
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn test_copy_array() -> anyhow::Result<()> {
+        let mut system = System::new(Box::new(TestPlatform), Box::new(KtfContext::new()));
+        let (_, jvm) = init_jvm(&mut system).await?;
+
+        let mut array = jvm.instantiate_array("I", 6).await?;
+        jvm.store_array(&mut array, 0, vec![1, 2, 3, 4, 5, 6]).await?;
+
+        // overlapping, within the same array
+        KtfJvmSupport::copy_array(&array, 0, &array, 2, 4)?;
+        assert_eq!(jvm.load_array::<i32>(&array, 0, 6).await?, [1, 2, 1, 2, 3, 4]);
+
+        let other = jvm.instantiate_array("I", 3).await?;
+        KtfJvmSupport::copy_array(&array, 3, &other, 0, 3)?;
+        assert_eq!(jvm.load_array::<i32>(&other, 0, 3).await?, [2, 3, 4]);
+
+        KtfJvmSupport::fill_array(&other, 1, 2, &JavaValue::Int(-1))?;
+        assert_eq!(jvm.load_array::<i32>(&other, 0, 3).await?, [2, -1, -1]);
+
+        let bytes = jvm.instantiate_array("B", 3).await?;
+        assert!(KtfJvmSupport::copy_array(&array, 0, &bytes, 0, 3).is_err());
+        assert!(KtfJvmSupport::copy_array(&array, 4, &other, 0, 3).is_err());
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn test_virtual_method() -> anyhow::Result<()> {
+        struct TestCard;
+
+        async fn key_notify(_: &Jvm, _: &mut dyn WIPIJavaContextBase, _: ClassInstanceRef<TestCard>, _: i32, _: i32) -> JvmResult<bool> {
+            Ok(true)
+        }
+
+        let mut system = System::new(Box::new(TestPlatform), Box::new(KtfContext::new()));
+        let (mut core, jvm) = init_jvm(&mut system).await?;
+
+        // like a card of the app, overriding one of Card
+        let proto = JavaClassProto::<dyn WIPIJavaContextBase> {
+            parent_class: Some("org/kwis/msp/lcdui/Card"),
+            interfaces: vec![],
+            methods: vec![JavaMethodProto::new("keyNotify", "(II)Z", key_notify, Default::default())],
+            fields: vec![],
+        };
+        let context = KtfWIPIJavaContext::new(&core, &system, jvm.clone());
+        let class = JavaClassDefinition::new(&mut core, &jvm, "TestCard", proto, Box::new(context) as Box<dyn WIPIJavaContextBase>).await?;
+
+        let card = class.instantiate();
+        let handled: bool = jvm.invoke_virtual(&card, "keyNotify", "(II)Z", (0, 0)).await?;
+        assert!(handled);
+
+        let card_class = jvm.resolve_class("org/kwis/msp/lcdui/Card").await?.definition;
+        let base_card = card_class.instantiate();
+        let handled: bool = jvm.invoke_virtual(&base_card, "keyNotify", "(II)Z", (0, 0)).await?;
+        assert!(!handled);
+
+        // not overridden, so the one of Card
+        let card_class = KtfJvmSupport::class_from_raw(&core, KtfJvmSupport::class_definition_raw(&*card_class)?);
+        let descriptor = "(Lorg/kwis/msp/lcdui/Graphics;)V";
+        assert_eq!(
+            class.virtual_method("paint", descriptor)?.map(|x| x.ptr_raw),
+            card_class.method("paint", descriptor)?.map(|x| x.ptr_raw)
+        );
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn test_interface() -> anyhow::Result<()> {
+        struct TestListener;
+
+        async fn id(_: &Jvm, _: &mut dyn WIPIJavaContextBase, _: ClassInstanceRef<TestListener>) -> JvmResult<i32> {
+            Ok(7)
+        }
+
+        let mut system = System::new(Box::new(TestPlatform), Box::new(KtfContext::new()));
+        let (mut core, jvm) = init_jvm(&mut system).await?;
+        let context = KtfWIPIJavaContext::new(&core, &system, jvm.clone());
+
+        let proto = JavaClassProto::<dyn WIPIJavaContextBase> {
+            parent_class: Some("java/lang/Object"),
+            interfaces: vec![],
+            methods: vec![JavaMethodProto::new("id", "()I", id, Default::default())],
+            fields: vec![],
+        };
+        let listener = JavaClassDefinition::new(
+            &mut core,
+            &jvm,
+            "TestListener",
+            proto,
+            Box::new(context.clone()) as Box<dyn WIPIJavaContextBase>,
+        )
+        .await?;
+        jvm.register_class(Box::new(listener.clone()), None).await?;
+
+        let proto = JavaClassProto::<dyn WIPIJavaContextBase> {
+            parent_class: Some("java/lang/Object"),
+            interfaces: vec!["TestListener"],
+            methods: vec![],
+            fields: vec![],
+        };
+        let class = JavaClassDefinition::new(&mut core, &jvm, "TestImpl", proto, Box::new(context) as Box<dyn WIPIJavaContextBase>).await?;
+
+        assert_eq!(class.interfaces()?.iter().map(|x| x.ptr_raw).collect::<Vec<_>>(), [listener.ptr_raw]);
+        assert!(class.is_assignable_to(&listener)?);
+        assert!(!listener.is_assignable_to(&class)?);
+
+        // resolved from the interface, as the class has none of its own
+        let instance = class.instantiate();
+        let result: i32 = jvm.invoke_virtual(&instance, "id", "()I", ()).await?;
+        assert_eq!(result, 7);
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn test_multi_array() -> anyhow::Result<()> {
+        let mut system = System::new(Box::new(TestPlatform), Box::new(KtfContext::new()));
+        let (_, jvm) = init_jvm(&mut system).await?;
+
+        let array = KtfJvmSupport::instantiate_multi_array(&jvm, "S", &[2, 3]).await?;
+        assert_eq!(array.class_definition().name(), "[[S");
+        assert_eq!(jvm.array_length(&array).await?, 2);
+
+        let rows: Vec<ClassInstanceRef<Array<i16>>> = jvm.load_array(&array, 0, 2).await?;
+        let mut row = rows[1].clone();
+        assert!(!rows[0].is_null());
+        assert_eq!(row.class_definition().name(), "[S");
+        jvm.store_array(&mut row, 0, vec![1i16, -2, 3]).await?;
+        assert_eq!(jvm.load_array::<i16>(&row, 0, 3).await?, [1, -2, 3]);
+        assert!(jvm.store_array(&mut row, 2, vec![4i16, 5]).await.is_err());
+
+        // 8 bytes wide
+        let longs = jvm.instantiate_array("J", 2).await?;
+        KtfJvmSupport::fill_array(&longs, 0, 2, &JavaValue::Long(-0x1_0000_0002))?;
+        assert_eq!(jvm.load_array::<i64>(&longs, 0, 2).await?, [-0x1_0000_0002, -0x1_0000_0002]);
+
+        let inner_null = KtfJvmSupport::instantiate_multi_array(&jvm, "[I", &[2]).await?;
+        let rows: Vec<ClassInstanceRef<Array<i32>>> = jvm.load_array(&inner_null, 0, 2).await?;
+        assert!(rows.iter().all(|x| x.is_null()));
+
+        Ok(())
+    }
 }
@@ -42,6 +42,11 @@ impl JavaArrayClassDefinition {
         let element_type_raw = if element_type_name.starts_with('L') {
             let class = jvm.resolve_class(&element_type_name[1..element_type_name.len() - 1]).await.unwrap();
 
+            Some(KtfJvmSupport::class_definition_raw(&*class.definition)?)
+        } else if element_type_name.starts_with('[') {
+            // arrays of arrays, like [[I
+            let class = jvm.resolve_class(element_type_name).await.unwrap();
+
             Some(KtfJvmSupport::class_definition_raw(&*class.definition)?)
         } else {
             None
@@ -1,9 +1,9 @@
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, format, string::String, vec::Vec};
 use core::fmt::{self, Debug, Formatter};
 
 use bytemuck::cast_vec;
 
-use jvm::{ArrayClassInstance, ClassDefinition, ClassInstance, JavaType, JavaValue, Result as JvmResult};
+use jvm::{ArrayClassInstance, ClassDefinition, ClassInstance, JavaError, JavaType, JavaValue, Result as JvmResult};
 
 use wie_core_arm::ArmCore;
 use wie_util::{read_generic, write_generic, ByteRead, ByteWrite};
@@ -35,10 +35,7 @@ impl JavaArrayClassInstance {
     }
 
     pub fn load_array(&self, offset: usize, count: usize) -> JvmSupportResult<Vec<u8>> {
-        let array_length = self.array_length()?;
-        if offset + count > array_length {
-            anyhow::bail!("Array index out of bounds");
-        }
+        self.check_bounds(offset, count)?;
 
         let base_address = self.class_instance.field_address(4)?;
         let element_size = self.element_size()?;
@@ -51,17 +48,44 @@ impl JavaArrayClassInstance {
     }
 
     pub fn store_array(&mut self, offset: usize, count: usize, values_raw: Vec<u8>) -> JvmSupportResult<()> {
-        let array_length = self.array_length()?;
-        if offset + count > array_length {
-            anyhow::bail!("Array index out of bounds");
-        }
+        self.check_bounds(offset, count)?;
 
         let base_address = self.class_instance.field_address(4)?;
         let element_size = self.element_size()?;
+        if values_raw.len() != count * element_size {
+            anyhow::bail!("Expected {} bytes for {} elements, got {}", count * element_size, count, values_raw.len());
+        }
 
         Ok(self.core.write_bytes(base_address + (element_size * offset) as u32, &values_raw)?)
     }
 
+    // moves raw elements without going through JavaValue. the source range is read whole before writing,
+    // so overlapping ranges of the same array copy like memmove
+    pub fn copy_array(&self, offset: usize, dest: &mut Self, dest_offset: usize, count: usize) -> JvmSupportResult<()> {
+        let descriptor = self.element_type_descriptor()?;
+        let dest_descriptor = dest.element_type_descriptor()?;
+        let is_reference = |x: &str| x.starts_with('L') || x.starts_with('[');
+        if descriptor != dest_descriptor && !(is_reference(&descriptor) && is_reference(&dest_descriptor)) {
+            anyhow::bail!("Array type mismatch");
+        }
+
+        let values_raw = self.load_array(offset, count)?;
+
+        dest.store_array(dest_offset, count, values_raw)
+    }
+
+    pub fn fill_array(&mut self, offset: usize, count: usize, value: &JavaValue) -> JvmSupportResult<()> {
+        let element_size = self.element_size()?;
+
+        self.store_array(offset, count, Self::element_to_raw(value, element_size).repeat(count))
+    }
+
+    pub fn store_element(&mut self, index: usize, value: &JavaValue) -> JvmSupportResult<()> {
+        let element_size = self.element_size()?;
+
+        self.store_array(index, 1, Self::element_to_raw(value, element_size))
+    }
+
     pub fn array_length(&self) -> JvmSupportResult<usize> {
         let length_address = self.class_instance.field_address(0)?;
         let result: u32 = read_generic(&self.core, length_address)?;
@@ -69,6 +93,43 @@ impl JavaArrayClassInstance {
         Ok(result as _)
     }
 
+    fn check_bounds(&self, offset: usize, count: usize) -> JvmSupportResult<()> {
+        let array_length = self.array_length()?;
+
+        match offset.checked_add(count) {
+            Some(end) if end <= array_length => Ok(()),
+            _ => anyhow::bail!(
+                "Array index out of bounds: {}..{} of length {}",
+                offset,
+                offset.wrapping_add(count),
+                array_length
+            ),
+        }
+    }
+
+    // long and double elements take 8 bytes, wider than KtfJvmWord
+    fn element_to_raw(value: &JavaValue, element_size: usize) -> Vec<u8> {
+        let raw = match value {
+            JavaValue::Long(x) => *x as u64,
+            JavaValue::Double(x) => x.to_bits(),
+            x => x.as_raw() as u64,
+        };
+
+        raw.to_le_bytes()[..element_size].to_vec()
+    }
+
+    fn element_from_raw(&self, raw: &[u8], element_type: &JavaType) -> JavaValue {
+        let mut bytes = [0; 8];
+        bytes[..raw.len()].copy_from_slice(raw);
+        let raw = u64::from_le_bytes(bytes);
+
+        match element_type {
+            JavaType::Long => JavaValue::Long(raw as i64),
+            JavaType::Double => JavaValue::Double(f64::from_bits(raw)),
+            _ => JavaValue::from_raw(raw as _, element_type, &self.core),
+        }
+    }
+
     fn element_size(&self) -> JvmSupportResult<usize> {
         let array_class = JavaArrayClassDefinition::from_raw(self.class_instance.class()?.ptr_raw, &self.core);
 
@@ -76,9 +137,13 @@ impl JavaArrayClassInstance {
     }
 
     fn element_type(&self) -> JvmSupportResult<JavaType> {
+        Ok(JavaType::parse(&self.element_type_descriptor()?))
+    }
+
+    fn element_type_descriptor(&self) -> JvmSupportResult<String> {
         let array_class = JavaArrayClassDefinition::from_raw(self.class_instance.class()?.ptr_raw, &self.core);
 
-        Ok(JavaType::parse(&array_class.element_type_descriptor()?))
+        array_class.element_type_descriptor()
     }
 }
 
@@ -102,46 +167,25 @@ impl ArrayClassInstance for JavaArrayClassInstance {
     fn store(&mut self, offset: usize, values: Box<[JavaValue]>) -> JvmResult<()> {
         let element_size = self.element_size().unwrap();
 
-        let values = values.to_vec();
         let count = values.len();
+        let raw_values = values.iter().flat_map(|x| Self::element_to_raw(x, element_size)).collect::<Vec<_>>();
 
-        let raw_values = match element_size {
-            1 => values.into_iter().map(|x| x.as_raw() as u8).collect::<Vec<_>>(),
-            2 => values
-                .into_iter()
-                .map(|x| x.as_raw() as u16)
-                .flat_map(u16::to_le_bytes)
-                .collect::<Vec<_>>(),
-            4 => values.into_iter().map(|x| x.as_raw()).flat_map(u32::to_le_bytes).collect::<Vec<_>>(),
-            _ => unreachable!(),
-        };
-
-        self.store_array(offset as _, count, raw_values).unwrap();
-
-        Ok(())
+        self.store_array(offset as _, count, raw_values)
+            .map_err(|x| JavaError::FatalError(format!("{}", x)))
     }
 
     fn load(&self, offset: usize, count: usize) -> JvmResult<Vec<JavaValue>> {
-        let values_raw = self.load_array(offset as _, count as _).unwrap();
+        let values_raw = self
+            .load_array(offset as _, count as _)
+            .map_err(|x| JavaError::FatalError(format!("{}", x)))?;
 
         let element_type = self.element_type().unwrap();
         let element_size = self.element_size().unwrap();
 
-        Ok(match element_size {
-            1 => values_raw
-                .into_iter()
-                .map(|x| JavaValue::from_raw(x as _, &element_type, &self.core))
-                .collect::<Vec<_>>(),
-            2 => values_raw
-                .chunks(2)
-                .map(|x| JavaValue::from_raw(u16::from_le_bytes(x.try_into().unwrap()) as _, &element_type, &self.core))
-                .collect::<Vec<_>>(),
-            4 => values_raw
-                .chunks(4)
-                .map(|x| JavaValue::from_raw(u32::from_le_bytes(x.try_into().unwrap()) as _, &element_type, &self.core))
-                .collect::<Vec<_>>(),
-            _ => unreachable!(),
-        })
+        Ok(values_raw
+            .chunks(element_size)
+            .map(|x| self.element_from_raw(x, &element_type))
+            .collect::<Vec<_>>())
     }
 
     fn store_bytes(&mut self, offset: usize, values: Box<[i8]>) -> JvmResult<()> {
@@ -74,6 +74,22 @@ impl JavaClassDefinition {
             None
         };
 
+        let mut interfaces = Vec::new();
+        for interface in proto.interfaces.iter() {
+            let class = jvm.resolve_class(interface).await?.definition;
+            let class = class.as_any().downcast_ref::<JavaClassDefinition>().unwrap();
+
+            interfaces.push(class.ptr_raw);
+        }
+        let ptr_interfaces = if interfaces.is_empty() {
+            0
+        } else {
+            let ptr_interfaces = Allocator::alloc(core, ((interfaces.len() + 1) * size_of::<u32>()) as _)?;
+            write_null_terminated_table(core, ptr_interfaces, &interfaces)?;
+
+            ptr_interfaces
+        };
+
         let mut vtable_builder = JavaVtableBuilder::new(&parent_class)?;
 
         let ptr_raw = Allocator::alloc(core, size_of::<RawJavaClass>() as u32)?;
@@ -114,7 +130,7 @@ impl JavaClassDefinition {
                 unk1: 0,
                 ptr_parent_class: parent_class.map(|x| x.ptr_raw).unwrap_or(0),
                 ptr_methods,
-                ptr_interfaces: 0,
+                ptr_interfaces,
                 ptr_fields_or_element_type: ptr_fields,
                 method_count: methods.len() as u16,
                 fields_size: (fields.len() * 4) as u16,
@@ -216,6 +232,38 @@ impl JavaClassDefinition {
         Ok(read_null_terminated_string(&self.core, descriptor.ptr_name)?)
     }
 
+    /// Only the ones this class declares, not ones of its parents
+    pub fn interfaces(&self) -> JvmSupportResult<Vec<JavaClassDefinition>> {
+        let raw: RawJavaClass = read_generic(&self.core, self.ptr_raw)?;
+        let descriptor: RawJavaClassDescriptor = read_generic(&self.core, raw.ptr_descriptor)?;
+
+        if descriptor.ptr_interfaces == 0 {
+            return Ok(Vec::new());
+        }
+
+        let ptr_interfaces = read_null_terminated_table(&self.core, descriptor.ptr_interfaces)?;
+
+        Ok(ptr_interfaces.into_iter().map(|x| JavaClassDefinition::from_raw(x, &self.core)).collect())
+    }
+
+    // whether `class` is this, one of its parents, or an interface implemented by any of them
+    pub fn is_assignable_to(&self, class: &JavaClassDefinition) -> JvmSupportResult<bool> {
+        if self.ptr_raw == class.ptr_raw {
+            return Ok(true);
+        }
+
+        for interface in self.interfaces()? {
+            if interface.is_assignable_to(class)? {
+                return Ok(true);
+            }
+        }
+
+        match self.parent_class()? {
+            Some(x) => x.is_assignable_to(class),
+            None => Ok(false),
+        }
+    }
+
     pub fn parent_class(&self) -> JvmSupportResult<Option<JavaClassDefinition>> {
         let raw: RawJavaClass = read_generic(&self.core, self.ptr_raw)?;
         let descriptor: RawJavaClassDescriptor = read_generic(&self.core, raw.ptr_descriptor)?;
@@ -238,10 +286,42 @@ impl JavaClassDefinition {
         }
 
         if let Some(x) = self.parent_class()? {
-            x.method(name, descriptor)
-        } else {
-            Ok(None)
+            if let Some(x) = x.method(name, descriptor)? {
+                return Ok(Some(x));
+            }
+        }
+
+        // bodies given by interfaces, for the ones implementing them without their own
+        for interface in self.interfaces()? {
+            if let Some(x) = interface.method(name, descriptor)? {
+                return Ok(Some(x));
+            }
         }
+
+        Ok(None)
+    }
+
+    // resolved through the vtable of this class, which has the overrides of subclasses in place of what they override.
+    // methods missing in the vtable, like ones of compiled classes not built with their statics, are looked up by hierarchy
+    pub fn virtual_method(&self, name: &str, descriptor: &str) -> JvmSupportResult<Option<JavaMethod>> {
+        let raw: RawJavaClass = read_generic(&self.core, self.ptr_raw)?;
+
+        if raw.ptr_vtable != 0 {
+            for index in 0..raw.vtable_count as u32 {
+                let ptr_method: u32 = read_generic(&self.core, raw.ptr_vtable + index * 4)?;
+                if ptr_method == 0 {
+                    continue;
+                }
+
+                let method = JavaMethod::from_raw(ptr_method, &self.core);
+                let full_name = method.name()?;
+                if full_name.name == name && full_name.descriptor == descriptor {
+                    return Ok(Some(method));
+                }
+            }
+        }
+
+        self.method(name, descriptor)
     }
 
     pub fn field(&self, name: &str, descriptor: &str, is_static: bool) -> JvmSupportResult<Option<JavaField>> {
@@ -287,7 +367,7 @@ impl ClassDefinition for JavaClassDefinition {
     }
 
     fn method(&self, name: &str, descriptor: &str) -> Option<Box<dyn Method>> {
-        self.method(name, descriptor).unwrap().map(|x| Box::new(x) as _)
+        self.virtual_method(name, descriptor).unwrap().map(|x| Box::new(x) as _)
     }
 
     fn field(&self, name: &str, descriptor: &str, is_static: bool) -> Option<Box<dyn Field>> {
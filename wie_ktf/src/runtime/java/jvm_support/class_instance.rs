@@ -13,7 +13,8 @@ use wie_core_arm::{Allocator, ArmCore};
 use wie_util::{read_generic, write_generic, ByteWrite};
 
 use super::{
-    class_definition::JavaClassDefinition, context_data::JavaContextData, field::JavaField, value::JavaValueExt, JvmSupportResult, KtfJvmWord,
+    class_definition::JavaClassDefinition, context_data::JavaContextData, field::JavaField, gc::JavaGarbageCollector, value::JavaValueExt,
+    JvmSupportResult, KtfJvmWord,
 };
 
 #[repr(C)]
@@ -53,6 +54,13 @@ impl JavaClassInstance {
     }
 
     pub fn destroy(mut self) -> JvmSupportResult<()> {
+        JavaGarbageCollector::unregister(&mut self.core, self.ptr_raw)?;
+
+        self.free()
+    }
+
+    // without removing from the instance table
+    pub(super) fn free(mut self) -> JvmSupportResult<()> {
         let raw = self.read_raw()?;
 
         Allocator::free(&mut self.core, raw.ptr_fields)?;
@@ -91,9 +99,20 @@ impl JavaClassInstance {
         Ok(raw.ptr_fields + offset + 4)
     }
 
+    pub(super) fn ptr_fields(&self) -> JvmSupportResult<u32> {
+        Ok(self.read_raw()?.ptr_fields)
+    }
+
     pub(super) fn instantiate(core: &mut ArmCore, class: &JavaClassDefinition, field_size: usize) -> JvmSupportResult<Self> {
         let ptr_raw = Allocator::alloc(core, size_of::<RawJavaClassInstance>() as _)?;
-        let ptr_fields = Allocator::alloc(core, (field_size + 4) as _)?;
+        let ptr_fields = match Allocator::alloc(core, (field_size + 4) as _) {
+            Ok(x) => x,
+            Err(e) => {
+                Allocator::free(core, ptr_raw)?;
+
+                return Err(e.into());
+            }
+        };
 
         let zero = iter::repeat(0).take((field_size + 4) as _).collect::<Vec<_>>();
         core.write_bytes(ptr_fields, &zero)?;
@@ -109,6 +128,7 @@ impl JavaClassInstance {
             },
         )?;
         write_generic(core, ptr_fields, (vtable_index * 4) << 5)?;
+        JavaGarbageCollector::register(core, ptr_raw)?;
 
         tracing::trace!("Instantiate {}, vtable_index {:#x}", class.name()?, vtable_index);
 
@@ -1,4 +1,4 @@
-use alloc::{boxed::Box, vec};
+use alloc::{boxed::Box, format, vec};
 
 use bytemuck::cast_vec;
 use dyn_clone::{clone_trait_object, DynClone};
@@ -89,6 +89,10 @@ impl KtfClassLoader {
 
             Ok(jvm.resolve_class(&name).await?.java_class(jvm).await?.into())
         } else {
+            if context.system().resource().id(&format!("{}.class", name)).is_some() {
+                tracing::warn!("Class {} is only in the archive as bytecode, which isn't loaded on KTF", name);
+            }
+
             Ok(None.into())
         }
     }
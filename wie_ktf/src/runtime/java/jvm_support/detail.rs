@@ -1,6 +1,6 @@
 use alloc::{boxed::Box, format};
 
-use jvm::{ClassDefinition, Jvm, JvmDetail, Result as JvmResult};
+use jvm::{ClassDefinition, JavaError, Jvm, JvmDetail, Result as JvmResult};
 
 use wie_core_arm::ArmCore;
 
@@ -18,8 +18,9 @@ impl KtfJvmDetail {
 
 #[async_trait::async_trait(?Send)]
 impl JvmDetail for KtfJvmDetail {
-    async fn define_class(&self, _jvm: &Jvm, _name: &str, _data: &[u8]) -> JvmResult<Box<dyn ClassDefinition>> {
-        unimplemented!()
+    // instances here live in guest memory laid out for compiled code, which classes from bytecode have no layout for
+    async fn define_class(&self, _jvm: &Jvm, name: &str, _data: &[u8]) -> JvmResult<Box<dyn ClassDefinition>> {
+        Err(JavaError::FatalError(format!("Can't define {} from bytecode on KTF", name)))
     }
 
     async fn define_array_class(&self, jvm: &Jvm, element_type_name: &str) -> JvmResult<Box<dyn ClassDefinition>> {
@@ -0,0 +1,209 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::{iter, mem::size_of};
+
+use bytemuck::{Pod, Zeroable};
+
+use wie_core_arm::{Allocator, ArmCore, ArmCoreContext, HEAP_BASE, PEB_BASE};
+use wie_util::{read_generic, write_generic, ByteRead};
+
+use crate::runtime::KtfPeb;
+
+use super::{class_instance::JavaClassInstance, JvmSupportResult};
+
+const INITIAL_CAPACITY: u32 = 256;
+
+// instances referenced only from the native side are not visible to us, like the jvm's own class objects
+const PINNED_CLASSES: [&str; 3] = ["java/lang/Class", "java/lang/ClassLoader", "java/lang/Thread"];
+
+// every instance we allocated, kept in guest memory to be part of savestates
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct RawInstanceTable {
+    count: u32,
+    capacity: u32,
+}
+
+// mark and sweep over the instances in the table. roots are found conservatively:
+// any word in registers, in guest memory outside of instances, or in pinned instances, pointing into an instance.
+// instances held only by rust code are not roots, so collecting is never done from inside an allocation:
+// a half built instance or one a native method is working with would be swept.
+// it must be requested explicitly at a point no rust code holds instances
+pub struct JavaGarbageCollector {}
+
+impl JavaGarbageCollector {
+    pub fn init(core: &mut ArmCore) -> JvmSupportResult<u32> {
+        let ptr_table = Allocator::alloc(core, size_of::<RawInstanceTable>() as u32 + INITIAL_CAPACITY * 4)?;
+        write_generic(
+            core,
+            ptr_table,
+            RawInstanceTable {
+                count: 0,
+                capacity: INITIAL_CAPACITY,
+            },
+        )?;
+
+        Ok(ptr_table)
+    }
+
+    pub fn register(core: &mut ArmCore, ptr_instance: u32) -> JvmSupportResult<()> {
+        let mut ptr_table = Self::ptr_table(core)?;
+        let mut table: RawInstanceTable = read_generic(core, ptr_table)?;
+
+        if table.count == table.capacity {
+            table.capacity *= 2;
+            ptr_table = Allocator::realloc(core, ptr_table, size_of::<RawInstanceTable>() as u32 + table.capacity * 4)?;
+
+            let mut peb: KtfPeb = read_generic(core, PEB_BASE)?;
+            peb.ptr_instance_table = ptr_table;
+            write_generic(core, PEB_BASE, peb)?;
+        }
+
+        write_generic(core, Self::entry_address(ptr_table, table.count), ptr_instance)?;
+        table.count += 1;
+        write_generic(core, ptr_table, table)?;
+
+        Ok(())
+    }
+
+    pub fn unregister(core: &mut ArmCore, ptr_instance: u32) -> JvmSupportResult<()> {
+        let ptr_table = Self::ptr_table(core)?;
+        let mut table: RawInstanceTable = read_generic(core, ptr_table)?;
+        let instances = Self::instances(core)?;
+
+        // order doesn't matter, the last one takes its place
+        if let Some(index) = instances.iter().position(|&x| x == ptr_instance) {
+            table.count -= 1;
+            write_generic(core, Self::entry_address(ptr_table, index as u32), instances[table.count as usize])?;
+            write_generic(core, ptr_table, table)?;
+        }
+
+        Ok(())
+    }
+
+    // returns the number of instances freed
+    pub fn collect(core: &mut ArmCore) -> JvmSupportResult<usize> {
+        let ptr_table = Self::ptr_table(core)?;
+        let instances = Self::instances(core)?;
+        let allocations = Allocator::allocations(core)?.into_iter().collect::<BTreeMap<_, _>>();
+
+        // start of an instance or its fields, to (end, instance)
+        let mut owners = BTreeMap::new();
+        let mut fields = BTreeMap::new();
+        for &instance in &instances {
+            let ptr_fields = JavaClassInstance::from_raw(instance, core).ptr_fields()?;
+            let fields_size = allocations.get(&ptr_fields).copied().unwrap_or(0);
+
+            owners.insert(instance, (instance + 8, instance));
+            owners.insert(ptr_fields, (ptr_fields + fields_size, instance));
+            fields.insert(instance, (ptr_fields, fields_size));
+        }
+
+        let mut marker = Marker {
+            owners: &owners,
+            marked: BTreeSet::new(),
+            pending: Vec::new(),
+        };
+
+        let contexts = core.task_contexts().into_iter().chain(iter::once(core.save_context()));
+        for context in contexts {
+            Self::context_words(&context).into_iter().for_each(|x| marker.mark(x));
+        }
+
+        for (address, size) in core.mapped_regions() {
+            // heap is scanned per allocation below
+            if (address..address + size).contains(&HEAP_BASE) {
+                continue;
+            }
+            marker.mark_all(&core.read_bytes(address, size)?);
+        }
+
+        for (&address, &size) in &allocations {
+            if address == ptr_table || owners.contains_key(&address) {
+                continue;
+            }
+            marker.mark_all(&core.read_bytes(address, size)?);
+        }
+
+        for &instance in &instances {
+            let class = JavaClassInstance::from_raw(instance, core).class()?;
+            for x in class.read_class_hierarchy()? {
+                if PINNED_CLASSES.contains(&x.name()?.as_str()) {
+                    marker.mark(instance);
+                }
+            }
+        }
+
+        while let Some(instance) = marker.pending.pop() {
+            let (ptr_fields, fields_size) = fields[&instance];
+            marker.mark_all(&core.read_bytes(ptr_fields, fields_size)?);
+        }
+
+        let (live, dead): (Vec<_>, Vec<_>) = instances.into_iter().partition(|x| marker.marked.contains(x));
+        for &instance in &dead {
+            JavaClassInstance::from_raw(instance, core).free()?;
+        }
+
+        for (index, &instance) in live.iter().enumerate() {
+            write_generic(core, Self::entry_address(ptr_table, index as u32), instance)?;
+        }
+        let mut table: RawInstanceTable = read_generic(core, ptr_table)?;
+        table.count = live.len() as u32;
+        write_generic(core, ptr_table, table)?;
+
+        Ok(dead.len())
+    }
+
+    fn instances(core: &ArmCore) -> JvmSupportResult<Vec<u32>> {
+        let ptr_table = Self::ptr_table(core)?;
+        let table: RawInstanceTable = read_generic(core, ptr_table)?;
+
+        let data = core.read_bytes(Self::entry_address(ptr_table, 0), table.count * 4)?;
+
+        Ok(data.chunks_exact(4).map(|x| u32::from_le_bytes(x.try_into().unwrap())).collect())
+    }
+
+    fn ptr_table(core: &ArmCore) -> JvmSupportResult<u32> {
+        let peb: KtfPeb = read_generic(core, PEB_BASE)?;
+
+        Ok(peb.ptr_instance_table)
+    }
+
+    fn entry_address(ptr_table: u32, index: u32) -> u32 {
+        ptr_table + size_of::<RawInstanceTable>() as u32 + index * 4
+    }
+
+    fn context_words(context: &ArmCoreContext) -> [u32; 15] {
+        [
+            context.r0, context.r1, context.r2, context.r3, context.r4, context.r5, context.r6, context.r7, context.r8, context.sb, context.sl,
+            context.fp, context.ip, context.sp, context.lr,
+        ]
+    }
+}
+
+struct Marker<'a> {
+    owners: &'a BTreeMap<u32, (u32, u32)>,
+    marked: BTreeSet<u32>,
+    pending: Vec<u32>,
+}
+
+impl Marker<'_> {
+    // pointers into the middle of an instance count as well, compiled code keeps field addresses in registers
+    fn mark(&mut self, word: u32) {
+        let owner = self.owners.range(..=word).next_back().filter(|(_, (end, _))| word < *end);
+
+        if let Some((_, &(_, instance))) = owner {
+            if self.marked.insert(instance) {
+                self.pending.push(instance);
+            }
+        }
+    }
+
+    fn mark_all(&mut self, data: &[u8]) {
+        for word in data.chunks_exact(4) {
+            self.mark(u32::from_le_bytes(word.try_into().unwrap()));
+        }
+    }
+}
@@ -12,12 +12,12 @@ use java_constants::MethodAccessFlags;
 use jvm::{JavaError, JavaType, JavaValue, Jvm, Method, Result as JvmResult};
 
 use wie_backend::System;
-use wie_core_arm::{Allocator, ArmCore, ArmCoreError, EmulatedFunction, EmulatedFunctionParam};
+use wie_core_arm::{Allocator, ArmCore, ArmCoreError, EmulatedFunction, Resume};
 use wie_util::{read_generic, write_generic, ByteWrite};
 
 use crate::context::KtfContextExt;
 
-use super::{name::JavaFullName, value::JavaValueExt, vtable_builder::JavaVtableBuilder, JvmSupportResult};
+use super::{name::JavaFullName, value::JavaValueExt, vtable_builder::JavaVtableBuilder, JvmSupportResult, KtfJvmSupport};
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
@@ -33,6 +33,16 @@ struct RawJavaMethod {
     unk6: u32,
 }
 
+// same as exception_table of class file, with states set by compiled code in place of bytecode offsets
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct RawJavaExceptionTableEntry {
+    pub start_state: u32,
+    pub end_state: u32,
+    pub handler_state: u32,
+    pub ptr_catch_class: u32, // 0 catches everything, for finally
+}
+
 pub struct JavaMethod {
     pub(crate) ptr_raw: u32,
     core: ArmCore,
@@ -101,6 +111,24 @@ impl JavaMethod {
         JavaFullName::from_ptr(&self.core, raw.ptr_name)
     }
 
+    pub fn exception_table(&self) -> JvmSupportResult<Vec<RawJavaExceptionTableEntry>> {
+        let raw: RawJavaMethod = read_generic(&self.core, self.ptr_raw)?;
+
+        // native methods use the field for their body instead
+        if MethodAccessFlags::from_bits_truncate(raw.access_flags).contains(MethodAccessFlags::NATIVE) {
+            return Ok(Vec::new());
+        }
+
+        (0..raw.exception_table_count as u32)
+            .map(|x| {
+                Ok(read_generic(
+                    &self.core,
+                    raw.fn_body_native_or_exception_table + x * size_of::<RawJavaExceptionTableEntry>() as u32,
+                )?)
+            })
+            .collect()
+    }
+
     pub async fn run(&self, args: Box<[JavaValue]>) -> JvmSupportResult<u32> {
         let raw: RawJavaMethod = read_generic(&self.core, self.ptr_raw)?;
 
@@ -145,21 +173,21 @@ impl JavaMethod {
         }
 
         #[async_trait::async_trait(?Send)]
-        impl<C, Context> EmulatedFunction<(), ArmCoreError, u32> for JavaMethodProxy<C, Context>
+        impl<C, Context> EmulatedFunction<(), ArmCoreError, Resume<u32>> for JavaMethodProxy<C, Context>
         where
             C: ?Sized,
             Context: Deref<Target = C> + DerefMut + Clone + 'static,
         {
-            async fn call(&self, core: &mut ArmCore, system: &mut System) -> Result<u32, ArmCoreError> {
+            async fn call(&self, core: &mut ArmCore, system: &mut System) -> Result<Resume<u32>, ArmCoreError> {
                 let param_count = self.parameter_types.len() as u32;
 
                 let args = if self.proto.access_flags.contains(MethodAccessFlags::NATIVE) {
-                    let param_base = u32::get(core, 1);
+                    let param_base = core.read_param(1)?;
                     (0..param_count)
                         .map(|x| read_generic(core, param_base + x * 4))
                         .collect::<wie_util::Result<Vec<u32>>>()?
                 } else {
-                    (0..param_count).map(|x| u32::get(core, (x + 1) as _)).collect::<Vec<_>>()
+                    core.read_params(param_count as usize + 1)?.split_off(1)
                 };
 
                 let args = args
@@ -170,9 +198,17 @@ impl JavaMethod {
 
                 let mut context = self.context.clone();
 
-                let result = self.proto.body.call(&system.jvm(), &mut context, args.into_boxed_slice()).await.unwrap();
-
-                Ok(result.as_raw())
+                let jvm = system.jvm();
+                match self.proto.body.call(&jvm, &mut context, args.into_boxed_slice()).await {
+                    Ok(x) => Ok(Resume::Return(x.as_raw())),
+                    // thrown to the guest, to be caught by compiled java code
+                    Err(JavaError::JavaException(x)) => Ok(Resume::Jump(
+                        KtfJvmSupport::throw(core, &jvm, &x)
+                            .await
+                            .map_err(|x| ArmCoreError::FunctionCallError(format!("{}", x)))?,
+                    )),
+                    Err(x) => Err(ArmCoreError::FunctionCallError(format!("{:?}", x))),
+                }
             }
         }
 
@@ -0,0 +1,87 @@
+use core::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+
+use wie_core_arm::{Allocator, ArmCore, PEB_BASE};
+use wie_util::{read_generic, write_generic, ByteRead};
+
+use crate::runtime::KtfPeb;
+
+use super::JvmSupportResult;
+
+const INITIAL_CAPACITY: u32 = 64;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct RawStringTable {
+    count: u32,
+    capacity: u32,
+}
+
+// string constants of compiled code, keyed by the address of their literal in the image.
+// kept in guest memory, so the instances stay reachable for the garbage collector and are part of savestates
+pub struct JavaStringTable {}
+
+impl JavaStringTable {
+    pub fn init(core: &mut ArmCore) -> JvmSupportResult<u32> {
+        let ptr_table = Allocator::alloc(core, size_of::<RawStringTable>() as u32 + INITIAL_CAPACITY * 8)?;
+        write_generic(
+            core,
+            ptr_table,
+            RawStringTable {
+                count: 0,
+                capacity: INITIAL_CAPACITY,
+            },
+        )?;
+
+        Ok(ptr_table)
+    }
+
+    pub fn get(core: &ArmCore, address: u32) -> JvmSupportResult<Option<u32>> {
+        let ptr_table = Self::ptr_table(core)?;
+        let table: RawStringTable = read_generic(core, ptr_table)?;
+
+        let data = core.read_bytes(Self::entry_address(ptr_table, 0), table.count * 8)?;
+        let entry = data
+            .chunks_exact(8)
+            .map(|x| {
+                (
+                    u32::from_le_bytes(x[..4].try_into().unwrap()),
+                    u32::from_le_bytes(x[4..].try_into().unwrap()),
+                )
+            })
+            .find(|(x, _)| *x == address);
+
+        Ok(entry.map(|(_, ptr_instance)| ptr_instance))
+    }
+
+    pub fn insert(core: &mut ArmCore, address: u32, ptr_instance: u32) -> JvmSupportResult<()> {
+        let mut ptr_table = Self::ptr_table(core)?;
+        let mut table: RawStringTable = read_generic(core, ptr_table)?;
+
+        if table.count == table.capacity {
+            table.capacity *= 2;
+            ptr_table = Allocator::realloc(core, ptr_table, size_of::<RawStringTable>() as u32 + table.capacity * 8)?;
+
+            let mut peb: KtfPeb = read_generic(core, PEB_BASE)?;
+            peb.ptr_string_table = ptr_table;
+            write_generic(core, PEB_BASE, peb)?;
+        }
+
+        write_generic(core, Self::entry_address(ptr_table, table.count), [address, ptr_instance])?;
+        table.count += 1;
+        write_generic(core, ptr_table, table)?;
+
+        Ok(())
+    }
+
+    fn ptr_table(core: &ArmCore) -> JvmSupportResult<u32> {
+        let peb: KtfPeb = read_generic(core, PEB_BASE)?;
+
+        Ok(peb.ptr_string_table)
+    }
+
+    fn entry_address(ptr_table: u32, index: u32) -> u32 {
+        ptr_table + size_of::<RawStringTable>() as u32 + index * 8
+    }
+}
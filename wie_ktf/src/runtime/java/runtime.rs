@@ -5,7 +5,7 @@ use wie_backend::{AsyncCallable, System};
 use wie_core_arm::ArmCore;
 
 use java_runtime::Runtime;
-use jvm::{Jvm, JvmCallback};
+use jvm::{JavaError, Jvm, JvmCallback};
 
 #[derive(Clone)]
 pub struct KtfRuntime {
@@ -27,7 +27,7 @@ impl KtfRuntime {
 #[async_trait::async_trait(?Send)]
 impl Runtime for KtfRuntime {
     async fn sleep(&self, duration: Duration) {
-        let now = self.system.platform().now();
+        let now = self.system.now();
         let until = now + duration.as_millis() as u64;
 
         self.system.clone().sleep(until).await; // TODO remove clone
@@ -46,20 +46,29 @@ impl Runtime for KtfRuntime {
         #[async_trait::async_trait(?Send)]
         impl AsyncCallable<u32, anyhow::Error> for SpawnProxy {
             async fn call(mut self) -> Result<u32, anyhow::Error> {
-                self.callback.call(&self.jvm, vec![].into_boxed_slice()).await?;
+                match self.callback.call(&self.jvm, vec![].into_boxed_slice()).await {
+                    // uncaught exception ends only the thread it's thrown on
+                    Err(JavaError::JavaException(x)) => tracing::warn!("Uncaught exception in thread: {:?}", x),
+                    x => {
+                        x?;
+                    }
+                }
 
                 Ok(0) // TODO
             }
         }
 
-        self.core.clone().spawn(SpawnProxy {
+        let result = self.core.clone().spawn(SpawnProxy {
             jvm: self.jvm.clone(),
             callback,
         });
+        if let Err(x) = result {
+            tracing::error!("Failed to spawn thread: {:?}", x);
+        }
     }
 
     fn now(&self) -> u64 {
-        self.system.platform().now().raw()
+        self.system.now().raw()
     }
 
     fn encode_str(&self, s: &str) -> Vec<u8> {
@@ -1,4 +1,4 @@
-use alloc::{boxed::Box, rc::Rc};
+use alloc::{boxed::Box, format, rc::Rc};
 
 use java_class_proto::MethodBody;
 use jvm::{JavaError, Jvm, Result as JvmResult};
@@ -49,12 +49,14 @@ impl WIPIJavaContextBase for KtfWIPIJavaContext {
             }
         }
 
-        self.core.spawn(SpawnProxy {
-            core: self.core.clone(),
-            system: self.system.clone(),
-            jvm: self.jvm.clone(),
-            callback,
-        });
+        self.core
+            .spawn(SpawnProxy {
+                core: self.core.clone(),
+                system: self.system.clone(),
+                jvm: self.jvm.clone(),
+                callback,
+            })
+            .map_err(|x| JavaError::FatalError(format!("{:?}", x)))?;
 
         Ok(())
     }
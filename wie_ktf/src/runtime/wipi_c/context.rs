@@ -1,10 +1,12 @@
-use alloc::{boxed::Box, vec, vec::Vec};
+use alloc::{boxed::Box, format, vec::Vec};
 
-use wie_backend::{AsyncCallable, System};
-use wie_core_arm::{Allocator, ArmCore, ArmCoreError, EmulatedFunction, EmulatedFunctionParam};
+use wie_backend::{AsyncCallable, ImportKind, ImportStatus, System};
+use wie_core_arm::{Allocator, ArmCore, ArmCoreError, EmulatedFunction};
 use wie_util::{read_generic, write_generic, ByteRead, ByteWrite};
 use wie_wipi_c::{WIPICContext, WIPICError, WIPICMemoryId, WIPICMethodBody, WIPICResult, WIPICWord};
 
+use crate::context::KtfContextExt;
+
 pub struct KtfWIPICContext<'a> {
     core: &'a mut ArmCore,
     system: &'a mut System,
@@ -61,23 +63,17 @@ impl WIPICContext for KtfWIPICContext<'_> {
         #[async_trait::async_trait(?Send)]
         impl EmulatedFunction<(), ArmCoreError, u32> for CMethodProxy {
             async fn call(&self, core: &mut ArmCore, system: &mut System) -> Result<u32, ArmCoreError> {
-                let a0 = u32::get(core, 0);
-                let a1 = u32::get(core, 1);
-                let a2 = u32::get(core, 2);
-                let a3 = u32::get(core, 3);
-                let a4 = u32::get(core, 4);
-                let a5 = u32::get(core, 5);
-                let a6 = u32::get(core, 6);
-                let a7 = u32::get(core, 7);
-                let a8 = u32::get(core, 8); // TODO create arg proxy
+                let args = core.read_params(10)?; // TODO create arg proxy
 
                 let mut context = KtfWIPICContext::new(core, system);
 
-                Ok(self
-                    .body
-                    .call(&mut context, vec![a0, a1, a2, a3, a4, a5, a6, a7, a8].into_boxed_slice())
-                    .await
-                    .unwrap())
+                let result = self.body.call(&mut context, args.into_boxed_slice()).await;
+
+                if let Err(WIPICError::Unimplemented(x)) = &result {
+                    system.report().record(ImportKind::Function, x, ImportStatus::Stub);
+                }
+
+                result.map_err(|x| ArmCoreError::FunctionCallError(format!("{:?}", x)))
             }
         }
 
@@ -86,6 +82,10 @@ impl WIPICContext for KtfWIPICContext<'_> {
         Ok(self.core.register_function(proxy).unwrap())
     }
 
+    fn set_function_name(&mut self, address: WIPICWord, name: &str) {
+        self.core.set_function_name(address, name)
+    }
+
     fn system(&mut self) -> &mut System {
         self.system
     }
@@ -112,20 +112,34 @@ impl WIPICContext for KtfWIPICContext<'_> {
 
         let system = self.system.clone();
 
-        self.core.spawn(SpawnProxy {
-            core: self.core.clone(),
-            system,
-            callback,
-        });
+        self.core
+            .spawn(SpawnProxy {
+                core: self.core.clone(),
+                system,
+                callback,
+            })
+            .map_err(|x| WIPICError::BackendError(format!("{:?}", x)))?;
 
         Ok(())
     }
+
+    fn screen_framebuffer(&mut self) -> Option<WIPICMemoryId> {
+        self.system.screen_framebuffer().map(WIPICMemoryId)
+    }
+
+    fn set_screen_framebuffer(&mut self, framebuffer: WIPICMemoryId) {
+        self.system.set_screen_framebuffer(framebuffer.0)
+    }
 }
 
 impl ByteRead for KtfWIPICContext<'_> {
     fn read_bytes(&self, address: WIPICWord, size: WIPICWord) -> wie_util::Result<Vec<u8>> {
         self.core.read_bytes(address, size)
     }
+
+    fn read_into(&self, address: WIPICWord, buffer: &mut [u8]) -> wie_util::Result<()> {
+        self.core.read_into(address, buffer)
+    }
 }
 
 impl ByteWrite for KtfWIPICContext<'_> {
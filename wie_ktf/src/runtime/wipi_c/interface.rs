@@ -1,4 +1,3 @@
-use alloc::vec::Vec;
 use core::mem::size_of;
 
 use bytemuck::{Pod, Zeroable};
@@ -8,11 +7,11 @@ use wie_core_arm::{ArmCore, ArmCoreResult};
 use wie_util::write_generic;
 use wie_wipi_c::{
     api::{
-        database::get_database_method_table, graphics::get_graphics_method_table, kernel::get_kernel_method_table, media::get_media_method_table,
-        misc::get_misc_method_table, net::get_net_method_table, stub::get_stub_method_table, uic::get_uic_method_table,
-        unk12::get_unk12_method_table, unk3::get_unk3_method_table, util::get_util_method_table,
+        database::get_database_method_table, fs::get_fs_method_table, graphics::get_graphics_method_table, kernel::get_kernel_method_table,
+        media::get_media_method_table, misc::get_misc_method_table, net::get_net_method_table, stub::get_stub_method_table,
+        uic::get_uic_method_table, unk12::get_unk12_method_table, unk3::get_unk3_method_table, util::get_util_method_table,
     },
-    WIPICContext, WIPICMethodBody, WIPICResult,
+    WIPICContext, WIPICMethodTable, WIPICResult,
 };
 
 use crate::runtime::wipi_c::context::KtfWIPICContext;
@@ -39,12 +38,15 @@ struct WIPICInterface {
     interface_16: u32,
 }
 
-fn write_methods(context: &mut dyn WIPICContext, methods: Vec<WIPICMethodBody>) -> WIPICResult<u32> {
+fn write_methods(context: &mut dyn WIPICContext, methods: WIPICMethodTable) -> WIPICResult<u32> {
     let address = context.alloc_raw((methods.len() * 4) as u32)?;
 
     let mut cursor = address;
-    for method in methods {
+    for (name, method) in methods {
         let address = context.register_function(method)?;
+        if !name.is_empty() {
+            context.set_function_name(address, name);
+        }
 
         write_generic(context, cursor, address)?;
         cursor += 4;
@@ -70,7 +72,7 @@ async fn get_wipic_interfaces(context: &mut dyn WIPICContext) -> WIPICResult<u32
     let interface_2 = write_methods(context, get_graphics_method_table())?;
     let interface_3 = write_methods(context, get_unk3_method_table())?;
     let interface_4 = write_methods(context, get_stub_method_table(4))?;
-    let interface_5 = write_methods(context, get_stub_method_table(5))?;
+    let interface_5 = write_methods(context, get_fs_method_table())?; // TODO unverified, next to the database
     let interface_6 = write_methods(context, get_database_method_table())?;
     let interface_7 = write_methods(context, get_stub_method_table(7))?;
     let interface_8 = write_methods(context, get_uic_method_table())?; // uic
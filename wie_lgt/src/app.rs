@@ -1,10 +1,12 @@
-use alloc::string::String;
+use alloc::{boxed::Box, string::String, vec::Vec};
 
 use anyhow::Context;
 use elf::{endian::AnyEndian, ElfBytes};
 
-use wie_backend::{App, Event, System};
-use wie_core_arm::{Allocator, ArmCore};
+use wie_backend::{App, CompatibilityReport, Event, GuestAccess, System, SystemSnapshot, Telemetry};
+use wie_core_arm::{Allocator, ArmCore, ArmCoreConfig, MemoryPermission, Segment};
+
+use crate::runtime::get_import_function;
 
 pub struct LgtApp {
     core: ArmCore,
@@ -15,7 +17,7 @@ pub struct LgtApp {
 
 impl LgtApp {
     pub fn new(main_class_name: Option<String>, system: System) -> anyhow::Result<Self> {
-        let mut core = ArmCore::new(system.clone())?;
+        let mut core = ArmCore::new(system.clone(), ArmCoreConfig::default())?;
 
         Allocator::init(&mut core)?;
 
@@ -37,10 +39,13 @@ impl LgtApp {
     }
 
     #[tracing::instrument(name = "start", skip_all)]
-    async fn do_start(core: &mut ArmCore, _system: &mut System, entrypoint: u32, _main_class_name: Option<String>) -> anyhow::Result<()> {
-        core.run_function(entrypoint + 1, &[]).await?;
+    async fn do_start(core: &mut ArmCore, _system: &mut System, entrypoint: u32, main_class_name: Option<String>) -> anyhow::Result<()> {
+        let fn_get_import_function = core.register_function(get_import_function)?;
+
+        core.run_function::<()>(entrypoint + 1, &[fn_get_import_function]).await?;
 
-        anyhow::bail!("Not yet implemented")
+        // TODO the jlet is started from the java side, which we don't have on lgt yet
+        anyhow::bail!("Starting {} is not implemented on LGT yet", main_class_name.as_deref().unwrap_or("jlet"))
     }
 
     fn load(core: &mut ArmCore, data: &[u8]) -> anyhow::Result<u32> {
@@ -57,18 +62,37 @@ impl LgtApp {
             strtab_opt.ok_or(anyhow::anyhow!("Invalid file"))?,
         );
 
+        let mut segments = Vec::new();
         for shdr in shdrs {
             let section_name = strtab.get(shdr.sh_name as usize)?;
 
             if shdr.sh_addr != 0 {
                 tracing::debug!("Section {} at {:x}", section_name, shdr.sh_addr);
 
-                let data = elf.section_data(&shdr)?.0;
-
-                core.load(data, shdr.sh_addr as u32, shdr.sh_size as usize)?;
+                // nobits sections like .bss have no data in the file
+                let data: &[u8] = if shdr.sh_type == elf::abi::SHT_NOBITS {
+                    &[]
+                } else {
+                    elf.section_data(&shdr)?.0
+                };
+                let permission = if shdr.sh_flags & elf::abi::SHF_EXECINSTR as u64 != 0 {
+                    MemoryPermission::ReadExecute
+                } else {
+                    MemoryPermission::ReadWrite
+                };
+
+                segments.push(Segment {
+                    name: section_name,
+                    address: shdr.sh_addr as u32,
+                    data,
+                    size: shdr.sh_size as usize,
+                    permission,
+                });
             }
         }
 
+        core.load("lgt", &segments)?;
+
         tracing::debug!("Entrypoint: {:#x}", elf.ehdr.e_entry);
 
         Ok(elf.ehdr.e_entry as u32)
@@ -96,4 +120,45 @@ impl App for LgtApp {
     fn tick(&mut self) -> anyhow::Result<()> {
         self.system.tick()
     }
+
+    fn report(&self) -> CompatibilityReport {
+        self.system.report().clone()
+    }
+
+    // memory of the core, followed by what the system keeps outside of it
+    fn save_state(&self) -> anyhow::Result<Vec<u8>> {
+        let core = self.core.snapshot()?;
+
+        let mut result = (core.len() as u32).to_le_bytes().to_vec();
+        result.extend(core);
+        result.extend(self.system.snapshot().to_bytes());
+
+        Ok(result)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> anyhow::Result<()> {
+        let (length, rest) = state.split_at_checked(4).context("Invalid state")?;
+        let (core, system) = rest
+            .split_at_checked(u32::from_le_bytes(length.try_into().unwrap()) as _)
+            .context("Invalid state")?;
+
+        // parsed before anything is restored, so a broken state leaves the app as it was
+        let system = SystemSnapshot::from_bytes(system)?;
+        self.core.restore_snapshot(core)?;
+        // inputs queued before loading are replaced by the ones pending when it was saved
+        self.system.restore_snapshot(&system)?;
+
+        Ok(())
+    }
+
+    fn guest(&self) -> Option<Box<dyn GuestAccess>> {
+        Some(Box::new(self.core.clone()))
+    }
+
+    fn telemetry(&self) -> Telemetry {
+        let mut telemetry = self.system.telemetry();
+        telemetry.heap_usage = Allocator::stats(&self.core).ok();
+
+        telemetry
+    }
 }
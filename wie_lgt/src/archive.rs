@@ -2,7 +2,7 @@ use alloc::{borrow::ToOwned, boxed::Box, collections::BTreeMap, format, string::
 
 use anyhow::Context;
 
-use wie_backend::{extract_zip, App, Archive, Platform, System};
+use wie_backend::{decode_euc_kr, extract_zip, App, Archive, Platform, System};
 
 use crate::app::LgtApp;
 
@@ -10,6 +10,7 @@ pub struct LgtArchive {
     jar: Vec<u8>,
     id: String,
     main_class_name: Option<String>,
+    name: Option<String>,
 }
 
 impl LgtArchive {
@@ -31,7 +32,10 @@ impl LgtArchive {
 
         let jar = files.remove(&format!("{}.jar", app_info.aid)).context("Invalid format")?;
 
-        Ok(Self::from_jar(jar, &app_info.aid, Some(app_info.mclass)))
+        Ok(Self {
+            name: app_info.name,
+            ..Self::from_jar(jar, &app_info.aid, Some(app_info.mclass))
+        })
     }
 
     pub fn from_jar(data: Vec<u8>, id: &str, main_class_name: Option<String>) -> Self {
@@ -39,6 +43,7 @@ impl LgtArchive {
             jar: data,
             id: id.into(),
             main_class_name,
+            name: None,
         }
     }
 }
@@ -48,6 +53,27 @@ impl Archive for LgtArchive {
         self.id.to_owned()
     }
 
+    fn platform(&self) -> &'static str {
+        "LGT"
+    }
+
+    fn main_class_name(&self) -> Option<String> {
+        self.main_class_name.clone()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    // TODO we don't know where the icon is
+    fn icon(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn files(&self) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
+        extract_zip(&self.jar)
+    }
+
     fn load_app(self: Box<Self>, platform: Box<dyn Platform>) -> anyhow::Result<Box<dyn App>> {
         let system = System::new(platform, Box::new(()));
 
@@ -61,12 +87,14 @@ impl Archive for LgtArchive {
 struct LgtAppInfo {
     aid: String,
     mclass: String,
+    name: Option<String>,
 }
 
 impl LgtAppInfo {
     pub fn parse(data: &[u8]) -> Self {
         let mut aid = String::new();
         let mut mclass = String::new();
+        let mut name = None;
 
         let mut lines = data.split(|x| *x == b'\n');
 
@@ -75,10 +103,11 @@ impl LgtAppInfo {
                 aid = String::from_utf8_lossy(&line[4..]).into();
             } else if line.starts_with(b"MClass:") {
                 mclass = String::from_utf8_lossy(&line[7..]).into();
+            } else if line.starts_with(b"Name:") {
+                name = Some(decode_euc_kr(&line[5..]).trim().into());
             }
-            // TODO load name, it's in euc-kr..
         }
 
-        Self { aid, mclass }
+        Self { aid, mclass, name }
     }
 }
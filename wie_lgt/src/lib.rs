@@ -3,5 +3,6 @@ extern crate alloc;
 
 mod app;
 mod archive;
+mod runtime;
 
 pub use archive::LgtArchive;
@@ -0,0 +1,45 @@
+use alloc::format;
+
+use wie_backend::{ImportKind, ImportStatus, System};
+use wie_core_arm::{ArmCore, ArmCoreError, ArmCoreResult, EmulatedFunction};
+
+// lgt binaries resolve their imports through a function we pass to the entrypoint, by table id and index in the table.
+// we don't know what's in each table yet, so every import resolves to a stub reporting itself when called
+pub async fn get_import_function(core: &mut ArmCore, system: &mut System, import_table: u32, function_index: u32) -> ArmCoreResult<u32> {
+    tracing::trace!("get_import_function({:#x}, {:#x})", import_table, function_index);
+
+    let name = format!("lgt_import_{:x}_{:x}", import_table, function_index);
+    system.report().record(ImportKind::Function, &name, ImportStatus::Missing);
+
+    let address = core.register_function(UnknownImport {
+        import_table,
+        function_index,
+    })?;
+    core.set_function_name(address, &name);
+
+    Ok(address)
+}
+
+struct UnknownImport {
+    import_table: u32,
+    function_index: u32,
+}
+
+#[async_trait::async_trait(?Send)]
+impl EmulatedFunction<(), ArmCoreError, u32> for UnknownImport {
+    async fn call(&self, core: &mut ArmCore, _: &mut System) -> Result<u32, ArmCoreError> {
+        let [a0, a1, a2, a3] = core.read_params(4)?.try_into().unwrap();
+
+        tracing::warn!(
+            "Unknown import {:#x}:{:#x}({:#x}, {:#x}, {:#x}, {:#x})",
+            self.import_table,
+            self.function_index,
+            a0,
+            a1,
+            a2,
+            a3
+        );
+
+        Ok(0)
+    }
+}
@@ -1 +1,3 @@
+pub mod lcdui;
 pub mod midlet;
+pub mod rms;
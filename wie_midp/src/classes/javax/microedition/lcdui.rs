@@ -0,0 +1,8 @@
+mod canvas;
+mod display;
+mod displayable;
+pub mod game;
+mod graphics;
+mod image;
+
+pub use self::{canvas::Canvas, display::Display, displayable::Displayable, graphics::Graphics, image::Image};
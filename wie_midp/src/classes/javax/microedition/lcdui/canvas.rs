@@ -0,0 +1,196 @@
+use alloc::vec;
+
+use java_class_proto::JavaMethodProto;
+use jvm::{ClassInstanceRef, Jvm, Result as JvmResult};
+
+use crate::{
+    classes::javax::microedition::lcdui::Display,
+    context::{MIDPJavaClassProto, MIDPJavaContext},
+};
+
+// game actions from the midp spec
+const UP: i32 = 1;
+const LEFT: i32 = 2;
+const RIGHT: i32 = 5;
+const DOWN: i32 = 6;
+const FIRE: i32 = 8;
+
+// arrow keys have no standard key code, we use the ones most handsets use
+pub(super) const KEY_UP: i32 = -1;
+pub(super) const KEY_DOWN: i32 = -2;
+pub(super) const KEY_LEFT: i32 = -3;
+pub(super) const KEY_RIGHT: i32 = -4;
+pub(super) const KEY_FIRE: i32 = -5;
+
+// class javax.microedition.lcdui.Canvas
+pub struct Canvas {}
+
+impl Canvas {
+    pub fn as_proto() -> MIDPJavaClassProto {
+        MIDPJavaClassProto {
+            parent_class: Some("javax/microedition/lcdui/Displayable"),
+            interfaces: vec![],
+            methods: vec![
+                JavaMethodProto::new("<init>", "()V", Self::init, Default::default()),
+                JavaMethodProto::new_abstract("paint", "(Ljavax/microedition/lcdui/Graphics;)V", Default::default()),
+                JavaMethodProto::new("repaint", "()V", Self::repaint, Default::default()),
+                JavaMethodProto::new("repaint", "(IIII)V", Self::repaint_with_area, Default::default()),
+                JavaMethodProto::new("serviceRepaints", "()V", Self::service_repaints, Default::default()),
+                JavaMethodProto::new("getGameAction", "(I)I", Self::get_game_action, Default::default()),
+                JavaMethodProto::new("getKeyCode", "(I)I", Self::get_key_code, Default::default()),
+                JavaMethodProto::new("setFullScreenMode", "(Z)V", Self::set_full_screen_mode, Default::default()),
+                JavaMethodProto::new("hasPointerEvents", "()Z", Self::has_pointer_events, Default::default()),
+                JavaMethodProto::new("isDoubleBuffered", "()Z", Self::is_double_buffered, Default::default()),
+                JavaMethodProto::new("keyPressed", "(I)V", Self::key_pressed, Default::default()),
+                JavaMethodProto::new("keyReleased", "(I)V", Self::key_released, Default::default()),
+                JavaMethodProto::new("pointerPressed", "(II)V", Self::pointer_pressed, Default::default()),
+                JavaMethodProto::new("pointerReleased", "(II)V", Self::pointer_released, Default::default()),
+                JavaMethodProto::new("pointerDragged", "(II)V", Self::pointer_dragged, Default::default()),
+                JavaMethodProto::new("showNotify", "()V", Self::show_notify, Default::default()),
+                JavaMethodProto::new("hideNotify", "()V", Self::hide_notify, Default::default()),
+            ],
+            fields: vec![],
+        }
+    }
+
+    async fn init(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::<init>({:?})", &this);
+
+        jvm.invoke_special(&this, "javax/microedition/lcdui/Displayable", "<init>", "()V", ())
+            .await
+    }
+
+    async fn repaint(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::repaint({:?})", &this);
+
+        let width: i32 = jvm.invoke_virtual(&this, "getWidth", "()I", ()).await?;
+        let height: i32 = jvm.invoke_virtual(&this, "getHeight", "()I", ()).await?;
+
+        jvm.invoke_virtual(&this, "repaint", "(IIII)V", (0, 0, width, height)).await
+    }
+
+    // the whole canvas is painted on the next redraw, there's no partial repaint yet
+    async fn repaint_with_area(
+        _: &Jvm,
+        context: &mut MIDPJavaContext,
+        this: ClassInstanceRef<Self>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.Canvas::repaint({:?}, {}, {}, {}, {})",
+            &this,
+            x,
+            y,
+            width,
+            height
+        );
+
+        let mut platform = context.system().platform();
+        let screen = platform.screen();
+        screen.request_redraw().unwrap();
+
+        Ok(())
+    }
+
+    async fn service_repaints(jvm: &Jvm, context: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::serviceRepaints({:?})", &this);
+
+        Display::repaint(jvm, context).await
+    }
+
+    async fn get_game_action(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, key_code: i32) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::getGameAction({:?}, {})", &this, key_code);
+
+        Ok(match key_code {
+            KEY_UP => UP,
+            KEY_DOWN => DOWN,
+            KEY_LEFT => LEFT,
+            KEY_RIGHT => RIGHT,
+            KEY_FIRE => FIRE,
+            // number keys double as game keys
+            0x32 => UP,
+            0x38 => DOWN,
+            0x34 => LEFT,
+            0x36 => RIGHT,
+            0x35 => FIRE,
+            _ => 0,
+        })
+    }
+
+    async fn get_key_code(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, game_action: i32) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::getKeyCode({:?}, {})", &this, game_action);
+
+        Ok(match game_action {
+            UP => KEY_UP,
+            DOWN => KEY_DOWN,
+            LEFT => KEY_LEFT,
+            RIGHT => KEY_RIGHT,
+            FIRE => KEY_FIRE,
+            _ => 0,
+        })
+    }
+
+    async fn set_full_screen_mode(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, mode: bool) -> JvmResult<()> {
+        tracing::warn!("stub javax.microedition.lcdui.Canvas::setFullScreenMode({:?}, {})", &this, mode);
+
+        Ok(())
+    }
+
+    async fn has_pointer_events(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<bool> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::hasPointerEvents({:?})", &this);
+
+        Ok(true)
+    }
+
+    async fn is_double_buffered(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<bool> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::isDoubleBuffered({:?})", &this);
+
+        Ok(true)
+    }
+
+    // defaults for canvases not overriding the event callbacks
+    async fn key_pressed(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, key_code: i32) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::keyPressed({:?}, {})", &this, key_code);
+
+        Ok(())
+    }
+
+    async fn key_released(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, key_code: i32) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::keyReleased({:?}, {})", &this, key_code);
+
+        Ok(())
+    }
+
+    async fn pointer_pressed(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, x: i32, y: i32) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::pointerPressed({:?}, {}, {})", &this, x, y);
+
+        Ok(())
+    }
+
+    async fn pointer_released(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, x: i32, y: i32) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::pointerReleased({:?}, {}, {})", &this, x, y);
+
+        Ok(())
+    }
+
+    async fn pointer_dragged(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, x: i32, y: i32) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::pointerDragged({:?}, {}, {})", &this, x, y);
+
+        Ok(())
+    }
+
+    async fn show_notify(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::showNotify({:?})", &this);
+
+        Ok(())
+    }
+
+    async fn hide_notify(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Canvas::hideNotify({:?})", &this);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+use alloc::vec;
+
+use java_class_proto::{JavaFieldProto, JavaMethodProto};
+use java_constants::{FieldAccessFlags, MethodAccessFlags};
+use jvm::{ClassInstanceRef, Jvm, Result as JvmResult};
+
+use wie_backend::{Event, KeyCode};
+
+use crate::{
+    classes::javax::microedition::{
+        lcdui::{
+            canvas::{KEY_DOWN, KEY_FIRE, KEY_LEFT, KEY_RIGHT, KEY_UP},
+            Canvas, Displayable, Image,
+        },
+        midlet::MIDlet,
+    },
+    context::{MIDPJavaClassProto, MIDPJavaContext},
+};
+
+// class javax.microedition.lcdui.Display
+pub struct Display {}
+
+impl Display {
+    pub fn as_proto() -> MIDPJavaClassProto {
+        MIDPJavaClassProto {
+            parent_class: Some("java/lang/Object"),
+            interfaces: vec![],
+            methods: vec![
+                JavaMethodProto::new("<init>", "()V", Self::init, Default::default()),
+                JavaMethodProto::new(
+                    "getDisplay",
+                    "(Ljavax/microedition/midlet/MIDlet;)Ljavax/microedition/lcdui/Display;",
+                    Self::get_display,
+                    MethodAccessFlags::STATIC,
+                ),
+                JavaMethodProto::new(
+                    "getCurrent",
+                    "()Ljavax/microedition/lcdui/Displayable;",
+                    Self::get_current,
+                    Default::default(),
+                ),
+                JavaMethodProto::new(
+                    "setCurrent",
+                    "(Ljavax/microedition/lcdui/Displayable;)V",
+                    Self::set_current,
+                    Default::default(),
+                ),
+                JavaMethodProto::new("isColor", "()Z", Self::is_color, Default::default()),
+                JavaMethodProto::new("numColors", "()I", Self::num_colors, Default::default()),
+            ],
+            fields: vec![
+                JavaFieldProto::new("current", "Ljavax/microedition/lcdui/Displayable;", Default::default()),
+                // canvases are painted here, and presented to the screen when the paint is done
+                JavaFieldProto::new("backBuffer", "Ljavax/microedition/lcdui/Image;", Default::default()),
+                // we run a single midlet at a time, so there's only one display
+                JavaFieldProto::new("instance", "Ljavax/microedition/lcdui/Display;", FieldAccessFlags::STATIC),
+            ],
+        }
+    }
+
+    async fn init(jvm: &Jvm, context: &mut MIDPJavaContext, mut this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Display::<init>({:?})", &this);
+
+        let (width, height) = {
+            let mut platform = context.system().platform();
+            let screen = platform.screen();
+            (screen.width(), screen.height())
+        };
+
+        let back_buffer: ClassInstanceRef<Image> = jvm
+            .invoke_static(
+                "javax/microedition/lcdui/Image",
+                "createImage",
+                "(II)Ljavax/microedition/lcdui/Image;",
+                (width as i32, height as i32),
+            )
+            .await?;
+        jvm.put_field(&mut this, "backBuffer", "Ljavax/microedition/lcdui/Image;", back_buffer)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_display(jvm: &Jvm, _: &mut MIDPJavaContext, midlet: ClassInstanceRef<MIDlet>) -> JvmResult<ClassInstanceRef<Self>> {
+        tracing::debug!("javax.microedition.lcdui.Display::getDisplay({:?})", &midlet);
+
+        let display: ClassInstanceRef<Self> = Self::instance(jvm).await?;
+        if !display.is_null() {
+            return Ok(display);
+        }
+
+        let display: ClassInstanceRef<Self> = jvm.new_class("javax/microedition/lcdui/Display", "()V", ()).await?.into();
+        jvm.put_static_field(
+            "javax/microedition/lcdui/Display",
+            "instance",
+            "Ljavax/microedition/lcdui/Display;",
+            display.clone(),
+        )
+        .await?;
+
+        Ok(display)
+    }
+
+    async fn get_current(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<ClassInstanceRef<Displayable>> {
+        tracing::debug!("javax.microedition.lcdui.Display::getCurrent({:?})", &this);
+
+        jvm.get_field(&this, "current", "Ljavax/microedition/lcdui/Displayable;").await
+    }
+
+    async fn set_current(
+        jvm: &Jvm,
+        context: &mut MIDPJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        displayable: ClassInstanceRef<Displayable>,
+    ) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Display::setCurrent({:?}, {:?})", &this, &displayable);
+
+        let old = Self::current_canvas(jvm).await?;
+        if !old.is_null() {
+            jvm.invoke_virtual(&old, "hideNotify", "()V", ()).await?;
+        }
+
+        jvm.put_field(&mut this, "current", "Ljavax/microedition/lcdui/Displayable;", displayable)
+            .await?;
+
+        let new = Self::current_canvas(jvm).await?;
+        if !new.is_null() {
+            jvm.invoke_virtual(&new, "showNotify", "()V", ()).await?;
+        }
+
+        let mut platform = context.system().platform();
+        platform.screen().request_redraw().unwrap();
+
+        Ok(())
+    }
+
+    async fn is_color(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<bool> {
+        tracing::debug!("javax.microedition.lcdui.Display::isColor({:?})", &this);
+
+        Ok(true)
+    }
+
+    async fn num_colors(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.Display::numColors({:?})", &this);
+
+        Ok(1 << 24)
+    }
+
+    // paints the current canvas to the back buffer, and presents it once the canvas is done
+    pub async fn repaint(jvm: &Jvm, context: &mut MIDPJavaContext) -> JvmResult<()> {
+        let display = Self::instance(jvm).await?;
+        if display.is_null() {
+            return Ok(());
+        }
+
+        let canvas = Self::current_canvas(jvm).await?;
+        if canvas.is_null() {
+            return Ok(());
+        }
+
+        let back_buffer: ClassInstanceRef<Image> = jvm.get_field(&display, "backBuffer", "Ljavax/microedition/lcdui/Image;").await?;
+        let graphics = jvm
+            .new_class(
+                "javax/microedition/lcdui/Graphics",
+                "(Ljavax/microedition/lcdui/Image;)V",
+                (back_buffer.clone(),),
+            )
+            .await?;
+
+        jvm.invoke_virtual(&canvas, "paint", "(Ljavax/microedition/lcdui/Graphics;)V", (graphics,))
+            .await?;
+
+        let image = Image::image(jvm, &back_buffer).await?;
+
+        context.system().repaint(&*image);
+
+        Ok(())
+    }
+
+    // there's no main loop in midp, events are delivered to the current canvas from a task of our own
+    pub async fn run_event_loop(jvm: &Jvm, context: &mut MIDPJavaContext) -> JvmResult<()> {
+        loop {
+            let maybe_event = context.system().event_queue().pop();
+
+            let event = match maybe_event {
+                Some(x) => x,
+                None => {
+                    let until = context.system().now() + 16;
+                    context.system().sleep(until).await; // TODO we need to wait for events
+
+                    continue;
+                }
+            };
+
+            match event {
+                Event::Redraw => Self::repaint(jvm, context).await?,
+                Event::Suspend => MIDlet::lifecycle_event(jvm, "pauseApp").await?,
+                Event::Resume => MIDlet::lifecycle_event(jvm, "startApp").await?,
+                _ => Self::input_event(jvm, event).await?,
+            }
+        }
+    }
+
+    async fn input_event(jvm: &Jvm, event: Event) -> JvmResult<()> {
+        let canvas = Self::current_canvas(jvm).await?;
+        if canvas.is_null() {
+            return Ok(());
+        }
+
+        match event {
+            Event::Keydown(x) => jvm.invoke_virtual(&canvas, "keyPressed", "(I)V", (Self::key_code(x),)).await,
+            Event::Keyup(x) => jvm.invoke_virtual(&canvas, "keyReleased", "(I)V", (Self::key_code(x),)).await,
+            Event::PointerDown { x, y } => jvm.invoke_virtual(&canvas, "pointerPressed", "(II)V", (x as i32, y as i32)).await,
+            Event::PointerMove { x, y } => jvm.invoke_virtual(&canvas, "pointerDragged", "(II)V", (x as i32, y as i32)).await,
+            Event::PointerUp { x, y } => jvm.invoke_virtual(&canvas, "pointerReleased", "(II)V", (x as i32, y as i32)).await,
+            _ => {
+                tracing::debug!("Ignoring {:?}, not delivered to midlets", event);
+
+                Ok(())
+            }
+        }
+    }
+
+    // null if nothing is shown or the current displayable is not a canvas
+    async fn current_canvas(jvm: &Jvm) -> JvmResult<ClassInstanceRef<Canvas>> {
+        let display = Self::instance(jvm).await?;
+        if display.is_null() {
+            return Ok(None.into());
+        }
+
+        let current: ClassInstanceRef<Canvas> = jvm.get_field(&display, "current", "Ljavax/microedition/lcdui/Displayable;").await?;
+        if current.is_null() || !jvm.is_instance(&**current, "javax/microedition/lcdui/Canvas").await? {
+            return Ok(None.into());
+        }
+
+        Ok(current)
+    }
+
+    // number keys are their ascii code, as in the spec
+    fn key_code(key: KeyCode) -> i32 {
+        match key {
+            KeyCode::UP => KEY_UP,
+            KeyCode::DOWN => KEY_DOWN,
+            KeyCode::LEFT => KEY_LEFT,
+            KeyCode::RIGHT => KEY_RIGHT,
+            KeyCode::OK => KEY_FIRE,
+            KeyCode::NUM0 => b'0' as _,
+            KeyCode::NUM1 => b'1' as _,
+            KeyCode::NUM2 => b'2' as _,
+            KeyCode::NUM3 => b'3' as _,
+            KeyCode::NUM4 => b'4' as _,
+            KeyCode::NUM5 => b'5' as _,
+            KeyCode::NUM6 => b'6' as _,
+            KeyCode::NUM7 => b'7' as _,
+            KeyCode::NUM8 => b'8' as _,
+            KeyCode::NUM9 => b'9' as _,
+            KeyCode::HASH => b'#' as _,
+            KeyCode::STAR => b'*' as _,
+            KeyCode::LEFT_SOFT_KEY => -6,
+            KeyCode::RIGHT_SOFT_KEY => -7,
+            KeyCode::CLEAR => -8,
+        }
+    }
+
+    async fn instance(jvm: &Jvm) -> JvmResult<ClassInstanceRef<Self>> {
+        jvm.get_static_field("javax/microedition/lcdui/Display", "instance", "Ljavax/microedition/lcdui/Display;")
+            .await
+    }
+}
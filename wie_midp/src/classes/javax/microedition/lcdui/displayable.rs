@@ -0,0 +1,68 @@
+use alloc::vec;
+
+use java_class_proto::JavaMethodProto;
+use jvm::{ClassInstanceRef, Jvm, Result as JvmResult};
+
+use crate::{
+    classes::javax::microedition::lcdui::Display,
+    context::{MIDPJavaClassProto, MIDPJavaContext},
+};
+
+// class javax.microedition.lcdui.Displayable
+pub struct Displayable {}
+
+impl Displayable {
+    pub fn as_proto() -> MIDPJavaClassProto {
+        MIDPJavaClassProto {
+            parent_class: Some("java/lang/Object"),
+            interfaces: vec![],
+            methods: vec![
+                JavaMethodProto::new("<init>", "()V", Self::init, Default::default()),
+                JavaMethodProto::new("getWidth", "()I", Self::get_width, Default::default()),
+                JavaMethodProto::new("getHeight", "()I", Self::get_height, Default::default()),
+                JavaMethodProto::new("isShown", "()Z", Self::is_shown, Default::default()),
+            ],
+            fields: vec![],
+        }
+    }
+
+    async fn init(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Displayable::<init>({:?})", &this);
+
+        Ok(())
+    }
+
+    async fn get_width(_: &Jvm, context: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.Displayable::getWidth({:?})", &this);
+
+        let mut platform = context.system().platform();
+
+        Ok(platform.screen().width() as _)
+    }
+
+    async fn get_height(_: &Jvm, context: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.Displayable::getHeight({:?})", &this);
+
+        let mut platform = context.system().platform();
+
+        Ok(platform.screen().height() as _)
+    }
+
+    async fn is_shown(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<bool> {
+        tracing::debug!("javax.microedition.lcdui.Displayable::isShown({:?})", &this);
+
+        let display: ClassInstanceRef<Display> = jvm
+            .get_static_field("javax/microedition/lcdui/Display", "instance", "Ljavax/microedition/lcdui/Display;")
+            .await?;
+        if display.is_null() {
+            return Ok(false);
+        }
+
+        let current: ClassInstanceRef<Self> = jvm.get_field(&display, "current", "Ljavax/microedition/lcdui/Displayable;").await?;
+        if current.is_null() {
+            return Ok(false);
+        }
+
+        jvm.invoke_virtual(&current, "equals", "(Ljava/lang/Object;)Z", (this,)).await
+    }
+}
@@ -0,0 +1,6 @@
+mod layer;
+mod layer_manager;
+mod sprite;
+mod tiled_layer;
+
+pub use self::{layer::Layer, layer_manager::LayerManager, sprite::Sprite, tiled_layer::TiledLayer};
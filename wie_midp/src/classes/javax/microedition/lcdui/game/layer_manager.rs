@@ -0,0 +1,199 @@
+use alloc::{vec, vec::Vec};
+
+use java_class_proto::{JavaFieldProto, JavaMethodProto};
+use java_runtime::classes::java::lang::Object;
+use jvm::{ClassInstanceRef, Jvm, Result as JvmResult};
+
+use crate::{
+    classes::javax::microedition::lcdui::game::Layer,
+    context::{MIDPJavaClassProto, MIDPJavaContext},
+};
+
+// class javax.microedition.lcdui.game.LayerManager
+pub struct LayerManager {}
+
+impl LayerManager {
+    pub fn as_proto() -> MIDPJavaClassProto {
+        MIDPJavaClassProto {
+            parent_class: Some("java/lang/Object"),
+            interfaces: vec![],
+            methods: vec![
+                JavaMethodProto::new("<init>", "()V", Self::init, Default::default()),
+                JavaMethodProto::new("append", "(Ljavax/microedition/lcdui/game/Layer;)V", Self::append, Default::default()),
+                JavaMethodProto::new("insert", "(Ljavax/microedition/lcdui/game/Layer;I)V", Self::insert, Default::default()),
+                JavaMethodProto::new("remove", "(Ljavax/microedition/lcdui/game/Layer;)V", Self::remove, Default::default()),
+                JavaMethodProto::new(
+                    "getLayerAt",
+                    "(I)Ljavax/microedition/lcdui/game/Layer;",
+                    Self::get_layer_at,
+                    Default::default(),
+                ),
+                JavaMethodProto::new("getSize", "()I", Self::get_size, Default::default()),
+                JavaMethodProto::new("setViewWindow", "(IIII)V", Self::set_view_window, Default::default()),
+                JavaMethodProto::new("paint", "(Ljavax/microedition/lcdui/Graphics;II)V", Self::paint, Default::default()),
+            ],
+            fields: vec![
+                // index 0 is the topmost layer
+                JavaFieldProto::new("layers", "[Ljavax/microedition/lcdui/game/Layer;", Default::default()),
+                JavaFieldProto::new("viewX", "I", Default::default()),
+                JavaFieldProto::new("viewY", "I", Default::default()),
+                JavaFieldProto::new("viewWidth", "I", Default::default()),
+                JavaFieldProto::new("viewHeight", "I", Default::default()),
+            ],
+        }
+    }
+
+    async fn init(jvm: &Jvm, _: &mut MIDPJavaContext, mut this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.game.LayerManager::<init>({:?})", &this);
+
+        Self::store_layers(jvm, &mut this, Vec::new()).await?;
+
+        jvm.put_field(&mut this, "viewX", "I", 0).await?;
+        jvm.put_field(&mut this, "viewY", "I", 0).await?;
+        jvm.put_field(&mut this, "viewWidth", "I", i32::MAX).await?;
+        jvm.put_field(&mut this, "viewHeight", "I", i32::MAX).await?;
+
+        Ok(())
+    }
+
+    // a layer already in the manager is moved, not added twice
+    async fn append(jvm: &Jvm, _: &mut MIDPJavaContext, mut this: ClassInstanceRef<Self>, layer: ClassInstanceRef<Layer>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.game.LayerManager::append({:?}, {:?})", &this, &layer);
+
+        let mut layers = Self::layers_without(jvm, &this, &layer).await?;
+        layers.push(layer);
+
+        Self::store_layers(jvm, &mut this, layers).await
+    }
+
+    async fn insert(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        layer: ClassInstanceRef<Layer>,
+        index: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.game.LayerManager::insert({:?}, {:?}, {})", &this, &layer, index);
+
+        let mut layers = Self::layers_without(jvm, &this, &layer).await?;
+        if index < 0 || index as usize > layers.len() {
+            tracing::warn!("Layer index {} is out of {} layers", index, layers.len());
+
+            return Ok(());
+        }
+        layers.insert(index as _, layer);
+
+        Self::store_layers(jvm, &mut this, layers).await
+    }
+
+    async fn remove(jvm: &Jvm, _: &mut MIDPJavaContext, mut this: ClassInstanceRef<Self>, layer: ClassInstanceRef<Layer>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.game.LayerManager::remove({:?}, {:?})", &this, &layer);
+
+        let layers = Self::layers_without(jvm, &this, &layer).await?;
+
+        Self::store_layers(jvm, &mut this, layers).await
+    }
+
+    async fn get_layer_at(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, index: i32) -> JvmResult<ClassInstanceRef<Layer>> {
+        tracing::debug!("javax.microedition.lcdui.game.LayerManager::getLayerAt({:?}, {})", &this, index);
+
+        let layers = Self::layers(jvm, &this).await?;
+
+        Ok(match usize::try_from(index).ok().and_then(|x| layers.get(x)) {
+            Some(x) => x.clone(),
+            None => {
+                tracing::warn!("Layer index {} is out of {} layers", index, layers.len());
+
+                None.into()
+            }
+        })
+    }
+
+    async fn get_size(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.game.LayerManager::getSize({:?})", &this);
+
+        Ok(Self::layers(jvm, &this).await?.len() as _)
+    }
+
+    async fn set_view_window(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.game.LayerManager::setViewWindow({:?}, {}, {}, {}, {})",
+            &this,
+            x,
+            y,
+            width,
+            height
+        );
+
+        jvm.put_field(&mut this, "viewX", "I", x).await?;
+        jvm.put_field(&mut this, "viewY", "I", y).await?;
+        jvm.put_field(&mut this, "viewWidth", "I", width).await?;
+        jvm.put_field(&mut this, "viewHeight", "I", height).await?;
+
+        Ok(())
+    }
+
+    // TODO translate and clip to the view window, we don't have midp graphics yet
+    async fn paint(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        this: ClassInstanceRef<Self>,
+        graphics: ClassInstanceRef<Object>,
+        x: i32,
+        y: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.game.LayerManager::paint({:?}, {:?}, {}, {})",
+            &this,
+            &graphics,
+            x,
+            y
+        );
+
+        // bottom layer is painted first
+        for layer in Self::layers(jvm, &this).await?.into_iter().rev() {
+            let visible: bool = jvm.get_field(&layer, "visible", "Z").await?;
+            if visible {
+                jvm.invoke_virtual(&layer, "paint", "(Ljavax/microedition/lcdui/Graphics;)V", (graphics.clone(),))
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn layers(jvm: &Jvm, this: &ClassInstanceRef<Self>) -> JvmResult<Vec<ClassInstanceRef<Layer>>> {
+        let layers = jvm.get_field(this, "layers", "[Ljavax/microedition/lcdui/game/Layer;").await?;
+        let length = jvm.array_length(&layers).await?;
+
+        jvm.load_array(&layers, 0, length).await
+    }
+
+    async fn layers_without(jvm: &Jvm, this: &ClassInstanceRef<Self>, layer: &ClassInstanceRef<Layer>) -> JvmResult<Vec<ClassInstanceRef<Layer>>> {
+        let mut result = Vec::new();
+        for x in Self::layers(jvm, this).await? {
+            let same: bool = jvm.invoke_virtual(&x, "equals", "(Ljava/lang/Object;)Z", (layer.clone(),)).await?;
+            if !same {
+                result.push(x);
+            }
+        }
+
+        Ok(result)
+    }
+
+    // array is recreated on every change, layer managers hold only a handful of layers
+    async fn store_layers(jvm: &Jvm, this: &mut ClassInstanceRef<Self>, layers: Vec<ClassInstanceRef<Layer>>) -> JvmResult<()> {
+        let mut array = jvm.instantiate_array("Ljavax/microedition/lcdui/game/Layer;", layers.len()).await?.into();
+        jvm.store_array(&mut array, 0, layers).await?;
+
+        jvm.put_field(this, "layers", "[Ljavax/microedition/lcdui/game/Layer;", array).await
+    }
+}
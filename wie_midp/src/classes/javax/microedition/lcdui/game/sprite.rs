@@ -0,0 +1,284 @@
+use alloc::{vec, vec::Vec};
+
+use java_class_proto::{JavaFieldProto, JavaMethodProto};
+use java_runtime::classes::java::lang::Object;
+use jvm::{Array, ClassInstanceRef, Jvm, Result as JvmResult};
+
+use crate::context::{MIDPJavaClassProto, MIDPJavaContext};
+
+// class javax.microedition.lcdui.game.Sprite
+pub struct Sprite {}
+
+impl Sprite {
+    pub fn as_proto() -> MIDPJavaClassProto {
+        MIDPJavaClassProto {
+            parent_class: Some("javax/microedition/lcdui/game/Layer"),
+            interfaces: vec![],
+            methods: vec![
+                JavaMethodProto::new("<init>", "(Ljavax/microedition/lcdui/Image;)V", Self::init, Default::default()),
+                JavaMethodProto::new(
+                    "<init>",
+                    "(Ljavax/microedition/lcdui/Image;II)V",
+                    Self::init_with_frame,
+                    Default::default(),
+                ),
+                JavaMethodProto::new("setFrameSequence", "([I)V", Self::set_frame_sequence, Default::default()),
+                JavaMethodProto::new("getFrameSequenceLength", "()I", Self::get_frame_sequence_length, Default::default()),
+                JavaMethodProto::new("getRawFrameCount", "()I", Self::get_raw_frame_count, Default::default()),
+                JavaMethodProto::new("getFrame", "()I", Self::get_frame, Default::default()),
+                JavaMethodProto::new("setFrame", "(I)V", Self::set_frame, Default::default()),
+                JavaMethodProto::new("nextFrame", "()V", Self::next_frame, Default::default()),
+                JavaMethodProto::new("prevFrame", "()V", Self::prev_frame, Default::default()),
+                JavaMethodProto::new(
+                    "defineCollisionRectangle",
+                    "(IIII)V",
+                    Self::define_collision_rectangle,
+                    Default::default(),
+                ),
+                JavaMethodProto::new(
+                    "collidesWith",
+                    "(Ljavax/microedition/lcdui/game/Sprite;Z)Z",
+                    Self::collides_with,
+                    Default::default(),
+                ),
+                JavaMethodProto::new("paint", "(Ljavax/microedition/lcdui/Graphics;)V", Self::paint, Default::default()),
+            ],
+            fields: vec![
+                JavaFieldProto::new("image", "Ljavax/microedition/lcdui/Image;", Default::default()),
+                JavaFieldProto::new("rawFrameCount", "I", Default::default()),
+                JavaFieldProto::new("frameSequence", "[I", Default::default()),
+                JavaFieldProto::new("sequenceIndex", "I", Default::default()),
+                JavaFieldProto::new("collisionX", "I", Default::default()),
+                JavaFieldProto::new("collisionY", "I", Default::default()),
+                JavaFieldProto::new("collisionWidth", "I", Default::default()),
+                JavaFieldProto::new("collisionHeight", "I", Default::default()),
+            ],
+        }
+    }
+
+    async fn init(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, image: ClassInstanceRef<Object>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.game.Sprite::<init>({:?}, {:?})", &this, &image);
+
+        let width: i32 = jvm.invoke_virtual(&image, "getWidth", "()I", ()).await?;
+        let height: i32 = jvm.invoke_virtual(&image, "getHeight", "()I", ()).await?;
+
+        jvm.invoke_special(
+            &this,
+            "javax/microedition/lcdui/game/Sprite",
+            "<init>",
+            "(Ljavax/microedition/lcdui/Image;II)V",
+            (image, width, height),
+        )
+        .await
+    }
+
+    // frames are laid out from left to right, then top to bottom in the image
+    async fn init_with_frame(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        image: ClassInstanceRef<Object>,
+        frame_width: i32,
+        frame_height: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.game.Sprite::<init>({:?}, {:?}, {}, {})",
+            &this,
+            &image,
+            frame_width,
+            frame_height
+        );
+
+        jvm.invoke_special(
+            &this,
+            "javax/microedition/lcdui/game/Layer",
+            "<init>",
+            "(II)V",
+            (frame_width, frame_height),
+        )
+        .await?;
+
+        let image_width: i32 = jvm.invoke_virtual(&image, "getWidth", "()I", ()).await?;
+        let image_height: i32 = jvm.invoke_virtual(&image, "getHeight", "()I", ()).await?;
+        let raw_frame_count = if frame_width > 0 && frame_height > 0 {
+            (image_width / frame_width) * (image_height / frame_height)
+        } else {
+            0
+        };
+
+        jvm.put_field(&mut this, "image", "Ljavax/microedition/lcdui/Image;", image).await?;
+        jvm.put_field(&mut this, "rawFrameCount", "I", raw_frame_count).await?;
+
+        let sequence = Self::default_sequence(jvm, raw_frame_count).await?;
+        jvm.put_field(&mut this, "frameSequence", "[I", sequence).await?;
+        jvm.put_field(&mut this, "sequenceIndex", "I", 0).await?;
+
+        jvm.put_field(&mut this, "collisionX", "I", 0).await?;
+        jvm.put_field(&mut this, "collisionY", "I", 0).await?;
+        jvm.put_field(&mut this, "collisionWidth", "I", frame_width).await?;
+        jvm.put_field(&mut this, "collisionHeight", "I", frame_height).await?;
+
+        Ok(())
+    }
+
+    // null resets to the default sequence, which shows every raw frame in order
+    async fn set_frame_sequence(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        sequence: ClassInstanceRef<Array<i32>>,
+    ) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.game.Sprite::setFrameSequence({:?}, {:?})", &this, &sequence);
+
+        let sequence = if sequence.is_null() {
+            let raw_frame_count: i32 = jvm.get_field(&this, "rawFrameCount", "I").await?;
+
+            Self::default_sequence(jvm, raw_frame_count).await?
+        } else {
+            sequence
+        };
+
+        jvm.put_field(&mut this, "frameSequence", "[I", sequence).await?;
+        jvm.put_field(&mut this, "sequenceIndex", "I", 0).await?;
+
+        Ok(())
+    }
+
+    async fn get_frame_sequence_length(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.game.Sprite::getFrameSequenceLength({:?})", &this);
+
+        Ok(Self::sequence_length(jvm, &this).await? as _)
+    }
+
+    async fn get_raw_frame_count(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.game.Sprite::getRawFrameCount({:?})", &this);
+
+        jvm.get_field(&this, "rawFrameCount", "I").await
+    }
+
+    // index in the frame sequence, not the raw frame number
+    async fn get_frame(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.game.Sprite::getFrame({:?})", &this);
+
+        jvm.get_field(&this, "sequenceIndex", "I").await
+    }
+
+    async fn set_frame(jvm: &Jvm, _: &mut MIDPJavaContext, mut this: ClassInstanceRef<Self>, sequence_index: i32) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.game.Sprite::setFrame({:?}, {})", &this, sequence_index);
+
+        let length = Self::sequence_length(jvm, &this).await?;
+        if sequence_index < 0 || sequence_index as usize >= length {
+            tracing::warn!("Frame index {} is out of the sequence of {} frames", sequence_index, length);
+
+            return Ok(());
+        }
+
+        jvm.put_field(&mut this, "sequenceIndex", "I", sequence_index).await
+    }
+
+    async fn next_frame(jvm: &Jvm, _: &mut MIDPJavaContext, mut this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.game.Sprite::nextFrame({:?})", &this);
+
+        let length = Self::sequence_length(jvm, &this).await? as i32;
+        let index: i32 = jvm.get_field(&this, "sequenceIndex", "I").await?;
+
+        jvm.put_field(&mut this, "sequenceIndex", "I", (index + 1) % length.max(1)).await
+    }
+
+    async fn prev_frame(jvm: &Jvm, _: &mut MIDPJavaContext, mut this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.game.Sprite::prevFrame({:?})", &this);
+
+        let length = Self::sequence_length(jvm, &this).await? as i32;
+        let index: i32 = jvm.get_field(&this, "sequenceIndex", "I").await?;
+
+        jvm.put_field(&mut this, "sequenceIndex", "I", (index - 1).rem_euclid(length.max(1)))
+            .await
+    }
+
+    async fn define_collision_rectangle(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.game.Sprite::defineCollisionRectangle({:?}, {}, {}, {}, {})",
+            &this,
+            x,
+            y,
+            width,
+            height
+        );
+
+        jvm.put_field(&mut this, "collisionX", "I", x).await?;
+        jvm.put_field(&mut this, "collisionY", "I", y).await?;
+        jvm.put_field(&mut this, "collisionWidth", "I", width).await?;
+        jvm.put_field(&mut this, "collisionHeight", "I", height).await?;
+
+        Ok(())
+    }
+
+    // TODO pixel level collision needs pixels of the image, so collision rectangles are compared for now
+    async fn collides_with(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        this: ClassInstanceRef<Self>,
+        other: ClassInstanceRef<Self>,
+        pixel_level: bool,
+    ) -> JvmResult<bool> {
+        tracing::debug!(
+            "javax.microedition.lcdui.game.Sprite::collidesWith({:?}, {:?}, {})",
+            &this,
+            &other,
+            pixel_level
+        );
+
+        let this_visible: bool = jvm.get_field(&this, "visible", "Z").await?;
+        let other_visible: bool = jvm.get_field(&other, "visible", "Z").await?;
+        if !this_visible || !other_visible {
+            return Ok(false);
+        }
+
+        let (x1, y1, width1, height1) = Self::collision_rectangle(jvm, &this).await?;
+        let (x2, y2, width2, height2) = Self::collision_rectangle(jvm, &other).await?;
+
+        Ok(x1 < x2 + width2 && x2 < x1 + width1 && y1 < y2 + height2 && y2 < y1 + height1)
+    }
+
+    async fn paint(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, graphics: ClassInstanceRef<Object>) -> JvmResult<()> {
+        tracing::warn!("stub javax.microedition.lcdui.game.Sprite::paint({:?}, {:?})", &this, &graphics);
+
+        Ok(())
+    }
+
+    async fn default_sequence(jvm: &Jvm, raw_frame_count: i32) -> JvmResult<ClassInstanceRef<Array<i32>>> {
+        let mut sequence: ClassInstanceRef<Array<i32>> = jvm.instantiate_array("I", raw_frame_count.max(0) as _).await?.into();
+        jvm.store_array(&mut sequence, 0, (0..raw_frame_count).collect::<Vec<_>>()).await?;
+
+        Ok(sequence)
+    }
+
+    async fn sequence_length(jvm: &Jvm, this: &ClassInstanceRef<Self>) -> JvmResult<usize> {
+        let sequence: ClassInstanceRef<Array<i32>> = jvm.get_field(this, "frameSequence", "[I").await?;
+
+        jvm.array_length(&sequence).await
+    }
+
+    // in the coordinates of the layer manager
+    async fn collision_rectangle(jvm: &Jvm, this: &ClassInstanceRef<Self>) -> JvmResult<(i32, i32, i32, i32)> {
+        let x: i32 = jvm.get_field(this, "x", "I").await?;
+        let y: i32 = jvm.get_field(this, "y", "I").await?;
+        let collision_x: i32 = jvm.get_field(this, "collisionX", "I").await?;
+        let collision_y: i32 = jvm.get_field(this, "collisionY", "I").await?;
+
+        Ok((
+            x + collision_x,
+            y + collision_y,
+            jvm.get_field(this, "collisionWidth", "I").await?,
+            jvm.get_field(this, "collisionHeight", "I").await?,
+        ))
+    }
+}
@@ -0,0 +1,186 @@
+use alloc::vec;
+
+use java_class_proto::{JavaFieldProto, JavaMethodProto};
+use java_runtime::classes::java::lang::Object;
+use jvm::{Array, ClassInstanceRef, Jvm, Result as JvmResult};
+
+use crate::context::{MIDPJavaClassProto, MIDPJavaContext};
+
+// class javax.microedition.lcdui.game.TiledLayer
+pub struct TiledLayer {}
+
+impl TiledLayer {
+    pub fn as_proto() -> MIDPJavaClassProto {
+        MIDPJavaClassProto {
+            parent_class: Some("javax/microedition/lcdui/game/Layer"),
+            interfaces: vec![],
+            methods: vec![
+                JavaMethodProto::new("<init>", "(IILjavax/microedition/lcdui/Image;II)V", Self::init, Default::default()),
+                JavaMethodProto::new("getColumns", "()I", Self::get_columns, Default::default()),
+                JavaMethodProto::new("getRows", "()I", Self::get_rows, Default::default()),
+                JavaMethodProto::new("getCellWidth", "()I", Self::get_cell_width, Default::default()),
+                JavaMethodProto::new("getCellHeight", "()I", Self::get_cell_height, Default::default()),
+                JavaMethodProto::new("setCell", "(III)V", Self::set_cell, Default::default()),
+                JavaMethodProto::new("getCell", "(II)I", Self::get_cell, Default::default()),
+                JavaMethodProto::new("fillCells", "(IIIII)V", Self::fill_cells, Default::default()),
+                JavaMethodProto::new("paint", "(Ljavax/microedition/lcdui/Graphics;)V", Self::paint, Default::default()),
+            ],
+            fields: vec![
+                JavaFieldProto::new("image", "Ljavax/microedition/lcdui/Image;", Default::default()),
+                JavaFieldProto::new("columns", "I", Default::default()),
+                JavaFieldProto::new("rows", "I", Default::default()),
+                JavaFieldProto::new("cellWidth", "I", Default::default()),
+                JavaFieldProto::new("cellHeight", "I", Default::default()),
+                // row major, 0 is empty, positive is a static tile and negative is an animated tile
+                JavaFieldProto::new("cells", "[I", Default::default()),
+            ],
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn init(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        columns: i32,
+        rows: i32,
+        image: ClassInstanceRef<Object>,
+        cell_width: i32,
+        cell_height: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.game.TiledLayer::<init>({:?}, {}, {}, {:?}, {}, {})",
+            &this,
+            columns,
+            rows,
+            &image,
+            cell_width,
+            cell_height
+        );
+
+        jvm.invoke_special(
+            &this,
+            "javax/microedition/lcdui/game/Layer",
+            "<init>",
+            "(II)V",
+            (columns * cell_width, rows * cell_height),
+        )
+        .await?;
+
+        let cells = jvm.instantiate_array("I", (columns * rows).max(0) as _).await?;
+
+        jvm.put_field(&mut this, "image", "Ljavax/microedition/lcdui/Image;", image).await?;
+        jvm.put_field(&mut this, "columns", "I", columns).await?;
+        jvm.put_field(&mut this, "rows", "I", rows).await?;
+        jvm.put_field(&mut this, "cellWidth", "I", cell_width).await?;
+        jvm.put_field(&mut this, "cellHeight", "I", cell_height).await?;
+        jvm.put_field(&mut this, "cells", "[I", cells).await?;
+
+        Ok(())
+    }
+
+    async fn get_columns(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.game.TiledLayer::getColumns({:?})", &this);
+
+        jvm.get_field(&this, "columns", "I").await
+    }
+
+    async fn get_rows(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.game.TiledLayer::getRows({:?})", &this);
+
+        jvm.get_field(&this, "rows", "I").await
+    }
+
+    async fn get_cell_width(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.game.TiledLayer::getCellWidth({:?})", &this);
+
+        jvm.get_field(&this, "cellWidth", "I").await
+    }
+
+    async fn get_cell_height(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.game.TiledLayer::getCellHeight({:?})", &this);
+
+        jvm.get_field(&this, "cellHeight", "I").await
+    }
+
+    async fn set_cell(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, col: i32, row: i32, tile_index: i32) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.game.TiledLayer::setCell({:?}, {}, {}, {})",
+            &this,
+            col,
+            row,
+            tile_index
+        );
+
+        jvm.invoke_virtual(&this, "fillCells", "(IIIII)V", (col, row, 1, 1, tile_index)).await
+    }
+
+    async fn get_cell(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, col: i32, row: i32) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.game.TiledLayer::getCell({:?}, {}, {})", &this, col, row);
+
+        let index = match Self::cell_index(jvm, &this, col, row).await? {
+            Some(x) => x,
+            None => {
+                tracing::warn!("Cell ({}, {}) is out of the layer", col, row);
+
+                return Ok(0);
+            }
+        };
+
+        let cells: ClassInstanceRef<Array<i32>> = jvm.get_field(&this, "cells", "[I").await?;
+
+        Ok(jvm.load_array(&cells, index, 1).await?[0])
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn fill_cells(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        this: ClassInstanceRef<Self>,
+        col: i32,
+        row: i32,
+        num_cols: i32,
+        num_rows: i32,
+        tile_index: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.game.TiledLayer::fillCells({:?}, {}, {}, {}, {}, {})",
+            &this,
+            col,
+            row,
+            num_cols,
+            num_rows,
+            tile_index
+        );
+
+        let mut cells: ClassInstanceRef<Array<i32>> = jvm.get_field(&this, "cells", "[I").await?;
+
+        for y in row..row + num_rows {
+            for x in col..col + num_cols {
+                match Self::cell_index(jvm, &this, x, y).await? {
+                    Some(index) => jvm.store_array(&mut cells, index, vec![tile_index]).await?,
+                    None => tracing::warn!("Cell ({}, {}) is out of the layer", x, y),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn paint(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, graphics: ClassInstanceRef<Object>) -> JvmResult<()> {
+        tracing::warn!("stub javax.microedition.lcdui.game.TiledLayer::paint({:?}, {:?})", &this, &graphics);
+
+        Ok(())
+    }
+
+    async fn cell_index(jvm: &Jvm, this: &ClassInstanceRef<Self>, col: i32, row: i32) -> JvmResult<Option<usize>> {
+        let columns: i32 = jvm.get_field(this, "columns", "I").await?;
+        let rows: i32 = jvm.get_field(this, "rows", "I").await?;
+
+        if col < 0 || row < 0 || col >= columns || row >= rows {
+            return Ok(None);
+        }
+
+        Ok(Some((row * columns + col) as _))
+    }
+}
@@ -0,0 +1,424 @@
+use alloc::vec;
+
+use java_class_proto::{JavaFieldProto, JavaMethodProto};
+use java_runtime::classes::java::lang::String;
+use jvm::{runtime::JavaLangString, ClassInstanceRef, Jvm, Result as JvmResult};
+
+use wie_backend::canvas::{Anchor, Font, PixelType, Rgb8Pixel, Transform};
+
+use crate::{
+    classes::javax::microedition::lcdui::Image,
+    context::{MIDPJavaClassProto, MIDPJavaContext},
+};
+
+// class javax.microedition.lcdui.Graphics
+pub struct Graphics {}
+
+impl Graphics {
+    pub fn as_proto() -> MIDPJavaClassProto {
+        MIDPJavaClassProto {
+            parent_class: Some("java/lang/Object"),
+            interfaces: vec![],
+            methods: vec![
+                JavaMethodProto::new("<init>", "(Ljavax/microedition/lcdui/Image;)V", Self::init, Default::default()),
+                JavaMethodProto::new("setColor", "(I)V", Self::set_color, Default::default()),
+                JavaMethodProto::new("setColor", "(III)V", Self::set_color_by_rgb, Default::default()),
+                JavaMethodProto::new("getColor", "()I", Self::get_color, Default::default()),
+                JavaMethodProto::new("fillRect", "(IIII)V", Self::fill_rect, Default::default()),
+                JavaMethodProto::new("drawRect", "(IIII)V", Self::draw_rect, Default::default()),
+                JavaMethodProto::new("drawLine", "(IIII)V", Self::draw_line, Default::default()),
+                JavaMethodProto::new("drawString", "(Ljava/lang/String;III)V", Self::draw_string, Default::default()),
+                JavaMethodProto::new(
+                    "drawImage",
+                    "(Ljavax/microedition/lcdui/Image;III)V",
+                    Self::draw_image,
+                    Default::default(),
+                ),
+                JavaMethodProto::new(
+                    "drawRegion",
+                    "(Ljavax/microedition/lcdui/Image;IIIIIIII)V",
+                    Self::draw_region,
+                    Default::default(),
+                ),
+                JavaMethodProto::new("translate", "(II)V", Self::translate, Default::default()),
+                JavaMethodProto::new("getTranslateX", "()I", Self::get_translate_x, Default::default()),
+                JavaMethodProto::new("getTranslateY", "()I", Self::get_translate_y, Default::default()),
+                JavaMethodProto::new("setClip", "(IIII)V", Self::set_clip, Default::default()),
+                JavaMethodProto::new("clipRect", "(IIII)V", Self::clip_rect, Default::default()),
+                JavaMethodProto::new("getClipX", "()I", Self::get_clip_x, Default::default()),
+                JavaMethodProto::new("getClipY", "()I", Self::get_clip_y, Default::default()),
+                JavaMethodProto::new("getClipWidth", "()I", Self::get_clip_width, Default::default()),
+                JavaMethodProto::new("getClipHeight", "()I", Self::get_clip_height, Default::default()),
+            ],
+            fields: vec![
+                JavaFieldProto::new("img", "Ljavax/microedition/lcdui/Image;", Default::default()),
+                JavaFieldProto::new("rgb", "I", Default::default()),
+                JavaFieldProto::new("translateX", "I", Default::default()),
+                JavaFieldProto::new("translateY", "I", Default::default()),
+            ],
+        }
+    }
+
+    async fn init(jvm: &Jvm, _: &mut MIDPJavaContext, mut this: ClassInstanceRef<Self>, image: ClassInstanceRef<Image>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Graphics::<init>({:?}, {:?})", &this, &image);
+
+        jvm.put_field(&mut this, "img", "Ljavax/microedition/lcdui/Image;", image).await?;
+
+        Ok(())
+    }
+
+    async fn set_color(jvm: &Jvm, _: &mut MIDPJavaContext, mut this: ClassInstanceRef<Self>, rgb: i32) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Graphics::setColor({:?}, {})", &this, rgb);
+
+        jvm.put_field(&mut this, "rgb", "I", rgb & 0xffffff).await?;
+
+        Ok(())
+    }
+
+    async fn set_color_by_rgb(jvm: &Jvm, _: &mut MIDPJavaContext, mut this: ClassInstanceRef<Self>, r: i32, g: i32, b: i32) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Graphics::setColor({:?}, {}, {}, {})", &this, r, g, b);
+
+        let rgb = ((r & 0xff) << 16) | ((g & 0xff) << 8) | (b & 0xff);
+
+        jvm.put_field(&mut this, "rgb", "I", rgb).await?;
+
+        Ok(())
+    }
+
+    async fn get_color(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.Graphics::getColor({:?})", &this);
+
+        jvm.get_field(&this, "rgb", "I").await
+    }
+
+    async fn fill_rect(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, x: i32, y: i32, width: i32, height: i32) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.Graphics::fillRect({:?}, {}, {}, {}, {})",
+            &this,
+            x,
+            y,
+            width,
+            height
+        );
+
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
+        let (x, y, width, height) = match Self::clamp_rect(x, y, width, height) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        let image = Self::image(jvm, &this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+
+        canvas.fill_rect(x, y, width, height, Rgb8Pixel::to_color(rgb as _));
+
+        canvas.flush().await;
+
+        Ok(())
+    }
+
+    async fn draw_rect(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, x: i32, y: i32, width: i32, height: i32) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.Graphics::drawRect({:?}, {}, {}, {}, {})",
+            &this,
+            x,
+            y,
+            width,
+            height
+        );
+
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
+        let (x, y, width, height) = match Self::clamp_rect(x, y, width, height) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        let image = Self::image(jvm, &this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+
+        canvas.draw_rect(x, y, width, height, Rgb8Pixel::to_color(rgb as _));
+
+        canvas.flush().await;
+
+        Ok(())
+    }
+
+    async fn draw_line(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, x1: i32, y1: i32, x2: i32, y2: i32) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Graphics::drawLine({:?}, {}, {}, {}, {})", &this, x1, y1, x2, y2);
+
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+        let (x1, y1) = Self::translated(jvm, &this, x1, y1).await?;
+        let (x2, y2) = Self::translated(jvm, &this, x2, y2).await?;
+
+        let image = Self::image(jvm, &this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+
+        canvas.draw_line(
+            x1.max(0) as _,
+            y1.max(0) as _,
+            x2.max(0) as _,
+            y2.max(0) as _,
+            Rgb8Pixel::to_color(rgb as _),
+        );
+
+        canvas.flush().await;
+
+        Ok(())
+    }
+
+    async fn draw_string(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        this: ClassInstanceRef<Self>,
+        string: ClassInstanceRef<String>,
+        x: i32,
+        y: i32,
+        anchor: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.Graphics::drawString({:?}, {:?}, {}, {}, {})",
+            &this,
+            &string,
+            x,
+            y,
+            anchor
+        );
+        let anchor = Anchor::from_bits_retain(anchor);
+
+        let rust_string = JavaLangString::to_rust_string(jvm, &string).await?;
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+        // TODO javax.microedition.lcdui.Font, the default font is used until we have it
+        let font = Font::default();
+
+        let image = Self::image(jvm, &this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+
+        let (alignment, y) = anchor.text_position(y, &font);
+
+        canvas.draw_text(
+            &rust_string,
+            x.max(0) as _,
+            y.max(0) as _,
+            &font,
+            Rgb8Pixel::to_color(rgb as _),
+            alignment,
+        );
+
+        canvas.flush().await;
+
+        Ok(())
+    }
+
+    async fn draw_image(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        this: ClassInstanceRef<Self>,
+        img: ClassInstanceRef<Image>,
+        x: i32,
+        y: i32,
+        anchor: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.Graphics::drawImage({:?}, {:?}, {}, {}, {})",
+            &this,
+            &img,
+            x,
+            y,
+            anchor
+        );
+        let anchor = Anchor::from_bits_retain(anchor);
+
+        let src_image = Image::image(jvm, &img).await?;
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
+
+        let (x_delta, y_delta) = anchor.offset(src_image.width(), src_image.height());
+
+        let (x, y) = (x + x_delta, y + y_delta);
+        let (dx, dy, width, height) = match Self::clamp_rect(x, y, src_image.width() as _, src_image.height() as _) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        let image = Self::image(jvm, &this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+
+        // part of the source left or above the destination is skipped
+        canvas.draw(dx, dy, width, height, &*src_image, (dx as i32 - x) as _, (dy as i32 - y) as _);
+
+        canvas.flush().await;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn draw_region(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        this: ClassInstanceRef<Self>,
+        src: ClassInstanceRef<Image>,
+        x_src: i32,
+        y_src: i32,
+        width: i32,
+        height: i32,
+        transform: i32,
+        x_dest: i32,
+        y_dest: i32,
+        anchor: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.lcdui.Graphics::drawRegion({:?}, {:?}, {}, {}, {}, {}, {}, {}, {}, {})",
+            &this,
+            &src,
+            x_src,
+            y_src,
+            width,
+            height,
+            transform,
+            x_dest,
+            y_dest,
+            anchor
+        );
+        let anchor = Anchor::from_bits_retain(anchor);
+
+        if x_src < 0 || y_src < 0 || width <= 0 || height <= 0 {
+            return Ok(());
+        }
+
+        let transform = match Transform::from_sprite_transform(transform) {
+            Some(x) => x,
+            None => {
+                tracing::warn!("Invalid transform {}", transform);
+
+                return Ok(());
+            }
+        };
+
+        let src_image = Image::image(jvm, &src).await?;
+        let (x, y) = Self::translated(jvm, &this, x_dest, y_dest).await?;
+        let (transformed_width, transformed_height) = transform.transformed_size(width as _, height as _);
+
+        let (x_delta, y_delta) = anchor.offset(transformed_width, transformed_height);
+
+        let image = Self::image(jvm, &this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+
+        canvas.draw_region(
+            x + x_delta,
+            y + y_delta,
+            &*src_image,
+            x_src as _,
+            y_src as _,
+            width as _,
+            height as _,
+            transform,
+        );
+
+        canvas.flush().await;
+
+        Ok(())
+    }
+
+    async fn translate(jvm: &Jvm, _: &mut MIDPJavaContext, mut this: ClassInstanceRef<Self>, x: i32, y: i32) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Graphics::translate({:?}, {}, {})", &this, x, y);
+
+        let translate_x: i32 = jvm.get_field(&this, "translateX", "I").await?;
+        let translate_y: i32 = jvm.get_field(&this, "translateY", "I").await?;
+
+        jvm.put_field(&mut this, "translateX", "I", translate_x + x).await?;
+        jvm.put_field(&mut this, "translateY", "I", translate_y + y).await?;
+
+        Ok(())
+    }
+
+    async fn get_translate_x(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.Graphics::getTranslateX({:?})", &this);
+
+        jvm.get_field(&this, "translateX", "I").await
+    }
+
+    async fn get_translate_y(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.Graphics::getTranslateY({:?})", &this);
+
+        jvm.get_field(&this, "translateY", "I").await
+    }
+
+    async fn set_clip(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, x: i32, y: i32, width: i32, height: i32) -> JvmResult<()> {
+        tracing::warn!(
+            "stub javax.microedition.lcdui.Graphics::setClip({:?}, {}, {}, {}, {})",
+            &this,
+            x,
+            y,
+            width,
+            height
+        );
+
+        Ok(())
+    }
+
+    async fn clip_rect(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, x: i32, y: i32, width: i32, height: i32) -> JvmResult<()> {
+        tracing::warn!(
+            "stub javax.microedition.lcdui.Graphics::clipRect({:?}, {}, {}, {}, {})",
+            &this,
+            x,
+            y,
+            width,
+            height
+        );
+
+        Ok(())
+    }
+
+    async fn get_clip_x(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::warn!("stub javax.microedition.lcdui.Graphics::getClipX({:?})", &this);
+
+        Ok(0)
+    }
+
+    async fn get_clip_y(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::warn!("stub javax.microedition.lcdui.Graphics::getClipY({:?})", &this);
+
+        Ok(0)
+    }
+
+    async fn get_clip_width(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::warn!("stub javax.microedition.lcdui.Graphics::getClipWidth({:?})", &this);
+
+        let image = Self::image(jvm, &this).await?;
+
+        jvm.get_field(&image, "w", "I").await
+    }
+
+    async fn get_clip_height(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::warn!("stub javax.microedition.lcdui.Graphics::getClipHeight({:?})", &this);
+
+        let image = Self::image(jvm, &this).await?;
+
+        jvm.get_field(&image, "h", "I").await
+    }
+
+    async fn image(jvm: &Jvm, this: &ClassInstanceRef<Self>) -> JvmResult<ClassInstanceRef<Image>> {
+        jvm.get_field(this, "img", "Ljavax/microedition/lcdui/Image;").await
+    }
+
+    async fn translated(jvm: &Jvm, this: &ClassInstanceRef<Self>, x: i32, y: i32) -> JvmResult<(i32, i32)> {
+        let translate_x: i32 = jvm.get_field(this, "translateX", "I").await?;
+        let translate_y: i32 = jvm.get_field(this, "translateY", "I").await?;
+
+        Ok((x + translate_x, y + translate_y))
+    }
+
+    // canvas takes unsigned coordinates, so the part left or above the image is cut off
+    fn clamp_rect(x: i32, y: i32, width: i32, height: i32) -> Option<(u32, u32, u32, u32)> {
+        let (left, top) = (x.max(0), y.max(0));
+        let (right, bottom) = (x + width, y + height);
+
+        if right <= left || bottom <= top {
+            return None;
+        }
+
+        Some((left as _, top as _, (right - left) as _, (bottom - top) as _))
+    }
+}
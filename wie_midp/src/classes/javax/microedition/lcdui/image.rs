@@ -0,0 +1,258 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::{
+    cell::Ref,
+    ops::{Deref, DerefMut},
+};
+
+use bytemuck::cast_vec;
+
+use java_class_proto::{JavaFieldProto, JavaMethodProto};
+use java_constants::MethodAccessFlags;
+use java_runtime::classes::java::lang::String;
+use jvm::{runtime::JavaLangString, Array, ClassInstanceRef, JavaError, Jvm, Result as JvmResult};
+
+use wie_backend::canvas::{byte_image, decode_image, ByteImageCanvas, Canvas, Image as BackendImage};
+
+use crate::{
+    classes::javax::microedition::lcdui::Graphics,
+    context::{MIDPJavaClassProto, MIDPJavaContext},
+    java_exception,
+};
+
+// class javax.microedition.lcdui.Image
+pub struct Image {}
+
+impl Image {
+    pub fn as_proto() -> MIDPJavaClassProto {
+        MIDPJavaClassProto {
+            parent_class: Some("java/lang/Object"),
+            interfaces: vec![],
+            methods: vec![
+                JavaMethodProto::new("<init>", "()V", Self::init, Default::default()),
+                JavaMethodProto::new(
+                    "createImage",
+                    "(II)Ljavax/microedition/lcdui/Image;",
+                    Self::create_image,
+                    MethodAccessFlags::STATIC,
+                ),
+                JavaMethodProto::new(
+                    "createImage",
+                    "(Ljava/lang/String;)Ljavax/microedition/lcdui/Image;",
+                    Self::create_image_from_file,
+                    MethodAccessFlags::STATIC,
+                ),
+                JavaMethodProto::new(
+                    "createImage",
+                    "([BII)Ljavax/microedition/lcdui/Image;",
+                    Self::create_image_from_bytes,
+                    MethodAccessFlags::STATIC,
+                ),
+                JavaMethodProto::new(
+                    "getGraphics",
+                    "()Ljavax/microedition/lcdui/Graphics;",
+                    Self::get_graphics,
+                    Default::default(),
+                ),
+                JavaMethodProto::new("getWidth", "()I", Self::get_width, Default::default()),
+                JavaMethodProto::new("getHeight", "()I", Self::get_height, Default::default()),
+                JavaMethodProto::new("isMutable", "()Z", Self::is_mutable, Default::default()),
+            ],
+            fields: vec![
+                JavaFieldProto::new("w", "I", Default::default()),
+                JavaFieldProto::new("h", "I", Default::default()),
+                // always argb, decoded images come in argb as well
+                JavaFieldProto::new("imgData", "[B", Default::default()),
+                JavaFieldProto::new("mutable", "Z", Default::default()),
+            ],
+        }
+    }
+
+    async fn init(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.lcdui.Image::<init>({:?})", &this);
+
+        Ok(())
+    }
+
+    // mutable images start out white
+    async fn create_image(jvm: &Jvm, _: &mut MIDPJavaContext, width: i32, height: i32) -> JvmResult<ClassInstanceRef<Self>> {
+        tracing::debug!("javax.microedition.lcdui.Image::createImage({}, {})", width, height);
+
+        let data = vec![0xff; (width * height * 4) as usize];
+
+        Self::create_image_instance(jvm, width as _, height as _, &data, true).await
+    }
+
+    async fn create_image_from_file(jvm: &Jvm, context: &mut MIDPJavaContext, name: ClassInstanceRef<String>) -> JvmResult<ClassInstanceRef<Self>> {
+        tracing::debug!("javax.microedition.lcdui.Image::createImage({:?})", &name);
+
+        let name = JavaLangString::to_rust_string(jvm, &name).await?;
+        let normalized_name = if let Some(x) = name.strip_prefix('/') { x } else { &name };
+
+        let id = match context.system().resource().id(normalized_name) {
+            Some(x) => x,
+            None => return Err(java_exception(jvm, "java/io/IOException", &name).await),
+        };
+        let system_clone = context.system().clone();
+
+        let image = {
+            let image_data = Ref::map(system_clone.resource(), |x| x.data(id));
+
+            decode_image(&image_data)
+        };
+
+        match image {
+            Ok(x) => Self::create_image_instance(jvm, x.width(), x.height(), x.raw(), false).await,
+            Err(_) => Err(java_exception(jvm, "java/io/IOException", &name).await),
+        }
+    }
+
+    async fn create_image_from_bytes(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        data: ClassInstanceRef<Array<i8>>,
+        offset: i32,
+        length: i32,
+    ) -> JvmResult<ClassInstanceRef<Self>> {
+        tracing::debug!("javax.microedition.lcdui.Image::createImage({:?}, {}, {})", &data, offset, length);
+
+        let image_data = jvm.load_byte_array(&data, offset as _, length as _).await?;
+
+        match decode_image(&cast_vec(image_data)) {
+            Ok(x) => Self::create_image_instance(jvm, x.width(), x.height(), x.raw(), false).await,
+            Err(_) => Err(java_exception(jvm, "java/lang/IllegalArgumentException", "Unsupported image format").await),
+        }
+    }
+
+    async fn get_graphics(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<ClassInstanceRef<Graphics>> {
+        tracing::debug!("javax.microedition.lcdui.Image::getGraphics({:?})", &this);
+
+        let mutable: bool = jvm.get_field(&this, "mutable", "Z").await?;
+        if !mutable {
+            return Err(java_exception(jvm, "java/lang/IllegalStateException", "Image is immutable").await);
+        }
+
+        let instance = jvm
+            .new_class("javax/microedition/lcdui/Graphics", "(Ljavax/microedition/lcdui/Image;)V", (this,))
+            .await?;
+
+        Ok(instance.into())
+    }
+
+    async fn get_width(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.Image::getWidth({:?})", &this);
+
+        jvm.get_field(&this, "w", "I").await
+    }
+
+    async fn get_height(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.lcdui.Image::getHeight({:?})", &this);
+
+        jvm.get_field(&this, "h", "I").await
+    }
+
+    async fn is_mutable(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<bool> {
+        tracing::debug!("javax.microedition.lcdui.Image::isMutable({:?})", &this);
+
+        jvm.get_field(&this, "mutable", "Z").await
+    }
+
+    pub async fn image(jvm: &Jvm, this: &ClassInstanceRef<Self>) -> JvmResult<Box<dyn BackendImage>> {
+        let (width, height, buf) = Self::raw(jvm, this).await?;
+
+        match byte_image(width, height, 4, buf) {
+            Some(x) => Ok(x),
+            None => Err(Self::invalid_data(jvm).await),
+        }
+    }
+
+    pub async fn canvas<'a>(jvm: &'a Jvm, this: &'a ClassInstanceRef<Self>) -> JvmResult<ImageCanvas<'a>> {
+        let (width, height, buf) = Self::raw(jvm, this).await?;
+
+        match ByteImageCanvas::new(width, height, 4, buf) {
+            Some(x) => Ok(ImageCanvas::new(jvm, this, x)),
+            None => Err(Self::invalid_data(jvm).await),
+        }
+    }
+
+    async fn raw(jvm: &Jvm, this: &ClassInstanceRef<Self>) -> JvmResult<(u32, u32, Vec<u8>)> {
+        let width: i32 = jvm.get_field(this, "w", "I").await?;
+        let height: i32 = jvm.get_field(this, "h", "I").await?;
+
+        let java_img_data = jvm.get_field(this, "imgData", "[B").await?;
+        let img_data_len = jvm.array_length(&java_img_data).await?;
+        let img_data = jvm.load_byte_array(&java_img_data, 0, img_data_len).await?;
+
+        // the fields can be changed by the app, the size is checked against imgData before drawing
+        if width < 0 || height < 0 {
+            return Err(Self::invalid_data(jvm).await);
+        }
+
+        Ok((width as _, height as _, cast_vec(img_data)))
+    }
+
+    async fn invalid_data(jvm: &Jvm) -> JavaError {
+        java_exception(jvm, "java/lang/IllegalArgumentException", "Invalid image data").await
+    }
+
+    async fn create_image_instance(jvm: &Jvm, width: u32, height: u32, data: &[u8], mutable: bool) -> JvmResult<ClassInstanceRef<Self>> {
+        let mut instance = jvm.new_class("javax/microedition/lcdui/Image", "()V", []).await?;
+
+        let mut data_array = jvm.instantiate_array("B", data.len() as _).await?;
+        jvm.store_byte_array(&mut data_array, 0, cast_vec(data.to_vec())).await?;
+
+        jvm.put_field(&mut instance, "w", "I", width as i32).await?;
+        jvm.put_field(&mut instance, "h", "I", height as i32).await?;
+        jvm.put_field(&mut instance, "imgData", "[B", data_array).await?;
+        jvm.put_field(&mut instance, "mutable", "Z", mutable).await?;
+
+        Ok(instance.into())
+    }
+}
+
+// same as the one in wipi, drawn pixels are written back to the java array on flush
+pub struct ImageCanvas<'a> {
+    image: &'a ClassInstanceRef<Image>,
+    jvm: &'a Jvm,
+    // taken on flush
+    canvas: Option<ByteImageCanvas>,
+}
+
+impl<'a> ImageCanvas<'a> {
+    fn new(jvm: &'a Jvm, image: &'a ClassInstanceRef<Image>, canvas: ByteImageCanvas) -> Self {
+        Self {
+            image,
+            jvm,
+            canvas: Some(canvas),
+        }
+    }
+
+    // We don't have async drop yet..
+    pub async fn flush(mut self) {
+        let mut data = self.jvm.get_field(self.image, "imgData", "[B").await.unwrap();
+        let raw = self.canvas.take().unwrap().into_raw();
+
+        self.jvm.store_byte_array(&mut data, 0, cast_vec(raw)).await.unwrap();
+    }
+}
+
+impl Drop for ImageCanvas<'_> {
+    fn drop(&mut self) {
+        if self.canvas.is_some() {
+            panic!("ImageCanvas was dropped without flushing")
+        }
+    }
+}
+
+impl Deref for ImageCanvas<'_> {
+    type Target = dyn Canvas;
+
+    fn deref(&self) -> &Self::Target {
+        &**self.canvas.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for ImageCanvas<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut **self.canvas.as_mut().unwrap()
+    }
+}
@@ -1,9 +1,14 @@
-use alloc::vec;
+use alloc::{boxed::Box, vec};
 
-use java_class_proto::JavaMethodProto;
-use jvm::{ClassInstanceRef, Jvm, Result as JvmResult};
+use java_class_proto::{JavaFieldProto, JavaMethodProto, MethodBody};
+use java_constants::FieldAccessFlags;
+use java_runtime::classes::java::lang::String;
+use jvm::{ClassInstanceRef, JavaError, JavaValue, Jvm, Result as JvmResult};
 
-use crate::context::{MIDPJavaClassProto, MIDPJavaContext};
+use crate::{
+    classes::javax::microedition::lcdui::Display,
+    context::{MIDPJavaClassProto, MIDPJavaContext},
+};
 
 // class javax.microedition.midlet.MIDlet
 pub struct MIDlet {}
@@ -15,15 +20,86 @@ impl MIDlet {
             interfaces: vec![],
             methods: vec![
                 JavaMethodProto::new("<init>", "()V", Self::init, Default::default()),
-                JavaMethodProto::new_abstract("startApp", "([Ljava/lang/String;)V", Default::default()),
+                JavaMethodProto::new_abstract("startApp", "()V", Default::default()),
+                JavaMethodProto::new_abstract("pauseApp", "()V", Default::default()),
+                JavaMethodProto::new_abstract("destroyApp", "(Z)V", Default::default()),
+                JavaMethodProto::new("notifyDestroyed", "()V", Self::notify_destroyed, Default::default()),
+                JavaMethodProto::new("notifyPaused", "()V", Self::notify_paused, Default::default()),
+                JavaMethodProto::new(
+                    "getAppProperty",
+                    "(Ljava/lang/String;)Ljava/lang/String;",
+                    Self::get_app_property,
+                    Default::default(),
+                ),
             ],
-            fields: vec![],
+            fields: vec![JavaFieldProto::new(
+                "activeMIDlet",
+                "Ljavax/microedition/midlet/MIDlet;",
+                FieldAccessFlags::STATIC,
+            )],
         }
     }
 
-    async fn init(_jvm: &Jvm, _context: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
+    async fn init(jvm: &Jvm, context: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
         tracing::debug!("javax.microedition.midlet.MIDlet::<init>({:?})", &this);
 
+        jvm.put_static_field(
+            "javax/microedition/midlet/MIDlet",
+            "activeMIDlet",
+            "Ljavax/microedition/midlet/MIDlet;",
+            this,
+        )
+        .await?;
+
+        struct EventLoopProxy {}
+        #[async_trait::async_trait(?Send)]
+        impl MethodBody<JavaError, MIDPJavaContext> for EventLoopProxy {
+            #[tracing::instrument(name = "event_loop", skip_all)]
+            async fn call(&self, jvm: &Jvm, context: &mut MIDPJavaContext, _: Box<[JavaValue]>) -> Result<JavaValue, JavaError> {
+                Display::run_event_loop(jvm, context).await?;
+
+                Ok(JavaValue::Void)
+            }
+        }
+
+        context.spawn(Box::new(EventLoopProxy {}))?;
+
+        Ok(())
+    }
+
+    async fn notify_destroyed(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::warn!("stub javax.microedition.midlet.MIDlet::notifyDestroyed({:?})", &this);
+
+        Ok(())
+    }
+
+    async fn notify_paused(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::warn!("stub javax.microedition.midlet.MIDlet::notifyPaused({:?})", &this);
+
         Ok(())
     }
+
+    // TODO read properties from the jad and the manifest
+    async fn get_app_property(
+        _: &Jvm,
+        _: &mut MIDPJavaContext,
+        this: ClassInstanceRef<Self>,
+        key: ClassInstanceRef<String>,
+    ) -> JvmResult<ClassInstanceRef<String>> {
+        tracing::warn!("stub javax.microedition.midlet.MIDlet::getAppProperty({:?}, {:?})", &this, &key);
+
+        Ok(None.into())
+    }
+
+    // startApp is called again on resume, as the spec says
+    pub(crate) async fn lifecycle_event(jvm: &Jvm, method: &str) -> JvmResult<()> {
+        let midlet: ClassInstanceRef<Self> = jvm
+            .get_static_field("javax/microedition/midlet/MIDlet", "activeMIDlet", "Ljavax/microedition/midlet/MIDlet;")
+            .await?;
+        if midlet.is_null() {
+            return Ok(());
+        }
+
+        jvm.invoke_virtual(&midlet, method, "()V", ()).await
+    }
 }
@@ -0,0 +1,5 @@
+mod invalid_record_id_exception;
+mod record_store;
+mod record_store_exception;
+
+pub use self::{invalid_record_id_exception::InvalidRecordIDException, record_store::RecordStore, record_store_exception::RecordStoreException};
@@ -0,0 +1,34 @@
+use alloc::vec;
+
+use java_class_proto::JavaMethodProto;
+use java_runtime::classes::java::lang::String;
+use jvm::{ClassInstanceRef, Jvm, Result as JvmResult};
+
+use crate::context::{MIDPJavaClassProto, MIDPJavaContext};
+
+// class javax.microedition.rms.InvalidRecordIDException
+pub struct InvalidRecordIDException {}
+
+impl InvalidRecordIDException {
+    pub fn as_proto() -> MIDPJavaClassProto {
+        MIDPJavaClassProto {
+            parent_class: Some("javax/microedition/rms/RecordStoreException"),
+            interfaces: vec![],
+            methods: vec![JavaMethodProto::new("<init>", "(Ljava/lang/String;)V", Self::init, Default::default())],
+            fields: vec![],
+        }
+    }
+
+    async fn init(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, message: ClassInstanceRef<String>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.rms.InvalidRecordIDException::<init>({:?}, {:?})", &this, &message);
+
+        jvm.invoke_special(
+            &this,
+            "javax/microedition/rms/RecordStoreException",
+            "<init>",
+            "(Ljava/lang/String;)V",
+            (message,),
+        )
+        .await
+    }
+}
@@ -0,0 +1,254 @@
+use alloc::{boxed::Box, format, vec, vec::Vec};
+
+use bytemuck::cast_vec;
+
+use java_class_proto::{JavaFieldProto, JavaMethodProto};
+use java_constants::MethodAccessFlags;
+use java_runtime::classes::java::lang::String;
+use jvm::{runtime::JavaLangString, Array, ClassInstanceRef, JavaError, Jvm, Result as JvmResult};
+
+use wie_backend::Database;
+
+use crate::{
+    context::{MIDPJavaClassProto, MIDPJavaContext},
+    java_exception,
+};
+
+// class javax.microedition.rms.RecordStore
+pub struct RecordStore {}
+
+impl RecordStore {
+    pub fn as_proto() -> MIDPJavaClassProto {
+        MIDPJavaClassProto {
+            parent_class: Some("java/lang/Object"),
+            interfaces: vec![],
+            methods: vec![
+                JavaMethodProto::new("<init>", "(Ljava/lang/String;)V", Self::init, Default::default()),
+                JavaMethodProto::new(
+                    "openRecordStore",
+                    "(Ljava/lang/String;Z)Ljavax/microedition/rms/RecordStore;",
+                    Self::open_record_store,
+                    MethodAccessFlags::STATIC,
+                ),
+                JavaMethodProto::new("closeRecordStore", "()V", Self::close_record_store, Default::default()),
+                JavaMethodProto::new("getName", "()Ljava/lang/String;", Self::get_name, Default::default()),
+                JavaMethodProto::new("getNumRecords", "()I", Self::get_num_records, Default::default()),
+                JavaMethodProto::new("getNextRecordID", "()I", Self::get_next_record_id, Default::default()),
+                JavaMethodProto::new("addRecord", "([BII)I", Self::add_record, Default::default()),
+                JavaMethodProto::new("getRecord", "(I)[B", Self::get_record, Default::default()),
+                JavaMethodProto::new("getRecord", "(I[BI)I", Self::get_record_into, Default::default()),
+                JavaMethodProto::new("getRecordSize", "(I)I", Self::get_record_size, Default::default()),
+                JavaMethodProto::new("setRecord", "(I[BII)V", Self::set_record, Default::default()),
+                JavaMethodProto::new("deleteRecord", "(I)V", Self::delete_record, Default::default()),
+            ],
+            fields: vec![JavaFieldProto::new("name", "Ljava/lang/String;", Default::default())],
+        }
+    }
+
+    async fn init(jvm: &Jvm, _: &mut MIDPJavaContext, mut this: ClassInstanceRef<Self>, name: ClassInstanceRef<String>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.rms.RecordStore::<init>({:?}, {:?})", &this, &name);
+
+        jvm.put_field(&mut this, "name", "Ljava/lang/String;", name).await?;
+
+        Ok(())
+    }
+
+    // databases are created on open by the backend, so createIfNecessary has no effect
+    async fn open_record_store(
+        jvm: &Jvm,
+        _: &mut MIDPJavaContext,
+        name: ClassInstanceRef<String>,
+        create_if_necessary: bool,
+    ) -> JvmResult<ClassInstanceRef<Self>> {
+        tracing::debug!(
+            "javax.microedition.rms.RecordStore::openRecordStore({:?}, {})",
+            &name,
+            create_if_necessary
+        );
+
+        let instance = jvm
+            .new_class("javax/microedition/rms/RecordStore", "(Ljava/lang/String;)V", (name,))
+            .await?;
+
+        Ok(instance.into())
+    }
+
+    async fn close_record_store(_: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.rms.RecordStore::closeRecordStore({:?})", &this);
+
+        Ok(())
+    }
+
+    async fn get_name(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<ClassInstanceRef<String>> {
+        tracing::debug!("javax.microedition.rms.RecordStore::getName({:?})", &this);
+
+        jvm.get_field(&this, "name", "Ljava/lang/String;").await
+    }
+
+    async fn get_num_records(jvm: &Jvm, context: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.rms.RecordStore::getNumRecords({:?})", &this);
+
+        let database = Self::get_database(jvm, context, &this).await?;
+
+        Ok(database.get_record_ids().len() as _)
+    }
+
+    async fn get_next_record_id(jvm: &Jvm, context: &mut MIDPJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.rms.RecordStore::getNextRecordID({:?})", &this);
+
+        let database = Self::get_database(jvm, context, &this).await?;
+
+        Ok(database.next_record_id() as _)
+    }
+
+    async fn add_record(
+        jvm: &Jvm,
+        context: &mut MIDPJavaContext,
+        this: ClassInstanceRef<Self>,
+        data: ClassInstanceRef<Array<i8>>,
+        offset: i32,
+        num_bytes: i32,
+    ) -> JvmResult<i32> {
+        tracing::debug!(
+            "javax.microedition.rms.RecordStore::addRecord({:?}, {:?}, {}, {})",
+            &this,
+            &data,
+            offset,
+            num_bytes
+        );
+
+        let mut database = Self::get_database(jvm, context, &this).await?;
+
+        let data = if data.is_null() {
+            vec![]
+        } else {
+            jvm.load_byte_array(&data, offset as _, num_bytes as _).await?
+        };
+
+        Ok(database.add(&cast_vec(data)) as _)
+    }
+
+    async fn get_record(
+        jvm: &Jvm,
+        context: &mut MIDPJavaContext,
+        this: ClassInstanceRef<Self>,
+        record_id: i32,
+    ) -> JvmResult<ClassInstanceRef<Array<i8>>> {
+        tracing::debug!("javax.microedition.rms.RecordStore::getRecord({:?}, {})", &this, record_id);
+
+        let data = Self::record(jvm, context, &this, record_id).await?;
+
+        // empty records are returned as null
+        if data.is_empty() {
+            return Ok(None.into());
+        }
+
+        let mut array = jvm.instantiate_array("B", data.len()).await?;
+        jvm.store_byte_array(&mut array, 0, cast_vec(data)).await?;
+
+        Ok(array.into())
+    }
+
+    async fn get_record_into(
+        jvm: &Jvm,
+        context: &mut MIDPJavaContext,
+        this: ClassInstanceRef<Self>,
+        record_id: i32,
+        mut buffer: ClassInstanceRef<Array<i8>>,
+        offset: i32,
+    ) -> JvmResult<i32> {
+        tracing::debug!(
+            "javax.microedition.rms.RecordStore::getRecord({:?}, {}, {:?}, {})",
+            &this,
+            record_id,
+            &buffer,
+            offset
+        );
+
+        let data = Self::record(jvm, context, &this, record_id).await?;
+        let length = data.len();
+
+        jvm.store_byte_array(&mut buffer, offset as _, cast_vec(data)).await?;
+
+        Ok(length as _)
+    }
+
+    async fn get_record_size(jvm: &Jvm, context: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, record_id: i32) -> JvmResult<i32> {
+        tracing::debug!("javax.microedition.rms.RecordStore::getRecordSize({:?}, {})", &this, record_id);
+
+        let data = Self::record(jvm, context, &this, record_id).await?;
+
+        Ok(data.len() as _)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn set_record(
+        jvm: &Jvm,
+        context: &mut MIDPJavaContext,
+        this: ClassInstanceRef<Self>,
+        record_id: i32,
+        data: ClassInstanceRef<Array<i8>>,
+        offset: i32,
+        num_bytes: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "javax.microedition.rms.RecordStore::setRecord({:?}, {}, {:?}, {}, {})",
+            &this,
+            record_id,
+            &data,
+            offset,
+            num_bytes
+        );
+
+        let mut database = Self::get_database(jvm, context, &this).await?;
+
+        let data = if data.is_null() {
+            vec![]
+        } else {
+            jvm.load_byte_array(&data, offset as _, num_bytes as _).await?
+        };
+
+        if !database.set(record_id as _, &cast_vec(data)) {
+            return Err(Self::invalid_record_id(jvm, record_id).await);
+        }
+
+        Ok(())
+    }
+
+    async fn delete_record(jvm: &Jvm, context: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, record_id: i32) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.rms.RecordStore::deleteRecord({:?}, {})", &this, record_id);
+
+        let mut database = Self::get_database(jvm, context, &this).await?;
+
+        if !database.delete(record_id as _) {
+            return Err(Self::invalid_record_id(jvm, record_id).await);
+        }
+
+        Ok(())
+    }
+
+    async fn record(jvm: &Jvm, context: &mut MIDPJavaContext, this: &ClassInstanceRef<Self>, record_id: i32) -> JvmResult<Vec<u8>> {
+        let database = Self::get_database(jvm, context, this).await?;
+
+        match database.get(record_id as _) {
+            Some(x) => Ok(x),
+            None => Err(Self::invalid_record_id(jvm, record_id).await),
+        }
+    }
+
+    async fn invalid_record_id(jvm: &Jvm, record_id: i32) -> JavaError {
+        java_exception(
+            jvm,
+            "javax/microedition/rms/InvalidRecordIDException",
+            &format!("Record {} does not exist", record_id),
+        )
+        .await
+    }
+
+    async fn get_database(jvm: &Jvm, context: &mut MIDPJavaContext, this: &ClassInstanceRef<Self>) -> JvmResult<Box<dyn Database>> {
+        let name = jvm.get_field(this, "name", "Ljava/lang/String;").await?;
+        let name = JavaLangString::to_rust_string(jvm, &name).await?;
+
+        Ok(context.system().platform().database_repository().open(&name))
+    }
+}
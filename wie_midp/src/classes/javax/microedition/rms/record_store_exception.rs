@@ -0,0 +1,28 @@
+use alloc::vec;
+
+use java_class_proto::JavaMethodProto;
+use java_runtime::classes::java::lang::String;
+use jvm::{ClassInstanceRef, Jvm, Result as JvmResult};
+
+use crate::context::{MIDPJavaClassProto, MIDPJavaContext};
+
+// class javax.microedition.rms.RecordStoreException
+pub struct RecordStoreException {}
+
+impl RecordStoreException {
+    pub fn as_proto() -> MIDPJavaClassProto {
+        MIDPJavaClassProto {
+            parent_class: Some("java/lang/Exception"),
+            interfaces: vec![],
+            methods: vec![JavaMethodProto::new("<init>", "(Ljava/lang/String;)V", Self::init, Default::default())],
+            fields: vec![],
+        }
+    }
+
+    async fn init(jvm: &Jvm, _: &mut MIDPJavaContext, this: ClassInstanceRef<Self>, message: ClassInstanceRef<String>) -> JvmResult<()> {
+        tracing::debug!("javax.microedition.rms.RecordStoreException::<init>({:?}, {:?})", &this, &message);
+
+        jvm.invoke_special(&this, "java/lang/Exception", "<init>", "(Ljava/lang/String;)V", (message,))
+            .await
+    }
+}
@@ -10,7 +10,8 @@ use context::MIDPJavaClassProto;
 pub use context::MIDPJavaContextBase;
 
 use alloc::boxed::Box;
-use jvm::{ClassDefinition, Jvm, Result as JvmResult};
+use java_runtime::classes::java::lang::String;
+use jvm::{runtime::JavaLangString, ClassDefinition, ClassInstanceRef, JavaError, Jvm, Result as JvmResult};
 
 pub async fn register<T, F>(jvm: &Jvm, class_creator: T) -> JvmResult<()>
 where
@@ -18,10 +19,54 @@ where
     F: Future<Output = Box<dyn ClassDefinition>>,
 {
     // superclass should come before subclass
-    let classes = [(
-        "javax/microedition/midlet/MIDlet",
-        classes::javax::microedition::midlet::MIDlet::as_proto(),
-    )];
+    let classes = [
+        (
+            "javax/microedition/lcdui/Displayable",
+            classes::javax::microedition::lcdui::Displayable::as_proto(),
+        ),
+        ("javax/microedition/lcdui/Canvas", classes::javax::microedition::lcdui::Canvas::as_proto()),
+        (
+            "javax/microedition/lcdui/Display",
+            classes::javax::microedition::lcdui::Display::as_proto(),
+        ),
+        (
+            "javax/microedition/lcdui/Graphics",
+            classes::javax::microedition::lcdui::Graphics::as_proto(),
+        ),
+        ("javax/microedition/lcdui/Image", classes::javax::microedition::lcdui::Image::as_proto()),
+        (
+            "javax/microedition/lcdui/game/Layer",
+            classes::javax::microedition::lcdui::game::Layer::as_proto(),
+        ),
+        (
+            "javax/microedition/lcdui/game/LayerManager",
+            classes::javax::microedition::lcdui::game::LayerManager::as_proto(),
+        ),
+        (
+            "javax/microedition/lcdui/game/Sprite",
+            classes::javax::microedition::lcdui::game::Sprite::as_proto(),
+        ),
+        (
+            "javax/microedition/lcdui/game/TiledLayer",
+            classes::javax::microedition::lcdui::game::TiledLayer::as_proto(),
+        ),
+        (
+            "javax/microedition/midlet/MIDlet",
+            classes::javax::microedition::midlet::MIDlet::as_proto(),
+        ),
+        (
+            "javax/microedition/rms/RecordStore",
+            classes::javax::microedition::rms::RecordStore::as_proto(),
+        ),
+        (
+            "javax/microedition/rms/RecordStoreException",
+            classes::javax::microedition::rms::RecordStoreException::as_proto(),
+        ),
+        (
+            "javax/microedition/rms/InvalidRecordIDException",
+            classes::javax::microedition::rms::InvalidRecordIDException::as_proto(),
+        ),
+    ];
 
     for (name, proto) in classes {
         let class = class_creator(name, proto).await;
@@ -31,3 +76,16 @@ where
 
     Ok(())
 }
+
+// exception with a message, to be returned as an error from native methods
+pub(crate) async fn java_exception(jvm: &Jvm, class_name: &str, message: &str) -> JavaError {
+    let message: ClassInstanceRef<String> = match JavaLangString::from_rust_string(jvm, message).await {
+        Ok(x) => x.into(),
+        Err(x) => return x,
+    };
+
+    match jvm.new_class(class_name, "(Ljava/lang/String;)V", (message,)).await {
+        Ok(x) => JavaError::JavaException(x),
+        Err(x) => x,
+    }
+}
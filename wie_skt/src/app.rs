@@ -1,8 +1,8 @@
-use alloc::{string::String, vec::Vec};
+use alloc::{boxed::Box, string::String, vec::Vec};
 
 use jvm::Result as JvmResult;
 
-use wie_backend::{App, Event, System};
+use wie_backend::{App, CompatibilityReport, Event, GuestAccess, System, Telemetry};
 use wie_core_jvm::JvmCore;
 
 pub struct SktApp {
@@ -37,7 +37,7 @@ impl SktApp {
         let main_class = core.jvm().new_class(&normalized_class_name, "()V", []).await?;
 
         let result: JvmResult<()> = if core.jvm().is_instance(&*main_class, "javax/microedition/midlet/MIDlet").await? {
-            core.jvm().invoke_virtual(&main_class, "startApp", "()V", [None.into()]).await
+            core.jvm().invoke_virtual(&main_class, "startApp", "()V", ()).await
         } else {
             core.jvm()
                 .invoke_virtual(&main_class, "startApp", "([Ljava/lang/String;)V", [None.into()])
@@ -72,4 +72,24 @@ impl App for SktApp {
     fn tick(&mut self) -> anyhow::Result<()> {
         self.system.tick()
     }
+
+    fn report(&self) -> CompatibilityReport {
+        self.system.report().clone()
+    }
+
+    fn save_state(&self) -> anyhow::Result<Vec<u8>> {
+        anyhow::bail!("Savestate is not supported on SKT yet")
+    }
+
+    fn load_state(&mut self, _state: &[u8]) -> anyhow::Result<()> {
+        anyhow::bail!("Savestate is not supported on SKT yet")
+    }
+
+    fn guest(&self) -> Option<Box<dyn GuestAccess>> {
+        None
+    }
+
+    fn telemetry(&self) -> Telemetry {
+        self.system.telemetry()
+    }
 }
@@ -9,7 +9,7 @@ use alloc::{
 
 use anyhow::Context;
 
-use wie_backend::{App, Archive, Platform, System};
+use wie_backend::{decode_euc_kr, extract_zip, App, Archive, Platform, System};
 
 use crate::app::SktApp;
 
@@ -17,6 +17,8 @@ pub struct SktArchive {
     jar: Vec<u8>,
     id: String,
     main_class_name: Option<String>,
+    name: Option<String>,
+    icon_path: Option<String>,
     additional_files: BTreeMap<String, Vec<u8>>,
 }
 
@@ -38,7 +40,11 @@ impl SktArchive {
         let jar_name = msd_file.0.replace(".msd", ".jar");
         let jar = files.remove(&jar_name).context("Invalid format")?;
 
-        Ok(Self::from_jar(jar, &msd.id, Some(msd.main_class), files))
+        Ok(Self {
+            name: msd.name,
+            icon_path: msd.icon_path,
+            ..Self::from_jar(jar, &msd.id, Some(msd.main_class), files)
+        })
     }
 
     pub fn from_jar(data: Vec<u8>, id: &str, main_class_name: Option<String>, additional_files: BTreeMap<String, Vec<u8>>) -> Self {
@@ -46,6 +52,8 @@ impl SktArchive {
             jar: data,
             id: id.into(),
             main_class_name,
+            name: None,
+            icon_path: None,
             additional_files,
         }
     }
@@ -56,6 +64,34 @@ impl Archive for SktArchive {
         self.id.to_owned()
     }
 
+    fn platform(&self) -> &'static str {
+        "SKT"
+    }
+
+    fn main_class_name(&self) -> Option<String> {
+        self.main_class_name.clone()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    fn icon(&self) -> Option<Vec<u8>> {
+        let path = self.icon_path.as_ref()?;
+
+        self.files().ok()?.remove(path.trim_start_matches('/'))
+    }
+
+    fn files(&self) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
+        // skt jar has 8 bytes of header before the zip
+        let jar = if Self::is_skt_jar(&self.jar) { &self.jar[8..] } else { &self.jar[..] };
+
+        let mut files = extract_zip(jar)?;
+        files.extend(self.additional_files.clone());
+
+        Ok(files)
+    }
+
     fn load_app(self: Box<Self>, platform: Box<dyn Platform>) -> anyhow::Result<Box<dyn App>> {
         let system = System::new(platform, Box::new(()));
 
@@ -70,26 +106,38 @@ impl Archive for SktArchive {
 struct SktMsd {
     id: String,
     main_class: String,
+    name: Option<String>,
+    icon_path: Option<String>,
 }
 
 impl SktMsd {
     pub fn parse(filename: &str, data: &[u8]) -> Self {
         let mut main_class = String::new();
+        let mut name = None;
+        let mut icon_path = None;
         let mut id = filename[..filename.find('.').unwrap()].into();
 
         let mut lines = data.split(|x| *x == b'\n');
 
         for line in &mut lines {
             if line.starts_with(b"MIDlet-1:") {
+                // name, icon, class
                 let value = line[10..].split(|x| *x == b',').collect::<Vec<_>>();
                 main_class = str::from_utf8(value[2]).unwrap().trim().to_string();
+
+                name = Some(decode_euc_kr(value[0]).trim().to_string()).filter(|x| !x.is_empty());
+                icon_path = Some(decode_euc_kr(value[1]).trim().to_string()).filter(|x| !x.is_empty());
             }
             if line.starts_with(b"DD-ProgName") {
                 id = str::from_utf8(&line[12..]).unwrap().trim().to_string();
             }
-            // TODO load name, it's in euc-kr..
         }
 
-        Self { id, main_class }
+        Self {
+            id,
+            main_class,
+            name,
+            icon_path,
+        }
     }
 }
@@ -4,7 +4,10 @@ extern crate alloc;
 use alloc::{string::String, vec::Vec};
 use core::{mem::size_of, result};
 
-use bytemuck::{bytes_of, from_bytes, AnyBitPattern, NoUninit};
+use bytemuck::{bytes_of, pod_read_unaligned, AnyBitPattern, NoUninit};
+
+// structs up to this size are read through a buffer on the stack instead of a Vec
+const STACK_READ_SIZE: usize = 64;
 
 pub fn round_up(num_to_round: usize, multiple: usize) -> usize {
     if multiple == 0 {
@@ -34,6 +37,13 @@ pub type Result<T> = result::Result<T, ByteReadWriteError>;
 
 pub trait ByteRead {
     fn read_bytes(&self, address: u32, size: u32) -> Result<Vec<u8>>;
+
+    /// Fills `buffer` from `address`. Readers that can copy directly should override this, as the default allocates
+    fn read_into(&self, address: u32, buffer: &mut [u8]) -> Result<()> {
+        buffer.copy_from_slice(&self.read_bytes(address, buffer.len() as u32)?);
+
+        Ok(())
+    }
 }
 
 pub trait ByteWrite {
@@ -45,12 +55,29 @@ where
     T: Copy + AnyBitPattern,
     R: ?Sized + ByteRead,
 {
-    let data = reader.read_bytes(address, size_of::<T>() as u32)?;
+    if size_of::<T>() <= STACK_READ_SIZE {
+        let mut buffer = [0; STACK_READ_SIZE];
+        reader.read_into(address, &mut buffer[..size_of::<T>()])?;
 
-    Ok(*from_bytes(&data))
+        Ok(pod_read_unaligned(&buffer[..size_of::<T>()]))
+    } else {
+        let data = reader.read_bytes(address, size_of::<T>() as u32)?;
+
+        Ok(pod_read_unaligned(&data))
+    }
 }
 
+// for ascii strings like symbol names, text of the app should go through read_null_terminated_bytes and its charset
 pub fn read_null_terminated_string<R>(reader: &R, address: u32) -> Result<String>
+where
+    R: ?Sized + ByteRead,
+{
+    let result = read_null_terminated_bytes(reader, address)?;
+
+    Ok(String::from_utf8(result).unwrap())
+}
+
+pub fn read_null_terminated_bytes<R>(reader: &R, address: u32) -> Result<Vec<u8>>
 where
     R: ?Sized + ByteRead,
 {
@@ -59,7 +86,8 @@ where
     let mut result = Vec::new();
     let mut cursor = address;
     loop {
-        let item = reader.read_bytes(cursor, 1)?;
+        let mut item = [0];
+        reader.read_into(cursor, &mut item)?;
         cursor += 1;
 
         if item[0] == 0 {
@@ -71,7 +99,7 @@ where
 
     // tracing::trace!("Read address: {:#x}, data: {:02x?}", address, result);
 
-    Ok(String::from_utf8(result).unwrap())
+    Ok(result)
 }
 
 pub fn write_null_terminated_string<W>(writer: &mut W, address: u32, string: &str) -> Result<()>
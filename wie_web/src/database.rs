@@ -0,0 +1,112 @@
+use std::collections::BTreeSet;
+
+use web_sys::Storage;
+
+use wie_backend::{Database as _, RecordId};
+
+// records are kept in local storage, hex encoded under `wie/<app id>/<database>/<record id>`
+pub struct DatabaseRepository {
+    prefix: String,
+}
+
+impl DatabaseRepository {
+    pub fn new(app_id: &str) -> Self {
+        Self {
+            prefix: format!("wie/{}", app_id),
+        }
+    }
+}
+
+impl wie_backend::DatabaseRepository for DatabaseRepository {
+    fn open(&self, name: &str) -> Box<dyn wie_backend::Database> {
+        let storage = web_sys::window().and_then(|x| x.local_storage().ok().flatten());
+
+        Box::new(Database {
+            storage,
+            prefix: format!("{}/{}/", self.prefix, name),
+        })
+    }
+
+    fn list(&self) -> Vec<String> {
+        let storage = match web_sys::window().and_then(|x| x.local_storage().ok().flatten()) {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+
+        let prefix = format!("{}/", self.prefix);
+        let length = storage.length().unwrap_or(0);
+
+        (0..length)
+            .filter_map(|i| storage.key(i).ok().flatten())
+            .filter_map(|x| Some(x.strip_prefix(&prefix)?.split_once('/')?.0.to_string()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+pub struct Database {
+    storage: Option<Storage>,
+    prefix: String,
+}
+
+impl Database {
+    fn key(&self, id: RecordId) -> String {
+        format!("{}{}", self.prefix, id)
+    }
+
+    fn encode(data: &[u8]) -> String {
+        data.iter().map(|x| format!("{:02x}", x)).collect()
+    }
+
+    fn decode(data: &str) -> Option<Vec<u8>> {
+        (0..data.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(data.get(i..i + 2)?, 16).ok())
+            .collect()
+    }
+}
+
+impl wie_backend::Database for Database {
+    fn add(&mut self, data: &[u8]) -> RecordId {
+        let id = self.next_record_id();
+
+        self.set(id, data);
+
+        id
+    }
+
+    fn get(&self, id: RecordId) -> Option<Vec<u8>> {
+        let value = self.storage.as_ref()?.get_item(&self.key(id)).ok()??;
+
+        Self::decode(&value)
+    }
+
+    fn set(&mut self, id: RecordId, data: &[u8]) -> bool {
+        match &self.storage {
+            Some(x) => x.set_item(&self.key(id), &Self::encode(data)).is_ok(),
+            None => false,
+        }
+    }
+
+    fn delete(&mut self, id: RecordId) -> bool {
+        match &self.storage {
+            Some(x) => x.remove_item(&self.key(id)).is_ok(),
+            None => false,
+        }
+    }
+
+    fn get_record_ids(&self) -> Vec<RecordId> {
+        let storage = match &self.storage {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+
+        let length = storage.length().unwrap_or(0);
+
+        (0..length)
+            .filter_map(|i| storage.key(i).ok().flatten())
+            .filter_map(|x| x.strip_prefix(&self.prefix)?.parse().ok())
+            .collect()
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::BTreeSet;
+
+use web_sys::Storage;
+
+// files are kept in local storage, hex encoded under `wie-files/<app id>/<path>`.
+// directories are keys with a trailing `/` and an empty value
+pub struct FileStorage {
+    storage: Option<Storage>,
+    prefix: String,
+}
+
+impl FileStorage {
+    pub fn new(app_id: &str) -> Self {
+        Self {
+            storage: web_sys::window().and_then(|x| x.local_storage().ok().flatten()),
+            prefix: format!("wie-files/{}/", app_id),
+        }
+    }
+
+    fn key(&self, path: &str) -> String {
+        format!("{}{}", self.prefix, path.trim_start_matches('/'))
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let storage = match &self.storage {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+
+        let length = storage.length().unwrap_or(0);
+
+        (0..length)
+            .filter_map(|i| storage.key(i).ok().flatten())
+            .filter_map(|x| Some(x.strip_prefix(&self.prefix)?.to_owned()))
+            .collect()
+    }
+
+    fn encode(data: &[u8]) -> String {
+        data.iter().map(|x| format!("{:02x}", x)).collect()
+    }
+
+    fn decode(data: &str) -> Option<Vec<u8>> {
+        (0..data.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(data.get(i..i + 2)?, 16).ok())
+            .collect()
+    }
+}
+
+impl wie_backend::FileStorage for FileStorage {
+    fn read(&self, path: &str) -> Option<Vec<u8>> {
+        let value = self.storage.as_ref()?.get_item(&self.key(path)).ok()??;
+
+        Self::decode(&value)
+    }
+
+    fn write(&self, path: &str, data: &[u8]) -> bool {
+        match &self.storage {
+            Some(x) => x.set_item(&self.key(path), &Self::encode(data)).is_ok(),
+            None => false,
+        }
+    }
+
+    fn remove(&self, path: &str) -> bool {
+        let storage = match &self.storage {
+            Some(x) => x,
+            None => return false,
+        };
+
+        let key = self.key(path);
+        let dir_key = format!("{}/", key.trim_end_matches('/'));
+
+        [key, dir_key]
+            .iter()
+            .any(|x| storage.get_item(x).ok().flatten().is_some() && storage.remove_item(x).is_ok())
+    }
+
+    fn create_dir(&self, path: &str) -> bool {
+        match &self.storage {
+            Some(x) => x.set_item(&format!("{}/", self.key(path).trim_end_matches('/')), "").is_ok(),
+            None => false,
+        }
+    }
+
+    fn list(&self, path: &str) -> Option<Vec<String>> {
+        let path = path.trim_matches('/');
+        let dir = if path.is_empty() { String::new() } else { format!("{}/", path) };
+
+        let keys = self.keys();
+        if !dir.is_empty() && !keys.iter().any(|x| x.starts_with(&dir)) {
+            return None;
+        }
+
+        let entries = keys
+            .iter()
+            .filter_map(|x| x.strip_prefix(&dir))
+            .filter(|x| !x.is_empty())
+            .map(|x| match x.find('/') {
+                Some(i) => x[..=i].to_owned(),
+                None => x.to_owned(),
+            })
+            .collect::<BTreeSet<_>>();
+
+        Some(entries.into_iter().collect())
+    }
+}
@@ -0,0 +1,147 @@
+mod database;
+mod file_storage;
+mod platform;
+
+use std::{cell::Cell, rc::Rc};
+
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+
+use wie_backend::{extract_zip, App, Archive, Event, KeyCode};
+use wie_j2me::J2MEArchive;
+use wie_ktf::KtfArchive;
+use wie_lgt::LgtArchive;
+use wie_skt::SktArchive;
+
+use self::platform::{CanvasScreen, WebPlatform};
+
+// browser frontend, driven by the page calling `update` on each animation frame
+#[wasm_bindgen]
+pub struct WieWeb {
+    app: Box<dyn App>,
+    redraw_requested: Rc<Cell<bool>>,
+}
+
+#[wasm_bindgen]
+impl WieWeb {
+    /// Loads an applet from zip or jar file contents, painting on `canvas`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(filename: &str, data: Vec<u8>, canvas: HtmlCanvasElement) -> Result<WieWeb, JsValue> {
+        console_error_panic_hook::set_once();
+        tracing_wasm::try_set_as_global_default().ok();
+
+        let archive = load_archive(filename, data).map_err(to_js_error)?;
+
+        let redraw_requested = Rc::new(Cell::new(false));
+        let screen = CanvasScreen::new(&canvas, redraw_requested.clone())?;
+        let platform = WebPlatform::new(&archive.id(), screen);
+
+        let mut app = archive.load_app(Box::new(platform)).map_err(to_js_error)?;
+        app.start().map_err(to_js_error)?;
+
+        Ok(Self { app, redraw_requested })
+    }
+
+    pub fn update(&mut self) -> Result<(), JsValue> {
+        if self.redraw_requested.replace(false) {
+            self.app.on_event(Event::Redraw);
+        }
+
+        self.app.tick().map_err(to_js_error)
+    }
+
+    /// Takes `KeyboardEvent.code`, returns whether the key is used by the app.
+    pub fn key_down(&mut self, code: &str) -> bool {
+        if let Some(x) = map_key(code) {
+            self.app.on_event(Event::Keydown(x));
+
+            return true;
+        }
+
+        false
+    }
+
+    pub fn key_up(&mut self, code: &str) -> bool {
+        if let Some(x) = map_key(code) {
+            self.app.on_event(Event::Keyup(x));
+
+            return true;
+        }
+
+        false
+    }
+
+    /// Coordinates are in canvas pixels.
+    pub fn pointer_down(&mut self, x: u32, y: u32) {
+        self.app.on_event(Event::PointerDown { x, y });
+    }
+
+    pub fn pointer_move(&mut self, x: u32, y: u32) {
+        self.app.on_event(Event::PointerMove { x, y });
+    }
+
+    pub fn pointer_up(&mut self, x: u32, y: u32) {
+        self.app.on_event(Event::PointerUp { x, y });
+    }
+}
+
+fn to_js_error(error: anyhow::Error) -> JsValue {
+    JsValue::from_str(&format!("{:?}", error))
+}
+
+fn load_archive(filename: &str, data: Vec<u8>) -> anyhow::Result<Box<dyn Archive>> {
+    let name = filename.rsplit_once('.').map(|x| x.0).unwrap_or(filename);
+
+    Ok(if filename.ends_with("zip") {
+        let files = extract_zip(&data)?;
+
+        if KtfArchive::is_ktf_archive(&files) {
+            Box::new(KtfArchive::from_zip(files)?)
+        } else if LgtArchive::is_lgt_archive(&files) {
+            Box::new(LgtArchive::from_zip(files)?)
+        } else if SktArchive::is_skt_archive(&files) {
+            Box::new(SktArchive::from_zip(files)?)
+        } else {
+            anyhow::bail!("Unknown archive format")
+        }
+    } else if filename.ends_with("jar") {
+        if KtfArchive::is_ktf_jar(&data) {
+            Box::new(KtfArchive::from_jar(data, name.into(), None, Default::default()))
+        } else if LgtArchive::is_lgt_jar(&data) {
+            Box::new(LgtArchive::from_jar(data, name, None))
+        } else if SktArchive::is_skt_jar(&data) {
+            Box::new(SktArchive::from_jar(data, name, None, Default::default()))
+        } else {
+            Box::new(J2MEArchive::from_jar(name.into(), data))
+        }
+    } else {
+        anyhow::bail!("Unknown file format")
+    })
+}
+
+// same layout as the default keymap of wie_cli
+fn map_key(code: &str) -> Option<KeyCode> {
+    Some(match code {
+        "Digit1" | "Numpad1" => KeyCode::NUM1,
+        "Digit2" | "Numpad2" => KeyCode::NUM2,
+        "Digit3" | "Numpad3" => KeyCode::NUM3,
+        "KeyQ" | "Numpad4" => KeyCode::NUM4,
+        "KeyW" | "Numpad5" => KeyCode::NUM5,
+        "KeyE" | "Numpad6" => KeyCode::NUM6,
+        "KeyA" | "Numpad7" => KeyCode::NUM7,
+        "KeyS" | "Numpad8" => KeyCode::NUM8,
+        "KeyD" | "Numpad9" => KeyCode::NUM9,
+        "KeyZ" | "NumpadMultiply" => KeyCode::STAR,
+        "KeyX" | "Numpad0" => KeyCode::NUM0,
+        "KeyC" | "NumpadDecimal" => KeyCode::HASH,
+        "Space" | "Enter" => KeyCode::OK,
+        "ArrowUp" => KeyCode::UP,
+        "ArrowDown" => KeyCode::DOWN,
+        "ArrowLeft" => KeyCode::LEFT,
+        "ArrowRight" => KeyCode::RIGHT,
+        "F1" => KeyCode::LEFT_SOFT_KEY,
+        "F2" => KeyCode::RIGHT_SOFT_KEY,
+        "Backspace" => KeyCode::CLEAR,
+        _ => return None,
+    })
+}
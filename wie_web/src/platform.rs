@@ -0,0 +1,191 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use wasm_bindgen::{Clamped, JsCast, JsValue};
+use web_sys::{AudioBufferSourceNode, AudioContext, CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
+
+use wie_backend::{
+    canvas::{Image, Rect},
+    AudioSink, Instant, Platform, Screen,
+};
+
+use crate::{database::DatabaseRepository, file_storage::FileStorage};
+
+pub struct CanvasScreen {
+    context: CanvasRenderingContext2d,
+    width: u32,
+    height: u32,
+    redraw_requested: Rc<Cell<bool>>,
+}
+
+impl CanvasScreen {
+    pub fn new(canvas: &HtmlCanvasElement, redraw_requested: Rc<Cell<bool>>) -> Result<Self, JsValue> {
+        let context = canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("Canvas 2d context is not available"))?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+
+        Ok(Self {
+            context,
+            width: canvas.width(),
+            height: canvas.height(),
+            redraw_requested,
+        })
+    }
+}
+
+impl Screen for CanvasScreen {
+    // redraw is requested on the next animation frame
+    fn request_redraw(&self) -> anyhow::Result<()> {
+        self.redraw_requested.set(true);
+
+        Ok(())
+    }
+
+    fn paint(&mut self, image: &dyn Image) {
+        let data = image.colors().iter().flat_map(|x| [x.r, x.g, x.b, 0xff]).collect::<Vec<_>>();
+
+        let image_data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&data), image.width(), image.height()).unwrap();
+        self.context.put_image_data(&image_data, 0.0, 0.0).unwrap();
+    }
+
+    // the browser shows it on its next frame
+    fn paint_rect(&mut self, image: &dyn Image, rect: Rect) {
+        let data = (rect.y as u32..rect.y as u32 + rect.height)
+            .flat_map(|y| (rect.x as u32..rect.x as u32 + rect.width).map(move |x| (x, y)))
+            .flat_map(|(x, y)| {
+                let color = image.get_pixel(x, y);
+
+                [color.r, color.g, color.b, 0xff]
+            })
+            .collect::<Vec<_>>();
+
+        let image_data = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&data), rect.width, rect.height).unwrap();
+        self.context.put_image_data(&image_data, rect.x as _, rect.y as _).unwrap();
+    }
+
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+pub struct WebAudioSink {
+    context: Option<AudioContext>,
+    clips: RefCell<HashMap<u32, AudioBufferSourceNode>>,
+}
+
+impl WebAudioSink {
+    pub fn new() -> Self {
+        // audio context may not be available, e.g. on headless browsers
+        Self {
+            context: AudioContext::new().ok(),
+            clips: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn create_source(&self, channel: u8, sampling_rate: u32, wave_data: &[i16]) -> Option<AudioBufferSourceNode> {
+        let context = self.context.as_ref()?;
+
+        let frames = wave_data.len() / channel as usize;
+        let buffer = match context.create_buffer(channel as _, frames as _, sampling_rate as _) {
+            Ok(x) => x,
+            Err(x) => {
+                tracing::error!("Failed to create audio buffer: {:?}", x);
+                return None;
+            }
+        };
+
+        // web audio takes planar f32 samples, while we have interleaved i16
+        for ch in 0..channel as usize {
+            let mut samples = wave_data
+                .iter()
+                .skip(ch)
+                .step_by(channel as _)
+                .map(|&x| x as f32 / i16::MAX as f32)
+                .collect::<Vec<_>>();
+
+            buffer.copy_to_channel(&mut samples, ch as _).unwrap();
+        }
+
+        let source = context.create_buffer_source().unwrap();
+        source.set_buffer(Some(&buffer));
+        source.connect_with_audio_node(&context.destination()).unwrap();
+
+        Some(source)
+    }
+}
+
+impl AudioSink for WebAudioSink {
+    fn play_wave(&self, channel: u8, sampling_rate: u32, wave_data: &[i16]) {
+        if let Some(source) = self.create_source(channel, sampling_rate, wave_data) {
+            source.start().unwrap();
+        }
+    }
+
+    fn play_clip(&self, id: u32, channel: u8, sampling_rate: u32, wave_data: &[i16], repeat: bool) {
+        self.stop_clip(id);
+
+        if let Some(source) = self.create_source(channel, sampling_rate, wave_data) {
+            source.set_loop(repeat);
+            source.start().unwrap();
+
+            self.clips.borrow_mut().insert(id, source);
+        }
+    }
+
+    fn stop_clip(&self, id: u32) {
+        if let Some(source) = self.clips.borrow_mut().remove(&id) {
+            // throws if it has ended already, which is fine
+            let _ = source.stop();
+        }
+    }
+}
+
+pub struct WebPlatform {
+    screen: CanvasScreen,
+    database_repository: DatabaseRepository,
+    file_storage: FileStorage,
+}
+
+impl WebPlatform {
+    pub fn new(app_id: &str, screen: CanvasScreen) -> Self {
+        Self {
+            screen,
+            database_repository: DatabaseRepository::new(app_id),
+            file_storage: FileStorage::new(app_id),
+        }
+    }
+}
+
+impl Platform for WebPlatform {
+    fn screen(&mut self) -> &mut dyn Screen {
+        &mut self.screen
+    }
+
+    fn now(&self) -> Instant {
+        Instant::from_epoch_millis(js_sys::Date::now() as _)
+    }
+
+    fn database_repository(&self) -> &dyn wie_backend::DatabaseRepository {
+        &self.database_repository
+    }
+
+    fn file_storage(&self) -> &dyn wie_backend::FileStorage {
+        &self.file_storage
+    }
+
+    fn audio_sink(&self) -> Box<dyn AudioSink> {
+        Box::new(WebAudioSink::new())
+    }
+
+    fn system_property(&self, _name: &str) -> Option<String> {
+        None
+    }
+}
@@ -6,7 +6,7 @@ use bytemuck::{Pod, Zeroable};
 use wie_backend::Database;
 use wie_util::{read_generic, write_generic};
 
-use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMethodBody, WIPICResult, WIPICWord};
+use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMethodBody, WIPICMethodTable, WIPICResult, WIPICWord};
 
 #[derive(Pod, Zeroable, Copy, Clone)]
 #[repr(C)]
@@ -14,10 +14,10 @@ struct DatabaseHandle {
     name: [u8; 32], // TODO hardcoded max size
 }
 
-fn gen_stub(_id: WIPICWord, name: &'static str) -> WIPICMethodBody {
+fn gen_stub(_id: WIPICWord, name: &'static str) -> (&'static str, WIPICMethodBody) {
     let body = move |_: &mut dyn WIPICContext| async move { Err::<(), _>(WIPICError::Unimplemented(name.into())) };
 
-    body.into_body()
+    (name, body.into_body())
 }
 
 async fn open_database(context: &mut dyn WIPICContext, name: String, record_size: i32, create: i32, mode: i32) -> WIPICResult<i32> {
@@ -50,6 +50,10 @@ async fn list_record(context: &mut dyn WIPICContext, db_id: i32, buf_ptr: WIPICW
     let db = get_database_from_db_id(context, db_id);
     let ids = db.get_record_ids();
 
+    if buf_len < (ids.len() * size_of::<WIPICWord>()) as _ {
+        return Ok(-18); // M_E_SHORTBUF
+    }
+
     let mut cursor = 0;
     for &id in &ids {
         write_generic(context, buf_ptr + cursor, id)?;
@@ -118,6 +122,89 @@ async fn select_record(context: &mut dyn WIPICContext, db_id: i32, rec_id: i32,
     }
 }
 
+// records are replaced as a whole, so they can grow or shrink
+async fn update_record(context: &mut dyn WIPICContext, db_id: i32, rec_id: i32, buf_ptr: WIPICWord, buf_len: WIPICWord) -> WIPICResult<i32> {
+    tracing::debug!("MC_dbUpdateRecord({:#x}, {}, {:#x}, {})", db_id, rec_id, buf_ptr, buf_len);
+
+    let data = context.read_bytes(buf_ptr, buf_len)?;
+    let mut db = get_database_from_db_id(context, db_id);
+
+    if db.get(rec_id as _).is_none() {
+        return Ok(-22); // M_E_BADRECID
+    }
+
+    if db.set(rec_id as _, &data) {
+        Ok(0)
+    } else {
+        Ok(-1) // M_E_ERROR
+    }
+}
+
+// TODO we assume the comparator takes pointers to two records and returns negative, zero or positive like strcmp.
+// sorted record ids are written to the buffer like MC_dbListRecords
+async fn sort_records(context: &mut dyn WIPICContext, db_id: i32, fn_compare: WIPICWord, buf_ptr: WIPICWord, buf_len: WIPICWord) -> WIPICResult<i32> {
+    tracing::debug!("MC_dbSortRecords({:#x}, {:#x}, {:#x}, {})", db_id, fn_compare, buf_ptr, buf_len);
+
+    let db = get_database_from_db_id(context, db_id);
+    let mut ids = db.get_record_ids();
+    ids.sort();
+
+    if buf_len < (ids.len() * size_of::<WIPICWord>()) as _ {
+        return Ok(-18); // M_E_SHORTBUF
+    }
+
+    let mut records = Vec::with_capacity(ids.len());
+    for &id in &ids {
+        let data = db.get(id).unwrap_or_default();
+        let ptr_data = context.alloc_raw(data.len().max(1) as _)?;
+        context.write_bytes(ptr_data, &data)?;
+
+        records.push((id, ptr_data));
+    }
+
+    // insertion sort, as the comparator is guest code we can't call from a sort closure
+    for i in 1..records.len() {
+        let mut j = i;
+        while j > 0 {
+            let result = context.call_function(fn_compare, &[records[j - 1].1, records[j].1]).await? as i32;
+            if result <= 0 {
+                break;
+            }
+            records.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+
+    let mut cursor = 0;
+    for &(id, ptr_data) in &records {
+        write_generic(context, buf_ptr + cursor, id)?;
+        cursor += size_of::<WIPICWord>() as u32;
+
+        context.free_raw(ptr_data)?;
+    }
+
+    Ok(records.len() as _)
+}
+
+async fn get_number_of_records(context: &mut dyn WIPICContext, db_id: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_dbGetNumberOfRecords({:#x})", db_id);
+
+    let db = get_database_from_db_id(context, db_id);
+
+    Ok(db.get_record_ids().len() as _)
+}
+
+async fn get_record_size(context: &mut dyn WIPICContext, db_id: i32, rec_id: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_dbGetRecordSize({:#x}, {})", db_id, rec_id);
+
+    let db = get_database_from_db_id(context, db_id);
+
+    match db.get(rec_id as _) {
+        Some(x) => Ok(x.len() as _),
+        None => Ok(-22), // M_E_BADRECID
+    }
+}
+
 async fn unk16(_context: &mut dyn WIPICContext) -> WIPICResult<i32> {
     tracing::warn!("stub MC_dbUnk16()");
 
@@ -133,24 +220,24 @@ fn get_database_from_db_id(context: &mut dyn WIPICContext, db_id: i32) -> Box<dy
     context.system().platform().database_repository().open(db_name)
 }
 
-pub fn get_database_method_table() -> Vec<WIPICMethodBody> {
+pub fn get_database_method_table() -> WIPICMethodTable {
     vec![
-        open_database.into_body(),
-        read_record_single.into_body(),
-        write_record_single.into_body(),
-        close_database.into_body(),
-        select_record.into_body(),
-        gen_stub(5, "MC_dbUpdateRecord"),
-        delete_record.into_body(),
-        list_record.into_body(),
-        gen_stub(8, "MC_dbSortRecords"),
+        ("MC_dbOpenDataBase", open_database.into_body()),
+        ("MC_dbReadRecord", read_record_single.into_body()),
+        ("MC_dbWriteRecord", write_record_single.into_body()),
+        ("MC_dbCloseDataBase", close_database.into_body()),
+        ("MC_dbSelectRecord", select_record.into_body()),
+        ("MC_dbUpdateRecord", update_record.into_body()),
+        ("MC_dbDeleteRecord", delete_record.into_body()),
+        ("MC_dbListRecords", list_record.into_body()),
+        ("MC_dbSortRecords", sort_records.into_body()),
         gen_stub(9, "MC_dbGetAccessMode"),
-        gen_stub(10, "MC_dbGetNumberOfRecords"),
-        gen_stub(11, "MC_dbGetRecordSize"),
+        ("MC_dbGetNumberOfRecords", get_number_of_records.into_body()),
+        ("MC_dbGetRecordSize", get_record_size.into_body()),
         gen_stub(12, "MC_dbListDataBases"),
         gen_stub(13, ""),
         gen_stub(14, ""),
         gen_stub(15, ""),
-        unk16.into_body(),
+        ("", unk16.into_body()),
     ]
 }
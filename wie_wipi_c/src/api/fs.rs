@@ -0,0 +1,168 @@
+use alloc::{string::String, vec, vec::Vec};
+
+use wie_backend::{FileError, OpenOptions, SeekOrigin};
+
+use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMethodBody, WIPICMethodTable, WIPICResult, WIPICWord};
+
+// TODO flag values are taken from apps we've seen, not from the headers
+const MC_FILE_OPEN_WRONLY: i32 = 1;
+const MC_FILE_OPEN_RDWR: i32 = 2;
+const MC_FILE_OPEN_WRTRUNC: i32 = 4;
+
+const MC_FILE_SEEK_SET: i32 = 0;
+const MC_FILE_SEEK_CUR: i32 = 1;
+const MC_FILE_SEEK_END: i32 = 2;
+
+fn gen_stub(_id: WIPICWord, name: &'static str) -> (&'static str, WIPICMethodBody) {
+    let body = move |_: &mut dyn WIPICContext| async move { Err::<(), _>(WIPICError::Unimplemented(name.into())) };
+
+    (name, body.into_body())
+}
+
+fn error_code(error: FileError) -> i32 {
+    tracing::warn!("File operation failed: {:?}", error);
+
+    -1 // M_E_ERROR
+}
+
+async fn open(context: &mut dyn WIPICContext, name: String, flag: i32, mode: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_fsOpen({}, {:#x}, {:#x})", name, flag, mode);
+
+    let write = flag & (MC_FILE_OPEN_WRONLY | MC_FILE_OPEN_RDWR | MC_FILE_OPEN_WRTRUNC) != 0;
+    let options = OpenOptions {
+        write,
+        create: write,
+        truncate: flag & MC_FILE_OPEN_WRTRUNC != 0,
+    };
+
+    let result = context.system().filesystem().open(&name, options);
+
+    Ok(result.map(|x| x as _).unwrap_or_else(error_code))
+}
+
+async fn read(context: &mut dyn WIPICContext, fd: i32, buf: WIPICWord, len: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_fsRead({}, {:#x}, {})", fd, buf, len);
+
+    let data = match context.system().filesystem().read(fd as _, len.max(0) as _) {
+        Ok(x) => x,
+        Err(x) => return Ok(error_code(x)),
+    };
+    context.write_bytes(buf, &data)?;
+
+    Ok(data.len() as _)
+}
+
+async fn write(context: &mut dyn WIPICContext, fd: i32, buf: WIPICWord, len: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_fsWrite({}, {:#x}, {})", fd, buf, len);
+
+    let data = context.read_bytes(buf, len.max(0) as _)?;
+    let result = context.system().filesystem().write(fd as _, &data);
+
+    Ok(result.map(|x| x as _).unwrap_or_else(error_code))
+}
+
+async fn close(context: &mut dyn WIPICContext, fd: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_fsClose({})", fd);
+
+    let result = context.system().filesystem().close(fd as _);
+
+    Ok(result.map(|_| 0).unwrap_or_else(error_code))
+}
+
+async fn remove(context: &mut dyn WIPICContext, name: String, mode: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_fsRemove({}, {:#x})", name, mode);
+
+    let result = context.system().filesystem().remove(&name);
+
+    Ok(result.map(|_| 0).unwrap_or_else(error_code))
+}
+
+async fn seek(context: &mut dyn WIPICContext, fd: i32, position: i32, whence: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_fsSeek({}, {}, {})", fd, position, whence);
+
+    let origin = match whence {
+        MC_FILE_SEEK_SET => SeekOrigin::Start,
+        MC_FILE_SEEK_CUR => SeekOrigin::Current,
+        MC_FILE_SEEK_END => SeekOrigin::End,
+        _ => return Ok(-1), // M_E_ERROR
+    };
+
+    let result = context.system().filesystem().seek(fd as _, position as _, origin);
+
+    Ok(result.map(|x| x as _).unwrap_or_else(error_code))
+}
+
+async fn tell(context: &mut dyn WIPICContext, fd: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_fsTell({})", fd);
+
+    let result = context.system().filesystem().tell(fd as _);
+
+    Ok(result.map(|x| x as _).unwrap_or_else(error_code))
+}
+
+async fn mkdir(context: &mut dyn WIPICContext, name: String, mode: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_fsMkDir({}, {:#x})", name, mode);
+
+    let result = context.system().filesystem().create_dir(&name);
+
+    Ok(result.map(|_| 0).unwrap_or_else(error_code))
+}
+
+// entries are written as consecutive null terminated names, directories end with `/`
+async fn list(context: &mut dyn WIPICContext, name: String, buf: WIPICWord, buf_len: i32, mode: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_fsList({}, {:#x}, {}, {:#x})", name, buf, buf_len, mode);
+
+    let entries = match context.system().filesystem().list(&name) {
+        Ok(x) => x,
+        Err(x) => return Ok(error_code(x)),
+    };
+
+    let mut data = Vec::new();
+    for entry in &entries {
+        data.extend(context.system().encode_str(entry));
+        data.push(0);
+    }
+
+    if buf_len < data.len() as _ {
+        return Ok(-18); // M_E_SHORTBUF
+    }
+    context.write_bytes(buf, &data)?;
+
+    Ok(entries.len() as _)
+}
+
+async fn is_exist(context: &mut dyn WIPICContext, name: String, mode: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_fsIsExist({}, {:#x})", name, mode);
+
+    let exists = context.system().filesystem().exists(&name);
+
+    Ok(if exists { 0 } else { -1 }) // M_E_ERROR
+}
+
+async fn file_size(context: &mut dyn WIPICContext, fd: i32) -> WIPICResult<i32> {
+    tracing::debug!("MC_fsFileSize({})", fd);
+
+    let result = context.system().filesystem().size(fd as _);
+
+    Ok(result.map(|x| x as _).unwrap_or_else(error_code))
+}
+
+pub fn get_fs_method_table() -> WIPICMethodTable {
+    vec![
+        ("MC_fsOpen", open.into_body()),
+        ("MC_fsRead", read.into_body()),
+        ("MC_fsWrite", write.into_body()),
+        ("MC_fsClose", close.into_body()),
+        ("MC_fsRemove", remove.into_body()),
+        gen_stub(5, "MC_fsRename"),
+        ("MC_fsSeek", seek.into_body()),
+        ("MC_fsTell", tell.into_body()),
+        ("MC_fsMkDir", mkdir.into_body()),
+        gen_stub(9, "MC_fsRmDir"),
+        ("MC_fsList", list.into_body()),
+        ("MC_fsIsExist", is_exist.into_body()),
+        ("MC_fsFileSize", file_size.into_body()),
+        gen_stub(13, "MC_fsFileAttribute"),
+        gen_stub(14, "MC_fsAvailable"),
+    ]
+}
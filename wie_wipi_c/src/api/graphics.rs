@@ -2,15 +2,15 @@ mod framebuffer;
 mod grp_context;
 mod image;
 
-use alloc::{vec, vec::Vec};
+use alloc::vec;
 use core::mem::size_of;
 
 use bytemuck::Zeroable;
 
-use wie_backend::canvas::{Color, PixelType, Rgb8Pixel};
+use wie_backend::canvas::{Color, PixelType, Rgb8Pixel, Transform};
 use wie_util::{read_generic, write_generic};
 
-use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMemoryId, WIPICMethodBody, WIPICResult, WIPICWord};
+use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMemoryId, WIPICMethodBody, WIPICMethodTable, WIPICResult, WIPICWord};
 
 use self::{
     framebuffer::{WIPICDisplayInfo, WIPICFramebuffer},
@@ -20,15 +20,20 @@ use self::{
 
 const FRAMEBUFFER_DEPTH: u32 = 16; // XXX hardcode to 16bpp as some game requires 16bpp framebuffer
 
-fn gen_stub(_id: WIPICWord, name: &'static str) -> WIPICMethodBody {
+fn gen_stub(_id: WIPICWord, name: &'static str) -> (&'static str, WIPICMethodBody) {
     let body = move |_: &mut dyn WIPICContext| async move { Err::<(), _>(WIPICError::Unimplemented(name.into())) };
 
-    body.into_body()
+    (name, body.into_body())
 }
 
+// every call gets the same framebuffer, so the pixels written through its pointer show up on the next flush
 async fn get_screen_framebuffer(context: &mut dyn WIPICContext, a0: WIPICWord) -> WIPICResult<WIPICMemoryId> {
     tracing::debug!("MC_grpGetScreenFrameBuffer({:#x})", a0);
 
+    if let Some(framebuffer) = context.screen_framebuffer() {
+        return Ok(framebuffer);
+    }
+
     let (width, height) = {
         let mut platform = context.system().platform();
         let screen = platform.screen();
@@ -39,10 +44,39 @@ async fn get_screen_framebuffer(context: &mut dyn WIPICContext, a0: WIPICWord) -
 
     let memory = context.alloc(size_of::<WIPICFramebuffer>() as WIPICWord)?;
     write_generic(context, context.data_ptr(memory)?, framebuffer)?;
+    context.set_screen_framebuffer(memory);
+
+    Ok(memory)
+}
+
+// the returned framebuffer shares the pixels of the image, so drawing on it changes the image
+async fn get_image_framebuffer(context: &mut dyn WIPICContext, image: WIPICMemoryId) -> WIPICResult<WIPICMemoryId> {
+    tracing::debug!("MC_grpGetImageFrameBuffer({:#x})", image.0);
+
+    let image: WIPICImage = read_generic(context, context.data_ptr(image)?)?;
+
+    let memory = context.alloc(size_of::<WIPICFramebuffer>() as WIPICWord)?;
+    write_generic(context, context.data_ptr(memory)?, image.img)?;
 
     Ok(memory)
 }
 
+async fn destroy_offscreen_framebuffer(context: &mut dyn WIPICContext, framebuffer: WIPICMemoryId) -> WIPICResult<()> {
+    tracing::debug!("MC_grpDestroyOffScreenFrameBuffer({:#x})", framebuffer.0);
+
+    if context.screen_framebuffer().map(|x| x.0) == Some(framebuffer.0) {
+        tracing::warn!("Not destroying the screen framebuffer");
+
+        return Ok(());
+    }
+
+    let data: WIPICFramebuffer = read_generic(context, context.data_ptr(framebuffer)?)?;
+    context.free(data.buf)?;
+    context.free(framebuffer)?;
+
+    Ok(())
+}
+
 async fn init_context(context: &mut dyn WIPICContext, p_grp_ctx: WIPICWord) -> WIPICResult<()> {
     tracing::debug!("MC_grpInitContext({:#x})", p_grp_ctx);
 
@@ -173,6 +207,59 @@ async fn draw_image(
     Ok(())
 }
 
+// TODO not in the spec, the name and slot of the handset extension this stands for are unverified
+#[allow(clippy::too_many_arguments)]
+async fn draw_image_transformed(
+    context: &mut dyn WIPICContext,
+    framebuffer: WIPICMemoryId,
+    dx: i32,
+    dy: i32,
+    w: i32,
+    h: i32,
+    image: WIPICMemoryId,
+    sx: i32,
+    sy: i32,
+    graphics_context: WIPICWord,
+    transform: i32,
+) -> WIPICResult<()> {
+    tracing::debug!(
+        "OEMC_grpDrawImageTransformed({:#x}, {}, {}, {}, {}, {:#x}, {}, {}, {:#x}, {})",
+        framebuffer.0,
+        dx,
+        dy,
+        w,
+        h,
+        image.0,
+        sx,
+        sy,
+        graphics_context,
+        transform
+    );
+
+    if sx < 0 || sy < 0 || w <= 0 || h <= 0 {
+        return Ok(());
+    }
+
+    let transform = match Transform::from_sprite_transform(transform) {
+        Some(x) => x,
+        None => {
+            tracing::warn!("Invalid transform {}", transform);
+
+            return Ok(());
+        }
+    };
+
+    let framebuffer: WIPICFramebuffer = read_generic(context, context.data_ptr(framebuffer)?)?;
+    let image: WIPICImage = read_generic(context, context.data_ptr(image)?)?;
+
+    let src_image = image.img.image(context)?;
+    let mut canvas = framebuffer.canvas(context)?;
+
+    canvas.draw_region(dx, dy, &*src_image, sx as _, sy as _, w as _, h as _, transform);
+
+    Ok(())
+}
+
 async fn flush(
     context: &mut dyn WIPICContext,
     a0: WIPICWord,
@@ -196,10 +283,24 @@ async fn flush(
 
     let src_canvas = framebuffer.image(context)?;
 
-    let mut platform = context.system().platform();
-    let screen = platform.screen();
+    context.system().repaint(&*src_canvas);
+
+    Ok(())
+}
+
+async fn repaint(context: &mut dyn WIPICContext, lcd: WIPICWord, x: i32, y: i32, w: i32, h: i32) -> WIPICResult<()> {
+    tracing::debug!("MC_grpRepaint({:#x}, {}, {}, {}, {})", lcd, x, y, w, h);
+
+    // nothing was drawn to the screen yet if the framebuffer wasn't asked for
+    let framebuffer = match context.screen_framebuffer() {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+
+    let framebuffer: WIPICFramebuffer = read_generic(context, context.data_ptr(framebuffer)?)?;
+    let image = framebuffer.image(context)?;
 
-    screen.paint(&*src_canvas);
+    context.system().repaint(&*image);
 
     Ok(())
 }
@@ -323,41 +424,41 @@ async fn copy_frame_buffer(
     Ok(())
 }
 
-pub fn get_graphics_method_table() -> Vec<WIPICMethodBody> {
+pub fn get_graphics_method_table() -> WIPICMethodTable {
     vec![
         gen_stub(0, "MC_grpGetImageProperty"),
-        gen_stub(1, "MC_grpGetImageFrameBuffer"),
-        get_screen_framebuffer.into_body(),
-        gen_stub(3, "MC_grpDestroyOffScreenFrameBuffer"),
-        create_offscreen_framebuffer.into_body(),
-        init_context.into_body(),
-        set_context.into_body(),
+        ("MC_grpGetImageFrameBuffer", get_image_framebuffer.into_body()),
+        ("MC_grpGetScreenFrameBuffer", get_screen_framebuffer.into_body()),
+        ("MC_grpDestroyOffScreenFrameBuffer", destroy_offscreen_framebuffer.into_body()),
+        ("MC_grpCreateOffScreenFrameBuffer", create_offscreen_framebuffer.into_body()),
+        ("MC_grpInitContext", init_context.into_body()),
+        ("MC_grpSetContext", set_context.into_body()),
         gen_stub(7, "MC_grpGetContext"),
-        put_pixel.into_body(),
+        ("MC_grpPutPixel", put_pixel.into_body()),
         gen_stub(9, "MC_grpDrawLine"),
         gen_stub(10, "MC_grpDrawRect"),
-        fill_rect.into_body(),
-        copy_frame_buffer.into_body(),
-        draw_image.into_body(),
-        copy_area.into_body(),
+        ("MC_grpFillRect", fill_rect.into_body()),
+        ("MC_grpCopyFrameBuffer", copy_frame_buffer.into_body()),
+        ("MC_grpDrawImage", draw_image.into_body()),
+        ("MC_grpCopyArea", copy_area.into_body()),
         gen_stub(15, "MC_grpDrawArc"),
         gen_stub(16, "MC_grpFillArc"),
         gen_stub(17, "MC_grpDrawString"),
         gen_stub(18, "MC_grpDrawUnicodeString"),
         gen_stub(19, "MC_grpGetRGBPixels"),
         gen_stub(20, "MC_grpSetRGBPixels"),
-        flush.into_body(),
-        get_pixel_from_rgb.into_body(),
+        ("MC_grpFlushLcd", flush.into_body()),
+        ("MC_grpGetPixelFromRGB", get_pixel_from_rgb.into_body()),
         gen_stub(23, "MC_grpGetRGBFromPixel"),
-        get_display_info.into_body(),
-        gen_stub(25, "MC_grpRepaint"),
+        ("MC_grpGetDisplayInfo", get_display_info.into_body()),
+        ("MC_grpRepaint", repaint.into_body()),
         gen_stub(26, "MC_grpGetFont"),
         gen_stub(27, "MC_grpGetFontHeight"),
         gen_stub(28, "MC_grpGetFontAscent"),
         gen_stub(29, "MC_grpGetFontDescent"),
         gen_stub(30, "MC_grpGetStringWidth"),
         gen_stub(31, "MC_grpGetUnicodeStringWidth"),
-        create_image.into_body(),
+        ("MC_grpCreateImage", create_image.into_body()),
         gen_stub(33, "MC_grpDestroyImage"),
         gen_stub(34, "MC_grpDecodeNextImage"),
         gen_stub(35, "MC_grpEncodeImage"),
@@ -385,7 +486,7 @@ pub fn get_graphics_method_table() -> Vec<WIPICMethodBody> {
         gen_stub(57, "OEMC_grpGetFontHelpLine"),
         gen_stub(58, "OEMC_grpEncodeImageEx"),
         gen_stub(59, "OEMC_grpGetImageInfo"),
-        gen_stub(60, ""),
+        ("", draw_image_transformed.into_body()),
         gen_stub(61, ""),
         gen_stub(62, ""),
         gen_stub(63, ""),
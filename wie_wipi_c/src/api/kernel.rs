@@ -9,13 +9,13 @@ use core::{cell::Ref, iter};
 
 use bytemuck::{Pod, Zeroable};
 
-use wie_backend::Instant;
-use wie_util::{read_generic, read_null_terminated_string, write_generic, write_null_terminated_string};
+use wie_backend::{Instant, TimerToken};
+use wie_util::{read_generic, read_null_terminated_bytes, write_generic};
 
 use crate::{
     context::WIPICContext,
     method::{MethodBody, MethodImpl},
-    WIPICError, WIPICMemoryId, WIPICMethodBody, WIPICResult, WIPICWord,
+    WIPICError, WIPICMemoryId, WIPICMethodBody, WIPICMethodTable, WIPICResult, WIPICWord,
 };
 
 #[repr(C, packed)]
@@ -31,22 +31,39 @@ pub struct WIPICTimer {
     fn_callback: WIPICWord,
 }
 
-fn gen_stub(_id: WIPICWord, name: &'static str) -> WIPICMethodBody {
+fn gen_stub(_id: WIPICWord, name: &'static str) -> (&'static str, WIPICMethodBody) {
     let body = move |_: &mut dyn WIPICContext| async move { Err::<(), _>(WIPICError::Unimplemented(name.into())) };
 
-    body.into_body()
+    (name, body.into_body())
 }
 
 async fn current_time(context: &mut dyn WIPICContext) -> WIPICResult<WIPICWord> {
     tracing::debug!("MC_knlCurrentTime()");
 
-    Ok(context.system().platform().now().raw() as WIPICWord)
+    Ok(context.system().now().raw() as WIPICWord)
 }
 
-async fn get_system_property(_context: &mut dyn WIPICContext, id: String, p_out: WIPICWord, buf_size: WIPICWord) -> WIPICResult<i32> {
-    tracing::warn!("stub MC_knlGetSystemProperty({}, {:#x}, {})", id, p_out, buf_size);
+async fn get_system_property(context: &mut dyn WIPICContext, id: String, p_out: WIPICWord, buf_size: WIPICWord) -> WIPICResult<i32> {
+    tracing::debug!("MC_knlGetSystemProperty({}, {:#x}, {})", id, p_out, buf_size);
 
-    Ok(0)
+    let value = context.system().platform().system_property(&id);
+    let value = match value {
+        Some(x) => x,
+        None => {
+            tracing::warn!("Unknown system property {}", id);
+
+            return Ok(0);
+        }
+    };
+
+    // truncated to fit in the buffer with null terminator
+    let mut bytes = context.system().encode_str(&value);
+    bytes.truncate((buf_size as usize).saturating_sub(1));
+    bytes.push(0);
+
+    context.write_bytes(p_out, &bytes)?;
+
+    Ok((bytes.len() - 1) as _)
 }
 
 async fn def_timer(context: &mut dyn WIPICContext, ptr_timer: WIPICWord, fn_callback: WIPICWord) -> WIPICResult<()> {
@@ -79,32 +96,53 @@ async fn set_timer(
     struct TimerCallback {
         ptr_timer: u32,
         wakeup: Instant,
-        param: WIPICWord,
+        token: TimerToken,
     }
 
     #[async_trait::async_trait(?Send)]
     impl MethodBody<WIPICError> for TimerCallback {
         #[tracing::instrument(name = "timer", skip_all)]
         async fn call(&self, context: &mut dyn WIPICContext, _: Box<[WIPICWord]>) -> Result<WIPICWord, WIPICError> {
-            let timer: WIPICTimer = read_generic(context, self.ptr_timer)?;
+            // unsetting or setting the timer again cancels this task instead of leaving it asleep until the stale expiry
+            if let Some(x) = context.system().current_task() {
+                if !context.system().timers().attach(self.ptr_timer, self.token, x) {
+                    return Ok(0);
+                }
+            }
 
             context.system().sleep(self.wakeup).await;
 
-            context.call_function(timer.fn_callback, &[self.ptr_timer, self.param]).await?;
+            // unset or set again while we were sleeping
+            if !context.system().timers().take(self.ptr_timer, self.token) {
+                return Ok(0);
+            }
+
+            // the callback and param are read on expiry, as the app may have changed them in the meantime
+            let timer: WIPICTimer = read_generic(context, self.ptr_timer)?;
+            context.call_function(timer.fn_callback, &[self.ptr_timer, timer.param]).await?;
 
             Ok(0)
         }
     }
 
-    let wakeup = context.system().platform().now() + (((timeout_high as u64) << 32) | (timeout_low as u64)) as _;
+    let timeout = ((timeout_high as u64) << 32) | (timeout_low as u64);
+    let wakeup = context.system().now() + timeout;
+
+    let mut timer: WIPICTimer = read_generic(context, ptr_timer)?;
+    timer.time = wakeup.raw();
+    timer.param = param;
+    write_generic(context, ptr_timer, timer)?;
 
-    context.spawn(Box::new(TimerCallback { ptr_timer, wakeup, param }))?;
+    let token = context.system().timers().set(ptr_timer, wakeup);
+    context.spawn(Box::new(TimerCallback { ptr_timer, wakeup, token }))?;
 
     Ok(())
 }
 
-async fn unset_timer(_: &mut dyn WIPICContext, a0: WIPICWord) -> WIPICResult<()> {
-    tracing::warn!("stub MC_knlUnsetTimer({:#x})", a0);
+async fn unset_timer(context: &mut dyn WIPICContext, ptr_timer: WIPICWord) -> WIPICResult<()> {
+    tracing::debug!("MC_knlUnsetTimer({:#x})", ptr_timer);
+
+    context.system().timers().unset(ptr_timer);
 
     Ok(())
 }
@@ -184,9 +222,12 @@ async fn sprintk(context: &mut dyn WIPICContext, dest: WIPICWord, format: String
 
     let result = sprintf(context, &format, &[a0, a1, a2, a3])?;
 
-    write_null_terminated_string(context, dest, &result)?;
+    let mut bytes = context.system().encode_str(&result);
+    let length = bytes.len();
+    bytes.push(0);
+    context.write_bytes(dest, &bytes)?;
 
-    Ok(result.len() as _)
+    Ok(length as _)
 }
 
 async fn get_total_memory(_context: &mut dyn WIPICContext) -> WIPICResult<i32> {
@@ -214,7 +255,8 @@ fn sprintf(context: &mut dyn WIPICContext, format: &str, args: &[u32]) -> WIPICR
                 'd' => result += &arg_iter.next().unwrap().to_string(),
                 's' => {
                     let ptr = arg_iter.next().unwrap();
-                    let str = read_null_terminated_string(context, *ptr)?;
+                    let bytes = read_null_terminated_bytes(context, *ptr)?;
+                    let str = context.system().decode_str(&bytes);
 
                     result += &str;
                 }
@@ -242,13 +284,13 @@ async fn get_cur_program_id(_context: &mut dyn WIPICContext) -> WIPICResult<WIPI
     Ok(1)
 }
 
-pub fn get_kernel_method_table<M, F, R, P>(reserved1: M) -> Vec<WIPICMethodBody>
+pub fn get_kernel_method_table<M, F, R, P>(reserved1: M) -> WIPICMethodTable
 where
     M: MethodImpl<F, R, WIPICError, P>,
 {
     vec![
-        printk.into_body(),
-        sprintk.into_body(),
+        ("MC_knlPrintk", printk.into_body()),
+        ("MC_knlSprintk", sprintk.into_body()),
         gen_stub(2, "MC_knlGetExecNames"),
         gen_stub(3, "MC_knlExecute"),
         gen_stub(4, "MC_knlMExecute"),
@@ -256,7 +298,7 @@ where
         gen_stub(6, "MC_knlMLoad"),
         gen_stub(7, "MC_knlExit"),
         gen_stub(8, "MC_knlProgramStop"),
-        get_cur_program_id.into_body(),
+        ("MC_knlGetCurProgramID", get_cur_program_id.into_body()),
         gen_stub(10, "MC_knlGetParentProgramID"),
         gen_stub(11, "MC_knlGetAppManagerID"),
         gen_stub(12, "MC_knlGetProgramInfo"),
@@ -267,20 +309,20 @@ where
         gen_stub(17, "MC_knlGetSharedBuf"),
         gen_stub(18, "MC_knlGetSharedBufSize"),
         gen_stub(19, "MC_knlResizeSharedBuf"),
-        alloc.into_body(),
-        calloc.into_body(),
-        free.into_body(),
-        get_total_memory.into_body(),
-        get_free_memory.into_body(),
-        def_timer.into_body(),
-        set_timer.into_body(),
-        unset_timer.into_body(),
-        current_time.into_body(),
-        get_system_property.into_body(),
+        ("MC_knlAlloc", alloc.into_body()),
+        ("MC_knlCalloc", calloc.into_body()),
+        ("MC_knlFree", free.into_body()),
+        ("MC_knlGetTotalMemory", get_total_memory.into_body()),
+        ("MC_knlGetFreeMemory", get_free_memory.into_body()),
+        ("MC_knlDefTimer", def_timer.into_body()),
+        ("MC_knlSetTimer", set_timer.into_body()),
+        ("MC_knlUnsetTimer", unset_timer.into_body()),
+        ("MC_knlCurrentTime", current_time.into_body()),
+        ("MC_knlGetSystemProperty", get_system_property.into_body()),
         gen_stub(30, "MC_knlSetSystemProperty"),
-        get_resource_id.into_body(),
-        get_resource.into_body(),
-        reserved1.into_body(),
+        ("MC_knlGetResourceID", get_resource_id.into_body()),
+        ("MC_knlGetResource", get_resource.into_body()),
+        ("MC_knlReserved1", reserved1.into_body()),
         gen_stub(34, "MC_knlReserved2"),
         gen_stub(35, "MC_knlReserved3"),
         gen_stub(36, "MC_knlReserved4"),
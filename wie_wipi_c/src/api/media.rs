@@ -1,7 +1,15 @@
 use alloc::{string::String, vec, vec::Vec};
-use core::mem::size_of;
+use core::mem::{offset_of, size_of};
 
-use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMethodBody, WIPICResult, WIPICWord};
+use wie_backend::{AudioHandle, Tone};
+use wie_util::{read_generic, write_generic};
+
+use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMethodBody, WIPICMethodTable, WIPICResult, WIPICWord};
+
+// value of MdaClip::type for clips taking headerless pcm, formats are detected by header otherwise
+const CLIP_TYPE_PCM: u8 = 1;
+// what raw pcm clips of handsets are usually recorded at
+const PCM_SAMPLING_RATE: u32 = 8000;
 
 #[repr(C)]
 struct MdaClip {
@@ -64,20 +72,58 @@ struct MdaClip {
     device_info: i32,
 }
 
-fn gen_stub(_id: WIPICWord, name: &'static str) -> WIPICMethodBody {
+fn gen_stub(_id: WIPICWord, name: &'static str) -> (&'static str, WIPICMethodBody) {
     let body = move |_: &mut dyn WIPICContext| async move { Err::<(), _>(WIPICError::Unimplemented(name.into())) };
 
-    body.into_body()
+    (name, body.into_body())
+}
+
+// clip_id of MdaClip holds the audio handle plus one, zero while the clip has no data
+fn audio_handle(context: &mut dyn WIPICContext, clip: WIPICWord) -> WIPICResult<Option<AudioHandle>> {
+    let clip_id: i32 = read_generic(context, clip)?;
+
+    Ok(if clip_id > 0 { Some(clip_id as AudioHandle - 1) } else { None })
+}
+
+fn set_audio_handle(context: &mut dyn WIPICContext, clip: WIPICWord, handle: Option<AudioHandle>) -> WIPICResult<()> {
+    if let Some(handle) = audio_handle(context, clip)? {
+        let _ = context.system().audio().unload(handle);
+    }
+
+    write_generic(context, clip, handle.map(|x| x as i32 + 1).unwrap_or(0))?;
+
+    Ok(())
+}
+
+fn read_i32_array(context: &mut dyn WIPICContext, address: WIPICWord, length: WIPICWord) -> WIPICResult<Vec<i32>> {
+    (0..length).map(|i| Ok(read_generic(context, address + i * 4)?)).collect()
 }
 
 async fn clip_create(context: &mut dyn WIPICContext, r#type: String, buf_size: WIPICWord, callback: WIPICWord) -> WIPICResult<WIPICWord> {
-    tracing::warn!("stub MC_mdaClipCreate({}, {:#x}, {:#x})", r#type, buf_size, callback);
+    tracing::debug!("MC_mdaClipCreate({}, {:#x}, {:#x})", r#type, buf_size, callback);
 
+    // TODO the callback isn't called when playback ends
     let clip = context.alloc_raw(size_of::<MdaClip>() as u32)?;
+    context.write_bytes(clip, &[0; size_of::<MdaClip>()])?;
+
+    if r#type.to_ascii_lowercase().contains("pcm") {
+        context.write_bytes(clip + offset_of!(MdaClip, r#type) as WIPICWord, &[CLIP_TYPE_PCM])?;
+    }
 
     Ok(clip)
 }
 
+async fn clip_free(context: &mut dyn WIPICContext, clip: WIPICWord) -> WIPICResult<WIPICWord> {
+    tracing::debug!("MC_mdaClipFree({:#x})", clip);
+
+    if let Some(handle) = audio_handle(context, clip)? {
+        let _ = context.system().audio().unload(handle);
+    }
+    context.free_raw(clip)?;
+
+    Ok(0)
+}
+
 async fn clip_get_type(_context: &mut dyn WIPICContext, clip: WIPICWord, buf: WIPICWord, buf_size: WIPICWord) -> WIPICResult<WIPICWord> {
     tracing::warn!("stub MC_mdaClipGetType({:#x}, {:#x}, {:#x})", clip, buf, buf_size);
 
@@ -102,16 +148,94 @@ async fn clip_get_info(
     Ok(0)
 }
 
-async fn clip_put_data(context: &mut dyn WIPICContext, clip: WIPICWord, buf: WIPICWord, buf_size: WIPICWord) -> WIPICResult<WIPICWord> {
-    tracing::warn!("stub MC_mdaClipPutData({:#x}, {:#x}, {:#x})", clip, buf, buf_size);
+async fn clip_put_data(context: &mut dyn WIPICContext, clip: WIPICWord, buf: WIPICWord, buf_size: WIPICWord) -> WIPICResult<i32> {
+    tracing::debug!("MC_mdaClipPutData({:#x}, {:#x}, {:#x})", clip, buf, buf_size);
 
     let data = context.read_bytes(buf, buf_size)?;
+    let r#type: u8 = read_generic(context, clip + offset_of!(MdaClip, r#type) as WIPICWord)?;
+
+    let result = if r#type == CLIP_TYPE_PCM {
+        context.system().audio().load_pcm(&data, 1, PCM_SAMPLING_RATE)
+    } else {
+        context.system().audio().load(&data)
+    };
+
+    match result {
+        Ok(x) => {
+            set_audio_handle(context, clip, Some(x))?;
+
+            Ok(buf_size as _)
+        }
+        Err(_) => {
+            tracing::warn!("Unsupported audio data on clip {:#x}", clip);
+            set_audio_handle(context, clip, None)?;
+
+            Ok(-1) // M_E_ERROR
+        }
+    }
+}
 
-    context
-        .system()
-        .audio()
-        .load_smaf(&data)
-        .map_err(|_| WIPICError::BackendError("Invalid Audio".into()))?;
+async fn clip_put_tone_data(
+    context: &mut dyn WIPICContext,
+    clip: WIPICWord,
+    tone: WIPICWord,
+    duration: WIPICWord,
+    len: WIPICWord,
+) -> WIPICResult<i32> {
+    tracing::debug!("MC_mdaClipPutToneData({:#x}, {:#x}, {:#x}, {})", clip, tone, duration, len);
+
+    let notes = read_i32_array(context, tone, len)?;
+    let durations = read_i32_array(context, duration, len)?;
+
+    let tones = notes
+        .into_iter()
+        .zip(durations)
+        .map(|(note, duration)| Tone::Note {
+            note,
+            duration: duration.max(0) as _,
+        })
+        .collect::<Vec<_>>();
+
+    let handle = context.system().audio().load_tones(&tones);
+    set_audio_handle(context, clip, Some(handle))?;
+
+    Ok(0)
+}
+
+async fn clip_put_freq_tone_data(
+    context: &mut dyn WIPICContext,
+    clip: WIPICWord,
+    hi_freq: WIPICWord,
+    low_freq: WIPICWord,
+    duration: WIPICWord,
+    len: WIPICWord,
+) -> WIPICResult<i32> {
+    tracing::debug!(
+        "MC_mdaClipPutFreqToneData({:#x}, {:#x}, {:#x}, {:#x}, {})",
+        clip,
+        hi_freq,
+        low_freq,
+        duration,
+        len
+    );
+
+    let highs = read_i32_array(context, hi_freq, len)?;
+    let lows = read_i32_array(context, low_freq, len)?;
+    let durations = read_i32_array(context, duration, len)?;
+
+    let tones = highs
+        .into_iter()
+        .zip(lows)
+        .zip(durations)
+        .map(|((high, low), duration)| Tone::Frequency {
+            high: high.max(0) as _,
+            low: low.max(0) as _,
+            duration: duration.max(0) as _,
+        })
+        .collect::<Vec<_>>();
+
+    let handle = context.system().audio().load_tones(&tones);
+    set_audio_handle(context, clip, Some(handle))?;
 
     Ok(0)
 }
@@ -128,26 +252,74 @@ async fn clip_set_position(_context: &mut dyn WIPICContext, clip: WIPICWord, ms:
     Ok(0)
 }
 
-async fn play(_context: &mut dyn WIPICContext, clip: WIPICWord, repeat: WIPICWord) -> WIPICResult<WIPICWord> {
-    tracing::warn!("stub MC_mdaPlay({:#x}, {})", clip, repeat);
+async fn clip_get_volume(context: &mut dyn WIPICContext, clip: WIPICWord) -> WIPICResult<i32> {
+    tracing::debug!("MC_mdaClipGetVolume({:#x})", clip);
 
-    Ok(0)
+    let handle = match audio_handle(context, clip)? {
+        Some(x) => x,
+        None => return Ok(-1), // M_E_ERROR
+    };
+
+    match context.system().audio().volume(handle) {
+        Ok(x) => Ok(x as _),
+        Err(_) => Ok(-1), // M_E_ERROR
+    }
+}
+
+async fn clip_set_volume(context: &mut dyn WIPICContext, clip: WIPICWord, volume: WIPICWord) -> WIPICResult<i32> {
+    tracing::debug!("MC_mdaClipSetVolume({:#x}, {})", clip, volume);
+
+    let handle = match audio_handle(context, clip)? {
+        Some(x) => x,
+        None => return Ok(-1), // M_E_ERROR
+    };
+
+    match context.system().audio().set_volume(handle, volume.min(100) as _) {
+        Ok(_) => Ok(0),
+        Err(_) => Ok(-1), // M_E_ERROR
+    }
 }
 
-async fn pause(_context: &mut dyn WIPICContext, clip: WIPICWord) -> WIPICResult<WIPICWord> {
-    tracing::warn!("stub MC_mdaPause({:#x})", clip);
+async fn play(context: &mut dyn WIPICContext, clip: WIPICWord, repeat: WIPICWord) -> WIPICResult<i32> {
+    tracing::debug!("MC_mdaPlay({:#x}, {})", clip, repeat);
+
+    let handle = match audio_handle(context, clip)? {
+        Some(x) => x,
+        None => return Ok(-1), // M_E_ERROR
+    };
+
+    match context.system().audio().play(handle, repeat != 0).await {
+        Ok(_) => Ok(0),
+        Err(_) => Ok(-1), // M_E_ERROR
+    }
+}
+
+async fn pause(context: &mut dyn WIPICContext, clip: WIPICWord) -> WIPICResult<WIPICWord> {
+    tracing::debug!("MC_mdaPause({:#x})", clip);
+
+    if let Some(handle) = audio_handle(context, clip)? {
+        let _ = context.system().audio().pause(handle);
+    }
 
     Ok(0)
 }
 
-async fn resume(_context: &mut dyn WIPICContext, clip: WIPICWord) -> WIPICResult<WIPICWord> {
-    tracing::warn!("stub MC_mdaResume({:#x})", clip);
+async fn resume(context: &mut dyn WIPICContext, clip: WIPICWord) -> WIPICResult<WIPICWord> {
+    tracing::debug!("MC_mdaResume({:#x})", clip);
+
+    if let Some(handle) = audio_handle(context, clip)? {
+        let _ = context.system().audio().resume(handle);
+    }
 
     Ok(0)
 }
 
-async fn stop(_context: &mut dyn WIPICContext, clip: WIPICWord) -> WIPICResult<WIPICWord> {
-    tracing::warn!("stub MC_mdaStop({:#x})", clip);
+async fn stop(context: &mut dyn WIPICContext, clip: WIPICWord) -> WIPICResult<WIPICWord> {
+    tracing::debug!("MC_mdaStop({:#x})", clip);
+
+    if let Some(handle) = audio_handle(context, clip)? {
+        let _ = context.system().audio().stop(handle);
+    }
 
     Ok(0)
 }
@@ -158,35 +330,35 @@ async fn record(_context: &mut dyn WIPICContext, clip: WIPICWord) -> WIPICResult
     Ok(0)
 }
 
-pub fn get_media_method_table() -> Vec<WIPICMethodBody> {
+pub fn get_media_method_table() -> WIPICMethodTable {
     vec![
-        clip_create.into_body(),
-        gen_stub(1, "MC_mdaClipFree"),
+        ("MC_mdaClipCreate", clip_create.into_body()),
+        ("MC_mdaClipFree", clip_free.into_body()),
         gen_stub(2, "MC_mdaSetWaterMark"),
-        clip_get_type.into_body(),
-        clip_put_data.into_body(),
+        ("MC_mdaClipGetType", clip_get_type.into_body()),
+        ("MC_mdaClipPutData", clip_put_data.into_body()),
         gen_stub(5, "MC_mdaClipPutDataByFile"),
-        gen_stub(6, "MC_mdaClipPutToneData"),
-        gen_stub(7, "MC_mdaClipPutFreqToneData"),
-        clip_get_data.into_body(),
+        ("MC_mdaClipPutToneData", clip_put_tone_data.into_body()),
+        ("MC_mdaClipPutFreqToneData", clip_put_freq_tone_data.into_body()),
+        ("MC_mdaClipGetData", clip_get_data.into_body()),
         gen_stub(9, "MC_mdaClipAvailableDataSize"),
         gen_stub(10, "MC_mdaClipClearData"),
-        clip_set_position.into_body(),
-        gen_stub(12, "MC_mdaClipGetVolume"),
-        gen_stub(13, "MC_mdaClipSetVolume"),
-        play.into_body(),
-        pause.into_body(),
-        resume.into_body(),
-        stop.into_body(),
-        record.into_body(),
+        ("MC_mdaClipSetPosition", clip_set_position.into_body()),
+        ("MC_mdaClipGetVolume", clip_get_volume.into_body()),
+        ("MC_mdaClipSetVolume", clip_set_volume.into_body()),
+        ("MC_mdaPlay", play.into_body()),
+        ("MC_mdaPause", pause.into_body()),
+        ("MC_mdaResume", resume.into_body()),
+        ("MC_mdaStop", stop.into_body()),
+        ("MC_mdaRecord", record.into_body()),
         gen_stub(19, "MC_mdaGetVolume"),
         gen_stub(20, "MC_mdaSetVolume"),
         gen_stub(21, "MC_mdaVibrator"),
         gen_stub(22, "MC_mdaReserved1"),
         gen_stub(23, "MC_mdaReserved2"),
         gen_stub(24, "MC_mdaSetMuteState"),
-        get_mute_state.into_body(),
-        clip_get_info.into_body(),
+        ("MC_mdaGetMuteState", get_mute_state.into_body()),
+        ("MC_mdaClipGetInfo", clip_get_info.into_body()),
         // gen_stub(27, "OEMC_mdaClipControl"),
         // gen_stub(28, "OEMC_mdaSetClipArea"),
         // gen_stub(29, "OEMC_mdaReleaseClipArea"),
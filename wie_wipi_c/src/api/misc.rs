@@ -1,11 +1,11 @@
-use alloc::{vec, vec::Vec};
+use alloc::vec;
 
-use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMethodBody, WIPICResult, WIPICWord};
+use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMethodBody, WIPICMethodTable, WIPICResult, WIPICWord};
 
-fn gen_stub(_id: WIPICWord, name: &'static str) -> WIPICMethodBody {
+fn gen_stub(_id: WIPICWord, name: &'static str) -> (&'static str, WIPICMethodBody) {
     let body = move |_: &mut dyn WIPICContext| async move { Err::<(), _>(WIPICError::Unimplemented(name.into())) };
 
-    body.into_body()
+    (name, body.into_body())
 }
 
 async fn back_light(
@@ -20,9 +20,9 @@ async fn back_light(
     Ok(0)
 }
 
-pub fn get_misc_method_table() -> Vec<WIPICMethodBody> {
+pub fn get_misc_method_table() -> WIPICMethodTable {
     vec![
-        back_light.into_body(),
+        ("MC_miscBackLight", back_light.into_body()),
         gen_stub(1, "MC_miscSetLed"),
         gen_stub(2, "MC_miscGetLed"),
         gen_stub(3, "MC_miscGetLedCount"),
@@ -1,11 +1,11 @@
-use alloc::{vec, vec::Vec};
+use alloc::vec;
 
-use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMethodBody, WIPICResult, WIPICWord};
+use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMethodBody, WIPICMethodTable, WIPICResult, WIPICWord};
 
-fn gen_stub(_id: WIPICWord, name: &'static str) -> WIPICMethodBody {
+fn gen_stub(_id: WIPICWord, name: &'static str) -> (&'static str, WIPICMethodBody) {
     let body = move |_: &mut dyn WIPICContext| async move { Err::<(), _>(WIPICError::Unimplemented(name.into())) };
 
-    body.into_body()
+    (name, body.into_body())
 }
 
 async fn connect(_context: &mut dyn WIPICContext, cb: WIPICWord, param: WIPICWord) -> WIPICResult<i32> {
@@ -26,15 +26,15 @@ async fn socket_close(_context: &mut dyn WIPICContext, fd: i32) -> WIPICResult<i
     Ok(-1) // M_E_ERROR
 }
 
-pub fn get_net_method_table() -> Vec<WIPICMethodBody> {
+pub fn get_net_method_table() -> WIPICMethodTable {
     vec![
-        connect.into_body(),
-        close.into_body(),
+        ("MC_netConnect", connect.into_body()),
+        ("MC_netClose", close.into_body()),
         gen_stub(2, "MC_netSocket"),
         gen_stub(3, "MC_netSocketConnect"),
         gen_stub(4, "MC_netSocketWrite"),
         gen_stub(5, "MC_netSocketRead"),
-        socket_close.into_body(),
+        ("MC_netSocketClose", socket_close.into_body()),
         gen_stub(7, "MC_netSocketBind"),
         gen_stub(8, "MC_netGetMaxPacketLength"),
         gen_stub(9, "MC_netSocketSendTo"),
@@ -1,13 +1,61 @@
-use alloc::{format, vec::Vec};
+use alloc::{boxed::Box, format, vec::Vec};
 
-use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMethodBody, WIPICWord};
+use wie_backend::{ImportKind, ImportStatus};
 
-fn gen_stub(interface: WIPICWord, id: WIPICWord) -> WIPICMethodBody {
-    let body = move |_: &mut dyn WIPICContext| async move { Err::<(), _>(WIPICError::Unimplemented(format!("unknown{}_{}", interface, id))) };
+use crate::{context::WIPICContext, method::MethodBody, WIPICError, WIPICMethodBody, WIPICMethodTable, WIPICWord};
 
-    body.into_body()
+// handset property read for the value unknown methods return, in decimal or 0x prefixed hex. 0 if not set
+const UNKNOWN_RETURN_PROPERTY: &str = "wie.wipi_c.unknown_return";
+
+// slots of interfaces we know nothing about. calls are reported and answered with the default
+// instead of stopping the app, so a title can be triaged for the apis it actually needs
+struct UnknownMethod {
+    interface: WIPICWord,
+    id: WIPICWord,
+}
+
+impl UnknownMethod {
+    fn default_return(context: &mut dyn WIPICContext) -> WIPICWord {
+        let value = match context.system().platform().system_property(UNKNOWN_RETURN_PROPERTY) {
+            Some(x) => x,
+            None => return 0,
+        };
+
+        let parsed = match value.strip_prefix("0x") {
+            Some(x) => WIPICWord::from_str_radix(x, 16),
+            None => value.parse::<i32>().map(|x| x as WIPICWord),
+        };
+
+        parsed.unwrap_or_else(|_| {
+            tracing::warn!("Invalid {}: {}", UNKNOWN_RETURN_PROPERTY, value);
+
+            0
+        })
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl MethodBody<WIPICError> for UnknownMethod {
+    async fn call(&self, context: &mut dyn WIPICContext, args: Box<[WIPICWord]>) -> Result<WIPICWord, WIPICError> {
+        let name = format!("unknown{}_{}", self.interface, self.id);
+        let result = Self::default_return(context);
+
+        tracing::warn!(
+            interface = self.interface,
+            method = self.id,
+            "Unknown WIPI C call {}({}) returning {:#x}",
+            name,
+            args.iter().map(|x| format!("{:#x}", x)).collect::<Vec<_>>().join(", "),
+            result
+        );
+        context.system().report().record(ImportKind::Function, &name, ImportStatus::Stub);
+
+        Ok(result)
+    }
 }
 
-pub fn get_stub_method_table(interface: WIPICWord) -> Vec<WIPICMethodBody> {
-    (0..64).map(|x| gen_stub(interface, x)).collect::<Vec<_>>()
+pub fn get_stub_method_table(interface: WIPICWord) -> WIPICMethodTable {
+    (0..64)
+        .map(|id| ("", Box::new(UnknownMethod { interface, id }) as WIPICMethodBody))
+        .collect::<Vec<_>>()
 }
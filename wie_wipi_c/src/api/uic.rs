@@ -1,11 +1,11 @@
-use alloc::{vec, vec::Vec};
+use alloc::vec;
 
-use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMemoryId, WIPICMethodBody, WIPICResult, WIPICWord};
+use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMemoryId, WIPICMethodBody, WIPICMethodTable, WIPICResult, WIPICWord};
 
-fn gen_stub(_id: WIPICWord, name: &'static str) -> WIPICMethodBody {
+fn gen_stub(_id: WIPICWord, name: &'static str) -> (&'static str, WIPICMethodBody) {
     let body = move |_: &mut dyn WIPICContext| async move { Err::<(), _>(WIPICError::Unimplemented(name.into())) };
 
-    body.into_body()
+    (name, body.into_body())
 }
 
 async fn create_application_context(_context: &mut dyn WIPICContext) -> WIPICResult<WIPICMemoryId> {
@@ -14,9 +14,9 @@ async fn create_application_context(_context: &mut dyn WIPICContext) -> WIPICRes
     Ok(WIPICMemoryId(0))
 }
 
-pub fn get_uic_method_table() -> Vec<WIPICMethodBody> {
+pub fn get_uic_method_table() -> WIPICMethodTable {
     vec![
-        create_application_context.into_body(),
+        ("MC_uicCreateApplicationContext", create_application_context.into_body()),
         gen_stub(1, "MC_uicGetClass"),
         gen_stub(2, "MC_uicCreate"),
         gen_stub(3, "MC_uicDestroy"),
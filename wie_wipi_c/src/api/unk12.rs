@@ -1,6 +1,6 @@
-use alloc::{vec, vec::Vec};
+use alloc::vec;
 
-use crate::{context::WIPICContext, method::MethodImpl, WIPICMethodBody, WIPICResult, WIPICWord};
+use crate::{context::WIPICContext, method::MethodImpl, WIPICMethodTable, WIPICResult, WIPICWord};
 
 async fn unk0(_context: &mut dyn WIPICContext) -> WIPICResult<WIPICWord> {
     tracing::warn!("stub unk12_0()");
@@ -20,6 +20,6 @@ async fn unk2(_context: &mut dyn WIPICContext) -> WIPICResult<WIPICWord> {
     Ok(0)
 }
 
-pub fn get_unk12_method_table() -> Vec<WIPICMethodBody> {
-    vec![unk0.into_body(), unk1.into_body(), unk2.into_body()]
+pub fn get_unk12_method_table() -> WIPICMethodTable {
+    vec![("", unk0.into_body()), ("", unk1.into_body()), ("", unk2.into_body())]
 }
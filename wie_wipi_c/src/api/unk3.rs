@@ -1,6 +1,6 @@
-use alloc::{vec, vec::Vec};
+use alloc::vec;
 
-use crate::{context::WIPICContext, method::MethodImpl, WIPICMethodBody, WIPICResult, WIPICWord};
+use crate::{context::WIPICContext, method::MethodImpl, WIPICMethodTable, WIPICResult, WIPICWord};
 
 async fn unk0(_context: &mut dyn WIPICContext) -> WIPICResult<WIPICWord> {
     tracing::warn!("stub unk3_0()");
@@ -32,6 +32,12 @@ async fn unk4(_context: &mut dyn WIPICContext) -> WIPICResult<WIPICWord> {
     Ok(0)
 }
 
-pub fn get_unk3_method_table() -> Vec<WIPICMethodBody> {
-    vec![unk0.into_body(), unk1.into_body(), unk2.into_body(), unk3.into_body(), unk4.into_body()]
+pub fn get_unk3_method_table() -> WIPICMethodTable {
+    vec![
+        ("", unk0.into_body()),
+        ("", unk1.into_body()),
+        ("", unk2.into_body()),
+        ("", unk3.into_body()),
+        ("", unk4.into_body()),
+    ]
 }
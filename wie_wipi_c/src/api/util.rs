@@ -1,11 +1,11 @@
-use alloc::{vec, vec::Vec};
+use alloc::vec;
 
-use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMethodBody, WIPICResult, WIPICWord};
+use crate::{context::WIPICContext, method::MethodImpl, WIPICError, WIPICMethodBody, WIPICMethodTable, WIPICResult, WIPICWord};
 
-fn gen_stub(_id: WIPICWord, name: &'static str) -> WIPICMethodBody {
+fn gen_stub(_id: WIPICWord, name: &'static str) -> (&'static str, WIPICMethodBody) {
     let body = move |_: &mut dyn WIPICContext| async move { Err::<(), _>(WIPICError::Unimplemented(name.into())) };
 
-    body.into_body()
+    (name, body.into_body())
 }
 
 async fn htons(_context: &mut dyn WIPICContext, val: WIPICWord) -> WIPICResult<WIPICWord> {
@@ -14,10 +14,10 @@ async fn htons(_context: &mut dyn WIPICContext, val: WIPICWord) -> WIPICResult<W
     Ok((val as u16).to_be() as _) // XXX we're always on little endian
 }
 
-pub fn get_util_method_table() -> Vec<WIPICMethodBody> {
+pub fn get_util_method_table() -> WIPICMethodTable {
     vec![
         gen_stub(0, "MC_utilHtonl"),
-        htons.into_body(),
+        ("MC_utilHtons", htons.into_body()),
         gen_stub(2, "MC_utilNtohl"),
         gen_stub(3, "MC_utilNtohs"),
         gen_stub(4, "MC_utilInetAddrInt"),
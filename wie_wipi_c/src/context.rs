@@ -1,7 +1,7 @@
 use alloc::{boxed::Box, string::String};
 
 use wie_backend::System;
-use wie_util::{read_null_terminated_string, ByteRead, ByteWrite};
+use wie_util::{read_null_terminated_bytes, ByteRead, ByteWrite};
 
 use crate::{method::TypeConverter, WIPICMemoryId, WIPICMethodBody, WIPICResult, WIPICWord};
 
@@ -13,9 +13,12 @@ pub trait WIPICContext: ByteRead + ByteWrite {
     fn free_raw(&mut self, address: WIPICWord) -> WIPICResult<()>;
     fn data_ptr(&self, memory: WIPICMemoryId) -> WIPICResult<WIPICWord>;
     fn register_function(&mut self, method: WIPICMethodBody) -> WIPICResult<WIPICWord>;
+    fn set_function_name(&mut self, address: WIPICWord, name: &str);
     async fn call_function(&mut self, address: WIPICWord, args: &[WIPICWord]) -> WIPICResult<WIPICWord>;
     fn system(&mut self) -> &mut System;
     fn spawn(&mut self, callback: WIPICMethodBody) -> WIPICResult<()>;
+    fn screen_framebuffer(&mut self) -> Option<WIPICMemoryId>;
+    fn set_screen_framebuffer(&mut self, framebuffer: WIPICMemoryId);
 }
 
 impl TypeConverter<WIPICWord> for WIPICWord {
@@ -58,7 +61,9 @@ impl TypeConverter<()> for () {
 
 impl TypeConverter<String> for String {
     fn to_rust(context: &mut dyn WIPICContext, raw: WIPICWord) -> String {
-        read_null_terminated_string(context, raw).unwrap()
+        let bytes = read_null_terminated_bytes(context, raw).unwrap();
+
+        context.system().decode_str(&bytes)
     }
 
     fn from_rust(_: &mut dyn WIPICContext, _: String) -> WIPICWord {
@@ -8,7 +8,7 @@ mod method;
 
 pub use self::{context::WIPICContext, error::WIPICError};
 
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec::Vec};
 
 use bytemuck::{Pod, Zeroable};
 
@@ -17,6 +17,8 @@ use crate::method::MethodBody;
 pub type WIPICResult<T> = core::result::Result<T, WIPICError>;
 pub type WIPICWord = u32; // wipi c is 32bit target
 pub type WIPICMethodBody = Box<dyn MethodBody<WIPICError>>;
+// methods of an interface by index, with their names in the WIPI C headers. empty if we don't know the name
+pub type WIPICMethodTable = Vec<(&'static str, WIPICMethodBody)>;
 
 #[derive(Clone, Copy, Pod, Zeroable)]
 #[repr(C)]
@@ -1,3 +1,4 @@
+use test_utils::TestPlatform;
 use wie_backend::System;
 use wie_util::{ByteRead, ByteWrite};
 use wie_wipi_c::{WIPICContext, WIPICMemoryId, WIPICMethodBody, WIPICResult, WIPICWord};
@@ -5,6 +6,8 @@ use wie_wipi_c::{WIPICContext, WIPICMemoryId, WIPICMethodBody, WIPICResult, WIPI
 pub struct TestContext {
     memory: [u8; 0x10000],
     last_alloc: usize,
+    system: System,
+    screen_framebuffer: Option<WIPICMemoryId>,
 }
 
 impl TestContext {
@@ -12,6 +15,8 @@ impl TestContext {
         Self {
             memory: [0; 0x10000],
             last_alloc: 0,
+            system: System::new(Box::new(TestPlatform), Box::new(())),
+            screen_framebuffer: None,
         }
     }
 }
@@ -45,17 +50,27 @@ impl WIPICContext for TestContext {
         todo!()
     }
 
+    fn set_function_name(&mut self, _address: WIPICWord, _name: &str) {}
+
     async fn call_function(&mut self, _address: WIPICWord, _args: &[WIPICWord]) -> WIPICResult<WIPICWord> {
         todo!()
     }
 
     fn system(&mut self) -> &mut System {
-        todo!()
+        &mut self.system
     }
 
     fn spawn(&mut self, _callback: WIPICMethodBody) -> WIPICResult<()> {
         todo!()
     }
+
+    fn screen_framebuffer(&mut self) -> Option<WIPICMemoryId> {
+        self.screen_framebuffer
+    }
+
+    fn set_screen_framebuffer(&mut self, framebuffer: WIPICMemoryId) {
+        self.screen_framebuffer = Some(framebuffer)
+    }
 }
 
 impl ByteWrite for TestContext {
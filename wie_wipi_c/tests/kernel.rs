@@ -1,4 +1,4 @@
-use wie_util::{read_null_terminated_string, write_null_terminated_string};
+use wie_util::{read_null_terminated_bytes, read_null_terminated_string, write_null_terminated_string, ByteWrite};
 use wie_wipi_c::{api::kernel::get_kernel_method_table, WIPICContext, WIPICError};
 
 mod context;
@@ -15,6 +15,7 @@ async fn test_sprintk() -> anyhow::Result<()> {
     let dest = context.alloc_raw(10).unwrap();
 
     kernel_methods[1]
+        .1
         .call(&mut context, Box::new([dest, format, 1234, 0, 0, 0, 0]))
         .await
         .unwrap();
@@ -25,3 +26,29 @@ async fn test_sprintk() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[futures_test::test]
+async fn test_sprintk_euc_kr() -> anyhow::Result<()> {
+    let mut context = context::TestContext::new();
+
+    let kernel_methods = get_kernel_method_table(|_: &mut dyn WIPICContext| async { Ok::<_, WIPICError>(()) });
+
+    // "점수 %d" and "점수 7" in EUC-KR
+    let format = context.alloc_raw(10).unwrap();
+    context.write_bytes(format, &[0xc1, 0xa1, 0xbc, 0xf6, b' ', b'%', b'd', 0]).unwrap();
+
+    let dest = context.alloc_raw(10).unwrap();
+
+    let length = kernel_methods[1]
+        .1
+        .call(&mut context, Box::new([dest, format, 7, 0, 0, 0, 0]))
+        .await
+        .unwrap();
+
+    let result = read_null_terminated_bytes(&context, dest).unwrap();
+
+    assert_eq!(result, [0xc1, 0xa1, 0xbc, 0xf6, b' ', b'7']);
+    assert_eq!(length, 6);
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use wie_backend::{ImportKind, ImportStatus};
+use wie_wipi_c::{api::stub::get_stub_method_table, WIPICContext};
+
+mod context;
+
+#[futures_test::test]
+async fn test_unknown_method() -> anyhow::Result<()> {
+    let mut context = context::TestContext::new();
+
+    let methods = get_stub_method_table(4);
+
+    let result = methods[3].1.call(&mut context, Box::new([1, 2, 3, 4, 0, 0, 0, 0, 0, 0])).await.unwrap();
+    assert_eq!(result, 0);
+
+    let names = context.system().report().names(ImportKind::Function);
+    assert_eq!(names, [("unknown4_3".to_string(), ImportStatus::Stub)]);
+
+    Ok(())
+}
@@ -25,11 +25,16 @@ impl HandsetProperty {
         }
     }
 
-    async fn get_system_property(jvm: &Jvm, _: &mut WIPIJavaContext, name: ClassInstanceRef<String>) -> JvmResult<ClassInstanceRef<String>> {
+    async fn get_system_property(jvm: &Jvm, context: &mut WIPIJavaContext, name: ClassInstanceRef<String>) -> JvmResult<ClassInstanceRef<String>> {
         let name = JavaLangString::to_rust_string(jvm, &name).await?;
-        tracing::warn!("stub org.kwis.msp.handset.HandsetProperty::getSystemProperty({})", name);
+        tracing::debug!("org.kwis.msp.handset.HandsetProperty::getSystemProperty({})", name);
 
-        let result = JavaLangString::from_rust_string(jvm, "").await?;
+        let value = context.system().platform().system_property(&name);
+        if value.is_none() {
+            tracing::warn!("Unknown system property {}", name);
+        }
+
+        let result = JavaLangString::from_rust_string(jvm, value.as_deref().unwrap_or("")).await?;
         Ok(result.into())
     }
 }
@@ -4,7 +4,7 @@ use java_class_proto::{JavaFieldProto, JavaMethodProto};
 use jvm::{ClassInstanceRef, Jvm, Result as JvmResult};
 
 use crate::{
-    classes::org::kwis::msp::lcdui::Display,
+    classes::org::kwis::msp::lcdui::{Display, EventQueue, Graphics},
     context::{WIPIJavaClassProto, WIPIJavaContext},
 };
 
@@ -26,6 +26,9 @@ impl Card {
                 JavaMethodProto::new("repaint", "(IIII)V", Self::repaint_with_area, Default::default()),
                 JavaMethodProto::new("repaint", "()V", Self::repaint, Default::default()),
                 JavaMethodProto::new("serviceRepaints", "()V", Self::service_repaints, Default::default()),
+                JavaMethodProto::new("pointerNotify", "(III)Z", Self::pointer_notify, Default::default()),
+                JavaMethodProto::new("keyNotify", "(II)Z", Self::key_notify, Default::default()),
+                JavaMethodProto::new("paint", "(Lorg/kwis/msp/lcdui/Graphics;)V", Self::paint, Default::default()),
             ],
             fields: vec![
                 JavaFieldProto::new("display", "Lorg/kwis/msp/lcdui/Display;", Default::default()),
@@ -85,10 +88,21 @@ impl Card {
         Ok(())
     }
 
-    async fn is_shown(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Card>) -> JvmResult<bool> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Card::isShown({:?})", &this);
+    // only the top card is shown, as cards cover the whole display
+    async fn is_shown(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Card>) -> JvmResult<bool> {
+        tracing::debug!("org.kwis.msp.lcdui.Card::isShown({:?})", &this);
 
-        Ok(true)
+        let display: ClassInstanceRef<Display> = jvm.get_field(&this, "display", "Lorg/kwis/msp/lcdui/Display;").await?;
+        if display.is_null() {
+            return Ok(false);
+        }
+
+        let top_card = Display::top_card(jvm, &display).await?;
+        if top_card.is_null() {
+            return Ok(false);
+        }
+
+        jvm.invoke_virtual(&top_card, "equals", "(Ljava/lang/Object;)Z", (this,)).await
     }
 
     async fn get_width(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Card>) -> JvmResult<i32> {
@@ -132,12 +146,30 @@ impl Card {
         Ok(())
     }
 
-    async fn service_repaints(_: &Jvm, context: &mut WIPIJavaContext, this: ClassInstanceRef<Card>) -> JvmResult<()> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Card::serviceRepaints({:?})", &this);
+    // paints and presents right away instead of waiting for the repaint event
+    async fn service_repaints(jvm: &Jvm, context: &mut WIPIJavaContext, this: ClassInstanceRef<Card>) -> JvmResult<()> {
+        tracing::debug!("org.kwis.msp.lcdui.Card::serviceRepaints({:?})", &this);
 
-        let mut platform = context.system().platform();
-        let screen = platform.screen();
-        screen.request_redraw().unwrap();
+        EventQueue::repaint(jvm, context).await
+    }
+
+    // default for cards not handling pointer
+    async fn pointer_notify(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Card>, r#type: i32, x: i32, y: i32) -> JvmResult<bool> {
+        tracing::debug!("org.kwis.msp.lcdui.Card::pointerNotify({:?}, {}, {}, {})", &this, r#type, x, y);
+
+        Ok(false)
+    }
+
+    // default for cards not handling keys, so the event goes on to the card below
+    async fn key_notify(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Card>, r#type: i32, chr: i32) -> JvmResult<bool> {
+        tracing::debug!("org.kwis.msp.lcdui.Card::keyNotify({:?}, {}, {})", &this, r#type, chr);
+
+        Ok(false)
+    }
+
+    // cards of the app override this. the event loop calls it virtually, so this runs only for cards without their own
+    async fn paint(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Card>, graphics: ClassInstanceRef<Graphics>) -> JvmResult<()> {
+        tracing::debug!("org.kwis.msp.lcdui.Card::paint({:?}, {:?})", &this, &graphics);
 
         Ok(())
     }
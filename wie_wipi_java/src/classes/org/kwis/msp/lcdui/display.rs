@@ -1,5 +1,4 @@
 use alloc::{boxed::Box, vec, vec::Vec};
-use core::iter;
 
 use java_class_proto::{JavaFieldProto, JavaMethodProto, MethodBody};
 use java_constants::MethodAccessFlags;
@@ -7,7 +6,7 @@ use java_runtime::classes::java::lang::{Object, Runnable, String};
 use jvm::{ClassInstanceRef, JavaError, JavaValue, Jvm, Result as JvmResult};
 
 use crate::{
-    classes::org::kwis::msp::lcdui::{Card, Jlet, JletEventListener},
+    classes::org::kwis::msp::lcdui::{Card, Image, Jlet, JletEventListener},
     context::{WIPIJavaClassProto, WIPIJavaContext},
 };
 
@@ -41,6 +40,8 @@ impl Display {
                 JavaMethodProto::new("isDoubleBuffered", "()Z", Self::is_double_buffered, Default::default()),
                 JavaMethodProto::new("getDockedCard", "()Lorg/kwis/msp/lcdui/Card;", Self::get_docked_card, Default::default()),
                 JavaMethodProto::new("pushCard", "(Lorg/kwis/msp/lcdui/Card;)V", Self::push_card, Default::default()),
+                JavaMethodProto::new("popCard", "()V", Self::pop_card, Default::default()),
+                JavaMethodProto::new("removeCard", "(Lorg/kwis/msp/lcdui/Card;)V", Self::remove_card, Default::default()),
                 JavaMethodProto::new("removeAllCards", "()V", Self::remove_all_cards, Default::default()),
                 JavaMethodProto::new(
                     "addJletEventListener",
@@ -63,6 +64,8 @@ impl Display {
                 JavaFieldProto::new("szCard", "I", Default::default()),
                 JavaFieldProto::new("m_w", "I", Default::default()),
                 JavaFieldProto::new("m_h", "I", Default::default()),
+                // drawn by graphics of the display, and presented to the screen when a paint is done
+                JavaFieldProto::new("backBuffer", "Lorg/kwis/msp/lcdui/Image;", Default::default()),
             ],
         }
     }
@@ -89,6 +92,16 @@ impl Display {
         jvm.put_field(&mut this, "m_w", "I", width as i32).await?;
         jvm.put_field(&mut this, "m_h", "I", height as i32).await?;
 
+        let back_buffer: ClassInstanceRef<Image> = jvm
+            .invoke_static(
+                "org/kwis/msp/lcdui/Image",
+                "createImage",
+                "(II)Lorg/kwis/msp/lcdui/Image;",
+                [width as i32, height as i32],
+            )
+            .await?;
+        jvm.put_field(&mut this, "backBuffer", "Lorg/kwis/msp/lcdui/Image;", back_buffer).await?;
+
         Ok(())
     }
 
@@ -126,30 +139,82 @@ impl Display {
     }
 
     async fn is_double_buffered(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<bool> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Display::isDoubleBuffered({:?})", &this);
+        tracing::debug!("org.kwis.msp.lcdui.Display::isDoubleBuffered({:?})", &this);
 
         Ok(true)
     }
 
-    async fn push_card(jvm: &Jvm, _: &mut WIPIJavaContext, mut this: ClassInstanceRef<Self>, c: ClassInstanceRef<Card>) -> JvmResult<()> {
+    async fn push_card(jvm: &Jvm, context: &mut WIPIJavaContext, this: ClassInstanceRef<Self>, c: ClassInstanceRef<Card>) -> JvmResult<()> {
         tracing::debug!("org.kwis.msp.lcdui.Display::pushCard({:?}, {:?})", &this, &c);
 
-        let mut cards = jvm.get_field(&this, "cards", "[Lorg/kwis/msp/lcdui/Card;").await?;
-        let card_size: i32 = jvm.get_field(&this, "szCard", "I").await?;
+        let mut cards = Self::cards(jvm, &this).await?;
+        cards.push(c);
 
-        let cards_data = jvm.load_array(&cards, 0, card_size as usize).await?;
-        let cards_data = cards_data.into_iter().chain(iter::once(c)).collect::<Vec<_>>();
+        Self::set_cards(jvm, context, this, cards).await
+    }
 
-        jvm.store_array(&mut cards, 0, cards_data).await?;
-        jvm.put_field(&mut this, "szCard", "I", card_size + 1).await?;
+    async fn pop_card(jvm: &Jvm, context: &mut WIPIJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        tracing::debug!("org.kwis.msp.lcdui.Display::popCard({:?})", &this);
 
-        Ok(())
+        let mut cards = Self::cards(jvm, &this).await?;
+        cards.pop();
+
+        Self::set_cards(jvm, context, this, cards).await
     }
 
-    async fn remove_all_cards(jvm: &Jvm, _: &mut WIPIJavaContext, mut this: ClassInstanceRef<Self>) -> JvmResult<()> {
+    async fn remove_card(jvm: &Jvm, context: &mut WIPIJavaContext, this: ClassInstanceRef<Self>, c: ClassInstanceRef<Card>) -> JvmResult<()> {
+        tracing::debug!("org.kwis.msp.lcdui.Display::removeCard({:?}, {:?})", &this, &c);
+
+        let mut remaining = Vec::new();
+        for card in Self::cards(jvm, &this).await? {
+            let same: bool = jvm.invoke_virtual(&card, "equals", "(Ljava/lang/Object;)Z", (c.clone(),)).await?;
+            if !same {
+                remaining.push(card);
+            }
+        }
+
+        Self::set_cards(jvm, context, this, remaining).await
+    }
+
+    async fn remove_all_cards(jvm: &Jvm, context: &mut WIPIJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<()> {
         tracing::debug!("org.kwis.msp.lcdui.Display::removeAllCards");
 
-        jvm.put_field(&mut this, "szCard", "I", 0).await?;
+        Self::set_cards(jvm, context, this, Vec::new()).await
+    }
+
+    // the last one is on top, which is the one painted and receiving events
+    pub async fn top_card(jvm: &Jvm, this: &ClassInstanceRef<Self>) -> JvmResult<ClassInstanceRef<Card>> {
+        Ok(Self::cards(jvm, this).await?.pop().unwrap_or_else(|| None.into()))
+    }
+
+    // bottom to top
+    pub async fn cards(jvm: &Jvm, this: &ClassInstanceRef<Self>) -> JvmResult<Vec<ClassInstanceRef<Card>>> {
+        let cards = jvm.get_field(this, "cards", "[Lorg/kwis/msp/lcdui/Card;").await?;
+        let card_size: i32 = jvm.get_field(this, "szCard", "I").await?;
+
+        jvm.load_array(&cards, 0, card_size as _).await
+    }
+
+    // grows the array when it's full, and repaints as the top card may have changed
+    async fn set_cards(
+        jvm: &Jvm,
+        context: &mut WIPIJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        cards: Vec<ClassInstanceRef<Card>>,
+    ) -> JvmResult<()> {
+        let mut array = jvm.get_field(&this, "cards", "[Lorg/kwis/msp/lcdui/Card;").await?;
+        let capacity = jvm.array_length(&array).await?;
+
+        if cards.len() > capacity {
+            array = jvm.instantiate_array("Lorg/kwis/msp/lcdui/Card;", capacity * 2).await?;
+            jvm.put_field(&mut this, "cards", "[Lorg/kwis/msp/lcdui/Card;", array.clone()).await?;
+        }
+
+        let card_size = cards.len() as i32;
+        jvm.store_array(&mut array, 0, cards).await?;
+        jvm.put_field(&mut this, "szCard", "I", card_size).await?;
+
+        context.system().platform().screen().request_redraw().unwrap();
 
         Ok(())
     }
@@ -193,7 +258,7 @@ impl Display {
         #[async_trait::async_trait(?Send)]
         impl MethodBody<JavaError, WIPIJavaContext> for SpawnProxy {
             async fn call(&self, jvm: &Jvm, context: &mut WIPIJavaContext, _: Box<[JavaValue]>) -> Result<JavaValue, JavaError> {
-                let until = context.system().platform().now() + 16; // TODO
+                let until = context.system().now() + 16; // TODO
                 context.system().sleep(until).await;
 
                 jvm.invoke_virtual(&self.runnable, "run", "()V", ()).await?;
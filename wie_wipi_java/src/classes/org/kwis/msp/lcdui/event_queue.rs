@@ -1,4 +1,4 @@
-use alloc::{vec, vec::Vec};
+use alloc::vec;
 
 use java_class_proto::JavaMethodProto;
 use jvm::{Array, ClassInstanceRef, Jvm, Result as JvmResult};
@@ -6,14 +6,18 @@ use jvm::{Array, ClassInstanceRef, Jvm, Result as JvmResult};
 use wie_backend::{Event, KeyCode};
 
 use crate::{
-    classes::org::kwis::msp::lcdui::{Card, Display, Image, Jlet},
+    classes::org::kwis::msp::lcdui::{Display, Image, Jlet},
     context::{WIPIJavaClassProto, WIPIJavaContext},
 };
 
 #[repr(i32)]
 enum EventQueueEvent {
     KeyEvent = 1,
+    PointerEvent = 2,
     RepaintEvent = 41,
+    // not from WIPI, only passed from getNextEvent to dispatchEvent
+    SuspendEvent = 100,
+    ResumeEvent = 101,
 }
 
 impl EventQueueEvent {
@@ -38,6 +42,21 @@ impl KeyboardEventType {
     }
 }
 
+#[repr(i32)]
+#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+enum PointerEventType {
+    PointerPressed = 1,
+    PointerReleased = 2,
+    PointerDragged = 3,
+}
+
+impl PointerEventType {
+    fn from_raw(raw: i32) -> Self {
+        unsafe { core::mem::transmute(raw) }
+    }
+}
+
 #[repr(i32)]
 #[allow(clippy::upper_case_acronyms)]
 enum WIPIKeyCode {
@@ -46,6 +65,9 @@ enum WIPIKeyCode {
     LEFT = -3,
     RIGHT = -4,
     FIRE = -5, // Ok
+    SOFT1 = -6,
+    SOFT2 = -7,
+    CLEAR = -8,
 
     NUM0 = 48,
     NUM1 = 49,
@@ -81,6 +103,9 @@ impl WIPIKeyCode {
             KeyCode::NUM9 => Self::NUM9,
             KeyCode::HASH => Self::HASH,
             KeyCode::STAR => Self::STAR,
+            KeyCode::LEFT_SOFT_KEY => Self::SOFT1,
+            KeyCode::RIGHT_SOFT_KEY => Self::SOFT2,
+            KeyCode::CLEAR => Self::CLEAR,
         }
     }
 }
@@ -134,13 +159,32 @@ impl EventQueue {
                         WIPIKeyCode::from_key_code(x) as _,
                         0,
                     ],
+                    Event::PointerDown { x, y } => vec![EventQueueEvent::PointerEvent as _, PointerEventType::PointerPressed as _, x as _, y as _],
+                    Event::PointerMove { x, y } => vec![EventQueueEvent::PointerEvent as _, PointerEventType::PointerDragged as _, x as _, y as _],
+                    Event::PointerUp { x, y } => vec![EventQueueEvent::PointerEvent as _, PointerEventType::PointerReleased as _, x as _, y as _],
+                    Event::Char(x) => match Self::char_code(context, x) {
+                        Some(code) => vec![EventQueueEvent::KeyEvent as _, KeyboardEventType::KeyTyped as _, code, 0],
+                        None => {
+                            tracing::warn!("Character {:?} is not representable in KSC5601", x);
+
+                            continue;
+                        }
+                    },
+                    Event::Suspend => vec![EventQueueEvent::SuspendEvent as _, 0, 0, 0],
+                    Event::Resume => vec![EventQueueEvent::ResumeEvent as _, 0, 0, 0],
+                    // java apps get these through listeners of each subsystem, not from the event queue
+                    Event::TimerExpired { .. } | Event::AudioComplete { .. } | Event::NetworkReady { .. } => {
+                        tracing::debug!("Ignoring {:?}, no listener is registered", x);
+
+                        continue;
+                    }
                 };
 
                 jvm.store_array(&mut event, 0, event_data).await?;
 
                 break;
             } else {
-                let until = context.system().platform().now() + 16;
+                let until = context.system().now() + 16;
                 context.system().sleep(until).await; // TODO we need to wait for events
             }
         }
@@ -148,6 +192,18 @@ impl EventQueue {
         Ok(())
     }
 
+    // typed characters are delivered in KSC5601, two bytes for hangul and one for ascii
+    fn char_code(context: &mut WIPIJavaContext, character: char) -> Option<i32> {
+        let mut buf = [0; 4];
+        let bytes = context.system().encode_str(character.encode_utf8(&mut buf));
+
+        match bytes[..] {
+            [x] if x.is_ascii() => Some(x as _),
+            [high, low] if high >= 0x80 => Some(((high as i32) << 8) | low as i32),
+            _ => None,
+        }
+    }
+
     async fn dispatch_event(
         jvm: &Jvm,
         context: &mut WIPIJavaContext,
@@ -169,61 +225,96 @@ impl EventQueue {
                 tracing::debug!("KeyEvent {:?} {}", event_type, code);
                 Self::key_event(jvm, event_type, code).await?;
             }
+            EventQueueEvent::PointerEvent => {
+                let event_type = PointerEventType::from_raw(event[1]);
+
+                tracing::debug!("PointerEvent {:?} {} {}", event_type, event[2], event[3]);
+                Self::pointer_event(jvm, event_type, event[2], event[3]).await?;
+            }
+            EventQueueEvent::SuspendEvent => {
+                tracing::debug!("SuspendEvent");
+                Self::lifecycle_event(jvm, "pauseApp").await?;
+            }
+            EventQueueEvent::ResumeEvent => {
+                tracing::debug!("ResumeEvent");
+                Self::lifecycle_event(jvm, "resumeApp").await?;
+            }
         }
 
         Ok(())
     }
 
+    // cards get the event from the top until one of them handles it
     async fn key_event(jvm: &Jvm, event_type: KeyboardEventType, code: i32) -> JvmResult<()> {
         let display = Self::get_current_display(jvm).await?;
         if display.is_null() {
             return Ok(());
         }
 
-        let card = Self::get_top_card(jvm, &display).await?;
-        if card.is_null() {
+        let event_type = event_type as i32;
+        for card in Display::cards(jvm, &display).await?.into_iter().rev() {
+            let handled: bool = jvm.invoke_virtual(&card, "keyNotify", "(II)Z", (event_type, code)).await?;
+            if handled {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn pointer_event(jvm: &Jvm, event_type: PointerEventType, x: i32, y: i32) -> JvmResult<()> {
+        let display = Self::get_current_display(jvm).await?;
+        if display.is_null() {
             return Ok(());
         }
 
-        let _: bool = jvm.invoke_virtual(&card, "keyNotify", "(II)Z", (event_type as i32, code)).await?;
+        let event_type = event_type as i32;
+        for card in Display::cards(jvm, &display).await?.into_iter().rev() {
+            let handled: bool = jvm.invoke_virtual(&card, "pointerNotify", "(III)Z", (event_type, x, y)).await?;
+            if handled {
+                break;
+            }
+        }
 
         Ok(())
     }
 
-    async fn repaint(jvm: &Jvm, context: &mut WIPIJavaContext) -> JvmResult<()> {
+    // pauseApp and resumeApp are abstract in Jlet, so every app implements them
+    async fn lifecycle_event(jvm: &Jvm, method: &str) -> JvmResult<()> {
+        let jlet: ClassInstanceRef<Jlet> = jvm
+            .invoke_static("org/kwis/msp/lcdui/Jlet", "getActiveJlet", "()Lorg/kwis/msp/lcdui/Jlet;", [])
+            .await?;
+        if jlet.is_null() {
+            return Ok(());
+        }
+
+        jvm.invoke_virtual(&jlet, method, "()V", ()).await
+    }
+
+    // paints the top card to the back buffer of the display, and presents it once the card is done.
+    // the back buffer keeps the previous frame, for the cards redrawing only what has changed
+    pub async fn repaint(jvm: &Jvm, context: &mut WIPIJavaContext) -> JvmResult<()> {
         let display = Self::get_current_display(jvm).await?;
         if display.is_null() {
             return Ok(());
         }
 
-        let card = Self::get_top_card(jvm, &display).await?;
+        let card = Display::top_card(jvm, &display).await?;
         if card.is_null() {
             return Ok(());
         }
 
-        let mut graphics = jvm
-            .new_class("org/kwis/msp/lcdui/Graphics", "(Lorg/kwis/msp/lcdui/Display;)V", (display,))
+        let graphics = jvm
+            .new_class("org/kwis/msp/lcdui/Graphics", "(Lorg/kwis/msp/lcdui/Display;)V", (display.clone(),))
             .await?;
 
-        jvm.invoke_virtual(&card, "paint", "(Lorg/kwis/msp/lcdui/Graphics;)V", [graphics.clone().into()])
+        jvm.invoke_virtual(&card, "paint", "(Lorg/kwis/msp/lcdui/Graphics;)V", [graphics.into()])
             .await?;
 
-        let java_image: ClassInstanceRef<Image> = jvm.get_field(&graphics, "img", "Lorg/kwis/msp/lcdui/Image;").await?;
+        let back_buffer: ClassInstanceRef<Image> = jvm.get_field(&display, "backBuffer", "Lorg/kwis/msp/lcdui/Image;").await?;
+        let image = Image::image(jvm, &back_buffer).await?;
 
-        if !java_image.is_null() {
-            let image = Image::image(jvm, &java_image).await?;
-
-            // TODO temporary until we have correct gc
-            let image_data = jvm.get_field(&java_image, "imgData", "[B").await?;
-            jvm.destroy(image_data)?;
-            jvm.destroy(java_image.into())?;
-            jvm.put_field(&mut graphics, "img", "Lorg/kwis/msp/lcdui/Image;", None).await?;
-
-            let mut platform = context.system().platform();
-            let screen = platform.screen();
-
-            screen.paint(&*image);
-        }
+        context.system().repaint(&*image);
 
         Ok(())
     }
@@ -235,16 +326,4 @@ impl EventQueue {
 
         jvm.get_field(&jlet, "dis", "Lorg/kwis/msp/lcdui/Display;").await
     }
-
-    async fn get_top_card(jvm: &Jvm, display: &ClassInstanceRef<Display>) -> JvmResult<ClassInstanceRef<Card>> {
-        let cards = jvm.get_field(display, "cards", "[Lorg/kwis/msp/lcdui/Card;").await?;
-        let card_size: i32 = jvm.get_field(display, "szCard", "I").await?;
-
-        if card_size > 0 {
-            let card_data: Vec<ClassInstanceRef<Card>> = jvm.load_array(&cards, 0, card_size as _).await?;
-            Ok(card_data[card_size as usize - 1].clone())
-        } else {
-            Ok(None.into())
-        }
-    }
 }
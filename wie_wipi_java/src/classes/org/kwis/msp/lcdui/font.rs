@@ -2,10 +2,24 @@ use alloc::vec;
 
 use java_class_proto::{JavaFieldProto, JavaMethodProto};
 use java_constants::{FieldAccessFlags, MethodAccessFlags};
-use jvm::{ClassInstanceRef, Jvm, Result as JvmResult};
+use java_runtime::classes::java::lang::String;
+use jvm::{runtime::JavaLangString, ClassInstanceRef, Jvm, Result as JvmResult};
+
+use wie_backend::canvas::{Font as BackendFont, FontFace, FontSize, FontStyle};
 
 use crate::context::{WIPIJavaClassProto, WIPIJavaContext};
 
+const FACE_SYSTEM: i32 = 0;
+const FACE_MONOSPACE: i32 = 32;
+const FACE_PROPORTIONAL: i32 = 64;
+const STYLE_PLAIN: i32 = 0;
+const STYLE_BOLD: i32 = 1;
+const STYLE_ITALIC: i32 = 2;
+const STYLE_UNDERLINED: i32 = 4;
+const SIZE_SMALL: i32 = 8;
+const SIZE_MEDIUM: i32 = 0;
+const SIZE_LARGE: i32 = 16;
+
 // class org.kwis.msp.lcdui.Font
 pub struct Font {}
 
@@ -16,8 +30,13 @@ impl Font {
             interfaces: vec![],
             methods: vec![
                 JavaMethodProto::new("<clinit>", "()V", Self::cl_init, MethodAccessFlags::STATIC),
-                JavaMethodProto::new("<init>", "()V", Self::init, Default::default()),
+                JavaMethodProto::new("<init>", "(III)V", Self::init, Default::default()),
                 JavaMethodProto::new("getHeight", "()I", Self::get_height, Default::default()),
+                JavaMethodProto::new("getBaselinePosition", "()I", Self::get_baseline_position, Default::default()),
+                JavaMethodProto::new("stringWidth", "(Ljava/lang/String;)I", Self::string_width, Default::default()),
+                JavaMethodProto::new("getFace", "()I", Self::get_face, Default::default()),
+                JavaMethodProto::new("getStyle", "()I", Self::get_style, Default::default()),
+                JavaMethodProto::new("getSize", "()I", Self::get_size, Default::default()),
                 JavaMethodProto::new(
                     "getDefaultFont",
                     "()Lorg/kwis/msp/lcdui/Font;",
@@ -28,8 +47,18 @@ impl Font {
             ],
             fields: vec![
                 JavaFieldProto::new("FACE_SYSTEM", "I", FieldAccessFlags::STATIC),
+                JavaFieldProto::new("FACE_MONOSPACE", "I", FieldAccessFlags::STATIC),
+                JavaFieldProto::new("FACE_PROPORTIONAL", "I", FieldAccessFlags::STATIC),
                 JavaFieldProto::new("STYLE_PLAIN", "I", FieldAccessFlags::STATIC),
+                JavaFieldProto::new("STYLE_BOLD", "I", FieldAccessFlags::STATIC),
+                JavaFieldProto::new("STYLE_ITALIC", "I", FieldAccessFlags::STATIC),
+                JavaFieldProto::new("STYLE_UNDERLINED", "I", FieldAccessFlags::STATIC),
                 JavaFieldProto::new("SIZE_SMALL", "I", FieldAccessFlags::STATIC),
+                JavaFieldProto::new("SIZE_MEDIUM", "I", FieldAccessFlags::STATIC),
+                JavaFieldProto::new("SIZE_LARGE", "I", FieldAccessFlags::STATIC),
+                JavaFieldProto::new("face", "I", Default::default()),
+                JavaFieldProto::new("style", "I", Default::default()),
+                JavaFieldProto::new("size", "I", Default::default()),
             ],
         }
     }
@@ -37,38 +66,111 @@ impl Font {
     async fn cl_init(jvm: &Jvm, _: &mut WIPIJavaContext) -> JvmResult<()> {
         tracing::debug!("org.kwis.msp.lcdui.Font::<clinit>");
 
-        jvm.put_static_field("org/kwis/msp/lcdui/Font", "FACE_SYSTEM", "I", 0).await?;
-        jvm.put_static_field("org/kwis/msp/lcdui/Font", "STYLE_PLAIN", "I", 0).await?;
-        jvm.put_static_field("org/kwis/msp/lcdui/Font", "SIZE_SMALL", "I", 8).await?;
+        for (name, value) in [
+            ("FACE_SYSTEM", FACE_SYSTEM),
+            ("FACE_MONOSPACE", FACE_MONOSPACE),
+            ("FACE_PROPORTIONAL", FACE_PROPORTIONAL),
+            ("STYLE_PLAIN", STYLE_PLAIN),
+            ("STYLE_BOLD", STYLE_BOLD),
+            ("STYLE_ITALIC", STYLE_ITALIC),
+            ("STYLE_UNDERLINED", STYLE_UNDERLINED),
+            ("SIZE_SMALL", SIZE_SMALL),
+            ("SIZE_MEDIUM", SIZE_MEDIUM),
+            ("SIZE_LARGE", SIZE_LARGE),
+        ] {
+            jvm.put_static_field("org/kwis/msp/lcdui/Font", name, "I", value).await?;
+        }
 
         Ok(())
     }
 
-    async fn init(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Font>) -> JvmResult<()> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Font::<init>({:?})", &this);
+    async fn init(jvm: &Jvm, _: &mut WIPIJavaContext, mut this: ClassInstanceRef<Font>, face: i32, style: i32, size: i32) -> JvmResult<()> {
+        tracing::debug!("org.kwis.msp.lcdui.Font::<init>({:?}, {}, {}, {})", &this, face, style, size);
+
+        jvm.put_field(&mut this, "face", "I", face).await?;
+        jvm.put_field(&mut this, "style", "I", style).await?;
+        jvm.put_field(&mut this, "size", "I", size).await?;
 
         Ok(())
     }
 
-    async fn get_height(_: &Jvm, _: &mut WIPIJavaContext) -> JvmResult<i32> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Font::getHeight");
+    async fn get_height(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Font>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Font::getHeight({:?})", &this);
+
+        Ok(Self::font(jvm, &this).await?.height() as _)
+    }
+
+    async fn get_baseline_position(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Font>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Font::getBaselinePosition({:?})", &this);
+
+        Ok(Self::font(jvm, &this).await?.ascent() as _)
+    }
+
+    async fn string_width(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Font>, string: ClassInstanceRef<String>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Font::stringWidth({:?}, {:?})", &this, &string);
+
+        let string = JavaLangString::to_rust_string(jvm, &string).await?;
+
+        Ok(Self::font(jvm, &this).await?.string_width(&string) as _)
+    }
+
+    async fn get_face(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Font>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Font::getFace({:?})", &this);
+
+        jvm.get_field(&this, "face", "I").await
+    }
+
+    async fn get_style(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Font>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Font::getStyle({:?})", &this);
 
-        Ok(12) // TODO: hardcoded
+        jvm.get_field(&this, "style", "I").await
+    }
+
+    async fn get_size(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Font>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Font::getSize({:?})", &this);
+
+        jvm.get_field(&this, "size", "I").await
     }
 
     async fn get_default_font(jvm: &Jvm, _: &mut WIPIJavaContext) -> JvmResult<ClassInstanceRef<Self>> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Font::getDefaultFont");
+        tracing::debug!("org.kwis.msp.lcdui.Font::getDefaultFont");
 
-        let instance = jvm.new_class("org/kwis/msp/lcdui/Font", "()V", []).await?;
+        let instance = jvm
+            .new_class("org/kwis/msp/lcdui/Font", "(III)V", (FACE_SYSTEM, STYLE_PLAIN, SIZE_MEDIUM))
+            .await?;
 
         Ok(instance.into())
     }
 
     async fn get_font(jvm: &Jvm, _: &mut WIPIJavaContext, face: i32, style: i32, size: i32) -> JvmResult<ClassInstanceRef<Font>> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Font::getFont({:?}, {:?}, {:?})", face, style, size);
+        tracing::debug!("org.kwis.msp.lcdui.Font::getFont({}, {}, {})", face, style, size);
 
-        let instance = jvm.new_class("org/kwis/msp/lcdui/Font", "()V", []).await?;
+        let instance = jvm.new_class("org/kwis/msp/lcdui/Font", "(III)V", (face, style, size)).await?;
 
         Ok(instance.into())
     }
+
+    pub async fn font(jvm: &Jvm, this: &ClassInstanceRef<Font>) -> JvmResult<BackendFont> {
+        let face: i32 = jvm.get_field(this, "face", "I").await?;
+        let style: i32 = jvm.get_field(this, "style", "I").await?;
+        let size: i32 = jvm.get_field(this, "size", "I").await?;
+
+        let face = match face {
+            FACE_MONOSPACE => FontFace::Monospace,
+            FACE_PROPORTIONAL => FontFace::Proportional,
+            _ => FontFace::System,
+        };
+        let style = FontStyle {
+            bold: style & STYLE_BOLD != 0,
+            italic: style & STYLE_ITALIC != 0,
+            underlined: style & STYLE_UNDERLINED != 0,
+        };
+        let size = match size {
+            SIZE_SMALL => FontSize::Small,
+            SIZE_LARGE => FontSize::Large,
+            _ => FontSize::Medium,
+        };
+
+        Ok(BackendFont::new(face, style, size))
+    }
 }
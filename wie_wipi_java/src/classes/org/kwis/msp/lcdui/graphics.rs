@@ -1,11 +1,11 @@
 use alloc::{format, vec, vec::Vec};
 
 use bytemuck::cast_vec;
-use jvm::{runtime::JavaLangString, JavaValue};
+use jvm::runtime::JavaLangString;
 
-use wie_backend::canvas::{PixelType, Rgb8Pixel, TextAlignment, VecImageBuffer};
+use wie_backend::canvas::{Anchor, PixelType, Rect, Rgb8Pixel, Transform, VecImageBuffer};
 
-use java_class_proto::{JavaFieldProto, JavaMethodProto, TypeConverter};
+use java_class_proto::{JavaFieldProto, JavaMethodProto};
 use java_runtime::classes::java::lang::String;
 
 use jvm::{Array, ClassInstanceRef, Jvm, Result as JvmResult};
@@ -15,29 +15,6 @@ use crate::{
     context::{WIPIJavaClassProto, WIPIJavaContext},
 };
 
-bitflags::bitflags! {
-    struct Anchor: i32 {
-        const TOP = 0;
-        const HCENTER = 1;
-        const VCENTER = 2;
-        const LEFT = 4;
-        const RIGHT = 8;
-        const BOTTOM = 32;
-        const BASELINE = 64;
-    }
-}
-
-impl TypeConverter<Anchor> for Anchor {
-    fn to_rust(_: &Jvm, raw: JavaValue) -> Anchor {
-        let raw: i32 = raw.into();
-        Anchor::from_bits_retain(raw)
-    }
-
-    fn from_rust(_: &Jvm, rust: Anchor) -> JavaValue {
-        rust.bits().into()
-    }
-}
-
 // class org.kwis.msp.lcdui.Graphics
 pub struct Graphics {}
 
@@ -52,13 +29,30 @@ impl Graphics {
                 JavaMethodProto::new("getFont", "()Lorg/kwis/msp/lcdui/Font;", Self::get_font, Default::default()),
                 JavaMethodProto::new("setColor", "(I)V", Self::set_color, Default::default()),
                 JavaMethodProto::new("setColor", "(III)V", Self::set_color_by_rgb, Default::default()),
+                JavaMethodProto::new("getColor", "()I", Self::get_color, Default::default()),
+                JavaMethodProto::new("getRedComponent", "()I", Self::get_red_component, Default::default()),
+                JavaMethodProto::new("getGreenComponent", "()I", Self::get_green_component, Default::default()),
+                JavaMethodProto::new("getBlueComponent", "()I", Self::get_blue_component, Default::default()),
                 JavaMethodProto::new("setFont", "(Lorg/kwis/msp/lcdui/Font;)V", Self::set_font, Default::default()),
                 JavaMethodProto::new("setAlpha", "(I)V", Self::set_alpha, Default::default()),
                 JavaMethodProto::new("fillRect", "(IIII)V", Self::fill_rect, Default::default()),
                 JavaMethodProto::new("drawLine", "(IIII)V", Self::draw_line, Default::default()),
                 JavaMethodProto::new("drawRect", "(IIII)V", Self::draw_rect, Default::default()),
+                JavaMethodProto::new("drawRoundRect", "(IIIIII)V", Self::draw_round_rect, Default::default()),
+                JavaMethodProto::new("fillRoundRect", "(IIIIII)V", Self::fill_round_rect, Default::default()),
+                JavaMethodProto::new("drawArc", "(IIIIII)V", Self::draw_arc, Default::default()),
+                JavaMethodProto::new("fillArc", "(IIIIII)V", Self::fill_arc, Default::default()),
+                JavaMethodProto::new("drawPolygon", "([I[II)V", Self::draw_polygon, Default::default()),
+                JavaMethodProto::new("fillPolygon", "([I[II)V", Self::fill_polygon, Default::default()),
                 JavaMethodProto::new("drawString", "(Ljava/lang/String;III)V", Self::draw_string, Default::default()),
                 JavaMethodProto::new("drawImage", "(Lorg/kwis/msp/lcdui/Image;III)V", Self::draw_image, Default::default()),
+                JavaMethodProto::new(
+                    "drawRegion",
+                    "(Lorg/kwis/msp/lcdui/Image;IIIIIIII)V",
+                    Self::draw_region,
+                    Default::default(),
+                ),
+                JavaMethodProto::new("copyArea", "(IIIIIII)V", Self::copy_area, Default::default()),
                 JavaMethodProto::new("setClip", "(IIII)V", Self::set_clip, Default::default()),
                 JavaMethodProto::new("clipRect", "(IIII)V", Self::clip_rect, Default::default()),
                 JavaMethodProto::new("getClipX", "()I", Self::get_clip_x, Default::default()),
@@ -75,6 +69,13 @@ impl Graphics {
                 JavaFieldProto::new("w", "I", Default::default()),
                 JavaFieldProto::new("h", "I", Default::default()),
                 JavaFieldProto::new("rgb", "I", Default::default()),
+                JavaFieldProto::new("font", "Lorg/kwis/msp/lcdui/Font;", Default::default()),
+                JavaFieldProto::new("clipX", "I", Default::default()),
+                JavaFieldProto::new("clipY", "I", Default::default()),
+                JavaFieldProto::new("clipW", "I", Default::default()),
+                JavaFieldProto::new("clipH", "I", Default::default()),
+                JavaFieldProto::new("translateX", "I", Default::default()),
+                JavaFieldProto::new("translateY", "I", Default::default()),
             ],
         }
     }
@@ -85,9 +86,13 @@ impl Graphics {
 
         let width: i32 = jvm.get_field(&display, "m_w", "I").await?;
         let height: i32 = jvm.get_field(&display, "m_h", "I").await?;
+        // drawing goes to the back buffer, so the screen never shows a half drawn frame
+        let back_buffer: ClassInstanceRef<Image> = jvm.get_field(&display, "backBuffer", "Lorg/kwis/msp/lcdui/Image;").await?;
 
+        jvm.put_field(&mut this, "img", "Lorg/kwis/msp/lcdui/Image;", back_buffer).await?;
         jvm.put_field(&mut this, "w", "I", width).await?;
         jvm.put_field(&mut this, "h", "I", height).await?;
+        Self::put_clip(jvm, &mut this, Rect::new(0, 0, width as _, height as _)).await?;
 
         Ok(())
     }
@@ -116,16 +121,15 @@ impl Graphics {
         jvm.put_field(&mut this, "img", "Lorg/kwis/msp/lcdui/Image;", image).await?;
         jvm.put_field(&mut this, "w", "I", width).await?;
         jvm.put_field(&mut this, "h", "I", height).await?;
+        Self::put_clip(jvm, &mut this, Rect::new(0, 0, width.max(0) as _, height.max(0) as _)).await?;
 
         Ok(())
     }
 
     async fn get_font(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<ClassInstanceRef<Font>> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Graphics::getFont({:?})", &this);
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::getFont({:?})", &this);
 
-        let instance = jvm.new_class("org/kwis/msp/lcdui/Font", "()V", []).await?;
-
-        Ok(instance.into())
+        Self::current_font(jvm, &this).await
     }
 
     async fn set_color(jvm: &Jvm, _: &mut WIPIJavaContext, mut this: ClassInstanceRef<Self>, rgb: i32) -> JvmResult<()> {
@@ -139,17 +143,47 @@ impl Graphics {
     async fn set_color_by_rgb(jvm: &Jvm, _: &mut WIPIJavaContext, mut this: ClassInstanceRef<Graphics>, r: i32, g: i32, b: i32) -> JvmResult<()> {
         tracing::debug!("org.kwis.msp.lcdui.Graphics::setColor({:?}, {}, {}, {})", &this, r, g, b);
 
-        let rgb = (r << 16) | (g << 8) | b;
+        let rgb = ((r & 0xff) << 16) | ((g & 0xff) << 8) | (b & 0xff);
 
         jvm.put_field(&mut this, "rgb", "I", rgb).await?;
 
         Ok(())
     }
 
-    async fn set_font(_jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>, font: ClassInstanceRef<Font>) -> JvmResult<()> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Graphics::setFont({:?}, {:?})", &this, &font);
+    async fn get_color(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::getColor({:?})", &this);
 
-        Ok(())
+        jvm.get_field(&this, "rgb", "I").await
+    }
+
+    async fn get_red_component(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::getRedComponent({:?})", &this);
+
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+
+        Ok((rgb >> 16) & 0xff)
+    }
+
+    async fn get_green_component(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::getGreenComponent({:?})", &this);
+
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+
+        Ok((rgb >> 8) & 0xff)
+    }
+
+    async fn get_blue_component(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::getBlueComponent({:?})", &this);
+
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+
+        Ok(rgb & 0xff)
+    }
+
+    async fn set_font(jvm: &Jvm, _: &mut WIPIJavaContext, mut this: ClassInstanceRef<Graphics>, font: ClassInstanceRef<Font>) -> JvmResult<()> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::setFont({:?}, {:?})", &this, &font);
+
+        jvm.put_field(&mut this, "font", "Lorg/kwis/msp/lcdui/Font;", font).await
     }
 
     async fn set_alpha(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>, a1: i32) -> JvmResult<()> {
@@ -158,33 +192,184 @@ impl Graphics {
         Ok(())
     }
 
-    async fn set_clip(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>, x: i32, y: i32, width: i32, height: i32) -> JvmResult<()> {
-        tracing::warn!(
-            "stub org.kwis.msp.lcdui.Graphics::setClip({:?}, {}, {}, {}, {})",
+    #[allow(clippy::too_many_arguments)]
+    async fn set_clip(
+        jvm: &Jvm,
+        _: &mut WIPIJavaContext,
+        mut this: ClassInstanceRef<Graphics>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::setClip({:?}, {}, {}, {}, {})", &this, x, y, width, height);
+
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
+        Self::put_clip(jvm, &mut this, Rect::new(x, y, width.max(0) as _, height.max(0) as _)).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn clip_rect(
+        jvm: &Jvm,
+        _: &mut WIPIJavaContext,
+        mut this: ClassInstanceRef<Graphics>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::clipRect({:?}, {}, {}, {}, {})", &this, x, y, width, height);
+
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
+        let clip = Self::clip(jvm, &this)
+            .await?
+            .intersection(&Rect::new(x, y, width.max(0) as _, height.max(0) as _));
+
+        Self::put_clip(jvm, &mut this, clip).await
+    }
+
+    async fn fill_rect(
+        jvm: &Jvm,
+        _: &mut WIPIJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::fillRect({:?}, {}, {}, {}, {})", &this, x, y, width, height);
+
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
+
+        let image = Self::image(jvm, &mut this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
+
+        canvas.fill_rect(x as _, y as _, width as _, height as _, Rgb8Pixel::to_color(rgb as _));
+
+        canvas.flush().await;
+
+        Ok(())
+    }
+
+    async fn draw_rect(
+        jvm: &Jvm,
+        _: &mut WIPIJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::drawRect({:?}, {}, {}, {}, {})", &this, x, y, width, height);
+
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
+
+        let image = Self::image(jvm, &mut this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
+
+        canvas.draw_rect(x as _, y as _, width as _, height as _, Rgb8Pixel::to_color(rgb as _));
+
+        canvas.flush().await;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn draw_round_rect(
+        jvm: &Jvm,
+        _: &mut WIPIJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        arc_width: i32,
+        arc_height: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "org.kwis.msp.lcdui.Graphics::drawRoundRect({:?}, {}, {}, {}, {}, {}, {})",
             &this,
             x,
             y,
             width,
-            height
+            height,
+            arc_width,
+            arc_height
         );
 
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
+
+        let image = Self::image(jvm, &mut this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
+
+        canvas.draw_round_rect(
+            x,
+            y,
+            width.max(0) as _,
+            height.max(0) as _,
+            arc_width.max(0) as _,
+            arc_height.max(0) as _,
+            Rgb8Pixel::to_color(rgb as _),
+        );
+
+        canvas.flush().await;
+
         Ok(())
     }
 
-    async fn clip_rect(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>, x: i32, y: i32, width: i32, height: i32) -> JvmResult<()> {
-        tracing::warn!(
-            "stub org.kwis.msp.lcdui.Graphics::clipRect({:?}, {}, {}, {}, {})",
+    #[allow(clippy::too_many_arguments)]
+    async fn fill_round_rect(
+        jvm: &Jvm,
+        _: &mut WIPIJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        arc_width: i32,
+        arc_height: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "org.kwis.msp.lcdui.Graphics::fillRoundRect({:?}, {}, {}, {}, {}, {}, {})",
             &this,
             x,
             y,
             width,
-            height
+            height,
+            arc_width,
+            arc_height
         );
 
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
+
+        let image = Self::image(jvm, &mut this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
+
+        canvas.fill_round_rect(
+            x,
+            y,
+            width.max(0) as _,
+            height.max(0) as _,
+            arc_width.max(0) as _,
+            arc_height.max(0) as _,
+            Rgb8Pixel::to_color(rgb as _),
+        );
+
+        canvas.flush().await;
+
         Ok(())
     }
 
-    async fn fill_rect(
+    #[allow(clippy::too_many_arguments)]
+    async fn draw_arc(
         jvm: &Jvm,
         _: &mut WIPIJavaContext,
         mut this: ClassInstanceRef<Self>,
@@ -192,22 +377,44 @@ impl Graphics {
         y: i32,
         width: i32,
         height: i32,
+        start_angle: i32,
+        arc_angle: i32,
     ) -> JvmResult<()> {
-        tracing::debug!("org.kwis.msp.lcdui.Graphics::fillRect({:?}, {}, {}, {}, {})", &this, x, y, width, height);
+        tracing::debug!(
+            "org.kwis.msp.lcdui.Graphics::drawArc({:?}, {}, {}, {}, {}, {}, {})",
+            &this,
+            x,
+            y,
+            width,
+            height,
+            start_angle,
+            arc_angle
+        );
 
         let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
 
         let image = Self::image(jvm, &mut this).await?;
         let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
 
-        canvas.fill_rect(x as _, y as _, width as _, height as _, Rgb8Pixel::to_color(rgb as _));
+        canvas.draw_arc(
+            x,
+            y,
+            width.max(0) as _,
+            height.max(0) as _,
+            start_angle,
+            arc_angle,
+            Rgb8Pixel::to_color(rgb as _),
+        );
 
         canvas.flush().await;
 
         Ok(())
     }
 
-    async fn draw_rect(
+    #[allow(clippy::too_many_arguments)]
+    async fn fill_arc(
         jvm: &Jvm,
         _: &mut WIPIJavaContext,
         mut this: ClassInstanceRef<Self>,
@@ -215,15 +422,110 @@ impl Graphics {
         y: i32,
         width: i32,
         height: i32,
+        start_angle: i32,
+        arc_angle: i32,
     ) -> JvmResult<()> {
-        tracing::debug!("org.kwis.msp.lcdui.Graphics::drawRect({:?}, {}, {}, {}, {})", &this, x, y, width, height);
+        tracing::debug!(
+            "org.kwis.msp.lcdui.Graphics::fillArc({:?}, {}, {}, {}, {}, {}, {})",
+            &this,
+            x,
+            y,
+            width,
+            height,
+            start_angle,
+            arc_angle
+        );
 
         let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
 
         let image = Self::image(jvm, &mut this).await?;
         let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
 
-        canvas.draw_rect(x as _, y as _, width as _, height as _, Rgb8Pixel::to_color(rgb as _));
+        canvas.fill_arc(
+            x,
+            y,
+            width.max(0) as _,
+            height.max(0) as _,
+            start_angle,
+            arc_angle,
+            Rgb8Pixel::to_color(rgb as _),
+        );
+
+        canvas.flush().await;
+
+        Ok(())
+    }
+
+    async fn draw_polygon(
+        jvm: &Jvm,
+        _: &mut WIPIJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        x_points: ClassInstanceRef<Array<i32>>,
+        y_points: ClassInstanceRef<Array<i32>>,
+        n_points: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "org.kwis.msp.lcdui.Graphics::drawPolygon({:?}, {:?}, {:?}, {})",
+            &this,
+            &x_points,
+            &y_points,
+            n_points
+        );
+
+        let xs: Vec<i32> = jvm.load_array(&x_points, 0, n_points.max(0) as _).await?;
+        let ys: Vec<i32> = jvm.load_array(&y_points, 0, n_points.max(0) as _).await?;
+        let (translate_x, translate_y) = Self::translated(jvm, &this, 0, 0).await?;
+        let points = xs
+            .into_iter()
+            .zip(ys)
+            .map(|(x, y)| (x + translate_x, y + translate_y))
+            .collect::<Vec<_>>();
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+
+        let image = Self::image(jvm, &mut this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
+
+        canvas.draw_polygon(&points, Rgb8Pixel::to_color(rgb as _));
+
+        canvas.flush().await;
+
+        Ok(())
+    }
+
+    async fn fill_polygon(
+        jvm: &Jvm,
+        _: &mut WIPIJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        x_points: ClassInstanceRef<Array<i32>>,
+        y_points: ClassInstanceRef<Array<i32>>,
+        n_points: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "org.kwis.msp.lcdui.Graphics::fillPolygon({:?}, {:?}, {:?}, {})",
+            &this,
+            &x_points,
+            &y_points,
+            n_points
+        );
+
+        let xs: Vec<i32> = jvm.load_array(&x_points, 0, n_points.max(0) as _).await?;
+        let ys: Vec<i32> = jvm.load_array(&y_points, 0, n_points.max(0) as _).await?;
+        let (translate_x, translate_y) = Self::translated(jvm, &this, 0, 0).await?;
+        let points = xs
+            .into_iter()
+            .zip(ys)
+            .map(|(x, y)| (x + translate_x, y + translate_y))
+            .collect::<Vec<_>>();
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+
+        let image = Self::image(jvm, &mut this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
+
+        canvas.fill_polygon(&points, Rgb8Pixel::to_color(rgb as _));
 
         canvas.flush().await;
 
@@ -237,7 +539,7 @@ impl Graphics {
         string: ClassInstanceRef<String>,
         x: i32,
         y: i32,
-        anchor: Anchor,
+        anchor: i32,
     ) -> JvmResult<()> {
         tracing::debug!(
             "org.kwis.msp.lcdui.Graphics::drawString({:?}, {:?}, {}, {}, {})",
@@ -245,23 +547,30 @@ impl Graphics {
             &string,
             x,
             y,
-            anchor.0
+            anchor
         );
+        let anchor = Anchor::from_bits_retain(anchor);
 
         let rust_string = JavaLangString::to_rust_string(jvm, &string).await?;
+        let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+        let font = Self::current_font(jvm, &this).await?;
+        let font = Font::font(jvm, &font).await?;
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
 
         let image = Self::image(jvm, &mut this).await?;
         let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
 
-        let alignment = if anchor.contains(Anchor::HCENTER) {
-            TextAlignment::Center
-        } else if anchor.contains(Anchor::RIGHT) {
-            TextAlignment::Right
-        } else {
-            TextAlignment::Left
-        };
+        let (alignment, y) = anchor.text_position(y, &font);
 
-        canvas.draw_text(&rust_string, x as _, y as _, alignment);
+        canvas.draw_text(
+            &rust_string,
+            x.max(0) as _,
+            y.max(0) as _,
+            &font,
+            Rgb8Pixel::to_color(rgb as _),
+            alignment,
+        );
 
         canvas.flush().await;
 
@@ -272,9 +581,12 @@ impl Graphics {
         tracing::debug!("org.kwis.msp.lcdui.Graphics::drawLine({:?}, {}, {}, {}, {})", &this, x1, y1, x2, y2);
 
         let rgb: i32 = jvm.get_field(&this, "rgb", "I").await?;
+        let (x1, y1) = Self::translated(jvm, &this, x1, y1).await?;
+        let (x2, y2) = Self::translated(jvm, &this, x2, y2).await?;
 
         let image = Self::image(jvm, &mut this).await?;
         let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
 
         canvas.draw_line(x1 as _, y1 as _, x2 as _, y2 as _, Rgb8Pixel::to_color(rgb as _));
 
@@ -285,12 +597,12 @@ impl Graphics {
 
     async fn draw_image(
         jvm: &Jvm,
-        _: &mut WIPIJavaContext,
+        context: &mut WIPIJavaContext,
         mut this: ClassInstanceRef<Self>,
         img: ClassInstanceRef<Image>,
         x: i32,
         y: i32,
-        anchor: Anchor,
+        anchor: i32,
     ) -> JvmResult<()> {
         tracing::debug!(
             "org.kwis.msp.lcdui.Graphics::drawImage({:?}, {:?}, {}, {}, {})",
@@ -298,82 +610,214 @@ impl Graphics {
             &img,
             x,
             y,
-            anchor.0
+            anchor
         );
+        let anchor = Anchor::from_bits_retain(anchor);
 
+        Image::update_frame(jvm, context, img.clone()).await?;
         let src_image = Image::image(jvm, &img).await?;
 
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
+        let (x_delta, y_delta) = anchor.offset(src_image.width(), src_image.height());
+
         let image = Self::image(jvm, &mut this).await?;
         let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
+
+        // not clamped to the top left, images partly off the screen are drawn in part
+        canvas.draw_region(
+            x + x_delta,
+            y + y_delta,
+            &*src_image,
+            0,
+            0,
+            src_image.width(),
+            src_image.height(),
+            Transform::None,
+        );
 
-        let x_delta = if anchor.contains(Anchor::HCENTER) {
-            -((src_image.width() / 2) as i32)
-        } else if anchor.contains(Anchor::RIGHT) {
-            -(src_image.width() as i32)
-        } else {
-            0
-        };
+        canvas.flush().await;
 
-        let y_delta = if anchor.contains(Anchor::VCENTER) {
-            -((src_image.height() / 2) as i32)
-        } else if anchor.contains(Anchor::BOTTOM) {
-            -(src_image.height() as i32)
-        } else {
-            0
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn draw_region(
+        jvm: &Jvm,
+        context: &mut WIPIJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        src: ClassInstanceRef<Image>,
+        x_src: i32,
+        y_src: i32,
+        width: i32,
+        height: i32,
+        transform: i32,
+        x_dest: i32,
+        y_dest: i32,
+        anchor: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "org.kwis.msp.lcdui.Graphics::drawRegion({:?}, {:?}, {}, {}, {}, {}, {}, {}, {}, {})",
+            &this,
+            &src,
+            x_src,
+            y_src,
+            width,
+            height,
+            transform,
+            x_dest,
+            y_dest,
+            anchor
+        );
+        let anchor = Anchor::from_bits_retain(anchor);
+
+        if x_src < 0 || y_src < 0 || width <= 0 || height <= 0 {
+            return Ok(());
+        }
+
+        let transform = match Transform::from_sprite_transform(transform) {
+            Some(x) => x,
+            None => {
+                tracing::warn!("Invalid transform {}", transform);
+
+                return Ok(());
+            }
         };
 
-        let x = (x + x_delta).max(0);
-        let y = (y + y_delta).max(0);
+        Image::update_frame(jvm, context, src.clone()).await?;
+        let src_image = Image::image(jvm, &src).await?;
 
-        canvas.draw(x as _, y as _, src_image.width(), src_image.height(), &*src_image, 0, 0);
+        let image = Self::image(jvm, &mut this).await?;
+        let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
+
+        // anchored by the size after the transform
+        let (transformed_width, transformed_height) = transform.transformed_size(width as _, height as _);
+        let (x_delta, y_delta) = anchor.offset(transformed_width, transformed_height);
+        let (x_dest, y_dest) = Self::translated(jvm, &this, x_dest, y_dest).await?;
+
+        canvas.draw_region(
+            x_dest + x_delta,
+            y_dest + y_delta,
+            &*src_image,
+            x_src as _,
+            y_src as _,
+            width as _,
+            height as _,
+            transform,
+        );
 
         canvas.flush().await;
 
         Ok(())
     }
 
-    async fn get_clip_x(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<i32> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Graphics::getClipX({:?})", &this);
+    #[allow(clippy::too_many_arguments)]
+    async fn copy_area(
+        jvm: &Jvm,
+        _: &mut WIPIJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        x_src: i32,
+        y_src: i32,
+        width: i32,
+        height: i32,
+        x_dest: i32,
+        y_dest: i32,
+        anchor: i32,
+    ) -> JvmResult<()> {
+        tracing::debug!(
+            "org.kwis.msp.lcdui.Graphics::copyArea({:?}, {}, {}, {}, {}, {}, {}, {})",
+            &this,
+            x_src,
+            y_src,
+            width,
+            height,
+            x_dest,
+            y_dest,
+            anchor
+        );
+        let anchor = Anchor::from_bits_retain(anchor);
+
+        let (x_src, y_src) = Self::translated(jvm, &this, x_src, y_src).await?;
+        let (x_dest, y_dest) = Self::translated(jvm, &this, x_dest, y_dest).await?;
+        if x_src < 0 || y_src < 0 || width <= 0 || height <= 0 {
+            return Ok(());
+        }
 
-        Ok(0)
+        let image = Self::image(jvm, &mut this).await?;
+        // taken before drawing, so overlapping areas copy what was there
+        let src_image = Image::image(jvm, &image).await?;
+
+        let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
+
+        let (x_delta, y_delta) = anchor.offset(width as _, height as _);
+        canvas.draw_region(
+            x_dest + x_delta,
+            y_dest + y_delta,
+            &*src_image,
+            x_src as _,
+            y_src as _,
+            width as _,
+            height as _,
+            Transform::None,
+        );
+
+        canvas.flush().await;
+
+        Ok(())
     }
 
-    async fn get_clip_y(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<i32> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Graphics::getClipY({:?})", &this);
+    async fn get_clip_x(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::getClipX({:?})", &this);
 
-        Ok(0)
+        let clip_x: i32 = jvm.get_field(&this, "clipX", "I").await?;
+        let translate_x: i32 = jvm.get_field(&this, "translateX", "I").await?;
+
+        Ok(clip_x - translate_x)
     }
 
-    async fn get_clip_width(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Graphics::getClipWidth({:?})", &this);
+    async fn get_clip_y(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::getClipY({:?})", &this);
 
-        let w: i32 = jvm.get_field(&this, "w", "I").await?;
+        let clip_y: i32 = jvm.get_field(&this, "clipY", "I").await?;
+        let translate_y: i32 = jvm.get_field(&this, "translateY", "I").await?;
 
-        Ok(w)
+        Ok(clip_y - translate_y)
     }
 
-    async fn get_clip_height(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Graphics::getClipHeight({:?})", &this);
+    async fn get_clip_width(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::getClipWidth({:?})", &this);
 
-        let h: i32 = jvm.get_field(&this, "h", "I").await?;
+        jvm.get_field(&this, "clipW", "I").await
+    }
+
+    async fn get_clip_height(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::getClipHeight({:?})", &this);
 
-        Ok(h)
+        jvm.get_field(&this, "clipH", "I").await
     }
 
-    async fn get_translate_x(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<i32> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Graphics::getTranslateX({:?})", &this);
+    async fn get_translate_x(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::getTranslateX({:?})", &this);
 
-        Ok(0)
+        jvm.get_field(&this, "translateX", "I").await
     }
 
-    async fn get_translate_y(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<i32> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Graphics::getTranslateY({:?})", &this);
+    async fn get_translate_y(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>) -> JvmResult<i32> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::getTranslateY({:?})", &this);
 
-        Ok(0)
+        jvm.get_field(&this, "translateY", "I").await
     }
 
-    async fn translate(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Graphics>, x: i32, y: i32) -> JvmResult<()> {
-        tracing::warn!("stub org.kwis.msp.lcdui.Graphics::translate({:?}, {}, {})", &this, x, y);
+    async fn translate(jvm: &Jvm, _: &mut WIPIJavaContext, mut this: ClassInstanceRef<Graphics>, x: i32, y: i32) -> JvmResult<()> {
+        tracing::debug!("org.kwis.msp.lcdui.Graphics::translate({:?}, {}, {})", &this, x, y);
+
+        let translate_x: i32 = jvm.get_field(&this, "translateX", "I").await?;
+        let translate_y: i32 = jvm.get_field(&this, "translateY", "I").await?;
+
+        jvm.put_field(&mut this, "translateX", "I", translate_x + x).await?;
+        jvm.put_field(&mut this, "translateY", "I", translate_y + y).await?;
 
         Ok(())
     }
@@ -406,9 +850,11 @@ impl Graphics {
         // TODO we need imagebuffer proxy, as it's not optimal to copy entire image from java/c buffer to rust every time
         let pixel_data: Vec<i32> = jvm.load_array(&rgb_pixels, offset as _, (width * height) as _).await?;
         let src_image = VecImageBuffer::<Rgb8Pixel>::from_raw(width as _, height as _, cast_vec(pixel_data));
+        let (x, y) = Self::translated(jvm, &this, x, y).await?;
 
         let image = Self::image(jvm, &mut this).await?;
         let mut canvas = Image::canvas(jvm, &image).await?;
+        canvas.push_clip(Self::clip(jvm, &this).await?);
 
         canvas.draw(x as _, y as _, width as _, height as _, &src_image, 0, 0);
 
@@ -417,6 +863,43 @@ impl Graphics {
         Ok(())
     }
 
+    // origin moved by translate(). clip is kept translated already
+    async fn translated(jvm: &Jvm, this: &ClassInstanceRef<Graphics>, x: i32, y: i32) -> JvmResult<(i32, i32)> {
+        let translate_x: i32 = jvm.get_field(this, "translateX", "I").await?;
+        let translate_y: i32 = jvm.get_field(this, "translateY", "I").await?;
+
+        Ok((x + translate_x, y + translate_y))
+    }
+
+    async fn clip(jvm: &Jvm, this: &ClassInstanceRef<Graphics>) -> JvmResult<Rect> {
+        let x: i32 = jvm.get_field(this, "clipX", "I").await?;
+        let y: i32 = jvm.get_field(this, "clipY", "I").await?;
+        let width: i32 = jvm.get_field(this, "clipW", "I").await?;
+        let height: i32 = jvm.get_field(this, "clipH", "I").await?;
+
+        Ok(Rect::new(x, y, width as _, height as _))
+    }
+
+    async fn put_clip(jvm: &Jvm, this: &mut ClassInstanceRef<Graphics>, clip: Rect) -> JvmResult<()> {
+        jvm.put_field(this, "clipX", "I", clip.x).await?;
+        jvm.put_field(this, "clipY", "I", clip.y).await?;
+        jvm.put_field(this, "clipW", "I", clip.width as i32).await?;
+        jvm.put_field(this, "clipH", "I", clip.height as i32).await?;
+
+        Ok(())
+    }
+
+    // default font until the app sets one
+    async fn current_font(jvm: &Jvm, this: &ClassInstanceRef<Graphics>) -> JvmResult<ClassInstanceRef<Font>> {
+        let font: ClassInstanceRef<Font> = jvm.get_field(this, "font", "Lorg/kwis/msp/lcdui/Font;").await?;
+        if !font.is_null() {
+            return Ok(font);
+        }
+
+        jvm.invoke_static("org/kwis/msp/lcdui/Font", "getDefaultFont", "()Lorg/kwis/msp/lcdui/Font;", [])
+            .await
+    }
+
     async fn image(jvm: &Jvm, this: &mut ClassInstanceRef<Graphics>) -> JvmResult<ClassInstanceRef<Image>> {
         let image: ClassInstanceRef<Image> = jvm.get_field(this, "img", "Lorg/kwis/msp/lcdui/Image;").await?;
 
@@ -452,7 +935,11 @@ mod test {
 
     use test_utils::test_jvm;
 
-    use crate::{classes::org::kwis::msp::lcdui::Image, context::test::DummyContext, register};
+    use crate::{
+        classes::org::kwis::msp::lcdui::{Graphics, Image},
+        context::test::DummyContext,
+        register,
+    };
 
     #[futures_test::test]
     async fn test_graphics() -> JvmResult<()> {
@@ -479,14 +966,74 @@ mod test {
 
         jvm.invoke_virtual(&graphics, "fillRect", "(IIII)V", (0, 0, 100, 100)).await?;
 
+        jvm.invoke_virtual(&graphics, "setClip", "(IIII)V", (10, 10, 20, 20)).await?;
+        jvm.invoke_virtual(&graphics, "clipRect", "(IIII)V", (0, 0, 15, 15)).await?;
+        let clip_width: i32 = jvm.invoke_virtual(&graphics, "getClipWidth", "()I", []).await?;
+        assert_eq!(clip_width, 5);
+
+        jvm.invoke_virtual(&graphics, "setColor", "(I)V", (0xff0000,)).await?;
+        jvm.invoke_virtual(&graphics, "fillRect", "(IIII)V", (0, 0, 100, 100)).await?;
+
         let image = Image::image(&jvm, &image).await?;
 
         assert_eq!(image.width(), 100);
         assert_eq!(image.height(), 100);
 
-        assert_eq!(image.raw()[0], 0);
-        assert_eq!(image.raw()[1], 255);
-        assert_eq!(image.raw()[2], 0);
+        assert_eq!(image.get_pixel(0, 0).g, 255);
+        assert_eq!(image.get_pixel(10, 10).r, 255);
+        assert_eq!(image.get_pixel(15, 15).g, 255);
+
+        Ok(())
+    }
+
+    #[futures_test::test]
+    async fn test_translate_and_anchor() -> JvmResult<()> {
+        let jvm = test_jvm().await?;
+
+        register(&jvm, |name, proto| {
+            ready(Box::new(ClassDefinitionImpl::from_class_proto(name, proto, Box::new(DummyContext) as Box<_>)) as Box<_>)
+        })
+        .await?;
+
+        let image: ClassInstanceRef<Image> = jvm
+            .invoke_static("org/kwis/msp/lcdui/Image", "createImage", "(II)Lorg/kwis/msp/lcdui/Image;", (20, 20))
+            .await?;
+        let graphics: ClassInstanceRef<Graphics> = jvm.invoke_virtual(&image, "getGraphics", "()Lorg/kwis/msp/lcdui/Graphics;", []).await?;
+
+        jvm.invoke_virtual(&graphics, "translate", "(II)V", (5, 5)).await?;
+        jvm.invoke_virtual(&graphics, "setColor", "(III)V", (255, 0, 0)).await?;
+        jvm.invoke_virtual(&graphics, "fillRect", "(IIII)V", (0, 0, 2, 2)).await?;
+
+        let red: i32 = jvm.invoke_virtual(&graphics, "getRedComponent", "()I", []).await?;
+        assert_eq!(red, 255);
+        let clip_x: i32 = jvm.invoke_virtual(&graphics, "getClipX", "()I", []).await?;
+        assert_eq!(clip_x, -5);
+
+        // BOTTOM | RIGHT
+        jvm.invoke_virtual(&graphics, "copyArea", "(IIIIIII)V", (0, 0, 2, 2, 10, 10, 40)).await?;
+
+        let source: ClassInstanceRef<Image> = jvm
+            .invoke_static("org/kwis/msp/lcdui/Image", "createImage", "(II)Lorg/kwis/msp/lcdui/Image;", (4, 4))
+            .await?;
+        let source_graphics: ClassInstanceRef<Graphics> = jvm.invoke_virtual(&source, "getGraphics", "()Lorg/kwis/msp/lcdui/Graphics;", []).await?;
+        jvm.invoke_virtual(&source_graphics, "setColor", "(I)V", (0x0000ff,)).await?;
+        jvm.invoke_virtual(&source_graphics, "fillRect", "(IIII)V", (0, 0, 4, 4)).await?;
+
+        // partly off the top left of the screen
+        jvm.invoke_virtual(&graphics, "translate", "(II)V", (-5, -5)).await?;
+        jvm.invoke_virtual(&graphics, "drawImage", "(Lorg/kwis/msp/lcdui/Image;III)V", (source, -2, -2, 0))
+            .await?;
+
+        let image = Image::image(&jvm, &image).await?;
+
+        assert_eq!(image.get_pixel(5, 5).r, 255);
+        assert_eq!(image.get_pixel(6, 6).r, 255);
+        assert_eq!(image.get_pixel(7, 7).r, 0);
+        assert_eq!(image.get_pixel(13, 13).r, 255);
+        assert_eq!(image.get_pixel(14, 14).r, 255);
+        assert_eq!(image.get_pixel(15, 15).r, 0);
+        assert_eq!(image.get_pixel(1, 1).b, 255);
+        assert_eq!(image.get_pixel(2, 2).b, 0);
 
         Ok(())
     }
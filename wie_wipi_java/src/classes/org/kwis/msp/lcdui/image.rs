@@ -4,18 +4,19 @@ use core::{
     ops::{Deref, DerefMut},
 };
 
-use bytemuck::{cast_vec, pod_collect_to_vec};
+use bytemuck::cast_vec;
 
 use java_class_proto::{JavaFieldProto, JavaMethodProto};
 use java_constants::MethodAccessFlags;
 use java_runtime::classes::java::lang::String;
-use jvm::{runtime::JavaLangString, Array, ClassInstanceRef, Jvm, Result as JvmResult};
+use jvm::{runtime::JavaLangString, Array, ClassInstanceRef, JavaError, Jvm, Result as JvmResult};
 
-use wie_backend::canvas::{decode_image, ArgbPixel, Canvas, Image as BackendImage, ImageBufferCanvas, Rgb565Pixel, VecImageBuffer};
+use wie_backend::canvas::{byte_image, AnimatedImage, ByteImageCanvas, Canvas, Image as BackendImage};
 
 use crate::{
     classes::org::kwis::msp::lcdui::Graphics,
     context::{WIPIJavaClassProto, WIPIJavaContext},
+    java_exception,
 };
 
 // class org.kwis.msp.lcdui.Image
@@ -55,6 +56,11 @@ impl Image {
                 JavaFieldProto::new("h", "I", Default::default()),
                 JavaFieldProto::new("imgData", "[B", Default::default()),
                 JavaFieldProto::new("bpl", "I", Default::default()),
+                // encoded source of animated images, decoded again when the frame changes instead of keeping every frame
+                JavaFieldProto::new("animation", "[B", Default::default()),
+                JavaFieldProto::new("frameDelays", "[I", Default::default()),
+                JavaFieldProto::new("animationStart", "J", Default::default()),
+                JavaFieldProto::new("frame", "I", Default::default()),
             ],
         }
     }
@@ -86,32 +92,34 @@ impl Image {
         let name = JavaLangString::to_rust_string(jvm, &name).await?;
         let normalized_name = if let Some(x) = name.strip_prefix('/') { x } else { &name };
 
-        let id = context.system().resource().id(normalized_name).unwrap();
+        let id = match context.system().resource().id(normalized_name) {
+            Some(x) => x,
+            None => return Err(java_exception(jvm, "java/io/IOException", &name).await),
+        };
         let system_clone = context.system().clone();
+        let image_data = Ref::map(system_clone.resource(), |x| x.data(id)).to_vec();
 
-        let image = {
-            let image_data = Ref::map(system_clone.resource(), |x| x.data(id));
-
-            decode_image(&image_data)
+        match AnimatedImage::decode(&image_data) {
+            Ok(x) => Self::create_image_from_data(jvm, context, x, &image_data).await,
+            Err(_) => Err(java_exception(jvm, "java/io/IOException", &name).await),
         }
-        .unwrap();
-
-        Self::create_image_instance(jvm, image.width(), image.height(), image.raw(), image.bytes_per_pixel()).await
     }
 
     async fn create_image_from_bytes(
         jvm: &Jvm,
-        _: &mut WIPIJavaContext,
+        context: &mut WIPIJavaContext,
         data: ClassInstanceRef<Array<i8>>,
         offset: i32,
         length: i32,
     ) -> JvmResult<ClassInstanceRef<Image>> {
         tracing::debug!("org.kwis.msp.lcdui.Image::createImage({:?}, {}, {})", &data, offset, length);
 
-        let image_data = jvm.load_byte_array(&data, offset as _, length as _).await?;
-        let image = decode_image(&cast_vec(image_data)).unwrap();
+        let image_data: Vec<u8> = cast_vec(jvm.load_byte_array(&data, offset as _, length as _).await?);
 
-        Self::create_image_instance(jvm, image.width(), image.height(), image.raw(), image.bytes_per_pixel()).await
+        match AnimatedImage::decode(&image_data) {
+            Ok(x) => Self::create_image_from_data(jvm, context, x, &image_data).await,
+            Err(_) => Err(java_exception(jvm, "java/lang/IllegalArgumentException", "Unsupported image format").await),
+        }
     }
 
     async fn get_graphics(jvm: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Self>) -> JvmResult<ClassInstanceRef<Graphics>> {
@@ -153,31 +161,102 @@ impl Image {
     }
 
     pub async fn image(jvm: &Jvm, this: &ClassInstanceRef<Self>) -> JvmResult<Box<dyn BackendImage>> {
-        let buf = Self::buf(jvm, this).await?;
+        let (width, height, bytes_per_pixel, buf) = Self::raw(jvm, this).await?;
 
-        let width: i32 = jvm.get_field(this, "w", "I").await?;
-        let height: i32 = jvm.get_field(this, "h", "I").await?;
-        let bpl: i32 = jvm.get_field(this, "bpl", "I").await?;
+        match byte_image(width, height, bytes_per_pixel, buf) {
+            Some(x) => Ok(x),
+            None => Err(Self::invalid_data(jvm).await),
+        }
+    }
 
-        let bytes_per_pixel = bpl / width;
+    pub async fn canvas<'a>(jvm: &'a Jvm, this: &'a ClassInstanceRef<Self>) -> JvmResult<ImageCanvas<'a>> {
+        let (width, height, bytes_per_pixel, buf) = Self::raw(jvm, this).await?;
 
-        Ok(match bytes_per_pixel {
-            2 => Box::new(VecImageBuffer::<Rgb565Pixel>::from_raw(width as _, height as _, pod_collect_to_vec(&buf))) as Box<_>,
-            4 => Box::new(VecImageBuffer::<ArgbPixel>::from_raw(width as _, height as _, pod_collect_to_vec(&buf))) as Box<_>,
-            _ => unimplemented!("Unsupported pixel format: {}", bytes_per_pixel),
-        })
+        match ByteImageCanvas::new(width, height, bytes_per_pixel, buf) {
+            Some(x) => Ok(ImageCanvas::new(jvm, this, x)),
+            None => Err(Self::invalid_data(jvm).await),
+        }
     }
 
-    pub async fn canvas<'a>(jvm: &'a Jvm, this: &'a ClassInstanceRef<Self>) -> JvmResult<ImageCanvas<'a>> {
+    // the fields can be changed by the app, so the layout is checked against imgData before drawing
+    async fn raw(jvm: &Jvm, this: &ClassInstanceRef<Self>) -> JvmResult<(u32, u32, u32, Vec<u8>)> {
         let buf = Self::buf(jvm, this).await?;
 
         let width: i32 = jvm.get_field(this, "w", "I").await?;
         let height: i32 = jvm.get_field(this, "h", "I").await?;
         let bpl: i32 = jvm.get_field(this, "bpl", "I").await?;
 
-        let bytes_per_pixel = bpl / width;
+        let bytes_per_pixel = if width > 0 && bpl % width == 0 { bpl / width } else { 0 };
+        if height < 0 || bytes_per_pixel <= 0 {
+            return Err(Self::invalid_data(jvm).await);
+        }
+
+        Ok((width as _, height as _, bytes_per_pixel as _, buf))
+    }
 
-        Ok(ImageCanvas::new(jvm, this, width as _, height as _, bytes_per_pixel as _, buf))
+    async fn invalid_data(jvm: &Jvm) -> JavaError {
+        java_exception(jvm, "java/lang/IllegalArgumentException", "Invalid image data").await
+    }
+
+    /// Brings the frame due at the current time into the image data, if the image is animated
+    pub async fn update_frame(jvm: &Jvm, context: &mut WIPIJavaContext, mut this: ClassInstanceRef<Self>) -> JvmResult<()> {
+        let animation: ClassInstanceRef<Array<i8>> = jvm.get_field(&this, "animation", "[B").await?;
+        if animation.is_null() {
+            return Ok(());
+        }
+
+        let frame_delays: ClassInstanceRef<Array<i32>> = jvm.get_field(&this, "frameDelays", "[I").await?;
+        let frame_count = jvm.array_length(&frame_delays).await?;
+        let delays: Vec<i32> = jvm.load_array(&frame_delays, 0, frame_count).await?;
+        let start: i64 = jvm.get_field(&this, "animationStart", "J").await?;
+        let current: i32 = jvm.get_field(&this, "frame", "I").await?;
+
+        let elapsed = context.system().now().raw().saturating_sub(start as u64);
+        let frame = AnimatedImage::frame_at(&delays.into_iter().map(|x| x as u32).collect::<Vec<_>>(), elapsed);
+        if frame == current as usize {
+            return Ok(());
+        }
+
+        let length = jvm.array_length(&animation).await?;
+        let data = jvm.load_byte_array(&animation, 0, length).await?;
+        let decoded = match AnimatedImage::decode(&cast_vec(data)) {
+            Ok(x) => x,
+            Err(_) => return Err(java_exception(jvm, "java/lang/IllegalStateException", "Animation is not decodable").await),
+        };
+
+        let mut img_data = jvm.get_field(&this, "imgData", "[B").await?;
+        jvm.store_byte_array(&mut img_data, 0, cast_vec(decoded.frame(frame).raw().to_vec()))
+            .await?;
+        jvm.put_field(&mut this, "frame", "I", frame as i32).await?;
+
+        Ok(())
+    }
+
+    // `data` is what `animation` was decoded from, kept to decode other frames
+    async fn create_image_from_data(
+        jvm: &Jvm,
+        context: &mut WIPIJavaContext,
+        animation: AnimatedImage,
+        data: &[u8],
+    ) -> JvmResult<ClassInstanceRef<Image>> {
+        let first = animation.frame(0);
+        let mut instance = Self::create_image_instance(jvm, first.width(), first.height(), first.raw(), first.bytes_per_pixel()).await?;
+
+        if animation.frame_count() > 1 {
+            let mut source = jvm.instantiate_array("B", data.len()).await?;
+            jvm.store_byte_array(&mut source, 0, cast_vec(data.to_vec())).await?;
+
+            let delays = animation.delays().into_iter().map(|x| x as i32).collect::<Vec<_>>();
+            let mut frame_delays = jvm.instantiate_array("I", delays.len()).await?;
+            jvm.store_array(&mut frame_delays, 0, delays).await?;
+
+            let now = context.system().now().raw();
+            jvm.put_field(&mut instance, "animation", "[B", source).await?;
+            jvm.put_field(&mut instance, "frameDelays", "[I", frame_delays).await?;
+            jvm.put_field(&mut instance, "animationStart", "J", now as i64).await?;
+        }
+
+        Ok(instance)
     }
 
     async fn create_image_instance(jvm: &Jvm, width: u32, height: u32, data: &[u8], bytes_per_pixel: u32) -> JvmResult<ClassInstanceRef<Image>> {
@@ -195,67 +274,50 @@ impl Image {
     }
 }
 
+// pixels are drawn in the layout of imgData and written back to it on flush
 pub struct ImageCanvas<'a> {
     image: &'a ClassInstanceRef<Image>,
     jvm: &'a Jvm,
-    canvas: Box<dyn Canvas>,
-    flushed: bool,
+    // taken on flush
+    canvas: Option<ByteImageCanvas>,
 }
 
 impl<'a> ImageCanvas<'a> {
-    pub fn new(jvm: &'a Jvm, image: &'a ClassInstanceRef<Image>, width: u32, height: i32, bytes_per_pixel: u32, buf: Vec<u8>) -> Self {
-        let canvas: Box<dyn Canvas> = match bytes_per_pixel {
-            2 => Box::new(ImageBufferCanvas::new(VecImageBuffer::<Rgb565Pixel>::from_raw(
-                width as _,
-                height as _,
-                pod_collect_to_vec(&buf),
-            ))),
-            4 => Box::new(ImageBufferCanvas::new(VecImageBuffer::<ArgbPixel>::from_raw(
-                width as _,
-                height as _,
-                pod_collect_to_vec(&buf),
-            ))),
-            _ => unimplemented!("Unsupported pixel format: {}", bytes_per_pixel),
-        };
-
+    fn new(jvm: &'a Jvm, image: &'a ClassInstanceRef<Image>, canvas: ByteImageCanvas) -> Self {
         Self {
             image,
             jvm,
-            canvas,
-            flushed: false,
+            canvas: Some(canvas),
         }
     }
 
     // We don't have async drop yet..
     pub async fn flush(mut self) {
         let mut data = self.jvm.get_field(self.image, "imgData", "[B").await.unwrap();
+        let raw = self.canvas.take().unwrap().into_raw();
 
-        self.jvm
-            .store_byte_array(&mut data, 0, cast_vec(self.canvas.image().raw().to_vec()))
-            .await
-            .unwrap();
-        self.flushed = true
+        self.jvm.store_byte_array(&mut data, 0, cast_vec(raw)).await.unwrap();
     }
 }
 
 impl Drop for ImageCanvas<'_> {
     fn drop(&mut self) {
-        if !self.flushed {
+        if self.canvas.is_some() {
             panic!("ImageCanvas was dropped without flushing")
         }
     }
 }
 
 impl Deref for ImageCanvas<'_> {
-    type Target = Box<dyn Canvas>;
+    type Target = dyn Canvas;
 
     fn deref(&self) -> &Self::Target {
-        &self.canvas
+        &**self.canvas.as_ref().unwrap()
     }
 }
 
 impl DerefMut for ImageCanvas<'_> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.canvas
+        &mut **self.canvas.as_mut().unwrap()
     }
 }
@@ -74,7 +74,7 @@ impl Jlet {
         impl MethodBody<JavaError, WIPIJavaContext> for MainProxy {
             #[tracing::instrument(name = "main", skip_all)]
             async fn call(&self, jvm: &Jvm, context: &mut WIPIJavaContext, _: Box<[JavaValue]>) -> Result<JavaValue, JavaError> {
-                let now = context.system().platform().now();
+                let now = context.system().now();
                 let until = now + 10;
                 context.system().sleep(until).await; // XXX wait until jlet to initialize
 
@@ -1,14 +1,21 @@
 use alloc::vec;
 
-use java_class_proto::JavaMethodProto;
+use bytemuck::cast_vec;
+
+use java_class_proto::{JavaFieldProto, JavaMethodProto};
 use java_runtime::classes::java::lang::String;
-use jvm::{Array, ClassInstanceRef, Jvm, Result as JvmResult};
+use jvm::{runtime::JavaLangString, Array, ClassInstanceRef, Jvm, Result as JvmResult};
+
+use wie_backend::AudioHandle;
 
 use crate::{
     classes::org::kwis::msp::media::PlayListener,
     context::{WIPIJavaClassProto, WIPIJavaContext},
 };
 
+// headerless pcm is taken as mono at the rate handsets usually record at
+const PCM_SAMPLING_RATE: u32 = 8000;
+
 // class org.kwis.msp.media.Clip
 pub struct Clip {}
 
@@ -28,43 +35,107 @@ impl Clip {
                     Default::default(),
                 ),
             ],
-            fields: vec![],
+            fields: vec![
+                // audio handle plus one, zero if the data couldn't be loaded
+                JavaFieldProto::new("audioHandle", "I", Default::default()),
+                JavaFieldProto::new("listener", "Lorg/kwis/msp/media/PlayListener;", Default::default()),
+            ],
         }
     }
 
     async fn init(
-        _: &Jvm,
-        _: &mut WIPIJavaContext,
+        jvm: &Jvm,
+        context: &mut WIPIJavaContext,
         this: ClassInstanceRef<Self>,
         r#type: ClassInstanceRef<String>,
         resource_name: ClassInstanceRef<String>,
     ) -> JvmResult<()> {
-        tracing::warn!("stub org.kwis.msp.media.Clip::<init>({:?}, {:?}, {:?})", &this, &r#type, &resource_name);
+        tracing::debug!("org.kwis.msp.media.Clip::<init>({:?}, {:?}, {:?})", &this, &r#type, &resource_name);
 
-        Ok(())
+        let r#type = JavaLangString::to_rust_string(jvm, &r#type).await?;
+        let resource_name = JavaLangString::to_rust_string(jvm, &resource_name).await?;
+        let normalized_name = resource_name.strip_prefix('/').unwrap_or(&resource_name);
+
+        let data = {
+            let resource = context.system().resource();
+
+            match resource.id(normalized_name) {
+                Some(id) => resource.data(id).to_vec(),
+                None => {
+                    tracing::warn!("No resource named {}", resource_name);
+
+                    return Ok(());
+                }
+            }
+        };
+
+        Self::load(jvm, context, this, &r#type, &data).await
     }
 
     async fn init_with_data(
-        _: &Jvm,
-        _: &mut WIPIJavaContext,
+        jvm: &Jvm,
+        context: &mut WIPIJavaContext,
         this: ClassInstanceRef<Self>,
         r#type: ClassInstanceRef<String>,
         data: ClassInstanceRef<Array<i8>>,
     ) -> JvmResult<()> {
-        tracing::warn!("stub org.kwis.msp.media.Clip::<init>({:?}, {:?}, {:?})", &this, r#type, &data);
+        tracing::debug!("org.kwis.msp.media.Clip::<init>({:?}, {:?}, {:?})", &this, r#type, &data);
 
-        Ok(())
+        let r#type = JavaLangString::to_rust_string(jvm, &r#type).await?;
+        let length = jvm.array_length(&data).await?;
+        let data = jvm.load_byte_array(&data, 0, length).await?;
+
+        Self::load(jvm, context, this, &r#type, &cast_vec(data)).await
     }
 
-    async fn set_volume(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Clip>, level: i32) -> JvmResult<()> {
-        tracing::warn!("stub org.kwis.msp.media.Clip::setVolume({:?}, {})", &this, level);
+    async fn set_volume(jvm: &Jvm, context: &mut WIPIJavaContext, this: ClassInstanceRef<Clip>, level: i32) -> JvmResult<bool> {
+        tracing::debug!("org.kwis.msp.media.Clip::setVolume({:?}, {})", &this, level);
 
-        Ok(())
+        let handle = match Self::audio_handle(jvm, &this).await? {
+            Some(x) => x,
+            None => return Ok(false),
+        };
+
+        Ok(context.system().audio().set_volume(handle, level.clamp(0, 100) as _).is_ok())
     }
 
-    async fn set_listener(_: &Jvm, _: &mut WIPIJavaContext, this: ClassInstanceRef<Self>, listener: ClassInstanceRef<PlayListener>) -> JvmResult<()> {
-        tracing::warn!("stub org.kwis.msp.media.Clip::setListener({:?}, {:?})", &this, &listener);
+    async fn set_listener(
+        jvm: &Jvm,
+        _: &mut WIPIJavaContext,
+        mut this: ClassInstanceRef<Self>,
+        listener: ClassInstanceRef<PlayListener>,
+    ) -> JvmResult<()> {
+        tracing::debug!("org.kwis.msp.media.Clip::setListener({:?}, {:?})", &this, &listener);
+
+        // TODO the listener isn't notified when playback ends
+        jvm.put_field(&mut this, "listener", "Lorg/kwis/msp/media/PlayListener;", listener)
+            .await?;
 
         Ok(())
     }
+
+    pub async fn audio_handle(jvm: &Jvm, this: &ClassInstanceRef<Self>) -> JvmResult<Option<AudioHandle>> {
+        let audio_handle: i32 = jvm.get_field(this, "audioHandle", "I").await?;
+
+        Ok(if audio_handle > 0 { Some(audio_handle as AudioHandle - 1) } else { None })
+    }
+
+    async fn load(jvm: &Jvm, context: &mut WIPIJavaContext, mut this: ClassInstanceRef<Self>, r#type: &str, data: &[u8]) -> JvmResult<()> {
+        let result = if r#type.to_ascii_lowercase().contains("pcm") {
+            context.system().audio().load_pcm(data, 1, PCM_SAMPLING_RATE)
+        } else {
+            context.system().audio().load(data)
+        };
+
+        let audio_handle = match result {
+            Ok(x) => x,
+            Err(_) => {
+                tracing::warn!("Unsupported audio data on {:?}", &this);
+
+                return Ok(());
+            }
+        };
+
+        jvm.put_field(&mut this, "audioHandle", "I", audio_handle as i32 + 1).await
+    }
 }
@@ -25,15 +25,25 @@ impl Player {
         }
     }
 
-    async fn play(_: &Jvm, _: &mut WIPIJavaContext, clip: ClassInstanceRef<Clip>, repeat: bool) -> JvmResult<bool> {
-        tracing::warn!("stub org.kwis.msp.media.Player::play({:?}, {})", &clip, repeat);
+    async fn play(jvm: &Jvm, context: &mut WIPIJavaContext, clip: ClassInstanceRef<Clip>, repeat: bool) -> JvmResult<bool> {
+        tracing::debug!("org.kwis.msp.media.Player::play({:?}, {})", &clip, repeat);
 
-        Ok(false)
+        let handle = match Clip::audio_handle(jvm, &clip).await? {
+            Some(x) => x,
+            None => return Ok(false),
+        };
+
+        Ok(context.system().audio().play(handle, repeat).await.is_ok())
     }
 
-    async fn stop(_: &Jvm, _: &mut WIPIJavaContext, clip: ClassInstanceRef<Clip>) -> JvmResult<bool> {
-        tracing::warn!("stub org.kwis.msp.media.Player::stop({:?})", &clip,);
+    async fn stop(jvm: &Jvm, context: &mut WIPIJavaContext, clip: ClassInstanceRef<Clip>) -> JvmResult<bool> {
+        tracing::debug!("org.kwis.msp.media.Player::stop({:?})", &clip);
+
+        let handle = match Clip::audio_handle(jvm, &clip).await? {
+            Some(x) => x,
+            None => return Ok(false),
+        };
 
-        Ok(false)
+        Ok(context.system().audio().stop(handle).is_ok())
     }
 }
@@ -10,7 +10,8 @@ use context::WIPIJavaClassProto;
 pub use context::WIPIJavaContextBase;
 
 use alloc::boxed::Box;
-use jvm::{ClassDefinition, Jvm, Result as JvmResult};
+use java_runtime::classes::java::lang::String;
+use jvm::{runtime::JavaLangString, ClassDefinition, ClassInstanceRef, JavaError, Jvm, Result as JvmResult};
 
 // TODO we need class loader
 pub async fn register<T, F>(jvm: &Jvm, class_creator: T) -> JvmResult<()>
@@ -93,3 +94,16 @@ where
 
     Ok(())
 }
+
+// exception with a message, to be returned as an error from native methods
+pub(crate) async fn java_exception(jvm: &Jvm, class_name: &str, message: &str) -> JavaError {
+    let message: ClassInstanceRef<String> = match JavaLangString::from_rust_string(jvm, message).await {
+        Ok(x) => x.into(),
+        Err(x) => return x,
+    };
+
+    match jvm.new_class(class_name, "(Ljava/lang/String;)V", (message,)).await {
+        Ok(x) => JavaError::JavaException(x),
+        Err(x) => x,
+    }
+}